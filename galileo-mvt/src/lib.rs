@@ -1,6 +1,7 @@
 use crate::error::GalileoMvtError;
+use crate::raw::{FieldReader, RawValue};
 use crate::vector_tile::tile::GeomType;
-use bytes::Buf;
+use bytes::{Buf, Bytes};
 use galileo_types::cartesian::{CartesianClosedContour, CartesianPoint2d, Winding};
 use galileo_types::impls::{ClosedContour, Contour, Polygon};
 use nalgebra::Point2;
@@ -10,13 +11,17 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 pub mod error;
+mod raw;
 
+// `Tile` message is not decoded directly, its layers are scanned by `raw::FieldReader` instead.
 #[cfg(feature = "generate_proto")]
+#[allow(dead_code)]
 mod vector_tile {
     include!(concat!(env!("OUT_DIR"), "/vector_tile.rs"));
 }
 
 #[cfg(not(feature = "generate_proto"))]
+#[allow(dead_code)]
 mod vector_tile {
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
@@ -198,16 +203,54 @@ impl From<Option<i32>> for GeomType {
     }
 }
 
+/// Protobuf tag of the `layers` field of the tile message.
+const TILE_LAYERS_TAG: u32 = 3;
+/// Protobuf tag of the `name` field of the layer message.
+const LAYER_NAME_TAG: u32 = 1;
+
 impl MvtTile {
     pub fn decode<B>(buffer: B, skip_recoverable_errors: bool) -> Result<MvtTile, GalileoMvtError>
     where
         B: Buf,
     {
-        let pb = vector_tile::Tile::decode(buffer)?;
+        Self::decode_filtered(buffer, skip_recoverable_errors, |_| true)
+    }
+
+    /// Decodes only the layers for which `layer_filter` returns true.
+    ///
+    /// The tile is scanned over the raw bytes, and the contents of the layers that are filtered out are skipped
+    /// without being parsed, so decoding a tile with many unused layers is much cheaper. If the `buffer` is
+    /// [`Bytes`], the layer data is not copied.
+    pub fn decode_filtered<B>(
+        mut buffer: B,
+        skip_recoverable_errors: bool,
+        layer_filter: impl Fn(&str) -> bool,
+    ) -> Result<MvtTile, GalileoMvtError>
+    where
+        B: Buf,
+    {
+        let buffer = buffer.copy_to_bytes(buffer.remaining());
+        let mut reader = FieldReader::new(buffer);
 
         let mut layers = vec![];
-        for layer in pb.layers.into_iter() {
-            match MvtLayer::decode(layer, skip_recoverable_errors) {
+        let mut has_skipped_layers = false;
+        while let Some((tag, value)) = reader.next_field()? {
+            let RawValue::LengthDelimited(layer_bytes) = value else {
+                continue;
+            };
+            if tag != TILE_LAYERS_TAG {
+                continue;
+            }
+
+            if !layer_filter(&Self::layer_name(&layer_bytes)?) {
+                has_skipped_layers = true;
+                continue;
+            }
+
+            let result = vector_tile::tile::Layer::decode(layer_bytes)
+                .map_err(GalileoMvtError::from)
+                .and_then(|layer| MvtLayer::decode(layer, skip_recoverable_errors));
+            match result {
                 Ok(v) => layers.push(v),
                 Err(e) => {
                     if skip_recoverable_errors {
@@ -221,7 +264,7 @@ impl MvtTile {
 
         let tile = MvtTile { layers };
 
-        if tile.layers.is_empty() {
+        if tile.layers.is_empty() && !has_skipped_layers {
             return Err(GalileoMvtError::Generic(
                 "Tile does not contain any valid layers".into(),
             ));
@@ -229,6 +272,20 @@ impl MvtTile {
 
         Ok(tile)
     }
+
+    fn layer_name(layer_bytes: &Bytes) -> Result<String, GalileoMvtError> {
+        let mut reader = FieldReader::new(layer_bytes.clone());
+        while let Some((tag, value)) = reader.next_field()? {
+            if let (LAYER_NAME_TAG, RawValue::LengthDelimited(name)) = (tag, value) {
+                return String::from_utf8(name.to_vec())
+                    .map_err(|_| GalileoMvtError::Proto("layer name is not valid utf-8".into()));
+            }
+        }
+
+        Err(GalileoMvtError::Generic(
+            "Layer does not have a name".into(),
+        ))
+    }
 }
 
 impl MvtLayer {
@@ -727,4 +784,24 @@ mod tests {
         let vt = include_bytes!("../test-data/vt.mvt");
         let _tile = MvtTile::decode(&mut Cursor::new(&vt), false).unwrap();
     }
+
+    #[test]
+    fn decode_filtered_skips_layers() {
+        let vt = include_bytes!("../test-data/vt.mvt");
+        let tile = MvtTile::decode(&mut Cursor::new(&vt), false).unwrap();
+        let first_name = tile.layers[0].name.clone();
+
+        let filtered =
+            MvtTile::decode_filtered(&mut Cursor::new(&vt), false, |name| name == first_name)
+                .unwrap();
+        assert_eq!(filtered.layers.len(), 1);
+        assert_eq!(filtered.layers[0].name, first_name);
+        assert_eq!(
+            filtered.layers[0].features.len(),
+            tile.layers[0].features.len()
+        );
+
+        let empty = MvtTile::decode_filtered(&mut Cursor::new(&vt), false, |_| false).unwrap();
+        assert!(empty.layers.is_empty());
+    }
 }
@@ -0,0 +1,122 @@
+//! Minimal reader of protobuf wire format, used to scan the tile without decoding all of its contents.
+
+use crate::error::GalileoMvtError;
+use bytes::{Buf, Bytes};
+
+/// Value of a single protobuf field. Length-delimited values reference the original buffer without copying.
+pub(crate) enum RawValue {
+    Varint(u64),
+    Fixed64,
+    Fixed32,
+    LengthDelimited(Bytes),
+}
+
+/// Iterates over the top-level fields of a protobuf message.
+pub(crate) struct FieldReader {
+    buf: Bytes,
+}
+
+impl FieldReader {
+    pub fn new(buf: Bytes) -> Self {
+        Self { buf }
+    }
+
+    /// Reads the next field of the message, returning its tag and value. Returns `None` at the end of the message.
+    pub fn next_field(&mut self) -> Result<Option<(u32, RawValue)>, GalileoMvtError> {
+        if !self.buf.has_remaining() {
+            return Ok(None);
+        }
+
+        let key = self.read_varint()?;
+        let tag = (key >> 3) as u32;
+        let value = match key & 0x7 {
+            0 => RawValue::Varint(self.read_varint()?),
+            1 => {
+                self.advance(8)?;
+                RawValue::Fixed64
+            }
+            2 => {
+                let len = self.read_varint()? as usize;
+                if len > self.buf.remaining() {
+                    return Err(unexpected_end());
+                }
+
+                RawValue::LengthDelimited(self.buf.split_to(len))
+            }
+            5 => {
+                self.advance(4)?;
+                RawValue::Fixed32
+            }
+            wire_type => {
+                return Err(GalileoMvtError::Proto(format!(
+                    "unsupported wire type {wire_type}"
+                )))
+            }
+        };
+
+        Ok(Some((tag, value)))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, GalileoMvtError> {
+        let mut value = 0u64;
+        for i in 0..10 {
+            if !self.buf.has_remaining() {
+                return Err(unexpected_end());
+            }
+
+            let byte = self.buf.get_u8();
+            value |= ((byte & 0x7f) as u64) << (i * 7);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(GalileoMvtError::Proto("invalid varint".into()))
+    }
+
+    fn advance(&mut self, count: usize) -> Result<(), GalileoMvtError> {
+        if self.buf.remaining() < count {
+            return Err(unexpected_end());
+        }
+
+        self.buf.advance(count);
+        Ok(())
+    }
+}
+
+fn unexpected_end() -> GalileoMvtError {
+    GalileoMvtError::Proto("unexpected end of buffer".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_fields() {
+        // field 1: varint 300, field 2: bytes "ab", field 3: fixed32
+        let buf = Bytes::from_static(&[0x08, 0xac, 0x02, 0x12, 0x02, b'a', b'b', 0x1d, 0, 0, 0, 0]);
+        let mut reader = FieldReader::new(buf);
+
+        assert!(matches!(
+            reader.next_field().unwrap(),
+            Some((1, RawValue::Varint(300)))
+        ));
+        match reader.next_field().unwrap() {
+            Some((2, RawValue::LengthDelimited(bytes))) => assert_eq!(&bytes[..], b"ab"),
+            _ => panic!("expected bytes field"),
+        }
+        assert!(matches!(
+            reader.next_field().unwrap(),
+            Some((3, RawValue::Fixed32))
+        ));
+        assert!(reader.next_field().unwrap().is_none());
+    }
+
+    #[test]
+    fn truncated_buffer_is_error() {
+        let buf = Bytes::from_static(&[0x12, 0x05, b'a']);
+        let mut reader = FieldReader::new(buf);
+        assert!(reader.next_field().is_err());
+    }
+}
@@ -3,11 +3,13 @@
 mod impls;
 mod orient;
 mod rect;
+pub(crate) mod shapes;
 mod size;
 mod traits;
 
 pub use impls::{Point2, Point2d, Point3, Point3d};
 pub use orient::Orientation;
 pub use rect::Rect;
+pub use shapes::{Arc, Circle, Ellipse, Sector};
 pub use size::Size;
 pub use traits::*;
@@ -1,9 +1,10 @@
 use crate::cartesian::CartesianPoint2d;
 use crate::impls::ClosedContour;
+use alloc::vec::Vec;
+use core::ops::Deref;
 use nalgebra::{Point2, Scalar};
 use num_traits::{FromPrimitive, Num};
 use serde::{Deserialize, Serialize};
-use std::ops::Deref;
 
 /// Rectangle in 2d cartesian coordinate space.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
@@ -253,7 +254,7 @@ impl<N: Num + Copy + PartialOrd + Scalar + FromPrimitive> Rect<N> {
     pub fn intersects(&self, other: Rect<N>) -> bool {
         self.x_max >= other.x_min
             && self.x_min <= other.x_max
-            && self.y_max >= other.y_max
+            && self.y_max >= other.y_min
             && self.y_min <= other.y_max
     }
 }
@@ -0,0 +1,341 @@
+use crate::cartesian::{CartesianPoint2d, Point2d};
+use crate::impls::{ClosedContour, Contour, Polygon};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::{PI, TAU};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Minimum number of segments used to approximate a full circle or ellipse.
+pub(crate) const MIN_RING_SEGMENTS: usize = 8;
+
+/// Circle in 2d cartesian coordinate space.
+///
+/// Circles, [ellipses](Ellipse), [arcs](Arc) and [sectors](Sector) are converted into polygons and contours for
+/// rendering. The number of segments is chosen so that the approximation deviates from the true shape by no more than
+/// the given `tolerance` (in the units of the coordinates), so shapes of very different sizes can be converted with
+/// the same tolerance, e.g. the map resolution.
+///
+/// ```
+/// use galileo_types::cartesian::{Circle, Point2d};
+///
+/// let circle = Circle::new(Point2d::new(0.0, 0.0), 100.0);
+/// let polygon = circle.to_polygon(0.5);
+/// assert_eq!(polygon.outer_contour.points.len(), 32);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Circle {
+    /// Center of the circle.
+    pub center: Point2d,
+    /// Radius of the circle.
+    pub radius: f64,
+}
+
+impl Circle {
+    /// Creates a new circle.
+    pub fn new(center: Point2d, radius: f64) -> Self {
+        Self { center, radius }
+    }
+
+    /// Returns true if the point is inside or on the boundary of the circle.
+    pub fn contains(&self, point: &impl CartesianPoint2d<Num = f64>) -> bool {
+        let dx = point.x() - self.center.x;
+        let dy = point.y() - self.center.y;
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+
+    /// Approximates the circle with a polygon.
+    pub fn to_polygon(&self, tolerance: f64) -> Polygon<Point2d> {
+        Ellipse::new(self.center, self.radius, self.radius, 0.0).to_polygon(tolerance)
+    }
+}
+
+/// Ellipse in 2d cartesian coordinate space. See [`Circle`] for details about conversion into polygons.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ellipse {
+    /// Center of the ellipse.
+    pub center: Point2d,
+    /// Half of the length of the axis that is rotated by `rotation` from the *X* axis.
+    pub semi_major: f64,
+    /// Half of the length of the other axis.
+    pub semi_minor: f64,
+    /// Counterclockwise angle between the *X* axis and the major axis, in radians.
+    pub rotation: f64,
+}
+
+impl Ellipse {
+    /// Creates a new ellipse.
+    pub fn new(center: Point2d, semi_major: f64, semi_minor: f64, rotation: f64) -> Self {
+        Self {
+            center,
+            semi_major,
+            semi_minor,
+            rotation,
+        }
+    }
+
+    /// Returns true if the point is inside or on the boundary of the ellipse.
+    pub fn contains(&self, point: &impl CartesianPoint2d<Num = f64>) -> bool {
+        let (sin, cos) = self.rotation.sin_cos();
+        let dx = point.x() - self.center.x;
+        let dy = point.y() - self.center.y;
+        let u = (dx * cos + dy * sin) / self.semi_major;
+        let v = (dy * cos - dx * sin) / self.semi_minor;
+        u * u + v * v <= 1.0
+    }
+
+    /// Point of the ellipse at the given parametric angle (in radians).
+    pub fn point_at(&self, angle: f64) -> Point2d {
+        let (sin, cos) = angle.sin_cos();
+        let (rot_sin, rot_cos) = self.rotation.sin_cos();
+        let u = self.semi_major * cos;
+        let v = self.semi_minor * sin;
+        Point2d::new(
+            self.center.x + u * rot_cos - v * rot_sin,
+            self.center.y + u * rot_sin + v * rot_cos,
+        )
+    }
+
+    /// Approximates the ellipse with a polygon.
+    pub fn to_polygon(&self, tolerance: f64) -> Polygon<Point2d> {
+        let radius = self.semi_major.abs().max(self.semi_minor.abs());
+        let count = segment_count(radius, TAU, tolerance).max(MIN_RING_SEGMENTS);
+        let points = (0..count)
+            .map(|i| self.point_at(TAU * i as f64 / count as f64))
+            .collect();
+
+        Polygon::new(ClosedContour::new(points), vec![])
+    }
+}
+
+/// Arc of a circle in 2d cartesian coordinate space. See [`Circle`] for details about conversion into contours.
+///
+/// The arc goes counterclockwise from the start angle to the end angle. Angles are in radians from the *X* axis. If
+/// the angles are equal, the arc is a full circle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Arc {
+    /// Circle the arc is a part of.
+    pub circle: Circle,
+    /// Angle of the start point of the arc.
+    pub start_angle: f64,
+    /// Angle of the end point of the arc.
+    pub end_angle: f64,
+}
+
+impl Arc {
+    /// Creates a new arc.
+    pub fn new(center: Point2d, radius: f64, start_angle: f64, end_angle: f64) -> Self {
+        Self {
+            circle: Circle::new(center, radius),
+            start_angle,
+            end_angle,
+        }
+    }
+
+    /// Angle between the start and the end of the arc in the range `(0, 2*PI]`.
+    pub fn sweep(&self) -> f64 {
+        sweep(self.start_angle, self.end_angle)
+    }
+
+    /// Length of the arc.
+    pub fn length(&self) -> f64 {
+        self.sweep() * self.circle.radius
+    }
+
+    /// Approximates the arc with an open contour. The contour starts and ends exactly at the ends of the arc.
+    pub fn to_contour(&self, tolerance: f64) -> Contour<Point2d> {
+        Contour::open(self.points(tolerance))
+    }
+
+    fn points(&self, tolerance: f64) -> Vec<Point2d> {
+        let Circle { center, radius } = self.circle;
+        arc_angles(self.start_angle, self.sweep(), radius, tolerance)
+            .map(|angle| {
+                let (sin, cos) = angle.sin_cos();
+                Point2d::new(center.x + radius * cos, center.y + radius * sin)
+            })
+            .collect()
+    }
+}
+
+/// Sector of a circle (a "pie slice") bounded by an [`Arc`] and two radii.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sector {
+    /// Arc of the sector.
+    pub arc: Arc,
+}
+
+impl Sector {
+    /// Creates a new sector. See [`Arc`] for the meaning of the angles.
+    pub fn new(center: Point2d, radius: f64, start_angle: f64, end_angle: f64) -> Self {
+        Self {
+            arc: Arc::new(center, radius, start_angle, end_angle),
+        }
+    }
+
+    /// Returns true if the point is inside or on the boundary of the sector.
+    pub fn contains(&self, point: &impl CartesianPoint2d<Num = f64>) -> bool {
+        if !self.arc.circle.contains(point) {
+            return false;
+        }
+
+        let center = self.arc.circle.center;
+        if point.x() == center.x && point.y() == center.y {
+            return true;
+        }
+
+        let angle = (point.y() - center.y).atan2(point.x() - center.x);
+        let sweep = self.arc.sweep();
+        sweep >= TAU || sweep_from(self.arc.start_angle, angle) <= sweep
+    }
+
+    /// Approximates the sector with a polygon. Full-circle sectors are converted into a polygon of the circle.
+    pub fn to_polygon(&self, tolerance: f64) -> Polygon<Point2d> {
+        if self.arc.sweep() >= TAU {
+            return self.arc.circle.to_polygon(tolerance);
+        }
+
+        let mut points = self.arc.points(tolerance);
+        points.push(self.arc.circle.center);
+        Polygon::new(ClosedContour::new(points), vec![])
+    }
+}
+
+/// Number of segments needed to approximate an arc of the given radius and angular length, so that no point of the
+/// approximation deviates from the arc by more than `tolerance`.
+pub(crate) fn segment_count(radius: f64, sweep: f64, tolerance: f64) -> usize {
+    let radius = radius.abs();
+    let sweep = sweep.abs();
+    if !(radius > 0.0 && sweep > 0.0) {
+        return 1;
+    }
+
+    let segment_angle = if tolerance > 0.0 && tolerance < radius {
+        2.0 * (1.0 - tolerance / radius).acos()
+    } else {
+        PI / 2.0
+    };
+
+    ((sweep / segment_angle).ceil() as usize).max(1)
+}
+
+/// Angles of the points approximating an arc, including both ends of the arc.
+pub(crate) fn arc_angles(
+    start: f64,
+    sweep: f64,
+    radius: f64,
+    tolerance: f64,
+) -> impl Iterator<Item = f64> {
+    let count = segment_count(radius, sweep, tolerance);
+    (0..=count).map(move |i| start + sweep * i as f64 / count as f64)
+}
+
+/// Counterclockwise angle from `start` to `end` in the range `(0, 2*PI]`.
+pub(crate) fn sweep(start: f64, end: f64) -> f64 {
+    let sweep = sweep_from(start, end);
+    if sweep == 0.0 {
+        TAU
+    } else {
+        sweep
+    }
+}
+
+/// Counterclockwise angle from `start` to `end` in the range `[0, 2*PI)`.
+fn sweep_from(start: f64, end: f64) -> f64 {
+    let sweep = (end - start) % TAU;
+    if sweep < 0.0 {
+        sweep + TAU
+    } else {
+        sweep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contour::Contour as _;
+    use core::f64::consts::FRAC_PI_2;
+
+    fn max_deviation(points: &[Point2d], center: Point2d, radius: f64) -> f64 {
+        points
+            .iter()
+            .zip(points.iter().skip(1))
+            .map(|(a, b)| {
+                let middle = Point2d::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+                radius - (middle - center).norm()
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn circle_segments_depend_on_tolerance() {
+        let center = Point2d::new(10.0, -5.0);
+        for radius in [1.0, 100.0, 10_000.0] {
+            let polygon = Circle::new(center, radius).to_polygon(0.1);
+            let mut points = polygon.outer_contour.points.clone();
+            points.push(points[0]);
+            assert!(max_deviation(&points, center, radius) <= 0.1);
+            assert!(points.len() > MIN_RING_SEGMENTS);
+        }
+
+        let coarse = Circle::new(center, 10_000.0).to_polygon(10.0);
+        let fine = Circle::new(center, 10_000.0).to_polygon(0.1);
+        assert!(coarse.outer_contour.points.len() < fine.outer_contour.points.len());
+        assert_eq!(
+            Circle::new(center, 1.0)
+                .to_polygon(10.0)
+                .outer_contour
+                .points
+                .len(),
+            MIN_RING_SEGMENTS
+        );
+    }
+
+    #[test]
+    fn ellipse_is_rotated() {
+        let ellipse = Ellipse::new(Point2d::new(0.0, 0.0), 10.0, 2.0, FRAC_PI_2);
+        let top = ellipse.point_at(0.0);
+        assert!(top.x.abs() < 1e-9);
+        assert!((top.y - 10.0).abs() < 1e-9);
+
+        assert!(ellipse.contains(&Point2d::new(0.0, 9.0)));
+        assert!(!ellipse.contains(&Point2d::new(9.0, 0.0)));
+        assert!(ellipse
+            .to_polygon(0.01)
+            .outer_contour
+            .points
+            .iter()
+            .all(|p| p.x.abs() <= 2.0 + 1e-9));
+    }
+
+    #[test]
+    fn arc_goes_counterclockwise() {
+        let arc = Arc::new(Point2d::new(0.0, 0.0), 2.0, -FRAC_PI_2, FRAC_PI_2);
+        assert!((arc.sweep() - PI).abs() < 1e-12);
+        assert!((arc.length() - 2.0 * PI).abs() < 1e-12);
+
+        let points: Vec<_> = arc.to_contour(0.01).iter_points().copied().collect();
+        assert!((points[0] - Point2d::new(0.0, -2.0)).norm() < 1e-9);
+        assert!((points[points.len() - 1] - Point2d::new(0.0, 2.0)).norm() < 1e-9);
+        assert!(points.iter().all(|p| p.x >= -1e-9));
+
+        let wrapped = Arc::new(Point2d::new(0.0, 0.0), 2.0, FRAC_PI_2, -FRAC_PI_2);
+        assert!(wrapped.to_contour(0.01).iter_points().all(|p| p.x <= 1e-9));
+        assert!((Arc::new(Point2d::new(0.0, 0.0), 1.0, 1.0, 1.0).sweep() - TAU).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sector_contains_points() {
+        let sector = Sector::new(Point2d::new(0.0, 0.0), 10.0, 0.0, FRAC_PI_2);
+        assert!(sector.contains(&Point2d::new(5.0, 5.0)));
+        assert!(sector.contains(&Point2d::new(0.0, 0.0)));
+        assert!(!sector.contains(&Point2d::new(-5.0, 5.0)));
+        assert!(!sector.contains(&Point2d::new(9.0, 9.0)));
+
+        let polygon = sector.to_polygon(0.1);
+        assert_eq!(
+            polygon.outer_contour.points.last(),
+            Some(&Point2d::new(0.0, 0.0))
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use crate::cartesian::traits::cartesian_point::CartesianPoint2d;
 use crate::contour::{ClosedContour, Contour};
+use core::cmp::Ordering;
+use core::fmt::Debug;
 use num_traits::{One, Zero};
-use std::cmp::Ordering;
-use std::fmt::Debug;
 
 /// Methods specific to closed contours in 2d cartesian space. This trait is auto-implemented for all types implementing
 /// [`ClosedContour`] trait and consist of [`CartesianPoint2d`].
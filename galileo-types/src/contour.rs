@@ -25,6 +25,8 @@ use crate::geo::Projection;
 use crate::geometry::{CartesianGeometry2dSpecialization, Geom, Geometry, GeometrySpecialization};
 use crate::geometry_type::{CartesianSpace2d, ContourGeometryType, GeometryType};
 use crate::segment::Segment;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 
 /// Sequence of points. See module level documentation for details.
 pub trait Contour {
@@ -6,7 +6,7 @@ use crate::multi_contour::MultiContour;
 use crate::multi_point::MultiPoint;
 use crate::multi_polygon::MultiPolygon;
 use crate::polygon::Polygon;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// Wrapper type that disambiguates coordinate space for generic geometries.
 ///
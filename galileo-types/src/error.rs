@@ -1,11 +1,27 @@
 //! Error type used by the crate.
 
-use thiserror::Error;
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
 
 /// Error enum.
-#[derive(Debug, Error)]
+#[derive(Debug)]
 pub enum GalileoTypesError {
     /// Geometry conversion error.
-    #[error("invalid input geometry: {0}")]
     Conversion(String),
+    /// Text cannot be parsed.
+    Parse(String),
 }
+
+impl Display for GalileoTypesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            GalileoTypesError::Conversion(message) => {
+                write!(f, "invalid input geometry: {message}")
+            }
+            GalileoTypesError::Parse(message) => write!(f, "failed to parse: {message}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GalileoTypesError {}
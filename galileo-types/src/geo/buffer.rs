@@ -0,0 +1,659 @@
+use crate::cartesian::Point2d;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::navigation::PathType;
+use crate::geo::traits::point::{GeoPoint, NewGeoPoint};
+use crate::geometry::Geom;
+use crate::impls::{ClosedContour, MultiPolygon, Polygon};
+use crate::{Contour as _, MultiContour as _, MultiPoint as _};
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Maximum number of grid cells along each axis used to trace the outline of a buffer. If the geometry is too large
+/// for the requested tolerance, the cells are made larger.
+const MAX_GRID_SIZE: usize = 1024;
+
+/// Maximum number of bins along each axis of the index of segments.
+const MAX_BIN_COUNT: usize = 256;
+
+/// Returns the area within `distance` meters of the geometry.
+///
+/// Points and lines are buffered with round caps and joins. Polygons are extended by the `distance`, or shrunk by it
+/// if the `distance` is negative. Overlapping parts of the buffer (e.g. of a line that turns back on itself, or of
+/// several nearby points) are merged, and the holes of polygons narrower than the buffer are closed, so the result can
+/// be rendered directly.
+///
+/// The buffer is calculated in an azimuthal equidistant projection centered on the geometry, so it is correct at any
+/// latitude and across the antimeridian, as long as the geometry is not larger than a few hundred kilometers (the
+/// error at 500 km from the center is about 0.1%). Larger geometries should be buffered in parts.
+///
+/// The outline is traced on a grid with the cell size of `tolerance` meters, which is the precision of the result. To
+/// limit the cost of the calculation, the grid has at most 1024 cells on each side, so for large geometries the cells
+/// may be larger than the `tolerance`.
+///
+/// ```
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::{buffer, NewGeoPoint};
+/// use galileo_types::geometry::Geom;
+/// use galileo_types::impls::Contour;
+///
+/// // 2 km wide corridor along the route.
+/// let route = Contour::open(vec![
+///     GeoPoint2d::latlon(69.65, 18.95),
+///     GeoPoint2d::latlon(69.70, 19.05),
+///     GeoPoint2d::latlon(69.68, 19.20),
+/// ]);
+/// let corridor = buffer(&Geom::Contour(route), 1000.0, 10.0);
+/// assert_eq!(corridor.parts.len(), 1);
+/// ```
+pub fn buffer<P: GeoPoint<Num = f64>>(
+    geometry: &Geom<P>,
+    distance: f64,
+    tolerance: f64,
+) -> MultiPolygon<GeoPoint2d> {
+    let parts = Parts::collect(geometry);
+    let Some(center) = parts.center() else {
+        return MultiPolygon::from(vec![]);
+    };
+    if distance <= 0.0 && parts.rings.is_empty() {
+        return MultiPolygon::from(vec![]);
+    }
+
+    let projection = LocalProjection { center };
+    let mut field = DistanceField::new(&parts, &projection);
+    let Some(grid) = Grid::new(&mut field, distance, tolerance) else {
+        return MultiPolygon::from(vec![]);
+    };
+
+    let polygons = grid
+        .trace()
+        .into_iter()
+        .map(|polygon| polygon.cast_points(|p| projection.unproject(p)))
+        .collect::<Vec<_>>();
+
+    MultiPolygon::from(polygons)
+}
+
+/// Parts of the geometry in geographic coordinates.
+#[derive(Default)]
+struct Parts {
+    points: Vec<GeoPoint2d>,
+    lines: Vec<Vec<GeoPoint2d>>,
+    /// Rings of all polygons. The area inside them is determined by even-odd rule.
+    rings: Vec<Vec<GeoPoint2d>>,
+}
+
+impl Parts {
+    fn collect<P: GeoPoint<Num = f64>>(geometry: &Geom<P>) -> Self {
+        let mut parts = Self::default();
+        let convert = |p: &P| GeoPoint2d::latlon(p.lat(), p.lon());
+        let add_polygon = |parts: &mut Self, polygon: &Polygon<P>| {
+            for ring in core::iter::once(&polygon.outer_contour).chain(&polygon.inner_contours) {
+                parts.rings.push(ring.points.iter().map(convert).collect());
+            }
+        };
+
+        match geometry {
+            Geom::Point(point) => parts.points.push(convert(point)),
+            Geom::MultiPoint(points) => parts.points.extend(points.iter_points().map(convert)),
+            Geom::Contour(contour) => parts
+                .lines
+                .push(contour.iter_points_closing().map(convert).collect()),
+            Geom::MultiContour(contours) => {
+                for contour in contours.contours() {
+                    parts
+                        .lines
+                        .push(contour.iter_points_closing().map(convert).collect());
+                }
+            }
+            Geom::Polygon(polygon) => add_polygon(&mut parts, polygon),
+            Geom::MultiPolygon(polygons) => {
+                for polygon in &polygons.parts {
+                    add_polygon(&mut parts, polygon);
+                }
+            }
+        }
+
+        parts
+    }
+
+    fn iter_points(&self) -> impl Iterator<Item = &GeoPoint2d> {
+        self.points
+            .iter()
+            .chain(self.lines.iter().flatten())
+            .chain(self.rings.iter().flatten())
+    }
+
+    /// Center of the geometry on the sphere (direction of the sum of unit vectors of all the points).
+    fn center(&self) -> Option<GeoPoint2d> {
+        let mut first = None;
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        for point in self.iter_points() {
+            first.get_or_insert(*point);
+            let (lat, lon) = (point.lat_rad(), point.lon_rad());
+            x += lat.cos() * lon.cos();
+            y += lat.cos() * lon.sin();
+            z += lat.sin();
+        }
+
+        let first = first?;
+        let horizontal = (x * x + y * y).sqrt();
+        if horizontal + z.abs() < 1e-9 {
+            return Some(first);
+        }
+
+        Some(GeoPoint2d::latlon(
+            z.atan2(horizontal).to_degrees(),
+            y.atan2(x).to_degrees(),
+        ))
+    }
+}
+
+/// Azimuthal equidistant projection with the center at the given point. Coordinates are in meters, *Y* axis points
+/// to the north.
+struct LocalProjection {
+    center: GeoPoint2d,
+}
+
+impl LocalProjection {
+    fn project(&self, point: &GeoPoint2d) -> Point2d {
+        let distance = PathType::GreatCircle.distance(&self.center, point);
+        let bearing = PathType::GreatCircle
+            .initial_bearing(&self.center, point)
+            .to_radians();
+        Point2d::new(distance * bearing.sin(), distance * bearing.cos())
+    }
+
+    fn unproject(&self, point: &Point2d) -> GeoPoint2d {
+        let distance = (point.x * point.x + point.y * point.y).sqrt();
+        let bearing = point.x.atan2(point.y).to_degrees();
+        PathType::GreatCircle.destination(&self.center, bearing, distance)
+    }
+}
+
+/// Signed distance to the geometry in the local projection. The distance is negative inside polygons.
+struct DistanceField {
+    points: Vec<Point2d>,
+    segments: Vec<(Point2d, Point2d)>,
+    rings: Vec<Vec<Point2d>>,
+    bins: Bins,
+}
+
+impl DistanceField {
+    fn new(parts: &Parts, projection: &LocalProjection) -> Self {
+        let project_all = |points: &[GeoPoint2d]| -> Vec<Point2d> {
+            points.iter().map(|p| projection.project(p)).collect()
+        };
+        let points = project_all(&parts.points);
+        let rings: Vec<_> = parts.rings.iter().map(|r| project_all(r)).collect();
+
+        let mut segments = vec![];
+        for line in parts.lines.iter().map(|l| project_all(l)) {
+            if line.len() == 1 {
+                segments.push((line[0], line[0]));
+            }
+            segments.extend(line.windows(2).map(|s| (s[0], s[1])));
+        }
+        for ring in &rings {
+            segments.extend(ring_segments(ring));
+        }
+
+        Self {
+            points,
+            segments,
+            rings,
+            bins: Bins::default(),
+        }
+    }
+
+    /// Bounding box of the geometry.
+    fn bounds(&self) -> Option<(Point2d, Point2d)> {
+        let mut points = self
+            .points
+            .iter()
+            .chain(self.segments.iter().flat_map(|(a, b)| [a, b]));
+        let first = *points.next()?;
+        Some(points.fold((first, first), |(min, max), p| {
+            (
+                Point2d::new(min.x.min(p.x), min.y.min(p.y)),
+                Point2d::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        }))
+    }
+
+    /// Builds the index used by [`DistanceField::distance`] for the given distance limit.
+    fn index(&mut self, limit: f64) {
+        if let Some((min, max)) = self.bounds() {
+            self.bins = Bins::new(&self.points, &self.segments, min, max, limit);
+        }
+    }
+
+    /// Distance from the point to the nearest part of the geometry, or `limit` if nothing is closer than that.
+    fn distance(&self, point: &Point2d, limit: f64) -> f64 {
+        let mut nearest = limit;
+        for item in self.bins.near(point) {
+            let distance = match *item {
+                BinItem::Point(index) => (self.points[index] - point).norm(),
+                BinItem::Segment(index) => {
+                    let (a, b) = self.segments[index];
+                    segment_distance(point, &a, &b)
+                }
+            };
+            nearest = nearest.min(distance);
+        }
+
+        nearest
+    }
+}
+
+/// Spatial index of points and segments on a regular grid of bins.
+#[derive(Default)]
+struct Bins {
+    origin: Point2d,
+    size: f64,
+    columns: usize,
+    rows: usize,
+    items: Vec<Vec<BinItem>>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum BinItem {
+    Point(usize),
+    Segment(usize),
+}
+
+impl Bins {
+    /// Creates an index in which all the items within `radius` from any point are stored in the bin of the point or
+    /// in the neighbouring bins.
+    fn new(
+        points: &[Point2d],
+        segments: &[(Point2d, Point2d)],
+        min: Point2d,
+        max: Point2d,
+        radius: f64,
+    ) -> Self {
+        let extent = (max.x - min.x).max(max.y - min.y);
+        let size = radius.max(extent / MAX_BIN_COUNT as f64).max(f64::EPSILON);
+        let columns = ((max.x - min.x) / size).floor() as usize + 1;
+        let rows = ((max.y - min.y) / size).floor() as usize + 1;
+
+        let mut bins = Self {
+            origin: min,
+            size,
+            columns,
+            rows,
+            items: vec![vec![]; columns * rows],
+        };
+
+        for (index, point) in points.iter().enumerate() {
+            bins.insert(point, point, BinItem::Point(index));
+        }
+        for (index, (a, b)) in segments.iter().enumerate() {
+            bins.insert(a, b, BinItem::Segment(index));
+        }
+
+        bins
+    }
+
+    fn bin(&self, point: &Point2d) -> (usize, usize) {
+        let column = ((point.x - self.origin.x) / self.size).floor().max(0.0) as usize;
+        let row = ((point.y - self.origin.y) / self.size).floor().max(0.0) as usize;
+        (column.min(self.columns - 1), row.min(self.rows - 1))
+    }
+
+    fn insert(&mut self, a: &Point2d, b: &Point2d, item: BinItem) {
+        let (c0, r0) = self.bin(&Point2d::new(a.x.min(b.x), a.y.min(b.y)));
+        let (c1, r1) = self.bin(&Point2d::new(a.x.max(b.x), a.y.max(b.y)));
+        for row in r0..=r1 {
+            for column in c0..=c1 {
+                self.items[row * self.columns + column].push(item);
+            }
+        }
+    }
+
+    /// Items in the bin of the point and in the neighbouring bins. Items may be repeated.
+    fn near(&self, point: &Point2d) -> impl Iterator<Item = &BinItem> {
+        let (column, row) = if self.items.is_empty() {
+            (0, 0)
+        } else {
+            self.bin(point)
+        };
+        let columns = column.saturating_sub(1)..(column + 2).min(self.columns);
+        let rows = row.saturating_sub(1)..(row + 2).min(self.rows);
+        rows.flat_map(move |r| columns.clone().map(move |c| r * self.columns + c))
+            .flat_map(|index| self.items[index].iter())
+    }
+}
+
+/// Values of `signed distance - buffer distance` at the vertices of a regular grid covering the buffer. Vertices with
+/// negative values are inside the buffer.
+struct Grid {
+    origin: Point2d,
+    cell_size: f64,
+    /// Number of cells along *X* axis.
+    columns: usize,
+    /// Number of cells along *Y* axis.
+    rows: usize,
+    values: Vec<f64>,
+}
+
+impl Grid {
+    fn new(field: &mut DistanceField, distance: f64, tolerance: f64) -> Option<Self> {
+        let (min, max) = field.bounds()?;
+        let margin = distance.max(0.0);
+        let extent = (max.x - min.x).max(max.y - min.y) + 2.0 * margin;
+        let min_cell_size = extent / MAX_GRID_SIZE as f64;
+        let cell_size = if tolerance > 0.0 {
+            tolerance.max(min_cell_size)
+        } else {
+            min_cell_size
+        };
+        if cell_size.is_nan() || cell_size <= 0.0 {
+            return None;
+        }
+
+        let padding = margin + 2.0 * cell_size;
+        let origin = Point2d::new(min.x - padding, min.y - padding);
+        let columns = ((max.x - min.x + 2.0 * padding) / cell_size).ceil() as usize;
+        let rows = ((max.y - min.y + 2.0 * padding) / cell_size).ceil() as usize;
+
+        // Only the sign of the values matters further than this from the geometry.
+        let limit = distance.abs() + 2.0 * cell_size;
+        field.index(limit);
+
+        let mut values = Vec::with_capacity((columns + 1) * (rows + 1));
+        let mut crossings = vec![];
+        for row in 0..=rows {
+            let y = origin.y + row as f64 * cell_size;
+            ring_crossings(&field.rings, y, &mut crossings);
+
+            let mut crossed = 0;
+            for column in 0..=columns {
+                let point = Point2d::new(origin.x + column as f64 * cell_size, y);
+                while crossed < crossings.len() && crossings[crossed] < point.x {
+                    crossed += 1;
+                }
+
+                let unsigned = field.distance(&point, limit);
+                let signed = if crossed % 2 == 1 {
+                    -unsigned
+                } else {
+                    unsigned
+                };
+                values.push(signed - distance);
+            }
+        }
+
+        Some(Self {
+            origin,
+            cell_size,
+            columns,
+            rows,
+            values,
+        })
+    }
+
+    fn value(&self, column: usize, row: usize) -> f64 {
+        self.values[row * (self.columns + 1) + column]
+    }
+
+    fn vertex(&self, column: usize, row: usize) -> Point2d {
+        Point2d::new(
+            self.origin.x + column as f64 * self.cell_size,
+            self.origin.y + row as f64 * self.cell_size,
+        )
+    }
+
+    /// Id of the grid edge starting at the vertex. Horizontal edges go to the right, vertical ones go up.
+    fn edge_id(&self, column: usize, row: usize, vertical: bool) -> usize {
+        (row * (self.columns + 1) + column) * 2 + vertical as usize
+    }
+
+    /// Point on the edge where the value is zero.
+    fn edge_point(&self, id: usize) -> Point2d {
+        let vertex = id / 2;
+        let (column, row) = (vertex % (self.columns + 1), vertex / (self.columns + 1));
+        let (end_column, end_row) = if id % 2 == 1 {
+            (column, row + 1)
+        } else {
+            (column + 1, row)
+        };
+
+        let (v0, v1) = (self.value(column, row), self.value(end_column, end_row));
+        let t = v0 / (v0 - v1);
+        let (a, b) = (self.vertex(column, row), self.vertex(end_column, end_row));
+        a + (b - a) * t
+    }
+
+    /// Traces the outline of the area with negative values (marching squares). Outer rings are counterclockwise and
+    /// holes are clockwise.
+    fn trace(&self) -> Vec<Polygon<Point2d>> {
+        // Each segment of the outline goes from one grid edge to another with the inside on the left.
+        let mut next = BTreeMap::new();
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let corners = [
+                    (column, row),
+                    (column + 1, row),
+                    (column + 1, row + 1),
+                    (column, row + 1),
+                ];
+                let edges = [
+                    self.edge_id(column, row, false),
+                    self.edge_id(column + 1, row, true),
+                    self.edge_id(column, row + 1, false),
+                    self.edge_id(column, row, true),
+                ];
+                let inside = corners.map(|(c, r)| self.value(c, r) < 0.0);
+
+                // Edges where the outline crosses the cell boundary, in counterclockwise order, and whether the
+                // outline leaves the inside area there.
+                let crossings: Vec<(usize, bool)> = (0..4)
+                    .filter(|&i| inside[i] != inside[(i + 1) % 4])
+                    .map(|i| (edges[i], inside[i]))
+                    .collect();
+
+                match crossings[..] {
+                    [(first, true), (second, false)] => {
+                        next.insert(first, second);
+                    }
+                    [(first, false), (second, true)] => {
+                        next.insert(second, first);
+                    }
+                    [_, _, _, _] => {
+                        let center: f64 = corners.iter().map(|&(c, r)| self.value(c, r)).sum();
+                        let offset = if center < 0.0 { 1 } else { 3 };
+                        for i in 0..4 {
+                            if crossings[i].1 {
+                                next.insert(crossings[i].0, crossings[(i + offset) % 4].0);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut outers = vec![];
+        let mut holes = vec![];
+        while let Some((&start, _)) = next.first_key_value() {
+            let mut ring = vec![];
+            let mut edge = start;
+            while let Some(to) = next.remove(&edge) {
+                ring.push(self.edge_point(edge));
+                edge = to;
+            }
+
+            if ring.len() < 3 {
+                continue;
+            }
+
+            let area = signed_area(&ring);
+            if area > 0.0 {
+                outers.push((area, Polygon::new(ClosedContour::new(ring), vec![])));
+            } else {
+                holes.push(ring);
+            }
+        }
+
+        for hole in holes {
+            let parent = outers
+                .iter_mut()
+                .filter(|(_, polygon)| is_inside(&hole[0], &polygon.outer_contour.points))
+                .min_by(|(a, _), (b, _)| a.total_cmp(b));
+            if let Some((_, polygon)) = parent {
+                polygon.inner_contours.push(ClosedContour::new(hole));
+            }
+        }
+
+        outers.into_iter().map(|(_, polygon)| polygon).collect()
+    }
+}
+
+fn ring_segments(ring: &[Point2d]) -> impl Iterator<Item = (Point2d, Point2d)> + '_ {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+/// Writes sorted *X* coordinates of the points where the rings cross the horizontal line at `y` into `crossings`.
+fn ring_crossings(rings: &[Vec<Point2d>], y: f64, crossings: &mut Vec<f64>) {
+    crossings.clear();
+    for ring in rings {
+        for (a, b) in ring_segments(ring) {
+            if (a.y > y) != (b.y > y) {
+                crossings.push(a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x));
+            }
+        }
+    }
+
+    crossings.sort_by(f64::total_cmp);
+}
+
+fn is_inside(point: &Point2d, ring: &[Point2d]) -> bool {
+    ring_segments(ring)
+        .filter(|(a, b)| {
+            (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        })
+        .count()
+        % 2
+        == 1
+}
+
+fn signed_area(ring: &[Point2d]) -> f64 {
+    ring_segments(ring)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f64>()
+        / 2.0
+}
+
+fn segment_distance(point: &Point2d, a: &Point2d, b: &Point2d) -> f64 {
+    let segment = b - a;
+    let length_squared = segment.norm_squared();
+    if length_squared == 0.0 {
+        return (point - a).norm();
+    }
+
+    let t = ((point - a).dot(&segment) / length_squared).clamp(0.0, 1.0);
+    (point - (a + segment * t)).norm()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::impls::Contour;
+    use crate::MultiPolygon as _;
+    use core::f64::consts::PI;
+
+    fn area(polygon: &Polygon<GeoPoint2d>) -> f64 {
+        let projection = LocalProjection {
+            center: polygon.outer_contour.points[0],
+        };
+        let ring_area = |ring: &ClosedContour<GeoPoint2d>| {
+            let points: Vec<_> = ring.points.iter().map(|p| projection.project(p)).collect();
+            signed_area(&points)
+        };
+
+        ring_area(&polygon.outer_contour)
+            + polygon.inner_contours.iter().map(ring_area).sum::<f64>()
+    }
+
+    #[test]
+    fn point_buffer_is_a_circle_at_any_latitude() {
+        for lat in [0.0, 45.0, 75.0, 89.9] {
+            let center = GeoPoint2d::latlon(lat, 179.99);
+            let result = buffer(&Geom::Point(center), 1000.0, 10.0);
+            assert_eq!(result.parts.len(), 1);
+
+            let polygon = &result.parts[0];
+            assert!(polygon.inner_contours.is_empty());
+            for point in &polygon.outer_contour.points {
+                let distance = PathType::GreatCircle.distance(&center, point);
+                assert!((distance - 1000.0).abs() < 10.0, "{distance} at {lat}");
+            }
+
+            let relative_error = area(polygon) / (PI * 1000.0 * 1000.0) - 1.0;
+            assert!(relative_error.abs() < 0.01, "{relative_error}");
+        }
+    }
+
+    #[test]
+    fn overlapping_parts_are_merged() {
+        // The line turns back, so the buffers of the two legs overlap.
+        let line = Contour::open(vec![
+            GeoPoint2d::latlon(10.0, 10.0),
+            GeoPoint2d::latlon(10.0, 10.1),
+            GeoPoint2d::latlon(10.005, 10.0),
+        ]);
+        let result = buffer(&Geom::Contour(line), 500.0, 20.0);
+        assert_eq!(result.parts.len(), 1);
+        assert!(result.parts[0].inner_contours.is_empty());
+
+        let points = vec![
+            GeoPoint2d::latlon(0.0, 0.0),
+            GeoPoint2d::latlon(0.0, 0.01),
+            GeoPoint2d::latlon(0.0, 1.0),
+        ];
+        let result = buffer(&Geom::MultiPoint(points.into()), 1000.0, 10.0);
+        assert_eq!(result.polygons().count(), 2);
+    }
+
+    #[test]
+    fn polygon_buffer_grows_and_shrinks() {
+        // 2 km square with 200 m hole.
+        let square = |half_size: f64| {
+            let d = half_size / 111_320.0;
+            ClosedContour::new(vec![
+                GeoPoint2d::latlon(-d, -d),
+                GeoPoint2d::latlon(-d, d),
+                GeoPoint2d::latlon(d, d),
+                GeoPoint2d::latlon(d, -d),
+            ])
+        };
+        let polygon = Polygon::new(square(1000.0), vec![square(100.0)]);
+        let geom = Geom::Polygon(polygon);
+
+        let grown = buffer(&geom, 200.0, 10.0);
+        assert_eq!(grown.parts.len(), 1);
+        assert!(grown.parts[0].inner_contours.is_empty());
+        // Square with rounded corners: 2400^2 - (4 - PI) * 200^2.
+        let expected = 2400.0 * 2400.0 - (4.0 - PI) * 200.0 * 200.0;
+        assert!((area(&grown.parts[0]) / expected - 1.0).abs() < 0.01);
+
+        let shrunk = buffer(&geom, -50.0, 10.0);
+        assert_eq!(shrunk.parts.len(), 1);
+        assert_eq!(shrunk.parts[0].inner_contours.len(), 1);
+        let expected = 1900.0 * 1900.0 - 300.0 * 300.0 + (4.0 - PI) * 50.0 * 50.0;
+        assert!((area(&shrunk.parts[0]) / expected - 1.0).abs() < 0.01);
+
+        assert!(buffer(&geom, -2000.0, 10.0).parts.is_empty());
+        assert!(
+            buffer(&Geom::Point(GeoPoint2d::latlon(0.0, 0.0)), -1.0, 1.0)
+                .parts
+                .is_empty()
+        );
+    }
+}
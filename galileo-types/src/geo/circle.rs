@@ -0,0 +1,141 @@
+use crate::cartesian::shapes::{arc_angles, segment_count, sweep, MIN_RING_SEGMENTS};
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::navigation::PathType;
+use crate::geo::traits::point::GeoPoint;
+use crate::impls::{ClosedContour, Contour, Polygon};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::TAU;
+
+/// Circle on the surface of the Earth with the radius in meters.
+///
+/// Unlike a [`Circle`](crate::cartesian::Circle) in projected coordinates, the points of the polygons created from a
+/// `GeoCircle` are calculated in geographic coordinates, at the given distance from the center. So when a layer
+/// projects them into the map CRS, the circle has the correct size and shape at any latitude (e.g. it is stretched
+/// to the north in Web Mercator). This is what range rings, coverage areas and geofences need.
+///
+/// Bearings of arcs and sectors are in degrees clockwise from the north. `tolerance` is the maximum deviation of the
+/// approximating segments from the true circle in meters. Distances are calculated on a sphere (see [`PathType`]).
+///
+/// Circles that contain a pole or cross the antimeridian are not split, so they are not drawn correctly in the
+/// projections that cut the Earth there.
+///
+/// ```
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::{GeoCircle, LengthUnit, NewGeoPoint};
+///
+/// let lighthouse = GeoPoint2d::latlon(50.6, -1.3);
+/// let range = GeoCircle::new(lighthouse, LengthUnit::NauticalMiles.to_meters(12.0));
+///
+/// let ring = range.to_polygon(10.0);
+/// let visible_sector = range.sector(300.0, 60.0, 10.0);
+///
+/// assert!(range.contains(&GeoPoint2d::latlon(50.7, -1.2)));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GeoCircle {
+    /// Center of the circle.
+    pub center: GeoPoint2d,
+    /// Radius of the circle in meters.
+    pub radius: f64,
+}
+
+impl GeoCircle {
+    /// Creates a new circle.
+    pub fn new(center: GeoPoint2d, radius: f64) -> Self {
+        Self { center, radius }
+    }
+
+    /// Returns true if the point is inside or on the boundary of the circle.
+    pub fn contains(&self, point: &impl GeoPoint<Num = f64>) -> bool {
+        PathType::GreatCircle.distance(&self.center, point) <= self.radius
+    }
+
+    /// Point of the circle at the given bearing from the center.
+    pub fn point_at(&self, bearing: f64) -> GeoPoint2d {
+        PathType::GreatCircle.destination(&self.center, bearing, self.radius)
+    }
+
+    /// Approximates the circle with a polygon.
+    pub fn to_polygon(&self, tolerance: f64) -> Polygon<GeoPoint2d> {
+        let count = segment_count(self.radius, TAU, tolerance).max(MIN_RING_SEGMENTS);
+        let points = (0..count)
+            .map(|i| self.point_at(360.0 * i as f64 / count as f64))
+            .collect();
+
+        Polygon::new(ClosedContour::new(points), vec![])
+    }
+
+    /// Approximates the arc of the circle from `start_bearing` clockwise to `end_bearing` with an open contour. If
+    /// the bearings are equal, the arc is the full circle.
+    pub fn arc(&self, start_bearing: f64, end_bearing: f64, tolerance: f64) -> Contour<GeoPoint2d> {
+        Contour::open(self.arc_points(start_bearing, end_bearing, tolerance))
+    }
+
+    /// Approximates the sector of the circle between `start_bearing` and `end_bearing` (clockwise) with a polygon.
+    pub fn sector(
+        &self,
+        start_bearing: f64,
+        end_bearing: f64,
+        tolerance: f64,
+    ) -> Polygon<GeoPoint2d> {
+        if sweep(start_bearing.to_radians(), end_bearing.to_radians()) >= TAU {
+            return self.to_polygon(tolerance);
+        }
+
+        let mut points = self.arc_points(start_bearing, end_bearing, tolerance);
+        points.push(self.center);
+        Polygon::new(ClosedContour::new(points), vec![])
+    }
+
+    fn arc_points(&self, start_bearing: f64, end_bearing: f64, tolerance: f64) -> Vec<GeoPoint2d> {
+        let sweep = sweep(start_bearing.to_radians(), end_bearing.to_radians());
+        arc_angles(start_bearing.to_radians(), sweep, self.radius, tolerance)
+            .map(|angle| self.point_at(angle.to_degrees()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contour::Contour as _;
+    use crate::geo::NewGeoPoint;
+
+    #[test]
+    fn polygon_points_are_at_radius() {
+        let circle = GeoCircle::new(GeoPoint2d::latlon(60.0, 25.0), 50_000.0);
+        let polygon = circle.to_polygon(5.0);
+
+        for point in &polygon.outer_contour.points {
+            let distance = PathType::GreatCircle.distance(&circle.center, point);
+            assert!((distance - circle.radius).abs() < 1e-6);
+        }
+
+        // At 60° a degree of longitude is half as long as a degree of latitude.
+        let points = &polygon.outer_contour.points;
+        let lat_span = points.iter().map(|p| p.lat()).fold(f64::MIN, f64::max)
+            - points.iter().map(|p| p.lat()).fold(f64::MAX, f64::min);
+        let lon_span = points.iter().map(|p| p.lon()).fold(f64::MIN, f64::max)
+            - points.iter().map(|p| p.lon()).fold(f64::MAX, f64::min);
+        assert!((lon_span / lat_span - 2.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn sector_goes_clockwise() {
+        let circle = GeoCircle::new(GeoPoint2d::latlon(0.0, 0.0), 10_000.0);
+
+        let arc = circle.arc(350.0, 10.0, 1.0);
+        let points: Vec<_> = arc.iter_points().copied().collect();
+        let bearing = |p: &GeoPoint2d| PathType::GreatCircle.initial_bearing(&circle.center, p);
+        assert!((bearing(&points[0]) - 350.0).abs() < 1e-6);
+        assert!((bearing(&points[points.len() - 1]) - 10.0).abs() < 1e-6);
+        assert!(points.iter().all(|p| p.lat() > 0.0));
+
+        let sector = circle.sector(90.0, 180.0, 1.0);
+        assert_eq!(sector.outer_contour.points.last(), Some(&circle.center));
+        assert!(sector.outer_contour.points.iter().all(|p| p.lat() <= 1e-9));
+        assert!(circle.contains(&GeoPoint2d::latlon(0.05, 0.05)));
+        assert!(!circle.contains(&GeoPoint2d::latlon(0.1, 0.0)));
+    }
+}
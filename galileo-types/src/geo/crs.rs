@@ -1,8 +1,12 @@
 use crate::cartesian::NewCartesianPoint2d;
 use crate::geo::datum::Datum;
-use crate::geo::impls::projection::{GeodesyProjection, WebMercator};
+#[cfg(feature = "geodesy")]
+use crate::geo::impls::projection::GeodesyProjection;
+use crate::geo::impls::projection::WebMercator;
 use crate::geo::traits::point::NewGeoPoint;
 use crate::geo::traits::projection::Projection;
+use alloc::boxed::Box;
+use alloc::string::String;
 use serde::{Deserialize, Serialize};
 
 /// Coordinate reference system.
@@ -59,6 +63,7 @@ impl Crs {
     {
         match &self.projection_type {
             ProjectionType::WebMercator => Some(Box::new(WebMercator::new(self.datum))),
+            #[cfg(feature = "geodesy")]
             ProjectionType::Other(definition) => {
                 Some(Box::new(GeodesyProjection::new(definition)?))
             }
@@ -23,6 +23,11 @@ impl Datum {
     pub fn inv_flattening(&self) -> f64 {
         self.inv_flattening
     }
+
+    /// Mean radius of the ellipsoid (`(2a + b) / 3`), used as the radius of the sphere in spherical calculations.
+    pub fn mean_radius(&self) -> f64 {
+        self.semimajor * (1.0 - 1.0 / (3.0 * self.inv_flattening))
+    }
 }
 
 impl Default for Datum {
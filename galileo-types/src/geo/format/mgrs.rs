@@ -0,0 +1,367 @@
+use super::utm::{central_meridian, UtmCoordinate, BAND_LETTERS, MAX_LATITUDE, MIN_LATITUDE};
+use crate::error::GalileoTypesError;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::traits::point::GeoPoint;
+use alloc::format;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Size of a grid square in meters.
+pub(super) const SQUARE_SIZE: f64 = 100_000.0;
+/// Northing after which the row letters repeat.
+const ROW_CYCLE: f64 = 2_000_000.0;
+
+/// Column letters of the grid squares, for the zones 1, 2 and 3 (repeated for every three zones).
+const COLUMN_LETTERS: [&[u8; 8]; 3] = [b"ABCDEFGH", b"JKLMNPQR", b"STUVWXYZ"];
+const ROW_LETTERS: &[u8; 20] = b"ABCDEFGHJKLMNPQRSTUV";
+
+/// Position in the Military Grid Reference System, which refers to the cells of the UTM grid with letters of 100 km
+/// squares and a given number of digits of easting and northing within the square.
+///
+/// ```
+/// use galileo_types::geo::format::MgrsCoordinate;
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::NewGeoPoint;
+///
+/// let mgrs = MgrsCoordinate::from_geo(&GeoPoint2d::latlon(0.0, 0.0), 5).expect("inside MGRS");
+/// assert_eq!(mgrs.to_string(), "31N AA 66021 00000");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MgrsCoordinate {
+    /// UTM zone number from 1 to 60.
+    pub zone: u8,
+    /// Latitude band letter.
+    pub band: char,
+    /// Column and row letters of the 100 km square.
+    pub square: [char; 2],
+    /// Easting within the square in meters, truncated to the precision of the coordinate.
+    pub easting: f64,
+    /// Northing within the square in meters, truncated to the precision of the coordinate.
+    pub northing: f64,
+    /// Number of digits of easting and northing, from 0 (100 km precision) to 5 (1 m precision).
+    pub digits: u8,
+}
+
+impl MgrsCoordinate {
+    /// Converts the point into MGRS with the given number of digits (clamped to 5). Returns `None` for the points
+    /// outside the UTM latitude range (80°S to 84°N).
+    pub fn from_geo(point: &impl GeoPoint<Num = f64>, digits: u8) -> Option<Self> {
+        let band = UtmCoordinate::latitude_band(point.lat())?;
+        Self::from_utm(&UtmCoordinate::from_geo(point)?, band, digits)
+    }
+
+    /// Creates the MGRS coordinate of the UTM position in the given latitude band. Returns `None` if the position is
+    /// outside the columns of the grid squares of the zone.
+    pub fn from_utm(utm: &UtmCoordinate, band: char, digits: u8) -> Option<Self> {
+        let digits = digits.min(5);
+        let [column, row] = square_letters(utm.zone, utm.easting, utm.northing)?;
+        let cell = cell_size(digits);
+        let truncate = |value: f64| ((value % SQUARE_SIZE) / cell).floor() * cell;
+
+        Some(Self {
+            zone: utm.zone,
+            band,
+            square: [column, row],
+            easting: truncate(utm.easting),
+            northing: truncate(utm.northing),
+            digits,
+        })
+    }
+
+    /// Converts the coordinate into UTM. The result is the south-west corner of the cell of the coordinate.
+    pub fn to_utm(&self) -> Option<UtmCoordinate> {
+        let band = self.band.to_ascii_uppercase();
+        let band_index = BAND_LETTERS.iter().position(|&c| c as char == band)?;
+        if !(1..=60).contains(&self.zone) {
+            return None;
+        }
+
+        let set = COLUMN_LETTERS[(self.zone as usize - 1) % 3];
+        let column = set
+            .iter()
+            .position(|&c| c as char == self.square[0].to_ascii_uppercase())?;
+        let mut row = ROW_LETTERS
+            .iter()
+            .position(|&c| c as char == self.square[1].to_ascii_uppercase())?;
+        if self.zone.is_multiple_of(2) {
+            row = (row + 15) % 20;
+        }
+
+        let mut northing = row as f64 * SQUARE_SIZE + self.northing;
+        let min_northing = MIN_NORTHINGS[band_index];
+        while northing < min_northing {
+            northing += ROW_CYCLE;
+        }
+
+        Some(UtmCoordinate {
+            zone: self.zone,
+            northern: band >= 'N',
+            easting: (column + 1) as f64 * SQUARE_SIZE + self.easting,
+            northing,
+        })
+    }
+
+    /// Converts the coordinate into a geographic point at the south-west corner of the cell of the coordinate.
+    pub fn to_geo(&self) -> Option<GeoPoint2d> {
+        self.to_utm()?.to_geo()
+    }
+
+    /// Size of the cell of the coordinate in meters.
+    pub fn precision(&self) -> f64 {
+        cell_size(self.digits)
+    }
+}
+
+impl Display for MgrsCoordinate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}{} {}{}",
+            self.zone, self.band, self.square[0], self.square[1]
+        )?;
+
+        if self.digits > 0 {
+            let cell = self.precision();
+            let width = self.digits as usize;
+            write!(
+                f,
+                " {:0width$} {:0width$}",
+                (self.easting / cell).floor() as u32,
+                (self.northing / cell).floor() as u32,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for MgrsCoordinate {
+    type Err = GalileoTypesError;
+
+    /// Parses the coordinate with or without spaces, e.g. `31N AA 66021 00000` or `31NAA6602100000`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || GalileoTypesError::Parse(format!("invalid MGRS coordinate: {s}"));
+        let compact: alloc::string::String = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+
+        let zone_length = compact.chars().take_while(|c| c.is_ascii_digit()).count();
+        if !(1..=2).contains(&zone_length) || !compact.is_ascii() {
+            return Err(error());
+        }
+
+        let zone: u8 = compact[..zone_length].parse().map_err(|_| error())?;
+        let letters = &compact.as_bytes()[zone_length..];
+        if letters.len() < 3 {
+            return Err(error());
+        }
+
+        let (band, column, row) = (letters[0], letters[1], letters[2]);
+        let numbers = &letters[3..];
+
+        let digits = numbers.len() / 2;
+        if !numbers.len().is_multiple_of(2) || digits > 5 || !numbers.iter().all(u8::is_ascii_digit)
+        {
+            return Err(error());
+        }
+
+        let cell = cell_size(digits as u8);
+        let parse_number = |digits: &[u8]| -> f64 {
+            digits
+                .iter()
+                .fold(0.0, |value, digit| value * 10.0 + (digit - b'0') as f64)
+                * cell
+        };
+
+        let mgrs = Self {
+            zone,
+            band: band as char,
+            square: [column as char, row as char],
+            easting: parse_number(&numbers[..digits]),
+            northing: parse_number(&numbers[digits..]),
+            digits: digits as u8,
+        };
+
+        match mgrs.to_geo() {
+            Some(_) => Ok(mgrs),
+            None => Err(error()),
+        }
+    }
+}
+
+/// Grid zone of MGRS: the part of a UTM zone inside one latitude band, e.g. `33U`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GridZone {
+    /// UTM zone number.
+    pub zone: u8,
+    /// Latitude band letter.
+    pub band: char,
+    /// Southern boundary of the grid zone in degrees.
+    pub lat_min: f64,
+    /// Northern boundary of the grid zone in degrees.
+    pub lat_max: f64,
+    /// Western boundary of the grid zone in degrees.
+    pub lon_min: f64,
+    /// Eastern boundary of the grid zone in degrees.
+    pub lon_max: f64,
+}
+
+impl GridZone {
+    /// All grid zones from 80°S to 84°N, including the irregular zones around Norway and Svalbard.
+    pub fn all() -> impl Iterator<Item = GridZone> {
+        BAND_LETTERS
+            .iter()
+            .enumerate()
+            .flat_map(|(band_index, &band)| {
+                let lat_min = MIN_LATITUDE + 8.0 * band_index as f64;
+                let lat_max = if band == b'X' {
+                    MAX_LATITUDE
+                } else {
+                    lat_min + 8.0
+                };
+
+                (1..=60).filter_map(move |zone| {
+                    let (lon_min, lon_max) = zone_longitudes(zone, band)?;
+                    Some(GridZone {
+                        zone,
+                        band: band as char,
+                        lat_min,
+                        lat_max,
+                        lon_min,
+                        lon_max,
+                    })
+                })
+            })
+    }
+}
+
+impl Display for GridZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{}", self.zone, self.band)
+    }
+}
+
+/// Western and eastern boundaries of the UTM zone in the given latitude band. Returns `None` for the zones that do not
+/// exist in the band (zones 32, 34 and 36 in band `X`).
+fn zone_longitudes(zone: u8, band: u8) -> Option<(f64, f64)> {
+    let lon_min = central_meridian(zone) - 3.0;
+    match (band, zone) {
+        (b'V', 31) => Some((0.0, 3.0)),
+        (b'V', 32) => Some((3.0, 12.0)),
+        (b'X', 32 | 34 | 36) => None,
+        (b'X', 31) => Some((0.0, 9.0)),
+        (b'X', 33) => Some((9.0, 21.0)),
+        (b'X', 35) => Some((21.0, 33.0)),
+        (b'X', 37) => Some((33.0, 42.0)),
+        _ => Some((lon_min, lon_min + 6.0)),
+    }
+}
+
+/// Column and row letters of the 100 km square containing the given UTM position.
+pub(super) fn square_letters(zone: u8, easting: f64, northing: f64) -> Option<[char; 2]> {
+    if !(1..=60).contains(&zone) || !easting.is_finite() || !northing.is_finite() {
+        return None;
+    }
+
+    let column = (easting / SQUARE_SIZE).floor() as i64;
+    if !(1..=8).contains(&column) {
+        return None;
+    }
+
+    let mut row = ((northing / SQUARE_SIZE).floor() as i64).rem_euclid(20) as usize;
+    if zone.is_multiple_of(2) {
+        row = (row + 5) % 20;
+    }
+
+    Some([
+        COLUMN_LETTERS[(zone as usize - 1) % 3][column as usize - 1] as char,
+        ROW_LETTERS[row] as char,
+    ])
+}
+
+fn cell_size(digits: u8) -> f64 {
+    10f64.powi(5 - digits.min(5) as i32)
+}
+
+/// Smallest northing of every latitude band (in the order of [`BAND_LETTERS`]), used to find which cycle of the row
+/// letters a coordinate refers to.
+const MIN_NORTHINGS: [f64; 20] = [
+    1_100_000.0,
+    2_000_000.0,
+    2_800_000.0,
+    3_700_000.0,
+    4_600_000.0,
+    5_500_000.0,
+    6_400_000.0,
+    7_300_000.0,
+    8_200_000.0,
+    9_100_000.0,
+    0.0,
+    800_000.0,
+    1_700_000.0,
+    2_600_000.0,
+    3_500_000.0,
+    4_400_000.0,
+    5_300_000.0,
+    6_200_000.0,
+    7_000_000.0,
+    7_900_000.0,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::traits::point::NewGeoPoint;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn mgrs_round_trip() {
+        let origin: MgrsCoordinate = "31NAA6602100000".parse().unwrap();
+        assert_eq!(
+            origin,
+            MgrsCoordinate::from_geo(&GeoPoint2d::latlon(0.0, 0.0), 5).unwrap()
+        );
+
+        for (lat, lon) in [
+            (-33.8688, 151.2093),
+            (50.0870, 14.4208),
+            (-79.5, -60.0),
+            (83.0, 30.0),
+        ] {
+            let mgrs = MgrsCoordinate::from_geo(&GeoPoint2d::latlon(lat, lon), 5).unwrap();
+            let parsed: MgrsCoordinate = mgrs.to_string().parse().unwrap();
+            assert_eq!(parsed, mgrs);
+
+            // The south-west corner of the 1 m cell.
+            let point = parsed.to_geo().unwrap();
+            assert!((point.lat() - lat).abs() < 2e-5, "{mgrs}");
+            assert!((point.lon() - lon).abs() < 1e-4, "{mgrs}");
+        }
+
+        let coarse = MgrsCoordinate::from_geo(&GeoPoint2d::latlon(0.0, 0.0), 2).unwrap();
+        assert_eq!(coarse.to_string(), "31N AA 66 00");
+        assert_eq!(coarse.precision(), 1000.0);
+
+        assert!("31NAA660210000".parse::<MgrsCoordinate>().is_err());
+        assert!("31NIA6602100000".parse::<MgrsCoordinate>().is_err());
+    }
+
+    #[test]
+    fn grid_zones_match_utm_zones() {
+        let zones: Vec<GridZone> = GridZone::all().collect();
+        assert_eq!(zones.len(), 20 * 60 - 3);
+
+        for zone in zones {
+            let center = GeoPoint2d::latlon(
+                (zone.lat_min + zone.lat_max) / 2.0,
+                (zone.lon_min + zone.lon_max) / 2.0,
+            );
+            let mgrs = MgrsCoordinate::from_geo(&center, 0).unwrap();
+            assert_eq!((mgrs.zone, mgrs.band), (zone.zone, zone.band), "{zone}");
+        }
+    }
+}
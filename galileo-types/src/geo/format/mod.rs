@@ -0,0 +1,375 @@
+//! Formatting and parsing of geographic coordinates in the notations used to show positions to the user: decimal
+//! degrees, degrees with minutes and seconds, UTM, MGRS and plus codes.
+
+mod mgrs;
+mod plus_code;
+mod utm;
+
+use crate::error::GalileoTypesError;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::traits::point::{GeoPoint, NewGeoPoint};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::mem;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+pub use mgrs::{GridZone, MgrsCoordinate};
+pub use plus_code::{decode_plus_code, encode_plus_code};
+pub use utm::UtmCoordinate;
+
+/// Notation of a geographic position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoordinateFormat {
+    /// Latitude and longitude in signed decimal degrees, e.g. `50.08700, 14.42080`.
+    DecimalDegrees {
+        /// Number of decimal places.
+        precision: u8,
+    },
+    /// Degrees, minutes and seconds with the hemisphere letters, e.g. `50°05'13.2"N 14°25'14.9"E`.
+    DegreesMinutesSeconds {
+        /// Number of decimal places of the seconds.
+        precision: u8,
+    },
+    /// Degrees and decimal minutes with the hemisphere letters, e.g. `50°05.220'N 14°25.248'E`.
+    DegreesDecimalMinutes {
+        /// Number of decimal places of the minutes.
+        precision: u8,
+    },
+    /// UTM zone, easting and northing in meters, e.g. `33N 458565 5548464`. See [`UtmCoordinate`].
+    Utm,
+    /// MGRS grid reference, e.g. `33U VR 58565 48464`. See [`MgrsCoordinate`].
+    Mgrs {
+        /// Number of digits of easting and northing, from 0 to 5.
+        digits: u8,
+    },
+    /// Open Location Code, e.g. `9F2P3CPC+R8`. See [`encode_plus_code`].
+    PlusCode {
+        /// Number of digits of the code.
+        length: u8,
+    },
+}
+
+impl Default for CoordinateFormat {
+    fn default() -> Self {
+        Self::DecimalDegrees { precision: 5 }
+    }
+}
+
+impl CoordinateFormat {
+    /// Formats the point. Returns `None` if the point cannot be represented in this notation, e.g. if it is outside
+    /// the latitude range of UTM.
+    pub fn format(&self, point: &impl GeoPoint<Num = f64>) -> Option<String> {
+        let (lat, lon) = (point.lat(), point.lon());
+        if !lat.is_finite() || !lon.is_finite() || lat.abs() > 90.0 {
+            return None;
+        }
+
+        let lon = utm::normalize_longitude(lon);
+        Some(match *self {
+            Self::DecimalDegrees { precision } => {
+                let precision = precision as usize;
+                format!("{lat:.precision$}, {lon:.precision$}")
+            }
+            Self::DegreesMinutesSeconds { precision } => format!(
+                "{} {}",
+                sexagesimal(lat, ['N', 'S'], 2, precision),
+                sexagesimal(lon, ['E', 'W'], 2, precision)
+            ),
+            Self::DegreesDecimalMinutes { precision } => format!(
+                "{} {}",
+                sexagesimal(lat, ['N', 'S'], 1, precision),
+                sexagesimal(lon, ['E', 'W'], 1, precision)
+            ),
+            Self::Utm => UtmCoordinate::from_geo(point)?.to_string(),
+            Self::Mgrs { digits } => MgrsCoordinate::from_geo(point, digits)?.to_string(),
+            Self::PlusCode { length } => encode_plus_code(point, length as usize),
+        })
+    }
+}
+
+/// Writes the absolute value of the angle in degrees and the given number of sexagesimal parts (minutes and
+/// seconds), followed by the hemisphere letter.
+fn sexagesimal(value: f64, hemispheres: [char; 2], parts: u32, precision: u8) -> String {
+    let hemisphere = if value < 0.0 {
+        hemispheres[1]
+    } else {
+        hemispheres[0]
+    };
+
+    // Round before splitting into parts, so that e.g. 59.99" becomes 1' instead of 60".
+    let scale = 10f64.powi(precision as i32);
+    let units_per_degree = 60f64.powi(parts as i32) * scale;
+    let total = (value.abs() * units_per_degree).round();
+
+    let degrees = (total / units_per_degree).floor();
+    let mut out = format!("{degrees:.0}°");
+    let mut rest = total - degrees * units_per_degree;
+    for part in 1..=parts {
+        let units_per_part = 60f64.powi((parts - part) as i32) * scale;
+        let symbol = if part == 1 { '\'' } else { '"' };
+        if part < parts {
+            let whole = (rest / units_per_part).floor();
+            out.push_str(&format!("{whole:02.0}{symbol}"));
+            rest -= whole * units_per_part;
+        } else {
+            let precision = precision as usize;
+            let width = if precision > 0 { precision + 3 } else { 2 };
+            out.push_str(&format!("{:0width$.precision$}{symbol}", rest / scale));
+        }
+    }
+
+    out.push(hemisphere);
+    out
+}
+
+/// Parses a geographic position written in any of the notations of [`CoordinateFormat`].
+///
+/// Latitude and longitude can be written as decimal degrees or with minutes and seconds, separated by spaces or
+/// degree, minute and second symbols, with or without hemisphere letters, e.g. `50.087, 14.4208`,
+/// `-33.8688 151.2093`, `50°05'13.2"N 14°25'14.9"E` or `N 50 05.22 E 14 25.248`. Without hemisphere letters,
+/// latitude goes first.
+///
+/// For MGRS references and plus codes, the center of the referenced area is returned.
+///
+/// ```
+/// use galileo_types::geo::format::parse_coordinate;
+/// use galileo_types::geo::GeoPoint;
+///
+/// let point = parse_coordinate("50°05'13.2\"N 14°25'14.9\"E").expect("valid coordinate");
+/// assert!((point.lat() - 50.087).abs() < 1e-4);
+/// ```
+pub fn parse_coordinate(input: &str) -> Result<GeoPoint2d, GalileoTypesError> {
+    let input = input.trim();
+
+    if input.contains('+') {
+        if let Ok(point) = decode_plus_code(input) {
+            return Ok(point);
+        }
+    }
+
+    if let Ok(mgrs) = input.parse::<MgrsCoordinate>() {
+        let half_cell = mgrs.precision() / 2.0;
+        if let Some(point) = mgrs.to_utm().and_then(|utm| {
+            UtmCoordinate {
+                easting: utm.easting + half_cell,
+                northing: utm.northing + half_cell,
+                ..utm
+            }
+            .to_geo()
+        }) {
+            return Ok(point);
+        }
+    }
+
+    if let Some(point) = input
+        .parse::<UtmCoordinate>()
+        .ok()
+        .and_then(|utm| utm.to_geo())
+    {
+        return Ok(point);
+    }
+
+    parse_lat_lon(input)
+        .ok_or_else(|| GalileoTypesError::Parse(format!("unrecognized coordinate: {input}")))
+}
+
+enum Token {
+    Number(f64),
+    Hemisphere(char),
+    Break,
+}
+
+/// Numbers of one of the coordinates (degrees, minutes and seconds) with its hemisphere letter.
+#[derive(Default)]
+struct Angle {
+    values: Vec<f64>,
+    hemisphere: Option<char>,
+}
+
+impl Angle {
+    fn value(&self) -> Option<f64> {
+        let (first, rest) = self.values.split_first()?;
+        if rest.len() > 2 {
+            return None;
+        }
+
+        let mut magnitude = first.abs();
+        let mut unit = 1.0;
+        for value in rest {
+            if !(0.0..60.0).contains(value) {
+                return None;
+            }
+
+            unit /= 60.0;
+            magnitude += value * unit;
+        }
+
+        let negative = first.is_sign_negative();
+        match self.hemisphere {
+            Some('S' | 'W') if negative => None,
+            Some('S' | 'W') => Some(-magnitude),
+            _ if negative => Some(-magnitude),
+            _ => Some(magnitude),
+        }
+    }
+}
+
+fn parse_lat_lon(input: &str) -> Option<GeoPoint2d> {
+    let mut tokens = Vec::new();
+    let mut number = String::new();
+    for c in input.chars().chain([' ']) {
+        if c.is_ascii_digit() || c == '.' || (number.is_empty() && (c == '-' || c == '+')) {
+            number.push(c);
+            continue;
+        }
+
+        if !number.is_empty() {
+            tokens.push(Token::Number(number.parse().ok()?));
+            number.clear();
+        }
+
+        match c.to_ascii_uppercase() {
+            hemisphere @ ('N' | 'S' | 'E' | 'W') => tokens.push(Token::Hemisphere(hemisphere)),
+            ',' | ';' => tokens.push(Token::Break),
+            '°' | 'º' | '\'' | '"' | '′' | '″' => {}
+            c if c.is_whitespace() => {}
+            _ => return None,
+        }
+    }
+
+    let mut angles = Vec::new();
+    let mut current = Angle::default();
+    for token in tokens {
+        match token {
+            Token::Number(value) => current.values.push(value),
+            // Hemisphere letter before the numbers, e.g. `N 50 E 14`.
+            Token::Hemisphere(hemisphere) if current.values.is_empty() => {
+                if current.hemisphere.replace(hemisphere).is_some() {
+                    return None;
+                }
+            }
+            Token::Hemisphere(hemisphere) if current.hemisphere.is_some() => {
+                angles.push(mem::take(&mut current));
+                current.hemisphere = Some(hemisphere);
+            }
+            // Hemisphere letter after the numbers, e.g. `50N 14E`.
+            Token::Hemisphere(hemisphere) => {
+                current.hemisphere = Some(hemisphere);
+                angles.push(mem::take(&mut current));
+            }
+            // A separator after a hemisphere letter, e.g. `50N, 14E`.
+            Token::Break if current.values.is_empty() => {
+                if current.hemisphere.is_some() {
+                    return None;
+                }
+            }
+            Token::Break => angles.push(mem::take(&mut current)),
+        }
+    }
+
+    if !current.values.is_empty() {
+        angles.push(current);
+    } else if current.hemisphere.is_some() {
+        return None;
+    }
+
+    // Without separators, the numbers are split evenly between latitude and longitude, e.g. `50 05 14 25`.
+    if let [angle] = angles.as_mut_slice() {
+        if angle.hemisphere.is_some() || angle.values.len() % 2 != 0 {
+            return None;
+        }
+
+        let second = angle.values.split_off(angle.values.len() / 2);
+        angles.push(Angle {
+            values: second,
+            hemisphere: None,
+        });
+    }
+
+    let [first, second] = <[Angle; 2]>::try_from(angles).ok()?;
+    let (lat, lon) = match (first.hemisphere, second.hemisphere) {
+        (Some('E' | 'W'), _) | (_, Some('N' | 'S')) => (second, first),
+        _ => (first, second),
+    };
+
+    if matches!(lat.hemisphere, Some('E' | 'W')) || matches!(lon.hemisphere, Some('N' | 'S')) {
+        return None;
+    }
+
+    let (lat, lon) = (lat.value()?, lon.value()?);
+    if lat.abs() > 90.0 || lon.abs() > 180.0 {
+        return None;
+    }
+
+    Some(GeoPoint2d::latlon(lat, lon))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_parsed(input: &str, lat: f64, lon: f64) {
+        let point = parse_coordinate(input).unwrap_or_else(|err| panic!("{input}: {err}"));
+        assert!((point.lat() - lat).abs() < 1e-4, "{input}: {point:?}");
+        assert!((point.lon() - lon).abs() < 1e-4, "{input}: {point:?}");
+    }
+
+    #[test]
+    fn format_coordinates() {
+        let prague = GeoPoint2d::latlon(50.087, 14.4208);
+        let format = |format: CoordinateFormat| format.format(&prague).unwrap();
+
+        assert_eq!(
+            format(CoordinateFormat::DecimalDegrees { precision: 4 }),
+            "50.0870, 14.4208"
+        );
+        assert_eq!(
+            format(CoordinateFormat::DegreesMinutesSeconds { precision: 1 }),
+            "50°05'13.2\"N 14°25'14.9\"E"
+        );
+        assert_eq!(
+            format(CoordinateFormat::DegreesDecimalMinutes { precision: 3 }),
+            "50°05.220'N 14°25.248'E"
+        );
+        assert_eq!(format(CoordinateFormat::Mgrs { digits: 1 }), "33U VR 5 4");
+        assert_eq!(
+            CoordinateFormat::DegreesMinutesSeconds { precision: 0 }
+                .format(&GeoPoint2d::latlon(-10.9999999, -0.5))
+                .unwrap(),
+            "11°00'00\"S 0°30'00\"W"
+        );
+        assert_eq!(
+            CoordinateFormat::Utm.format(&GeoPoint2d::latlon(85.0, 0.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_coordinates() {
+        assert_parsed("50.087, 14.4208", 50.087, 14.4208);
+        assert_parsed("-33.8688 151.2093", -33.8688, 151.2093);
+        assert_parsed("50°05'13.2\"N 14°25'14.9\"E", 50.087, 14.4208);
+        assert_parsed("14°25.248'E, 50°05.220'N", 50.087, 14.4208);
+        assert_parsed("N 50 05.22 E 14 25.248", 50.087, 14.4208);
+        assert_parsed("33.8688S 151.2093E", -33.8688, 151.2093);
+        assert_parsed("50 05 13.2 14 25 14.9", 50.087, 14.4208);
+        assert_parsed("31N 166021 0", 0.0, 0.0);
+        assert_parsed("31N AA 66021 00000", 0.0, 0.0);
+        assert_parsed("8FVC2222+22", 47.0000625, 8.0000625);
+
+        for invalid in [
+            "",
+            "abc",
+            "95, 10",
+            "50 14 10",
+            "50N 14N",
+            "-50S 14E",
+            "50 61 14 0",
+        ] {
+            assert!(parse_coordinate(invalid).is_err(), "{invalid}");
+        }
+    }
+}
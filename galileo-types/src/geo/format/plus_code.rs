@@ -0,0 +1,186 @@
+use crate::error::GalileoTypesError;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::traits::point::{GeoPoint, NewGeoPoint};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+const ALPHABET: &[u8; 20] = b"23456789CFGHJMPQRVWX";
+const SEPARATOR: char = '+';
+const SEPARATOR_POSITION: usize = 8;
+const PADDING: char = '0';
+const PAIR_CODE_LENGTH: usize = 10;
+const MAX_CODE_LENGTH: usize = 15;
+const GRID_ROWS: i64 = 5;
+const GRID_COLUMNS: i64 = 4;
+
+/// Number of steps of the most precise code per degree of latitude (`8000 * 5^5`) and longitude (`8000 * 4^5`).
+const LAT_PRECISION: f64 = 25_000_000.0;
+const LON_PRECISION: f64 = 8_192_000.0;
+/// Place values of the first pair of digits in the steps of the most precise code.
+const FIRST_LAT_PLACE: i64 = 500_000_000;
+const FIRST_LON_PLACE: i64 = 163_840_000;
+
+/// Encodes the point as an [Open Location Code](https://maps.google.com/pluscodes/) (plus code) with the given number
+/// of digits.
+///
+/// Valid lengths are 2, 4, 6, 8 and 10 to 15 digits, other values are rounded up to the nearest valid length (but no
+/// more than 15). The default length of plus codes is 10 digits, which gives the precision of about 14 meters.
+///
+/// ```
+/// use galileo_types::geo::format::encode_plus_code;
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::NewGeoPoint;
+///
+/// let code = encode_plus_code(&GeoPoint2d::latlon(47.0000625, 8.0000625), 10);
+/// assert_eq!(code, "8FVC2222+22");
+/// ```
+pub fn encode_plus_code(point: &impl GeoPoint<Num = f64>, length: usize) -> String {
+    let length = match length {
+        length if length < PAIR_CODE_LENGTH => length.max(2).div_ceil(2) * 2,
+        length => length.min(MAX_CODE_LENGTH),
+    };
+
+    // Round to avoid floating point errors putting the value just below the edge of a cell.
+    let steps = |value: f64, precision: f64| ((value * precision * 1e6).round() / 1e6).floor();
+    let lat = point.lat().clamp(-90.0, 90.0) + 90.0;
+    let mut lat_value = (steps(lat, LAT_PRECISION) as i64).min(180 * LAT_PRECISION as i64 - 1);
+    let lon = (point.lon() + 180.0) % 360.0;
+    let lon = if lon < 0.0 { lon + 360.0 } else { lon };
+    let mut lon_value = (steps(lon, LON_PRECISION) as i64) % (360 * LON_PRECISION as i64);
+
+    let mut digits = Vec::with_capacity(MAX_CODE_LENGTH);
+    for _ in PAIR_CODE_LENGTH..MAX_CODE_LENGTH {
+        let index = (lat_value % GRID_ROWS) * GRID_COLUMNS + lon_value % GRID_COLUMNS;
+        digits.push(ALPHABET[index as usize]);
+        lat_value /= GRID_ROWS;
+        lon_value /= GRID_COLUMNS;
+    }
+
+    for _ in 0..PAIR_CODE_LENGTH / 2 {
+        digits.push(ALPHABET[(lon_value % 20) as usize]);
+        digits.push(ALPHABET[(lat_value % 20) as usize]);
+        lat_value /= 20;
+        lon_value /= 20;
+    }
+
+    digits.reverse();
+
+    let mut code = String::with_capacity(MAX_CODE_LENGTH + 1);
+    for (index, &digit) in digits
+        .iter()
+        .enumerate()
+        .take(SEPARATOR_POSITION.max(length))
+    {
+        if index == SEPARATOR_POSITION {
+            code.push(SEPARATOR);
+        }
+
+        code.push(if index < length {
+            digit as char
+        } else {
+            PADDING
+        });
+    }
+
+    if length <= SEPARATOR_POSITION {
+        code.push(SEPARATOR);
+    }
+
+    code
+}
+
+/// Decodes a full [Open Location Code](https://maps.google.com/pluscodes/) (plus code) into the center of its area.
+///
+/// Short codes (e.g. `CWC8+R9`), which are relative to a reference location, are not supported.
+pub fn decode_plus_code(code: &str) -> Result<GeoPoint2d, GalileoTypesError> {
+    let error = || GalileoTypesError::Parse(format!("invalid plus code: {code}"));
+    let code = code.trim().to_ascii_uppercase();
+
+    let separator = code.find(SEPARATOR).ok_or_else(error)?;
+    let (head, tail) = (&code[..separator], &code[separator + 1..]);
+    if separator != SEPARATOR_POSITION || tail.contains(SEPARATOR) || tail.len() == 1 {
+        return Err(error());
+    }
+
+    let digits_end = head.find(PADDING).unwrap_or(head.len());
+    let padding = &head[digits_end..];
+    if digits_end < 2
+        || digits_end % 2 != 0
+        || !padding.chars().all(|c| c == PADDING)
+        || (!padding.is_empty() && !tail.is_empty())
+    {
+        return Err(error());
+    }
+
+    let digits = head[..digits_end]
+        .bytes()
+        .chain(tail.bytes())
+        .take(MAX_CODE_LENGTH)
+        .map(|c| ALPHABET.iter().position(|&a| a == c).map(|v| v as i64))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(error)?;
+
+    if digits[0] >= 9 || digits[1] >= 18 {
+        return Err(error());
+    }
+
+    let (mut lat_value, mut lon_value) = (0, 0);
+    let (mut lat_place, mut lon_place) = (FIRST_LAT_PLACE * 20, FIRST_LON_PLACE * 20);
+    for pair in digits[..digits.len().min(PAIR_CODE_LENGTH)].chunks(2) {
+        lat_place /= 20;
+        lon_place /= 20;
+        lat_value += pair[0] * lat_place;
+        lon_value += pair[1] * lon_place;
+    }
+
+    for &digit in digits.iter().skip(PAIR_CODE_LENGTH) {
+        lat_place /= GRID_ROWS;
+        lon_place /= GRID_COLUMNS;
+        lat_value += digit / GRID_COLUMNS * lat_place;
+        lon_value += digit % GRID_COLUMNS * lon_place;
+    }
+
+    let center =
+        |value: i64, place: i64, precision: f64| (value as f64 + place as f64 / 2.0) / precision;
+    Ok(GeoPoint2d::latlon(
+        center(lat_value, lat_place, LAT_PRECISION) - 90.0,
+        center(lon_value, lon_place, LON_PRECISION) - 180.0,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plus_code_round_trip() {
+        let point = GeoPoint2d::latlon(47.0000625, 8.0000625);
+        assert_eq!(encode_plus_code(&point, 10), "8FVC2222+22");
+        assert_eq!(encode_plus_code(&point, 4), "8FVC0000+");
+        assert_eq!(encode_plus_code(&point, 11), "8FVC2222+22G");
+
+        let decoded = decode_plus_code("8fvc2222+22").unwrap();
+        assert!((decoded.lat() - 47.0000625).abs() < 1e-9);
+        assert!((decoded.lon() - 8.0000625).abs() < 1e-9);
+
+        let area = decode_plus_code("8FVC0000+").unwrap();
+        assert!((area.lat() - 47.5).abs() < 1e-9);
+        assert!((area.lon() - 8.5).abs() < 1e-9);
+
+        for (lat, lon) in [(-33.8688, 151.2093), (90.0, 180.0), (-90.0, -180.0)] {
+            let code = encode_plus_code(&GeoPoint2d::latlon(lat, lon), 15);
+            let decoded = decode_plus_code(&code).unwrap();
+            assert!((decoded.lat() - lat).abs() < 1e-6, "{code}");
+            let lon_diff = (decoded.lon() - lon).abs();
+            assert!(lon_diff < 1e-6 || (lon_diff - 360.0).abs() < 1e-6, "{code}");
+        }
+
+        assert!(decode_plus_code("8FVC2222").is_err());
+        assert!(decode_plus_code("8FVC0022+").is_err());
+        assert!(decode_plus_code("8FVC0000+22").is_err());
+        assert!(decode_plus_code("XFVC2222+22").is_err());
+    }
+}
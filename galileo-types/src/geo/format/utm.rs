@@ -0,0 +1,308 @@
+use crate::error::GalileoTypesError;
+use crate::geo::datum::Datum;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::traits::point::{GeoPoint, NewGeoPoint};
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Scale factor at the central meridian of a UTM zone.
+const SCALE_FACTOR: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500_000.0;
+const FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+
+/// Southern and northern limits of the UTM system (and of the latitude bands of MGRS).
+pub(super) const MIN_LATITUDE: f64 = -80.0;
+pub(super) const MAX_LATITUDE: f64 = 84.0;
+
+/// Letters of the 8° latitude bands from 80°S. The last band (`X`) is 12° high.
+pub(super) const BAND_LETTERS: &[u8; 20] = b"CDEFGHJKLMNPQRSTUVWX";
+
+/// Position in the Universal Transverse Mercator coordinate system on the WGS84 ellipsoid.
+///
+/// ```
+/// use galileo_types::geo::format::UtmCoordinate;
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::NewGeoPoint;
+///
+/// let utm = UtmCoordinate::from_geo(&GeoPoint2d::latlon(0.0, 3.0)).expect("inside UTM");
+/// assert_eq!(utm.to_string(), "31N 500000 0");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UtmCoordinate {
+    /// Zone number from 1 to 60.
+    pub zone: u8,
+    /// Whether the position is in the northern hemisphere. Northings in the southern hemisphere are measured from the
+    /// false origin 10 000 km south of the equator.
+    pub northern: bool,
+    /// Easting in meters.
+    pub easting: f64,
+    /// Northing in meters.
+    pub northing: f64,
+}
+
+impl UtmCoordinate {
+    /// Converts the point into its UTM zone, taking into account the exceptions of the zones around Norway and
+    /// Svalbard. Returns `None` for the points outside the UTM latitude range (80°S to 84°N).
+    pub fn from_geo(point: &impl GeoPoint<Num = f64>) -> Option<Self> {
+        Self::from_geo_in_zone(point, zone_of(point.lat(), point.lon())?)
+    }
+
+    /// Converts the point into the given UTM zone, even if the point is outside of it. Returns `None` if the zone
+    /// number is not valid or the point is too far from the zone for the projection to be defined.
+    pub fn from_geo_in_zone(point: &impl GeoPoint<Num = f64>, zone: u8) -> Option<Self> {
+        if !(1..=60).contains(&zone) {
+            return None;
+        }
+
+        let series = Series::wgs84();
+        let lat = point.lat_rad();
+        let lon = normalize_longitude(point.lon() - central_meridian(zone)).to_radians();
+
+        let t = (lat.sin().atanh() - series.e * (series.e * lat.sin()).atanh()).sinh();
+        let xi = t.atan2(lon.cos());
+        let eta = (lon.sin() / (1.0 + t * t).sqrt()).atanh();
+
+        let mut easting = eta;
+        let mut northing = xi;
+        for (j, alpha) in series.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            easting += alpha * (k * xi).cos() * (k * eta).sinh();
+            northing += alpha * (k * xi).sin() * (k * eta).cosh();
+        }
+
+        let scale = SCALE_FACTOR * series.a;
+        let northern = point.lat() >= 0.0;
+        let easting = FALSE_EASTING + scale * easting;
+        let northing = scale * northing + if northern { 0.0 } else { FALSE_NORTHING_SOUTH };
+
+        (easting.is_finite() && northing.is_finite()).then_some(Self {
+            zone,
+            northern,
+            easting,
+            northing,
+        })
+    }
+
+    /// Converts the coordinate into a geographic point. Returns `None` if the zone number is not valid or the
+    /// coordinates are not finite.
+    pub fn to_geo(&self) -> Option<GeoPoint2d> {
+        if !(1..=60).contains(&self.zone) || !self.easting.is_finite() || !self.northing.is_finite()
+        {
+            return None;
+        }
+
+        let series = Series::wgs84();
+        let scale = SCALE_FACTOR * series.a;
+        let northing = if self.northern {
+            self.northing
+        } else {
+            self.northing - FALSE_NORTHING_SOUTH
+        };
+        let xi = northing / scale;
+        let eta = (self.easting - FALSE_EASTING) / scale;
+
+        let mut xi_prime = xi;
+        let mut eta_prime = eta;
+        for (j, beta) in series.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_prime -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_prime -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+
+        let chi = (xi_prime.sin() / eta_prime.cosh()).asin();
+        let mut lat = chi;
+        for (j, delta) in series.delta.iter().enumerate() {
+            lat += delta * (2.0 * (j + 1) as f64 * chi).sin();
+        }
+
+        let lon = eta_prime.sinh().atan2(xi_prime.cos());
+        let lon = normalize_longitude(central_meridian(self.zone) + lon.to_degrees());
+
+        Some(GeoPoint2d::latlon(lat.to_degrees(), lon))
+    }
+
+    /// Latitude band letter of the point, used by MGRS and commonly written after the zone number instead of the
+    /// hemisphere. Returns `None` for the points outside the UTM latitude range.
+    pub fn latitude_band(lat: f64) -> Option<char> {
+        if !(MIN_LATITUDE..=MAX_LATITUDE).contains(&lat) {
+            return None;
+        }
+
+        let index = (((lat - MIN_LATITUDE) / 8.0).floor() as usize).min(BAND_LETTERS.len() - 1);
+        Some(BAND_LETTERS[index] as char)
+    }
+}
+
+impl Display for UtmCoordinate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let hemisphere = if self.northern { 'N' } else { 'S' };
+        write!(
+            f,
+            "{}{hemisphere} {:.0} {:.0}",
+            self.zone,
+            self.easting.floor(),
+            self.northing.floor()
+        )
+    }
+}
+
+impl FromStr for UtmCoordinate {
+    type Err = GalileoTypesError;
+
+    /// Parses the coordinate written as zone number with a hemisphere (`N` or `S`) or latitude band letter, followed by
+    /// easting and northing in meters, e.g. `33N 457025 5546512` or `33U 457025 5546512`. Note that the letters `N`
+    /// and `S` are interpreted as hemispheres, not as latitude bands.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || GalileoTypesError::Parse(format!("invalid UTM coordinate: {s}"));
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let (zone, easting, northing) = match parts.as_slice() {
+            [zone, easting, northing] => (zone.to_string(), *easting, *northing),
+            [zone, letter, easting, northing] => (format!("{zone}{letter}"), *easting, *northing),
+            _ => return Err(error()),
+        };
+
+        let letter = zone
+            .chars()
+            .last()
+            .filter(|c| c.is_ascii_alphabetic())
+            .ok_or_else(error)?
+            .to_ascii_uppercase();
+        let zone: u8 = zone[..zone.len() - 1].parse().map_err(|_| error())?;
+        let northern = match letter {
+            'N' => true,
+            'S' => false,
+            band if BAND_LETTERS.contains(&(band as u8)) => band > 'M',
+            _ => return Err(error()),
+        };
+
+        let utm = Self {
+            zone,
+            northern,
+            easting: easting.parse().map_err(|_| error())?,
+            northing: northing.parse().map_err(|_| error())?,
+        };
+
+        match utm.to_geo() {
+            Some(_) => Ok(utm),
+            None => Err(error()),
+        }
+    }
+}
+
+/// Longitude of the central meridian of the zone.
+pub(super) fn central_meridian(zone: u8) -> f64 {
+    zone as f64 * 6.0 - 183.0
+}
+
+/// UTM zone of the point, including the exceptions for Norway and Svalbard.
+pub(super) fn zone_of(lat: f64, lon: f64) -> Option<u8> {
+    if !(MIN_LATITUDE..=MAX_LATITUDE).contains(&lat) || !lon.is_finite() {
+        return None;
+    }
+
+    let lon = normalize_longitude(lon);
+    if (56.0..64.0).contains(&lat) && (3.0..12.0).contains(&lon) {
+        return Some(32);
+    }
+
+    if lat >= 72.0 && (0.0..42.0).contains(&lon) {
+        return Some(match lon {
+            lon if lon < 9.0 => 31,
+            lon if lon < 21.0 => 33,
+            lon if lon < 33.0 => 35,
+            _ => 37,
+        });
+    }
+
+    Some((((lon + 180.0) / 6.0).floor() as u8).min(59) + 1)
+}
+
+/// Brings the longitude into the `[-180, 180)` range.
+pub(super) fn normalize_longitude(lon: f64) -> f64 {
+    let lon = (lon + 180.0) % 360.0;
+    if lon < 0.0 {
+        lon + 180.0
+    } else {
+        lon - 180.0
+    }
+}
+
+/// Coefficients of the Krüger series of the transverse Mercator projection.
+struct Series {
+    a: f64,
+    e: f64,
+    alpha: [f64; 3],
+    beta: [f64; 3],
+    delta: [f64; 3],
+}
+
+impl Series {
+    fn wgs84() -> Self {
+        let datum = Datum::WGS84;
+        let f = 1.0 / datum.inv_flattening();
+        let n = f / (2.0 - f);
+        let (n2, n3) = (n * n, n * n * n);
+
+        Self {
+            a: datum.semimajor() / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0),
+            e: (f * (2.0 - f)).sqrt(),
+            alpha: [
+                n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0,
+                13.0 * n2 / 48.0 - 3.0 * n3 / 5.0,
+                61.0 * n3 / 240.0,
+            ],
+            beta: [
+                n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0,
+                n2 / 48.0 + n3 / 15.0,
+                17.0 * n3 / 480.0,
+            ],
+            delta: [
+                2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3,
+                7.0 * n2 / 3.0 - 8.0 * n3 / 5.0,
+                56.0 * n3 / 15.0,
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utm_round_trip() {
+        let origin = UtmCoordinate::from_geo(&GeoPoint2d::latlon(0.0, 0.0)).unwrap();
+        assert_eq!(origin.zone, 31);
+        assert!((origin.easting - 166_021.443).abs() < 1e-3);
+        assert!(origin.northing.abs() < 1e-6);
+
+        for (lat, lon) in [
+            (50.0870, 14.4208),
+            (-33.8688, 151.2093),
+            (60.0, 5.0),
+            (78.2, 15.6),
+        ] {
+            let utm = UtmCoordinate::from_geo(&GeoPoint2d::latlon(lat, lon)).unwrap();
+            let point = utm.to_geo().unwrap();
+            assert!((point.lat() - lat).abs() < 1e-8, "{utm}");
+            assert!((point.lon() - lon).abs() < 1e-8, "{utm}");
+        }
+
+        // Norway and Svalbard exceptions.
+        assert_eq!(zone_of(60.0, 5.0), Some(32));
+        assert_eq!(zone_of(78.2, 15.6), Some(33));
+        assert_eq!(zone_of(85.0, 0.0), None);
+        assert_eq!(zone_of(0.0, 180.0), Some(1));
+
+        let parsed: UtmCoordinate = "33U 457025 5546512".parse().unwrap();
+        assert_eq!((parsed.zone, parsed.northern), (33, true));
+        assert!("61N 500000 0".parse::<UtmCoordinate>().is_err());
+        assert_eq!(UtmCoordinate::latitude_band(50.0), Some('U'));
+        assert_eq!(UtmCoordinate::latitude_band(83.0), Some('X'));
+    }
+}
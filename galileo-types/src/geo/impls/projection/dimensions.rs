@@ -1,6 +1,6 @@
 use crate::cartesian::{NewCartesianPoint2d, NewCartesianPoint3d};
 use crate::geo::traits::projection::Projection;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// Projection that adds a default z-value to a 2d point. Reversed projecting drops the z-value.
 pub struct AddDimensionProjection<Num, In, Out> {
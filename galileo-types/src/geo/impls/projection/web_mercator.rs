@@ -2,7 +2,9 @@ use crate::cartesian::NewCartesianPoint2d;
 use crate::geo::datum::Datum;
 use crate::geo::traits::point::NewGeoPoint;
 use crate::geo::traits::projection::Projection;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 /// Web Mercator projection.
 #[derive(Debug, Copy, Clone)]
@@ -40,7 +42,7 @@ impl<In: NewGeoPoint<f64>, Out: NewCartesianPoint2d<f64>> Projection for WebMerc
     fn project(&self, input: &Self::InPoint) -> Option<Self::OutPoint> {
         let x = self.datum.semimajor() * input.lon_rad();
         let y = self.datum.semimajor()
-            * (std::f64::consts::FRAC_PI_4 + input.lat_rad() / 2.0)
+            * (core::f64::consts::FRAC_PI_4 + input.lat_rad() / 2.0)
                 .tan()
                 .ln();
 
@@ -52,7 +54,7 @@ impl<In: NewGeoPoint<f64>, Out: NewCartesianPoint2d<f64>> Projection for WebMerc
     }
 
     fn unproject(&self, input: &Self::OutPoint) -> Option<Self::InPoint> {
-        let lat = std::f64::consts::FRAC_PI_2
+        let lat = core::f64::consts::FRAC_PI_2
             - 2.0 * (-(*input).y() / self.datum.semimajor()).exp().atan();
         let lon = input.x() / self.datum.semimajor();
 
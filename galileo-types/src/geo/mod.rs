@@ -1,12 +1,23 @@
 //! Geometries in geographic coordinates (latitude and longitude) (see [`GeoPoint`]) and conversion between different geographic
 //! coordinate systems (see [`Projection`]).
 
+mod buffer;
+mod circle;
 mod crs;
 mod datum;
+pub mod format;
 pub mod impls;
+mod navigation;
 mod traits;
+mod units;
 
+pub use buffer::buffer;
+pub use circle::GeoCircle;
 pub use crs::{Crs, ProjectionType};
 pub use datum::Datum;
+pub use navigation::PathType;
 pub use traits::point::{GeoPoint, NewGeoPoint};
 pub use traits::projection::{ChainProjection, InvertedProjection, Projection};
+pub use units::{
+    LengthUnit, SpeedUnit, METERS_PER_FOOT, METERS_PER_NAUTICAL_MILE, METERS_PER_STATUTE_MILE,
+};
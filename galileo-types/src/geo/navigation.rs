@@ -0,0 +1,342 @@
+use crate::geo::datum::Datum;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::traits::point::{GeoPoint, NewGeoPoint};
+use alloc::vec::Vec;
+use core::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+/// Difference in isometric latitude below which a rhumb line is considered to run along a parallel.
+const PARALLEL_EPSILON: f64 = 1e-12;
+
+/// Type of the path between two points on the surface of the Earth.
+///
+/// All calculations are done on a sphere with the [mean radius](Datum::mean_radius) of the WGS84 ellipsoid, which gives
+/// errors of up to 0.5% compared to ellipsoidal calculations. Distances are in meters, bearings are in degrees
+/// clockwise from the true north in the range `[0, 360)`.
+///
+/// ```
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::{LengthUnit, NewGeoPoint, PathType};
+///
+/// let lisbon = GeoPoint2d::latlon(38.7, -9.4);
+/// let new_york = GeoPoint2d::latlon(40.7, -74.0);
+///
+/// let great_circle = PathType::GreatCircle.distance(&lisbon, &new_york);
+/// let rhumb = PathType::Rhumb.distance(&lisbon, &new_york);
+/// assert!(great_circle < rhumb);
+///
+/// let course = PathType::Rhumb.initial_bearing(&lisbon, &new_york);
+/// assert!((course - 272.3).abs() < 0.1);
+/// assert!((LengthUnit::NauticalMiles.from_meters(rhumb) - 2986.0).abs() < 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PathType {
+    /// The shortest path between the points (orthodrome). The bearing changes along the path.
+    #[default]
+    GreatCircle,
+    /// Path with a constant bearing (loxodrome), which is a straight line in the Mercator projection.
+    Rhumb,
+}
+
+impl PathType {
+    /// Distance between the points along the path in meters.
+    pub fn distance(&self, from: &impl GeoPoint<Num = f64>, to: &impl GeoPoint<Num = f64>) -> f64 {
+        let (lat1, lat2) = (from.lat_rad(), to.lat_rad());
+        let d_lat = lat2 - lat1;
+        let d_lon = lon_difference(from, to);
+
+        let angle = match self {
+            PathType::GreatCircle => {
+                let a = (d_lat / 2.0).sin().powi(2)
+                    + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+                2.0 * a.sqrt().min(1.0).asin()
+            }
+            PathType::Rhumb => {
+                let q = stretch_factor(lat1, lat2);
+                (d_lat * d_lat + q * q * d_lon * d_lon).sqrt()
+            }
+        };
+
+        angle * radius()
+    }
+
+    /// Bearing at the start of the path in degrees. For rhumb lines the bearing is the same along the whole path.
+    pub fn initial_bearing(
+        &self,
+        from: &impl GeoPoint<Num = f64>,
+        to: &impl GeoPoint<Num = f64>,
+    ) -> f64 {
+        let (lat1, lat2) = (from.lat_rad(), to.lat_rad());
+        let d_lon = lon_difference(from, to);
+
+        let bearing = match self {
+            PathType::GreatCircle => (d_lon.sin() * lat2.cos())
+                .atan2(lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lon.cos()),
+            PathType::Rhumb => d_lon.atan2(isometric_latitude(lat2) - isometric_latitude(lat1)),
+        };
+
+        modulo(bearing.to_degrees(), 360.0)
+    }
+
+    /// Point reached by travelling the given `distance` (in meters) from the `from` point with the initial `bearing`
+    /// (in degrees).
+    pub fn destination(
+        &self,
+        from: &impl GeoPoint<Num = f64>,
+        bearing: f64,
+        distance: f64,
+    ) -> GeoPoint2d {
+        let angle = distance / radius();
+        let bearing = bearing.to_radians();
+        let (lat1, lon1) = (from.lat_rad(), from.lon_rad());
+
+        let (lat2, lon2) = match self {
+            PathType::GreatCircle => {
+                let lat2 = (lat1.sin() * angle.cos() + lat1.cos() * angle.sin() * bearing.cos())
+                    .clamp(-1.0, 1.0)
+                    .asin();
+                let lon2 = lon1
+                    + (bearing.sin() * angle.sin() * lat1.cos())
+                        .atan2(angle.cos() - lat1.sin() * lat2.sin());
+                (lat2, lon2)
+            }
+            PathType::Rhumb => {
+                let lat2 = (lat1 + angle * bearing.cos()).clamp(-FRAC_PI_2, FRAC_PI_2);
+                let q = stretch_factor(lat1, lat2);
+                let lon2 = if q.abs() > PARALLEL_EPSILON {
+                    lon1 + angle * bearing.sin() / q
+                } else {
+                    lon1
+                };
+                (lat2, lon2)
+            }
+        };
+
+        GeoPoint2d::latlon(lat2.to_degrees(), normalize_lon(lon2.to_degrees()))
+    }
+
+    /// Point at the given `fraction` of the path from `from` (`0.0`) to `to` (`1.0`).
+    pub fn interpolate(
+        &self,
+        from: &impl GeoPoint<Num = f64>,
+        to: &impl GeoPoint<Num = f64>,
+        fraction: f64,
+    ) -> GeoPoint2d {
+        match self {
+            PathType::GreatCircle => {
+                let angle = self.distance(from, to) / radius();
+                if angle < PARALLEL_EPSILON {
+                    return GeoPoint2d::from(from);
+                }
+
+                let a = ((1.0 - fraction) * angle).sin() / angle.sin();
+                let b = (fraction * angle).sin() / angle.sin();
+                let [x1, y1, z1] = to_unit_vector(from);
+                let [x2, y2, z2] = to_unit_vector(to);
+                let (x, y, z) = (a * x1 + b * x2, a * y1 + b * y2, a * z1 + b * z2);
+
+                GeoPoint2d::latlon(
+                    z.atan2((x * x + y * y).sqrt()).to_degrees(),
+                    y.atan2(x).to_degrees(),
+                )
+            }
+            PathType::Rhumb => self.destination(
+                from,
+                self.initial_bearing(from, to),
+                self.distance(from, to) * fraction,
+            ),
+        }
+    }
+
+    /// Inserts intermediate points into the line so that no segment is longer than `max_segment_length` meters.
+    ///
+    /// The resulting line follows the path of this type between the original points when drawn in any projection.
+    /// This is needed for example to show rhumb lines on a chart in a projection other than Mercator, or great circle
+    /// routes on a Mercator chart. The original points are kept in the output.
+    pub fn densify<P: GeoPoint<Num = f64>>(
+        &self,
+        points: impl IntoIterator<Item = P>,
+        max_segment_length: f64,
+    ) -> Vec<GeoPoint2d> {
+        let mut result = Vec::new();
+        let mut prev: Option<GeoPoint2d> = None;
+        for point in points {
+            let point = GeoPoint2d::from(&point);
+            if let Some(prev) = prev {
+                let distance = self.distance(&prev, &point);
+                if max_segment_length > 0.0 && distance > max_segment_length {
+                    let count = (distance / max_segment_length).ceil() as usize;
+                    for i in 1..count {
+                        result.push(self.interpolate(&prev, &point, i as f64 / count as f64));
+                    }
+                }
+            }
+
+            result.push(point);
+            prev = Some(point);
+        }
+
+        result
+    }
+}
+
+fn radius() -> f64 {
+    Datum::WGS84.mean_radius()
+}
+
+/// Longitude difference in radians along the shorter way around the globe.
+fn lon_difference(from: &impl GeoPoint<Num = f64>, to: &impl GeoPoint<Num = f64>) -> f64 {
+    let d_lon = to.lon_rad() - from.lon_rad();
+    modulo(d_lon + PI, 2.0 * PI) - PI
+}
+
+fn normalize_lon(lon: f64) -> f64 {
+    modulo(lon + 180.0, 360.0) - 180.0
+}
+
+/// Non-negative remainder of the division (`f64::rem_euclid` is not available without `std`).
+fn modulo(value: f64, divisor: f64) -> f64 {
+    let remainder = value % divisor;
+    if remainder < 0.0 {
+        remainder + divisor
+    } else {
+        remainder
+    }
+}
+
+/// Isometric latitude of the sphere (the `y` coordinate of the Mercator projection).
+fn isometric_latitude(lat: f64) -> f64 {
+    (FRAC_PI_4 + lat / 2.0).tan().ln()
+}
+
+/// Ratio of the latitude difference to the isometric latitude difference of a rhumb line between the latitudes.
+/// Along a parallel it is the cosine of the latitude.
+fn stretch_factor(lat1: f64, lat2: f64) -> f64 {
+    let d_psi = isometric_latitude(lat2) - isometric_latitude(lat1);
+    if d_psi.abs() > PARALLEL_EPSILON {
+        (lat2 - lat1) / d_psi
+    } else {
+        lat1.cos()
+    }
+}
+
+fn to_unit_vector(point: &impl GeoPoint<Num = f64>) -> [f64; 3] {
+    let (lat, lon) = (point.lat_rad(), point.lon_rad());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::units::LengthUnit;
+
+    fn assert_close(a: f64, b: f64, tolerance: f64) {
+        assert!((a - b).abs() < tolerance, "{a} != {b}");
+    }
+
+    #[test]
+    fn great_circle_distance_and_bearing() {
+        let from = GeoPoint2d::latlon(50.0, -5.0);
+        let to = GeoPoint2d::latlon(58.0, -3.0);
+
+        assert_close(
+            PathType::GreatCircle.distance(&from, &to),
+            899_000.0,
+            1000.0,
+        );
+        assert_close(
+            PathType::GreatCircle.initial_bearing(&from, &to),
+            7.556,
+            0.001,
+        );
+
+        let equator = GeoPoint2d::latlon(0.0, 0.0);
+        let east = GeoPoint2d::latlon(0.0, 1.0);
+        assert_close(
+            LengthUnit::NauticalMiles.from_meters(PathType::GreatCircle.distance(&equator, &east)),
+            60.0,
+            0.1,
+        );
+        assert_close(
+            PathType::GreatCircle.initial_bearing(&east, &equator),
+            270.0,
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn rhumb_distance_and_bearing() {
+        let from = GeoPoint2d::latlon(51.127, 1.338);
+        let to = GeoPoint2d::latlon(50.964, 1.853);
+
+        assert_close(PathType::Rhumb.distance(&from, &to), 40_310.0, 50.0);
+        assert_close(PathType::Rhumb.initial_bearing(&from, &to), 116.72, 0.01);
+
+        assert_close(PathType::Rhumb.distance(&from, &from), 0.0, 1e-9);
+        assert_close(
+            PathType::Rhumb.distance(
+                &GeoPoint2d::latlon(60.0, 0.0),
+                &GeoPoint2d::latlon(60.0, 10.0),
+            ),
+            PathType::GreatCircle.distance(
+                &GeoPoint2d::latlon(0.0, 0.0),
+                &GeoPoint2d::latlon(0.0, 10.0),
+            ) / 2.0,
+            1.0,
+        );
+    }
+
+    #[test]
+    fn paths_cross_antimeridian() {
+        let from = GeoPoint2d::latlon(10.0, 179.5);
+        let to = GeoPoint2d::latlon(10.0, -179.5);
+
+        for path in [PathType::GreatCircle, PathType::Rhumb] {
+            assert!(path.distance(&from, &to) < 120_000.0);
+            let middle = path.interpolate(&from, &to, 0.5);
+            assert_close(middle.lon().abs(), 180.0, 1e-6);
+        }
+    }
+
+    #[test]
+    fn destination_is_inverse_of_bearing_and_distance() {
+        let from = GeoPoint2d::latlon(-33.9, 18.4);
+        let to = GeoPoint2d::latlon(-34.6, -58.4);
+
+        for path in [PathType::GreatCircle, PathType::Rhumb] {
+            let bearing = path.initial_bearing(&from, &to);
+            let distance = path.distance(&from, &to);
+            let destination = path.destination(&from, bearing, distance);
+            assert_close(destination.lat(), to.lat(), 1e-6);
+            assert_close(destination.lon(), to.lon(), 1e-6);
+        }
+    }
+
+    #[test]
+    fn densify_keeps_constant_rhumb_bearing() {
+        let from = GeoPoint2d::latlon(38.7, -9.4);
+        let to = GeoPoint2d::latlon(40.7, -74.0);
+        let max_length = LengthUnit::NauticalMiles.to_meters(100.0);
+
+        let points = PathType::Rhumb.densify([from, to], max_length);
+        assert_eq!(points.len(), 31);
+        assert_eq!(points[0], from);
+        assert_eq!(points[30], to);
+
+        let bearing = PathType::Rhumb.initial_bearing(&from, &to);
+        for segment in points.windows(2) {
+            assert!(PathType::Rhumb.distance(&segment[0], &segment[1]) <= max_length);
+            assert_close(
+                PathType::Rhumb.initial_bearing(&segment[0], &segment[1]),
+                bearing,
+                1e-6,
+            );
+        }
+
+        let great_circle = PathType::GreatCircle.densify([from, to], max_length);
+        let middle = great_circle[great_circle.len() / 2];
+        assert!(middle.lat() > points[15].lat());
+    }
+}
@@ -1,3 +1,5 @@
+use alloc::boxed::Box;
+
 /// Projections convert points between different coordinate sysytems.
 pub trait Projection {
     /// Point type that will be used as input for projecting.
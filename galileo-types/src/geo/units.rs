@@ -0,0 +1,146 @@
+use core::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// Length of an international nautical mile in meters.
+pub const METERS_PER_NAUTICAL_MILE: f64 = 1852.0;
+/// Length of an international foot in meters.
+pub const METERS_PER_FOOT: f64 = 0.3048;
+/// Length of an international statute mile in meters.
+pub const METERS_PER_STATUTE_MILE: f64 = 1609.344;
+
+/// Unit of length used to display or enter distances, altitudes and depths.
+///
+/// ```
+/// use galileo_types::geo::LengthUnit;
+///
+/// let range = LengthUnit::NauticalMiles.to_meters(12.0);
+/// assert_eq!(range, 22_224.0);
+/// assert_eq!(LengthUnit::Feet.from_meters(3048.0), 10_000.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LengthUnit {
+    /// Meters (SI unit).
+    #[default]
+    Meters,
+    /// Kilometers.
+    Kilometers,
+    /// International nautical miles (1852 m), used in marine and air navigation.
+    NauticalMiles,
+    /// International statute miles.
+    StatuteMiles,
+    /// International feet, used for altitudes in aviation.
+    Feet,
+}
+
+impl LengthUnit {
+    /// Length of one unit in meters.
+    pub fn meters(&self) -> f64 {
+        match self {
+            LengthUnit::Meters => 1.0,
+            LengthUnit::Kilometers => 1000.0,
+            LengthUnit::NauticalMiles => METERS_PER_NAUTICAL_MILE,
+            LengthUnit::StatuteMiles => METERS_PER_STATUTE_MILE,
+            LengthUnit::Feet => METERS_PER_FOOT,
+        }
+    }
+
+    /// Converts the value in these units to meters.
+    pub fn to_meters(&self, value: f64) -> f64 {
+        value * self.meters()
+    }
+
+    /// Converts the value in meters to these units.
+    pub fn from_meters(&self, meters: f64) -> f64 {
+        meters / self.meters()
+    }
+
+    /// Converts the value in these units to the `target` units.
+    pub fn convert(&self, value: f64, target: LengthUnit) -> f64 {
+        target.from_meters(self.to_meters(value))
+    }
+
+    /// Abbreviation of the unit for display.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            LengthUnit::Meters => "m",
+            LengthUnit::Kilometers => "km",
+            LengthUnit::NauticalMiles => "NM",
+            LengthUnit::StatuteMiles => "mi",
+            LengthUnit::Feet => "ft",
+        }
+    }
+}
+
+impl Display for LengthUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Unit of speed.
+///
+/// ```
+/// use galileo_types::geo::SpeedUnit;
+///
+/// let speed = SpeedUnit::Knots.to_meters_per_second(10.0);
+/// assert!((SpeedUnit::KilometersPerHour.from_meters_per_second(speed) - 18.52).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SpeedUnit {
+    /// Meters per second (SI unit).
+    #[default]
+    MetersPerSecond,
+    /// Kilometers per hour.
+    KilometersPerHour,
+    /// Knots (nautical miles per hour).
+    Knots,
+    /// Statute miles per hour.
+    MilesPerHour,
+    /// Feet per minute, used for vertical speed in aviation.
+    FeetPerMinute,
+}
+
+impl SpeedUnit {
+    /// Speed of one unit in meters per second.
+    pub fn meters_per_second(&self) -> f64 {
+        match self {
+            SpeedUnit::MetersPerSecond => 1.0,
+            SpeedUnit::KilometersPerHour => 1000.0 / 3600.0,
+            SpeedUnit::Knots => METERS_PER_NAUTICAL_MILE / 3600.0,
+            SpeedUnit::MilesPerHour => METERS_PER_STATUTE_MILE / 3600.0,
+            SpeedUnit::FeetPerMinute => METERS_PER_FOOT / 60.0,
+        }
+    }
+
+    /// Converts the value in these units to meters per second.
+    pub fn to_meters_per_second(&self, value: f64) -> f64 {
+        value * self.meters_per_second()
+    }
+
+    /// Converts the value in meters per second to these units.
+    pub fn from_meters_per_second(&self, meters_per_second: f64) -> f64 {
+        meters_per_second / self.meters_per_second()
+    }
+
+    /// Converts the value in these units to the `target` units.
+    pub fn convert(&self, value: f64, target: SpeedUnit) -> f64 {
+        target.from_meters_per_second(self.to_meters_per_second(value))
+    }
+
+    /// Abbreviation of the unit for display.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            SpeedUnit::MetersPerSecond => "m/s",
+            SpeedUnit::KilometersPerHour => "km/h",
+            SpeedUnit::Knots => "kn",
+            SpeedUnit::MilesPerHour => "mph",
+            SpeedUnit::FeetPerMinute => "ft/min",
+        }
+    }
+}
+
+impl Display for SpeedUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
@@ -1,5 +1,4 @@
 use crate::geo::Projection;
-use crate::geojson::point::GeoJsonPoint;
 use crate::geometry::{Geom, Geometry};
 use crate::impls::Contour;
 use crate::impls::MultiContour;
@@ -10,6 +9,8 @@ use geojson::{LineStringType, PolygonType, Position, Value};
 
 mod point;
 
+pub use point::GeoJsonPoint;
+
 impl Geometry for geojson::Geometry {
     type Point = GeoJsonPoint;
 
@@ -3,6 +3,7 @@ use crate::geo::{GeoPoint, NewGeoPoint};
 use crate::geometry_type::{GeoSpace2d, GeometryType, PointGeometryType};
 use geojson::Position;
 
+/// Point of a GeoJSON geometry. Used as the point type of [`geojson::Geometry`].
 pub struct GeoJsonPoint(Position);
 
 impl TryFrom<Position> for GeoJsonPoint {
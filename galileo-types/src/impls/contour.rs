@@ -1,5 +1,6 @@
 use crate::geo::Projection;
 use crate::geometry_type::{ContourGeometryType, GeometryType};
+use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
 /// Simple [`crate::Contour`] implementation.
@@ -1,5 +1,6 @@
 use crate::geometry_type::{GeometryType, MultiContourGeometryType};
 use crate::impls::contour::Contour;
+use alloc::vec::Vec;
 
 /// A set of contours.
 pub struct MultiContour<P>(Vec<Contour<P>>);
@@ -1,4 +1,5 @@
 use crate::geometry_type::{GeometryType, MultiPointGeometryType};
+use alloc::vec::Vec;
 
 /// A set of points.
 pub struct MultiPoint<P>(Vec<P>);
@@ -1,5 +1,6 @@
 use crate::geometry_type::{GeometryType, MultiPolygonGeometryType};
 use crate::impls::polygon::Polygon;
+use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
 /// A set of polygons.
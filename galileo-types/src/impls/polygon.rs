@@ -1,5 +1,8 @@
 use crate::geometry_type::{GeometryType, PolygonGeometryType};
 use crate::impls::contour::ClosedContour;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
 /// Simple implementation of the [`Polygon`](crate::Polygon) trait.
@@ -79,10 +79,25 @@
 //! `galileo-types` provides geometry traits implementation for these crates:
 //! * `geo-types` - enabled by `geo-types` feature
 //! * `geojson` - enabled by `geojson` feature
+//!
+//! # `no_std` support
+//!
+//! The geometry traits, their default implementations, cartesian algorithms and Web Mercator projection only need
+//! `alloc`, so the crate can be used without the standard library (e.g. for preprocessing geometries on embedded
+//! devices). To do that, disable the default features:
+//!
+//! ```toml
+//! galileo-types = { version = "0.1", default-features = false }
+//! ```
+//!
+//! The `std` feature (enabled by default) is required by `geodesy`, `geo-types` and `geojson` features.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(clippy::unwrap_used)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
 pub mod cartesian;
 pub mod contour;
 mod disambig;
@@ -102,6 +117,8 @@ mod geo_types;
 
 #[cfg(feature = "geojson")]
 mod geojson;
+#[cfg(feature = "geojson")]
+pub use geojson::GeoJsonPoint;
 
 pub use contour::{ClosedContour, Contour};
 pub use disambig::{Disambig, Disambiguate};
@@ -5,6 +5,7 @@ use crate::geometry::{
     CartesianGeometry2d, CartesianGeometry2dSpecialization, Geom, Geometry, GeometrySpecialization,
 };
 use crate::geometry_type::{CartesianSpace2d, GeometryType, MultiContourGeometryType};
+use alloc::vec::Vec;
 
 /// Geometry consisting of several contours.
 pub trait MultiContour {
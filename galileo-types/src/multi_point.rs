@@ -4,6 +4,7 @@ use crate::geometry::{
     CartesianGeometry2d, CartesianGeometry2dSpecialization, Geom, GeometrySpecialization,
 };
 use crate::geometry_type::{CartesianSpace2d, GeometryType, MultiPointGeometryType};
+use alloc::vec::Vec;
 
 /// Geometry type consisting of several points.
 pub trait MultiPoint {
@@ -6,6 +6,7 @@ use crate::geometry::{
 };
 use crate::geometry_type::{CartesianSpace2d, GeometryType, MultiPolygonGeometryType};
 use crate::impls::Polygon;
+use alloc::vec::Vec;
 
 /// Geometry consisting of several polygons.
 pub trait MultiPolygon {
@@ -6,6 +6,8 @@ use crate::geometry::{
 };
 use crate::geometry_type::{CartesianSpace2d, GeometryType, PolygonGeometryType};
 use crate::segment::Segment;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 
 /// Polygon geometry. Polygon consists of one outer contour, and zero or more inner contours.
 ///
@@ -22,7 +24,7 @@ pub trait Polygon {
 
     /// Iterates over all contours of the polygon starting with the outer one.
     fn iter_contours(&self) -> impl Iterator<Item = &'_ Self::Contour> {
-        Box::new(std::iter::once(self.outer_contour()).chain(self.inner_contours()))
+        Box::new(core::iter::once(self.outer_contour()).chain(self.inner_contours()))
     }
 
     /// Iterates over all segments of the polygon contour lines.
@@ -1,4 +1,4 @@
-use galileo::layer::feature_layer::Feature;
+use galileo::layer::feature_layer::{AttributeValue, Feature, FeatureId};
 use galileo::Color;
 use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
 use galileo_types::geo::impls::GeoPoint2d;
@@ -33,6 +33,25 @@ impl Feature for Country {
     fn geometry(&self) -> &Self::Geom {
         self
     }
+
+    fn id(&self) -> Option<FeatureId> {
+        Some(self.name.as_str().into())
+    }
+
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        match name {
+            "name" => Some(self.name.as_str().into()),
+            "is_selected" => Some(self.is_selected.into()),
+            _ => None,
+        }
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        vec![
+            ("name".to_string(), self.name.as_str().into()),
+            ("is_selected".to_string(), self.is_selected.into()),
+        ]
+    }
 }
 
 impl Geometry for Country {
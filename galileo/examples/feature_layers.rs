@@ -68,13 +68,12 @@ pub async fn run(builder: MapBuilder) {
                 if *button == MouseButton::Left {
                     let mut layer = feature_layer.write().unwrap();
 
-                    let Some(position) = map.view().screen_to_map(event.screen_pointer_position)
-                    else {
+                    let Some(position) = event.map_pointer_position else {
                         return EventPropagation::Stop;
                     };
 
                     for mut feature_container in
-                        layer.get_features_at_mut(&position, map.view().resolution() * 2.0)
+                        layer.get_features_at_pointer_mut(&position, event.pointer_type, map.view())
                     {
                         log::info!(
                             "Found {} with bbox {:?}",
@@ -99,11 +98,11 @@ pub async fn run(builder: MapBuilder) {
                 let mut layer = feature_layer.write().unwrap();
 
                 let mut new_selected = usize::MAX;
-                let Some(position) = map.view().screen_to_map(event.screen_pointer_position) else {
+                let Some(position) = event.map_pointer_position else {
                     return EventPropagation::Stop;
                 };
                 if let Some(feature_container) = layer
-                    .get_features_at_mut(&position, map.view().resolution() * 2.0)
+                    .get_features_at_pointer_mut(&position, event.pointer_type, map.view())
                     .next()
                 {
                     let index = feature_container.index();
@@ -4,11 +4,9 @@ use galileo::layer::feature_layer::symbol::{SimplePolygonSymbol, Symbol};
 use galileo::layer::feature_layer::FeatureLayer;
 use galileo::render::render_bundle::RenderPrimitive;
 use galileo::{MapBuilder, MapView};
-use galileo_types::cartesian::{CartesianPoint3d, Point2d};
+use galileo_types::cartesian::CartesianPoint3d;
 use galileo_types::geo::impls::GeoPoint2d;
-use galileo_types::geo::{
-    ChainProjection, Crs, Datum, InvertedProjection, NewGeoPoint, Projection, ProjectionType,
-};
+use galileo_types::geo::{Crs, Datum, NewGeoPoint, ProjectionType};
 use galileo_types::geometry::Geom;
 use galileo_types::impls::{Contour, Polygon};
 use num_traits::AsPrimitive;
@@ -50,23 +48,12 @@ pub async fn run(builder: MapBuilder) {
                 let mut layer = feature_layer.write().unwrap();
 
                 let mut new_selected = usize::MAX;
-                let Some(position) = map.view().screen_to_map(event.screen_pointer_position) else {
-                    return EventPropagation::Stop;
-                };
-
-                let projection = ChainProjection::new(
-                    Box::new(InvertedProjection::new(
-                        map.view().crs().get_projection::<GeoPoint2d, _>().unwrap(),
-                    )),
-                    layer.crs().get_projection::<_, Point2d>().unwrap(),
-                );
-
-                let Some(projected) = projection.project(&position) else {
+                let Some(projected) = event.position_in_crs(layer.crs()) else {
                     return EventPropagation::Stop;
                 };
 
                 if let Some(feature_container) = layer
-                    .get_features_at_mut(&projected, map.view().resolution() * 2.0)
+                    .get_features_at_pointer_mut(&projected, event.pointer_type, map.view())
                     .next()
                 {
                     let index = feature_container.index();
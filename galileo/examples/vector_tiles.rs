@@ -53,18 +53,19 @@ async fn main() {
 
 pub async fn run(builder: MapBuilder, style: VectorTileStyle) {
     let layer = LAYER.with(|v| v.clone());
-    layer.write().unwrap().update_style(style);
+    layer.write().unwrap().set_style(style);
 
     builder
         .with_layer(layer.clone())
         .with_event_handler(move |ev, map| match ev {
             UserEvent::Click(MouseButton::Left, mouse_event) => {
                 let view = map.view().clone();
-                let position = map
-                    .view()
-                    .screen_to_map(mouse_event.screen_pointer_position)
-                    .unwrap();
-                let features = layer.read().unwrap().get_features_at(&position, &view);
+                let position = mouse_event.map_pointer_position.unwrap();
+                let features = layer.read().unwrap().get_features_at_pointer(
+                    &position,
+                    mouse_event.pointer_type,
+                    &view,
+                );
 
                 for (layer, feature) in features {
                     println!("{layer}, {:?}", feature.properties);
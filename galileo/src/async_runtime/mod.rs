@@ -1,23 +1,279 @@
-#[cfg(not(target_arch = "wasm32"))]
-use maybe_sync::MaybeSend;
+//! Executor abstraction used for tile loading and other background work.
+//!
+//! Galileo does not depend on a specific async runtime. All the background tasks are given to the [`Executor`] set
+//! with [`set_executor`]. If no executor is set, the default one is used:
+//! * [`TokioExecutor`] on native platforms with the `tokio` feature (enabled by default);
+//! * [`ThreadExecutor`] on native platforms without the `tokio` feature;
+//! * [`WasmExecutor`] (based on `wasm-bindgen-futures`) on the web.
+//!
+//! To use another runtime (e.g. `async-std` or `smol`), implement [`Executor`] for it and set it before creating any
+//! layers:
+//!
+//! ```no_run
+//! use galileo::async_runtime::{set_executor, BoxFuture, Executor};
+//! use std::time::Duration;
+//!
+//! struct MyExecutor;
+//!
+//! impl Executor for MyExecutor {
+//!     fn spawn(&self, future: BoxFuture) {
+//!         // my_runtime::spawn(future);
+//!     }
+//!
+//!     fn sleep(&self, duration: Duration) -> BoxFuture {
+//!         // Box::pin(my_runtime::sleep(duration))
+//!         # unimplemented!()
+//!     }
+//! }
+//!
+//! set_executor(MyExecutor).expect("executor is set only once");
+//! ```
+//!
+//! Note that the built-in HTTP data providers on native platforms use `reqwest`, which requires a `tokio` reactor to
+//! be running. Applications using other runtimes should load the data with their own
+//! [`DataProvider`](crate::layer::data_provider::DataProvider)s.
+
+use crate::error::GalileoError;
+use maybe_sync::{MaybeSend, MaybeSync};
 use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// Boxed future of a background task.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+/// Boxed future of a background task.
+#[cfg(target_arch = "wasm32")]
+pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + 'static>>;
+
+/// Boxed blocking function.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BlockingTask = Box<dyn FnOnce() + Send + 'static>;
+/// Boxed blocking function.
+#[cfg(target_arch = "wasm32")]
+pub type BlockingTask = Box<dyn FnOnce() + 'static>;
+
+/// Runs the background tasks of Galileo.
+pub trait Executor: MaybeSend + MaybeSync {
+    /// Runs the future to completion in the background.
+    fn spawn(&self, future: BoxFuture);
+
+    /// Returns a future that completes after the given time passes.
+    fn sleep(&self, duration: Duration) -> BoxFuture;
+
+    /// Runs a CPU-heavy function (e.g. tessellation of a vector tile) without blocking the async tasks.
+    ///
+    /// The default implementation calls the function in place, which is fine for single-threaded executors.
+    fn spawn_blocking(&self, task: BlockingTask) {
+        task();
+    }
+}
+
+/// Executor that uses the current `tokio` runtime.
+#[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TokioExecutor;
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+impl Executor for TokioExecutor {
+    fn spawn(&self, future: BoxFuture) {
+        tokio::spawn(future);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn spawn_blocking(&self, task: BlockingTask) {
+        tokio::task::spawn_blocking(task);
+    }
+}
 
+/// Executor that does not need any async runtime: every task runs on its own OS thread.
+///
+/// It is simple, but creates a thread for every task, so it is only suitable for applications that load a moderate
+/// amount of data.
 #[cfg(not(target_arch = "wasm32"))]
-pub fn spawn<T>(future: T)
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ThreadExecutor;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Executor for ThreadExecutor {
+    fn spawn(&self, future: BoxFuture) {
+        std::thread::spawn(move || futures::executor::block_on(future));
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture {
+        let (sender, receiver) = futures::channel::oneshot::channel::<()>();
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            let _ = sender.send(());
+        });
+
+        Box::pin(async move {
+            let _ = receiver.await;
+        })
+    }
+
+    fn spawn_blocking(&self, task: BlockingTask) {
+        std::thread::spawn(task);
+    }
+}
+
+/// Executor that runs the tasks in the browser event loop with `wasm-bindgen-futures`. Works both in the main thread
+/// and in web workers.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct WasmExecutor;
+
+#[cfg(target_arch = "wasm32")]
+impl Executor for WasmExecutor {
+    fn spawn(&self, future: BoxFuture) {
+        wasm_bindgen_futures::spawn_local(future);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture {
+        use wasm_bindgen::JsCast;
+
+        let timeout = duration.as_millis().min(i32::MAX as u128) as i32;
+        let mut cb = |resolve: js_sys::Function, _reject: js_sys::Function| {
+            let result = if let Some(window) = web_sys::window() {
+                window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, timeout)
+            } else {
+                js_sys::global()
+                    .dyn_into::<web_sys::WorkerGlobalScope>()
+                    .expect("global object is not available")
+                    .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, timeout)
+            };
+            result.expect("failed to set timeout");
+        };
+
+        let p = js_sys::Promise::new(&mut cb);
+        Box::pin(async move {
+            let _ = wasm_bindgen_futures::JsFuture::from(p).await;
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static EXECUTOR: std::sync::OnceLock<Box<dyn Executor>> = std::sync::OnceLock::new();
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static EXECUTOR: std::cell::OnceCell<std::rc::Rc<dyn Executor>> = const { std::cell::OnceCell::new() };
+}
+
+/// Sets the executor for all the background tasks of Galileo.
+///
+/// The executor can be set only once, and only before any background task is started. Otherwise, an error is
+/// returned.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_executor(executor: impl Executor + 'static) -> Result<(), GalileoError> {
+    EXECUTOR
+        .set(Box::new(executor))
+        .map_err(|_| GalileoError::Generic("executor is already set".into()))
+}
+
+/// Sets the executor for all the background tasks of Galileo in the current thread (the main thread or a web worker).
+///
+/// The executor can be set only once, and only before any background task is started. Otherwise, an error is
+/// returned.
+#[cfg(target_arch = "wasm32")]
+pub fn set_executor(executor: impl Executor + 'static) -> Result<(), GalileoError> {
+    EXECUTOR.with(|cell| {
+        cell.set(std::rc::Rc::new(executor))
+            .map_err(|_| GalileoError::Generic("executor is already set".into()))
+    })
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "tokio"))]
+fn default_executor() -> Box<dyn Executor> {
+    Box::new(TokioExecutor)
+}
+
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "tokio")))]
+fn default_executor() -> Box<dyn Executor> {
+    Box::new(ThreadExecutor)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn with_executor<R>(f: impl FnOnce(&dyn Executor) -> R) -> R {
+    f(&**EXECUTOR.get_or_init(default_executor))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn with_executor<R>(f: impl FnOnce(&dyn Executor) -> R) -> R {
+    let executor =
+        EXECUTOR.with(|cell| cell.get_or_init(|| std::rc::Rc::new(WasmExecutor)).clone());
+    f(&*executor)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn spawn<T>(future: T)
 where
     T: Future + MaybeSend + 'static,
     T::Output: MaybeSend + 'static,
 {
-    tokio::spawn(future);
+    with_executor(|executor| {
+        executor.spawn(Box::pin(async move {
+            future.await;
+        }))
+    });
 }
 
 #[cfg(target_arch = "wasm32")]
-pub fn spawn<T>(future: T)
+pub(crate) fn spawn<T>(future: T)
 where
     T: Future + 'static,
     T::Output: 'static,
 {
-    wasm_bindgen_futures::spawn_local(async {
-        future.await;
+    with_executor(|executor| {
+        executor.spawn(Box::pin(async move {
+            future.await;
+        }))
+    });
+}
+
+/// Completes after the given time passes.
+pub(crate) async fn sleep(duration: Duration) {
+    with_executor(|executor| executor.sleep(duration)).await;
+}
+
+/// Runs a CPU-heavy function with [`Executor::spawn_blocking`] and returns its result.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn spawn_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, GalileoError> {
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    with_executor(|executor| {
+        executor.spawn_blocking(Box::new(move || {
+            let _ = sender.send(task());
+        }))
     });
+
+    receiver
+        .await
+        .map_err(|_| GalileoError::Generic("blocking task was cancelled".into()))
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use web_time::Instant;
+
+    #[test]
+    fn thread_executor_runs_tasks() {
+        let executor = ThreadExecutor;
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        let sleep = executor.sleep(Duration::from_millis(20));
+        executor.spawn(Box::pin(async move {
+            let start = Instant::now();
+            sleep.await;
+            let _ = sender.send(start.elapsed());
+        }));
+
+        let elapsed = futures::executor::block_on(receiver).expect("task is completed");
+        assert!(elapsed >= Duration::from_millis(15), "{elapsed:?}");
+    }
 }
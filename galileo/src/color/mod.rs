@@ -0,0 +1,504 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::GalileoError;
+
+mod css;
+pub mod palette;
+
+/// Color representation.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+impl From<String> for Color {
+    fn from(value: String) -> Self {
+        Self::parse_css(&value).unwrap_or(Color::rgba(0, 0, 0, 255))
+    }
+}
+
+impl FromStr for Color {
+    type Err = GalileoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_css(s).ok_or_else(|| GalileoError::Generic(format!("invalid color: {s}")))
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_css())
+    }
+}
+
+/// Color space used to interpolate between two colors.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ColorSpace {
+    /// Channels of sRGB color are interpolated independently.
+    #[default]
+    Rgb,
+    /// Hue, saturation and lightness are interpolated, with hue going along the shorter arc of the color wheel.
+    Hsl,
+    /// CIE L\*a\*b\* space. Produces perceptually even gradients.
+    Lab,
+}
+
+impl From<Color> for String {
+    fn from(val: Color) -> Self {
+        val.to_hex()
+    }
+}
+
+impl Color {
+    /// Transparent color: `#00000000`
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+    /// Red color: `#FF0000FF`
+    pub const RED: Color = Color::rgba(255, 0, 0, 255);
+    /// Green color: `#00FF00FF`
+    pub const GREEN: Color = Color::rgba(0, 255, 0, 255);
+    /// Blue color: `#0000FFFF`
+    pub const BLUE: Color = Color::rgba(0, 0, 255, 255);
+    /// White color: `#FFFFFFFF`
+    pub const WHITE: Color = Color::rgba(255, 255, 255, 255);
+    /// Black color: `#000000FF`
+    pub const BLACK: Color = Color::rgba(0, 0, 0, 255);
+
+    /// Constructs color from its RGBA channels.
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Constructs opaque color from its RGB channels.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Red channel.
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    /// Green channel.
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    /// Blue channel.
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+
+    /// Alpha channel.
+    pub fn a(&self) -> u8 {
+        self.a
+    }
+
+    /// Converts the color into f32 array as used by wgpu.
+    pub fn to_f32_array(&self) -> [f32; 4] {
+        [
+            self.r as f32 / 255.0,
+            self.g as f32 / 255.0,
+            self.b as f32 / 255.0,
+            self.a as f32 / 255.0,
+        ]
+    }
+
+    /// Converts the color into f32 array with the color channels converted from sRGB encoding into linear space.
+    /// Alpha channel is not changed.
+    pub fn to_linear_f32_array(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.to_f32_array();
+        [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+    }
+
+    /// Converts the color into u8 array (RGBA).
+    pub fn to_u8_array(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    /// Converts the color into HEX8 string: `#RRGGBBAA`.
+    pub fn to_hex(&self) -> String {
+        format!("#{:02X}{:02X}{:02X}{:02X}", self.r, self.g, self.b, self.a)
+    }
+
+    /// Parses a color from the hex string. Hex string can be either HEX6 (`#RRGGBB`) or HEX8 (`#RRGGBBAA`).
+    pub fn try_from_hex(hex_string: &str) -> Option<Self> {
+        if hex_string.len() != 7 && hex_string.len() != 9 || hex_string.chars().next()? != '#' {
+            return None;
+        }
+
+        let r = u8::from_str_radix(&hex_string[1..3], 16).ok()?;
+        let g = u8::from_str_radix(&hex_string[3..5], 16).ok()?;
+        let b = u8::from_str_radix(&hex_string[5..7], 16).ok()?;
+        let a = if hex_string.len() == 9 {
+            u8::from_str_radix(&hex_string[7..9], 16).ok()?
+        } else {
+            255
+        };
+
+        Some(Self { r, g, b, a })
+    }
+
+    /// Parses a color from the hex string. Hex string can be either HEX6 (`#RRGGBB`) or HEX8 (`#RRGGBBAA`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parsing fails.
+    pub const fn from_hex(hex_string: &'static str) -> Self {
+        let bytes = hex_string.as_bytes();
+        if bytes.len() != 7 && bytes.len() != 9 || bytes[0] != b'#' {
+            panic!("Invalid color hex string");
+        }
+
+        let r = decode_byte(&[bytes[1], bytes[2]]);
+        let g = decode_byte(&[bytes[3], bytes[4]]);
+        let b = decode_byte(&[bytes[5], bytes[6]]);
+        let a = if hex_string.len() == 9 {
+            decode_byte(&[bytes[7], bytes[8]])
+        } else {
+            255
+        };
+
+        Self { r, g, b, a }
+    }
+
+    /// Returns a new color instance, copied from the base one but with the given alpha channel.
+    pub fn with_alpha(&self, a: u8) -> Self {
+        Self { a, ..*self }
+    }
+
+    /// Returns true if the color is fully transparent (`a == 0`).
+    pub fn is_transparent(&self) -> bool {
+        self.a == 0
+    }
+
+    /// Parses a CSS color string. Supported formats are:
+    /// * hex notation: `#RGB`, `#RGBA`, `#RRGGBB` and `#RRGGBBAA`,
+    /// * `rgb()`/`rgba()` functions with channels as numbers or percentages, e.g. `rgba(255, 0, 0, 0.5)` or
+    ///   `rgb(100% 0% 0% / 50%)`,
+    /// * `hsl()`/`hsla()` functions, e.g. `hsl(120deg, 100%, 50%)`,
+    /// * CSS named colors (e.g. `steelblue`) and `transparent`.
+    pub fn parse_css(value: &str) -> Option<Self> {
+        css::parse_css(value)
+    }
+
+    /// Converts the color into a CSS string: `#RRGGBB` for opaque colors and `rgba(r, g, b, a)` otherwise.
+    pub fn to_css(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+        } else {
+            let alpha = (self.a as f64 / 255.0 * 1000.0).round() / 1000.0;
+            format!("rgba({}, {}, {}, {alpha})", self.r, self.g, self.b)
+        }
+    }
+
+    /// Constructs color from hue (in degrees), saturation and lightness (both in `0..=1` range).
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64, a: u8) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = lightness - chroma / 2.0;
+
+        Self::rgba(to_byte(r + m), to_byte(g + m), to_byte(b + m), a)
+    }
+
+    /// Converts the color into hue (in degrees), saturation and lightness (both in `0..=1` range). Alpha channel is
+    /// ignored.
+    pub fn to_hsl(&self) -> [f64; 3] {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f64 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let lightness = (max + min) / 2.0;
+
+        if chroma == 0.0 {
+            return [0.0, 0.0, lightness];
+        }
+
+        let hue = if max == r {
+            ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        let saturation = chroma / (1.0 - (2.0 * lightness - 1.0).abs());
+
+        [hue * 60.0, saturation, lightness]
+    }
+
+    /// Constructs color from CIE L\*a\*b\* coordinates (D65 white point). Colors outside of sRGB gamut are clipped.
+    pub fn from_lab(l: f64, a: f64, b: f64, alpha: u8) -> Self {
+        let fy = (l + 16.0) / 116.0;
+        let fx = fy + a / 500.0;
+        let fz = fy - b / 200.0;
+        let [x, y, z] = [
+            lab_f_inv(fx) * WHITE_D65[0],
+            lab_f_inv(fy) * WHITE_D65[1],
+            lab_f_inv(fz) * WHITE_D65[2],
+        ];
+
+        let r = 3.2404542 * x - 1.5371385 * y - 0.4985314 * z;
+        let g = -0.9692660 * x + 1.8760108 * y + 0.0415560 * z;
+        let b = 0.0556434 * x - 0.2040259 * y + 1.0572252 * z;
+
+        Self::rgba(
+            to_byte(linear_to_srgb(r)),
+            to_byte(linear_to_srgb(g)),
+            to_byte(linear_to_srgb(b)),
+            alpha,
+        )
+    }
+
+    /// Converts the color into CIE L\*a\*b\* coordinates (D65 white point). Alpha channel is ignored.
+    pub fn to_lab(&self) -> [f64; 3] {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| srgb_to_linear_f64(c as f64 / 255.0));
+        let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+        let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+        let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+
+        let fx = lab_f(x / WHITE_D65[0]);
+        let fy = lab_f(y / WHITE_D65[1]);
+        let fz = lab_f(z / WHITE_D65[2]);
+
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+
+    /// Interpolates between this color (`t == 0`) and the `other` one (`t == 1`) in the given color space. Alpha
+    /// channel is always interpolated linearly. `t` is clamped into `0..=1` range.
+    pub fn interpolate(&self, other: &Color, t: f64, color_space: ColorSpace) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |from: f64, to: f64| from + (to - from) * t;
+        let a = mix(self.a as f64, other.a as f64).round() as u8;
+
+        match color_space {
+            ColorSpace::Rgb => Self::rgba(
+                mix(self.r as f64, other.r as f64).round() as u8,
+                mix(self.g as f64, other.g as f64).round() as u8,
+                mix(self.b as f64, other.b as f64).round() as u8,
+                a,
+            ),
+            ColorSpace::Hsl => {
+                let [mut h1, s1, l1] = self.to_hsl();
+                let [mut h2, s2, l2] = other.to_hsl();
+                // Hue of achromatic colors is undefined, so the hue of the other color is used.
+                if s1 == 0.0 {
+                    h1 = h2;
+                } else if s2 == 0.0 {
+                    h2 = h1;
+                }
+                let mut delta = h2 - h1;
+                if delta > 180.0 {
+                    delta -= 360.0;
+                } else if delta < -180.0 {
+                    delta += 360.0;
+                }
+
+                Self::from_hsl(h1 + delta * t, mix(s1, s2), mix(l1, l2), a)
+            }
+            ColorSpace::Lab => {
+                let [l1, a1, b1] = self.to_lab();
+                let [l2, a2, b2] = other.to_lab();
+                Self::from_lab(mix(l1, l2), mix(a1, a2), mix(b1, b2), a)
+            }
+        }
+    }
+
+    /// Composites this color over the `background` color using source-over alpha blending.
+    pub fn over(&self, background: &Color) -> Self {
+        let src_a = self.a as f64 / 255.0;
+        let dst_a = background.a as f64 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a == 0.0 {
+            return Self::TRANSPARENT;
+        }
+
+        let channel = |src: u8, dst: u8| {
+            let value = (src as f64 * src_a + dst as f64 * dst_a * (1.0 - src_a)) / out_a;
+            value.round() as u8
+        };
+
+        Self::rgba(
+            channel(self.r, background.r),
+            channel(self.g, background.g),
+            channel(self.b, background.b),
+            to_byte(out_a),
+        )
+    }
+
+    /// Returns the color with the alpha channel multiplied by the `opacity` (in `0..=1` range).
+    pub fn with_opacity(&self, opacity: f64) -> Self {
+        self.with_alpha((self.a as f64 * opacity.clamp(0.0, 1.0)).round() as u8)
+    }
+}
+
+/// Reference white of D65 illuminant in XYZ space.
+const WHITE_D65: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+fn to_byte(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn srgb_to_linear_f64(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn lab_f(t: f64) -> f64 {
+    const DELTA: f64 = 6.0 / 29.0;
+    if t > DELTA * DELTA * DELTA {
+        t.cbrt()
+    } else {
+        t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+    }
+}
+
+fn lab_f_inv(t: f64) -> f64 {
+    const DELTA: f64 = 6.0 / 29.0;
+    if t > DELTA {
+        t * t * t
+    } else {
+        3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+const fn decode_byte(chars: &[u8]) -> u8 {
+    debug_assert!(chars.len() == 2);
+    let first = decode_char(chars[0]);
+    let second = decode_char(chars[1]);
+
+    first * 16 + second
+}
+
+const fn decode_char(byte: u8) -> u8 {
+    match byte {
+        b'0'..=b'9' => byte - b'0',
+        b'a'..=b'f' => byte - b'a' + 10,
+        b'A'..=b'F' => byte - b'A' + 10,
+        _ => panic!("Invalid hex character"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_serialization() {
+        let hex = "#FF1000AA";
+        let color = Color::try_from_hex(hex).unwrap();
+        assert_eq!(&color.to_hex(), hex);
+
+        assert_eq!(Color::from_hex(&hex), color);
+    }
+
+    #[test]
+    fn linear_conversion() {
+        assert_eq!(Color::WHITE.to_linear_f32_array(), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(Color::TRANSPARENT.to_linear_f32_array(), [0.0; 4]);
+
+        let [r, _, _, a] = Color::rgba(128, 0, 0, 128).to_linear_f32_array();
+        assert!((r - 0.2158).abs() < 1e-3);
+        assert_eq!(a, 128.0 / 255.0);
+    }
+
+    #[test]
+    fn hsl_and_lab_round_trip() {
+        for color in [
+            Color::RED,
+            Color::rgb(12, 200, 97),
+            Color::rgb(70, 130, 180),
+            Color::rgb(128, 128, 128),
+        ] {
+            let [h, s, l] = color.to_hsl();
+            assert_eq!(Color::from_hsl(h, s, l, 255), color);
+            let [l, a, b] = color.to_lab();
+            assert_eq!(Color::from_lab(l, a, b, 255), color);
+        }
+
+        let [l, a, b] = Color::WHITE.to_lab();
+        assert!((l - 100.0).abs() < 1e-3 && a.abs() < 1e-3 && b.abs() < 1e-3);
+    }
+
+    #[test]
+    fn interpolation() {
+        let from = Color::rgba(0, 0, 0, 0);
+        let to = Color::rgba(255, 255, 255, 255);
+        assert_eq!(
+            from.interpolate(&to, 0.5, ColorSpace::Rgb),
+            Color::rgba(128, 128, 128, 128)
+        );
+
+        // Red to blue goes through magenta in HSL, not through green.
+        assert_eq!(
+            Color::RED.interpolate(&Color::BLUE, 0.5, ColorSpace::Hsl),
+            Color::rgb(255, 0, 255)
+        );
+
+        let middle = Color::BLACK.interpolate(&Color::WHITE, 0.5, ColorSpace::Lab);
+        assert!((middle.to_lab()[0] - 50.0).abs() < 0.5);
+        assert_eq!(
+            Color::RED.interpolate(&Color::BLUE, 1.0, ColorSpace::Lab),
+            Color::BLUE
+        );
+    }
+
+    #[test]
+    fn compositing() {
+        assert_eq!(Color::RED.over(&Color::BLUE), Color::RED);
+        assert_eq!(
+            Color::RED.with_alpha(128).over(&Color::BLUE),
+            Color::rgb(128, 0, 127)
+        );
+        assert_eq!(Color::TRANSPARENT.over(&Color::GREEN), Color::GREEN);
+        assert_eq!(Color::WHITE.with_opacity(0.5).a(), 128);
+    }
+
+    #[test]
+    fn css_serialization() {
+        assert_eq!(Color::RED.to_css(), "#FF0000");
+        assert_eq!(Color::rgba(1, 2, 3, 51).to_css(), "rgba(1, 2, 3, 0.2)");
+        for color in [Color::rgb(10, 20, 30), Color::rgba(10, 20, 30, 51)] {
+            assert_eq!(color.to_css().parse::<Color>().unwrap(), color);
+        }
+        assert!("nope".parse::<Color>().is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Named color palettes.
+//!
+//! Sequential and diverging palettes are meant to be used as stops of a color ramp (e.g.
+//! [`ColorRamp`](crate::layer::feature_layer::symbol::ColorRamp)), while categorical palettes provide distinct colors
+//! for unordered classes.
+
+use crate::Color;
+
+/// Perceptually uniform sequential palette from dark blue to yellow.
+pub const VIRIDIS: &[Color] = &[
+    Color::from_hex("#440154"),
+    Color::from_hex("#482878"),
+    Color::from_hex("#3E4989"),
+    Color::from_hex("#31688E"),
+    Color::from_hex("#26828E"),
+    Color::from_hex("#1F9E89"),
+    Color::from_hex("#35B779"),
+    Color::from_hex("#6DCD59"),
+    Color::from_hex("#B4DE2C"),
+    Color::from_hex("#FDE725"),
+];
+
+/// Perceptually uniform sequential palette from black through purple to light yellow.
+pub const MAGMA: &[Color] = &[
+    Color::from_hex("#000004"),
+    Color::from_hex("#1C1050"),
+    Color::from_hex("#4F127B"),
+    Color::from_hex("#812581"),
+    Color::from_hex("#B5367A"),
+    Color::from_hex("#E55064"),
+    Color::from_hex("#FB8861"),
+    Color::from_hex("#FEC287"),
+    Color::from_hex("#FCFDBF"),
+];
+
+/// ColorBrewer sequential `Blues` palette.
+pub const BLUES: &[Color] = &[
+    Color::from_hex("#F7FBFF"),
+    Color::from_hex("#DEEBF7"),
+    Color::from_hex("#C6DBEF"),
+    Color::from_hex("#9ECAE1"),
+    Color::from_hex("#6BAED6"),
+    Color::from_hex("#4292C6"),
+    Color::from_hex("#2171B5"),
+    Color::from_hex("#08519C"),
+    Color::from_hex("#08306B"),
+];
+
+/// ColorBrewer sequential `Greens` palette.
+pub const GREENS: &[Color] = &[
+    Color::from_hex("#F7FCF5"),
+    Color::from_hex("#E5F5E0"),
+    Color::from_hex("#C7E9C0"),
+    Color::from_hex("#A1D99B"),
+    Color::from_hex("#74C476"),
+    Color::from_hex("#41AB5D"),
+    Color::from_hex("#238B45"),
+    Color::from_hex("#006D2C"),
+    Color::from_hex("#00441B"),
+];
+
+/// ColorBrewer sequential `Reds` palette.
+pub const REDS: &[Color] = &[
+    Color::from_hex("#FFF5F0"),
+    Color::from_hex("#FEE0D2"),
+    Color::from_hex("#FCBBA1"),
+    Color::from_hex("#FC9272"),
+    Color::from_hex("#FB6A4A"),
+    Color::from_hex("#EF3B2C"),
+    Color::from_hex("#CB181D"),
+    Color::from_hex("#A50F15"),
+    Color::from_hex("#67000D"),
+];
+
+/// ColorBrewer sequential `YlOrRd` palette.
+pub const YL_OR_RD: &[Color] = &[
+    Color::from_hex("#FFFFCC"),
+    Color::from_hex("#FFEDA0"),
+    Color::from_hex("#FED976"),
+    Color::from_hex("#FEB24C"),
+    Color::from_hex("#FD8D3C"),
+    Color::from_hex("#FC4E2A"),
+    Color::from_hex("#E31A1C"),
+    Color::from_hex("#BD0026"),
+    Color::from_hex("#800026"),
+];
+
+/// ColorBrewer diverging `RdYlBu` palette.
+pub const RD_YL_BU: &[Color] = &[
+    Color::from_hex("#A50026"),
+    Color::from_hex("#D73027"),
+    Color::from_hex("#F46D43"),
+    Color::from_hex("#FDAE61"),
+    Color::from_hex("#FEE090"),
+    Color::from_hex("#FFFFBF"),
+    Color::from_hex("#E0F3F8"),
+    Color::from_hex("#ABD9E9"),
+    Color::from_hex("#74ADD1"),
+    Color::from_hex("#4575B4"),
+    Color::from_hex("#313695"),
+];
+
+/// ColorBrewer diverging `Spectral` palette.
+pub const SPECTRAL: &[Color] = &[
+    Color::from_hex("#9E0142"),
+    Color::from_hex("#D53E4F"),
+    Color::from_hex("#F46D43"),
+    Color::from_hex("#FDAE61"),
+    Color::from_hex("#FEE08B"),
+    Color::from_hex("#FFFFBF"),
+    Color::from_hex("#E6F598"),
+    Color::from_hex("#ABDDA4"),
+    Color::from_hex("#66C2A5"),
+    Color::from_hex("#3288BD"),
+    Color::from_hex("#5E4FA2"),
+];
+
+/// Tableau 10 categorical palette.
+pub const TABLEAU10: &[Color] = &[
+    Color::from_hex("#4E79A7"),
+    Color::from_hex("#F28E2B"),
+    Color::from_hex("#E15759"),
+    Color::from_hex("#76B7B2"),
+    Color::from_hex("#59A14F"),
+    Color::from_hex("#EDC948"),
+    Color::from_hex("#B07AA1"),
+    Color::from_hex("#FF9DA7"),
+    Color::from_hex("#9C755F"),
+    Color::from_hex("#BAB0AC"),
+];
+
+/// ColorBrewer qualitative `Set1` palette.
+pub const SET1: &[Color] = &[
+    Color::from_hex("#E41A1C"),
+    Color::from_hex("#377EB8"),
+    Color::from_hex("#4DAF4A"),
+    Color::from_hex("#984EA3"),
+    Color::from_hex("#FF7F00"),
+    Color::from_hex("#FFFF33"),
+    Color::from_hex("#A65628"),
+    Color::from_hex("#F781BF"),
+    Color::from_hex("#999999"),
+];
+
+/// ColorBrewer qualitative `Set2` palette.
+pub const SET2: &[Color] = &[
+    Color::from_hex("#66C2A5"),
+    Color::from_hex("#FC8D62"),
+    Color::from_hex("#8DA0CB"),
+    Color::from_hex("#E78AC3"),
+    Color::from_hex("#A6D854"),
+    Color::from_hex("#FFD92F"),
+    Color::from_hex("#E5C494"),
+    Color::from_hex("#B3B3B3"),
+];
+
+/// ColorBrewer qualitative `Dark2` palette.
+pub const DARK2: &[Color] = &[
+    Color::from_hex("#1B9E77"),
+    Color::from_hex("#D95F02"),
+    Color::from_hex("#7570B3"),
+    Color::from_hex("#E7298A"),
+    Color::from_hex("#66A61E"),
+    Color::from_hex("#E6AB02"),
+    Color::from_hex("#A6761D"),
+    Color::from_hex("#666666"),
+];
+
+/// ColorBrewer qualitative `Pastel1` palette.
+pub const PASTEL1: &[Color] = &[
+    Color::from_hex("#FBB4AE"),
+    Color::from_hex("#B3CDE3"),
+    Color::from_hex("#CCEBC5"),
+    Color::from_hex("#DECBE4"),
+    Color::from_hex("#FED9A6"),
+    Color::from_hex("#FFFFCC"),
+    Color::from_hex("#E5D8BD"),
+    Color::from_hex("#FDDAEC"),
+    Color::from_hex("#F2F2F2"),
+];
+
+/// ColorBrewer qualitative `Paired` palette.
+pub const PAIRED: &[Color] = &[
+    Color::from_hex("#A6CEE3"),
+    Color::from_hex("#1F78B4"),
+    Color::from_hex("#B2DF8A"),
+    Color::from_hex("#33A02C"),
+    Color::from_hex("#FB9A99"),
+    Color::from_hex("#E31A1C"),
+    Color::from_hex("#FDBF6F"),
+    Color::from_hex("#FF7F00"),
+    Color::from_hex("#CAB2D6"),
+    Color::from_hex("#6A3D9A"),
+    Color::from_hex("#FFFF99"),
+    Color::from_hex("#B15928"),
+];
+
+/// Returns the palette with the given name. Names are case-insensitive and match the names of the constants in this
+/// module, with or without underscores (e.g. `"viridis"`, `"YlOrRd"` or `"yl_or_rd"`).
+pub fn by_name(name: &str) -> Option<&'static [Color]> {
+    let name: String = name
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let palette = match name.as_str() {
+        "viridis" => VIRIDIS,
+        "magma" => MAGMA,
+        "blues" => BLUES,
+        "greens" => GREENS,
+        "reds" => REDS,
+        "ylorrd" => YL_OR_RD,
+        "rdylbu" => RD_YL_BU,
+        "spectral" => SPECTRAL,
+        "tableau10" => TABLEAU10,
+        "set1" => SET1,
+        "set2" => SET2,
+        "dark2" => DARK2,
+        "pastel1" => PASTEL1,
+        "paired" => PAIRED,
+        _ => return None,
+    };
+
+    Some(palette)
+}
@@ -0,0 +1,348 @@
+use crate::control::{
+    EventPropagation, Key, MapController, MouseButton, UserEvent, UserEventHandler,
+};
+use crate::map::Map;
+use crate::view::MapView;
+use galileo_types::cartesian::Point2d;
+use nalgebra::{Point2, Vector2};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// The way a user navigates the map with a [`CameraController`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NavigationMode {
+    /// Standard navigation of the [`MapController`]: dragging pans the map, dragging with the right button rotates and
+    /// tilts it, and scrolling zooms.
+    #[default]
+    TopDown,
+    /// Dragging orbits the camera around the point the drag started at, dragging with the right button pans the map,
+    /// and scrolling zooms. Convenient for inspecting 3D content from different sides.
+    Orbit,
+    /// The camera flies over the map: `W`/`S` (or arrow keys) move it forward and backward, `A`/`D` move it to the
+    /// sides, `Space`/`E` and `Shift`/`Q` move it up and down. Dragging turns the camera around its position
+    /// (mouse-look), and scrolling changes the altitude.
+    FirstPerson,
+}
+
+/// Event handler that moves the camera according to the current [`NavigationMode`], which can be changed at any time.
+///
+/// Clones of the controller share the mode, so a clone can be kept by the application to switch the mode after the
+/// controller is added to the [`EventProcessor`](crate::control::EventProcessor).
+///
+/// ```
+/// use galileo::control::{CameraController, EventProcessor, NavigationMode};
+///
+/// let controller = CameraController::default();
+/// let mut event_processor = EventProcessor::default();
+/// event_processor.add_handler(controller.clone());
+///
+/// controller.set_mode(NavigationMode::FirstPerson);
+/// ```
+#[derive(Clone)]
+pub struct CameraController {
+    mode: Arc<RwLock<NavigationMode>>,
+    /// Screen and map position of the point the camera orbits around.
+    orbit_pivot: Arc<Mutex<Option<(Point2d, Point2d)>>>,
+    top_down: Arc<MapController>,
+    rotation_speed: f64,
+    max_tilt: f64,
+    move_step: f64,
+    altitude_step: f64,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            mode: Default::default(),
+            orbit_pivot: Default::default(),
+            top_down: Default::default(),
+            rotation_speed: 0.005,
+            max_tilt: 80f64.to_radians(),
+            move_step: 20.0,
+            altitude_step: 0.2,
+        }
+    }
+}
+
+impl CameraController {
+    /// Creates a new controller with the given navigation mode.
+    pub fn new(mode: NavigationMode) -> Self {
+        Self::default().with_mode(mode)
+    }
+
+    /// Sets the navigation mode.
+    pub fn with_mode(self, mode: NavigationMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Sets the speed of rotation and mouse-look in radians per pixel of pointer movement.
+    pub fn with_rotation_speed(mut self, rotation_speed: f64) -> Self {
+        self.rotation_speed = rotation_speed;
+        self
+    }
+
+    /// Sets the maximum tilt of the camera in radians.
+    pub fn with_max_tilt(mut self, max_tilt: f64) -> Self {
+        self.max_tilt = max_tilt;
+        self
+    }
+
+    /// Sets the controller that handles the events in the [`NavigationMode::TopDown`] mode, and the zoom gestures in
+    /// the [`NavigationMode::Orbit`] mode.
+    pub fn with_map_controller(mut self, controller: MapController) -> Self {
+        self.top_down = Arc::new(controller);
+        self
+    }
+
+    /// Sets the distance the camera moves in the first person mode with one key press, in pixels at the point right
+    /// below the camera.
+    pub fn with_move_step(mut self, move_step: f64) -> Self {
+        self.move_step = move_step;
+        self
+    }
+
+    /// Current navigation mode.
+    pub fn mode(&self) -> NavigationMode {
+        *self.mode.read().expect("lock is poisoned")
+    }
+
+    /// Changes the navigation mode. The camera position is not changed.
+    pub fn set_mode(&self, mode: NavigationMode) {
+        *self.mode.write().expect("lock is poisoned") = mode;
+        *self.orbit_pivot.lock().expect("mutex is poisoned") = None;
+    }
+
+    fn handle_orbit(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        match event {
+            UserEvent::DragStarted(MouseButton::Left | MouseButton::Other, e) => {
+                let view = map.view();
+                let center = Point2d::new(view.size().half_width(), view.size().half_height());
+                let pivot = [e.screen_pointer_position, center]
+                    .into_iter()
+                    .find_map(|screen| view.screen_to_map(screen).map(|point| (screen, point)));
+                *self.orbit_pivot.lock().expect("mutex is poisoned") = pivot;
+
+                EventPropagation::Consume
+            }
+            UserEvent::DragStarted(MouseButton::Right, _) => EventPropagation::Consume,
+            UserEvent::Drag(MouseButton::Left | MouseButton::Other, delta, _) => {
+                let pivot = *self.orbit_pivot.lock().expect("mutex is poisoned");
+                if let Some(view) = self.orbit(map.view(), pivot, *delta) {
+                    map.set_view(view);
+                }
+
+                EventPropagation::Stop
+            }
+            UserEvent::Drag(MouseButton::Right, delta, e) => {
+                let current_position = e.screen_pointer_position;
+                map.set_view(
+                    map.view()
+                        .translate_by_pixels(current_position - delta, current_position),
+                );
+
+                EventPropagation::Stop
+            }
+            UserEvent::DragEnded(..) => {
+                *self.orbit_pivot.lock().expect("mutex is poisoned") = None;
+                EventPropagation::Propagate
+            }
+            _ => self.top_down.handle(event, map),
+        }
+    }
+
+    fn handle_first_person(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        let view = match event {
+            UserEvent::DragStarted(..) => return EventPropagation::Consume,
+            UserEvent::Drag(_, delta, _) => self.look(map.view(), *delta),
+            UserEvent::Scroll(delta, _) => {
+                self.change_altitude(map.view(), (self.altitude_step + 1.0).powf(-delta.lines()))
+            }
+            UserEvent::KeyPressed(key) => match key {
+                Key::Char('w') | Key::ArrowUp => self.fly(map.view(), Vector2::new(0.0, 1.0)),
+                Key::Char('s') | Key::ArrowDown => self.fly(map.view(), Vector2::new(0.0, -1.0)),
+                Key::Char('a') | Key::ArrowLeft => self.fly(map.view(), Vector2::new(-1.0, 0.0)),
+                Key::Char('d') | Key::ArrowRight => self.fly(map.view(), Vector2::new(1.0, 0.0)),
+                Key::Char('e') | Key::Space => {
+                    self.change_altitude(map.view(), 1.0 + self.altitude_step)
+                }
+                Key::Char('q') | Key::Shift => {
+                    self.change_altitude(map.view(), 1.0 / (1.0 + self.altitude_step))
+                }
+                _ => return EventPropagation::Propagate,
+            },
+            _ => return EventPropagation::Propagate,
+        };
+
+        if let Some(view) = view {
+            map.set_view(view);
+        }
+
+        EventPropagation::Stop
+    }
+
+    fn clamp_tilt(&self, rotation_x: f64) -> f64 {
+        rotation_x.clamp(0.0, self.max_tilt)
+    }
+
+    /// Rotates the view, keeping the map point of the `pivot` at its screen position.
+    fn orbit(
+        &self,
+        view: &MapView,
+        pivot: Option<(Point2d, Point2d)>,
+        px_delta: Vector2<f64>,
+    ) -> Option<MapView> {
+        let rotated = view.with_rotation(
+            self.clamp_tilt(view.rotation_x() - px_delta.y * self.rotation_speed),
+            view.rotation_z() + px_delta.x * self.rotation_speed,
+        );
+
+        let Some((screen, point)) = pivot else {
+            return Some(rotated);
+        };
+
+        // If the pivot would go over the horizon, the rotation is not applied.
+        let moved = rotated.screen_to_map(screen)?;
+        Some(rotated.translate(moved - point))
+    }
+
+    /// Turns the camera around its position.
+    fn look(&self, view: &MapView, px_delta: Vector2<f64>) -> Option<MapView> {
+        let (eye, height) = eye(view)?;
+        look_from(
+            view,
+            eye,
+            height,
+            self.clamp_tilt(view.rotation_x() - px_delta.y * self.rotation_speed),
+            view.rotation_z() + px_delta.x * self.rotation_speed,
+        )
+    }
+
+    /// Moves the camera horizontally. Direction `(0, 1)` is forward, and `(1, 0)` is to the right.
+    fn fly(&self, view: &MapView, direction: Vector2<f64>) -> Option<MapView> {
+        let (eye, height) = eye(view)?;
+        let step = self.move_step * height / view.size().half_height();
+        let rotation_z = view.rotation_z();
+        let offset = forward(rotation_z) * direction.y + right(rotation_z) * direction.x;
+
+        look_from(
+            view,
+            eye + offset * step,
+            height,
+            view.rotation_x(),
+            rotation_z,
+        )
+    }
+
+    /// Moves the camera vertically, multiplying its altitude by `k`.
+    fn change_altitude(&self, view: &MapView, k: f64) -> Option<MapView> {
+        let (eye, height) = eye(view)?;
+        look_from(view, eye, height * k, view.rotation_x(), view.rotation_z())
+    }
+}
+
+impl UserEventHandler for CameraController {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        match self.mode() {
+            NavigationMode::TopDown => self.top_down.handle(event, map),
+            NavigationMode::Orbit => self.handle_orbit(event, map),
+            NavigationMode::FirstPerson => self.handle_first_person(event, map),
+        }
+    }
+}
+
+/// Direction of the top of the screen in map coordinates.
+fn forward(rotation_z: f64) -> Vector2<f64> {
+    Vector2::new(rotation_z.sin(), rotation_z.cos())
+}
+
+/// Direction of the right side of the screen in map coordinates.
+fn right(rotation_z: f64) -> Vector2<f64> {
+    Vector2::new(rotation_z.cos(), -rotation_z.sin())
+}
+
+/// Returns the map point right below the camera and the altitude of the camera.
+fn eye(view: &MapView) -> Option<(Point2<f64>, f64)> {
+    let center = view.projected_position()?;
+    let distance = view.size().half_height() * view.resolution();
+    let ground = Point2::new(center.x, center.y)
+        - forward(view.rotation_z()) * distance * view.rotation_x().sin();
+
+    Some((ground, distance * view.rotation_x().cos()))
+}
+
+/// Creates a view from the camera at the given position looking in the given direction.
+fn look_from(
+    view: &MapView,
+    eye: Point2<f64>,
+    height: f64,
+    rotation_x: f64,
+    rotation_z: f64,
+) -> Option<MapView> {
+    let center = view.projected_position()?;
+    let half_height = view.size().half_height();
+    if half_height <= 0.0 || height <= 0.0 {
+        return None;
+    }
+
+    let distance = height / rotation_x.cos();
+    let new_center = eye + forward(rotation_z) * distance * rotation_x.sin();
+
+    Some(
+        view.with_rotation(rotation_x, rotation_z)
+            .with_resolution(distance / half_height)
+            .translate(Point2::new(center.x, center.y) - new_center),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::Size;
+
+    fn test_view() -> MapView {
+        MapView::new_projected(&Point2d::new(100.0, 100.0), 1.0).with_size(Size::new(200.0, 100.0))
+    }
+
+    #[test]
+    fn orbit_keeps_pivot_on_screen() {
+        let controller = CameraController::new(NavigationMode::Orbit);
+        let view = test_view();
+        let screen = Point2d::new(150.0, 70.0);
+        let pivot = view.screen_to_map(screen).unwrap();
+
+        let orbited = controller
+            .orbit(&view, Some((screen, pivot)), Vector2::new(30.0, -40.0))
+            .unwrap();
+
+        assert!(orbited.rotation_x() > 0.0);
+        assert!(orbited.rotation_z() > 0.0);
+        assert_abs_diff_eq!(
+            orbited.screen_to_map(screen).unwrap(),
+            pivot,
+            epsilon = 0.0001
+        );
+    }
+
+    #[test]
+    fn first_person_look_keeps_camera_position() {
+        let controller = CameraController::new(NavigationMode::FirstPerson);
+        let view = test_view().with_rotation(0.3, 0.5);
+        let (eye_before, height_before) = eye(&view).unwrap();
+
+        let turned = controller.look(&view, Vector2::new(40.0, -20.0)).unwrap();
+        let (eye_after, height_after) = eye(&turned).unwrap();
+
+        assert_abs_diff_eq!(eye_before, eye_after, epsilon = 0.0001);
+        assert_abs_diff_eq!(height_before, height_after, epsilon = 0.0001);
+        assert!(turned.rotation_x() > view.rotation_x());
+
+        let moved = controller.fly(&view, Vector2::new(0.0, 1.0)).unwrap();
+        let (eye_moved, _) = eye(&moved).unwrap();
+        let step = eye_moved - eye_before;
+        assert_abs_diff_eq!(step.normalize(), forward(0.5), epsilon = 0.0001);
+    }
+}
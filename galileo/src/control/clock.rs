@@ -0,0 +1,75 @@
+//! Time sources for the [`EventProcessor`](super::EventProcessor).
+
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use web_time::SystemTime;
+
+/// Source of the current time used by the [`EventProcessor`](super::EventProcessor) to recognize clicks, double clicks
+/// and other time-dependent gestures.
+pub trait Clock: MaybeSend + MaybeSync {
+    /// Current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// Clock that returns the current system time. This is the default clock of the event processor.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Clock that only changes when it is moved explicitly. Clones of the clock share the same time, so a test can keep
+/// a clone and drive the time of an event processor that owns another one.
+///
+/// ```
+/// use galileo::control::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::default();
+/// let start = clock.now();
+/// clock.advance(Duration::from_millis(100));
+/// assert_eq!(clock.now().duration_since(start).unwrap(), Duration::from_millis(100));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    time: Arc<Mutex<SystemTime>>,
+}
+
+/// Initial time of a [`ManualClock`] created with `default()`. It is far enough from `UNIX_EPOCH` for the first
+/// events not to be considered as a continuation of previous gestures.
+pub(crate) const MANUAL_CLOCK_START: Duration = Duration::from_secs(1_000_000_000);
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new(SystemTime::UNIX_EPOCH + MANUAL_CLOCK_START)
+    }
+}
+
+impl ManualClock {
+    /// Creates a new clock set to the given time.
+    pub fn new(time: SystemTime) -> Self {
+        Self {
+            time: Arc::new(Mutex::new(time)),
+        }
+    }
+
+    /// Sets the time of the clock.
+    pub fn set(&self, time: SystemTime) {
+        *self.time.lock().expect("mutex is poisoned") = time;
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.time.lock().expect("mutex is poisoned") += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.time.lock().expect("mutex is poisoned")
+    }
+}
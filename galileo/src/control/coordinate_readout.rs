@@ -0,0 +1,146 @@
+//! Display of the coordinates of the point under the mouse pointer. See [`CoordinateReadout`].
+
+use crate::control::{EventPropagation, UserEvent, UserEventHandler};
+use crate::map::Map;
+use galileo_types::geo::format::CoordinateFormat;
+use galileo_types::geo::impls::GeoPoint2d;
+use std::sync::{Arc, Mutex};
+
+/// Event handler that keeps the geographic coordinates of the point under the mouse pointer, formatted for display
+/// e.g. in the status bar of the application.
+///
+/// The controller is cheaply cloneable: add one clone to the [`EventProcessor`](crate::control::EventProcessor) and
+/// keep another to call [`CoordinateReadout::text`] every time the UI is drawn. The map is redrawn every time the text
+/// changes.
+///
+/// ```
+/// use galileo::control::{CoordinateReadout, EventProcessor};
+/// use galileo_types::geo::format::CoordinateFormat;
+///
+/// let readout = CoordinateReadout::new(CoordinateFormat::DegreesMinutesSeconds { precision: 1 });
+///
+/// let mut event_processor = EventProcessor::default();
+/// event_processor.add_handler(readout.clone());
+///
+/// // When drawing the UI:
+/// if let Some(text) = readout.text() {
+///     // draw the text in the status bar
+/// }
+///
+/// // The user switched the notation in the settings:
+/// readout.set_format(CoordinateFormat::Mgrs { digits: 5 });
+/// ```
+#[derive(Clone, Default)]
+pub struct CoordinateReadout {
+    state: Arc<Mutex<ReadoutState>>,
+}
+
+#[derive(Debug, Default)]
+struct ReadoutState {
+    format: CoordinateFormat,
+    position: Option<GeoPoint2d>,
+    text: Option<String>,
+}
+
+impl ReadoutState {
+    /// Formats the current position. Returns true if the text has changed.
+    fn update_text(&mut self) -> bool {
+        let text = self
+            .position
+            .and_then(|position| self.format.format(&position));
+        let changed = text != self.text;
+        self.text = text;
+        changed
+    }
+}
+
+impl CoordinateReadout {
+    /// Creates a new readout with the given coordinate format.
+    pub fn new(format: CoordinateFormat) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ReadoutState {
+                format,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Format of the coordinates.
+    pub fn format(&self) -> CoordinateFormat {
+        self.state.lock().expect("mutex is poisoned").format
+    }
+
+    /// Changes the format of the coordinates. The current position is reformatted immediately.
+    pub fn set_format(&self, format: CoordinateFormat) {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.format = format;
+        state.update_text();
+    }
+
+    /// Geographic position of the mouse pointer. `None` if the pointer is not over the map.
+    pub fn position(&self) -> Option<GeoPoint2d> {
+        self.state.lock().expect("mutex is poisoned").position
+    }
+
+    /// Formatted position of the mouse pointer. `None` if the pointer is not over the map, or the position cannot be
+    /// written in the selected format (e.g. polar regions in UTM).
+    pub fn text(&self) -> Option<String> {
+        self.state.lock().expect("mutex is poisoned").text.clone()
+    }
+}
+
+impl UserEventHandler for CoordinateReadout {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        if let UserEvent::PointerMoved(e) = event {
+            let mut state = self.state.lock().expect("mutex is poisoned");
+            state.position = e.geo_position();
+            if state.update_text() {
+                map.redraw();
+            }
+        }
+
+        EventPropagation::Propagate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{MouseButtonsState, MouseEvent};
+    use crate::messenger::DummyMessenger;
+    use crate::view::MapView;
+    use galileo_types::cartesian::{Point2d, Size};
+    use galileo_types::geo::Crs;
+
+    fn moved_to(map_position: Option<Point2d>) -> UserEvent {
+        UserEvent::PointerMoved(MouseEvent {
+            screen_pointer_position: Point2d::new(50.0, 50.0),
+            physical_pointer_position: Point2d::new(50.0, 50.0),
+            map_pointer_position: map_position,
+            map_crs: Crs::EPSG3857,
+            buttons: MouseButtonsState::default(),
+            motion: Default::default(),
+            pointer_type: Default::default(),
+        })
+    }
+
+    #[test]
+    fn readout_follows_pointer() {
+        let mut map = Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0)),
+            vec![],
+            None::<DummyMessenger>,
+        );
+        let readout = CoordinateReadout::new(CoordinateFormat::DecimalDegrees { precision: 2 });
+
+        readout.handle(&moved_to(Some(Point2d::new(0.0, 0.0))), &mut map);
+        assert_eq!(readout.text().as_deref(), Some("0.00, 0.00"));
+
+        readout.set_format(CoordinateFormat::Mgrs { digits: 5 });
+        assert_eq!(readout.text().as_deref(), Some("31N AA 66021 00000"));
+
+        readout.handle(&moved_to(None), &mut map);
+        assert_eq!(readout.text(), None);
+        assert_eq!(readout.position(), None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Rotation of the map to keep the direction of movement pointing up. See [`CourseUpController`].
+
+use crate::control::{Clock, SystemClock};
+use crate::map::Map;
+use std::f64::consts::{PI, TAU};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use web_time::SystemTime;
+
+/// Difference in radians between the current and the target rotation below which the rotation is considered finished.
+const ROTATION_EPSILON: f64 = 1e-4;
+
+/// Keeps the map rotated so that the given heading (e.g. GPS bearing of a vehicle) points to the top of the screen,
+/// for turn-by-turn style applications.
+///
+/// The controller has two modes:
+/// * course-up - the map is rotated so that the heading points up;
+/// * north-up - the map is rotated back so that north points up.
+///
+/// Changes of the heading and switching between the modes are applied gradually with exponential smoothing, so noisy
+/// GPS bearings do not make the map shake, and toggling the mode produces a short transition instead of a jump.
+///
+/// The controller changes the map only in [`CourseUpController::update`], which should be called before every frame,
+/// like [`Map::animate`]. Clones of the controller share their state, so one clone can be given to the code receiving
+/// the position updates and another one to the render loop.
+///
+/// ```
+/// use galileo::control::CourseUpController;
+///
+/// let course_up = CourseUpController::default();
+/// course_up.set_heading(90.0);
+/// course_up.set_course_up(true);
+///
+/// // In the render loop:
+/// // course_up.update(&mut map);
+///
+/// // The user pressed the compass button:
+/// course_up.set_course_up(false);
+/// ```
+#[derive(Clone)]
+pub struct CourseUpController {
+    state: Arc<Mutex<CourseUpState>>,
+    clock: Arc<dyn Clock>,
+    smoothing: Duration,
+}
+
+#[derive(Debug, Default)]
+struct CourseUpState {
+    course_up: bool,
+    heading: f64,
+    last_update: Option<SystemTime>,
+}
+
+impl Default for CourseUpController {
+    fn default() -> Self {
+        Self {
+            state: Default::default(),
+            clock: Arc::new(SystemClock),
+            smoothing: Duration::from_millis(300),
+        }
+    }
+}
+
+impl CourseUpController {
+    /// Sets the time constant of the smoothing: after this time the map makes about 63% of the turn to the new
+    /// rotation. Zero duration disables smoothing. Default is 300 ms.
+    pub fn with_smoothing(mut self, smoothing: Duration) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    /// Sets the clock used to calculate the smoothing.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Sets the heading in degrees clockwise from north.
+    pub fn set_heading(&self, heading: f64) {
+        if heading.is_finite() {
+            self.state.lock().expect("mutex is poisoned").heading = heading.to_radians();
+        }
+    }
+
+    /// Current heading in degrees clockwise from north, in the range `[0, 360)`.
+    pub fn heading(&self) -> f64 {
+        self.state
+            .lock()
+            .expect("mutex is poisoned")
+            .heading
+            .rem_euclid(TAU)
+            .to_degrees()
+    }
+
+    /// Switches between course-up (`true`) and north-up (`false`) modes.
+    pub fn set_course_up(&self, course_up: bool) {
+        self.state.lock().expect("mutex is poisoned").course_up = course_up;
+    }
+
+    /// Switches to the other mode. Returns true if the new mode is course-up.
+    pub fn toggle(&self) -> bool {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.course_up = !state.course_up;
+        state.course_up
+    }
+
+    /// Returns true if the controller is in the course-up mode.
+    pub fn is_course_up(&self) -> bool {
+        self.state.lock().expect("mutex is poisoned").course_up
+    }
+
+    /// Rotates the map towards the target rotation. Returns true if the map has not reached the target rotation yet
+    /// and `update` should be called again on the next frame.
+    pub fn update(&self, map: &mut Map) -> bool {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let elapsed = state
+            .last_update
+            .and_then(|last| now.duration_since(last).ok())
+            .unwrap_or_default();
+        state.last_update = Some(now);
+
+        let target = if state.course_up { state.heading } else { 0.0 };
+        let current = map.view().rotation_z();
+        let difference = shortest_turn(current, target);
+        if difference.abs() < ROTATION_EPSILON {
+            if current != target {
+                map.set_view(map.view().with_rotation_z(target.rem_euclid(TAU)));
+            }
+            state.last_update = None;
+            return false;
+        }
+
+        let k = if self.smoothing.is_zero() {
+            1.0
+        } else {
+            1.0 - (-elapsed.as_secs_f64() / self.smoothing.as_secs_f64()).exp()
+        };
+        let rotation = (current + difference * k).rem_euclid(TAU);
+        map.set_view(map.view().with_rotation_z(rotation));
+
+        true
+    }
+}
+
+/// Signed angle of the shortest turn from `from` to `to`, in the range `[-PI, PI)`.
+fn shortest_turn(from: f64, to: f64) -> f64 {
+    (to - from + PI).rem_euclid(TAU) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::ManualClock;
+    use crate::messenger::DummyMessenger;
+    use crate::view::MapView;
+    use galileo_types::cartesian::Point2d;
+
+    #[test]
+    fn rotates_to_heading_and_back() {
+        let mut map = Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0),
+            vec![],
+            None::<DummyMessenger>,
+        );
+        let clock = ManualClock::default();
+        let controller = CourseUpController::default()
+            .with_smoothing(Duration::from_millis(100))
+            .with_clock(clock.clone());
+
+        controller.set_heading(350.0);
+        controller.set_course_up(true);
+        assert!(controller.update(&mut map));
+        assert_eq!(map.view().rotation_z(), 0.0);
+
+        // Turns through north instead of going around.
+        clock.advance(Duration::from_millis(100));
+        assert!(controller.update(&mut map));
+        let rotation = map.view().rotation_z().to_degrees();
+        assert!(rotation > 353.0 && rotation < 354.0, "{rotation}");
+
+        for _ in 0..20 {
+            clock.advance(Duration::from_millis(100));
+            controller.update(&mut map);
+        }
+        assert!(!controller.update(&mut map));
+        assert!((map.view().rotation_z().to_degrees() - 350.0).abs() < 1e-6);
+
+        assert!(!controller.toggle());
+        for _ in 0..20 {
+            clock.advance(Duration::from_millis(100));
+            controller.update(&mut map);
+        }
+        assert_eq!(map.view().rotation_z(), 0.0);
+    }
+}
@@ -1,9 +1,11 @@
+use crate::control::motion::MotionTracker;
 use crate::control::{
-    EventPropagation, MouseButton, MouseButtonsState, MouseEvent, RawUserEvent, TouchId, UserEvent,
-    UserEventHandler,
+    Clock, EventPropagation, InteractionRecorder, InteractionRecording, MouseButton,
+    MouseButtonsState, MouseEvent, PointerMotion, PointerType, RawUserEvent, SystemClock, TouchId,
+    UserEvent, UserEventHandler,
 };
 use crate::map::Map;
-use galileo_types::cartesian::{CartesianPoint2d, Point2d};
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
 use web_time::SystemTime;
 
 const DRAG_THRESHOLD: f64 = 3.0;
@@ -15,24 +17,40 @@ struct TouchInfo {
     start_position: Point2d,
     _start_time: SystemTime,
     prev_position: Point2d,
+    motion: MotionTracker,
 }
 
+/// Id of a UI region registered with [`EventProcessor::add_ui_region`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct UiRegionId(u64);
+
 /// Stores input state, converts [`RawUserEvent`] into [`UserEvent`] and manages a list of event handlers.
 ///
 /// When an even is called, the `EventProcessor` will go through event handlers one by one until a handler returns
 /// [`EventPropagation::Consume`] or [`EventPropagation::Stop`]. At this point the event is considered to be handled.
+///
+/// Parts of the screen covered by on-map widgets (compass, zoom buttons, attribution etc.) can be registered as UI
+/// regions with [`EventProcessor::add_ui_region`]. Gestures started in these regions are not turned into clicks,
+/// drags or zooms of the map.
 pub struct EventProcessor {
     handlers: Vec<Box<dyn UserEventHandler>>,
     pointer_position: Point2d,
     pointer_pressed_position: Point2d,
+    pointer_motion: MotionTracker,
     touches: Vec<TouchInfo>,
 
     buttons_state: MouseButtonsState,
 
     last_pressed_time: SystemTime,
     last_click_time: SystemTime,
+    event_time: SystemTime,
 
     drag_target: Option<usize>,
+    ui_regions: Vec<(UiRegionId, Rect)>,
+    next_ui_region_id: u64,
+    pressed_in_ui: bool,
+    recording: Option<(SystemTime, InteractionRecorder)>,
+    clock: Box<dyn Clock>,
 }
 
 impl Default for EventProcessor {
@@ -41,37 +59,120 @@ impl Default for EventProcessor {
             handlers: vec![],
             pointer_position: Default::default(),
             pointer_pressed_position: Default::default(),
+            pointer_motion: Default::default(),
             touches: Vec::new(),
             buttons_state: Default::default(),
             last_pressed_time: SystemTime::UNIX_EPOCH,
             last_click_time: SystemTime::UNIX_EPOCH,
+            event_time: SystemTime::UNIX_EPOCH,
             drag_target: None,
+            ui_regions: Vec::new(),
+            next_ui_region_id: 0,
+            pressed_in_ui: false,
+            recording: None,
+            clock: Box::new(SystemClock),
         }
     }
 }
 
 impl EventProcessor {
+    /// Creates a new processor that takes the time of the events from the given clock.
+    ///
+    /// By default the system time is used. A [`ManualClock`](crate::control::ManualClock) can be used to drive the
+    /// time explicitly in tests.
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Box::new(clock),
+            ..Default::default()
+        }
+    }
+
+    /// Replaces the clock of the processor.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
     /// Adds a new handler to the end of the handler list.
     pub fn add_handler(&mut self, handler: impl UserEventHandler + 'static) {
         self.handlers.push(Box::new(handler));
     }
 
+    /// Registers a screen area (in logical pixels) covered by an on-map UI widget, and returns the id of the region.
+    ///
+    /// Mouse presses and touches that start in a UI region are not interpreted as map gestures: they produce no
+    /// clicks, drags or pinch zooms, even if the pointer then leaves the region. Scrolling over a UI region does not
+    /// zoom the map. [`UserEvent::ButtonPressed`], [`UserEvent::ButtonReleased`] and [`UserEvent::PointerMoved`]
+    /// events are still emitted.
+    pub fn add_ui_region(&mut self, region: Rect) -> UiRegionId {
+        let id = UiRegionId(self.next_ui_region_id);
+        self.next_ui_region_id += 1;
+        self.ui_regions.push((id, region));
+        id
+    }
+
+    /// Moves or resizes the UI region, e.g. when the window is resized. Does nothing if the region was removed.
+    pub fn set_ui_region(&mut self, id: UiRegionId, region: Rect) {
+        if let Some((_, rect)) = self.ui_regions.iter_mut().find(|(v, _)| *v == id) {
+            *rect = region;
+        }
+    }
+
+    /// Removes the UI region.
+    pub fn remove_ui_region(&mut self, id: UiRegionId) {
+        self.ui_regions.retain(|(v, _)| *v != id);
+    }
+
+    /// Removes all the UI regions.
+    pub fn clear_ui_regions(&mut self) {
+        self.ui_regions.clear();
+    }
+
+    /// Returns true if the given screen position (in logical pixels) is inside one of the UI regions.
+    pub fn is_over_ui(&self, position: Point2d) -> bool {
+        self.ui_regions
+            .iter()
+            .any(|(_, region)| region.contains(&position))
+    }
+
+    /// Starts recording all the events given to the processor. If a recording is already in progress, it is discarded.
+    pub fn start_recording(&mut self) {
+        self.recording = Some((self.clock.now(), InteractionRecorder::new()));
+    }
+
+    /// Stops recording and returns the recorded events. Returns `None` if no recording was in progress.
+    pub fn stop_recording(&mut self) -> Option<InteractionRecording> {
+        self.recording.take().map(|(_, recorder)| recorder.finish())
+    }
+
     /// Handles the event.
     pub fn handle(&mut self, event: RawUserEvent, map: &mut Map) {
-        if let Some(user_events) = self.process(event) {
+        let now = self.clock.now();
+        if let Some((start, recorder)) = &mut self.recording {
+            recorder.record_at(
+                event.clone(),
+                now.duration_since(*start).unwrap_or_default(),
+            );
+        }
+
+        self.handle_at(event, map, now);
+    }
+
+    /// Handles the event as if it was received at the given `time`, ignoring the clock of the processor. This is used
+    /// to replay recorded events, see [`InteractionPlayer`](crate::control::InteractionPlayer).
+    pub fn handle_at(&mut self, event: RawUserEvent, map: &mut Map, time: SystemTime) {
+        if let Some(user_events) = self.process(event, time, map) {
             for user_event in user_events {
                 let mut drag_start_target = None;
 
                 if let UserEvent::Click(
                     _,
                     MouseEvent {
-                        screen_pointer_position,
+                        map_pointer_position,
                         ..
                     },
-                ) = user_event
+                ) = &user_event
                 {
-                    let map_position = map.view().screen_to_map(screen_pointer_position);
-                    log::info!("click position: {map_position:?}");
+                    log::info!("click position: {map_pointer_position:?}");
                 }
 
                 for (index, handler) in self.handlers.iter_mut().enumerate() {
@@ -109,39 +210,52 @@ impl EventProcessor {
         }
     }
 
-    fn process(&mut self, event: RawUserEvent) -> Option<Vec<UserEvent>> {
-        let now = SystemTime::now();
+    fn process(
+        &mut self,
+        event: RawUserEvent,
+        now: SystemTime,
+        map: &Map,
+    ) -> Option<Vec<UserEvent>> {
+        self.event_time = now;
         match event {
             RawUserEvent::ButtonPressed(button) => {
                 self.buttons_state.set_pressed(button);
                 self.last_pressed_time = now;
                 self.pointer_pressed_position = self.pointer_position;
+                self.pressed_in_ui = self.is_over_ui(self.pointer_position);
+
+                // Movement of the pointer before the button was pressed is not a part of the drag.
+                self.pointer_motion.reset();
+                self.pointer_motion.push(now, self.pointer_position);
 
                 Some(vec![UserEvent::ButtonPressed(
                     button,
-                    self.get_mouse_event(),
+                    self.get_mouse_event(map),
                 )])
             }
             RawUserEvent::ButtonReleased(button) => {
                 self.buttons_state.set_released(button);
-                let mut events = vec![UserEvent::ButtonReleased(button, self.get_mouse_event())];
+                let mut events = vec![UserEvent::ButtonReleased(button, self.get_mouse_event(map))];
 
-                if (now.duration_since(self.last_pressed_time)).unwrap_or_default() < CLICK_TIMEOUT
+                if !self.pressed_in_ui
+                    && (now.duration_since(self.last_pressed_time)).unwrap_or_default()
+                        < CLICK_TIMEOUT
                 {
                     log::info!("click position: {:?}", self.pointer_position);
-                    events.push(UserEvent::Click(button, self.get_mouse_event()));
+                    events.push(UserEvent::Click(button, self.get_mouse_event(map)));
 
                     if (now.duration_since(self.last_click_time)).unwrap_or_default()
                         < DBL_CLICK_TIMEOUT
                     {
-                        events.push(UserEvent::DoubleClick(button, self.get_mouse_event()));
+                        events.push(UserEvent::DoubleClick(button, self.get_mouse_event(map)));
                     }
 
                     self.last_click_time = now;
+                }
 
-                    if self.drag_target.take().is_some() {
-                        events.push(UserEvent::DragEnded(button, self.get_mouse_event()));
-                    }
+                // The drag target is reset after the event is delivered to it.
+                if self.drag_target.is_some() {
+                    events.push(UserEvent::DragEnded(button, self.get_mouse_event(map)));
                 }
 
                 Some(events)
@@ -149,9 +263,14 @@ impl EventProcessor {
             RawUserEvent::PointerMoved(position) => {
                 let prev_position = self.pointer_position;
                 self.pointer_position = position;
+                self.pointer_motion.push(now, position);
 
-                let mut events = vec![UserEvent::PointerMoved(self.get_mouse_event())];
-                if let Some(button) = self.buttons_state.single_pressed() {
+                let mut events = vec![UserEvent::PointerMoved(self.get_mouse_event(map))];
+                if let Some(button) = self
+                    .buttons_state
+                    .single_pressed()
+                    .filter(|_| !self.pressed_in_ui)
+                {
                     let mut is_dragging = self.drag_target.is_some();
                     if self.drag_target.is_none()
                         && position.taxicab_distance(&self.pointer_pressed_position)
@@ -159,7 +278,11 @@ impl EventProcessor {
                     {
                         events.push(UserEvent::DragStarted(
                             button,
-                            self.get_mouse_event_pos(self.pointer_pressed_position),
+                            self.get_mouse_event_pos(
+                                self.pointer_pressed_position,
+                                PointerMotion::default(),
+                                map,
+                            ),
                         ));
 
                         is_dragging = true;
@@ -169,7 +292,7 @@ impl EventProcessor {
                         events.push(UserEvent::Drag(
                             button,
                             self.pointer_position - prev_position,
-                            self.get_mouse_event(),
+                            self.get_mouse_event(map),
                         ));
                     }
                 }
@@ -177,7 +300,11 @@ impl EventProcessor {
                 Some(events)
             }
             RawUserEvent::Scroll(delta) => {
-                Some(vec![UserEvent::Scroll(delta, self.get_mouse_event())])
+                if self.is_over_ui(self.pointer_position) {
+                    return None;
+                }
+
+                Some(vec![UserEvent::Scroll(delta, self.get_mouse_event(map))])
             }
             RawUserEvent::TouchStart(touch) => {
                 for i in 0..self.touches.len() {
@@ -188,20 +315,29 @@ impl EventProcessor {
                     }
                 }
 
+                // Touches in UI regions are not tracked, so they never become a part of a map gesture.
+                if self.is_over_ui(touch.position) {
+                    return None;
+                }
+
+                let mut motion = MotionTracker::default();
+                motion.push(now, touch.position);
+
                 self.touches.push(TouchInfo {
                     id: touch.touch_id,
                     start_position: touch.position,
                     _start_time: now,
                     prev_position: touch.position,
+                    motion,
                 });
 
                 None
             }
             RawUserEvent::TouchMove(touch) => {
-                let Some(touch_info) = self.touches.iter().find(|t| t.id == touch.touch_id) else {
-                    return None;
-                };
+                let index = self.touches.iter().position(|t| t.id == touch.touch_id)?;
                 let position = touch.position;
+                self.touches[index].motion.push(now, position);
+                let touch_info = &self.touches[index];
 
                 let mut events = vec![];
 
@@ -212,7 +348,11 @@ impl EventProcessor {
                     {
                         events.push(UserEvent::DragStarted(
                             MouseButton::Other,
-                            self.get_mouse_event_pos(touch_info.start_position),
+                            self.get_touch_event(
+                                touch_info.start_position,
+                                PointerMotion::default(),
+                                map,
+                            ),
                         ));
 
                         is_dragging = true
@@ -222,7 +362,7 @@ impl EventProcessor {
                         events.push(UserEvent::Drag(
                             MouseButton::Other,
                             position - touch_info.prev_position,
-                            self.get_mouse_event_pos(position),
+                            self.get_touch_event(position, touch_info.motion.motion(now), map),
                         ));
                     }
                 } else if self.touches.len() == 2 {
@@ -249,9 +389,12 @@ impl EventProcessor {
                 Some(events)
             }
             RawUserEvent::TouchEnd(touch) => {
+                let mut motion = PointerMotion::default();
                 for i in 0..self.touches.len() {
                     if self.touches[i].id == touch.touch_id {
-                        self.touches.remove(i);
+                        let mut touch_info = self.touches.remove(i);
+                        touch_info.motion.push(now, touch.position);
+                        motion = touch_info.motion.motion(now);
                         break;
                     }
                 }
@@ -259,26 +402,268 @@ impl EventProcessor {
                 let mut events = vec![];
 
                 if self.drag_target.is_some() && self.touches.is_empty() {
-                    self.drag_target = None;
                     events.push(UserEvent::DragEnded(
                         MouseButton::Other,
-                        self.get_mouse_event_pos(touch.position),
+                        self.get_touch_event(touch.position, motion, map),
                     ));
                 }
 
                 Some(events)
             }
+            RawUserEvent::KeyPressed(key) => Some(vec![UserEvent::KeyPressed(key)]),
+            RawUserEvent::KeyReleased(key) => Some(vec![UserEvent::KeyReleased(key)]),
         }
     }
 
-    fn get_mouse_event(&self) -> MouseEvent {
-        self.get_mouse_event_pos(self.pointer_position)
+    fn get_mouse_event(&self, map: &Map) -> MouseEvent {
+        self.get_mouse_event_pos(
+            self.pointer_position,
+            self.pointer_motion.motion(self.event_time),
+            map,
+        )
     }
 
-    fn get_mouse_event_pos(&self, screen_pointer_position: Point2d) -> MouseEvent {
+    fn get_mouse_event_pos(
+        &self,
+        screen_pointer_position: Point2d,
+        motion: PointerMotion,
+        map: &Map,
+    ) -> MouseEvent {
+        let scale_factor = map.scale_factor();
         MouseEvent {
             screen_pointer_position,
+            physical_pointer_position: screen_pointer_position * scale_factor,
+            map_pointer_position: map.view().screen_to_map(screen_pointer_position),
+            map_crs: map.view().crs().clone(),
             buttons: self.buttons_state,
+            motion,
+            pointer_type: PointerType::Mouse,
+        }
+    }
+
+    fn get_touch_event(
+        &self,
+        screen_pointer_position: Point2d,
+        motion: PointerMotion,
+        map: &Map,
+    ) -> MouseEvent {
+        MouseEvent {
+            pointer_type: PointerType::Touch,
+            ..self.get_mouse_event_pos(screen_pointer_position, motion, map)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{ManualClock, MapController, ScrollDelta};
+    use crate::messenger::DummyMessenger;
+    use crate::view::MapView;
+    use galileo_types::cartesian::Size;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn test_map() -> Map {
+        Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0),
+            vec![],
+            None::<DummyMessenger>,
+        )
+    }
+
+    fn processor_with_log(clock: &ManualClock) -> (EventProcessor, Arc<Mutex<Vec<String>>>) {
+        let log = Arc::new(Mutex::new(vec![]));
+        let log_clone = log.clone();
+
+        let mut processor = EventProcessor::with_clock(clock.clone());
+        processor.add_handler(move |event: &UserEvent, _map: &mut Map| {
+            let name = match event {
+                UserEvent::Click(..) => "click",
+                UserEvent::DoubleClick(..) => "double_click",
+                _ => return EventPropagation::Propagate,
+            };
+            log_clone.lock().unwrap().push(name.to_string());
+            EventPropagation::Propagate
+        });
+
+        (processor, log)
+    }
+
+    fn click(processor: &mut EventProcessor, map: &mut Map, clock: &ManualClock, hold_ms: u64) {
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), map);
+        clock.advance(Duration::from_millis(hold_ms));
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), map);
+    }
+
+    #[test]
+    fn short_press_is_click() {
+        let clock = ManualClock::default();
+        let (mut processor, log) = processor_with_log(&clock);
+        let mut map = test_map();
+
+        click(&mut processor, &mut map, &clock, 100);
+        assert_eq!(*log.lock().unwrap(), vec!["click"]);
+
+        clock.advance(Duration::from_secs(1));
+        click(&mut processor, &mut map, &clock, 300);
+        assert_eq!(*log.lock().unwrap(), vec!["click"]);
+    }
+
+    #[test]
+    fn double_click_depends_on_interval() {
+        let clock = ManualClock::default();
+        let (mut processor, log) = processor_with_log(&clock);
+        let mut map = test_map();
+
+        click(&mut processor, &mut map, &clock, 50);
+        clock.advance(Duration::from_millis(200));
+        click(&mut processor, &mut map, &clock, 50);
+        assert_eq!(*log.lock().unwrap(), vec!["click", "click", "double_click"]);
+
+        log.lock().unwrap().clear();
+        clock.advance(Duration::from_millis(600));
+        click(&mut processor, &mut map, &clock, 50);
+        assert_eq!(*log.lock().unwrap(), vec!["click"]);
+    }
+
+    #[test]
+    fn touchpad_scroll_zooms_immediately() {
+        let mut processor = EventProcessor::default();
+        processor.add_handler(MapController::default());
+        let mut map = test_map();
+        map.set_size(Size::new(100.0, 100.0));
+
+        processor.handle(
+            RawUserEvent::Scroll(ScrollDelta::Pixels(ScrollDelta::PIXELS_PER_LINE)),
+            &mut map,
+        );
+        assert!((map.view().resolution() - 1.0 / 1.2).abs() < 1e-9);
+
+        // Wheel scroll is animated, so only the target view changes right away.
+        processor.handle(RawUserEvent::Scroll(ScrollDelta::Lines(1.0)), &mut map);
+        assert!((map.view().resolution() - 1.0 / 1.2).abs() < 1e-9);
+        assert!((map.target_view().resolution() - 1.0 / 1.44).abs() < 1e-9);
+    }
+
+    #[test]
+    fn drag_reports_pointer_velocity() {
+        let clock = ManualClock::default();
+        let mut processor = EventProcessor::with_clock(clock.clone());
+        let motions = Arc::new(Mutex::new(vec![]));
+        let motions_clone = motions.clone();
+        processor.add_handler(move |event: &UserEvent, _map: &mut Map| match event {
+            UserEvent::DragStarted(..) => EventPropagation::Consume,
+            UserEvent::Drag(_, _, e) | UserEvent::DragEnded(_, e) => {
+                motions_clone.lock().unwrap().push(e.motion);
+                EventPropagation::Consume
+            }
+            _ => EventPropagation::Propagate,
+        });
+        let mut map = test_map();
+
+        processor.handle(RawUserEvent::PointerMoved(Point2d::new(0.0, 0.0)), &mut map);
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+        for i in 1..=5 {
+            clock.advance(Duration::from_millis(10));
+            processor.handle(
+                RawUserEvent::PointerMoved(Point2d::new(i as f64 * 10.0, 0.0)),
+                &mut map,
+            );
+        }
+        clock.advance(Duration::from_millis(10));
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), &mut map);
+
+        let motions = motions.lock().unwrap();
+        assert_eq!(motions.len(), 6);
+        let released = motions.last().unwrap();
+        assert!((released.velocity.x - 1000.0).abs() < 1e-6);
+        assert!(released.velocity.y.abs() < 1e-6);
+        assert!(released.acceleration.magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn mouse_event_has_all_coordinate_spaces() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let events_clone = events.clone();
+        let mut processor = EventProcessor::default();
+        processor.add_handler(move |event: &UserEvent, _map: &mut Map| {
+            if let UserEvent::PointerMoved(e) = event {
+                events_clone.lock().unwrap().push(e.clone());
+            }
+            EventPropagation::Propagate
+        });
+        let mut map = test_map();
+        map.set_size(Size::new(100.0, 100.0));
+        map.set_scale_factor(2.0);
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(60.0, 50.0)),
+            &mut map,
+        );
+
+        let events = events.lock().unwrap();
+        let event = &events[0];
+        assert_eq!(event.screen_pointer_position, Point2d::new(60.0, 50.0));
+        assert_eq!(event.physical_pointer_position, Point2d::new(120.0, 100.0));
+
+        let map_position = event.map_pointer_position.unwrap();
+        assert!((map_position.x - 10.0).abs() < 1e-9);
+        assert!(map_position.y.abs() < 1e-9);
+        assert_eq!(event.position_in_crs(map.view().crs()), Some(map_position));
+        assert!(event.geo_position().is_some());
+    }
+
+    #[test]
+    fn gestures_in_ui_regions_are_ignored() {
+        let clock = ManualClock::default();
+        let (mut processor, log) = processor_with_log(&clock);
+        processor.add_handler(MapController::default());
+        let mut map = test_map();
+        map.set_size(Size::new(100.0, 100.0));
+        let region = processor.add_ui_region(Rect::new(0.0, 0.0, 20.0, 20.0));
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(10.0, 10.0)),
+            &mut map,
+        );
+        click(&mut processor, &mut map, &clock, 50);
+        assert!(log.lock().unwrap().is_empty());
+
+        let view = map.view().clone();
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(50.0, 50.0)),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), &mut map);
+        assert_eq!(*map.view(), view);
+
+        processor.remove_ui_region(region);
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(10.0, 10.0)),
+            &mut map,
+        );
+        clock.advance(Duration::from_secs(1));
+        click(&mut processor, &mut map, &clock, 50);
+        assert_eq!(*log.lock().unwrap(), vec!["click"]);
+    }
+
+    #[test]
+    fn recording_uses_clock() {
+        let clock = ManualClock::default();
+        let (mut processor, _) = processor_with_log(&clock);
+        let mut map = test_map();
+
+        processor.start_recording();
+        clock.advance(Duration::from_millis(10));
+        click(&mut processor, &mut map, &clock, 100);
+
+        let recording = processor.stop_recording().unwrap();
+        let times: Vec<_> = recording.events().iter().map(|e| e.time).collect();
+        assert_eq!(
+            times,
+            vec![Duration::from_millis(10), Duration::from_millis(110)]
+        );
+    }
+}
@@ -1,12 +1,24 @@
-use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
-use crate::map::Map;
+use crate::control::{
+    EventPropagation, InteractionConfig, MouseButton, ScrollDelta, UserEvent, UserEventHandler,
+};
+use crate::map::{Easing, Map};
 use crate::view::MapView;
 use nalgebra::Vector2;
 use std::time::Duration;
 
 const DEFAULT_ZOOM_DURATION: Duration = Duration::from_millis(50);
 
+/// Pointer speed (pixels per second) below which a released pan does not continue with inertia.
+const MIN_INERTIA_SPEED: f64 = 50.0;
+/// Pointer speed (pixels per second) above which inertia does not accelerate the map further.
+const MAX_INERTIA_SPEED: f64 = 5000.0;
+/// Time constant of the inertia movement: the map travels the distance the pointer would travel during this time.
+const INERTIA_TIME: f64 = 0.25;
+
 /// Event handler of a map, providing panning, zooming and tilting capabilities.
+///
+/// The set of enabled gestures and the zoom limits can be set with an [`InteractionConfig`], see
+/// [`MapController::from_config`].
 #[derive(Default)]
 pub struct MapController {
     parameters: MapControllerParameters,
@@ -20,17 +32,34 @@ pub struct MapControllerParameters {
 
     rotation_speed: f64,
     max_rotation_x: f64,
+
+    pan: bool,
+    scroll_zoom: bool,
+    pinch_zoom: bool,
+    rotate: bool,
+    inertia: bool,
 }
 
 impl Default for MapControllerParameters {
     fn default() -> Self {
+        Self::from(&InteractionConfig::default())
+    }
+}
+
+impl From<&InteractionConfig> for MapControllerParameters {
+    fn from(config: &InteractionConfig) -> Self {
         Self {
             zoom_duration: DEFAULT_ZOOM_DURATION,
-            zoom_speed: 0.2,
-            max_resolution: 156543.03392800014 / 8.0,
-            min_resolution: 156543.03392800014 / 8.0 / 2.0f64.powi(16),
+            zoom_speed: config.zoom_speed,
+            max_resolution: config.max_resolution,
+            min_resolution: config.min_resolution,
             rotation_speed: 0.005,
-            max_rotation_x: 80f64.to_radians(),
+            max_rotation_x: config.max_tilt.to_radians(),
+            pan: config.pan,
+            scroll_zoom: config.scroll_zoom,
+            pinch_zoom: config.pinch_zoom,
+            rotate: config.rotate,
+            inertia: config.inertia,
         }
     }
 }
@@ -38,11 +67,9 @@ impl Default for MapControllerParameters {
 impl UserEventHandler for MapController {
     fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
         match event {
-            UserEvent::DragStarted(button, _)
-                if *button == MouseButton::Left
-                    || *button == MouseButton::Right
-                    || *button == MouseButton::Other =>
-            {
+            UserEvent::DragStarted(button, _) if self.is_drag_enabled(*button) => {
+                // The user catches the map moving with inertia.
+                map.stop_animation();
                 EventPropagation::Consume
             }
             UserEvent::Drag(button, delta, e) => match button {
@@ -62,17 +89,48 @@ impl UserEventHandler for MapController {
                 }
                 _ => EventPropagation::Propagate,
             },
-            UserEvent::Scroll(delta, mouse_event) => {
-                let zoom = self.get_zoom(*delta, map.view().resolution());
-                let target = map
-                    .target_view()
-                    .zoom(zoom, mouse_event.screen_pointer_position);
-                map.animate_to(target, self.parameters.zoom_duration);
+            UserEvent::DragEnded(MouseButton::Left | MouseButton::Other, e)
+                if self.parameters.inertia =>
+            {
+                let speed = e.motion.speed();
+                if speed > MIN_INERTIA_SPEED {
+                    let velocity = e.motion.velocity * (speed.min(MAX_INERTIA_SPEED) / speed);
+                    let position = e.screen_pointer_position;
+                    let target = map
+                        .view()
+                        .translate_by_pixels(position, position + velocity * INERTIA_TIME);
+
+                    // With the cubic ease-out the initial speed of the animation is equal to the speed of the pointer.
+                    map.animate_to_with_easing(
+                        target,
+                        Duration::from_secs_f64(INERTIA_TIME * 3.0),
+                        Easing::EaseOut,
+                    );
+                }
 
                 EventPropagation::Stop
             }
-            UserEvent::Zoom(zoom, center) => {
-                let target = map.view().zoom(*zoom, *center);
+            UserEvent::Scroll(delta, mouse_event) if self.parameters.scroll_zoom => {
+                let position = mouse_event.screen_pointer_position;
+                match delta {
+                    // Touchpads send many small deltas, so the view follows the fingers without animation.
+                    ScrollDelta::Pixels(_) => {
+                        let zoom = self.get_zoom(delta.lines(), map.view().resolution());
+                        let target = map.view().zoom(zoom, position);
+                        map.set_view(target);
+                    }
+                    ScrollDelta::Lines(lines) => {
+                        let zoom = self.get_zoom(*lines, map.target_view().resolution());
+                        let target = map.target_view().zoom(zoom, position);
+                        map.animate_to(target, self.parameters.zoom_duration);
+                    }
+                }
+
+                EventPropagation::Stop
+            }
+            UserEvent::Zoom(zoom, center) if self.parameters.pinch_zoom => {
+                let zoom = self.clamp_zoom(*zoom, map.view().resolution());
+                let target = map.view().zoom(zoom, *center);
                 map.set_view(target);
 
                 EventPropagation::Stop
@@ -83,8 +141,27 @@ impl UserEventHandler for MapController {
 }
 
 impl MapController {
+    /// Creates a controller with the gestures and zoom limits of the given configuration.
+    pub fn from_config(config: &InteractionConfig) -> Self {
+        Self {
+            parameters: config.into(),
+        }
+    }
+
+    fn is_drag_enabled(&self, button: MouseButton) -> bool {
+        match button {
+            MouseButton::Left | MouseButton::Other => self.parameters.pan,
+            MouseButton::Right => self.parameters.rotate,
+            MouseButton::Middle => false,
+        }
+    }
+
     fn get_zoom(&self, delta: f64, current_resolution: f64) -> f64 {
         let zoom = (self.parameters.zoom_speed + 1.0).powf(-delta);
+        self.clamp_zoom(zoom, current_resolution)
+    }
+
+    fn clamp_zoom(&self, zoom: f64, current_resolution: f64) -> f64 {
         let target_resolution = current_resolution * zoom;
         if target_resolution > self.parameters.max_resolution {
             self.parameters.max_resolution / current_resolution
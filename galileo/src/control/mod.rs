@@ -13,14 +13,34 @@
 
 use crate::map::Map;
 use galileo_types::cartesian::Point2d;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, Projection};
 use maybe_sync::{MaybeSend, MaybeSync};
 use nalgebra::Vector2;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+mod camera;
+mod clock;
+mod coordinate_readout;
+mod course_up;
 mod event_processor;
 mod map;
+mod motion;
+mod profile;
+mod recorder;
+mod tooltip;
 
-pub use event_processor::EventProcessor;
+pub use camera::{CameraController, NavigationMode};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use coordinate_readout::CoordinateReadout;
+pub use course_up::CourseUpController;
+pub use event_processor::{EventProcessor, UiRegionId};
 pub use map::MapController;
+pub use motion::PointerMotion;
+pub use profile::{InteractionConfig, InteractionProfile};
+pub use recorder::{InteractionPlayer, InteractionRecorder, InteractionRecording, RecordedEvent};
+pub use tooltip::{Tooltip, TooltipController, TooltipTemplate};
 
 /// User input handler.
 pub trait UserEventHandler {
@@ -41,6 +61,8 @@ where
 /// by the application. It does not provide any state information, as not all supported platforms give this information
 /// together with the event. Instead, the input state information is stored in the [`EventProcessor`] struct, which
 /// can combine `RawUserEvent` with the state to produce [`UserEvent`] which is then given to the application.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RawUserEvent {
     /// A mouse button was pressed.
     ButtonPressed(MouseButton),
@@ -48,15 +70,19 @@ pub enum RawUserEvent {
     ButtonReleased(MouseButton),
     /// Mouse pointer was moved to the given screen pixel position.
     PointerMoved(Point2d),
-    /// Scroll was called (by a mouse wheel or touch pad scrolling). The number is the number of lines that the event
-    /// would scroll if it was scrolling a text.
-    Scroll(f64),
+    /// Scroll was called by a mouse wheel or touchpad scrolling.
+    Scroll(ScrollDelta),
     /// New touch started.
     TouchStart(TouchEvent),
     /// Existing touch moved.
     TouchMove(TouchEvent),
     /// Existing touch was released.
     TouchEnd(TouchEvent),
+    /// A keyboard key was pressed. Platforms that repeat the key while it is held down send this event for every
+    /// repetition.
+    KeyPressed(Key),
+    /// A keyboard key was released.
+    KeyReleased(Key),
 }
 
 /// User interaction event. This is the main type that the application would use through [`UserEventHandler`]s.
@@ -80,19 +106,26 @@ pub enum UserEvent {
     /// This event is also fired when a single-finger touch is moved around.
     DragStarted(MouseButton, MouseEvent),
 
-    /// Mouse pointer moved after drag started was consumed.
+    /// Mouse pointer moved after drag started was consumed. [`MouseEvent::motion`] contains the velocity and
+    /// acceleration of the drag.
     Drag(MouseButton, Vector2<f64>, MouseEvent),
 
-    /// Mouse button was released while dragging.
+    /// Mouse button was released (or the touch was lifted) while dragging. [`MouseEvent::motion`] contains the
+    /// velocity of the pointer at the moment of release, which can be used to continue the movement with inertia.
     DragEnded(MouseButton, MouseEvent),
 
-    /// Scroll event is called. The number is number of text lines the scroll is requested for. This is then converted
-    /// into zoom delta based on [`EventProcessor`] zoom speed configuration.
-    Scroll(f64, MouseEvent),
+    /// Scroll event is called. Wheel scrolls are usually converted into stepped zoom, and precise touchpad scrolls into
+    /// smooth zoom proportional to the scrolled distance (see [`MapController`]).
+    Scroll(ScrollDelta, MouseEvent),
 
     /// Zoom is called around a point. This is different from [`UserEvent::Scroll`], as it is not produced by a mouse
     /// but rather by multi-tough gestures. The first parameter is zoom delta value.
     Zoom(f64, Point2d),
+
+    /// A keyboard key was pressed (or repeated while held down).
+    KeyPressed(Key),
+    /// A keyboard key was released.
+    KeyReleased(Key),
 }
 
 /// Value returned by an [`UserEventHandler`] to indicate the status of the event.
@@ -109,6 +142,7 @@ pub enum EventPropagation {
 
 /// Mouse button enum.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MouseButton {
     /// The button you click when you want to shoot.
     Left,
@@ -120,13 +154,127 @@ pub enum MouseButton {
     Other,
 }
 
+/// Amount of scrolling of a [`RawUserEvent::Scroll`] event. Positive values scroll up (zoom in).
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ScrollDelta {
+    /// Scroll by a mouse wheel, in lines (notches of the wheel).
+    Lines(f64),
+    /// Precise scroll by a touchpad, including the momentum scroll generated by the OS after the fingers are lifted,
+    /// in pixels.
+    Pixels(f64),
+}
+
+impl ScrollDelta {
+    /// Number of pixels of a precise scroll considered equal to scrolling by one line.
+    pub const PIXELS_PER_LINE: f64 = 114.0;
+
+    /// Scroll amount in lines. Pixel deltas are converted with [`ScrollDelta::PIXELS_PER_LINE`].
+    pub fn lines(&self) -> f64 {
+        match self {
+            ScrollDelta::Lines(lines) => *lines,
+            ScrollDelta::Pixels(pixels) => pixels / Self::PIXELS_PER_LINE,
+        }
+    }
+
+    /// Returns true if the scroll is precise (by a touchpad) rather than by wheel steps.
+    pub fn is_precise(&self) -> bool {
+        matches!(self, ScrollDelta::Pixels(_))
+    }
+}
+
+/// Keyboard key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Key {
+    /// A key producing a character. Letters are always given in lower case.
+    Char(char),
+    /// Arrow up.
+    ArrowUp,
+    /// Arrow down.
+    ArrowDown,
+    /// Arrow left.
+    ArrowLeft,
+    /// Arrow right.
+    ArrowRight,
+    /// Space bar.
+    Space,
+    /// Shift key.
+    Shift,
+    /// Control key.
+    Control,
+    /// Escape key.
+    Escape,
+    /// Any other key.
+    Other,
+}
+
 /// State of the mouse at the moment of the event.
+///
+/// The position of the pointer is given in several coordinate spaces, so that handlers do not need to convert them
+/// on their own:
+/// * [`screen_pointer_position`](MouseEvent::screen_pointer_position) - in logical pixels, the same units the size of
+///   the [`MapView`](crate::view::MapView) is set in. Use this for anything that works with the map view.
+/// * [`physical_pointer_position`](MouseEvent::physical_pointer_position) - in physical pixels of the surface the map
+///   is rendered to.
+/// * [`map_pointer_position`](MouseEvent::map_pointer_position) - in the CRS of the map view. Use
+///   [`MouseEvent::geo_position`] and [`MouseEvent::position_in_crs`] to get the position in other coordinate systems.
 #[derive(Debug, Clone)]
 pub struct MouseEvent {
-    /// Pointer position on the screen in pixels from the top-left corner.
+    /// Pointer position on the screen in logical pixels from the top-left corner.
     pub screen_pointer_position: Point2d,
+    /// Pointer position on the screen in physical pixels from the top-left corner. This is the logical position
+    /// multiplied by the [scale factor](Map::scale_factor) of the map.
+    pub physical_pointer_position: Point2d,
+    /// Position of the pointer on the map in the CRS of the map view. `None` if the pointer is not over the map, e.g.
+    /// above the horizon of a tilted map.
+    pub map_pointer_position: Option<Point2d>,
+    /// CRS of the map view at the moment of the event.
+    pub map_crs: Crs,
     /// State of the mouse buttons.
     pub buttons: MouseButtonsState,
+    /// Velocity and acceleration of the pointer. For touch events this is the motion of the touch that caused the
+    /// event.
+    pub motion: PointerMotion,
+    /// Type of the input device that caused the event.
+    pub pointer_type: PointerType,
+}
+
+/// Type of the input device that controls the pointer.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PointerType {
+    /// Mouse, touchpad or pen.
+    #[default]
+    Mouse,
+    /// Touch screen.
+    Touch,
+}
+
+impl MouseEvent {
+    /// Geographic coordinates of the pointer. Returns `None` if the pointer is not over the map or the CRS of the map
+    /// cannot be unprojected.
+    pub fn geo_position(&self) -> Option<GeoPoint2d> {
+        let position = self.map_pointer_position?;
+        self.map_crs
+            .get_projection::<GeoPoint2d, Point2d>()?
+            .unproject(&position)
+    }
+
+    /// Position of the pointer projected into the given CRS, e.g. the CRS of a [`FeatureLayer`](crate::layer::FeatureLayer)
+    /// to hit-test its features.
+    ///
+    /// Returns `None` if the pointer is not over the map or the position cannot be projected. CRSs without projection
+    /// (geographic coordinates) are not supported by this method, use [`MouseEvent::geo_position`] for them instead.
+    pub fn position_in_crs(&self, crs: &Crs) -> Option<Point2d> {
+        let position = self.map_pointer_position?;
+        if *crs == self.map_crs {
+            return Some(position);
+        }
+
+        crs.get_projection::<GeoPoint2d, Point2d>()?
+            .project(&self.geo_position()?)
+    }
 }
 
 /// Id of the current touch.
@@ -134,6 +282,7 @@ pub type TouchId = u64;
 
 /// Details of a touch event.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TouchEvent {
     /// Id of the touch. Id is valid and unique only until the touch is ended. After that a new touch can have the same
     /// id.
@@ -0,0 +1,168 @@
+use galileo_types::cartesian::Point2d;
+use nalgebra::Vector2;
+use std::collections::VecDeque;
+use std::time::Duration;
+use web_time::SystemTime;
+
+/// Time span of the pointer positions used to estimate the motion of the pointer.
+const MOTION_WINDOW: Duration = Duration::from_millis(100);
+
+/// Velocity and acceleration of the pointer (or of the touch) at the moment of the event.
+///
+/// The values are estimated from the pointer positions over the last 100 ms, so a pointer that stopped before the
+/// button was released has zero velocity. This can be used by event handlers to implement inertia, fling gestures
+/// or any other velocity based behavior.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PointerMotion {
+    /// Velocity of the pointer in pixels per second.
+    pub velocity: Vector2<f64>,
+    /// Acceleration of the pointer in pixels per second squared.
+    pub acceleration: Vector2<f64>,
+}
+
+impl PointerMotion {
+    /// Speed of the pointer in pixels per second.
+    pub fn speed(&self) -> f64 {
+        self.velocity.magnitude()
+    }
+}
+
+/// Keeps recent positions of a pointer to estimate its motion.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct MotionTracker {
+    samples: VecDeque<(SystemTime, Point2d)>,
+}
+
+impl MotionTracker {
+    /// Forgets all the previous positions, e.g. when a new gesture starts.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Records the position of the pointer at the given time.
+    pub fn push(&mut self, time: SystemTime, position: Point2d) {
+        // Events can come with the same timestamp, in this case only the latest position is meaningful.
+        if let Some((last_time, last_position)) = self.samples.back_mut() {
+            if time <= *last_time {
+                *last_position = position;
+                return;
+            }
+        }
+
+        self.samples.push_back((time, position));
+        while self.samples.len() > 2 && Self::age(self.samples[1].0, time) > MOTION_WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Motion of the pointer at the given time.
+    pub fn motion(&self, now: SystemTime) -> PointerMotion {
+        let Some(&(last_time, _)) = self.samples.back() else {
+            return PointerMotion::default();
+        };
+
+        if Self::age(last_time, now) > MOTION_WINDOW {
+            return PointerMotion::default();
+        }
+
+        let samples: Vec<_> = self
+            .samples
+            .iter()
+            .filter(|(time, _)| Self::age(*time, last_time) <= MOTION_WINDOW)
+            .copied()
+            .collect();
+        if samples.len() < 2 {
+            return PointerMotion::default();
+        }
+
+        let velocity = Self::velocity(&samples);
+        let acceleration = if samples.len() < 3 {
+            Vector2::zeros()
+        } else {
+            let mid = samples.len() / 2;
+            let first = &samples[..=mid];
+            let second = &samples[mid..];
+            let dt = Self::secs(Self::mid_time(first), Self::mid_time(second));
+            if dt > 0.0 {
+                (Self::velocity(second) - Self::velocity(first)) / dt
+            } else {
+                Vector2::zeros()
+            }
+        };
+
+        PointerMotion {
+            velocity,
+            acceleration,
+        }
+    }
+
+    fn velocity(samples: &[(SystemTime, Point2d)]) -> Vector2<f64> {
+        let (first_time, first_position) = samples[0];
+        let (last_time, last_position) = samples[samples.len() - 1];
+        let dt = Self::secs(first_time, last_time);
+        if dt > 0.0 {
+            (last_position - first_position) / dt
+        } else {
+            Vector2::zeros()
+        }
+    }
+
+    fn mid_time(samples: &[(SystemTime, Point2d)]) -> SystemTime {
+        let start = samples[0].0;
+        let end = samples[samples.len() - 1].0;
+        start + Self::age(start, end) / 2
+    }
+
+    fn age(time: SystemTime, now: SystemTime) -> Duration {
+        now.duration_since(time).unwrap_or_default()
+    }
+
+    fn secs(from: SystemTime, to: SystemTime) -> f64 {
+        Self::age(from, to).as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn constant_velocity() {
+        let mut tracker = MotionTracker::default();
+        for i in 0..10 {
+            tracker.push(at(i * 10), Point2d::new(i as f64 * 5.0, 0.0));
+        }
+
+        let motion = tracker.motion(at(90));
+        assert!((motion.velocity.x - 500.0).abs() < 1e-6);
+        assert!(motion.velocity.y.abs() < 1e-6);
+        assert!(motion.acceleration.magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn constant_acceleration() {
+        let mut tracker = MotionTracker::default();
+        for i in 0..9 {
+            let t = i as f64 * 0.01;
+            tracker.push(at(i * 10), Point2d::new(0.0, 1000.0 * t * t / 2.0));
+        }
+
+        let motion = tracker.motion(at(80));
+        assert!((motion.acceleration.y - 1000.0).abs() < 1e-6);
+        assert!(motion.acceleration.x.abs() < 1e-6);
+    }
+
+    #[test]
+    fn stopped_pointer_has_no_velocity() {
+        let mut tracker = MotionTracker::default();
+        tracker.push(at(0), Point2d::new(0.0, 0.0));
+        tracker.push(at(10), Point2d::new(10.0, 0.0));
+        assert!(tracker.motion(at(10)).speed() > 0.0);
+
+        assert_eq!(tracker.motion(at(300)), PointerMotion::default());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::control::{CameraController, EventProcessor, MapController, NavigationMode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Preset configuration of map interactions for a common embedding scenario. Use [`InteractionProfile::config`] to get
+/// the configuration and adjust it if needed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InteractionProfile {
+    /// All the gestures of mouse, touchpad and touch screen, including rotation and tilt, with inertia after panning.
+    #[default]
+    Desktop,
+    /// Public touch screen: panning with inertia and pinch zoom within a limited zoom range. Mouse wheel and rotation
+    /// are disabled.
+    TouchKiosk,
+    /// Map embedded into a scrollable page: panning and pinch zoom only, so that scrolling the page with the mouse
+    /// wheel does not zoom the map.
+    EmbeddedViewer,
+    /// Static map that does not react to the user input.
+    ReadOnly,
+}
+
+impl InteractionProfile {
+    /// Configuration of the profile.
+    pub fn config(&self) -> InteractionConfig {
+        let default = InteractionConfig::default();
+        match self {
+            Self::Desktop => InteractionConfig {
+                inertia: true,
+                ..default
+            },
+            Self::TouchKiosk => InteractionConfig {
+                scroll_zoom: false,
+                rotate: false,
+                inertia: true,
+                max_resolution: default.max_resolution / 8.0,
+                ..default
+            },
+            Self::EmbeddedViewer => InteractionConfig {
+                scroll_zoom: false,
+                rotate: false,
+                ..default
+            },
+            Self::ReadOnly => InteractionConfig {
+                navigation: None,
+                pan: false,
+                scroll_zoom: false,
+                pinch_zoom: false,
+                rotate: false,
+                ..default
+            },
+        }
+    }
+}
+
+/// Declarative configuration of the way the user interacts with the map.
+///
+/// The configuration can be deserialized (with the `serde` feature) from application settings, and then used to set up
+/// the event handlers of an [`EventProcessor`] with [`InteractionConfig::add_handlers`]:
+///
+/// ```
+/// use galileo::control::{EventProcessor, InteractionProfile};
+///
+/// let config = InteractionProfile::EmbeddedViewer.config();
+/// let mut event_processor = EventProcessor::default();
+/// config.add_handlers(&mut event_processor);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct InteractionConfig {
+    /// Navigation handler added to the event processor: [`MapController`] for [`NavigationMode::TopDown`], and
+    /// [`CameraController`] for other modes. If `None`, no handler is added and the map cannot be moved by the user.
+    pub navigation: Option<NavigationMode>,
+    /// Pan the map by dragging with the left mouse button or a single finger.
+    pub pan: bool,
+    /// Zoom the map with the mouse wheel or touchpad scroll.
+    pub scroll_zoom: bool,
+    /// Zoom the map with two fingers.
+    pub pinch_zoom: bool,
+    /// Rotate and tilt the map by dragging with the right mouse button.
+    pub rotate: bool,
+    /// Keep the map moving for a short time after a fast pan gesture is released. Disabled by default.
+    pub inertia: bool,
+    /// Minimum resolution (maximum zoom) the user can zoom the map to.
+    pub min_resolution: f64,
+    /// Maximum resolution (minimum zoom) the user can zoom the map to.
+    pub max_resolution: f64,
+    /// Portion of the resolution change for one step of the mouse wheel.
+    pub zoom_speed: f64,
+    /// Maximum tilt of the map in degrees.
+    pub max_tilt: f64,
+}
+
+impl Default for InteractionConfig {
+    fn default() -> Self {
+        Self {
+            navigation: Some(NavigationMode::TopDown),
+            pan: true,
+            scroll_zoom: true,
+            pinch_zoom: true,
+            rotate: true,
+            inertia: false,
+            max_resolution: 156543.03392800014 / 8.0,
+            min_resolution: 156543.03392800014 / 8.0 / 2.0f64.powi(16),
+            zoom_speed: 0.2,
+            max_tilt: 80.0,
+        }
+    }
+}
+
+impl From<InteractionProfile> for InteractionConfig {
+    fn from(profile: InteractionProfile) -> Self {
+        profile.config()
+    }
+}
+
+impl InteractionConfig {
+    /// Creates a map controller with the gestures and limits of this configuration.
+    pub fn map_controller(&self) -> MapController {
+        MapController::from_config(self)
+    }
+
+    /// Adds the navigation handler of this configuration to the end of the handler list of the processor.
+    pub fn add_handlers(&self, event_processor: &mut EventProcessor) {
+        match self.navigation {
+            None => {}
+            Some(NavigationMode::TopDown) => event_processor.add_handler(self.map_controller()),
+            Some(mode) => event_processor.add_handler(
+                CameraController::new(mode)
+                    .with_max_tilt(self.max_tilt.to_radians())
+                    .with_map_controller(self.map_controller()),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{MouseButton, RawUserEvent, ScrollDelta};
+    use crate::map::Map;
+    use crate::messenger::DummyMessenger;
+    use crate::view::MapView;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    fn drag_and_scroll(profile: InteractionProfile) -> (MapView, MapView) {
+        let mut map = Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0)),
+            vec![],
+            None::<DummyMessenger>,
+        );
+        let mut processor = EventProcessor::default();
+        profile.config().add_handlers(&mut processor);
+        let initial = map.view().clone();
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(10.0, 10.0)),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(30.0, 10.0)),
+            &mut map,
+        );
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), &mut map);
+        processor.handle(RawUserEvent::Scroll(ScrollDelta::Lines(1.0)), &mut map);
+
+        (initial, map.target_view().clone())
+    }
+
+    #[test]
+    fn read_only_profile_ignores_input() {
+        let (initial, result) = drag_and_scroll(InteractionProfile::ReadOnly);
+        assert_eq!(initial, result);
+    }
+
+    #[test]
+    fn embedded_viewer_pans_but_does_not_scroll_zoom() {
+        let (initial, result) = drag_and_scroll(InteractionProfile::EmbeddedViewer);
+        assert_ne!(initial, result);
+        assert_eq!(initial.resolution(), result.resolution());
+
+        let (initial, result) = drag_and_scroll(InteractionProfile::Desktop);
+        assert!(result.resolution() < initial.resolution());
+    }
+}
@@ -0,0 +1,245 @@
+//! Recording and playback of user interactions. See [`InteractionRecorder`] and [`InteractionPlayer`].
+
+use crate::control::clock::MANUAL_CLOCK_START;
+use crate::control::{EventProcessor, RawUserEvent};
+use crate::map::Map;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use web_time::{Instant, SystemTime};
+
+/// Time that recorded events are replayed from. It is fixed, so that replaying the same recording always gives the
+/// same result, and is far enough from the default state of a new [`EventProcessor`] to not produce false clicks.
+/// Same as the start of a default [`ManualClock`](crate::control::ManualClock), so that playback can be combined with
+/// it.
+const PLAYBACK_START: Duration = MANUAL_CLOCK_START;
+
+/// Raw user event together with the time it was received, relative to the start of the recording.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RecordedEvent {
+    /// Time since the start of the recording.
+    pub time: Duration,
+    /// The event.
+    pub event: RawUserEvent,
+}
+
+/// A sequence of recorded user events.
+///
+/// A recording can be replayed against a [`Map`] with [`InteractionPlayer`]. Replay does not depend on the wall
+/// clock, so a recording can be used as a regression test for gesture handling, or attached to a bug report
+/// (with `serde` feature recordings can be serialized).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InteractionRecording {
+    events: Vec<RecordedEvent>,
+}
+
+impl InteractionRecording {
+    /// Creates a recording from the given events. Events are sorted by their time.
+    pub fn new(mut events: Vec<RecordedEvent>) -> Self {
+        events.sort_by_key(|e| e.time);
+        Self { events }
+    }
+
+    /// Recorded events.
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Time of the last event of the recording.
+    pub fn duration(&self) -> Duration {
+        self.events.last().map(|e| e.time).unwrap_or_default()
+    }
+
+    /// Replays all the events of the recording at once.
+    ///
+    /// Events are processed with the time they were recorded at, so clicks, double clicks and drags are recognized
+    /// the same way as in the original interaction.
+    pub fn replay(&self, processor: &mut EventProcessor, map: &mut Map) {
+        let mut player = InteractionPlayer::new(self.clone());
+        player.advance_to(self.duration(), processor, map);
+    }
+}
+
+/// Records raw user events with the time they were received.
+///
+/// ```
+/// use galileo::control::{InteractionRecorder, MouseButton, RawUserEvent};
+///
+/// let mut recorder = InteractionRecorder::new();
+/// recorder.record(&RawUserEvent::ButtonPressed(MouseButton::Left));
+/// recorder.record(&RawUserEvent::ButtonReleased(MouseButton::Left));
+///
+/// let recording = recorder.finish();
+/// assert_eq!(recording.events().len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct InteractionRecorder {
+    start: Instant,
+    events: Vec<RecordedEvent>,
+}
+
+impl Default for InteractionRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InteractionRecorder {
+    /// Starts a new recording.
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            events: vec![],
+        }
+    }
+
+    /// Records the event as received now.
+    pub fn record(&mut self, event: &RawUserEvent) {
+        let time = self.start.elapsed();
+        self.record_at(event.clone(), time);
+    }
+
+    /// Records the event with the given time since the start of the recording.
+    pub fn record_at(&mut self, event: RawUserEvent, time: Duration) {
+        self.events.push(RecordedEvent { time, event });
+    }
+
+    /// Finishes the recording.
+    pub fn finish(self) -> InteractionRecording {
+        InteractionRecording::new(self.events)
+    }
+}
+
+/// Replays an [`InteractionRecording`] step by step, allowing to check the state of the map between the events.
+pub struct InteractionPlayer {
+    recording: InteractionRecording,
+    position: usize,
+}
+
+impl InteractionPlayer {
+    /// Creates a new player, positioned at the start of the recording.
+    pub fn new(recording: InteractionRecording) -> Self {
+        Self {
+            recording,
+            position: 0,
+        }
+    }
+
+    /// Replays all the events recorded before or at `elapsed` time since the start of the recording, that were not
+    /// replayed yet. Returns the number of replayed events.
+    pub fn advance_to(
+        &mut self,
+        elapsed: Duration,
+        processor: &mut EventProcessor,
+        map: &mut Map,
+    ) -> usize {
+        let start_position = self.position;
+        while let Some(recorded) = self.recording.events.get(self.position) {
+            if recorded.time > elapsed {
+                break;
+            }
+
+            let time = SystemTime::UNIX_EPOCH + PLAYBACK_START + recorded.time;
+            processor.handle_at(recorded.event.clone(), map, time);
+            self.position += 1;
+        }
+
+        self.position - start_position
+    }
+
+    /// Returns true if all the events were replayed.
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{EventPropagation, MouseButton, UserEvent};
+    use crate::messenger::DummyMessenger;
+    use crate::view::MapView;
+    use galileo_types::cartesian::Point2d;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn test_map() -> Map {
+        Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0),
+            vec![],
+            None::<DummyMessenger>,
+        )
+    }
+
+    fn click_counter(processor: &mut EventProcessor) -> (Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let clicks = Arc::new(AtomicUsize::new(0));
+        let double_clicks = Arc::new(AtomicUsize::new(0));
+        let clicks_clone = clicks.clone();
+        let double_clicks_clone = double_clicks.clone();
+        processor.add_handler(move |event: &UserEvent, _map: &mut Map| {
+            match event {
+                UserEvent::Click(..) => clicks_clone.fetch_add(1, Ordering::Relaxed),
+                UserEvent::DoubleClick(..) => double_clicks_clone.fetch_add(1, Ordering::Relaxed),
+                _ => 0,
+            };
+            EventPropagation::Propagate
+        });
+
+        (clicks, double_clicks)
+    }
+
+    fn press_release(recorder: &mut InteractionRecorder, press_ms: u64, release_ms: u64) {
+        recorder.record_at(
+            RawUserEvent::ButtonPressed(MouseButton::Left),
+            Duration::from_millis(press_ms),
+        );
+        recorder.record_at(
+            RawUserEvent::ButtonReleased(MouseButton::Left),
+            Duration::from_millis(release_ms),
+        );
+    }
+
+    #[test]
+    fn replay_recognizes_clicks() {
+        let mut recorder = InteractionRecorder::new();
+        press_release(&mut recorder, 0, 100);
+        press_release(&mut recorder, 300, 350);
+        press_release(&mut recorder, 2000, 2500);
+        let recording = recorder.finish();
+
+        let mut processor = EventProcessor::default();
+        let (clicks, double_clicks) = click_counter(&mut processor);
+        let mut map = test_map();
+        recording.replay(&mut processor, &mut map);
+
+        assert_eq!(clicks.load(Ordering::Relaxed), 2);
+        assert_eq!(double_clicks.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn player_advances_step_by_step() {
+        let mut recorder = InteractionRecorder::new();
+        press_release(&mut recorder, 0, 100);
+        let mut player = InteractionPlayer::new(recorder.finish());
+
+        let mut processor = EventProcessor::default();
+        let (clicks, _) = click_counter(&mut processor);
+        let mut map = test_map();
+
+        assert_eq!(
+            player.advance_to(Duration::from_millis(50), &mut processor, &mut map),
+            1
+        );
+        assert_eq!(clicks.load(Ordering::Relaxed), 0);
+        assert!(!player.is_finished());
+
+        assert_eq!(
+            player.advance_to(Duration::from_millis(100), &mut processor, &mut map),
+            1
+        );
+        assert_eq!(clicks.load(Ordering::Relaxed), 1);
+        assert!(player.is_finished());
+    }
+}
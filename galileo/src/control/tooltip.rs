@@ -0,0 +1,366 @@
+//! Tooltips that show information about the feature under the mouse pointer. See [`TooltipController`].
+
+use crate::control::{Clock, EventPropagation, SystemClock, UserEvent, UserEventHandler};
+use crate::map::Map;
+use galileo_types::cartesian::{Point2d, Size};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use web_time::SystemTime;
+
+/// Text template with `{attribute}` placeholders, e.g. `"{name}: {population} people"`.
+///
+/// Placeholders are replaced with the values given by the lookup function. Placeholders for which the lookup returns
+/// `None` are replaced with an empty string. Literal braces are written as `{{` and `}}`.
+///
+/// ```
+/// use galileo::control::TooltipTemplate;
+///
+/// let template = TooltipTemplate::new("{name} ({{{code}}})");
+/// let text = template.format(|name| match name {
+///     "name" => Some("Lisbon".to_string()),
+///     "code" => Some("LIS".to_string()),
+///     _ => None,
+/// });
+/// assert_eq!(text, "Lisbon ({LIS})");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TooltipTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    Attribute(String),
+}
+
+impl TooltipTemplate {
+    /// Parses the template. An unclosed `{` is treated as literal text.
+    pub fn new(template: &str) -> Self {
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let name: String = chars.clone().take_while(|c| *c != '}').collect();
+                    if chars.clone().nth(name.chars().count()) != Some('}') {
+                        text.push('{');
+                        continue;
+                    }
+
+                    chars.nth(name.chars().count());
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(TemplatePart::Attribute(name.trim().to_string()));
+                }
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+
+        Self { parts }
+    }
+
+    /// Names of the attributes used in the template.
+    pub fn attributes(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            TemplatePart::Attribute(name) => Some(name.as_str()),
+            TemplatePart::Text(_) => None,
+        })
+    }
+
+    /// Substitutes the attribute values given by `lookup` into the template.
+    pub fn format(&self, lookup: impl Fn(&str) -> Option<String>) -> String {
+        let mut result = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => result.push_str(text),
+                TemplatePart::Attribute(name) => {
+                    if let Some(value) = lookup(name) {
+                        result.push_str(&value);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Tooltip to be displayed by the application.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tooltip {
+    /// Text of the tooltip.
+    pub text: String,
+    /// Position of the mouse pointer on the screen, in pixels.
+    pub pointer_position: Point2d,
+    /// Offset of the tooltip box from the pointer, in pixels.
+    pub offset: Point2d,
+}
+
+impl Tooltip {
+    /// Position of the top-left corner of the tooltip box with the given size on the screen of the given size.
+    ///
+    /// The box is placed at the offset from the pointer. If it does not fit into the screen this way, it is flipped to
+    /// the other side of the pointer, and then moved inside the screen if it still does not fit.
+    pub fn box_position(&self, box_size: Size, screen_size: Size) -> Point2d {
+        let place = |pointer: f64, offset: f64, size: f64, screen: f64| {
+            let mut position = pointer + offset;
+            if position + size > screen {
+                position = pointer - offset - size;
+            }
+            position.min(screen - size).max(0.0)
+        };
+
+        Point2d::new(
+            place(
+                self.pointer_position.x,
+                self.offset.x,
+                box_size.width(),
+                screen_size.width(),
+            ),
+            place(
+                self.pointer_position.y,
+                self.offset.y,
+                box_size.height(),
+                screen_size.height(),
+            ),
+        )
+    }
+}
+
+type TooltipProvider = dyn Fn(Point2d, f64) -> Option<String> + Send + Sync;
+
+/// Event handler that tracks the feature under the mouse pointer and prepares a tooltip for it.
+///
+/// The controller does not know how to find features: it is given a function that takes a position in the map
+/// coordinates and the search tolerance in map units, and returns the text of the tooltip for the feature at this
+/// position, if any. A [`TooltipTemplate`] can be used to build the text from the feature attributes.
+///
+/// The tooltip appears when the pointer stays over the same feature for the configured delay, and is hidden when
+/// the pointer leaves the feature, or a mouse button is pressed. The controller is cheaply cloneable: add one clone to
+/// the [`EventProcessor`](crate::control::EventProcessor) and keep another to call [`TooltipController::tooltip`]
+/// every time the UI is drawn.
+///
+/// ```no_run
+/// use galileo::control::{EventProcessor, TooltipController, TooltipTemplate};
+/// use galileo_types::cartesian::Point2d;
+/// use std::collections::HashMap;
+///
+/// # fn find_city(position: Point2d, tolerance: f64) -> Option<HashMap<String, String>> { None }
+/// let template = TooltipTemplate::new("{name}\nPopulation: {population}");
+/// let tooltips = TooltipController::new(move |position, tolerance| {
+///     let city = find_city(position, tolerance)?;
+///     Some(template.format(|name| city.get(name).cloned()))
+/// });
+///
+/// let mut event_processor = EventProcessor::default();
+/// event_processor.add_handler(tooltips.clone());
+///
+/// // When drawing the UI:
+/// if let Some(tooltip) = tooltips.tooltip() {
+///     // draw `tooltip.text` at `tooltip.box_position(...)`
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TooltipController {
+    provider: Arc<TooltipProvider>,
+    clock: Arc<dyn Clock>,
+    delay: Duration,
+    offset: Point2d,
+    tolerance: f64,
+    state: Arc<Mutex<Option<HoverState>>>,
+}
+
+#[derive(Debug, Clone)]
+struct HoverState {
+    text: String,
+    pointer_position: Point2d,
+    since: SystemTime,
+}
+
+impl TooltipController {
+    const DEFAULT_DELAY: Duration = Duration::from_millis(500);
+    const DEFAULT_TOLERANCE: f64 = 3.0;
+
+    /// Creates a new controller with the function that returns the tooltip text for the given map position and the
+    /// search tolerance in map units.
+    pub fn new(provider: impl Fn(Point2d, f64) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            clock: Arc::new(SystemClock),
+            delay: Self::DEFAULT_DELAY,
+            offset: Point2d::new(12.0, 16.0),
+            tolerance: Self::DEFAULT_TOLERANCE,
+            state: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Sets the time the pointer must stay over a feature before the tooltip is shown. Default is 500 ms.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Sets the offset of the tooltip box from the pointer, in pixels. Default is `(12, 16)`.
+    pub fn with_offset(mut self, dx: f64, dy: f64) -> Self {
+        self.offset = Point2d::new(dx, dy);
+        self
+    }
+
+    /// Sets the distance from the pointer, in pixels, at which features are searched. Default is 3.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.max(0.0);
+        self
+    }
+
+    /// Sets the clock used to measure the delay.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Tooltip to be displayed now, if the pointer has stayed over a feature for long enough.
+    pub fn tooltip(&self) -> Option<Tooltip> {
+        let state = self.state.lock().expect("mutex is poisoned");
+        let state = state.as_ref()?;
+        let elapsed = self
+            .clock
+            .now()
+            .duration_since(state.since)
+            .unwrap_or_default();
+        if elapsed < self.delay {
+            return None;
+        }
+
+        Some(Tooltip {
+            text: state.text.clone(),
+            pointer_position: state.pointer_position,
+            offset: self.offset,
+        })
+    }
+
+    /// Returns true if the pointer is over a feature, but the delay has not passed yet. Applications that redraw the
+    /// UI only on events can use it to schedule a redraw when the tooltip is about to appear.
+    pub fn is_pending(&self) -> bool {
+        self.state.lock().expect("mutex is poisoned").is_some() && self.tooltip().is_none()
+    }
+
+    /// Hides the current tooltip until the pointer moves to another feature.
+    pub fn hide(&self) {
+        *self.state.lock().expect("mutex is poisoned") = None;
+    }
+
+    fn pointer_moved(&self, pointer_position: Point2d, map: &Map) {
+        let view = map.view();
+        let text = view
+            .screen_to_map(pointer_position)
+            .and_then(|position| (self.provider)(position, self.tolerance * view.resolution()));
+
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let Some(text) = text else {
+            if state.take().is_some() {
+                map.redraw();
+            }
+            return;
+        };
+
+        match state.as_mut() {
+            Some(state) if state.text == text => {
+                state.pointer_position = pointer_position;
+            }
+            _ => {
+                *state = Some(HoverState {
+                    text,
+                    pointer_position,
+                    since: self.clock.now(),
+                });
+            }
+        }
+
+        map.redraw();
+    }
+}
+
+impl UserEventHandler for TooltipController {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        match event {
+            UserEvent::PointerMoved(e) => self.pointer_moved(e.screen_pointer_position, map),
+            UserEvent::ButtonPressed(..) | UserEvent::DragStarted(..) | UserEvent::Scroll(..) => {
+                self.hide();
+            }
+            _ => {}
+        }
+
+        EventPropagation::Propagate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{ManualClock, MouseButtonsState, MouseEvent};
+    use crate::messenger::DummyMessenger;
+    use crate::view::MapView;
+    use galileo_types::geo::Crs;
+
+    fn moved_to(x: f64, y: f64) -> UserEvent {
+        UserEvent::PointerMoved(MouseEvent {
+            screen_pointer_position: Point2d::new(x, y),
+            physical_pointer_position: Point2d::new(x, y),
+            map_pointer_position: None,
+            map_crs: Crs::EPSG3857,
+            buttons: MouseButtonsState::default(),
+            motion: Default::default(),
+            pointer_type: Default::default(),
+        })
+    }
+
+    #[test]
+    fn tooltip_appears_after_delay() {
+        let mut map = Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0)),
+            vec![],
+            None::<DummyMessenger>,
+        );
+        let clock = ManualClock::default();
+        // Map x > 0 is the right half of the screen.
+        let controller = TooltipController::new(|position, _| {
+            (position.x > 0.0)
+                .then(|| TooltipTemplate::new("{side}").format(|_| Some("right".into())))
+        })
+        .with_clock(clock.clone());
+
+        controller.handle(&moved_to(80.0, 50.0), &mut map);
+        assert!(controller.is_pending());
+        clock.advance(Duration::from_millis(300));
+        controller.handle(&moved_to(90.0, 50.0), &mut map);
+        assert_eq!(controller.tooltip(), None);
+
+        clock.advance(Duration::from_millis(300));
+        let tooltip = controller.tooltip().expect("tooltip is shown");
+        assert_eq!(tooltip.text, "right");
+        assert_eq!(
+            tooltip.box_position(Size::new(30.0, 10.0), Size::new(100.0, 100.0)),
+            Point2d::new(48.0, 66.0)
+        );
+
+        controller.handle(&moved_to(10.0, 50.0), &mut map);
+        assert_eq!(controller.tooltip(), None);
+        assert!(!controller.is_pending());
+    }
+}
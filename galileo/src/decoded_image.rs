@@ -1,15 +1,18 @@
-#[cfg(not(target_arch = "wasm32"))]
 use crate::error::GalileoError;
 #[cfg(not(target_arch = "wasm32"))]
 use std::ops::Deref;
 
+/// Image decoded into RGBA pixels.
 #[derive(Debug, Clone)]
 pub struct DecodedImage {
+    /// Pixel data, 4 bytes per pixel in RGBA order, row by row starting from the top left corner.
     pub bytes: Vec<u8>,
+    /// Width and height of the image in pixels.
     pub dimensions: (u32, u32),
 }
 
 impl DecodedImage {
+    /// Decodes an image from an encoded image file (PNG, JPEG etc.).
     #[cfg(not(target_arch = "wasm32"))]
     pub fn new(bytes: &[u8]) -> Result<Self, GalileoError> {
         use image::GenericImageView;
@@ -22,4 +25,21 @@ impl DecodedImage {
             dimensions,
         })
     }
+
+    /// Creates an image from raw RGBA pixels. Returns an error if the length of `rgba` does not match the
+    /// dimensions.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, GalileoError> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return Err(GalileoError::Generic(format!(
+                "expected {} bytes for {width}x{height} RGBA image, but got {}",
+                width as usize * height as usize * 4,
+                rgba.len()
+            )));
+        }
+
+        Ok(Self {
+            bytes: rgba,
+            dimensions: (width, height),
+        })
+    }
 }
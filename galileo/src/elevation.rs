@@ -0,0 +1,374 @@
+//! Decoding of raster elevation (DEM) tiles and [`ElevationCache`] that shares the decoded tiles between all the
+//! parts of an application that need elevation data.
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::{DataProcessor, DataProvider, ImageDecoderRegistry};
+use crate::tile_scheme::{TileIndex, TileSchema};
+use bytes::Bytes;
+use galileo_types::cartesian::CartesianPoint2d;
+use maybe_sync::{MaybeSend, MaybeSync};
+use quick_cache::sync::Cache;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Grid of elevation values of a tile.
+///
+/// The grid can hold values of any single-band raster (temperature, precipitation etc.), e.g. to be colored with a
+/// [`ColorMap`](crate::layer::data_provider::ColorMap).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElevationTile {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+impl ElevationTile {
+    /// Creates a new tile from elevation values, row by row starting from the top left corner. Returns an error if
+    /// the number of values does not match the dimensions.
+    pub fn new(width: u32, height: u32, values: Vec<f32>) -> Result<Self, GalileoError> {
+        if width == 0 || height == 0 || values.len() != width as usize * height as usize {
+            return Err(GalileoError::Generic(format!(
+                "expected {} values for {width}x{height} elevation tile, but got {}",
+                width as usize * height as usize,
+                values.len()
+            )));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            values,
+        })
+    }
+
+    /// Decodes elevation values from the pixels of an image encoded with the given RGB `encoding`.
+    pub fn from_image(
+        image: &DecodedImage,
+        encoding: ElevationEncoding,
+    ) -> Result<Self, GalileoError> {
+        let (width, height) = image.dimensions;
+        let values = image
+            .bytes
+            .chunks_exact(4)
+            .map(|pixel| encoding.decode_rgb(pixel[0], pixel[1], pixel[2]))
+            .collect::<Result<_, _>>()?;
+
+        Self::new(width, height, values)
+    }
+
+    /// Width of the grid.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the grid.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Elevation values, row by row starting from the top left corner.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Elevation value of the given cell of the grid. Returns `None` if the cell is outside the grid.
+    pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(self.values[(y * self.width + x) as usize])
+    }
+
+    /// Bilinearly interpolated elevation at the given position in the tile, where `(0.0, 0.0)` is the top left
+    /// corner of the tile and `(1.0, 1.0)` is the bottom right one.
+    pub fn sample(&self, u: f64, v: f64) -> f32 {
+        let x = (u * self.width as f64 - 0.5).clamp(0.0, (self.width - 1) as f64);
+        let y = (v * self.height as f64 - 0.5).clamp(0.0, (self.height - 1) as f64);
+
+        let x0 = x.floor() as u32;
+        let y0 = y.floor() as u32;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let dx = (x - x0 as f64) as f32;
+        let dy = (y - y0 as f64) as f32;
+
+        let value = |x, y| self.values[(y * self.width + x) as usize];
+        let top = value(x0, y0) * (1.0 - dx) + value(x1, y0) * dx;
+        let bottom = value(x0, y1) * (1.0 - dx) + value(x1, y1) * dx;
+
+        top * (1.0 - dy) + bottom * dy
+    }
+
+    /// Minimum and maximum elevation of the tile.
+    pub fn range(&self) -> (f32, f32) {
+        self.values
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+                (min.min(*value), max.max(*value))
+            })
+    }
+}
+
+/// Method the elevation values are encoded with in the images of a DEM tile set.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ElevationEncoding {
+    /// Mapbox Terrain-RGB: `-10000 + (R * 256 * 256 + G * 256 + B) * 0.1` meters.
+    TerrainRgb,
+    /// Terrarium encoding used by AWS terrain tiles: `R * 256 + G + B / 256 - 32768` meters.
+    Terrarium,
+    /// Single channel 16-bit images (PNG or GeoTIFF), where the elevation is `value * scale + offset`.
+    Gray16 {
+        /// Multiplier of the stored value.
+        scale: f32,
+        /// Elevation of the stored value `0`.
+        offset: f32,
+    },
+}
+
+impl ElevationEncoding {
+    fn decode_rgb(&self, r: u8, g: u8, b: u8) -> Result<f32, GalileoError> {
+        let (r, g, b) = (r as f32, g as f32, b as f32);
+        match self {
+            Self::TerrainRgb => Ok(-10000.0 + (r * 65536.0 + g * 256.0 + b) * 0.1),
+            Self::Terrarium => Ok(r * 256.0 + g + b / 256.0 - 32768.0),
+            Self::Gray16 { .. } => Err(GalileoError::Generic(
+                "16-bit elevation cannot be decoded from an RGBA image".into(),
+            )),
+        }
+    }
+}
+
+/// Data processor that decodes DEM tiles into [`ElevationTile`]s. It can be used with
+/// [`UrlDataProvider`](crate::layer::data_provider::UrlDataProvider) to load the tiles for an [`ElevationCache`].
+///
+/// RGB encoded images are decoded with the [image decoders](ImageDecoderRegistry), so on the web a decoder must be
+/// registered for them. 16-bit images are only supported on native platforms.
+#[derive(Clone)]
+pub struct ElevationDecoder {
+    encoding: ElevationEncoding,
+    image_decoders: ImageDecoderRegistry,
+}
+
+impl ElevationDecoder {
+    /// Creates a new decoder.
+    pub fn new(encoding: ElevationEncoding) -> Self {
+        Self {
+            encoding,
+            image_decoders: ImageDecoderRegistry::new(),
+        }
+    }
+
+    /// Sets the decoders used to decode RGB encoded images.
+    pub fn with_image_decoders(mut self, image_decoders: ImageDecoderRegistry) -> Self {
+        self.image_decoders = image_decoders;
+        self
+    }
+
+    /// Encoding of the elevation values.
+    pub fn encoding(&self) -> ElevationEncoding {
+        self.encoding
+    }
+
+    /// Decodes a DEM tile.
+    pub fn decode(&self, bytes: &[u8]) -> Result<ElevationTile, GalileoError> {
+        match self.encoding {
+            ElevationEncoding::Gray16 { scale, offset } => {
+                Self::decode_gray16(bytes, scale, offset)
+            }
+            encoding => ElevationTile::from_image(&self.image_decoders.decode(bytes)?, encoding),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn decode_gray16(bytes: &[u8], scale: f32, offset: f32) -> Result<ElevationTile, GalileoError> {
+        let image = image::load_from_memory(bytes)?.into_luma16();
+        let (width, height) = image.dimensions();
+        let values = image
+            .into_raw()
+            .into_iter()
+            .map(|value| value as f32 * scale + offset)
+            .collect();
+
+        ElevationTile::new(width, height, values)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn decode_gray16(
+        _bytes: &[u8],
+        _scale: f32,
+        _offset: f32,
+    ) -> Result<ElevationTile, GalileoError> {
+        Err(GalileoError::Generic(
+            "16-bit elevation tiles are not supported on the web".into(),
+        ))
+    }
+}
+
+impl DataProcessor for ElevationDecoder {
+    type Input = Bytes;
+    type Output = ElevationTile;
+    type Context = ();
+
+    fn process(&self, input: Bytes, _context: ()) -> Result<ElevationTile, GalileoError> {
+        self.decode(&input)
+    }
+}
+
+/// In-memory cache of decoded elevation tiles.
+///
+/// Everything that needs elevation data (hillshading, terrain meshes, contour lines, elevation queries) should use
+/// clones of the same cache, so that every tile is loaded and decoded only once. If a tile is requested while it is
+/// being loaded, the request waits for the loading to finish instead of loading the tile again.
+///
+/// ```no_run
+/// use galileo::elevation::{ElevationCache, ElevationDecoder, ElevationEncoding};
+/// use galileo::layer::data_provider::UrlDataProvider;
+/// use galileo::tile_scheme::{TileIndex, TileSchema};
+/// use galileo_types::cartesian::Point2d;
+///
+/// # async fn run() -> Result<(), galileo::error::GalileoError> {
+/// let cache = ElevationCache::new(
+///     TileSchema::web(15),
+///     UrlDataProvider::new(
+///         |index: &TileIndex| format!("https://example.com/dem/{}/{}/{}.png", index.z, index.x, index.y),
+///         ElevationDecoder::new(ElevationEncoding::TerrainRgb),
+///     ),
+/// );
+///
+/// let elevation = cache.elevation_at(&Point2d::new(960000.0, 6000000.0), 12).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ElevationCache<Provider>
+where
+    Provider: DataProvider<TileIndex, ElevationTile, ()>,
+{
+    tile_schema: Arc<TileSchema>,
+    provider: Arc<Provider>,
+    tiles: Arc<Cache<TileIndex, Arc<ElevationTile>>>,
+}
+
+impl<Provider> Clone for ElevationCache<Provider>
+where
+    Provider: DataProvider<TileIndex, ElevationTile, ()>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tile_schema: self.tile_schema.clone(),
+            provider: self.provider.clone(),
+            tiles: self.tiles.clone(),
+        }
+    }
+}
+
+impl<Provider> ElevationCache<Provider>
+where
+    Provider: DataProvider<TileIndex, ElevationTile, ()> + MaybeSend + MaybeSync,
+{
+    const DEFAULT_CAPACITY: usize = 256;
+
+    /// Creates a new cache that loads the tiles of the `tile_schema` with the `provider`.
+    pub fn new(tile_schema: TileSchema, provider: Provider) -> Self {
+        Self::with_capacity(tile_schema, provider, Self::DEFAULT_CAPACITY)
+    }
+
+    /// Creates a new cache that keeps at most `capacity` decoded tiles.
+    pub fn with_capacity(tile_schema: TileSchema, provider: Provider, capacity: usize) -> Self {
+        Self {
+            tile_schema: Arc::new(tile_schema),
+            provider: Arc::new(provider),
+            tiles: Arc::new(Cache::new(capacity.max(1))),
+        }
+    }
+
+    /// Tile schema of the elevation tiles.
+    pub fn tile_schema(&self) -> &TileSchema {
+        &self.tile_schema
+    }
+
+    /// Returns the tile if it is already loaded.
+    pub fn get_tile(&self, index: TileIndex) -> Option<Arc<ElevationTile>> {
+        self.tiles.get(&index)
+    }
+
+    /// Returns the tile, loading it if it is not loaded yet.
+    pub async fn load_tile(&self, index: TileIndex) -> Result<Arc<ElevationTile>, GalileoError> {
+        match self.tiles.get_value_or_guard_async(&index).await {
+            Ok(tile) => Ok(tile),
+            Err(guard) => {
+                let tile = Arc::new(self.provider.load(&index, ()).await?);
+                let _ = guard.insert(tile.clone());
+                Ok(tile)
+            }
+        }
+    }
+
+    /// Returns the elevation at the `point` (in the CRS of the tile schema) using the tile of the `z` level, loading
+    /// the tile if needed. Returns `None` if the point is outside the tile schema.
+    pub async fn elevation_at(
+        &self,
+        point: &impl CartesianPoint2d<Num = f64>,
+        z: u32,
+    ) -> Result<Option<f32>, GalileoError> {
+        let Some(index) = self.tile_schema.tile_at(point, z) else {
+            return Ok(None);
+        };
+
+        let tile = self.load_tile(index).await?;
+        Ok(self.sample(&tile, index, point))
+    }
+
+    /// Returns the elevation at the `point` if the tile of the `z` level containing the point is already loaded.
+    pub fn cached_elevation_at(
+        &self,
+        point: &impl CartesianPoint2d<Num = f64>,
+        z: u32,
+    ) -> Option<f32> {
+        let index = self.tile_schema.tile_at(point, z)?;
+        let tile = self.get_tile(index)?;
+        self.sample(&tile, index, point)
+    }
+
+    fn sample(
+        &self,
+        tile: &ElevationTile,
+        index: TileIndex,
+        point: &impl CartesianPoint2d<Num = f64>,
+    ) -> Option<f32> {
+        let bbox = self.tile_schema.tile_bbox(index)?;
+        let u = (point.x() - bbox.x_min()) / bbox.width();
+        let v = (bbox.y_max() - point.y()) / bbox.height();
+
+        Some(tile.sample(u, v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_encodings() {
+        let image = DecodedImage::from_rgba(vec![1, 134, 160, 255, 128, 0, 0, 255], 2, 1).unwrap();
+
+        let tile = ElevationTile::from_image(&image, ElevationEncoding::TerrainRgb).unwrap();
+        assert!((tile.get(0, 0).unwrap() - 0.0).abs() < 0.01);
+
+        let tile = ElevationTile::from_image(&image, ElevationEncoding::Terrarium).unwrap();
+        assert_eq!(tile.get(1, 0), Some(0.0));
+        assert_eq!(tile.get(2, 0), None);
+    }
+
+    #[test]
+    fn sample_interpolates_values() {
+        let tile = ElevationTile::new(2, 2, vec![0.0, 10.0, 20.0, 30.0]).unwrap();
+
+        assert_eq!(tile.sample(0.0, 0.0), 0.0);
+        assert_eq!(tile.sample(1.0, 1.0), 30.0);
+        assert_eq!(tile.sample(0.5, 0.5), 15.0);
+        assert_eq!(tile.sample(0.5, 0.0), 5.0);
+        assert_eq!(tile.range(), (0.0, 30.0));
+    }
+}
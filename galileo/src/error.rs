@@ -33,6 +33,13 @@ pub enum GalileoError {
     FsIo(#[from] std::io::Error),
 }
 
+impl GalileoError {
+    /// Returns true if the error may not happen again if the operation is repeated, e.g. a network error.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::IO | Self::Wasm(_))
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl From<reqwest::Error> for GalileoError {
     fn from(_value: reqwest::Error) -> Self {
@@ -1,4 +1,4 @@
-use crate::control::{EventProcessor, EventPropagation, MapController, UserEvent};
+use crate::control::{EventProcessor, EventPropagation, InteractionConfig, UserEvent};
 use crate::layer::data_provider::UrlSource;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::layer::Layer;
@@ -11,6 +11,7 @@ use galileo_types::cartesian::Size;
 use galileo_types::geo::impls::GeoPoint2d;
 use maybe_sync::{MaybeSend, MaybeSync};
 use std::sync::{Arc, RwLock};
+use web_time::Duration;
 use winit::dpi::PhysicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
@@ -73,9 +74,13 @@ impl GalileoMap {
                             }
 
                             *backend.write().expect("poisoned lock") = Some(renderer);
-                            map.write()
-                                .expect("poisoned lock")
-                                .set_size(Size::new(size.width as f64, size.height as f64));
+                            {
+                                let mut map = map.write().expect("poisoned lock");
+                                map.set_size(logical_size(new_size, window.scale_factor()));
+                                map.set_scale_factor(window.scale_factor());
+                                // Layers may keep bundles packed by the renderer that was dropped on suspend.
+                                map.reset_gpu_resources();
+                            }
                             window.request_redraw();
                         });
                     }
@@ -95,30 +100,46 @@ impl GalileoMap {
                                     backend.resize(Size::new(size.width, size.height));
 
                                     let mut map = map.write().expect("lock is poisoned");
-                                    map.set_size(Size::new(size.width as f64, size.height as f64));
+                                    map.set_size(logical_size(size, window.scale_factor()));
                                 }
                             }
+                            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                                // The window is moved to a monitor with another scale factor. The map view is
+                                // measured in logical pixels, so symbols and labels keep their physical size. The
+                                // new physical size of the window comes with the `Resized` event.
+                                let mut map = map.write().expect("lock is poisoned");
+                                map.set_size(logical_size(window.inner_size(), scale_factor));
+                                map.set_scale_factor(scale_factor);
+                            }
                             WindowEvent::RedrawRequested => {
-                                if let Some(backend) =
-                                    backend.read().expect("lock is poisoned").as_ref()
+                                let mut backend_lock = backend.write().expect("lock is poisoned");
+                                if backend_lock
+                                    .as_ref()
+                                    .is_some_and(|backend| backend.is_device_lost())
                                 {
+                                    // The renderer is taken out while it recovers, so that nothing is
+                                    // rendered with the lost device.
+                                    if let Some(renderer) = backend_lock.take() {
+                                        recover_renderer(
+                                            renderer,
+                                            window.clone(),
+                                            backend.clone(),
+                                            map.clone(),
+                                        );
+                                    }
+                                } else if let Some(backend) = backend_lock.as_ref() {
                                     let map = map.read().expect("lock is poisoned");
                                     map.load_layers();
                                     if let Err(err) = backend.render(&map) {
                                         log::error!("Render error: {err:?}");
+                                        // The frame was not drawn, so it is retried a bit later.
+                                        request_redraw_later(window.clone());
                                     }
                                 }
                             }
                             other => {
-                                // Phone emulator in browsers works funny with scaling, using this code fixes it.
-                                // But my real phone works fine without it, so it's commented out for now, and probably
-                                // should be deleted later, when we know that it's not needed on any devices.
-
-                                // #[cfg(target_arch = "wasm32")]
-                                // let scale = window.scale_factor();
-                                //
-                                // #[cfg(not(target_arch = "wasm32"))]
-                                let scale = 1.0;
+                                // Pointer positions are given in physical pixels, and the map view is in logical ones.
+                                let scale = window.scale_factor();
 
                                 if let Some(raw_event) =
                                     input_handler.process_user_input(&other, scale)
@@ -139,6 +160,51 @@ impl GalileoMap {
     }
 }
 
+/// Size of the map view in logical pixels for the window of the given physical size.
+fn logical_size(size: PhysicalSize<u32>, scale_factor: f64) -> Size {
+    let logical = size.to_logical::<f64>(scale_factor);
+    Size::new(logical.width, logical.height)
+}
+
+/// Delay before the map is drawn again after the renderer failed to draw it or to recover.
+const RECOVERY_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Requests a redraw of the window after [`RECOVERY_RETRY_DELAY`].
+fn request_redraw_later(window: Arc<Window>) {
+    crate::async_runtime::spawn(async move {
+        crate::async_runtime::sleep(RECOVERY_RETRY_DELAY).await;
+        window.request_redraw();
+    });
+}
+
+/// Creates a new device for the renderer after the device was lost, and puts the renderer back to the `backend`.
+///
+/// If the recovery fails, the renderer is put back still marked as lost, and a redraw is requested after
+/// [`RECOVERY_RETRY_DELAY`] to retry the recovery.
+fn recover_renderer(
+    mut renderer: WgpuRenderer,
+    window: Arc<Window>,
+    backend: Arc<RwLock<Option<WgpuRenderer>>>,
+    map: Arc<RwLock<Map>>,
+) {
+    crate::async_runtime::spawn(async move {
+        let size = window.inner_size();
+        if let Err(err) = renderer
+            .recover_with_window(window.clone(), Size::new(size.width, size.height))
+            .await
+        {
+            log::error!("Failed to recover the renderer after the device loss: {err}");
+            *backend.write().expect("lock is poisoned") = Some(renderer);
+            request_redraw_later(window);
+            return;
+        }
+
+        *backend.write().expect("lock is poisoned") = Some(renderer);
+        map.write().expect("lock is poisoned").reset_gpu_resources();
+        window.request_redraw();
+    });
+}
+
 #[cfg(target_arch = "wasm32")]
 type EventHandler = dyn (Fn(&UserEvent, &mut Map) -> EventPropagation);
 #[cfg(not(target_arch = "wasm32"))]
@@ -152,6 +218,7 @@ pub struct MapBuilder {
     pub(crate) view: Option<MapView>,
     pub(crate) layers: Vec<Box<dyn Layer>>,
     pub(crate) event_handlers: Vec<Box<EventHandler>>,
+    pub(crate) interaction: InteractionConfig,
     pub(crate) window: Option<Window>,
     pub(crate) event_loop: Option<EventLoop<()>>,
 }
@@ -192,7 +259,7 @@ impl MapBuilder {
         for handler in self.event_handlers.drain(..) {
             event_processor.add_handler(handler);
         }
-        event_processor.add_handler(MapController::default());
+        self.interaction.add_handlers(&mut event_processor);
 
         GalileoMap {
             window,
@@ -255,6 +322,14 @@ impl MapBuilder {
         self
     }
 
+    /// Set the gestures the user can control the map with, e.g. a preset
+    /// [`InteractionProfile`](crate::control::InteractionProfile). The navigation handler of the configuration is
+    /// added after all the event handlers of the builder.
+    pub fn with_interaction(mut self, config: impl Into<InteractionConfig>) -> Self {
+        self.interaction = config.into();
+        self
+    }
+
     /// Add an event handler.
     pub fn with_event_handler(
         mut self,
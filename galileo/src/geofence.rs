@@ -0,0 +1,486 @@
+//! Monitoring of a moving position against a set of geographic areas. See [`GeofenceMonitor`].
+//!
+//! The monitor does not track the position by itself. The application feeds it the positions from any location
+//! source (GNSS receiver, browser geolocation, recorded track etc.) with [`GeofenceMonitor::update`], and gets back
+//! the events that these positions caused. The fences can be displayed on the map with a
+//! [`GeofenceLayer`](crate::layer::GeofenceLayer).
+
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Datum, GeoCircle, GeoPoint, PathType};
+use galileo_types::impls::Polygon;
+use std::sync::{Arc, Mutex};
+use web_time::{Duration, SystemTime};
+
+/// Identifier of a fence added to a [`GeofenceMonitor`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FenceId(u64);
+
+/// Area of a [`Geofence`].
+#[derive(Debug, Clone)]
+pub enum FenceShape {
+    /// Polygon in geographic coordinates. Holes of the polygon are not part of the area.
+    Polygon(Polygon<GeoPoint2d>),
+    /// Circle with the radius in meters.
+    Circle(GeoCircle),
+}
+
+impl FenceShape {
+    /// Returns true if the point is inside the area.
+    pub fn contains(&self, point: &impl GeoPoint<Num = f64>) -> bool {
+        self.signed_distance(point) <= 0.0
+    }
+
+    /// Distance from the point to the boundary of the area in meters, negative if the point is inside.
+    ///
+    /// For polygons the distance is calculated in a local plane around the point, so it is only accurate for points
+    /// that are not farther than a few hundred kilometers from the boundary. This is enough to apply the hysteresis.
+    pub fn signed_distance(&self, point: &impl GeoPoint<Num = f64>) -> f64 {
+        match self {
+            FenceShape::Circle(circle) => {
+                PathType::GreatCircle.distance(&circle.center, point) - circle.radius
+            }
+            FenceShape::Polygon(polygon) => polygon_signed_distance(polygon, point),
+        }
+    }
+
+    /// Approximates the area with a polygon with the given tolerance in meters.
+    pub fn to_polygon(&self, tolerance: f64) -> Polygon<GeoPoint2d> {
+        match self {
+            FenceShape::Circle(circle) => circle.to_polygon(tolerance),
+            FenceShape::Polygon(polygon) => polygon.clone(),
+        }
+    }
+}
+
+impl From<Polygon<GeoPoint2d>> for FenceShape {
+    fn from(value: Polygon<GeoPoint2d>) -> Self {
+        Self::Polygon(value)
+    }
+}
+
+impl From<GeoCircle> for FenceShape {
+    fn from(value: GeoCircle) -> Self {
+        Self::Circle(value)
+    }
+}
+
+/// Named area watched by a [`GeofenceMonitor`].
+#[derive(Debug, Clone)]
+pub struct Geofence {
+    /// Name of the fence.
+    pub name: String,
+    /// Area of the fence.
+    pub shape: FenceShape,
+    /// If set, a [`GeofenceEventKind::Dwell`] event is emitted once the position stays inside the fence for this time.
+    pub dwell_time: Option<Duration>,
+}
+
+impl Geofence {
+    /// Creates a new fence without dwell time.
+    pub fn new(name: impl Into<String>, shape: impl Into<FenceShape>) -> Self {
+        Self {
+            name: name.into(),
+            shape: shape.into(),
+            dwell_time: None,
+        }
+    }
+
+    /// Sets the dwell time of the fence.
+    pub fn with_dwell_time(mut self, dwell_time: Duration) -> Self {
+        self.dwell_time = Some(dwell_time);
+        self
+    }
+}
+
+/// Kind of a [`GeofenceEvent`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GeofenceEventKind {
+    /// The position entered the fence.
+    Enter,
+    /// The position left the fence.
+    Exit,
+    /// The position stayed inside the fence for its dwell time.
+    Dwell,
+}
+
+/// Event emitted by a [`GeofenceMonitor`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GeofenceEvent {
+    /// Fence the event is about.
+    pub fence: FenceId,
+    /// Kind of the event.
+    pub kind: GeofenceEventKind,
+    /// Last known position at the moment of the event.
+    pub position: GeoPoint2d,
+    /// Time of the event.
+    pub time: SystemTime,
+}
+
+/// Watches a position against a set of [`Geofence`]s and reports when it enters, leaves or dwells in them.
+///
+/// To avoid a stream of enter and exit events when the position jitters near the boundary of a fence, the position
+/// must be deeper than the hysteresis distance inside the fence to enter it, and farther than the hysteresis
+/// distance outside to leave it. The first position only sets the initial state: if it is inside a fence, the
+/// `Enter` event is emitted, but being outside does not produce an `Exit` event.
+///
+/// The monitor is cheap to clone, all the clones share the same fences and state. This allows giving a clone to a
+/// [`GeofenceLayer`](crate::layer::GeofenceLayer) and keep updating the position from the application.
+///
+/// ```
+/// use galileo::geofence::{GeofenceEventKind, Geofence, GeofenceMonitor};
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::{GeoCircle, NewGeoPoint};
+/// use web_time::{Duration, SystemTime};
+///
+/// let monitor = GeofenceMonitor::new().with_hysteresis(20.0);
+/// let harbour = monitor.add(
+///     Geofence::new("Harbour", GeoCircle::new(GeoPoint2d::latlon(59.9, 10.7), 500.0))
+///         .with_dwell_time(Duration::from_secs(60)),
+/// );
+///
+/// let start = SystemTime::now();
+/// let events = monitor.update(GeoPoint2d::latlon(59.9, 10.7), start);
+/// assert_eq!(events[0].fence, harbour);
+/// assert_eq!(events[0].kind, GeofenceEventKind::Enter);
+///
+/// let events = monitor.tick(start + Duration::from_secs(90));
+/// assert_eq!(events[0].kind, GeofenceEventKind::Dwell);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GeofenceMonitor {
+    state: Arc<Mutex<MonitorState>>,
+}
+
+#[derive(Debug, Default)]
+struct MonitorState {
+    fences: Vec<FenceEntry>,
+    next_id: u64,
+    hysteresis: f64,
+    position: Option<GeoPoint2d>,
+    revision: u64,
+}
+
+#[derive(Debug)]
+struct FenceEntry {
+    id: FenceId,
+    fence: Geofence,
+    presence: Presence,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Presence {
+    Unknown,
+    Outside,
+    Inside {
+        since: SystemTime,
+        dwell_reported: bool,
+    },
+}
+
+impl GeofenceMonitor {
+    /// Creates a new monitor without fences and without hysteresis.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the hysteresis distance in meters.
+    pub fn with_hysteresis(self, hysteresis: f64) -> Self {
+        self.state.lock().expect("mutex is poisoned").hysteresis = hysteresis.max(0.0);
+        self
+    }
+
+    /// Adds a fence. Its state is updated with the next position.
+    pub fn add(&self, fence: Geofence) -> FenceId {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let id = FenceId(state.next_id);
+        state.next_id += 1;
+        state.fences.push(FenceEntry {
+            id,
+            fence,
+            presence: Presence::Unknown,
+        });
+        state.revision += 1;
+
+        id
+    }
+
+    /// Removes the fence, returning it if it was in the monitor. No event is emitted for the removed fence.
+    pub fn remove(&self, id: FenceId) -> Option<Geofence> {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let index = state.fences.iter().position(|entry| entry.id == id)?;
+        state.revision += 1;
+
+        Some(state.fences.remove(index).fence)
+    }
+
+    /// Returns a copy of the fence with the given id.
+    pub fn fence(&self, id: FenceId) -> Option<Geofence> {
+        let state = self.state.lock().expect("mutex is poisoned");
+        state
+            .fences
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.fence.clone())
+    }
+
+    /// Ids of all the fences in the order they were added.
+    pub fn fence_ids(&self) -> Vec<FenceId> {
+        let state = self.state.lock().expect("mutex is poisoned");
+        state.fences.iter().map(|entry| entry.id).collect()
+    }
+
+    /// Returns true if the position is currently considered to be inside the fence.
+    pub fn is_inside(&self, id: FenceId) -> bool {
+        let state = self.state.lock().expect("mutex is poisoned");
+        state
+            .fences
+            .iter()
+            .any(|entry| entry.id == id && matches!(entry.presence, Presence::Inside { .. }))
+    }
+
+    /// Last position given to the monitor.
+    pub fn position(&self) -> Option<GeoPoint2d> {
+        self.state.lock().expect("mutex is poisoned").position
+    }
+
+    /// Updates the position and returns the events it caused, including dwell events that became due at `time`.
+    pub fn update(&self, position: GeoPoint2d, time: SystemTime) -> Vec<GeofenceEvent> {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.position = Some(position);
+
+        let hysteresis = state.hysteresis;
+        let mut events = vec![];
+        let mut changed = false;
+        for entry in &mut state.fences {
+            let distance = entry.fence.shape.signed_distance(&position);
+            let is_inside = matches!(entry.presence, Presence::Inside { .. });
+
+            if !is_inside && distance < -hysteresis {
+                entry.presence = Presence::Inside {
+                    since: time,
+                    dwell_reported: false,
+                };
+                events.push(entry.event(GeofenceEventKind::Enter, position, time));
+                changed = true;
+            } else if is_inside && distance > hysteresis {
+                entry.presence = Presence::Outside;
+                events.push(entry.event(GeofenceEventKind::Exit, position, time));
+                changed = true;
+            } else if entry.presence == Presence::Unknown && distance > hysteresis {
+                entry.presence = Presence::Outside;
+            }
+
+            events.extend(entry.check_dwell(position, time));
+        }
+
+        if changed {
+            state.revision += 1;
+        }
+
+        events
+    }
+
+    /// Updates the position with every point of the track in order and returns all the events they caused.
+    pub fn update_track(
+        &self,
+        positions: impl IntoIterator<Item = (GeoPoint2d, SystemTime)>,
+    ) -> Vec<GeofenceEvent> {
+        positions
+            .into_iter()
+            .flat_map(|(position, time)| self.update(position, time))
+            .collect()
+    }
+
+    /// Returns dwell events that became due at `time` without changing the position. Call this periodically if
+    /// positions come rarely, e.g. when the device is not moving.
+    pub fn tick(&self, time: SystemTime) -> Vec<GeofenceEvent> {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let Some(position) = state.position else {
+            return vec![];
+        };
+
+        state
+            .fences
+            .iter_mut()
+            .filter_map(|entry| entry.check_dwell(position, time))
+            .collect()
+    }
+
+    /// Calls `f` for every fence with a flag whether the position is inside it.
+    pub(crate) fn for_each_fence(&self, mut f: impl FnMut(&Geofence, bool)) {
+        let state = self.state.lock().expect("mutex is poisoned");
+        for entry in &state.fences {
+            f(
+                &entry.fence,
+                matches!(entry.presence, Presence::Inside { .. }),
+            );
+        }
+    }
+
+    /// Number that changes every time fences are added or removed, or the position enters or leaves a fence.
+    pub(crate) fn revision(&self) -> u64 {
+        self.state.lock().expect("mutex is poisoned").revision
+    }
+}
+
+impl FenceEntry {
+    fn event(
+        &self,
+        kind: GeofenceEventKind,
+        position: GeoPoint2d,
+        time: SystemTime,
+    ) -> GeofenceEvent {
+        GeofenceEvent {
+            fence: self.id,
+            kind,
+            position,
+            time,
+        }
+    }
+
+    fn check_dwell(&mut self, position: GeoPoint2d, time: SystemTime) -> Option<GeofenceEvent> {
+        let dwell_time = self.fence.dwell_time?;
+        let Presence::Inside {
+            since,
+            dwell_reported: false,
+        } = self.presence
+        else {
+            return None;
+        };
+
+        if time.duration_since(since).unwrap_or_default() < dwell_time {
+            return None;
+        }
+
+        self.presence = Presence::Inside {
+            since,
+            dwell_reported: true,
+        };
+        Some(self.event(GeofenceEventKind::Dwell, position, time))
+    }
+}
+
+/// Signed distance from the point to the boundary of the polygon, calculated in an equirectangular plane centered at
+/// the point.
+fn polygon_signed_distance(polygon: &Polygon<GeoPoint2d>, point: &impl GeoPoint<Num = f64>) -> f64 {
+    let meters_per_degree = Datum::WGS84.mean_radius().to_radians();
+    let lon_scale = point.lat().to_radians().cos() * meters_per_degree;
+    let to_local = |p: &GeoPoint2d| {
+        let lon = (p.lon() - point.lon() + 540.0) % 360.0 - 180.0;
+        (lon * lon_scale, (p.lat() - point.lat()) * meters_per_degree)
+    };
+
+    let mut is_inside = false;
+    let mut distance = f64::INFINITY;
+    let rings = std::iter::once(&polygon.outer_contour).chain(&polygon.inner_contours);
+    for ring in rings {
+        let points: Vec<_> = ring.points.iter().map(to_local).collect();
+        for (i, &(x1, y1)) in points.iter().enumerate() {
+            let (x2, y2) = points[(i + 1) % points.len()];
+            if (y1 > 0.0) != (y2 > 0.0) && x1 + (x2 - x1) * (0.0 - y1) / (y2 - y1) > 0.0 {
+                is_inside = !is_inside;
+            }
+
+            distance = distance.min(origin_segment_distance((x1, y1), (x2, y2)));
+        }
+    }
+
+    if is_inside {
+        -distance
+    } else {
+        distance
+    }
+}
+
+fn origin_segment_distance((x1, y1): (f64, f64), (x2, y2): (f64, f64)) -> f64 {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (-(x1 * dx + y1 * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (x1 + t * dx).hypot(y1 + t * dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::geo::NewGeoPoint;
+    use galileo_types::impls::ClosedContour;
+
+    fn square() -> Polygon<GeoPoint2d> {
+        Polygon::new(
+            ClosedContour::new(vec![
+                GeoPoint2d::latlon(0.0, 0.0),
+                GeoPoint2d::latlon(0.0, 0.01),
+                GeoPoint2d::latlon(0.01, 0.01),
+                GeoPoint2d::latlon(0.01, 0.0),
+            ]),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn polygon_distance() {
+        let shape = FenceShape::from(square());
+        // 0.001° of latitude is about 111 m.
+        let inside = shape.signed_distance(&GeoPoint2d::latlon(0.001, 0.005));
+        assert!((inside + 111.2).abs() < 0.5, "{inside}");
+        let outside = shape.signed_distance(&GeoPoint2d::latlon(-0.001, 0.005));
+        assert!((outside - 111.2).abs() < 0.5, "{outside}");
+        assert!(!shape.contains(&GeoPoint2d::latlon(0.005, 0.02)));
+    }
+
+    #[test]
+    fn hysteresis_suppresses_jitter() {
+        let monitor = GeofenceMonitor::new().with_hysteresis(50.0);
+        let fence = monitor.add(Geofence::new("square", square()));
+        let time = SystemTime::UNIX_EPOCH;
+
+        // Starting outside produces no events.
+        assert!(monitor
+            .update(GeoPoint2d::latlon(-0.002, 0.005), time)
+            .is_empty());
+        // Just inside the boundary, but not deeper than the hysteresis.
+        assert!(monitor
+            .update(GeoPoint2d::latlon(0.0002, 0.005), time)
+            .is_empty());
+        assert!(!monitor.is_inside(fence));
+
+        let events = monitor.update(GeoPoint2d::latlon(0.001, 0.005), time);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, GeofenceEventKind::Enter);
+        assert!(monitor.is_inside(fence));
+
+        // Jitter just outside does not leave the fence.
+        assert!(monitor
+            .update(GeoPoint2d::latlon(-0.0002, 0.005), time)
+            .is_empty());
+        let events = monitor.update(GeoPoint2d::latlon(-0.001, 0.005), time);
+        assert_eq!(events[0].kind, GeofenceEventKind::Exit);
+    }
+
+    #[test]
+    fn dwell_is_reported_once() {
+        let monitor = GeofenceMonitor::new();
+        let circle = GeoCircle::new(GeoPoint2d::latlon(10.0, 10.0), 1000.0);
+        monitor.add(Geofence::new("circle", circle).with_dwell_time(Duration::from_secs(10)));
+
+        let start = SystemTime::UNIX_EPOCH;
+        let track = (0..30).map(|i| {
+            (
+                GeoPoint2d::latlon(10.0, 10.0),
+                start + Duration::from_secs(i),
+            )
+        });
+        let kinds: Vec<_> = monitor
+            .update_track(track)
+            .iter()
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(kinds, [GeofenceEventKind::Enter, GeofenceEventKind::Dwell]);
+        assert!(monitor.tick(start + Duration::from_secs(100)).is_empty());
+    }
+}
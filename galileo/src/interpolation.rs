@@ -0,0 +1,421 @@
+//! Interpolation of scattered point measurements (sensor readings, travel times etc.) into a raster surface, and
+//! extraction of isolines from the surface.
+//!
+//! The surface is an [`ElevationTile`], so it can be colored with a
+//! [`ColorMap`](crate::layer::data_provider::ColorMap) like any other single-band raster. The
+//! [`SurfaceLayer`](crate::layer::SurfaceLayer) does both steps for a set of geographic measurements and draws the
+//! result on the map.
+
+use crate::elevation::ElevationTile;
+use crate::error::GalileoError;
+use galileo_types::cartesian::{Point2d, Rect};
+use galileo_types::impls::Contour;
+use std::collections::HashMap;
+
+/// Inverse distance weighting interpolation.
+///
+/// The value at a position is the average of the measured values weighted by `1 / distance^power`, so the surface
+/// passes through every measurement and is smooth between them. A higher power makes the influence of the closest
+/// measurements stronger.
+///
+/// ```
+/// use galileo::interpolation::Idw;
+/// use galileo_types::cartesian::{Point2d, Rect};
+///
+/// let readings = [
+///     (Point2d::new(0.0, 0.0), 10.0),
+///     (Point2d::new(100.0, 0.0), 20.0),
+///     (Point2d::new(50.0, 100.0), 30.0),
+/// ];
+///
+/// let idw = Idw::new().with_power(2.0);
+/// assert_eq!(idw.value_at(&readings, Point2d::new(100.0, 0.0)), Some(20.0));
+///
+/// let surface = idw
+///     .interpolate(&readings, Rect::new(0.0, 0.0, 100.0, 100.0), 64, 64)
+///     .expect("valid grid");
+/// assert_eq!(surface.width(), 64);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Idw {
+    power: f64,
+    radius: Option<f64>,
+    max_points: Option<usize>,
+}
+
+impl Default for Idw {
+    fn default() -> Self {
+        Self {
+            power: 2.0,
+            radius: None,
+            max_points: None,
+        }
+    }
+}
+
+impl Idw {
+    /// Creates a new interpolation with power 2, using all the measurements for every position.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the power of the distance in the weights.
+    pub fn with_power(mut self, power: f64) -> Self {
+        self.power = power;
+        self
+    }
+
+    /// Uses only the measurements closer than `radius`. Positions without measurements in the radius have no value.
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    /// Uses only the given number of the closest measurements for every position.
+    pub fn with_max_points(mut self, max_points: usize) -> Self {
+        self.max_points = Some(max_points.max(1));
+        self
+    }
+
+    /// Interpolated value at the position. Returns `None` if no measurement can be used for the position.
+    pub fn value_at(&self, points: &[(Point2d, f64)], position: Point2d) -> Option<f64> {
+        let mut neighbours: Vec<(f64, f64)> = points
+            .iter()
+            .filter(|(_, value)| value.is_finite())
+            .map(|(point, value)| ((point.x - position.x).hypot(point.y - position.y), *value))
+            .filter(|(distance, _)| self.radius.is_none_or(|radius| *distance <= radius))
+            .collect();
+
+        if let Some(max_points) = self.max_points {
+            if neighbours.len() > max_points {
+                neighbours.select_nth_unstable_by(max_points, |a, b| a.0.total_cmp(&b.0));
+                neighbours.truncate(max_points);
+            }
+        }
+
+        let mut weight_sum = 0.0;
+        let mut value_sum = 0.0;
+        for (distance, value) in neighbours {
+            if distance == 0.0 {
+                return Some(value);
+            }
+
+            let weight = distance.powf(-self.power);
+            weight_sum += weight;
+            value_sum += weight * value;
+        }
+
+        (weight_sum > 0.0).then(|| value_sum / weight_sum)
+    }
+
+    /// Interpolates the measurements into a grid of `width` x `height` cells covering the `bounds`. The values are
+    /// calculated for the centers of the cells, row by row starting from the top (`y_max`) left corner. Cells without
+    /// a value are `NaN`.
+    pub fn interpolate(
+        &self,
+        points: &[(Point2d, f64)],
+        bounds: Rect,
+        width: u32,
+        height: u32,
+    ) -> Result<ElevationTile, GalileoError> {
+        let cell_width = bounds.width() / width as f64;
+        let cell_height = bounds.height() / height as f64;
+
+        let mut values = Vec::with_capacity(width as usize * height as usize);
+        for row in 0..height {
+            let y = bounds.y_max() - (row as f64 + 0.5) * cell_height;
+            for column in 0..width {
+                let x = bounds.x_min() + (column as f64 + 0.5) * cell_width;
+                let value = self.value_at(points, Point2d::new(x, y));
+                values.push(value.map_or(f32::NAN, |v| v as f32));
+            }
+        }
+
+        ElevationTile::new(width, height, values)
+    }
+}
+
+/// Lines of equal value of a surface.
+#[derive(Debug, Clone)]
+pub struct Isoline {
+    /// Value of the surface along the lines.
+    pub level: f64,
+    /// Lines in the coordinates of the surface bounds. Lines around the areas that are completely inside the surface
+    /// are closed, the ones ending at the edges of the surface or at cells without data are open.
+    pub lines: Vec<Contour<Point2d>>,
+}
+
+/// Extracts the isolines of the given levels from the surface covering the `bounds` (see [`Idw::interpolate`] for
+/// the layout of the surface). Levels without lines are omitted.
+pub fn isolines(surface: &ElevationTile, bounds: Rect, levels: &[f64]) -> Vec<Isoline> {
+    levels
+        .iter()
+        .filter_map(|&level| {
+            let lines = trace_level(surface, bounds, level);
+            (!lines.is_empty()).then_some(Isoline { level, lines })
+        })
+        .collect()
+}
+
+/// Crossing of the isoline with an edge between the centers of two neighbouring cells. `horizontal` edges go from
+/// cell `(x, y)` to `(x + 1, y)`, vertical ones from `(x, y)` to `(x, y + 1)`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Edge {
+    x: u32,
+    y: u32,
+    horizontal: bool,
+}
+
+/// Marching squares over the squares formed by the centers of four neighbouring cells.
+fn trace_level(surface: &ElevationTile, bounds: Rect, level: f64) -> Vec<Contour<Point2d>> {
+    let (width, height) = (surface.width(), surface.height());
+    if width < 2 || height < 2 {
+        return vec![];
+    }
+
+    let value = |x: u32, y: u32| surface.get(x, y).map_or(f64::NAN, |v| v as f64);
+    let cell_width = bounds.width() / width as f64;
+    let cell_height = bounds.height() / height as f64;
+    let position = |x: f64, y: f64| {
+        Point2d::new(
+            bounds.x_min() + (x + 0.5) * cell_width,
+            bounds.y_max() - (y + 0.5) * cell_height,
+        )
+    };
+
+    let mut segments: Vec<(Edge, Edge)> = vec![];
+    let mut crossings: HashMap<Edge, Point2d> = HashMap::new();
+    for y in 0..height - 1 {
+        for x in 0..width - 1 {
+            let corners = [
+                value(x, y),
+                value(x + 1, y),
+                value(x + 1, y + 1),
+                value(x, y + 1),
+            ];
+            if corners.iter().any(|v| v.is_nan()) {
+                continue;
+            }
+
+            // Edges of the square in the order top, right, bottom, left, each with its end corners.
+            let edges = [
+                (
+                    Edge {
+                        x,
+                        y,
+                        horizontal: true,
+                    },
+                    0,
+                    1,
+                ),
+                (
+                    Edge {
+                        x: x + 1,
+                        y,
+                        horizontal: false,
+                    },
+                    1,
+                    2,
+                ),
+                (
+                    Edge {
+                        x,
+                        y: y + 1,
+                        horizontal: true,
+                    },
+                    3,
+                    2,
+                ),
+                (
+                    Edge {
+                        x,
+                        y,
+                        horizontal: false,
+                    },
+                    0,
+                    3,
+                ),
+            ];
+            let crossed: Vec<usize> = (0..4)
+                .filter(|&i| {
+                    let (_, a, b) = edges[i];
+                    (corners[a] >= level) != (corners[b] >= level)
+                })
+                .collect();
+
+            for &i in &crossed {
+                let (edge, a, b) = edges[i];
+                crossings.entry(edge).or_insert_with(|| {
+                    let t = (level - corners[a]) / (corners[b] - corners[a]);
+                    if edge.horizontal {
+                        position(edge.x as f64 + t, edge.y as f64)
+                    } else {
+                        position(edge.x as f64, edge.y as f64 + t)
+                    }
+                });
+            }
+
+            match crossed[..] {
+                [a, b] => segments.push((edges[a].0, edges[b].0)),
+                [top, right, bottom, left] => {
+                    // Saddle: the center of the square decides which corners are connected.
+                    let center = corners.iter().sum::<f64>() / 4.0;
+                    if (center >= level) == (corners[0] >= level) {
+                        segments.push((edges[top].0, edges[right].0));
+                        segments.push((edges[bottom].0, edges[left].0));
+                    } else {
+                        segments.push((edges[top].0, edges[left].0));
+                        segments.push((edges[right].0, edges[bottom].0));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    join_segments(&segments)
+        .into_iter()
+        .map(|(edges, is_closed)| {
+            let points = edges.iter().map(|edge| crossings[edge]).collect();
+            Contour::new(points, is_closed)
+        })
+        .collect()
+}
+
+/// Joins the segments sharing the ends into chains. Every edge is an end of at most two segments.
+fn join_segments(segments: &[(Edge, Edge)]) -> Vec<(Vec<Edge>, bool)> {
+    let mut by_edge: HashMap<Edge, Vec<usize>> = HashMap::new();
+    for (index, (a, b)) in segments.iter().enumerate() {
+        by_edge.entry(*a).or_default().push(index);
+        by_edge.entry(*b).or_default().push(index);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let next = |edge: Edge, used: &[bool]| {
+        by_edge[&edge]
+            .iter()
+            .copied()
+            .find(|&segment| !used[segment])
+    };
+    let other_end = |segment: usize, edge: Edge| {
+        let (a, b) = segments[segment];
+        if a == edge {
+            b
+        } else {
+            a
+        }
+    };
+
+    let mut chains = vec![];
+    for start in 0..segments.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+
+        let (first, second) = segments[start];
+        let mut chain = vec![first, second];
+        let mut end = second;
+        while let Some(segment) = next(end, &used) {
+            used[segment] = true;
+            end = other_end(segment, end);
+            chain.push(end);
+        }
+
+        if end == first && chain.len() > 2 {
+            chain.pop();
+            chains.push((chain, true));
+            continue;
+        }
+
+        // The chain is open, so it is extended from the other end as well.
+        let mut start_edge = first;
+        let mut head = vec![];
+        while let Some(segment) = next(start_edge, &used) {
+            used[segment] = true;
+            start_edge = other_end(segment, start_edge);
+            head.push(start_edge);
+        }
+        head.reverse();
+        head.extend(chain);
+        chains.push((head, false));
+    }
+
+    chains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::Contour as _;
+
+    #[test]
+    fn idw_passes_through_measurements() {
+        let points = [
+            (Point2d::new(0.0, 0.0), 0.0),
+            (Point2d::new(10.0, 0.0), 10.0),
+        ];
+        let idw = Idw::new();
+        assert_eq!(idw.value_at(&points, Point2d::new(0.0, 0.0)), Some(0.0));
+        assert_eq!(idw.value_at(&points, Point2d::new(5.0, 0.0)), Some(5.0));
+
+        let near = idw
+            .value_at(&points, Point2d::new(1.0, 0.0))
+            .expect("has value");
+        assert!(near > 0.0 && near < 1.0);
+
+        let limited = idw.with_radius(3.0);
+        assert_eq!(limited.value_at(&points, Point2d::new(5.0, 0.0)), None);
+        assert_eq!(
+            limited.value_at(&points, Point2d::new(8.0, 0.0)),
+            Some(10.0)
+        );
+
+        let nearest = Idw::new().with_max_points(1);
+        assert_eq!(nearest.value_at(&points, Point2d::new(4.0, 0.0)), Some(0.0));
+    }
+
+    #[test]
+    fn isolines_of_a_cone_are_closed_circles() {
+        let size = 41;
+        let values = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as f32 - 20.0, (i / size) as f32 - 20.0);
+                100.0 - x.hypot(y) * 5.0
+            })
+            .collect();
+        let surface = ElevationTile::new(size, size, values).expect("valid tile");
+        let bounds = Rect::new(0.0, 0.0, 41.0, 41.0);
+
+        let isolines = isolines(&surface, bounds, &[50.0, 80.0, 200.0]);
+        assert_eq!(isolines.len(), 2);
+
+        for isoline in &isolines {
+            assert_eq!(isoline.lines.len(), 1);
+            let line = &isoline.lines[0];
+            assert!(line.is_closed());
+
+            let radius = (100.0 - isoline.level) / 5.0;
+            for point in line.iter_points() {
+                let distance = (point.x - 20.5).hypot(point.y - 20.5);
+                assert!((distance - radius).abs() < 0.1, "{distance} vs {radius}");
+            }
+        }
+    }
+
+    #[test]
+    fn isolines_are_cut_by_missing_data() {
+        let mut values = vec![0.0; 24];
+        for y in 0..6 {
+            values[y * 4 + 2] = 10.0;
+            values[y * 4 + 3] = 10.0;
+        }
+        values[4 * 2 + 1] = f32::NAN;
+        let surface = ElevationTile::new(4, 6, values).expect("valid tile");
+
+        let isolines = isolines(&surface, Rect::new(0.0, 0.0, 4.0, 6.0), &[5.0]);
+        let lines = &isolines[0].lines;
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| !line.is_closed()));
+    }
+}
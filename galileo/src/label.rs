@@ -0,0 +1,457 @@
+//! Screen-space placement of labels: priorities, density limits and zoom-based declutter rules.
+//!
+//! The placement does not draw anything. It decides which of the candidate labels are displayed with the current
+//! [`MapView`], so that dense datasets produce readable maps without pre-filtering the data by hand. The selected
+//! labels can then be drawn by the application (e.g. with a UI framework on top of the map) or by a layer.
+
+use crate::occlusion::Occlusion;
+use crate::view::MapView;
+use galileo_types::cartesian::{CartesianPoint2dFloat, Point2d, Point3d, Rect, Size};
+use std::collections::HashMap;
+
+/// Label that may be displayed on the map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelCandidate {
+    /// Anchor point of the label in the CRS of the map view.
+    pub position: Point2d,
+    /// Size of the label box in pixels.
+    pub size: Size,
+    /// Offset of the center of the label box from the anchor point, in pixels.
+    pub offset: Point2d,
+    /// Labels with higher priority are placed first.
+    pub priority: i32,
+    /// Class of the label (e.g. "city" or "river"), used by the spacing and declutter rules.
+    pub class: Option<String>,
+}
+
+impl LabelCandidate {
+    /// Creates a new label centered at the `position`, with priority 0 and without class.
+    pub fn new(position: Point2d, size: Size) -> Self {
+        Self {
+            position,
+            size,
+            offset: Point2d::new(0.0, 0.0),
+            priority: 0,
+            class: None,
+        }
+    }
+
+    /// Sets the offset of the label box center from the anchor point.
+    pub fn with_offset(mut self, dx: f64, dy: f64) -> Self {
+        self.offset = Point2d::new(dx, dy);
+        self
+    }
+
+    /// Sets the priority of the label.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the class of the label.
+    pub fn with_class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// Box of the label on the screen with the given view, in pixels. Returns `None` if the anchor point is behind the
+    /// camera.
+    pub fn screen_box(&self, view: &MapView) -> Option<Rect> {
+        let anchor = view.map_to_screen(self.position).position()?;
+        Some(self.box_at(anchor))
+    }
+
+    fn box_at(&self, anchor: Point2d) -> Rect {
+        let center = Point2d::new(anchor.x + self.offset.x, anchor.y + self.offset.y);
+        Rect::new(
+            center.x - self.size.half_width(),
+            center.y - self.size.half_height(),
+            center.x + self.size.half_width(),
+            center.y + self.size.half_height(),
+        )
+    }
+}
+
+/// Rule changing the placement of labels in a range of map resolutions.
+///
+/// The rule applies to the labels of the given class, or to all labels if the class is not set. When several rules
+/// apply to a label, the first one in the list is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclutterRule {
+    class: Option<String>,
+    min_resolution: f64,
+    max_resolution: f64,
+    min_priority: Option<i32>,
+    min_spacing: Option<f64>,
+    hidden: bool,
+}
+
+impl Default for DeclutterRule {
+    fn default() -> Self {
+        Self {
+            class: None,
+            min_resolution: 0.0,
+            max_resolution: f64::INFINITY,
+            min_priority: None,
+            min_spacing: None,
+            hidden: false,
+        }
+    }
+}
+
+impl DeclutterRule {
+    /// Creates a rule that applies to all labels at all resolutions and changes nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies the rule only to the labels of the given class.
+    pub fn for_class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// Applies the rule only when the map resolution is in the given range (inclusive).
+    pub fn with_resolution_range(mut self, min_resolution: f64, max_resolution: f64) -> Self {
+        self.min_resolution = min_resolution;
+        self.max_resolution = max_resolution;
+        self
+    }
+
+    /// Hides labels with priority lower than the given one.
+    pub fn with_min_priority(mut self, min_priority: i32) -> Self {
+        self.min_priority = Some(min_priority);
+        self
+    }
+
+    /// Overrides the minimum spacing between labels of the same class, in pixels.
+    pub fn with_min_spacing(mut self, min_spacing: f64) -> Self {
+        self.min_spacing = Some(min_spacing);
+        self
+    }
+
+    /// Hides all the labels the rule applies to.
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    fn applies(&self, label: &LabelCandidate, resolution: f64) -> bool {
+        (self.min_resolution..=self.max_resolution).contains(&resolution)
+            && (self.class.is_none() || self.class == label.class)
+    }
+}
+
+/// Density limits for [label placement](LabelDensity::place).
+///
+/// Labels are placed in the order of decreasing priority. A label is skipped if:
+/// * it is hidden by a [`DeclutterRule`],
+/// * its box (extended by the padding) overlaps a box of an already placed label,
+/// * it is closer than the minimum spacing to a placed label of the same class,
+/// * the screen cell containing its anchor already has the maximum number of labels.
+///
+/// ```
+/// use galileo::label::{DeclutterRule, LabelCandidate, LabelDensity};
+/// use galileo::MapView;
+/// use galileo_types::cartesian::{Point2d, Size};
+///
+/// let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(400.0, 400.0));
+/// let candidates = vec![
+///     LabelCandidate::new(Point2d::new(0.0, 0.0), Size::new(60.0, 16.0)).with_class("city").with_priority(10),
+///     LabelCandidate::new(Point2d::new(20.0, 0.0), Size::new(60.0, 16.0)).with_class("city"),
+///     LabelCandidate::new(Point2d::new(0.0, 100.0), Size::new(60.0, 16.0)).with_class("village"),
+/// ];
+///
+/// let density = LabelDensity::new()
+///     .with_min_spacing(50.0)
+///     .with_rule(DeclutterRule::new().for_class("village").with_resolution_range(0.5, 1000.0).hidden());
+///
+/// // The second city overlaps the first one, and villages are hidden at this resolution.
+/// assert_eq!(density.place(&candidates, &view), vec![0]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelDensity {
+    padding: f64,
+    min_spacing: f64,
+    max_per_cell: Option<(usize, f64)>,
+    rules: Vec<DeclutterRule>,
+}
+
+impl Default for LabelDensity {
+    fn default() -> Self {
+        Self {
+            padding: 2.0,
+            min_spacing: 0.0,
+            max_per_cell: None,
+            rules: vec![],
+        }
+    }
+}
+
+impl LabelDensity {
+    /// Creates density settings that only prevent labels from overlapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum free space around each label box, in pixels. Default is 2.
+    pub fn with_padding(mut self, padding: f64) -> Self {
+        self.padding = padding.max(0.0);
+        self
+    }
+
+    /// Sets the minimum distance between the anchors of labels of the same class, in pixels. Labels without class are
+    /// not affected. Default is 0.
+    pub fn with_min_spacing(mut self, min_spacing: f64) -> Self {
+        self.min_spacing = min_spacing.max(0.0);
+        self
+    }
+
+    /// Limits the number of labels in every square screen cell with the side of `cell_size` pixels.
+    pub fn with_max_labels_per_cell(mut self, max_labels: usize, cell_size: f64) -> Self {
+        self.max_per_cell = Some((max_labels, cell_size.max(1.0)));
+        self
+    }
+
+    /// Adds a declutter rule. Rules are checked in the order they were added.
+    pub fn with_rule(mut self, rule: DeclutterRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Declutter rules.
+    pub fn rules(&self) -> &[DeclutterRule] {
+        &self.rules
+    }
+
+    /// Selects the labels to be displayed with the given view. Returns indices of the selected candidates in the order
+    /// they were placed (by decreasing priority). Labels with equal priority are placed in the order of the candidates
+    /// list.
+    pub fn place(&self, candidates: &[LabelCandidate], view: &MapView) -> Vec<usize> {
+        self.place_visible(candidates, view, |_| true)
+    }
+
+    /// Same as [`LabelDensity::place`], but skips labels whose anchors are hidden from the camera by terrain or
+    /// buildings. Hidden labels do not take space, so labels behind them can be placed instead.
+    pub fn place_with_occlusion(
+        &self,
+        candidates: &[LabelCandidate],
+        view: &MapView,
+        occlusion: &Occlusion,
+    ) -> Vec<usize> {
+        self.place_visible(candidates, view, |label| {
+            let position = Point3d::new(label.position.x, label.position.y, 0.0);
+            occlusion.is_visible(view, position)
+        })
+    }
+
+    fn place_visible(
+        &self,
+        candidates: &[LabelCandidate],
+        view: &MapView,
+        is_visible: impl Fn(&LabelCandidate) -> bool,
+    ) -> Vec<usize> {
+        let resolution = view.resolution();
+        let screen = Rect::new(0.0, 0.0, view.size().width(), view.size().height());
+
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(candidates[i].priority));
+
+        let mut boxes = BoxGrid::new(64.0);
+        let mut anchors: HashMap<&str, Vec<Point2d>> = HashMap::new();
+        let mut cell_counts: HashMap<(i64, i64), usize> = HashMap::new();
+        let mut placed = vec![];
+
+        for index in order {
+            let label = &candidates[index];
+            let rule = self.rules.iter().find(|r| r.applies(label, resolution));
+            if let Some(rule) = rule {
+                if rule.hidden || rule.min_priority.is_some_and(|p| label.priority < p) {
+                    continue;
+                }
+            }
+
+            let Some(anchor) = view.map_to_screen(label.position).position() else {
+                continue;
+            };
+            let label_box = label.box_at(anchor);
+            if !label_box.intersects(screen) {
+                continue;
+            }
+
+            let padded = label_box.shrink(-self.padding);
+            if boxes.intersects(padded) {
+                continue;
+            }
+
+            let spacing = rule.and_then(|r| r.min_spacing).unwrap_or(self.min_spacing);
+            if let Some(class) = &label.class {
+                let too_close = anchors
+                    .get(class.as_str())
+                    .is_some_and(|a| a.iter().any(|p| p.distance(&anchor) < spacing));
+                if too_close {
+                    continue;
+                }
+            }
+
+            let cell = self.max_per_cell.map(|(max_labels, cell_size)| {
+                let cell = (
+                    (anchor.x / cell_size).floor() as i64,
+                    (anchor.y / cell_size).floor() as i64,
+                );
+                (cell, max_labels)
+            });
+            if let Some((cell, max_labels)) = cell {
+                if cell_counts
+                    .get(&cell)
+                    .is_some_and(|count| *count >= max_labels)
+                {
+                    continue;
+                }
+            }
+
+            // Checked last, as this is the most expensive check.
+            if !is_visible(label) {
+                continue;
+            }
+
+            if let Some((cell, _)) = cell {
+                *cell_counts.entry(cell).or_default() += 1;
+            }
+
+            boxes.insert(padded);
+            if let Some(class) = &label.class {
+                anchors.entry(class.as_str()).or_default().push(anchor);
+            }
+            placed.push(index);
+        }
+
+        placed
+    }
+}
+
+/// Boxes of the placed labels indexed by the screen cells they cover.
+pub(crate) struct BoxGrid {
+    cell_size: f64,
+    boxes: Vec<Rect>,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl BoxGrid {
+    pub(crate) fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size,
+            boxes: vec![],
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cells(&self, rect: Rect) -> impl Iterator<Item = (i64, i64)> {
+        let x_min = (rect.x_min() / self.cell_size).floor() as i64;
+        let x_max = (rect.x_max() / self.cell_size).floor() as i64;
+        let y_min = (rect.y_min() / self.cell_size).floor() as i64;
+        let y_max = (rect.y_max() / self.cell_size).floor() as i64;
+        (x_min..=x_max).flat_map(move |x| (y_min..=y_max).map(move |y| (x, y)))
+    }
+
+    pub(crate) fn intersects(&self, rect: Rect) -> bool {
+        self.cells(rect).any(|cell| {
+            self.cells
+                .get(&cell)
+                .is_some_and(|ids| ids.iter().any(|&id| overlaps(self.boxes[id], rect)))
+        })
+    }
+
+    pub(crate) fn insert(&mut self, rect: Rect) {
+        let id = self.boxes.len();
+        self.boxes.push(rect);
+        let cells: Vec<_> = self.cells(rect).collect();
+        for cell in cells {
+            self.cells.entry(cell).or_default().push(id);
+        }
+    }
+}
+
+/// Strict overlap: boxes that only touch each other do not overlap.
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.x_min() < b.x_max() && b.x_min() < a.x_max() && a.y_min() < b.y_max() && b.y_min() < a.y_max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view() -> MapView {
+        MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(1000.0, 1000.0))
+    }
+
+    fn label(x: f64, y: f64) -> LabelCandidate {
+        LabelCandidate::new(Point2d::new(x, y), Size::new(10.0, 10.0))
+    }
+
+    #[test]
+    fn higher_priority_wins_collisions() {
+        let candidates = vec![
+            label(0.0, 0.0),
+            label(5.0, 0.0).with_priority(1),
+            label(100.0, 0.0),
+        ];
+        let placed = LabelDensity::new().place(&candidates, &view());
+        assert_eq!(placed, vec![1, 2]);
+    }
+
+    #[test]
+    fn spacing_cell_limits_and_rules() {
+        let candidates: Vec<_> = (0..10)
+            .map(|i| {
+                label(i as f64 * 20.0, 0.0)
+                    .with_class("poi")
+                    .with_priority(i)
+            })
+            .collect();
+
+        let placed = LabelDensity::new()
+            .with_min_spacing(50.0)
+            .place(&candidates, &view());
+        assert_eq!(placed, vec![9, 6, 3, 0]);
+
+        let placed = LabelDensity::new()
+            .with_max_labels_per_cell(1, 1000.0)
+            .place(&candidates, &view());
+        assert_eq!(placed, vec![9]);
+
+        let density = LabelDensity::new().with_rule(
+            DeclutterRule::new()
+                .for_class("poi")
+                .with_resolution_range(0.5, 2.0)
+                .with_min_priority(8),
+        );
+        assert_eq!(density.place(&candidates, &view()), vec![9, 8]);
+        let zoomed_in = view().with_resolution(0.4);
+        assert_eq!(density.place(&candidates, &zoomed_in).len(), 10);
+    }
+
+    #[test]
+    fn occluded_labels_are_not_placed() {
+        use crate::occlusion::ExtrusionOccluder;
+        use galileo_types::impls::ClosedContour;
+
+        let building = ClosedContour::new(vec![
+            Point2d::new(-20.0, 80.0),
+            Point2d::new(20.0, 80.0),
+            Point2d::new(20.0, 120.0),
+            Point2d::new(-20.0, 120.0),
+        ]);
+        let occlusion =
+            Occlusion::new().with_occluder(ExtrusionOccluder::new(vec![(building.into(), 200.0)]));
+        let view = view().with_rotation_x(60f64.to_radians());
+
+        let candidates = vec![label(0.0, 200.0).with_priority(1), label(0.0, -200.0)];
+        let density = LabelDensity::new();
+        assert_eq!(density.place(&candidates, &view), vec![0, 1]);
+        assert_eq!(
+            density.place_with_occlusion(&candidates, &view, &occlusion),
+            vec![1]
+        );
+    }
+}
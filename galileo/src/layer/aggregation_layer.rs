@@ -0,0 +1,555 @@
+//! Aggregation of point features into hexagonal or square bins. See [`AggregationLayer`].
+
+use crate::layer::feature_layer::symbol::ColorRamp;
+use crate::layer::feature_layer::Feature;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{
+    Canvas, LineCap, LinePaint, PackedBundle, PolygonPaint, RenderOptions, SizeUnit,
+};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, GeoPoint, NewGeoPoint, Projection};
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const SQRT_3: f64 = 1.732_050_807_568_877_2;
+
+/// Shape of the bins of an [`AggregationLayer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum BinShape {
+    /// Hexagons with a vertex pointing up.
+    #[default]
+    Hexagon,
+    /// Squares aligned with the axes of the map CRS.
+    Square,
+}
+
+/// Size of the bins of an [`AggregationLayer`]: the distance between the centers of the neighbouring bins in a row,
+/// which is the width of a square or the distance between the opposite sides of a hexagon.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BinSize {
+    /// Size on the screen in pixels. The bins are recomputed when the map is zoomed, with the size changing by the
+    /// factor of 2 between the zoom levels, so that they keep the size on the screen close to the given one.
+    Pixels(f64),
+    /// Size in the units of the map CRS. The bins do not change with zoom.
+    Map(f64),
+}
+
+impl BinSize {
+    /// Size of the bins in map units for the given resolution.
+    fn map_size(&self, resolution: f64) -> f64 {
+        match *self {
+            BinSize::Pixels(pixels) => pixels * 2f64.powf(resolution.log2().round()),
+            BinSize::Map(size) => size,
+        }
+    }
+}
+
+/// Value calculated for each bin of an [`AggregationLayer`] from the features inside it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Aggregate {
+    /// Number of the features.
+    #[default]
+    Count,
+    /// Sum of the numeric attribute with the given name.
+    Sum(String),
+    /// Mean of the numeric attribute with the given name.
+    Mean(String),
+    /// Minimum of the numeric attribute with the given name.
+    Min(String),
+    /// Maximum of the numeric attribute with the given name.
+    Max(String),
+}
+
+impl Aggregate {
+    fn attribute(&self) -> Option<&str> {
+        match self {
+            Aggregate::Count => None,
+            Aggregate::Sum(name)
+            | Aggregate::Mean(name)
+            | Aggregate::Min(name)
+            | Aggregate::Max(name) => Some(name),
+        }
+    }
+}
+
+/// Bin calculated by an [`AggregationLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bin {
+    /// Center of the bin in the CRS of the map.
+    pub center: Point2d,
+    /// Number of the features in the bin.
+    pub count: usize,
+    /// Aggregated value of the bin. `None` if no feature of the bin has a numeric value of the aggregated attribute.
+    pub value: Option<f64>,
+}
+
+/// Layer that draws point features aggregated into a grid of hexagons or squares, colored by the number of the
+/// features in each bin or by an aggregate of their attribute.
+///
+/// Drawing one shape per bin instead of a symbol per feature keeps the map fast and readable with hundreds of
+/// thousands of points. The bins are aligned to the origin of the map CRS, so they do not move while the map is
+/// panned. Bins without features are not drawn.
+///
+/// The colors are taken from the color ramp stretched over the range of the values of all the bins, or over the
+/// [fixed range](AggregationLayer::with_value_range).
+///
+/// ```
+/// use galileo::layer::feature_layer::symbol::ColorRamp;
+/// use galileo::layer::{Aggregate, AggregationLayer, BinShape, BinSize};
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::NewGeoPoint;
+///
+/// let sightings = vec![
+///     GeoPoint2d::latlon(51.50, -0.12),
+///     GeoPoint2d::latlon(51.51, -0.13),
+///     GeoPoint2d::latlon(48.85, 2.35),
+/// ];
+///
+/// let layer = AggregationLayer::new(sightings)
+///     .with_shape(BinShape::Hexagon)
+///     .with_size(BinSize::Pixels(30.0))
+///     .with_aggregate(Aggregate::Count)
+///     .with_color_ramp(ColorRamp::viridis());
+/// ```
+pub struct AggregationLayer<F> {
+    features: Vec<F>,
+    shape: BinShape,
+    size: BinSize,
+    aggregate: Aggregate,
+    color_ramp: ColorRamp,
+    value_range: Option<(f64, f64)>,
+    stroke: Option<(Color, f64)>,
+    points: Mutex<Option<(Crs, Vec<BinnedPoint>)>>,
+    packed: Mutex<Option<(PackKey, Box<dyn PackedBundle>)>>,
+}
+
+/// Feature projected into the map CRS, with the value of the aggregated attribute.
+#[derive(Debug, Copy, Clone)]
+struct BinnedPoint {
+    position: Point2d,
+    value: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PackKey {
+    crs: Crs,
+    size: f64,
+}
+
+impl<F> AggregationLayer<F>
+where
+    F: Feature,
+    F::Geom: GeoPoint<Num = f64>,
+{
+    /// Creates a new layer counting the features in hexagons of 40 pixels.
+    pub fn new(features: Vec<F>) -> Self {
+        Self {
+            features,
+            shape: BinShape::default(),
+            size: BinSize::Pixels(40.0),
+            aggregate: Aggregate::default(),
+            color_ramp: ColorRamp::yellow_orange_red(),
+            value_range: None,
+            stroke: None,
+            points: Mutex::new(None),
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Sets the shape of the bins.
+    pub fn with_shape(mut self, shape: BinShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets the size of the bins.
+    pub fn with_size(mut self, size: BinSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the value calculated for each bin.
+    pub fn with_aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregate = aggregate;
+        self
+    }
+
+    /// Sets the color ramp the values of the bins are mapped to.
+    pub fn with_color_ramp(mut self, color_ramp: ColorRamp) -> Self {
+        self.color_ramp = color_ramp;
+        self
+    }
+
+    /// Maps the values from `min` to `max` to the color ramp, instead of the range of the values of the bins. This
+    /// keeps the colors stable when the map is zoomed.
+    pub fn with_value_range(mut self, min: f64, max: f64) -> Self {
+        self.value_range = Some((min, max));
+        self
+    }
+
+    /// Draws the outlines of the bins with the given color and width in pixels.
+    pub fn with_stroke(mut self, color: Color, width: f64) -> Self {
+        self.stroke = Some((color, width));
+        self
+    }
+
+    /// Features aggregated by the layer.
+    pub fn features(&self) -> &[F] {
+        &self.features
+    }
+
+    /// Replaces the features aggregated by the layer.
+    pub fn set_features(&mut self, features: Vec<F>) {
+        self.features = features;
+        *self.points.get_mut().expect("mutex is poisoned") = None;
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    /// Bins the layer draws with the given view, e.g. to show their values in a tooltip or a legend.
+    pub fn bins(&self, view: &MapView) -> Vec<Bin> {
+        let size = self.size.map_size(view.resolution());
+        self.with_points(view.crs(), |points| {
+            aggregate_points(points, self.shape, size, &self.aggregate)
+        })
+    }
+
+    /// Color of a bin with the given value among the given bins.
+    fn color_of(&self, value: f64, range: (f64, f64)) -> Color {
+        let (min, max) = self.value_range.unwrap_or(range);
+        let t = if max > min {
+            (value - min) / (max - min)
+        } else {
+            1.0
+        };
+
+        self.color_ramp.sample(t)
+    }
+
+    fn with_points<T>(&self, crs: &Crs, f: impl FnOnce(&[BinnedPoint]) -> T) -> T {
+        let mut points = self.points.lock().expect("mutex is poisoned");
+        if !matches!(points.as_ref(), Some((points_crs, _)) if points_crs == crs) {
+            *points = Some((crs.clone(), self.project_points(crs)));
+        }
+
+        f(points
+            .as_ref()
+            .map(|(_, points)| &points[..])
+            .unwrap_or(&[]))
+    }
+
+    fn project_points(&self, crs: &Crs) -> Vec<BinnedPoint> {
+        let Some(projection) = crs.get_projection::<GeoPoint2d, Point2d>() else {
+            return vec![];
+        };
+        let attribute = self.aggregate.attribute();
+
+        self.features
+            .iter()
+            .filter_map(|feature| {
+                let point = feature.geometry();
+                let position = projection.project(&GeoPoint2d::latlon(point.lat(), point.lon()))?;
+                let value = attribute
+                    .and_then(|name| feature.attribute(name))
+                    .and_then(|value| value.as_f64());
+
+                Some(BinnedPoint { position, value })
+            })
+            .collect()
+    }
+
+    fn pack(&self, view: &MapView, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        let resolution = view.resolution();
+        let size = self.size.map_size(resolution);
+
+        let bins = self.with_points(view.crs(), |points| {
+            aggregate_points(points, self.shape, size, &self.aggregate)
+        });
+        let range = bins
+            .iter()
+            .filter_map(|bin| bin.value)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+
+        for bin in &bins {
+            let Some(value) = bin.value else {
+                continue;
+            };
+
+            let outline: Vec<Point3d> = bin_outline(self.shape, size, bin.center)
+                .into_iter()
+                .map(|p| Point3d::new(p.x, p.y, 0.0))
+                .collect();
+            bundle.add(
+                RenderPrimitive::<_, _, Contour<_>, _>::new_polygon(
+                    Polygon::new(ClosedContour::new(outline.clone()), vec![]),
+                    PolygonPaint {
+                        color: self.color_of(value, range),
+                        gradient: None,
+                        zoom: None,
+                    },
+                ),
+                resolution,
+            );
+
+            if let Some((color, width)) = self.stroke {
+                bundle.add(
+                    RenderPrimitive::<_, _, _, Polygon<_>>::new_contour(
+                        Contour::closed(outline),
+                        LinePaint {
+                            color,
+                            width,
+                            offset: 0.0,
+                            width_unit: SizeUnit::Pixels,
+                            line_cap: LineCap::Butt,
+                            pattern: None,
+                            gradient: None,
+                            zoom: None,
+                        },
+                    ),
+                    resolution,
+                );
+            }
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+}
+
+impl<F> Layer for AggregationLayer<F>
+where
+    F: Feature + MaybeSend + MaybeSync + 'static,
+    F::Geom: GeoPoint<Num = f64>,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let key = PackKey {
+            crs: view.crs().clone(),
+            size: self.size.map_size(view.resolution()),
+        };
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let is_outdated = !matches!(packed.as_ref(), Some((packed_key, _)) if *packed_key == key);
+        if is_outdated {
+            *packed = Some((key, self.pack(view, canvas)));
+        }
+
+        if let Some((_, bundle)) = packed.as_ref() {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {}
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct Accumulator {
+    count: usize,
+    value_count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+/// Groups the points into bins and calculates the aggregate of each bin. Bins are sorted by their index.
+fn aggregate_points(
+    points: &[BinnedPoint],
+    shape: BinShape,
+    size: f64,
+    aggregate: &Aggregate,
+) -> Vec<Bin> {
+    let mut bins: HashMap<(i64, i64), Accumulator> = HashMap::new();
+    for point in points {
+        let bin = bins
+            .entry(bin_index(shape, size, point.position))
+            .or_default();
+        bin.count += 1;
+        if let Some(value) = point.value {
+            if bin.value_count == 0 {
+                bin.min = value;
+                bin.max = value;
+            }
+            bin.value_count += 1;
+            bin.sum += value;
+            bin.min = bin.min.min(value);
+            bin.max = bin.max.max(value);
+        }
+    }
+
+    let mut bins: Vec<_> = bins.into_iter().collect();
+    bins.sort_unstable_by_key(|(index, _)| *index);
+    bins.into_iter()
+        .map(|(index, bin)| {
+            let value = match aggregate {
+                Aggregate::Count => Some(bin.count as f64),
+                _ if bin.value_count == 0 => None,
+                Aggregate::Sum(_) => Some(bin.sum),
+                Aggregate::Mean(_) => Some(bin.sum / bin.value_count as f64),
+                Aggregate::Min(_) => Some(bin.min),
+                Aggregate::Max(_) => Some(bin.max),
+            };
+
+            Bin {
+                center: bin_center(shape, size, index),
+                count: bin.count,
+                value,
+            }
+        })
+        .collect()
+}
+
+/// Index of the bin the point belongs to. For hexagons these are the axial coordinates of the hexagon.
+fn bin_index(shape: BinShape, size: f64, point: Point2d) -> (i64, i64) {
+    match shape {
+        BinShape::Square => (
+            (point.x / size).floor() as i64,
+            (point.y / size).floor() as i64,
+        ),
+        BinShape::Hexagon => {
+            let radius = size / SQRT_3;
+            let q = (SQRT_3 / 3.0 * point.x - point.y / 3.0) / radius;
+            let r = (2.0 / 3.0 * point.y) / radius;
+            hex_round(q, r)
+        }
+    }
+}
+
+/// Rounds fractional axial coordinates to the nearest hexagon.
+fn hex_round(q: f64, r: f64) -> (i64, i64) {
+    let s = -q - r;
+    let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+
+    (rq as i64, rr as i64)
+}
+
+fn bin_center(shape: BinShape, size: f64, (i, j): (i64, i64)) -> Point2d {
+    match shape {
+        BinShape::Square => Point2d::new((i as f64 + 0.5) * size, (j as f64 + 0.5) * size),
+        BinShape::Hexagon => Point2d::new(
+            size * (i as f64 + j as f64 / 2.0),
+            size * SQRT_3 / 2.0 * j as f64,
+        ),
+    }
+}
+
+fn bin_outline(shape: BinShape, size: f64, center: Point2d) -> Vec<Point2d> {
+    match shape {
+        BinShape::Square => {
+            let half = size / 2.0;
+            vec![
+                Point2d::new(center.x - half, center.y - half),
+                Point2d::new(center.x + half, center.y - half),
+                Point2d::new(center.x + half, center.y + half),
+                Point2d::new(center.x - half, center.y + half),
+            ]
+        }
+        BinShape::Hexagon => {
+            let radius = size / SQRT_3;
+            (0..6)
+                .map(|i| {
+                    let angle = (30.0 + 60.0 * i as f64).to_radians();
+                    Point2d::new(
+                        center.x + radius * angle.cos(),
+                        center.y + radius * angle.sin(),
+                    )
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f64, y: f64, value: Option<f64>) -> BinnedPoint {
+        BinnedPoint {
+            position: Point2d::new(x, y),
+            value,
+        }
+    }
+
+    #[test]
+    fn points_fall_into_nearest_hexagon() {
+        let size = 10.0;
+        for index in [(0, 0), (3, -2), (-5, 7)] {
+            let center = bin_center(BinShape::Hexagon, size, index);
+            assert_eq!(bin_index(BinShape::Hexagon, size, center), index);
+
+            // Points near the vertices are still closer to the center than to the neighbours.
+            for vertex in bin_outline(BinShape::Hexagon, size, center) {
+                let inner = Point2d::new(
+                    center.x + (vertex.x - center.x) * 0.95,
+                    center.y + (vertex.y - center.y) * 0.95,
+                );
+                assert_eq!(bin_index(BinShape::Hexagon, size, inner), index);
+            }
+        }
+
+        // Neighbouring hexagons in a row are `size` apart.
+        let a = bin_center(BinShape::Hexagon, size, (0, 0));
+        let b = bin_center(BinShape::Hexagon, size, (1, 0));
+        let c = bin_center(BinShape::Hexagon, size, (0, 1));
+        assert!(((b.x - a.x).hypot(b.y - a.y) - size).abs() < 1e-9);
+        assert!(((c.x - a.x).hypot(c.y - a.y) - size).abs() < 1e-9);
+    }
+
+    #[test]
+    fn aggregates_attribute_values() {
+        let points = [
+            point(1.0, 1.0, Some(2.0)),
+            point(2.0, 3.0, Some(4.0)),
+            point(3.0, 3.0, None),
+            point(-1.0, 1.0, None),
+        ];
+
+        let bins = aggregate_points(&points, BinShape::Square, 5.0, &Aggregate::Count);
+        assert_eq!(bins.len(), 2);
+        assert_eq!(bins[0].center, Point2d::new(-2.5, 2.5));
+        assert_eq!(bins[1].value, Some(3.0));
+
+        let mean = aggregate_points(&points, BinShape::Square, 5.0, &Aggregate::Mean("v".into()));
+        assert_eq!(mean[0].value, None);
+        assert_eq!(mean[1].value, Some(3.0));
+        assert_eq!(mean[1].count, 3);
+
+        let max = aggregate_points(&points, BinShape::Square, 5.0, &Aggregate::Max("v".into()));
+        assert_eq!(max[1].value, Some(4.0));
+    }
+
+    #[test]
+    fn pixel_size_changes_by_zoom_levels() {
+        let size = BinSize::Pixels(40.0);
+        assert_eq!(size.map_size(1.0), 40.0);
+        assert_eq!(size.map_size(1.2), 40.0);
+        assert_eq!(size.map_size(1.6), 80.0);
+        assert_eq!(BinSize::Map(500.0).map_size(10.0), 500.0);
+    }
+}
@@ -0,0 +1,557 @@
+use crate::label::BoxGrid;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::occlusion::Occlusion;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{Canvas, LineCap, LinePaint, PackedBundle, RenderOptions, SizeUnit};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d, Rect, Size};
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use nalgebra::{Point2, Vector2};
+use std::any::Any;
+use std::sync::Mutex;
+
+/// Colors and widths of a [`TextBox`] annotation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnnotationStyle {
+    /// Fill color of the box.
+    pub fill: Color,
+    /// Color of the box border.
+    pub border_color: Color,
+    /// Width of the box border in pixels. Zero width disables the border.
+    pub border_width: f32,
+    /// Color of the leader line connecting the box with its anchor.
+    pub leader_color: Color,
+    /// Width of the leader line in pixels.
+    pub leader_width: f32,
+}
+
+impl Default for AnnotationStyle {
+    fn default() -> Self {
+        Self {
+            fill: Color::WHITE,
+            border_color: Color::BLACK,
+            border_width: 1.0,
+            leader_color: Color::BLACK,
+            leader_width: 1.5,
+        }
+    }
+}
+
+/// Text box anchored to a point of the map.
+///
+/// The layer draws the box and, if the box is moved away from the anchor, the leader line (callout) pointing to the
+/// anchor. The text itself is drawn by the application at the position returned by [`AnnotationLayer::layout`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextBox {
+    /// Point of the map the box refers to, in the CRS of the map.
+    pub anchor: Point2d,
+    /// Text of the annotation.
+    pub text: String,
+    /// Size of the box in pixels.
+    pub size: Size,
+    /// Preferred offset of the box center from the anchor, in pixels (`y` axis pointing down).
+    pub offset: Point2d,
+    /// Style of the box.
+    pub style: AnnotationStyle,
+}
+
+impl TextBox {
+    /// Creates a box centered at the anchor point.
+    pub fn new(anchor: Point2d, text: impl Into<String>, size: Size) -> Self {
+        Self {
+            anchor,
+            text: text.into(),
+            size,
+            offset: Point2d::new(0.0, 0.0),
+            style: AnnotationStyle::default(),
+        }
+    }
+
+    /// Sets the preferred offset of the box center from the anchor, turning the box into a callout with a leader line.
+    pub fn with_offset(mut self, dx: f64, dy: f64) -> Self {
+        self.offset = Point2d::new(dx, dy);
+        self
+    }
+
+    /// Sets the style of the box.
+    pub fn with_style(mut self, style: AnnotationStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Offsets of the box center tried in turn until the box does not collide with anything.
+    fn candidate_offsets(&self) -> Vec<Vector2<f64>> {
+        let (dx, dy) = (self.offset.x, self.offset.y);
+        let mut offsets = vec![
+            Vector2::new(dx, dy),
+            Vector2::new(-dx, dy),
+            Vector2::new(dx, -dy),
+            Vector2::new(-dx, -dy),
+            Vector2::new(dy, dx),
+            Vector2::new(-dy, -dx),
+        ];
+        offsets.dedup();
+        offsets
+    }
+}
+
+/// Arrow between two points of the map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arrow {
+    /// Start of the arrow in the CRS of the map.
+    pub from: Point2d,
+    /// Point the arrow head points to, in the CRS of the map.
+    pub to: Point2d,
+    /// Color of the arrow.
+    pub color: Color,
+    /// Width of the arrow line in pixels.
+    pub width: f64,
+    /// Length of the arrow head in pixels.
+    pub head_size: f32,
+}
+
+impl Arrow {
+    /// Creates a black arrow with the line width of 2 pixels.
+    pub fn new(from: Point2d, to: Point2d) -> Self {
+        Self {
+            from,
+            to,
+            color: Color::BLACK,
+            width: 2.0,
+            head_size: 12.0,
+        }
+    }
+
+    /// Sets the color of the arrow.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the width of the arrow line and the length of the arrow head, in pixels.
+    pub fn with_size(mut self, width: f64, head_size: f32) -> Self {
+        self.width = width;
+        self.head_size = head_size;
+        self
+    }
+}
+
+/// Annotation drawn by an [`AnnotationLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Annotation {
+    /// Text box or callout.
+    TextBox(TextBox),
+    /// Arrow.
+    Arrow(Arrow),
+}
+
+impl From<TextBox> for Annotation {
+    fn from(value: TextBox) -> Self {
+        Self::TextBox(value)
+    }
+}
+
+impl From<Arrow> for Annotation {
+    fn from(value: Arrow) -> Self {
+        Self::Arrow(value)
+    }
+}
+
+/// Position of a text box annotation on the screen, calculated by [`AnnotationLayer::layout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextBoxPlacement {
+    /// Index of the annotation in the layer.
+    pub index: usize,
+    /// Position of the anchor point on the screen, in pixels.
+    pub anchor: Point2d,
+    /// Box on the screen, in pixels from the top-left corner.
+    pub screen_box: Rect,
+    /// Visibility of the anchor from the camera, from `0.0` to `1.0` (see
+    /// [`AnnotationLayer::with_occlusion`]). The text of the box should be drawn with this opacity.
+    pub visibility: f64,
+}
+
+/// Layer with map-anchored annotations: text boxes, callouts with leader lines and arrows, for annotated story maps
+/// and report figures.
+///
+/// Text boxes are placed at their preferred offset from the anchor. If the box there would overlap another annotation
+/// or one of the [obstacles](AnnotationLayer::set_obstacles) (e.g. labels placed with
+/// [`LabelDensity`](crate::label::LabelDensity)), the box is moved to the other side of the anchor. If no position is
+/// free, the preferred one is used.
+///
+/// If the map has terrain or 3D buildings, set the [occlusion](AnnotationLayer::with_occlusion) so that annotations
+/// anchored behind them are faded out instead of floating through the hills.
+///
+/// ```
+/// use galileo::layer::{AnnotationLayer, Arrow, TextBox};
+/// use galileo_types::cartesian::{Point2d, Size};
+///
+/// let layer = AnnotationLayer::new(vec![
+///     TextBox::new(Point2d::new(0.0, 0.0), "Landing site", Size::new(100.0, 24.0))
+///         .with_offset(80.0, -40.0)
+///         .into(),
+///     Arrow::new(Point2d::new(-5000.0, 0.0), Point2d::new(0.0, 0.0)).into(),
+/// ]);
+/// ```
+pub struct AnnotationLayer {
+    annotations: Vec<Annotation>,
+    obstacles: Vec<Rect>,
+    padding: f64,
+    occlusion: Option<Occlusion>,
+    packed: Mutex<Option<(MapView, Box<dyn PackedBundle>)>>,
+}
+
+impl AnnotationLayer {
+    /// Creates a new layer with the given annotations.
+    pub fn new(annotations: Vec<Annotation>) -> Self {
+        Self {
+            annotations,
+            obstacles: vec![],
+            padding: 4.0,
+            occlusion: None,
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Sets the minimum free space around the text boxes, in pixels. Default is 4.
+    pub fn with_padding(mut self, padding: f64) -> Self {
+        self.padding = padding.max(0.0);
+        self
+    }
+
+    /// Sets the terrain and buildings that can hide the anchors of the annotations from the camera. Text boxes with
+    /// hidden anchors are not displayed, partially hidden ones are faded, and arrows are not affected.
+    pub fn with_occlusion(mut self, occlusion: Occlusion) -> Self {
+        self.occlusion = Some(occlusion);
+        self
+    }
+
+    /// Replaces the occlusion set with [`AnnotationLayer::with_occlusion`].
+    pub fn set_occlusion(&mut self, occlusion: Option<Occlusion>) {
+        self.occlusion = occlusion;
+        self.invalidate();
+    }
+
+    /// Annotations of the layer.
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    /// Adds an annotation to the layer.
+    pub fn push(&mut self, annotation: impl Into<Annotation>) {
+        self.annotations.push(annotation.into());
+        self.invalidate();
+    }
+
+    /// Removes the annotation with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Annotation {
+        self.invalidate();
+        self.annotations.remove(index)
+    }
+
+    /// Sets the screen areas (in pixels) occupied by other map elements, e.g. labels, that text boxes should avoid.
+    /// The obstacles are usually recalculated on every view change.
+    pub fn set_obstacles(&mut self, obstacles: Vec<Rect>) {
+        self.obstacles = obstacles;
+        self.invalidate();
+    }
+
+    /// Calculates the positions of the text boxes on the screen for the given view, so that the application can draw
+    /// their text. Boxes with anchors hidden by the [occlusion](AnnotationLayer::with_occlusion) are skipped.
+    pub fn layout(&self, view: &MapView) -> Vec<TextBoxPlacement> {
+        let mut occupied = BoxGrid::new(64.0);
+        for obstacle in &self.obstacles {
+            occupied.insert(*obstacle);
+        }
+
+        let mut placements = vec![];
+        for (index, annotation) in self.annotations.iter().enumerate() {
+            let Annotation::TextBox(text_box) = annotation else {
+                continue;
+            };
+            let Some(anchor) = view.map_to_screen(text_box.anchor).position() else {
+                continue;
+            };
+
+            let visibility = self.occlusion.as_ref().map_or(1.0, |occlusion| {
+                let position = Point3d::new(text_box.anchor.x, text_box.anchor.y, 0.0);
+                occlusion.visibility(view, position)
+            });
+            if visibility <= 0.0 {
+                continue;
+            }
+
+            let box_at = |offset: &Vector2<f64>| {
+                let center = anchor + *offset;
+                Rect::new(
+                    center.x - text_box.size.half_width(),
+                    center.y - text_box.size.half_height(),
+                    center.x + text_box.size.half_width(),
+                    center.y + text_box.size.half_height(),
+                )
+            };
+            let offsets = text_box.candidate_offsets();
+            let screen_box = offsets
+                .iter()
+                .map(box_at)
+                .find(|b| !occupied.intersects(b.shrink(-self.padding)))
+                .unwrap_or_else(|| box_at(&offsets[0]));
+
+            occupied.insert(screen_box.shrink(-self.padding));
+            placements.push(TextBoxPlacement {
+                index,
+                anchor,
+                screen_box,
+                visibility,
+            });
+        }
+
+        placements
+    }
+
+    fn invalidate(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn pack(&self, view: &MapView, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        let resolution = view.resolution();
+
+        for annotation in &self.annotations {
+            let Annotation::Arrow(arrow) = annotation else {
+                continue;
+            };
+            let (Some(from), Some(to)) = (
+                view.map_to_screen(arrow.from).position(),
+                view.map_to_screen(arrow.to).position(),
+            ) else {
+                continue;
+            };
+
+            let shaft = Contour::open(vec![
+                Point3d::new(arrow.from.x, arrow.from.y, 0.0),
+                Point3d::new(arrow.to.x, arrow.to.y, 0.0),
+            ]);
+            bundle.add(
+                RenderPrimitive::<_, _, _, Polygon<_>>::new_contour(
+                    shaft,
+                    LinePaint {
+                        color: arrow.color,
+                        width: arrow.width,
+                        offset: 0.0,
+                        width_unit: SizeUnit::Pixels,
+                        line_cap: LineCap::Butt,
+                        pattern: None,
+                        gradient: None,
+                        zoom: None,
+                    },
+                ),
+                resolution,
+            );
+
+            if let Some(head) = arrow_head(to - from, arrow.head_size) {
+                add_shape(
+                    &mut bundle,
+                    arrow.to,
+                    PointPaint::owned_shape(arrow.color, head, 1.0),
+                );
+            }
+        }
+
+        for placement in self.layout(view) {
+            let Annotation::TextBox(text_box) = &self.annotations[placement.index] else {
+                continue;
+            };
+            let style = &text_box.style;
+            let fade =
+                |color: Color| color.with_alpha((color.a() as f64 * placement.visibility) as u8);
+            let anchor = placement.anchor;
+            let b = placement.screen_box;
+
+            let nearest = Point2d::new(
+                anchor.x.clamp(b.x_min(), b.x_max()),
+                anchor.y.clamp(b.y_min(), b.y_max()),
+            );
+            if let Some(leader) = leader_line(nearest - anchor, style.leader_width) {
+                add_shape(
+                    &mut bundle,
+                    text_box.anchor,
+                    PointPaint::owned_shape(fade(style.leader_color), leader, 1.0),
+                );
+            }
+
+            // Shapes are drawn relative to the anchor with the `y` axis pointing up.
+            let corner = |x: f64, y: f64| Point2::new((x - anchor.x) as f32, (anchor.y - y) as f32);
+            let box_shape = ClosedContour::new(vec![
+                corner(b.x_min(), b.y_min()),
+                corner(b.x_max(), b.y_min()),
+                corner(b.x_max(), b.y_max()),
+                corner(b.x_min(), b.y_max()),
+            ]);
+            let mut paint = PointPaint::owned_shape(fade(style.fill), box_shape, 1.0);
+            if style.border_width > 0.0 {
+                paint = paint.with_outline(fade(style.border_color), style.border_width);
+            }
+            add_shape(&mut bundle, text_box.anchor, paint);
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+}
+
+fn add_shape(bundle: &mut RenderBundle, position: Point2d, paint: PointPaint) {
+    bundle.add(
+        RenderPrimitive::<_, _, Contour<_>, Polygon<_>>::new_point(
+            Point3d::new(position.x, position.y, 0.0),
+            paint,
+        ),
+        1.0,
+    );
+}
+
+/// Triangle with the tip at the origin pointing along the screen `direction` (`y` down). The result has `y` up.
+fn arrow_head(direction: Vector2<f64>, size: f32) -> Option<ClosedContour<Point2<f32>>> {
+    let norm = direction.norm();
+    if norm < f64::EPSILON {
+        return None;
+    }
+
+    let dir = Vector2::new(direction.x / norm, -direction.y / norm).cast::<f32>();
+    let normal = Vector2::new(-dir.y, dir.x);
+    let base = -dir * size;
+    let half_width = size / 2.5;
+
+    Some(ClosedContour::new(vec![
+        Point2::new(0.0, 0.0),
+        Point2::from(base + normal * half_width),
+        Point2::from(base - normal * half_width),
+    ]))
+}
+
+/// Thin rectangle from the origin to the screen `vector` (`y` down). The result has `y` up.
+fn leader_line(vector: Vector2<f64>, width: f32) -> Option<ClosedContour<Point2<f32>>> {
+    let length = vector.norm();
+    if length < 1.0 || width <= 0.0 {
+        return None;
+    }
+
+    let end = Vector2::new(vector.x, -vector.y).cast::<f32>();
+    let normal = Vector2::new(-end.y, end.x) / length as f32 * (width / 2.0);
+
+    Some(ClosedContour::new(vec![
+        Point2::from(normal),
+        Point2::from(end + normal),
+        Point2::from(end - normal),
+        Point2::from(-normal),
+    ]))
+}
+
+impl Layer for AnnotationLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let is_outdated = !matches!(packed.as_ref(), Some((packed_view, _)) if packed_view == view);
+        if is_outdated {
+            *packed = Some((view.clone(), self.pack(view, canvas)));
+        }
+
+        if let Some((_, bundle)) = packed.as_ref() {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {}
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+    fn reset_gpu_resources(&mut self) {
+        self.invalidate();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_boxes_avoid_obstacles_and_each_other() {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(400.0, 400.0));
+        let text_box =
+            TextBox::new(Point2d::new(0.0, 0.0), "A", Size::new(40.0, 20.0)).with_offset(50.0, 0.0);
+        let mut layer = AnnotationLayer::new(vec![text_box.clone().into(), text_box.into()]);
+
+        let placements = layer.layout(&view);
+        assert_eq!(
+            placements[0].screen_box,
+            Rect::new(230.0, 190.0, 270.0, 210.0)
+        );
+        assert_eq!(
+            placements[1].screen_box,
+            Rect::new(130.0, 190.0, 170.0, 210.0)
+        );
+
+        layer.set_obstacles(vec![Rect::new(220.0, 180.0, 280.0, 220.0)]);
+        let placements = layer.layout(&view);
+        assert_eq!(
+            placements[0].screen_box,
+            Rect::new(130.0, 190.0, 170.0, 210.0)
+        );
+        assert_eq!(
+            placements[1].screen_box,
+            Rect::new(180.0, 240.0, 220.0, 260.0)
+        );
+    }
+
+    #[test]
+    fn arrow_head_points_along_direction() {
+        let head = arrow_head(Vector2::new(10.0, 0.0), 10.0).expect("non-zero direction");
+        assert_eq!(head.points[0], Point2::new(0.0, 0.0));
+        assert!(head.points[1..].iter().all(|p| p.x == -10.0));
+        assert!(arrow_head(Vector2::new(0.0, 0.0), 10.0).is_none());
+    }
+
+    #[test]
+    fn occluded_text_boxes_are_hidden() {
+        use crate::occlusion::ExtrusionOccluder;
+
+        let building = ClosedContour::new(vec![
+            Point2d::new(-20.0, 80.0),
+            Point2d::new(20.0, 80.0),
+            Point2d::new(20.0, 120.0),
+            Point2d::new(-20.0, 120.0),
+        ]);
+        let occlusion =
+            Occlusion::new().with_occluder(ExtrusionOccluder::new(vec![(building.into(), 200.0)]));
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+            .with_size(Size::new(1000.0, 1000.0))
+            .with_rotation_x(60f64.to_radians());
+
+        let size = Size::new(40.0, 20.0);
+        let mut layer = AnnotationLayer::new(vec![
+            TextBox::new(Point2d::new(0.0, 200.0), "Behind", size).into(),
+            TextBox::new(Point2d::new(0.0, -200.0), "In front", size).into(),
+        ]);
+        assert_eq!(layer.layout(&view).len(), 2);
+
+        layer.set_occlusion(Some(occlusion));
+        let placements = layer.layout(&view);
+        assert_eq!(placements.len(), 1);
+        assert_eq!(placements[0].index, 1);
+        assert_eq!(placements[0].visibility, 1.0);
+    }
+}
@@ -0,0 +1,78 @@
+// Full-screen pass drawing the sky above the horizon and the fog over the far part of the map.
+
+struct ViewUniform {
+    view_proj: mat4x4<f32>,
+    view_rotation: mat4x4<f32>,
+    inv_screen_size: vec2<f32>,
+    resolution: f32,
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> transform: ViewUniform;
+
+struct Atmosphere {
+    // Transforms clip coordinates into map coordinates relative to the center of the view.
+    inv_view_proj: mat4x4<f32>,
+    // x: distance where the fog starts, y: distance where the fog is opaque, z: elevation angle of the sky gradient.
+    fog: vec4<f32>,
+    sky_color: vec4<f32>,
+    horizon_color: vec4<f32>,
+    fog_color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> atmosphere: Atmosphere;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) screen_position: vec2<f32>,
+};
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    // The vertices of the quad are far away in all directions from the origin, so only their signs are used to
+    // cover the whole screen.
+    let corner = sign(model.position.xy);
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(corner, 0.0, 1.0);
+    out.screen_position = corner;
+    return out;
+}
+
+fn unproject(screen_position: vec2<f32>, depth: f32) -> vec3<f32> {
+    let point = atmosphere.inv_view_proj * vec4<f32>(screen_position, depth, 1.0);
+    return point.xyz / point.w;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let near = unproject(in.screen_position, 0.0);
+    let direction = unproject(in.screen_position, 0.25) - near;
+
+    var color: vec4<f32>;
+    if (direction.z < 0.0) {
+        let ground = near + direction * (-near.z / direction.z);
+        let fog = smoothstep(atmosphere.fog.x, atmosphere.fog.y, length(ground.xy));
+        if (fog <= 0.0) {
+            discard;
+        }
+
+        color = linear_color(atmosphere.fog_color);
+        color.a = color.a * fog;
+    } else {
+        let elevation = atan2(direction.z, length(direction.xy));
+        let k = clamp(elevation / atmosphere.fog.z, 0.0, 1.0);
+        color = mix(linear_color(atmosphere.horizon_color), linear_color(atmosphere.sky_color), k);
+    }
+
+    return output_color(with_opacity(color, transform.opacity), transform.encode_srgb);
+}
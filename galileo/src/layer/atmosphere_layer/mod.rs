@@ -0,0 +1,218 @@
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::{Canvas, CustomShader, PackedBundle, PolygonPaint, RenderOptions};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::Point3d;
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use nalgebra::Translation3;
+use std::any::Any;
+use std::mem::size_of;
+use std::sync::Mutex;
+
+/// Distance of the corners of the full-screen quad from the origin. The shader only uses the signs of the coordinates,
+/// and the quad must be large enough not to be culled as invisible for any view.
+const QUAD_EXTENT: f64 = 1e12;
+
+/// Layer that draws the sky above the horizon and a fog that fades the far part of the map out, for views tilted
+/// far enough for the horizon to become visible.
+///
+/// Tiles are only loaded up to the [far distance](MapView::far_distance) of the view, so without this layer a
+/// tilted map ends with a sharp edge at some distance with the background color behind it. The fog reaches full
+/// opacity at the far distance, so the edge is hidden, and the sky fills the space above the horizon with a gradient
+/// from the horizon color to the sky color.
+///
+/// The layer should be the last (top) layer of the map. Nothing is drawn if the view is not tilted.
+///
+/// The sky and the fog are drawn with a custom shader, so they are only displayed by GPU rendering backends.
+pub struct AtmosphereLayer {
+    sky_color: Color,
+    horizon_color: Color,
+    fog_color: Color,
+    fog_start: f64,
+    sky_gradient_angle: f64,
+    shader: CustomShader,
+    packed: Mutex<Option<Box<dyn PackedBundle>>>,
+}
+
+impl Default for AtmosphereLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AtmosphereLayer {
+    /// Creates a new layer with light blue sky and a white-blue haze at the horizon.
+    pub fn new() -> Self {
+        let horizon_color = Color::rgba(220, 232, 245, 255);
+        Self {
+            sky_color: Color::rgba(110, 160, 225, 255),
+            horizon_color,
+            fog_color: horizon_color,
+            fog_start: 0.6,
+            sky_gradient_angle: 30f64.to_radians(),
+            shader: CustomShader::new(include_str!("atmosphere.wgsl"))
+                .with_uniform(&[0; size_of::<AtmosphereUniform>()]),
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Sets the color of the sky high above the horizon and the color of the sky at the horizon. The gradient between
+    /// them goes up to `gradient_angle` (in radians) above the horizon.
+    pub fn with_sky(mut self, sky_color: Color, horizon_color: Color, gradient_angle: f64) -> Self {
+        self.sky_color = sky_color;
+        self.horizon_color = horizon_color;
+        self.sky_gradient_angle = gradient_angle.max(f64::EPSILON);
+        self
+    }
+
+    /// Sets the color of the fog and the distance where it starts, as a portion of the
+    /// [far distance](MapView::far_distance) of the view (from `0.0` to `1.0`). The fog becomes opaque at the far
+    /// distance. For a seamless horizon, the fog color should be the same as the horizon color of the sky.
+    pub fn with_fog(mut self, fog_color: Color, fog_start: f64) -> Self {
+        self.fog_color = fog_color;
+        self.fog_start = fog_start.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Color of the sky high above the horizon.
+    pub fn sky_color(&self) -> Color {
+        self.sky_color
+    }
+
+    /// Color of the sky at the horizon.
+    pub fn horizon_color(&self) -> Color {
+        self.horizon_color
+    }
+
+    /// Color of the fog.
+    pub fn fog_color(&self) -> Color {
+        self.fog_color
+    }
+
+    fn uniform(&self, view: &MapView) -> Option<AtmosphereUniform> {
+        let center = view.projected_position()?;
+        let transform = view.map_to_scene_transform()?
+            * Translation3::new(center.x, center.y, center.z).to_homogeneous();
+        let inv_view_proj = transform.try_inverse()?;
+
+        let far_distance = view.far_distance();
+        Some(AtmosphereUniform {
+            inv_view_proj: inv_view_proj.cast::<f32>().data.0,
+            fog: [
+                (far_distance * self.fog_start) as f32,
+                far_distance as f32,
+                self.sky_gradient_angle as f32,
+                0.0,
+            ],
+            sky_color: self.sky_color.to_f32_array(),
+            horizon_color: self.horizon_color.to_f32_array(),
+            fog_color: self.fog_color.to_f32_array(),
+        })
+    }
+
+    fn pack_quad(canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        let quad = Polygon::new(
+            ClosedContour::new(vec![
+                Point3d::new(-QUAD_EXTENT, -QUAD_EXTENT, 0.0),
+                Point3d::new(QUAD_EXTENT, -QUAD_EXTENT, 0.0),
+                Point3d::new(QUAD_EXTENT, QUAD_EXTENT, 0.0),
+                Point3d::new(-QUAD_EXTENT, QUAD_EXTENT, 0.0),
+            ]),
+            vec![],
+        );
+
+        // The quad is transparent, so that backends without custom shaders draw nothing.
+        bundle.add(
+            RenderPrimitive::<_, _, Contour<_>, _>::new_polygon(
+                quad,
+                PolygonPaint {
+                    color: Color::TRANSPARENT,
+                    gradient: None,
+                    zoom: None,
+                },
+            ),
+            1.0,
+        );
+
+        canvas.pack_bundle(&bundle)
+    }
+}
+
+impl Layer for AtmosphereLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if view.rotation_x() == 0.0 {
+            return;
+        }
+
+        let Some(uniform) = self.uniform(view) else {
+            return;
+        };
+        if let Err(err) = self.shader.set_uniform(bytemuck::bytes_of(&uniform)) {
+            log::warn!("Failed to update atmosphere parameters: {err}");
+            return;
+        }
+
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let quad = packed.get_or_insert_with(|| Self::pack_quad(canvas));
+        canvas.draw_bundles_with_shader(&[&**quad], RenderOptions::default(), &self.shader);
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // Nothing to load
+    }
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {
+        // The layer does not change by itself
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Layout of the `Atmosphere` uniform of the shader.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct AtmosphereUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    fog: [f32; 4],
+    sky_color: [f32; 4],
+    horizon_color: [f32; 4],
+    fog_color: [f32; 4],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::{Point2d, Size};
+    use nalgebra::Point3;
+
+    #[test]
+    fn uniform_unprojects_screen_into_map_relative_to_center() {
+        let view = MapView::new_projected(&Point2d::new(1000.0, 2000.0), 2.0)
+            .with_size(Size::new(200.0, 100.0))
+            .with_rotation_x(0.5);
+        let uniform = AtmosphereLayer::new().uniform(&view).unwrap();
+        let inv_view_proj = nalgebra::Matrix4::from(uniform.inv_view_proj).cast::<f64>();
+
+        // Center of the screen is the center of the view.
+        let point = view.map_to_scene_transform().unwrap()
+            * Point3::new(1000.0, 2000.0, 0.0).to_homogeneous();
+        let unprojected = inv_view_proj * point;
+        assert!((unprojected.x / unprojected.w).abs() < 1e-2);
+        assert!((unprojected.y / unprojected.w).abs() < 1e-2);
+
+        assert_eq!(uniform.fog[1] as f64, view.far_distance());
+    }
+}
@@ -0,0 +1,411 @@
+use crate::control::{Clock, SystemClock};
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::{Layer, LayerGroup};
+use crate::messenger::{Messenger, SharedMessenger};
+use crate::render::{Canvas, SoftwareRenderer};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::Size;
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+use web_time::SystemTime;
+
+const DEFAULT_FADE_DURATION: Duration = Duration::from_millis(300);
+
+/// Layer that shows one of a set of alternative basemaps and cross-fades between them when the basemap is switched.
+///
+/// Each basemap is a [`LayerGroup`] (e.g. "Streets" with a vector tile layer, or "Satellite" with imagery and a
+/// labels overlay). The switcher is added to the map as a single layer below the overlay layers, so switching the
+/// basemap does not touch the rest of the map's [`LayerCollection`](crate::LayerCollection).
+///
+/// To switch the basemap after the switcher was added to the map, keep it behind `Arc<RwLock<_>>` or downcast it
+/// from the map's layers:
+///
+/// ```
+/// use galileo::layer::{BasemapSwitcher, LayerGroup, TestLayer};
+/// use galileo::LayerCollection;
+///
+/// let switcher = BasemapSwitcher::new(vec![
+///     LayerGroup::new("Streets").with_layer(TestLayer("OSM")),
+///     LayerGroup::new("Satellite").with_layer(TestLayer("Imagery")),
+/// ]);
+///
+/// let mut layers = LayerCollection::default();
+/// layers.push(switcher);
+/// layers.push(TestLayer("Operational"));
+///
+/// let switcher = layers[0]
+///     .as_any_mut()
+///     .downcast_mut::<BasemapSwitcher>()
+///     .unwrap();
+/// switcher.switch_to_name("Satellite").unwrap();
+/// assert_eq!(switcher.active_basemap().name(), "Satellite");
+/// ```
+pub struct BasemapSwitcher {
+    basemaps: Vec<LayerGroup>,
+    active: usize,
+    fade: Option<Fade>,
+    fade_duration: Duration,
+    clock: Box<dyn Clock>,
+    messenger: Option<Arc<dyn Messenger>>,
+}
+
+/// Cross-fade from the previously active basemap.
+struct Fade {
+    from: usize,
+    start: SystemTime,
+}
+
+impl BasemapSwitcher {
+    /// Creates a new switcher with the given basemaps. The first basemap is active.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `basemaps` is empty.
+    pub fn new(basemaps: Vec<LayerGroup>) -> Self {
+        assert!(!basemaps.is_empty(), "at least one basemap is required");
+
+        Self {
+            basemaps,
+            active: 0,
+            fade: None,
+            fade_duration: DEFAULT_FADE_DURATION,
+            clock: Box::new(SystemClock),
+            messenger: None,
+        }
+    }
+
+    /// Sets the duration of the cross-fade between basemaps. Zero duration switches basemaps instantly.
+    pub fn with_fade_duration(mut self, duration: Duration) -> Self {
+        self.fade_duration = duration;
+        self
+    }
+
+    /// Sets the clock used to time the cross-fade.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Duration of the cross-fade between basemaps.
+    pub fn fade_duration(&self) -> Duration {
+        self.fade_duration
+    }
+
+    /// All basemaps of the switcher.
+    pub fn basemaps(&self) -> &[LayerGroup] {
+        &self.basemaps
+    }
+
+    /// Mutable reference to the basemap with the given index.
+    pub fn basemap_mut(&mut self, index: usize) -> Option<&mut LayerGroup> {
+        self.basemaps.get_mut(index)
+    }
+
+    /// Adds a basemap to the end of the list. If the switcher already has a messenger, it is shared with the basemap.
+    pub fn push(&mut self, mut basemap: LayerGroup) {
+        if let Some(messenger) = &self.messenger {
+            basemap.set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        }
+
+        self.basemaps.push(basemap);
+    }
+
+    /// Index of the active basemap.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// The active basemap.
+    pub fn active_basemap(&self) -> &LayerGroup {
+        &self.basemaps[self.active]
+    }
+
+    /// Index of the basemap with the given name.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.basemaps.iter().position(|b| b.name() == name)
+    }
+
+    /// Makes the basemap with the given index active, cross-fading from the currently displayed one. The new basemap
+    /// fades in over the previous one, which is drawn until the fade is finished.
+    ///
+    /// Returns an error if there is no basemap with this index.
+    pub fn switch_to(&mut self, index: usize) -> Result<(), GalileoError> {
+        if index >= self.basemaps.len() {
+            return Err(GalileoError::Generic(format!(
+                "basemap index {index} is out of range, the switcher has {} basemaps",
+                self.basemaps.len()
+            )));
+        }
+
+        if index == self.active {
+            return Ok(());
+        }
+
+        let now = self.clock.now();
+        self.fade = (!self.fade_duration.is_zero()).then_some(Fade {
+            from: self.active,
+            start: now,
+        });
+        self.active = index;
+
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+
+        Ok(())
+    }
+
+    /// Makes the basemap with the given name active, cross-fading from the currently displayed one.
+    ///
+    /// Returns an error if there is no basemap with this name.
+    pub fn switch_to_name(&mut self, name: &str) -> Result<(), GalileoError> {
+        let index = self
+            .position(name)
+            .ok_or_else(|| GalileoError::Generic(format!("basemap '{name}' does not exist")))?;
+        self.switch_to(index)
+    }
+
+    /// Returns true if the switcher is cross-fading between two basemaps.
+    pub fn is_fading(&self) -> bool {
+        self.fade_state().is_some()
+    }
+
+    /// Renders a preview of the basemap with the given index into an image of `size` pixels, e.g. for a basemap picker.
+    ///
+    /// The preview shows the area of the `view` scaled down to the size of the image. Layers load their data
+    /// asynchronously, so the first preview of a basemap may be incomplete. The basemap is prepared for the preview
+    /// view by this method, so calling it again after the data is loaded gives the complete image.
+    ///
+    /// Returns an error if there is no basemap with this index or the size is zero.
+    pub fn render_thumbnail(
+        &self,
+        index: usize,
+        view: &MapView,
+        size: Size<u32>,
+    ) -> Result<DecodedImage, GalileoError> {
+        let basemap = self.basemaps.get(index).ok_or_else(|| {
+            GalileoError::Generic(format!("basemap index {index} is out of range"))
+        })?;
+        if size.width() == 0 || size.height() == 0 {
+            return Err(GalileoError::Generic(
+                "thumbnail size cannot be zero".into(),
+            ));
+        }
+
+        let thumbnail_size = Size::new(size.width() as f64, size.height() as f64);
+        let view_width = view.size().width();
+        let view = if view_width > 0.0 {
+            view.with_resolution(view.resolution() * view_width / thumbnail_size.width())
+                .with_size(thumbnail_size)
+        } else {
+            view.with_size(thumbnail_size)
+        };
+        basemap.prepare(&view);
+
+        let mut renderer = SoftwareRenderer::new(size);
+        renderer.set_background(Color::TRANSPARENT);
+        renderer.render_layers([basemap as &dyn Layer], &view);
+
+        DecodedImage::from_rgba(renderer.get_image(), size.width(), size.height())
+    }
+
+    /// Index of the basemap being faded out and the progress of the fade, if the fade is not finished yet.
+    fn fade_state(&self) -> Option<(usize, f32)> {
+        let fade = self.fade.as_ref()?;
+        let elapsed = self
+            .clock
+            .now()
+            .duration_since(fade.start)
+            .unwrap_or_default();
+        (elapsed < self.fade_duration).then(|| {
+            (
+                fade.from,
+                elapsed.as_secs_f32() / self.fade_duration.as_secs_f32(),
+            )
+        })
+    }
+}
+
+impl Layer for BasemapSwitcher {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let active = &self.basemaps[self.active];
+        let Some((from, k)) = self.fade_state() else {
+            active.render(view, canvas);
+            return;
+        };
+
+        // The previous basemap stays opaque under the fading in one, so the background never shows through.
+        self.basemaps[from].render(view, canvas);
+        active.render_with_opacity(view, canvas, k);
+
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    fn prepare(&self, view: &MapView) {
+        self.basemaps[self.active].prepare(view);
+        if let Some((from, _)) = self.fade_state() {
+            self.basemaps[from].prepare(view);
+        }
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = Arc::from(messenger);
+        for basemap in &mut self.basemaps {
+            basemap.set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        }
+
+        self.messenger = Some(messenger);
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        for basemap in &mut self.basemaps {
+            basemap.set_scale_factor(scale_factor);
+        }
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        for basemap in &mut self.basemaps {
+            basemap.reset_gpu_resources();
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::ManualClock;
+    use crate::render::render_bundle::RenderPrimitive;
+    use crate::render::{PolygonPaint, RenderOptions};
+    use crate::{DummyMessenger, Map};
+    use galileo_types::cartesian::{Point2d, Point3d};
+    use galileo_types::impls::{ClosedContour, Contour, Polygon};
+
+    /// Layer that fills the whole view with one color.
+    struct FillLayer(Color);
+
+    impl Layer for FillLayer {
+        fn render(&self, _view: &MapView, canvas: &mut dyn Canvas) {
+            let mut bundle = canvas.create_bundle();
+            let points = [(-1e3, -1e3), (1e3, -1e3), (1e3, 1e3), (-1e3, 1e3)]
+                .map(|(x, y)| Point3d::new(x, y, 0.0));
+            bundle.add(
+                RenderPrimitive::<_, _, Contour<_>, _>::new_polygon(
+                    Polygon::new(ClosedContour::new(points.to_vec()), vec![]),
+                    PolygonPaint {
+                        color: self.0,
+                        gradient: None,
+                        zoom: None,
+                    },
+                ),
+                1.0,
+            );
+            let packed = canvas.pack_bundle(&bundle);
+            canvas.draw_bundles(&[&*packed], RenderOptions::default());
+        }
+
+        fn prepare(&self, _view: &MapView) {}
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    fn switcher(clock: &ManualClock) -> BasemapSwitcher {
+        BasemapSwitcher::new(vec![
+            LayerGroup::new("red").with_layer(FillLayer(Color::RED)),
+            LayerGroup::new("blue").with_layer(FillLayer(Color::BLUE)),
+        ])
+        .with_fade_duration(Duration::from_secs(1))
+        .with_clock(clock.clone())
+    }
+
+    fn center_pixel(renderer: &SoftwareRenderer) -> [u8; 4] {
+        let image = renderer.get_image();
+        let offset = (5 * 10 + 5) * 4;
+        [0, 1, 2, 3].map(|i| image[offset + i])
+    }
+
+    #[test]
+    fn switching_cross_fades_basemaps() {
+        let clock = ManualClock::default();
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(10.0, 10.0));
+        let mut map = Map::new(
+            view,
+            vec![Box::new(switcher(&clock))],
+            None::<DummyMessenger>,
+        );
+        let mut renderer = SoftwareRenderer::new(Size::new(10, 10));
+        renderer.set_background(Color::GREEN);
+
+        renderer.render(&map);
+        assert_eq!(center_pixel(&renderer), [255, 0, 0, 255]);
+
+        let switcher = map.layers_mut()[0]
+            .as_any_mut()
+            .downcast_mut::<BasemapSwitcher>()
+            .unwrap();
+        assert!(switcher.switch_to(2).is_err());
+        assert!(switcher.switch_to_name("green").is_err());
+        switcher.switch_to_name("blue").unwrap();
+        assert_eq!(switcher.active(), 1);
+        assert!(switcher.is_fading());
+
+        clock.advance(Duration::from_millis(500));
+        renderer.render(&map);
+        let [r, g, b, a] = center_pixel(&renderer);
+        assert!(r > 0 && r < 255);
+        assert!(b > 0 && b < 255);
+        // The background does not show through in the middle of the fade.
+        assert_eq!((g, a), (0, 255));
+
+        clock.advance(Duration::from_millis(500));
+        renderer.render(&map);
+        assert_eq!(center_pixel(&renderer), [0, 0, 255, 255]);
+        assert!(!map.layers()[0]
+            .as_any()
+            .downcast_ref::<BasemapSwitcher>()
+            .unwrap()
+            .is_fading());
+    }
+
+    #[test]
+    fn thumbnail_renders_one_basemap() {
+        let clock = ManualClock::default();
+        let switcher = switcher(&clock);
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+
+        let thumbnail = switcher
+            .render_thumbnail(1, &view, Size::new(8, 4))
+            .unwrap();
+        assert_eq!(thumbnail.dimensions, (8, 4));
+        assert_eq!(&thumbnail.bytes[0..4], &[0, 0, 255, 255]);
+
+        assert!(switcher
+            .render_thumbnail(2, &view, Size::new(8, 4))
+            .is_err());
+        assert!(switcher
+            .render_thumbnail(0, &view, Size::new(0, 4))
+            .is_err());
+    }
+}
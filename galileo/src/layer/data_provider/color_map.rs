@@ -0,0 +1,260 @@
+use crate::color::ColorSpace;
+use crate::decoded_image::DecodedImage;
+use crate::elevation::ElevationTile;
+use crate::error::GalileoError;
+use crate::layer::data_provider::DataProvider;
+use crate::layer::feature_layer::symbol::ColorRamp;
+use crate::tile_scheme::TileIndex;
+use crate::Color;
+use bytes::Bytes;
+
+/// Transfer function that converts values of a single-band raster (elevation, temperature, precipitation etc.) into
+/// colors.
+///
+/// A color map is defined by a list of `(value, color)` stops sorted by value, and works in one of two modes:
+/// * continuous ([`ColorMap::continuous`]) - colors between the stops are interpolated, values outside of the stops
+///   range get the color of the closest stop;
+/// * discrete ([`ColorMap::classes`]) - every stop starts a class that lasts until the next stop, values below the
+///   first stop are transparent.
+///
+/// `NaN` values and the [no-data value](ColorMap::with_no_data) are always transparent.
+///
+/// ```
+/// use galileo::layer::data_provider::ColorMap;
+/// use galileo::Color;
+///
+/// let color_map = ColorMap::classes(vec![
+///     (0.0, Color::BLUE),
+///     (10.0, Color::GREEN),
+///     (20.0, Color::RED),
+/// ])
+/// .with_no_data(-9999.0);
+///
+/// assert_eq!(color_map.color_of(15.0), Color::GREEN);
+/// assert_eq!(color_map.color_of(-9999.0), Color::TRANSPARENT);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorMap {
+    stops: Vec<(f32, Color)>,
+    discrete: bool,
+    no_data: Option<f32>,
+    color_space: ColorSpace,
+}
+
+impl ColorMap {
+    /// Creates a continuous color map from the `(value, color)` stops. The stops are sorted by their values.
+    pub fn continuous(stops: Vec<(f32, Color)>) -> Self {
+        Self::with_stops(stops, false)
+    }
+
+    /// Creates a discrete color map where each of the `(value, color)` stops starts a class of values. The stops are
+    /// sorted by their values.
+    pub fn classes(stops: Vec<(f32, Color)>) -> Self {
+        Self::with_stops(stops, true)
+    }
+
+    /// Creates a continuous color map that stretches the `ramp` over the values from `min` to `max`.
+    pub fn from_ramp(ramp: &ColorRamp, min: f32, max: f32) -> Self {
+        let colors = ramp.stops();
+        let step = match colors.len() {
+            1 => 0.0,
+            len => (max - min) / (len - 1) as f32,
+        };
+        let stops = colors
+            .iter()
+            .enumerate()
+            .map(|(i, color)| (min + step * i as f32, *color))
+            .collect();
+
+        Self::continuous(stops).with_color_space(ramp.color_space())
+    }
+
+    /// Creates a discrete color map with the classes starting at the `breaks`, colored with the colors evenly taken
+    /// from the `ramp`. The last break is the upper boundary of the last class: values above it are drawn with the
+    /// color of the last class. Breaks can be calculated with a
+    /// [`Classification`](crate::layer::feature_layer::symbol::Classification).
+    pub fn from_breaks(breaks: &[f32], ramp: &ColorRamp) -> Self {
+        let class_count = breaks.len().saturating_sub(1).max(1);
+        let colors = ramp.colors(class_count);
+        let stops = breaks.iter().copied().zip(colors).collect();
+
+        Self::classes(stops)
+    }
+
+    fn with_stops(mut stops: Vec<(f32, Color)>, discrete: bool) -> Self {
+        stops.retain(|(value, _)| !value.is_nan());
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Self {
+            stops,
+            discrete,
+            no_data: None,
+            color_space: ColorSpace::default(),
+        }
+    }
+
+    /// Sets the value that marks cells without data. Such cells are transparent.
+    pub fn with_no_data(mut self, no_data: f32) -> Self {
+        self.no_data = Some(no_data);
+        self
+    }
+
+    /// Sets the color space the colors of a continuous color map are interpolated in.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Stops of the color map sorted by value.
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.stops
+    }
+
+    /// Returns true if the color map assigns colors by classes without interpolation.
+    pub fn is_discrete(&self) -> bool {
+        self.discrete
+    }
+
+    /// Value that marks cells without data.
+    pub fn no_data(&self) -> Option<f32> {
+        self.no_data
+    }
+
+    /// Color of the given value.
+    pub fn color_of(&self, value: f32) -> Color {
+        if value.is_nan() || self.no_data == Some(value) || self.stops.is_empty() {
+            return Color::TRANSPARENT;
+        }
+
+        let next_index = self.stops.partition_point(|(stop, _)| *stop <= value);
+        if self.discrete {
+            return match next_index {
+                0 => Color::TRANSPARENT,
+                index => self.stops[index - 1].1,
+            };
+        }
+
+        if next_index == 0 {
+            return self.stops[0].1;
+        }
+        if next_index == self.stops.len() {
+            return self.stops[next_index - 1].1;
+        }
+
+        let (from_value, from) = self.stops[next_index - 1];
+        let (to_value, to) = self.stops[next_index];
+        let t = (value - from_value) / (to_value - from_value);
+        from.interpolate(&to, t as f64, self.color_space)
+    }
+
+    /// Converts the grid of values into an image.
+    pub fn apply(&self, tile: &ElevationTile) -> DecodedImage {
+        let bytes = tile
+            .values()
+            .iter()
+            .flat_map(|value| self.color_of(*value).to_u8_array())
+            .collect();
+
+        DecodedImage {
+            bytes,
+            dimensions: (tile.width(), tile.height()),
+        }
+    }
+}
+
+/// Data provider for a [`RasterTileLayer`](crate::layer::RasterTileLayer) that colors the single-band tiles of the
+/// inner provider with a [`ColorMap`].
+///
+/// The inner provider can be any provider of value grids, e.g. a
+/// [`UrlDataProvider`](super::UrlDataProvider) with an [`ElevationDecoder`](crate::elevation::ElevationDecoder), or a
+/// [`ProceduralTileProvider`](super::ProceduralTileProvider) generating [`ElevationTile`]s.
+///
+/// ```no_run
+/// use galileo::elevation::{ElevationDecoder, ElevationEncoding};
+/// use galileo::layer::data_provider::{ColorMap, ColorMapProvider, UrlDataProvider};
+/// use galileo::layer::feature_layer::symbol::ColorRamp;
+/// use galileo::layer::RasterTileLayer;
+/// use galileo::tile_scheme::{TileIndex, TileSchema};
+///
+/// let provider = ColorMapProvider::new(
+///     UrlDataProvider::new(
+///         |index: &TileIndex| format!("https://example.com/dem/{}/{}/{}.png", index.z, index.x, index.y),
+///         ElevationDecoder::new(ElevationEncoding::TerrainRgb),
+///     ),
+///     ColorMap::from_ramp(&ColorRamp::viridis(), 0.0, 4000.0),
+/// );
+///
+/// let layer = RasterTileLayer::new(TileSchema::web(15), provider, None);
+/// ```
+pub struct ColorMapProvider<Provider> {
+    inner: Provider,
+    color_map: ColorMap,
+}
+
+impl<Provider> ColorMapProvider<Provider> {
+    /// Creates a new provider.
+    pub fn new(inner: Provider, color_map: ColorMap) -> Self {
+        Self { inner, color_map }
+    }
+
+    /// Color map used to color the tiles.
+    pub fn color_map(&self) -> &ColorMap {
+        &self.color_map
+    }
+
+    /// Provider of the value grids.
+    pub fn inner(&self) -> &Provider {
+        &self.inner
+    }
+}
+
+// `cache_key` is not delegated: colored tiles depend on the color map, so they cannot be reused by a provider with a
+// different one.
+impl<Provider> DataProvider<TileIndex, DecodedImage, ()> for ColorMapProvider<Provider>
+where
+    Provider: DataProvider<TileIndex, ElevationTile, ()>,
+{
+    async fn load_raw(&self, key: &TileIndex) -> Result<Bytes, GalileoError> {
+        self.inner.load_raw(key).await
+    }
+
+    fn decode(&self, bytes: Bytes, context: ()) -> Result<DecodedImage, GalileoError> {
+        Ok(self.color_map.apply(&self.inner.decode(bytes, context)?))
+    }
+
+    async fn load(&self, key: &TileIndex, context: ()) -> Result<DecodedImage, GalileoError> {
+        Ok(self.color_map.apply(&self.inner.load(key, context).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuous_color_map() {
+        let color_map = ColorMap::continuous(vec![(100.0, Color::WHITE), (0.0, Color::BLACK)]);
+
+        assert_eq!(color_map.color_of(-5.0), Color::BLACK);
+        assert_eq!(color_map.color_of(50.0), Color::rgb(128, 128, 128));
+        assert_eq!(color_map.color_of(500.0), Color::WHITE);
+        assert_eq!(color_map.color_of(f32::NAN), Color::TRANSPARENT);
+
+        let tile = ElevationTile::new(2, 1, vec![0.0, 100.0]).unwrap();
+        let image = color_map.with_no_data(0.0).apply(&tile);
+        assert_eq!(image.dimensions, (2, 1));
+        assert_eq!(image.bytes, [0, 0, 0, 0, 255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn discrete_color_map() {
+        let ramp = ColorRamp::new(vec![Color::BLUE, Color::RED]);
+        let color_map = ColorMap::from_breaks(&[0.0, 10.0, 20.0], &ramp);
+
+        assert!(color_map.is_discrete());
+        assert_eq!(color_map.color_of(-1.0), Color::TRANSPARENT);
+        assert_eq!(color_map.color_of(5.0), Color::BLUE);
+        assert_eq!(color_map.color_of(10.0), Color::RED);
+        assert_eq!(color_map.color_of(25.0), Color::RED);
+    }
+}
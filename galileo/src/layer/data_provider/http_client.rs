@@ -0,0 +1,51 @@
+use crate::error::GalileoError;
+use crate::platform::{PlatformService, PlatformServiceImpl};
+use async_trait::async_trait;
+use bytes::Bytes;
+use maybe_sync::{MaybeSend, MaybeSync};
+
+/// HTTP client used by the data providers to load data from the Internet.
+///
+/// By default, the providers use [`DefaultHttpClient`]. A custom client can be used to send requests through a proxy,
+/// use custom TLS configuration, sign requests or add authentication headers.
+///
+/// Implementations should return [`GalileoError::NotFound`] if the server responds that the resource does not exist
+/// (e.g. with the status `404`), and [`GalileoError::IO`] for other failed requests, so that the layers can decide
+/// which requests to retry.
+///
+/// On native platforms, [`reqwest::Client`] implements this trait, so a client configured with the `reqwest` builder
+/// can be used directly.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait HttpClient: MaybeSend + MaybeSync {
+    /// Makes a `GET` request to the `url` and returns the body of the response.
+    async fn get(&self, url: &str) -> Result<Bytes, GalileoError>;
+}
+
+/// HTTP client of the platform: `reqwest` on native platforms, and `fetch` API on the web.
+pub struct DefaultHttpClient {
+    platform_service: PlatformServiceImpl,
+}
+
+impl DefaultHttpClient {
+    /// Creates a new client.
+    pub fn new() -> Self {
+        Self {
+            platform_service: PlatformServiceImpl::new(),
+        }
+    }
+}
+
+impl Default for DefaultHttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpClient for DefaultHttpClient {
+    async fn get(&self, url: &str) -> Result<Bytes, GalileoError> {
+        self.platform_service.load_bytes_from_url(url).await
+    }
+}
@@ -0,0 +1,159 @@
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::DataProcessor;
+use bytes::Bytes;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::sync::Arc;
+
+/// Method that decodes an encoded image into RGBA pixels.
+pub trait ImageDecoder:
+    (Fn(&[u8]) -> Result<DecodedImage, GalileoError>) + MaybeSend + MaybeSync
+{
+}
+impl<T: Fn(&[u8]) -> Result<DecodedImage, GalileoError>> ImageDecoder for T where
+    T: MaybeSend + MaybeSync
+{
+}
+
+/// Method that checks if the encoded image can be decoded by the decoder it was registered with.
+pub trait ImageFormatMatcher: (Fn(&[u8]) -> bool) + MaybeSend + MaybeSync {}
+impl<T: Fn(&[u8]) -> bool> ImageFormatMatcher for T where T: MaybeSend + MaybeSync {}
+
+#[derive(Clone)]
+struct RegisteredDecoder {
+    matcher: Arc<dyn ImageFormatMatcher>,
+    decoder: Arc<dyn ImageDecoder>,
+}
+
+/// Set of decoders used to decode raster tiles.
+///
+/// By default, PNG and JPEG images are decoded with the `image` crate on native platforms, and by the browser on the
+/// web. Other formats (WebP, AVIF, JPEG2000, or PNG with 16-bit channels that should be decoded as elevation data) can
+/// be supported by registering a decoder for them.
+///
+/// When an image is decoded, the registered decoders are checked starting from the last registered one, and the first
+/// one whose matcher accepts the image is used. If no registered decoder matches the image, the default decoder is
+/// used.
+///
+/// ```no_run
+/// use galileo::layer::data_provider::{ImageDecoderRegistry, UrlImageProvider};
+/// use galileo::tile_scheme::TileIndex;
+/// use galileo::DecodedImage;
+///
+/// fn decode_webp(bytes: &[u8]) -> Result<DecodedImage, galileo::error::GalileoError> {
+///     // Call a WebP decoding library here.
+///     # unimplemented!()
+/// }
+///
+/// let decoders = ImageDecoderRegistry::new().with_signature(b"RIFF", decode_webp);
+/// let provider = UrlImageProvider::new(|index: &TileIndex| {
+///     format!("https://example.com/{}/{}/{}.webp", index.z, index.x, index.y)
+/// })
+/// .with_decoders(decoders);
+/// ```
+#[derive(Clone, Default)]
+pub struct ImageDecoderRegistry {
+    decoders: Vec<RegisteredDecoder>,
+}
+
+impl ImageDecoderRegistry {
+    /// Creates a new registry with only the default decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a `decoder` for the images accepted by the `matcher`.
+    pub fn register(
+        &mut self,
+        matcher: impl ImageFormatMatcher + 'static,
+        decoder: impl ImageDecoder + 'static,
+    ) {
+        self.decoders.push(RegisteredDecoder {
+            matcher: Arc::new(matcher),
+            decoder: Arc::new(decoder),
+        });
+    }
+
+    /// Registers a `decoder` for the images accepted by the `matcher`.
+    pub fn with_decoder(
+        mut self,
+        matcher: impl ImageFormatMatcher + 'static,
+        decoder: impl ImageDecoder + 'static,
+    ) -> Self {
+        self.register(matcher, decoder);
+        self
+    }
+
+    /// Registers a `decoder` for the images that start with the given `signature` (magic bytes of the format).
+    pub fn with_signature(
+        self,
+        signature: &'static [u8],
+        decoder: impl ImageDecoder + 'static,
+    ) -> Self {
+        self.with_decoder(move |bytes: &[u8]| bytes.starts_with(signature), decoder)
+    }
+
+    /// Returns true if no decoders were registered, and the images are decoded with the default decoder.
+    pub fn is_empty(&self) -> bool {
+        self.decoders.is_empty()
+    }
+
+    /// Decodes the image with the matching decoder.
+    pub fn decode(&self, bytes: &[u8]) -> Result<DecodedImage, GalileoError> {
+        match self
+            .decoders
+            .iter()
+            .rev()
+            .find(|registered| (registered.matcher)(bytes))
+        {
+            Some(registered) => (registered.decoder)(bytes),
+            None => Self::decode_default(bytes),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn decode_default(bytes: &[u8]) -> Result<DecodedImage, GalileoError> {
+        DecodedImage::new(bytes)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn decode_default(_bytes: &[u8]) -> Result<DecodedImage, GalileoError> {
+        Err(GalileoError::Generic(
+            "no decoder is registered for the image format".into(),
+        ))
+    }
+}
+
+impl DataProcessor for ImageDecoderRegistry {
+    type Input = Bytes;
+    type Output = DecodedImage;
+    type Context = ();
+
+    fn process(&self, input: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+        self.decode(&input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(value: u8) -> impl ImageDecoder {
+        move |_: &[u8]| DecodedImage::from_rgba(vec![value; 4], 1, 1)
+    }
+
+    #[test]
+    fn last_matching_decoder_is_used() {
+        let registry = ImageDecoderRegistry::new()
+            .with_signature(b"AB", solid(1))
+            .with_signature(b"ABC", solid(2))
+            .with_signature(b"X", solid(3));
+
+        assert_eq!(registry.decode(b"ABCD").unwrap().bytes[0], 2);
+        assert_eq!(registry.decode(b"ABD").unwrap().bytes[0], 1);
+        assert_eq!(registry.decode(b"XYZ").unwrap().bytes[0], 3);
+
+        // Falls back to the default decoder, which cannot decode this.
+        assert!(registry.decode(b"not an image").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use crate::error::GalileoError;
+use crate::layer::data_provider::{DataProcessor, DataProvider};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tile_scheme::TileIndex;
+use bytes::Bytes;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::marker::PhantomData;
+
+/// Method that returns the data item embedded into the application binary (e.g. with [`include_bytes`]) by its key.
+pub trait EmbeddedSource<Key: ?Sized>:
+    (Fn(&Key) -> Option<&'static [u8]>) + MaybeSend + MaybeSync
+{
+}
+impl<Key: ?Sized, T: Fn(&Key) -> Option<&'static [u8]>> EmbeddedSource<Key> for T where
+    T: MaybeSend + MaybeSync
+{
+}
+
+/// Loads data embedded into the application binary, so that a layer can be displayed without network access.
+///
+/// ```no_run
+/// use galileo::layer::data_provider::EmbeddedDataProvider;
+/// use galileo::layer::vector_tile_layer::tile_provider::VtProcessor;
+/// use galileo::tile_scheme::TileIndex;
+///
+/// // In a real application this would be `include_bytes!("tiles/0/0/0.pbf")`.
+/// static WORLD_TILE: &[u8] = &[];
+///
+/// let provider = EmbeddedDataProvider::new(
+///     |index: &TileIndex| match (index.z, index.x, index.y) {
+///         (0, 0, 0) => Some(WORLD_TILE),
+///         _ => None,
+///     },
+///     VtProcessor {},
+/// );
+/// ```
+pub struct EmbeddedDataProvider<Key, Decoder>
+where
+    Key: ?Sized,
+    Decoder: DataProcessor<Input = Bytes>,
+{
+    source: Box<dyn EmbeddedSource<Key>>,
+    decoder: Decoder,
+    _phantom_key: PhantomData<Key>,
+}
+
+impl<Key, Decoder> EmbeddedDataProvider<Key, Decoder>
+where
+    Key: ?Sized,
+    Decoder: DataProcessor<Input = Bytes>,
+{
+    /// Creates a new provider. If the `source` returns `None` for a key, loading of the item fails with
+    /// [`GalileoError::NotFound`].
+    pub fn new(source: impl EmbeddedSource<Key> + 'static, decoder: Decoder) -> Self {
+        Self {
+            source: Box::new(source),
+            decoder,
+            _phantom_key: Default::default(),
+        }
+    }
+}
+
+impl<Key, Decoder> DataProvider<Key, Decoder::Output, Decoder::Context>
+    for EmbeddedDataProvider<Key, Decoder>
+where
+    Key: ?Sized + MaybeSend + MaybeSync,
+    Decoder: DataProcessor<Input = Bytes> + MaybeSend + MaybeSync,
+    Decoder::Context: MaybeSend + MaybeSync,
+{
+    async fn load_raw(&self, key: &Key) -> Result<Bytes, GalileoError> {
+        (self.source)(key)
+            .map(Bytes::from_static)
+            .ok_or(GalileoError::NotFound)
+    }
+
+    fn decode(
+        &self,
+        raw: Bytes,
+        context: Decoder::Context,
+    ) -> Result<Decoder::Output, GalileoError> {
+        self.decoder.process(raw, context)
+    }
+}
+
+/// Method that constructs the path of the file with a data item using the data key.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait PathSource<Key: ?Sized>:
+    (Fn(&Key) -> std::path::PathBuf) + MaybeSend + MaybeSync
+{
+}
+#[cfg(not(target_arch = "wasm32"))]
+impl<Key: ?Sized, T: Fn(&Key) -> std::path::PathBuf> PathSource<Key> for T where
+    T: MaybeSend + MaybeSync
+{
+}
+
+/// Loads data from files in the local file system, e.g. tiles exported into a directory for fully offline use.
+///
+/// On the web, files bundled with the application can be loaded with [`UrlDataProvider`](super::UrlDataProvider)
+/// using URLs relative to the page.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileDataProvider<Key, Decoder>
+where
+    Key: ?Sized,
+    Decoder: DataProcessor<Input = Bytes>,
+{
+    path_source: Box<dyn PathSource<Key>>,
+    decoder: Decoder,
+    _phantom_key: PhantomData<Key>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<Key, Decoder> FileDataProvider<Key, Decoder>
+where
+    Key: ?Sized,
+    Decoder: DataProcessor<Input = Bytes>,
+{
+    /// Creates a new provider that reads the file at the path returned by `path_source` for each item.
+    pub fn new(path_source: impl PathSource<Key> + 'static, decoder: Decoder) -> Self {
+        Self {
+            path_source: Box::new(path_source),
+            decoder,
+            _phantom_key: Default::default(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<Decoder> FileDataProvider<TileIndex, Decoder>
+where
+    Decoder: DataProcessor<Input = Bytes>,
+{
+    /// Creates a provider that reads tiles from a directory with the `{z}/{x}/{y}.{extension}` structure, as created
+    /// by most tile export tools.
+    pub fn from_directory(
+        directory: impl Into<std::path::PathBuf>,
+        extension: &str,
+        decoder: Decoder,
+    ) -> Self {
+        let directory = directory.into();
+        let extension = extension.to_string();
+        Self::new(
+            move |index: &TileIndex| tile_path(&directory, index, &extension),
+            decoder,
+        )
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn tile_path(
+    directory: &std::path::Path,
+    index: &TileIndex,
+    extension: &str,
+) -> std::path::PathBuf {
+    directory
+        .join(index.z.to_string())
+        .join(index.x.to_string())
+        .join(format!("{}.{extension}", index.y))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<Key, Decoder> DataProvider<Key, Decoder::Output, Decoder::Context>
+    for FileDataProvider<Key, Decoder>
+where
+    Key: ?Sized + MaybeSend + MaybeSync,
+    Decoder: DataProcessor<Input = Bytes> + MaybeSend + MaybeSync,
+    Decoder::Context: MaybeSend + MaybeSync,
+{
+    async fn load_raw(&self, key: &Key) -> Result<Bytes, GalileoError> {
+        let path = (self.path_source)(key);
+        match std::fs::read(&path) {
+            Ok(bytes) => Ok(bytes.into()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(GalileoError::NotFound),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn decode(
+        &self,
+        raw: Bytes,
+        context: Decoder::Context,
+    ) -> Result<Decoder::Output, GalileoError> {
+        self.decoder.process(raw, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RawProcessor;
+
+    impl DataProcessor for RawProcessor {
+        type Input = Bytes;
+        type Output = Bytes;
+        type Context = ();
+
+        fn process(&self, input: Bytes, _context: ()) -> Result<Bytes, GalileoError> {
+            Ok(input)
+        }
+    }
+
+    #[test]
+    fn embedded_provider_loads_static_data() {
+        let provider = EmbeddedDataProvider::new(
+            |key: &str| (key == "tile").then_some(b"data".as_slice()),
+            RawProcessor,
+        );
+
+        let loaded = tokio_test::block_on(provider.load("tile", ())).unwrap();
+        assert_eq!(&loaded[..], b"data");
+        assert!(matches!(
+            tokio_test::block_on(provider.load_raw("other")),
+            Err(GalileoError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn file_provider_reads_tile_directory() {
+        let directory = std::env::temp_dir().join("galileo_file_data_provider_test");
+        let index = TileIndex {
+            z: 1,
+            x: 3,
+            y: 2,
+            display_x: 3,
+        };
+        let path = tile_path(&directory, &index, "pbf");
+        assert!(path.ends_with("1/3/2.pbf"));
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, b"tile").unwrap();
+
+        let provider = FileDataProvider::from_directory(&directory, "pbf", RawProcessor);
+        let loaded = tokio_test::block_on(provider.load_raw(&index)).unwrap();
+        assert_eq!(&loaded[..], b"tile");
+        assert!(matches!(
+            tokio_test::block_on(provider.load_raw(&TileIndex {
+                z: 5,
+                x: 0,
+                y: 0,
+                display_x: 0,
+            })),
+            Err(GalileoError::NotFound)
+        ));
+
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
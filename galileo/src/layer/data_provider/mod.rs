@@ -1,8 +1,24 @@
 //! Data sources for layers.
 
+mod color_map;
+mod http_client;
+mod image_decoder;
+mod local_data_provider;
+mod procedural;
+mod rate_limiter;
+mod tile_url;
 mod url_data_provider;
 mod url_image_provider;
 
+pub use color_map::{ColorMap, ColorMapProvider};
+pub use http_client::{DefaultHttpClient, HttpClient};
+pub use image_decoder::{ImageDecoder, ImageDecoderRegistry, ImageFormatMatcher};
+pub use local_data_provider::{EmbeddedDataProvider, EmbeddedSource};
+#[cfg(not(target_arch = "wasm32"))]
+pub use local_data_provider::{FileDataProvider, PathSource};
+pub use procedural::{ProceduralTileProvider, TileGenerator};
+pub use rate_limiter::{RateLimitPermit, RateLimitedProvider, RateLimiter};
+pub use tile_url::{TileUrlTemplate, WmsTileSource};
 pub use url_data_provider::UrlDataProvider;
 pub use url_image_provider::UrlImageProvider;
 
@@ -48,6 +64,14 @@ where
             self.decode(raw, context)
         }
     }
+
+    /// Identifier of the data item that is the same for all providers that load the same data for it, e.g. the URL
+    /// of the item. Layers use it to reuse already loaded items when their data provider is replaced.
+    ///
+    /// Returns `None` by default, meaning the items are never reused.
+    fn cache_key(&self, _key: &Key) -> Option<String> {
+        None
+    }
 }
 
 /// Data processors are used to decode raw loaded data into something useful by a layer.
@@ -79,6 +103,21 @@ pub trait PersistentCacheController<Key: ?Sized, Data> {
 pub trait UrlSource<Key: ?Sized>: (Fn(&Key) -> String) + MaybeSend + MaybeSync {}
 impl<Key: ?Sized, T: Fn(&Key) -> String> UrlSource<Key> for T where T: MaybeSend + MaybeSync {}
 
+/// Percent-encodes the value to be used as a URL query parameter.
+pub(crate) fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() * 3);
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
 mod dummy {
     use crate::error::GalileoError;
     use crate::layer::data_provider::PersistentCacheController;
@@ -0,0 +1,211 @@
+use crate::decoded_image::DecodedImage;
+use crate::elevation::ElevationTile;
+use crate::error::GalileoError;
+use crate::layer::data_provider::DataProvider;
+use crate::layer::vector_tile_layer::tile_provider::{
+    FeaturePrimitives, VectorTileDecodeContext, VtProcessor,
+};
+use crate::render::render_bundle::RenderBundle;
+use crate::tile_scheme::{TileIndex, TileSchema};
+use bytes::Bytes;
+use galileo_mvt::MvtTile;
+use galileo_types::cartesian::Rect;
+use maybe_sync::{MaybeSend, MaybeSync};
+
+/// Generates the content of tiles programmatically instead of loading it, e.g. to display fractals, outputs of a
+/// simulation or heat grids calculated on the fly.
+///
+/// Generators are used by [`ProceduralTileProvider`], which plugs them into the tile layers, so the generated tiles
+/// are cached and rendered the same way as the loaded ones. The type of the generated tiles defines the layer the
+/// provider can be used with:
+/// * [`DecodedImage`] - raster tiles for a [`RasterTileLayer`](crate::layer::RasterTileLayer). The image should have
+///   the size of a tile of the tile schema.
+/// * [`ElevationTile`] - grids of values of a single-band raster, e.g. for an
+///   [`ElevationCache`](crate::elevation::ElevationCache) or a [`ColorMapProvider`](super::ColorMapProvider).
+/// * [`MvtTile`] - vector tiles for a [`VectorTileLayer`](crate::layer::VectorTileLayer), drawn with the style of the
+///   layer. Coordinates of the features are relative to the tile: `(0.0, 0.0)` is the top left corner of the tile,
+///   and `(1.0, 1.0)` is the bottom right corner.
+///
+/// Any function or closure `Fn(TileIndex, Rect) -> Result<Tile, GalileoError>` is a generator.
+pub trait TileGenerator: MaybeSend + MaybeSync {
+    /// Type of the generated tiles.
+    type Tile;
+
+    /// Generates the tile with the given `index`. `bbox` is the area the tile covers in the projection of the tile
+    /// schema.
+    fn generate(&self, index: TileIndex, bbox: Rect) -> Result<Self::Tile, GalileoError>;
+}
+
+impl<Tile, F> TileGenerator for F
+where
+    F: Fn(TileIndex, Rect) -> Result<Tile, GalileoError> + MaybeSend + MaybeSync,
+{
+    type Tile = Tile;
+
+    fn generate(&self, index: TileIndex, bbox: Rect) -> Result<Tile, GalileoError> {
+        self(index, bbox)
+    }
+}
+
+/// Data provider that creates tiles with a [`TileGenerator`].
+///
+/// ```no_run
+/// use galileo::layer::data_provider::ProceduralTileProvider;
+/// use galileo::layer::RasterTileLayer;
+/// use galileo::tile_scheme::TileIndex;
+/// use galileo::{DecodedImage, TileSchema};
+/// use galileo_types::cartesian::Rect;
+///
+/// let tile_schema = TileSchema::web(18);
+/// let provider = ProceduralTileProvider::new(
+///     |index: TileIndex, _bbox: Rect| {
+///         let shade = (index.z * 16 % 256) as u8;
+///         DecodedImage::from_rgba([shade, shade, shade, 255].repeat(256 * 256), 256, 256)
+///     },
+///     tile_schema.clone(),
+/// );
+///
+/// let layer = RasterTileLayer::new(tile_schema, provider, None);
+/// ```
+pub struct ProceduralTileProvider<G> {
+    generator: G,
+    tile_schema: TileSchema,
+}
+
+impl<G: TileGenerator> ProceduralTileProvider<G> {
+    /// Creates a new provider. The `tile_schema` is used to calculate the bounding boxes of the tiles, and must be the
+    /// same as the tile schema of the layer.
+    pub fn new(generator: G, tile_schema: TileSchema) -> Self {
+        Self {
+            generator,
+            tile_schema,
+        }
+    }
+
+    /// Generator of the tiles.
+    pub fn generator(&self) -> &G {
+        &self.generator
+    }
+
+    fn generate(&self, index: TileIndex) -> Result<G::Tile, GalileoError> {
+        let bbox = self
+            .tile_schema
+            .tile_bbox(index)
+            .ok_or_else(|| GalileoError::Generic("cannot get tile bbox".into()))?;
+        self.generator.generate(index, bbox)
+    }
+}
+
+impl<G> DataProvider<TileIndex, DecodedImage, ()> for ProceduralTileProvider<G>
+where
+    G: TileGenerator<Tile = DecodedImage>,
+{
+    async fn load_raw(&self, _key: &TileIndex) -> Result<Bytes, GalileoError> {
+        Err(GalileoError::Generic(
+            "procedural raster tiles have no raw data".into(),
+        ))
+    }
+
+    fn decode(&self, _bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+        Err(GalileoError::Generic(
+            "procedural raster tiles have no raw data".into(),
+        ))
+    }
+
+    async fn load(&self, key: &TileIndex, _context: ()) -> Result<DecodedImage, GalileoError> {
+        self.generate(*key)
+    }
+}
+
+impl<G> DataProvider<TileIndex, ElevationTile, ()> for ProceduralTileProvider<G>
+where
+    G: TileGenerator<Tile = ElevationTile>,
+{
+    async fn load_raw(&self, _key: &TileIndex) -> Result<Bytes, GalileoError> {
+        Err(GalileoError::Generic(
+            "procedural value tiles have no raw data".into(),
+        ))
+    }
+
+    fn decode(&self, _bytes: Bytes, _context: ()) -> Result<ElevationTile, GalileoError> {
+        Err(GalileoError::Generic(
+            "procedural value tiles have no raw data".into(),
+        ))
+    }
+
+    async fn load(&self, key: &TileIndex, _context: ()) -> Result<ElevationTile, GalileoError> {
+        self.generate(*key)
+    }
+}
+
+impl<G> DataProvider<TileIndex, (RenderBundle, MvtTile, FeaturePrimitives), VectorTileDecodeContext>
+    for ProceduralTileProvider<G>
+where
+    G: TileGenerator<Tile = MvtTile>,
+{
+    async fn load_raw(&self, _key: &TileIndex) -> Result<Bytes, GalileoError> {
+        // Tiles are generated when decoded, so that vector tile providers do it in a background thread.
+        Ok(Bytes::new())
+    }
+
+    fn decode(
+        &self,
+        _bytes: Bytes,
+        context: VectorTileDecodeContext,
+    ) -> Result<(RenderBundle, MvtTile, FeaturePrimitives), GalileoError> {
+        let VectorTileDecodeContext {
+            index,
+            style,
+            tile_schema,
+            mut bundle,
+        } = context;
+
+        let mvt_tile = self.generate(index)?;
+        let feature_primitives =
+            VtProcessor::prepare(&mvt_tile, &mut bundle, index, &style, &tile_schema)?;
+
+        Ok((bundle, mvt_tile, feature_primitives))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raster_tiles_are_generated_for_tile_bbox() {
+        let tile_schema = TileSchema::web(4);
+        let provider = ProceduralTileProvider::new(
+            |index: TileIndex, bbox: Rect| {
+                assert_eq!(index.z, 1);
+                assert!(bbox.x_min().abs() < 0.01);
+                assert!(bbox.y_max().abs() < 0.01);
+                DecodedImage::from_rgba(vec![255; 16], 2, 2)
+            },
+            tile_schema,
+        );
+
+        let index = TileIndex {
+            z: 1,
+            x: 1,
+            y: 1,
+            display_x: 1,
+        };
+        let image = tokio_test::block_on(provider.load(&index, ())).unwrap();
+        assert_eq!(image.dimensions, (2, 2));
+
+        let out_of_schema = TileIndex {
+            z: 10,
+            x: 0,
+            y: 0,
+            display_x: 0,
+        };
+        assert!(tokio_test::block_on(provider.load(&out_of_schema, ())).is_err());
+    }
+
+    #[test]
+    fn image_size_is_validated() {
+        assert!(DecodedImage::from_rgba(vec![0; 15], 2, 2).is_err());
+        assert!(DecodedImage::from_rgba(vec![0; 16], 2, 2).is_ok());
+    }
+}
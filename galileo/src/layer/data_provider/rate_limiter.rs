@@ -0,0 +1,197 @@
+use crate::error::GalileoError;
+use crate::layer::data_provider::DataProvider;
+use bytes::Bytes;
+use futures_intrusive::sync::{SharedSemaphore, SharedSemaphoreReleaser};
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::sync::{Arc, Mutex};
+use web_time::{Duration, Instant};
+
+/// Longest interval between the requests, so that the reserved slots do not overflow [`Instant`].
+const MAX_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Limits the rate and the number of simultaneous requests to a data source, e.g. to follow the tile usage policy of
+/// OpenStreetMap or the limits of a commercial plan.
+///
+/// Clones of the limiter share the limits, so the same limiter can be used by several providers that load data from
+/// the same server. Limits must be set before the limiter is cloned.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    concurrency: Option<SharedSemaphore>,
+    interval: Option<Duration>,
+    next_slot: Arc<Mutex<Option<Instant>>>,
+}
+
+/// Permission to make a request given by a [`RateLimiter`]. The request is counted as running until the permit is
+/// dropped.
+pub struct RateLimitPermit {
+    _releaser: Option<SharedSemaphoreReleaser>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of the requests started during one second.
+    ///
+    /// Rates below one request per day are treated as one request per day.
+    pub fn with_requests_per_second(mut self, requests_per_second: f64) -> Self {
+        self.interval = (requests_per_second > 0.0).then(|| {
+            Duration::try_from_secs_f64(1.0 / requests_per_second)
+                .map_or(MAX_INTERVAL, |interval| interval.min(MAX_INTERVAL))
+        });
+        self
+    }
+
+    /// Limits the number of the requests running at the same time.
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.concurrency = Some(SharedSemaphore::new(true, max_concurrent.max(1)));
+        self
+    }
+
+    /// Waits until a request can be made without exceeding the limits.
+    pub async fn acquire(&self) -> RateLimitPermit {
+        let releaser = match &self.concurrency {
+            Some(semaphore) => Some(semaphore.acquire(1).await),
+            None => None,
+        };
+
+        if let Some(slot) = self.reserve_slot(Instant::now()) {
+            let now = Instant::now();
+            if slot > now {
+                crate::async_runtime::sleep(slot - now).await;
+            }
+        }
+
+        RateLimitPermit {
+            _releaser: releaser,
+        }
+    }
+
+    /// Reserves the earliest time the next request can start at.
+    fn reserve_slot(&self, now: Instant) -> Option<Instant> {
+        let interval = self.interval?;
+        let mut next_slot = self.next_slot.lock().expect("mutex is poisoned");
+        let slot = match *next_slot {
+            Some(next) if next > now => next,
+            _ => now,
+        };
+        *next_slot = Some(slot + interval);
+
+        Some(slot)
+    }
+}
+
+/// Data provider that makes requests of the inner provider through a [`RateLimiter`].
+///
+/// ```no_run
+/// use galileo::layer::data_provider::{RateLimitedProvider, RateLimiter, UrlImageProvider};
+/// use galileo::tile_scheme::TileIndex;
+///
+/// let provider = RateLimitedProvider::new(
+///     UrlImageProvider::new(|index: &TileIndex| {
+///         format!("https://tile.openstreetmap.org/{}/{}/{}.png", index.z, index.x, index.y)
+///     }),
+///     RateLimiter::new().with_max_concurrent(2),
+/// );
+/// ```
+pub struct RateLimitedProvider<Provider> {
+    inner: Provider,
+    limiter: RateLimiter,
+}
+
+impl<Provider> RateLimitedProvider<Provider> {
+    /// Creates a new provider.
+    pub fn new(inner: Provider, limiter: RateLimiter) -> Self {
+        Self { inner, limiter }
+    }
+
+    /// Limiter used by the provider.
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// Provider the requests are made by.
+    pub fn inner(&self) -> &Provider {
+        &self.inner
+    }
+}
+
+impl<Key, Data, Context, Provider> DataProvider<Key, Data, Context>
+    for RateLimitedProvider<Provider>
+where
+    Key: MaybeSend + MaybeSync + ?Sized,
+    Context: MaybeSend + MaybeSync,
+    Provider: DataProvider<Key, Data, Context>,
+{
+    async fn load_raw(&self, key: &Key) -> Result<Bytes, GalileoError> {
+        let _permit = self.limiter.acquire().await;
+        self.inner.load_raw(key).await
+    }
+
+    fn decode(&self, bytes: Bytes, context: Context) -> Result<Data, GalileoError> {
+        self.inner.decode(bytes, context)
+    }
+
+    async fn load(&self, key: &Key, context: Context) -> Result<Data, GalileoError> {
+        // Some providers load data without calling `load_raw`, so the permit is held for the whole loading.
+        let _permit = self.limiter.acquire().await;
+        self.inner.load(key, context).await
+    }
+
+    fn cache_key(&self, key: &Key) -> Option<String> {
+        self.inner.cache_key(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn concurrent_requests_are_limited() {
+        let limiter = RateLimiter::new().with_max_concurrent(1);
+        let permit = tokio_test::block_on(limiter.acquire());
+
+        let mut second = tokio_test::task::spawn(limiter.acquire());
+        assert_pending!(second.poll());
+
+        drop(permit);
+        assert!(second.is_woken());
+        let _ = assert_ready!(second.poll());
+    }
+
+    #[test]
+    fn requests_are_spread_by_rate() {
+        let limiter = RateLimiter::new().with_requests_per_second(4.0);
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve_slot(now), Some(now));
+        assert_eq!(
+            limiter.reserve_slot(now),
+            Some(now + Duration::from_millis(250))
+        );
+
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.reserve_slot(later), Some(later));
+
+        assert_eq!(RateLimiter::new().reserve_slot(now), None);
+    }
+
+    #[test]
+    fn tiny_rates_are_clamped() {
+        let now = Instant::now();
+        for rate in [1e-300, f64::MIN_POSITIVE, 1e-6] {
+            let limiter = RateLimiter::new().with_requests_per_second(rate);
+            assert_eq!(limiter.reserve_slot(now), Some(now));
+            assert_eq!(limiter.reserve_slot(now), Some(now + MAX_INTERVAL));
+        }
+
+        for rate in [0.0, -1.0, f64::NAN] {
+            let limiter = RateLimiter::new().with_requests_per_second(rate);
+            assert_eq!(limiter.reserve_slot(now), None);
+        }
+    }
+}
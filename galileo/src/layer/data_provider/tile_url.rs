@@ -0,0 +1,203 @@
+use crate::layer::data_provider::UrlSource;
+use crate::tile_scheme::{TileIndex, TileSchema};
+
+/// Template of tile URLs with `{z}`, `{x}`, `{y}` and `{time}` tokens, e.g.
+/// `https://example.com/radar/{time}/{z}/{x}/{y}.png`.
+///
+/// The time is percent-encoded when substituted into the URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileUrlTemplate {
+    template: String,
+}
+
+impl TileUrlTemplate {
+    /// Creates a new template.
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+        }
+    }
+
+    /// Returns true if the template contains the `{time}` token.
+    pub fn has_time(&self) -> bool {
+        self.template.contains("{time}")
+    }
+
+    /// URL of the tile at the given time. If `time` is `None`, the `{time}` token is removed.
+    pub fn url(&self, index: &TileIndex, time: Option<&str>) -> String {
+        self.template
+            .replace("{z}", &index.z.to_string())
+            .replace("{x}", &index.x.to_string())
+            .replace("{y}", &index.y.to_string())
+            .replace("{time}", &time.map(encode_component).unwrap_or_default())
+    }
+
+    /// URL source for a [`UrlImageProvider`](super::UrlImageProvider) loading the tiles at the given time.
+    pub fn source(&self, time: Option<&str>) -> impl UrlSource<TileIndex> {
+        let template = self.clone();
+        let time = time.map(str::to_string);
+        move |index: &TileIndex| template.url(index, time.as_deref())
+    }
+}
+
+/// Source of tiles requested from a WMS server with `GetMap` requests (version 1.3.0).
+///
+/// The bounding box of each request is the area of the tile in the tile schema, so the schema must be in the CRS of the
+/// request. Time steps are requested with the `TIME` parameter.
+#[derive(Debug, Clone)]
+pub struct WmsTileSource {
+    base_url: String,
+    layers: String,
+    styles: String,
+    format: String,
+    crs: String,
+    transparent: bool,
+    tile_schema: TileSchema,
+}
+
+impl WmsTileSource {
+    /// Creates a new source requesting the given comma-separated `layers` from the server at `base_url` in
+    /// `EPSG:3857` as transparent PNG images.
+    pub fn new(
+        base_url: impl Into<String>,
+        layers: impl Into<String>,
+        tile_schema: TileSchema,
+    ) -> Self {
+        Self {
+            base_url: base_url.into(),
+            layers: layers.into(),
+            styles: String::new(),
+            format: "image/png".to_string(),
+            crs: "EPSG:3857".to_string(),
+            transparent: true,
+            tile_schema,
+        }
+    }
+
+    /// Sets the `STYLES` parameter of the requests.
+    pub fn with_styles(mut self, styles: impl Into<String>) -> Self {
+        self.styles = styles.into();
+        self
+    }
+
+    /// Sets the image format of the requests, e.g. `image/jpeg`.
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Sets the `CRS` parameter of the requests, e.g. `EPSG:4326`. It must be the CRS of the tile schema.
+    pub fn with_crs(mut self, crs: impl Into<String>) -> Self {
+        self.crs = crs.into();
+        self
+    }
+
+    /// Sets whether the background of the images should be transparent.
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// `GetMap` URL of the tile at the given time. Returns `None` if the tile schema does not contain the tile.
+    pub fn url(&self, index: &TileIndex, time: Option<&str>) -> Option<String> {
+        let bbox = self.tile_schema.tile_bbox(*index)?;
+        // WMS 1.3.0 uses latitude-longitude axis order for EPSG:4326.
+        let bbox = if self.crs == "EPSG:4326" {
+            [bbox.y_min(), bbox.x_min(), bbox.y_max(), bbox.x_max()]
+        } else {
+            [bbox.x_min(), bbox.y_min(), bbox.x_max(), bbox.y_max()]
+        };
+
+        let separator = if self.base_url.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        let mut url = format!(
+            "{}{separator}SERVICE=WMS&VERSION=1.3.0&REQUEST=GetMap&LAYERS={}&STYLES={}&FORMAT={}&TRANSPARENT={}&CRS={}&BBOX={},{},{},{}&WIDTH={}&HEIGHT={}",
+            self.base_url,
+            encode_component(&self.layers),
+            encode_component(&self.styles),
+            encode_component(&self.format),
+            if self.transparent { "TRUE" } else { "FALSE" },
+            encode_component(&self.crs),
+            bbox[0],
+            bbox[1],
+            bbox[2],
+            bbox[3],
+            self.tile_schema.tile_width(),
+            self.tile_schema.tile_height(),
+        );
+
+        if let Some(time) = time {
+            url.push_str("&TIME=");
+            url.push_str(&encode_component(time));
+        }
+
+        Some(url)
+    }
+
+    /// URL source for a [`UrlImageProvider`](super::UrlImageProvider) loading the tiles at the given time.
+    pub fn source(&self, time: Option<&str>) -> impl UrlSource<TileIndex> {
+        let source = self.clone();
+        let time = time.map(str::to_string);
+        move |index: &TileIndex| source.url(index, time.as_deref()).unwrap_or_default()
+    }
+}
+
+/// Percent-encodes everything except the unreserved characters (and commas, which are used as list separators in WMS).
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_substitutes_time() {
+        let template = TileUrlTemplate::new("https://example.com/{time}/{z}/{x}/{y}.png");
+        let index = TileIndex {
+            z: 1,
+            x: 3,
+            y: 2,
+            display_x: 3,
+        };
+        assert_eq!(
+            template.url(&index, Some("2024-06-01T12:00:00Z")),
+            "https://example.com/2024-06-01T12%3A00%3A00Z/1/3/2.png"
+        );
+    }
+
+    #[test]
+    fn wms_url_has_bbox_and_time() {
+        let source = WmsTileSource::new("https://example.com/wms", "radar", TileSchema::web(4));
+        let url = source
+            .url(
+                &TileIndex {
+                    z: 0,
+                    x: 0,
+                    y: 0,
+                    display_x: 0,
+                },
+                Some("2024-06-01T12:00:00Z"),
+            )
+            .expect("tile exists");
+
+        assert!(url.starts_with("https://example.com/wms?SERVICE=WMS&VERSION=1.3.0&REQUEST=GetMap"));
+        assert!(url.contains("&LAYERS=radar&"));
+        assert!(url.contains("&CRS=EPSG%3A3857&"));
+        assert!(url.contains("&WIDTH=256&HEIGHT=256"));
+        assert!(url.ends_with("&TIME=2024-06-01T12%3A00%3A00Z"));
+    }
+}
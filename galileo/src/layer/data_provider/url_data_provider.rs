@@ -1,12 +1,13 @@
 use crate::error::GalileoError;
 use crate::layer::data_provider::dummy::DummyCacheController;
 use crate::layer::data_provider::{
-    DataProcessor, DataProvider, PersistentCacheController, UrlSource,
+    DataProcessor, DataProvider, DefaultHttpClient, HttpClient, PersistentCacheController,
+    UrlSource,
 };
-use crate::platform::{PlatformService, PlatformServiceImpl};
 use bytes::Bytes;
 use maybe_sync::{MaybeSend, MaybeSync};
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 /// Loads data from Internet and uses `Cache` persistent cache to save data locally.
 pub struct UrlDataProvider<Key, Decoder, Cache = DummyCacheController>
@@ -19,7 +20,7 @@ where
     decoder: Decoder,
     cache: Option<Cache>,
     offline_mode: bool,
-    platform_service: PlatformServiceImpl,
+    http_client: Arc<dyn HttpClient>,
     _phantom_key: PhantomData<Key>,
 }
 
@@ -35,7 +36,7 @@ where
             decoder,
             cache: None,
             offline_mode: false,
-            platform_service: PlatformServiceImpl::new(),
+            http_client: Arc::new(DefaultHttpClient::new()),
             _phantom_key: Default::default(),
         }
     }
@@ -59,11 +60,17 @@ where
             decoder,
             cache: Some(cache),
             offline_mode: false,
-            platform_service: PlatformServiceImpl::new(),
+            http_client: Arc::new(DefaultHttpClient::new()),
             _phantom_key: Default::default(),
         }
     }
 
+    /// Sets the HTTP client used to load the data.
+    pub fn with_http_client(mut self, http_client: impl HttpClient + 'static) -> Self {
+        self.http_client = Arc::new(http_client);
+        self
+    }
+
     /// If offline mode is enabled, the provider will not attempt to download data from Internet, and will only use
     /// its cache as the source of data.
     #[cfg(not(target_arch = "wasm32"))]
@@ -103,7 +110,7 @@ where
 
         self.check_offline_mode()?;
 
-        let data = self.platform_service.load_bytes_from_url(&url).await?;
+        let data = self.http_client.get(&url).await?;
 
         if let Some(cache) = &self.cache {
             if let Err(error) = cache.insert(&url, &data) {
@@ -121,4 +128,7 @@ where
     ) -> Result<Decoder::Output, GalileoError> {
         self.decoder.process(raw, context)
     }
+    fn cache_key(&self, key: &Key) -> Option<String> {
+        Some((self.url_source)(key))
+    }
 }
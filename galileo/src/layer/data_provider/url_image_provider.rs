@@ -1,11 +1,14 @@
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
 use crate::layer::data_provider::dummy::DummyCacheController;
-use crate::layer::data_provider::{DataProvider, PersistentCacheController, UrlSource};
+use crate::layer::data_provider::{
+    DataProvider, HttpClient, ImageDecoderRegistry, PersistentCacheController, UrlSource,
+};
 use crate::platform::{PlatformService, PlatformServiceImpl};
 use bytes::Bytes;
 use maybe_sync::{MaybeSend, MaybeSync};
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 #[cfg(target_arch = "wasm32")]
 use std::future::Future;
@@ -16,7 +19,9 @@ pub struct UrlImageProvider<Key, Cache = DummyCacheController> {
     url_source: Box<dyn UrlSource<Key>>,
     cache: Option<Cache>,
     platform_service: PlatformServiceImpl,
+    http_client: Option<Arc<dyn HttpClient>>,
     offline_mode: bool,
+    decoders: ImageDecoderRegistry,
     _phantom_key: PhantomData<Key>,
 }
 
@@ -27,7 +32,9 @@ impl<Key> UrlImageProvider<Key, DummyCacheController> {
             url_source: Box::new(url_source),
             cache: None,
             platform_service: PlatformServiceImpl::new(),
+            http_client: None,
             offline_mode: false,
+            decoders: ImageDecoderRegistry::new(),
             _phantom_key: Default::default(),
         }
     }
@@ -40,11 +47,28 @@ impl<Key, Cache> UrlImageProvider<Key, Cache> {
             url_source: Box::new(url_source),
             cache: Some(cache),
             platform_service: PlatformServiceImpl::new(),
+            http_client: None,
             offline_mode: false,
+            decoders: ImageDecoderRegistry::new(),
             _phantom_key: Default::default(),
         }
     }
 
+    /// Sets the decoders used to decode the loaded images.
+    ///
+    /// On the web, images are decoded by the browser unless any custom decoders are registered or a custom HTTP
+    /// client is set.
+    pub fn with_decoders(mut self, decoders: ImageDecoderRegistry) -> Self {
+        self.decoders = decoders;
+        self
+    }
+
+    /// Sets the HTTP client used to load the images.
+    pub fn with_http_client(mut self, http_client: impl HttpClient + 'static) -> Self {
+        self.http_client = Some(Arc::new(http_client));
+        self
+    }
+
     /// If offline mode is enabled, the provider will not attempt to download data from Internet, and will only use
     /// its cache as the source of data.
     #[cfg(not(target_arch = "wasm32"))]
@@ -57,6 +81,13 @@ impl<Key, Cache> UrlImageProvider<Key, Cache> {
         self.offline_mode = enabled;
     }
 
+    async fn load_bytes(&self, url: &str) -> Result<Bytes, GalileoError> {
+        match &self.http_client {
+            Some(http_client) => http_client.get(url).await,
+            None => self.platform_service.load_bytes_from_url(url).await,
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn check_offline_mode(&self) -> Result<(), GalileoError> {
         if self.offline_mode {
@@ -85,7 +116,7 @@ where
         self.check_offline_mode()?;
 
         log::info!("Loading {url}");
-        let data = self.platform_service.load_bytes_from_url(&url).await?;
+        let data = self.load_bytes(&url).await?;
 
         if let Some(cache) = &self.cache {
             if let Err(error) = cache.insert(&url, &data) {
@@ -97,7 +128,11 @@ where
     }
 
     fn decode(&self, bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
-        DecodedImage::new(&bytes)
+        self.decoders.decode(&bytes)
+    }
+
+    fn cache_key(&self, key: &Key) -> Option<String> {
+        Some((self.url_source)(key))
     }
 }
 
@@ -120,6 +155,15 @@ where
 
     async fn load(&self, key: &Key, _context: ()) -> Result<DecodedImage, GalileoError> {
         let url = (self.url_source)(key);
-        self.platform_service.load_image_url(&url).await
+        if self.decoders.is_empty() && self.http_client.is_none() {
+            return self.platform_service.load_image_url(&url).await;
+        }
+
+        let bytes = self.load_bytes(&url).await?;
+        self.decoders.decode(&bytes)
+    }
+
+    fn cache_key(&self, key: &Key) -> Option<String> {
+        Some((self.url_source)(key))
     }
 }
@@ -0,0 +1,549 @@
+use crate::error::GalileoError;
+use crate::layer::feature_layer::wkb::read_wkb;
+use crate::layer::feature_layer::{AttributeValue, Feature, FeatureLayer};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, ArrayRef, FixedSizeListArray, RecordBatch};
+use arrow_schema::DataType;
+use galileo_types::cartesian::Point2d;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::NewGeoPoint;
+use galileo_types::geometry::Geom;
+use galileo_types::geometry_type::{GeoSpace2d, GeometryType};
+use galileo_types::impls::{
+    ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon,
+};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Feature layer with features loaded from Arrow record batches.
+pub type ArrowLayer<S> = FeatureLayer<GeoPoint2d, ArrowFeature<GeoPoint2d>, S, GeoSpace2d>;
+
+/// Metadata key of the Arrow extension type name of a column.
+const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+
+/// Feature created from a row of an Arrow [`RecordBatch`].
+///
+/// The geometry is read from a column with GeoArrow native encoding (points stored as interleaved `FixedSizeList` or
+/// as a `Struct` with `x` and `y` fields, nested into lists for other geometry types) or with WKB encoding. Only the
+/// *x* and *y* coordinates are used.
+///
+/// Attributes are not copied into the feature: all features of a batch share it, and the values are read from the
+/// columns of the batch when requested.
+///
+/// ```no_run
+/// use galileo::layer::feature_layer::ArrowFeature;
+/// # fn load_batch() -> arrow_array::RecordBatch { unimplemented!() }
+///
+/// let features = ArrowFeature::from_record_batch(load_batch(), "geometry").unwrap();
+/// for feature in &features {
+///     println!("{:?}", feature.get_str("name"));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrowFeature<P> {
+    geometry: Geom<P>,
+    batch: Arc<RecordBatch>,
+    row: usize,
+}
+
+impl<P> ArrowFeature<P> {
+    /// Record batch the feature was created from.
+    pub fn batch(&self) -> &RecordBatch {
+        &self.batch
+    }
+
+    /// Index of the feature row in the record batch.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Value of the attribute column with the given name. Returns `None` if there is no such column, or the type of
+    /// the column is not supported.
+    ///
+    /// String values are copied. Use [`ArrowFeature::get_str`] to read them without allocation.
+    pub fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        array_value(self.batch.column_by_name(name)?, self.row)
+    }
+
+    /// Value of a numeric attribute column.
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        let column = self.batch.column_by_name(name)?;
+        if matches!(column.data_type(), DataType::Utf8 | DataType::LargeUtf8) {
+            return None;
+        }
+
+        array_value(column, self.row)?.as_f64()
+    }
+
+    /// Value of a string attribute column.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        let column = self.batch.column_by_name(name)?;
+        if column.is_null(self.row) {
+            return None;
+        }
+
+        match column.data_type() {
+            DataType::Utf8 => Some(column.as_string::<i32>().value(self.row)),
+            DataType::LargeUtf8 => Some(column.as_string::<i64>().value(self.row)),
+            _ => None,
+        }
+    }
+}
+
+impl<P: GeometryType> Feature for ArrowFeature<P> {
+    type Geom = Geom<P>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        ArrowFeature::attribute(self, name)
+    }
+
+    /// Columns of types that cannot be represented as an [`AttributeValue`] (including the geometry column) are
+    /// skipped.
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        self.batch
+            .schema()
+            .fields()
+            .iter()
+            .zip(self.batch.columns())
+            .filter_map(|(field, column)| {
+                Some((field.name().clone(), array_value(column, self.row)?))
+            })
+            .collect()
+    }
+}
+
+impl ArrowFeature<GeoPoint2d> {
+    /// Creates features from the rows of the batch with geographic coordinates in the `geometry_column` (longitude as
+    /// *x* and latitude as *y*, as in GeoArrow). Rows with null geometries are skipped.
+    pub fn from_record_batch(
+        batch: RecordBatch,
+        geometry_column: &str,
+    ) -> Result<Vec<Self>, GalileoError> {
+        read_record_batch(batch, geometry_column, |x, y| GeoPoint2d::latlon(y, x))
+    }
+}
+
+impl ArrowFeature<Point2d> {
+    /// Creates features from the rows of the batch with projected coordinates in the `geometry_column`. Rows with
+    /// null geometries are skipped.
+    pub fn from_record_batch_projected(
+        batch: RecordBatch,
+        geometry_column: &str,
+    ) -> Result<Vec<Self>, GalileoError> {
+        read_record_batch(batch, geometry_column, Point2d::new)
+    }
+}
+
+#[cfg(all(feature = "parquet", not(target_arch = "wasm32")))]
+impl ArrowFeature<GeoPoint2d> {
+    /// Reads all the features from a GeoParquet file with geographic coordinates.
+    ///
+    /// The geometry column is taken from the GeoParquet metadata of the file, or `geometry` column is used if the
+    /// file has no such metadata.
+    pub fn read_geoparquet(path: impl AsRef<std::path::Path>) -> Result<Vec<Self>, GalileoError> {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let parquet_error = |err: parquet::errors::ParquetError| {
+            GalileoError::Generic(format!("failed to read parquet file: {err}"))
+        };
+
+        let file = std::fs::File::open(path)?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(parquet_error)?;
+
+        let geometry_column = builder
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .and_then(|metadata| metadata.iter().find(|kv| kv.key == "geo"))
+            .and_then(|kv| kv.value.as_deref())
+            .and_then(|geo| serde_json::from_str::<serde_json::Value>(geo).ok())
+            .and_then(|geo| geo.get("primary_column")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| "geometry".to_string());
+
+        let mut features = vec![];
+        for batch in builder.build().map_err(parquet_error)? {
+            let batch = batch.map_err(|err| {
+                GalileoError::Generic(format!("failed to read parquet file: {err}"))
+            })?;
+            features.extend(Self::from_record_batch(batch, &geometry_column)?);
+        }
+
+        Ok(features)
+    }
+}
+
+fn read_record_batch<P>(
+    batch: RecordBatch,
+    geometry_column: &str,
+    point: impl Fn(f64, f64) -> P,
+) -> Result<Vec<ArrowFeature<P>>, GalileoError> {
+    let batch = Arc::new(batch);
+    let schema = batch.schema();
+    let field = schema.field_with_name(geometry_column).map_err(|_| {
+        GalileoError::Generic(format!("geometry column {geometry_column} is not found"))
+    })?;
+    let column = batch
+        .column_by_name(geometry_column)
+        .expect("schema contains the column");
+    let reader = GeometryReader::new(
+        column,
+        field.metadata().get(EXTENSION_NAME_KEY).map(String::as_str),
+    )?;
+
+    let mut features = Vec::with_capacity(batch.num_rows());
+    for row in 0..batch.num_rows() {
+        if column.is_null(row) {
+            continue;
+        }
+
+        features.push(ArrowFeature {
+            geometry: reader.read(row, &point)?,
+            batch: batch.clone(),
+            row,
+        });
+    }
+
+    Ok(features)
+}
+
+fn array_value(array: &ArrayRef, row: usize) -> Option<AttributeValue> {
+    if array.is_null(row) {
+        return Some(AttributeValue::Null);
+    }
+
+    let value = match array.data_type() {
+        DataType::Boolean => AttributeValue::Bool(array.as_boolean().value(row)),
+        DataType::Int8 => AttributeValue::Int(array.as_primitive::<Int8Type>().value(row) as i64),
+        DataType::Int16 => AttributeValue::Int(array.as_primitive::<Int16Type>().value(row) as i64),
+        DataType::Int32 => AttributeValue::Int(array.as_primitive::<Int32Type>().value(row) as i64),
+        DataType::Int64 => AttributeValue::Int(array.as_primitive::<Int64Type>().value(row)),
+        DataType::UInt8 => AttributeValue::Int(array.as_primitive::<UInt8Type>().value(row) as i64),
+        DataType::UInt16 => {
+            AttributeValue::Int(array.as_primitive::<UInt16Type>().value(row) as i64)
+        }
+        DataType::UInt32 => {
+            AttributeValue::Int(array.as_primitive::<UInt32Type>().value(row) as i64)
+        }
+        DataType::UInt64 => {
+            AttributeValue::Int(array.as_primitive::<UInt64Type>().value(row) as i64)
+        }
+        DataType::Float32 => {
+            AttributeValue::Float(array.as_primitive::<Float32Type>().value(row) as f64)
+        }
+        DataType::Float64 => AttributeValue::Float(array.as_primitive::<Float64Type>().value(row)),
+        DataType::Utf8 => AttributeValue::String(array.as_string::<i32>().value(row).to_string()),
+        DataType::LargeUtf8 => {
+            AttributeValue::String(array.as_string::<i64>().value(row).to_string())
+        }
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+fn unsupported_geometry() -> GalileoError {
+    GalileoError::Generic("unsupported encoding of the geometry column".to_string())
+}
+
+/// Geometry types of the GeoArrow native encoding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum GeoArrowType {
+    Point,
+    LineString,
+    Polygon,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+}
+
+impl GeoArrowType {
+    fn from_extension_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "geoarrow.point" => Self::Point,
+            "geoarrow.linestring" => Self::LineString,
+            "geoarrow.polygon" => Self::Polygon,
+            "geoarrow.multipoint" => Self::MultiPoint,
+            "geoarrow.multilinestring" => Self::MultiLineString,
+            "geoarrow.multipolygon" => Self::MultiPolygon,
+            _ => return None,
+        })
+    }
+
+    fn from_nesting(depth: usize) -> Option<Self> {
+        Some(match depth {
+            0 => Self::Point,
+            1 => Self::LineString,
+            2 => Self::Polygon,
+            3 => Self::MultiPolygon,
+            _ => return None,
+        })
+    }
+
+    fn nesting(&self) -> usize {
+        match self {
+            Self::Point => 0,
+            Self::LineString | Self::MultiPoint => 1,
+            Self::Polygon | Self::MultiLineString => 2,
+            Self::MultiPolygon => 3,
+        }
+    }
+}
+
+/// Reads geometries from a geometry column. All the offsets of the column are converted once, so that reading a
+/// single geometry does not need to downcast arrays.
+enum GeometryReader<'a> {
+    Wkb(&'a ArrayRef),
+    Native {
+        geometry_type: GeoArrowType,
+        /// Offsets of the list levels, from the outer to the inner one.
+        offsets: Vec<Vec<usize>>,
+        coords: Coords<'a>,
+    },
+}
+
+enum Coords<'a> {
+    Interleaved {
+        list: &'a FixedSizeListArray,
+        values: &'a [f64],
+    },
+    Separated {
+        x: &'a [f64],
+        y: &'a [f64],
+    },
+}
+
+impl<'a> Coords<'a> {
+    fn new(array: &'a ArrayRef) -> Result<Self, GalileoError> {
+        if let Some(list) = array.as_fixed_size_list_opt() {
+            if list.value_length() < 2 {
+                return Err(unsupported_geometry());
+            }
+
+            let values = list
+                .values()
+                .as_primitive_opt::<Float64Type>()
+                .ok_or_else(unsupported_geometry)?;
+            return Ok(Self::Interleaved {
+                list,
+                values: values.values(),
+            });
+        }
+
+        if let Some(coords) = array.as_struct_opt() {
+            let component = |name: &str| {
+                coords
+                    .column_by_name(name)
+                    .and_then(|c| c.as_primitive_opt::<Float64Type>())
+                    .map(|c| c.values().as_ref())
+                    .ok_or_else(unsupported_geometry)
+            };
+
+            return Ok(Self::Separated {
+                x: component("x")?,
+                y: component("y")?,
+            });
+        }
+
+        Err(unsupported_geometry())
+    }
+
+    fn get<P>(&self, index: usize, point: &impl Fn(f64, f64) -> P) -> P {
+        match self {
+            Coords::Interleaved { list, values } => {
+                let offset = list.value_offset(index) as usize;
+                point(values[offset], values[offset + 1])
+            }
+            Coords::Separated { x, y } => point(x[index], y[index]),
+        }
+    }
+
+    fn range<P>(&self, range: Range<usize>, point: &impl Fn(f64, f64) -> P) -> Vec<P> {
+        range.map(|index| self.get(index, point)).collect()
+    }
+
+    fn ring<P>(&self, range: Range<usize>, point: &impl Fn(f64, f64) -> P) -> ClosedContour<P> {
+        // Rings are closed explicitly in GeoArrow, while the closing point is implicit in `ClosedContour`.
+        let end = if range.len() > 1 {
+            range.end - 1
+        } else {
+            range.end
+        };
+        ClosedContour::new(self.range(range.start..end, point))
+    }
+}
+
+fn list_parts(array: &ArrayRef) -> Option<(Vec<usize>, &ArrayRef)> {
+    if let Some(list) = array.as_list_opt::<i32>() {
+        let offsets = list.value_offsets().iter().map(|o| *o as usize).collect();
+        Some((offsets, list.values()))
+    } else if let Some(list) = array.as_list_opt::<i64>() {
+        let offsets = list.value_offsets().iter().map(|o| *o as usize).collect();
+        Some((offsets, list.values()))
+    } else {
+        None
+    }
+}
+
+impl<'a> GeometryReader<'a> {
+    fn new(column: &'a ArrayRef, extension_name: Option<&str>) -> Result<Self, GalileoError> {
+        if matches!(column.data_type(), DataType::Binary | DataType::LargeBinary)
+            || extension_name == Some("geoarrow.wkb")
+        {
+            return Ok(Self::Wkb(column));
+        }
+
+        let mut offsets = vec![];
+        let mut values = column;
+        while let Some((level_offsets, inner)) = list_parts(values) {
+            offsets.push(level_offsets);
+            values = inner;
+        }
+
+        let geometry_type = match extension_name {
+            Some(name) => GeoArrowType::from_extension_name(name),
+            None => GeoArrowType::from_nesting(offsets.len()),
+        }
+        .filter(|geometry_type| geometry_type.nesting() == offsets.len())
+        .ok_or_else(unsupported_geometry)?;
+
+        Ok(Self::Native {
+            geometry_type,
+            offsets,
+            coords: Coords::new(values)?,
+        })
+    }
+
+    fn read<P>(&self, row: usize, point: &impl Fn(f64, f64) -> P) -> Result<Geom<P>, GalileoError> {
+        let (geometry_type, offsets, coords) = match self {
+            GeometryReader::Wkb(column) => {
+                let bytes = match column.data_type() {
+                    DataType::LargeBinary => column.as_binary::<i64>().value(row),
+                    _ => column
+                        .as_binary_opt::<i32>()
+                        .ok_or_else(unsupported_geometry)?
+                        .value(row),
+                };
+                return read_wkb(bytes, point);
+            }
+            GeometryReader::Native {
+                geometry_type,
+                offsets,
+                coords,
+            } => (geometry_type, offsets, coords),
+        };
+
+        let range = |level: usize, index: usize| offsets[level][index]..offsets[level][index + 1];
+
+        let geometry = match geometry_type {
+            GeoArrowType::Point => Geom::Point(coords.get(row, point)),
+            GeoArrowType::LineString => {
+                Geom::Contour(Contour::open(coords.range(range(0, row), point)))
+            }
+            GeoArrowType::MultiPoint => {
+                Geom::MultiPoint(MultiPoint::from(coords.range(range(0, row), point)))
+            }
+            GeoArrowType::Polygon => {
+                Geom::Polygon(read_polygon(coords, &range, 0, row, point).ok_or_else(|| {
+                    GalileoError::Generic(format!("polygon in row {row} has no rings"))
+                })?)
+            }
+            GeoArrowType::MultiLineString => Geom::MultiContour(MultiContour::from(
+                range(0, row)
+                    .map(|line| Contour::open(coords.range(range(1, line), point)))
+                    .collect::<Vec<_>>(),
+            )),
+            GeoArrowType::MultiPolygon => Geom::MultiPolygon(MultiPolygon::from(
+                range(0, row)
+                    .filter_map(|polygon| read_polygon(coords, &range, 1, polygon, point))
+                    .collect::<Vec<_>>(),
+            )),
+        };
+
+        Ok(geometry)
+    }
+}
+
+fn read_polygon<P>(
+    coords: &Coords,
+    range: &impl Fn(usize, usize) -> Range<usize>,
+    level: usize,
+    index: usize,
+    point: &impl Fn(f64, f64) -> P,
+) -> Option<Polygon<P>> {
+    let mut rings = range(level, index).map(|ring| coords.ring(range(level + 1, ring), point));
+    let outer = rings.next()?;
+    Some(Polygon::new(outer, rings.collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::builder::{Float64Builder, ListBuilder, StructBuilder};
+    use arrow_array::StringArray;
+    use arrow_schema::{Field, Fields, Schema};
+    use galileo_types::contour::Contour as _;
+    use galileo_types::geo::GeoPoint;
+
+    #[test]
+    fn features_from_linestring_batch() {
+        let point_fields = Fields::from(vec![
+            Field::new("x", DataType::Float64, false),
+            Field::new("y", DataType::Float64, false),
+        ]);
+        let mut lines = ListBuilder::new(StructBuilder::from_fields(point_fields.clone(), 0));
+        for line in [
+            &[(10.0, 50.0), (11.0, 51.0)][..],
+            &[(20.0, 40.0), (21.0, 41.0)],
+        ] {
+            for (x, y) in line {
+                let points = lines.values();
+                points
+                    .field_builder::<Float64Builder>(0)
+                    .unwrap()
+                    .append_value(*x);
+                points
+                    .field_builder::<Float64Builder>(1)
+                    .unwrap()
+                    .append_value(*y);
+                points.append(true);
+            }
+            lines.append(true);
+        }
+        let lines = lines.finish();
+
+        let schema = Schema::new(vec![
+            Field::new("geometry", lines.data_type().clone(), true),
+            Field::new("name", DataType::Utf8, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(lines),
+                Arc::new(StringArray::from(vec![Some("first"), None])),
+            ],
+        )
+        .unwrap();
+
+        let features = ArrowFeature::from_record_batch(batch, "geometry").unwrap();
+        assert_eq!(features.len(), 2);
+
+        let Geom::Contour(contour) = features[0].geometry() else {
+            panic!("not a contour");
+        };
+        let first = contour.iter_points().next().unwrap();
+        assert_eq!((first.lat(), first.lon()), (50.0, 10.0));
+
+        assert_eq!(features[0].get_str("name"), Some("first"));
+        assert_eq!(features[1].get_str("name"), None);
+        assert_eq!(features[1].attribute("name"), Some(AttributeValue::Null));
+        assert!(ArrowFeature::from_record_batch(features[0].batch().clone(), "name").is_err());
+    }
+}
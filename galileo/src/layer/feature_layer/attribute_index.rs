@@ -0,0 +1,131 @@
+use crate::layer::feature_layer::{AttributeValue, Feature, FeatureStore};
+use std::collections::HashMap;
+
+/// Hash index of the values of one attribute of the features in a [`FeatureStore`].
+///
+/// The index is rebuilt from scratch when the store is changed, so it is intended for the data sets that are looked
+/// up more often than they are modified.
+pub(crate) struct AttributeIndex {
+    attribute: String,
+    revision: Option<u64>,
+    values: HashMap<IndexKey, Vec<usize>>,
+}
+
+impl AttributeIndex {
+    pub(crate) fn new(attribute: &str) -> Self {
+        Self {
+            attribute: attribute.to_string(),
+            revision: None,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Indices of the features with the given value of the attribute, in the order of the features in the store.
+    pub(crate) fn find<F: Feature>(
+        &mut self,
+        store: &FeatureStore<F>,
+        value: &AttributeValue,
+    ) -> Vec<usize> {
+        if self.revision != Some(store.revision()) {
+            self.rebuild(store);
+        }
+
+        self.values
+            .get(&IndexKey::from(value))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn rebuild<F: Feature>(&mut self, store: &FeatureStore<F>) {
+        self.values.clear();
+        for feature in store.iter() {
+            if let Some(value) = feature.as_ref().attribute(&self.attribute) {
+                self.values
+                    .entry(IndexKey::from(&value))
+                    .or_default()
+                    .push(feature.index());
+            }
+        }
+
+        self.revision = Some(store.revision());
+    }
+}
+
+/// Hashable form of an [`AttributeValue`]. Values that are equal as attribute values have equal keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum IndexKey {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(u64),
+    String(String),
+}
+
+impl From<&AttributeValue> for IndexKey {
+    fn from(value: &AttributeValue) -> Self {
+        match value {
+            AttributeValue::Null => Self::Null,
+            AttributeValue::Bool(v) => Self::Bool(*v),
+            AttributeValue::Int(v) => Self::Int(*v),
+            // Zeros of both signs are equal as floats.
+            AttributeValue::Float(v) if *v == 0.0 => Self::Float(0),
+            AttributeValue::Float(v) => Self::Float(v.to_bits()),
+            AttributeValue::String(v) => Self::String(v.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::FeatureId;
+    use galileo_types::cartesian::Point2d;
+
+    #[derive(Debug, PartialEq)]
+    struct City {
+        name: &'static str,
+        position: Point2d,
+    }
+
+    impl Feature for City {
+        type Geom = Point2d;
+
+        fn geometry(&self) -> &Self::Geom {
+            &self.position
+        }
+
+        fn id(&self) -> Option<FeatureId> {
+            Some(self.name.into())
+        }
+
+        fn attribute(&self, name: &str) -> Option<AttributeValue> {
+            (name == "name").then(|| self.name.into())
+        }
+    }
+
+    fn city(name: &'static str) -> City {
+        City {
+            name,
+            position: Point2d::new(0.0, 0.0),
+        }
+    }
+
+    #[test]
+    fn index_follows_store_changes() {
+        let mut store = FeatureStore::new([city("Paris"), city("Lyon")].into_iter());
+        let mut index = AttributeIndex::new("name");
+        let paris = AttributeValue::from("Paris");
+
+        assert_eq!(index.find(&store, &paris), vec![0]);
+        assert!(index.find(&store, &"Nice".into()).is_empty());
+
+        store.insert_at(0, city("Nice"));
+        assert_eq!(index.find(&store, &paris), vec![1]);
+
+        store.get_mut(2).unwrap().as_mut().name = "Paris";
+        assert_eq!(index.find(&store, &paris), vec![1, 2]);
+
+        store.remove(1);
+        assert_eq!(index.find(&store, &paris), vec![1]);
+    }
+}
@@ -0,0 +1,354 @@
+use crate::error::GalileoError;
+use crate::layer::feature_layer::{AttributeValue, Feature, FeatureLayer};
+use galileo_types::cartesian::Point2d;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, NewGeoPoint, Projection};
+use galileo_types::geometry_type::GeoSpace2d;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Arc;
+
+/// Feature layer with point features loaded from a CSV file.
+pub type CsvLayer<S> = FeatureLayer<GeoPoint2d, CsvFeature, S, GeoSpace2d>;
+
+/// Columns the coordinates of the points are read from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoordinateColumns {
+    /// Latitude and longitude in degrees.
+    LatLon {
+        /// Name of the latitude column.
+        lat: String,
+        /// Name of the longitude column.
+        lon: String,
+    },
+    /// Projected coordinates in the given CRS. The points are converted into geographic coordinates when loaded.
+    Projected {
+        /// Name of the *x* coordinate column.
+        x: String,
+        /// Name of the *y* coordinate column.
+        y: String,
+        /// CRS of the coordinates.
+        crs: Crs,
+    },
+}
+
+/// Type the values of an attribute column are parsed into.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColumnType {
+    /// `true`/`false` (case-insensitive), `1`/`0` or `yes`/`no`.
+    Bool,
+    /// Integer value.
+    Int,
+    /// Floating point value.
+    Float,
+    /// The value is kept as is.
+    String,
+}
+
+/// Point feature loaded by a [`CsvReader`]. Empty fields of the row become [`AttributeValue::Null`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvFeature {
+    point: GeoPoint2d,
+    columns: Arc<Vec<String>>,
+    attributes: Vec<AttributeValue>,
+}
+
+impl CsvFeature {
+    /// Position of the feature.
+    pub fn point(&self) -> &GeoPoint2d {
+        &self.point
+    }
+
+    /// Value of the attribute column with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&AttributeValue> {
+        let index = self.columns.iter().position(|column| column == name)?;
+        self.attributes.get(index)
+    }
+
+    /// Iterates over names and values of all the attributes of the feature, including the coordinate columns.
+    pub fn attributes(&self) -> impl Iterator<Item = (&str, &AttributeValue)> {
+        self.columns
+            .iter()
+            .map(String::as_str)
+            .zip(self.attributes.iter())
+    }
+}
+
+impl Feature for CsvFeature {
+    type Geom = GeoPoint2d;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.point
+    }
+
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        CsvFeature::attribute(self, name).cloned()
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        CsvFeature::attributes(self)
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+}
+
+/// Reads point features from delimited text (CSV, TSV etc.) with a header row.
+///
+/// The types of the attribute columns can be set with [`CsvReader::with_column_type`]. The values of the other
+/// columns are parsed as integers, floats or booleans if possible, and kept as strings otherwise.
+///
+/// ```
+/// use galileo::layer::feature_layer::{AttributeValue, ColumnType, CsvReader};
+///
+/// let csv = "name,lat,lon,population\nParis,48.86,2.35,2102650\nLyon,45.76,4.84,\n";
+/// let features = CsvReader::latlon("lat", "lon")
+///     .with_column_type("population", ColumnType::Float)
+///     .read(csv.as_bytes())
+///     .unwrap();
+///
+/// assert_eq!(features.len(), 2);
+/// assert_eq!(features[0].attribute("population"), Some(&AttributeValue::Float(2102650.0)));
+/// assert_eq!(features[1].attribute("population"), Some(&AttributeValue::Null));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvReader {
+    coordinates: CoordinateColumns,
+    delimiter: u8,
+    column_types: HashMap<String, ColumnType>,
+}
+
+impl CsvReader {
+    /// Creates a new reader of comma separated values with the given coordinate columns.
+    pub fn new(coordinates: CoordinateColumns) -> Self {
+        Self {
+            coordinates,
+            delimiter: b',',
+            column_types: HashMap::new(),
+        }
+    }
+
+    /// Creates a new reader of comma separated values with latitude and longitude columns.
+    pub fn latlon(lat: impl Into<String>, lon: impl Into<String>) -> Self {
+        Self::new(CoordinateColumns::LatLon {
+            lat: lat.into(),
+            lon: lon.into(),
+        })
+    }
+
+    /// Creates a new reader of comma separated values with projected coordinate columns.
+    pub fn projected(x: impl Into<String>, y: impl Into<String>, crs: Crs) -> Self {
+        Self::new(CoordinateColumns::Projected {
+            x: x.into(),
+            y: y.into(),
+            crs,
+        })
+    }
+
+    /// Sets the field delimiter.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets the field delimiter to tab, to read TSV files.
+    pub fn tsv(self) -> Self {
+        self.with_delimiter(b'\t')
+    }
+
+    /// Sets the type of the values of the column.
+    pub fn with_column_type(mut self, column: impl Into<String>, column_type: ColumnType) -> Self {
+        self.column_types.insert(column.into(), column_type);
+        self
+    }
+
+    /// Reads all the features from the reader.
+    ///
+    /// Returns an error if the coordinate columns are missing, or a row has invalid coordinates or a value that
+    /// cannot be parsed into the type set for its column.
+    pub fn read(&self, reader: impl Read) -> Result<Vec<CsvFeature>, GalileoError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        let columns: Vec<String> = reader
+            .headers()
+            .map_err(csv_error)?
+            .iter()
+            .map(str::to_string)
+            .collect();
+        let column_index = |name: &str| {
+            columns
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| GalileoError::Generic(format!("column {name} is not found")))
+        };
+
+        let (x_index, y_index) = match &self.coordinates {
+            CoordinateColumns::LatLon { lat, lon } => (column_index(lon)?, column_index(lat)?),
+            CoordinateColumns::Projected { x, y, .. } => (column_index(x)?, column_index(y)?),
+        };
+        let to_geo = self.coordinate_converter()?;
+        let types: Vec<Option<ColumnType>> = columns
+            .iter()
+            .map(|column| self.column_types.get(column).copied())
+            .collect();
+
+        let columns = Arc::new(columns);
+        let mut features = vec![];
+        for record in reader.records() {
+            let record = record.map_err(csv_error)?;
+            let line = record.position().map(|p| p.line()).unwrap_or_default();
+
+            let coordinate = |index: usize| {
+                record
+                    .get(index)
+                    .and_then(|value| value.parse::<f64>().ok())
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| {
+                        GalileoError::Generic(format!("invalid coordinates at line {line}"))
+                    })
+            };
+            let point = to_geo(coordinate(x_index)?, coordinate(y_index)?).ok_or_else(|| {
+                GalileoError::Generic(format!("cannot convert coordinates at line {line}"))
+            })?;
+
+            let attributes = types
+                .iter()
+                .enumerate()
+                .map(|(index, column_type)| {
+                    parse_value(record.get(index).unwrap_or_default(), *column_type).ok_or_else(
+                        || {
+                            GalileoError::Generic(format!(
+                                "invalid value of column {} at line {line}",
+                                columns[index]
+                            ))
+                        },
+                    )
+                })
+                .collect::<Result<_, _>>()?;
+
+            features.push(CsvFeature {
+                point,
+                columns: columns.clone(),
+                attributes,
+            });
+        }
+
+        Ok(features)
+    }
+
+    /// Reads all the features from the file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<CsvFeature>, GalileoError> {
+        self.read(std::fs::File::open(path)?)
+    }
+
+    fn coordinate_converter(
+        &self,
+    ) -> Result<Box<dyn Fn(f64, f64) -> Option<GeoPoint2d>>, GalileoError> {
+        match &self.coordinates {
+            CoordinateColumns::LatLon { .. } => {
+                Ok(Box::new(|lon, lat| Some(GeoPoint2d::latlon(lat, lon))))
+            }
+            CoordinateColumns::Projected { crs, .. } if *crs == Crs::WGS84 => {
+                Ok(Box::new(|x, y| Some(GeoPoint2d::latlon(y, x))))
+            }
+            CoordinateColumns::Projected { crs, .. } => {
+                let projection = crs.get_projection::<GeoPoint2d, Point2d>().ok_or_else(|| {
+                    GalileoError::Generic(format!("unsupported CRS of coordinates: {crs:?}"))
+                })?;
+                Ok(Box::new(move |x, y| {
+                    projection.unproject(&Point2d::new(x, y))
+                }))
+            }
+        }
+    }
+}
+
+fn csv_error(err: csv::Error) -> GalileoError {
+    GalileoError::Generic(format!("failed to read CSV: {err}"))
+}
+
+/// Parses the field value. Returns `None` if the value does not match the column type.
+fn parse_value(value: &str, column_type: Option<ColumnType>) -> Option<AttributeValue> {
+    if value.is_empty() {
+        return Some(AttributeValue::Null);
+    }
+
+    let Some(column_type) = column_type else {
+        return Some(infer_value(value));
+    };
+
+    Some(match column_type {
+        ColumnType::Bool => AttributeValue::Bool(parse_bool(value)?),
+        ColumnType::Int => AttributeValue::Int(value.parse().ok()?),
+        ColumnType::Float => AttributeValue::Float(value.parse().ok()?),
+        ColumnType::String => AttributeValue::String(value.to_string()),
+    })
+}
+
+fn infer_value(value: &str) -> AttributeValue {
+    if let Ok(v) = value.parse::<i64>() {
+        AttributeValue::Int(v)
+    } else if let Ok(v) = value.parse::<f64>() {
+        AttributeValue::Float(v)
+    } else if value.eq_ignore_ascii_case("true") {
+        AttributeValue::Bool(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        AttributeValue::Bool(false)
+    } else {
+        AttributeValue::String(value.to_string())
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use galileo_types::geo::GeoPoint;
+
+    #[test]
+    fn read_tsv_with_projected_coordinates() {
+        let tsv = "id\tx\ty\tname\tactive\n1\t0\t0\tNull Island\tyes\n2\t1113194.9\t0\t\tno\n";
+        let features = CsvReader::projected("x", "y", Crs::EPSG3857)
+            .tsv()
+            .with_column_type("active", ColumnType::Bool)
+            .read(tsv.as_bytes())
+            .unwrap();
+
+        assert_eq!(features.len(), 2);
+        assert_abs_diff_eq!(features[1].point().lon(), 10.0, epsilon = 0.0001);
+        assert_abs_diff_eq!(features[1].point().lat(), 0.0, epsilon = 0.0001);
+        assert_eq!(
+            features[0].attribute("name"),
+            Some(&AttributeValue::String("Null Island".into()))
+        );
+        assert_eq!(features[1].attribute("name"), Some(&AttributeValue::Null));
+        assert_eq!(features[0].attribute("id"), Some(&AttributeValue::Int(1)));
+        assert_eq!(
+            features[1].attribute("active"),
+            Some(&AttributeValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn read_invalid_rows() {
+        let reader = CsvReader::latlon("lat", "lon").with_column_type("n", ColumnType::Int);
+        assert!(reader.read("lat,lon,n\n10,abc,1\n".as_bytes()).is_err());
+        assert!(reader.read("lat,lon,n\n10,20,1.5\n".as_bytes()).is_err());
+        assert!(reader.read("lat,n\n10,1\n".as_bytes()).is_err());
+        assert_eq!(reader.read("lat,lon,n\n".as_bytes()).unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,100 @@
+//! Thinning of overlapping point features. See [`FeatureLayerOptions::declutter_cell_size`](super::FeatureLayerOptions::declutter_cell_size).
+
+use crate::view::MapView;
+use galileo_types::cartesian::Point3d;
+use std::collections::{HashMap, HashSet};
+
+/// State of the map the point features were decluttered for. The points are decluttered again when it changes.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct DeclutterKey {
+    /// Grid level, if the points are thinned out.
+    pub level: Option<i32>,
+    /// View of the map, if the points can be hidden by [occlusion](super::FeatureLayer::with_occlusion).
+    pub view: Option<MapView>,
+}
+
+/// Level of the declutter grid for the map resolution. The grid changes only when the resolution changes by a factor
+/// of 2, so points do not blink in and out while the map is zoomed smoothly.
+pub(super) fn grid_level(resolution: f64) -> i32 {
+    resolution.log2().round() as i32
+}
+
+/// Size of a grid cell in map units for the cell size in pixels at the given grid level.
+pub(super) fn cell_size(cell_pixels: f64, level: i32) -> f64 {
+    cell_pixels * 2f64.powi(level)
+}
+
+/// Returns indices of the points that must not be drawn, given `(index, position, priority)` of every point.
+///
+/// Only the point with the highest priority is kept in every cell of the grid. Of the points with the same
+/// priority, the one with the smallest index is kept. The grid is aligned to the map coordinates rather than to the
+/// screen, so panning the map does not change which points are visible.
+pub(super) fn suppressed_points(
+    points: impl IntoIterator<Item = (usize, Point3d, f64)>,
+    cell_size: f64,
+) -> HashSet<usize> {
+    let mut suppressed = HashSet::new();
+    let mut cells: HashMap<(i64, i64), (usize, f64)> = HashMap::new();
+    for (index, position, priority) in points {
+        let cell = (
+            (position.x / cell_size).floor() as i64,
+            (position.y / cell_size).floor() as i64,
+        );
+        match cells.get_mut(&cell) {
+            Some(kept) => {
+                let wins = priority.total_cmp(&kept.1).then(kept.0.cmp(&index)).is_gt();
+                if wins {
+                    suppressed.insert(kept.0);
+                    *kept = (index, priority);
+                } else {
+                    suppressed.insert(index);
+                }
+            }
+            None => {
+                cells.insert(cell, (index, priority));
+            }
+        }
+    }
+
+    suppressed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_highest_priority_point_in_cell() {
+        let points = vec![
+            (0, Point3d::new(1.0, 1.0, 0.0), 1.0),
+            (1, Point3d::new(2.0, 2.0, 0.0), 5.0),
+            (2, Point3d::new(3.0, 3.0, 0.0), 2.0),
+            (3, Point3d::new(15.0, 1.0, 0.0), 0.0),
+        ];
+
+        let suppressed = suppressed_points(points, 10.0);
+        assert_eq!(suppressed, HashSet::from([0, 2]));
+    }
+
+    #[test]
+    fn ties_are_resolved_by_index() {
+        let points = vec![
+            (4, Point3d::new(1.0, 1.0, 0.0), 1.0),
+            (2, Point3d::new(2.0, 2.0, 0.0), 1.0),
+            (7, Point3d::new(-1.0, -1.0, 0.0), 1.0),
+        ];
+
+        let suppressed = suppressed_points(points, 10.0);
+        assert_eq!(suppressed, HashSet::from([4]));
+    }
+
+    #[test]
+    fn grid_changes_by_zoom_levels() {
+        assert_eq!(grid_level(1.0), 0);
+        assert_eq!(grid_level(1.3), 0);
+        assert_eq!(grid_level(1.6), 1);
+        assert_eq!(grid_level(0.25), -2);
+        assert_eq!(cell_size(32.0, 2), 128.0);
+        assert_eq!(cell_size(32.0, -1), 16.0);
+    }
+}
@@ -0,0 +1,188 @@
+//! Densification of geographic geometries before projection, so that lines follow great circles or rhumb lines.
+
+use galileo_types::cartesian::Point3d;
+use galileo_types::geo::impls::projection::IdentityProjection;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint, PathType};
+use galileo_types::geometry::{Geom, Geometry};
+use galileo_types::geometry_type::GeoSpace2d;
+use galileo_types::impls::{
+    ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon,
+};
+use galileo_types::{Contour as _, MultiContour as _, MultiPoint as _, MultiPolygon as _};
+
+/// Function that projects the geometry of a feature with the given point projection, inserting intermediate points
+/// into its lines.
+pub(super) type DensifyFn<G, P> =
+    Box<dyn Fn(&G, &dyn Fn(&P) -> Option<Point3d>) -> Option<Geom<Point3d>> + Send + Sync>;
+
+/// Creates a [`DensifyFn`] for geometries with points of type `P`.
+pub(super) fn densify_fn<G, P>(path: PathType, max_segment_length: f64) -> DensifyFn<G, P>
+where
+    G: Geometry<Point = P> + 'static,
+    P: NewGeoPoint + 'static,
+{
+    Box::new(
+        move |geometry: &G, project: &dyn Fn(&P) -> Option<Point3d>| {
+            let geom = geometry.project(&IdentityProjection::<P, GeoPoint2d, GeoSpace2d>::new())?;
+            densify_geom(&geom, path, max_segment_length, |point| {
+                project(&P::latlon(point.lat(), point.lon()))
+            })
+        },
+    )
+}
+
+/// Projects the geometry with the `project` function, first adding points to every line and polygon ring, so that no
+/// segment is longer than `max_segment_length` meters along the path of the given type. Points are not changed.
+pub(super) fn densify_geom(
+    geom: &Geom<GeoPoint2d>,
+    path: PathType,
+    max_segment_length: f64,
+    project: impl Fn(&GeoPoint2d) -> Option<Point3d>,
+) -> Option<Geom<Point3d>> {
+    let densify = Densify {
+        path,
+        max_segment_length,
+        project,
+    };
+
+    Some(match geom {
+        Geom::Point(point) => Geom::Point((densify.project)(point)?),
+        Geom::MultiPoint(points) => Geom::MultiPoint(MultiPoint::from(
+            points
+                .iter_points()
+                .map(&densify.project)
+                .collect::<Option<Vec<_>>>()?,
+        )),
+        Geom::Contour(contour) => Geom::Contour(densify.contour(contour)?),
+        Geom::MultiContour(contours) => Geom::MultiContour(MultiContour::from(
+            contours
+                .contours()
+                .map(|c| densify.contour(c))
+                .collect::<Option<Vec<_>>>()?,
+        )),
+        Geom::Polygon(polygon) => Geom::Polygon(densify.polygon(polygon)?),
+        Geom::MultiPolygon(polygons) => Geom::MultiPolygon(MultiPolygon::from(
+            polygons
+                .polygons()
+                .map(|p| densify.polygon(p))
+                .collect::<Option<Vec<_>>>()?,
+        )),
+    })
+}
+
+struct Densify<F> {
+    path: PathType,
+    max_segment_length: f64,
+    project: F,
+}
+
+impl<F: Fn(&GeoPoint2d) -> Option<Point3d>> Densify<F> {
+    fn contour(&self, contour: &Contour<GeoPoint2d>) -> Option<Contour<Point3d>> {
+        let points: Vec<GeoPoint2d> = contour.iter_points().copied().collect();
+        if contour.is_closed() {
+            Some(Contour::closed(self.ring(&points)?))
+        } else {
+            Some(Contour::open(self.line(points)?))
+        }
+    }
+
+    fn polygon(&self, polygon: &Polygon<GeoPoint2d>) -> Option<Polygon<Point3d>> {
+        let outer_contour = ClosedContour::new(self.ring(&polygon.outer_contour.points)?);
+        let inner_contours = polygon
+            .inner_contours
+            .iter()
+            .map(|c| self.ring(&c.points).map(ClosedContour::new))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Polygon::new(outer_contour, inner_contours))
+    }
+
+    /// Densifies a closed ring given without the closing point, including the closing segment.
+    fn ring(&self, points: &[GeoPoint2d]) -> Option<Vec<Point3d>> {
+        let Some(first) = points.first() else {
+            return Some(vec![]);
+        };
+
+        let mut ring = self.line(points.iter().chain(std::iter::once(first)).copied())?;
+        ring.pop();
+        Some(ring)
+    }
+
+    fn line(&self, points: impl IntoIterator<Item = GeoPoint2d>) -> Option<Vec<Point3d>> {
+        self.path
+            .densify(points, self.max_segment_length)
+            .iter()
+            .map(&self.project)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::CartesianPoint3d;
+    use galileo_types::geo::LengthUnit;
+
+    fn plate_carree(point: &GeoPoint2d) -> Option<Point3d> {
+        Some(Point3d::new(point.lon(), point.lat(), 0.0))
+    }
+
+    #[test]
+    fn densifies_lines_and_rings() {
+        let line = Contour::open(vec![
+            GeoPoint2d::latlon(0.0, 0.0),
+            GeoPoint2d::latlon(0.0, 10.0),
+        ]);
+        let max_length = LengthUnit::NauticalMiles.to_meters(60.0);
+
+        let Some(Geom::Contour(densified)) = densify_geom(
+            &Geom::Contour(line),
+            PathType::Rhumb,
+            max_length,
+            plate_carree,
+        ) else {
+            panic!("contour expected");
+        };
+        assert_eq!(densified.iter_points().count(), 12);
+
+        let ring = Polygon::new(
+            ClosedContour::new(vec![
+                GeoPoint2d::latlon(0.0, 0.0),
+                GeoPoint2d::latlon(0.0, 10.0),
+                GeoPoint2d::latlon(10.0, 10.0),
+            ]),
+            vec![],
+        );
+        let Some(Geom::Polygon(densified)) = densify_geom(
+            &Geom::Polygon(ring),
+            PathType::Rhumb,
+            max_length,
+            plate_carree,
+        ) else {
+            panic!("polygon expected");
+        };
+        let points = &densified.outer_contour.points;
+        assert!(points.len() > 30);
+        assert_ne!(points.first(), points.last());
+    }
+
+    #[test]
+    fn great_circle_bends_towards_pole() {
+        let line = Contour::open(vec![
+            GeoPoint2d::latlon(50.0, -60.0),
+            GeoPoint2d::latlon(50.0, 0.0),
+        ]);
+        let Some(Geom::Contour(densified)) = densify_geom(
+            &Geom::Contour(line),
+            PathType::GreatCircle,
+            100_000.0,
+            plate_carree,
+        ) else {
+            panic!("contour expected");
+        };
+
+        assert!(densified.iter_points().all(|p| p.y() >= 50.0 - 1e-9));
+        assert!(densified.iter_points().any(|p| p.y() > 53.5));
+    }
+}
@@ -0,0 +1,337 @@
+use crate::layer::feature_layer::FeatureStore;
+
+/// Undo/redo log of the changes made to a [`FeatureStore`].
+///
+/// Features are created, modified and deleted through the methods of the history instead of the store itself, so
+/// every change is recorded and can be reverted with [`EditHistory::undo`] and applied again with
+/// [`EditHistory::redo`]. Each call records a separate transaction, unless it is made between
+/// [`EditHistory::begin_group`] and [`EditHistory::end_group`] calls: then all the changes of the group are undone
+/// and redone together.
+///
+/// Changes are recorded with the indices of the features in the store, so the store must not be modified bypassing
+/// the history while it has any transactions. If it is, call [`EditHistory::clear`].
+///
+/// ```
+/// use galileo::layer::feature_layer::{EditHistory, FeatureStore};
+///
+/// let mut store = FeatureStore::new(["a".to_string()].into_iter());
+/// let mut history = EditHistory::new();
+///
+/// history.begin_group("Rename and add");
+/// history.update(&mut store, 0, |f| *f = "b".to_string());
+/// history.insert(&mut store, "c".to_string());
+/// history.end_group();
+/// assert_eq!(store.len(), 2);
+///
+/// history.undo(&mut store);
+/// assert_eq!(store.get(0).map(String::as_str), Some("a"));
+/// assert_eq!(store.len(), 1);
+///
+/// history.redo(&mut store);
+/// assert_eq!(store.get(0).map(String::as_str), Some("b"));
+/// ```
+#[derive(Debug)]
+pub struct EditHistory<F> {
+    undo_stack: Vec<Transaction<F>>,
+    redo_stack: Vec<Transaction<F>>,
+    group: Option<Transaction<F>>,
+    group_depth: usize,
+    limit: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Transaction<F> {
+    label: Option<String>,
+    changes: Vec<Change<F>>,
+}
+
+#[derive(Debug)]
+enum Change<F> {
+    Insert { index: usize, feature: F },
+    Update { index: usize, before: F, after: F },
+    Remove { index: usize, feature: F },
+}
+
+impl<F> Default for EditHistory<F> {
+    fn default() -> Self {
+        Self {
+            undo_stack: vec![],
+            redo_stack: vec![],
+            group: None,
+            group_depth: 0,
+            limit: None,
+        }
+    }
+}
+
+impl<F: Clone> EditHistory<F> {
+    /// Creates a new empty history without a limit on the number of transactions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of transactions that can be undone. The oldest transactions are dropped when the limit
+    /// is exceeded.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self.trim();
+        self
+    }
+
+    /// Adds the feature at the end of the store. Returns the index of the new feature.
+    pub fn insert(&mut self, store: &mut FeatureStore<F>, feature: F) -> usize {
+        let index = store.len();
+        self.insert_at(store, index, feature);
+        index
+    }
+
+    /// Inserts the feature at the given position in the store.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of features in the store.
+    pub fn insert_at(&mut self, store: &mut FeatureStore<F>, index: usize, feature: F) {
+        store.insert_at(index, feature.clone());
+        self.record(Change::Insert { index, feature });
+    }
+
+    /// Modifies the feature with the given index with the `edit` function. Returns false if there is no such feature.
+    pub fn update(
+        &mut self,
+        store: &mut FeatureStore<F>,
+        index: usize,
+        edit: impl FnOnce(&mut F),
+    ) -> bool {
+        let Some(mut container) = store.get_mut(index) else {
+            return false;
+        };
+
+        let before = container.as_ref().clone();
+        edit(container.as_mut());
+        let after = container.as_ref().clone();
+        self.record(Change::Update {
+            index,
+            before,
+            after,
+        });
+
+        true
+    }
+
+    /// Removes the feature with the given index from the store. Returns `None` if there is no such feature.
+    pub fn remove(&mut self, store: &mut FeatureStore<F>, index: usize) -> Option<F> {
+        if index >= store.len() {
+            return None;
+        }
+
+        let feature = store.remove(index);
+        self.record(Change::Remove {
+            index,
+            feature: feature.clone(),
+        });
+
+        Some(feature)
+    }
+
+    /// Starts a group of changes that are undone and redone as a single transaction. Groups can be nested, in which
+    /// case the changes are collected into the outermost group.
+    pub fn begin_group(&mut self, label: impl Into<String>) {
+        self.group_depth += 1;
+        if self.group.is_none() {
+            self.group = Some(Transaction {
+                label: Some(label.into()),
+                changes: vec![],
+            });
+        }
+    }
+
+    /// Finishes the group started with [`EditHistory::begin_group`]. Empty groups are not recorded.
+    pub fn end_group(&mut self) {
+        if self.group_depth == 0 {
+            log::warn!("EditHistory::end_group called without a matching begin_group");
+            return;
+        }
+
+        self.group_depth -= 1;
+        if self.group_depth == 0 {
+            if let Some(group) = self.group.take() {
+                self.push(group);
+            }
+        }
+    }
+
+    /// Reverts the last transaction. Returns false if there is nothing to undo.
+    ///
+    /// An unfinished group is finished before undoing.
+    pub fn undo(&mut self, store: &mut FeatureStore<F>) -> bool {
+        self.close_group();
+        let Some(transaction) = self.undo_stack.pop() else {
+            return false;
+        };
+
+        for change in transaction.changes.iter().rev() {
+            match change {
+                Change::Insert { index, .. } => {
+                    store.remove(*index);
+                }
+                Change::Update { index, before, .. } => set(store, *index, before),
+                Change::Remove { index, feature } => store.insert_at(*index, feature.clone()),
+            }
+        }
+
+        self.redo_stack.push(transaction);
+        true
+    }
+
+    /// Applies again the last undone transaction. Returns false if there is nothing to redo.
+    pub fn redo(&mut self, store: &mut FeatureStore<F>) -> bool {
+        self.close_group();
+        let Some(transaction) = self.redo_stack.pop() else {
+            return false;
+        };
+
+        for change in &transaction.changes {
+            match change {
+                Change::Insert { index, feature } => store.insert_at(*index, feature.clone()),
+                Change::Update { index, after, .. } => set(store, *index, after),
+                Change::Remove { index, .. } => {
+                    store.remove(*index);
+                }
+            }
+        }
+
+        self.undo_stack.push(transaction);
+        true
+    }
+}
+
+impl<F> EditHistory<F> {
+    /// Returns true if there is a transaction to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty() || self.group.as_ref().is_some_and(|g| !g.changes.is_empty())
+    }
+
+    /// Returns true if there is a transaction to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Label of the group that will be reverted by the next [`EditHistory::undo`] call, e.g. to show it in the
+    /// "Undo ..." menu item. Single changes made outside of groups have no label.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo_stack.last()?.label.as_deref()
+    }
+
+    /// Label of the group that will be applied by the next [`EditHistory::redo`] call.
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo_stack.last()?.label.as_deref()
+    }
+
+    /// Removes all the transactions from the history.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.group = None;
+        self.group_depth = 0;
+    }
+
+    fn record(&mut self, change: Change<F>) {
+        match &mut self.group {
+            Some(group) => group.changes.push(change),
+            None => self.push(Transaction {
+                label: None,
+                changes: vec![change],
+            }),
+        }
+    }
+
+    fn push(&mut self, transaction: Transaction<F>) {
+        if transaction.changes.is_empty() {
+            return;
+        }
+
+        self.redo_stack.clear();
+        self.undo_stack.push(transaction);
+        self.trim();
+    }
+
+    fn close_group(&mut self) {
+        self.group_depth = 0;
+        if let Some(group) = self.group.take() {
+            self.push(group);
+        }
+    }
+
+    fn trim(&mut self) {
+        if let Some(limit) = self.limit {
+            let excess = self.undo_stack.len().saturating_sub(limit);
+            self.undo_stack.drain(..excess);
+        }
+    }
+}
+
+fn set<F: Clone>(store: &mut FeatureStore<F>, index: usize, value: &F) {
+    if let Some(mut container) = store.get_mut(index) {
+        *container.as_mut() = value.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(store: &FeatureStore<&'static str>) -> Vec<&'static str> {
+        store.iter().map(|f| *f.as_ref()).collect()
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut store = FeatureStore::new(["a", "b", "c"].into_iter());
+        let mut history = EditHistory::new();
+
+        history.remove(&mut store, 1);
+        history.update(&mut store, 0, |f| *f = "A");
+        history.insert(&mut store, "d");
+        assert_eq!(contents(&store), ["A", "c", "d"]);
+
+        assert!(history.undo(&mut store));
+        assert!(history.undo(&mut store));
+        assert_eq!(contents(&store), ["a", "c"]);
+        assert!(history.undo(&mut store));
+        assert_eq!(contents(&store), ["a", "b", "c"]);
+        assert!(!history.undo(&mut store));
+
+        assert!(history.redo(&mut store));
+        assert_eq!(contents(&store), ["a", "c"]);
+
+        // A new change discards the undone transactions.
+        history.insert_at(&mut store, 0, "z");
+        assert!(!history.can_redo());
+        assert_eq!(contents(&store), ["z", "a", "c"]);
+    }
+
+    #[test]
+    fn grouped_changes() {
+        let mut store = FeatureStore::new(["a", "b"].into_iter());
+        let mut history = EditHistory::new().with_limit(2);
+
+        history.begin_group("Merge");
+        history.remove(&mut store, 1);
+        history.begin_group("Nested");
+        history.update(&mut store, 0, |f| *f = "ab");
+        history.end_group();
+        history.end_group();
+        assert_eq!(history.undo_label(), Some("Merge"));
+
+        history.insert(&mut store, "c");
+        history.insert(&mut store, "d");
+        assert_eq!(contents(&store), ["ab", "c", "d"]);
+
+        // The group is dropped due to the limit.
+        assert!(history.undo(&mut store));
+        assert!(history.undo(&mut store));
+        assert!(!history.undo(&mut store));
+        assert_eq!(contents(&store), ["ab"]);
+        assert_eq!(history.redo_label(), None);
+    }
+}
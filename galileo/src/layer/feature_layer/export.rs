@@ -0,0 +1,252 @@
+//! Conversion of features into GeoJSON and WKT.
+
+use crate::error::GalileoError;
+use crate::layer::feature_layer::{AttributeValue, Feature, FeatureId};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, Projection};
+use galileo_types::geometry::{Geom, Geometry};
+use galileo_types::impls;
+use galileo_types::{Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use serde_json::{json, Map, Value};
+use std::fmt::Write;
+
+/// Converts the geometry of the feature into geographic coordinates.
+pub(crate) fn feature_geometry<F, P>(
+    feature: &F,
+    projection: &(impl Projection<InPoint = P, OutPoint = GeoPoint2d> + ?Sized),
+) -> Result<Geom<GeoPoint2d>, GalileoError>
+where
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    feature.geometry().project(projection).ok_or_else(|| {
+        GalileoError::Generic("feature geometry cannot be converted to WGS84".to_string())
+    })
+}
+
+/// GeoJSON `Feature` object with the geometry and all the attributes of the feature.
+pub(crate) fn geojson_feature<F: Feature>(feature: &F, geometry: &Geom<GeoPoint2d>) -> Value {
+    let properties: Map<String, Value> = feature
+        .attributes()
+        .into_iter()
+        .map(|(name, value)| (name, json_value(value)))
+        .collect();
+
+    let mut object = Map::new();
+    object.insert("type".into(), "Feature".into());
+    if let Some(id) = feature.id() {
+        object.insert(
+            "id".into(),
+            match id {
+                FeatureId::Int(id) => id.into(),
+                FeatureId::String(id) => id.into(),
+            },
+        );
+    }
+    object.insert("geometry".into(), geojson_geometry(geometry));
+    object.insert("properties".into(), properties.into());
+
+    object.into()
+}
+
+/// GeoJSON `FeatureCollection` object with the given features.
+pub(crate) fn geojson_feature_collection(features: Vec<Value>) -> Value {
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+fn json_value(value: AttributeValue) -> Value {
+    match value {
+        AttributeValue::Null => Value::Null,
+        AttributeValue::Bool(v) => v.into(),
+        AttributeValue::Int(v) => v.into(),
+        AttributeValue::Float(v) => v.into(),
+        AttributeValue::String(v) => v.into(),
+    }
+}
+
+fn geojson_geometry(geometry: &Geom<GeoPoint2d>) -> Value {
+    let (geometry_type, coordinates) = match geometry {
+        Geom::Point(point) => ("Point", position(point)),
+        Geom::MultiPoint(points) => ("MultiPoint", points.iter_points().map(position).collect()),
+        Geom::Contour(contour) => ("LineString", line(contour)),
+        Geom::MultiContour(contours) => {
+            ("MultiLineString", contours.contours().map(line).collect())
+        }
+        Geom::Polygon(polygon) => ("Polygon", rings(polygon)),
+        Geom::MultiPolygon(polygons) => ("MultiPolygon", polygons.polygons().map(rings).collect()),
+    };
+
+    json!({
+        "type": geometry_type,
+        "coordinates": coordinates,
+    })
+}
+
+fn position(point: &GeoPoint2d) -> Value {
+    json!([point.lon(), point.lat()])
+}
+
+fn line(contour: &impl Contour<Point = GeoPoint2d>) -> Value {
+    contour.iter_points_closing().map(position).collect()
+}
+
+fn rings(polygon: &impls::Polygon<GeoPoint2d>) -> Value {
+    polygon.iter_contours().map(line).collect()
+}
+
+/// Well-known text representation of the geometry.
+pub(crate) fn wkt(geometry: &Geom<GeoPoint2d>) -> String {
+    let mut out = String::new();
+    match geometry {
+        Geom::Point(point) => {
+            out.push_str("POINT (");
+            write_position(&mut out, point);
+            out.push(')');
+        }
+        Geom::MultiPoint(points) => {
+            out.push_str("MULTIPOINT ");
+            write_list(&mut out, points.iter_points(), |out, point| {
+                out.push('(');
+                write_position(out, point);
+                out.push(')');
+            });
+        }
+        Geom::Contour(contour) => {
+            out.push_str("LINESTRING ");
+            write_line(&mut out, contour);
+        }
+        Geom::MultiContour(contours) => {
+            out.push_str("MULTILINESTRING ");
+            write_list(&mut out, contours.contours(), |out, contour| {
+                write_line(out, contour)
+            });
+        }
+        Geom::Polygon(polygon) => {
+            out.push_str("POLYGON ");
+            write_rings(&mut out, polygon);
+        }
+        Geom::MultiPolygon(polygons) => {
+            out.push_str("MULTIPOLYGON ");
+            write_list(&mut out, polygons.polygons(), write_rings);
+        }
+    }
+
+    out
+}
+
+fn write_position(out: &mut String, point: &GeoPoint2d) {
+    let _ = write!(out, "{} {}", point.lon(), point.lat());
+}
+
+fn write_line(out: &mut String, contour: &impl Contour<Point = GeoPoint2d>) {
+    write_list(out, contour.iter_points_closing(), write_position);
+}
+
+fn write_rings(out: &mut String, polygon: &impls::Polygon<GeoPoint2d>) {
+    write_list(out, polygon.iter_contours(), |out, contour| {
+        write_line(out, contour)
+    });
+}
+
+/// Writes the items in parentheses separated by commas, or `EMPTY` if there are no items.
+fn write_list<T>(
+    out: &mut String,
+    items: impl Iterator<Item = T>,
+    mut write_item: impl FnMut(&mut String, T),
+) {
+    let start = out.len();
+    out.push('(');
+    for (index, item) in items.enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        write_item(out, item);
+    }
+
+    if out.len() == start + 1 {
+        out.truncate(start);
+        out.push_str("EMPTY");
+    } else {
+        out.push(')');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::geo::impls::projection::IdentityProjection;
+    use galileo_types::geo::NewGeoPoint;
+    use galileo_types::geometry_type::GeoSpace2d;
+
+    struct Marker {
+        position: GeoPoint2d,
+    }
+
+    impl Feature for Marker {
+        type Geom = GeoPoint2d;
+
+        fn geometry(&self) -> &Self::Geom {
+            &self.position
+        }
+
+        fn id(&self) -> Option<FeatureId> {
+            Some(FeatureId::Int(7))
+        }
+
+        fn attributes(&self) -> Vec<(String, AttributeValue)> {
+            vec![
+                ("name".into(), "Well".into()),
+                ("depth".into(), AttributeValue::Float(12.5)),
+                ("checked".into(), AttributeValue::Null),
+            ]
+        }
+    }
+
+    #[test]
+    fn feature_to_geojson() {
+        let marker = Marker {
+            position: GeoPoint2d::latlon(50.0, 10.0),
+        };
+        let projection = IdentityProjection::<GeoPoint2d, GeoPoint2d, GeoSpace2d>::new();
+        let geometry = feature_geometry(&marker, &projection).unwrap();
+
+        assert_eq!(
+            geojson_feature(&marker, &geometry),
+            json!({
+                "type": "Feature",
+                "id": 7,
+                "geometry": { "type": "Point", "coordinates": [10.0, 50.0] },
+                "properties": { "name": "Well", "depth": 12.5, "checked": null },
+            })
+        );
+    }
+
+    #[test]
+    fn polygon_to_wkt() {
+        let ring = |points: &[(f64, f64)]| {
+            impls::ClosedContour::new(
+                points
+                    .iter()
+                    .map(|&(lon, lat)| GeoPoint2d::latlon(lat, lon))
+                    .collect(),
+            )
+        };
+        let polygon = impls::Polygon::new(
+            ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)]),
+            vec![ring(&[(1.0, 1.0), (2.0, 1.0), (2.0, 2.0)])],
+        );
+
+        assert_eq!(
+            wkt(&Geom::Polygon(polygon)),
+            "POLYGON ((0 0, 4 0, 4 4, 0 0), (1 1, 2 1, 2 2, 1 1))"
+        );
+        assert_eq!(wkt(&Geom::MultiPoint(vec![].into())), "MULTIPOINT EMPTY");
+        assert_eq!(
+            wkt(&Geom::Point(GeoPoint2d::latlon(1.5, -2.0))),
+            "POINT (-2 1.5)"
+        );
+    }
+}
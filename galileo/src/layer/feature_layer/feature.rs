@@ -1,16 +1,154 @@
+use crate::layer::feature_layer::AttributeValue;
 use galileo_types::cartesian::{Point2d, Point3d};
 use galileo_types::geo::impls::GeoPoint2d;
 use galileo_types::geometry::Geometry;
 use galileo_types::geometry_type::GeometryType;
 use galileo_types::impls::{Contour, MultiContour, MultiPolygon, Polygon};
 use galileo_types::Disambig;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 
 /// A feature is an arbitrary geographic object.
+///
+/// The trait is meant to be implemented by the domain types of the application, so that they can be rendered by
+/// a [`FeatureLayer`](super::FeatureLayer) directly without being copied into other structures. Only the geometry is
+/// required. The id and the attributes are optional: they are used by the parts of the crate that work with any
+/// feature type, e.g. to match features with [`FeatureLayer::set_features`](super::FeatureLayer::set_features).
+///
+/// ```
+/// use galileo::layer::feature_layer::{AttributeValue, Feature, FeatureId};
+/// use galileo_types::cartesian::Point2d;
+///
+/// struct Vehicle {
+///     number: i64,
+///     position: Point2d,
+///     speed: f64,
+/// }
+///
+/// impl Feature for Vehicle {
+///     type Geom = Point2d;
+///
+///     fn geometry(&self) -> &Self::Geom {
+///         &self.position
+///     }
+///
+///     fn id(&self) -> Option<FeatureId> {
+///         Some(self.number.into())
+///     }
+///
+///     fn attribute(&self, name: &str) -> Option<AttributeValue> {
+///         match name {
+///             "speed" => Some(self.speed.into()),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
 pub trait Feature {
     /// Type of the geometry the feature returns.
     type Geom: Geometry;
     /// Returns the geometry of the feature.
     fn geometry(&self) -> &Self::Geom;
+
+    /// Identifier of the feature, unique in its data set. Default implementation returns `None`.
+    fn id(&self) -> Option<FeatureId> {
+        None
+    }
+
+    /// Value of the attribute with the given name. Returns `None` if the feature has no such attribute, which is
+    /// the case for all names in the default implementation.
+    fn attribute(&self, _name: &str) -> Option<AttributeValue> {
+        None
+    }
+
+    /// Names and values of all the attributes of the feature, e.g. to [export](super::FeatureLayer::to_geojson) the
+    /// feature. Default implementation returns an empty list.
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        Vec::new()
+    }
+}
+
+/// Identifier of a [`Feature`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FeatureId {
+    /// Numeric id.
+    Int(i64),
+    /// String id.
+    String(String),
+}
+
+impl Display for FeatureId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int(v) => write!(f, "{v}"),
+            Self::String(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl From<i64> for FeatureId {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<String> for FeatureId {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for FeatureId {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+macro_rules! impl_feature_for_pointer {
+    ($pointer:ident) => {
+        impl<F: Feature + ?Sized> Feature for $pointer<F> {
+            type Geom = F::Geom;
+
+            fn geometry(&self) -> &Self::Geom {
+                (**self).geometry()
+            }
+
+            fn id(&self) -> Option<FeatureId> {
+                (**self).id()
+            }
+
+            fn attribute(&self, name: &str) -> Option<AttributeValue> {
+                (**self).attribute(name)
+            }
+
+            fn attributes(&self) -> Vec<(String, AttributeValue)> {
+                (**self).attributes()
+            }
+        }
+    };
+}
+
+impl_feature_for_pointer!(Box);
+impl_feature_for_pointer!(Arc);
+
+impl<F: Feature + ?Sized> Feature for &F {
+    type Geom = F::Geom;
+
+    fn geometry(&self) -> &Self::Geom {
+        (**self).geometry()
+    }
+
+    fn id(&self) -> Option<FeatureId> {
+        (**self).id()
+    }
+
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        (**self).attribute(name)
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        (**self).attributes()
+    }
 }
 
 macro_rules! impl_feature {
@@ -54,3 +192,54 @@ where
 
 #[cfg(feature = "geojson")]
 mod geojson;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Station {
+        code: &'static str,
+        position: Point2d,
+        temperature: f64,
+    }
+
+    impl Feature for Station {
+        type Geom = Point2d;
+
+        fn geometry(&self) -> &Self::Geom {
+            &self.position
+        }
+
+        fn id(&self) -> Option<FeatureId> {
+            Some(self.code.into())
+        }
+
+        fn attribute(&self, name: &str) -> Option<AttributeValue> {
+            (name == "temperature").then_some(self.temperature.into())
+        }
+    }
+
+    fn id_and_temperature(feature: &impl Feature) -> (Option<FeatureId>, Option<f64>) {
+        (
+            feature.id(),
+            feature.attribute("temperature").and_then(|v| v.as_f64()),
+        )
+    }
+
+    #[test]
+    fn shared_features_keep_accessors() {
+        let station = Arc::new(Station {
+            code: "ABC",
+            position: Point2d::new(1.0, 2.0),
+            temperature: 12.5,
+        });
+
+        let expected = (Some(FeatureId::String("ABC".into())), Some(12.5));
+        assert_eq!(id_and_temperature(&station), expected);
+        assert_eq!(id_and_temperature(&&*station), expected);
+        assert_eq!(*station.clone().geometry(), Point2d::new(1.0, 2.0));
+
+        let point = Point2d::new(0.0, 0.0);
+        assert_eq!(id_and_temperature(&point), (None, None));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::layer::feature_layer::feature::Feature;
+use crate::layer::feature_layer::feature::{Feature, FeatureId};
+use crate::layer::feature_layer::AttributeValue;
+use geojson::feature::Id;
+use serde_json::Value;
 
 impl Feature for geojson::Feature {
     type Geom = geojson::Geometry;
@@ -7,4 +10,40 @@ impl Feature for geojson::Feature {
         let res = self.geometry.as_ref().unwrap();
         &res
     }
+
+    fn id(&self) -> Option<FeatureId> {
+        match self.id.as_ref()? {
+            Id::String(id) => Some(id.as_str().into()),
+            Id::Number(id) => Some(match id.as_i64() {
+                Some(id) => id.into(),
+                None => id.to_string().into(),
+            }),
+        }
+    }
+
+    /// Nested arrays and objects are returned as JSON strings.
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        json_attribute(self.property(name)?)
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        self.properties_iter()
+            .filter_map(|(name, value)| Some((name.clone(), json_attribute(value)?)))
+            .collect()
+    }
+}
+
+fn json_attribute(value: &Value) -> Option<AttributeValue> {
+    let value = match value {
+        Value::Null => AttributeValue::Null,
+        Value::Bool(v) => AttributeValue::Bool(*v),
+        Value::Number(v) => match v.as_i64() {
+            Some(v) => AttributeValue::Int(v),
+            None => AttributeValue::Float(v.as_f64()?),
+        },
+        Value::String(v) => AttributeValue::String(v.clone()),
+        value @ (Value::Array(_) | Value::Object(_)) => AttributeValue::String(value.to_string()),
+    };
+
+    Some(value)
 }
@@ -0,0 +1,132 @@
+use crate::layer::feature_layer::FeatureStore;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Changes made to the features of a layer when it is updated from a data source.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct FeatureDiff {
+    /// Number of the features added to the layer.
+    pub added: usize,
+    /// Number of the features that were changed.
+    pub updated: usize,
+    /// Number of the features removed from the layer.
+    pub removed: usize,
+}
+
+impl FeatureDiff {
+    /// Returns true if the layer was not changed.
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.updated == 0 && self.removed == 0
+    }
+}
+
+/// Updates the features in the store to match the given list, matching the features by the `key`.
+///
+/// Features without a key cannot be matched, so they are always replaced.
+pub(crate) fn apply_features<F: PartialEq, K: Hash + Eq>(
+    store: &mut FeatureStore<F>,
+    features: impl IntoIterator<Item = F>,
+    key: impl Fn(&F) -> Option<K>,
+) -> FeatureDiff {
+    let mut existing = HashMap::new();
+    let mut to_remove = vec![];
+    for feature in store.iter() {
+        match key(feature.as_ref()) {
+            Some(key) => {
+                if let Some(duplicate) = existing.insert(key, feature.index()) {
+                    to_remove.push(duplicate);
+                }
+            }
+            None => to_remove.push(feature.index()),
+        }
+    }
+
+    let mut diff = FeatureDiff::default();
+    let mut matched = HashSet::new();
+    let mut to_add = vec![];
+    for feature in features {
+        let index = key(&feature).and_then(|key| existing.get(&key).copied());
+        match index {
+            Some(index) if matched.insert(index) => {
+                let mut container = store.get_mut(index).expect("feature index is valid");
+                if container.as_ref() != &feature {
+                    *container.as_mut() = feature;
+                    diff.updated += 1;
+                }
+            }
+            _ => to_add.push(feature),
+        }
+    }
+
+    to_remove.extend(
+        existing
+            .into_values()
+            .filter(|index| !matched.contains(index)),
+    );
+    to_remove.sort_unstable();
+    to_remove.dedup();
+
+    // Removing from the end keeps the indices of the rest of the features valid.
+    for index in to_remove.into_iter().rev() {
+        store.remove(index);
+        diff.removed += 1;
+    }
+
+    for feature in to_add {
+        store.insert(feature);
+        diff.added += 1;
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::feature_store::FeatureUpdate;
+
+    fn key(feature: &(u32, &'static str)) -> Option<u32> {
+        Some(feature.0)
+    }
+
+    #[test]
+    fn only_changed_features_are_updated() {
+        let mut store = FeatureStore::new([(1, "a"), (2, "b"), (3, "c")].into_iter());
+        store.drain_updates();
+
+        let diff = apply_features(&mut store, [(3, "c"), (1, "changed"), (4, "d")], key);
+        assert_eq!(
+            diff,
+            FeatureDiff {
+                added: 1,
+                updated: 1,
+                removed: 1,
+            }
+        );
+
+        let features: Vec<_> = store.iter().map(|f| *f.as_ref()).collect();
+        assert_eq!(features, vec![(1, "changed"), (3, "c"), (4, "d")]);
+
+        let updates = store.drain_updates();
+        assert_eq!(updates.len(), 3);
+        assert!(matches!(
+            updates[0],
+            FeatureUpdate::Update { feature_index: 0 }
+        ));
+        assert!(matches!(
+            updates[1],
+            FeatureUpdate::Delete {
+                removed_index: Some(1),
+                ..
+            }
+        ));
+        assert!(matches!(
+            updates[2],
+            FeatureUpdate::Update { feature_index: 2 }
+        ));
+
+        let diff = apply_features(&mut store, [(1, "changed"), (3, "c"), (4, "d")], key);
+        assert!(diff.is_empty());
+        assert!(store.drain_updates().is_empty());
+    }
+}
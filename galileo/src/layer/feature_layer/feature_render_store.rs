@@ -1,6 +1,7 @@
-use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
-use crate::render::{Canvas, PackedBundle, PrimitiveId};
-use galileo_types::cartesian::Point3d;
+use crate::layer::feature_layer::declutter::DeclutterKey;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive, TessellationError};
+use crate::render::{Canvas, HighlightStyle, PackedBundle, PrimitiveId};
+use galileo_types::cartesian::{Point3d, Rect};
 use galileo_types::impls::{Contour, Polygon};
 use std::collections::{HashMap, HashSet};
 
@@ -9,15 +10,49 @@ pub(super) struct FeatureRenderStore {
     min_resolution: f64,
     render_bundles: Vec<RenderBundle>,
     packed_bundles: Vec<Option<Box<dyn PackedBundle>>>,
+    bundle_orders: Vec<DrawOrder>,
+    /// Indices of the bundles sorted by their draw order.
+    draw_sequence: Vec<usize>,
     feature_render_map: HashMap<usize, RenderMapEntry>,
     buffer_size_limit: usize,
     bundle_indices_to_pack: HashSet<usize>,
     next_index: usize,
+    deferred: Vec<DeferredFeature>,
+    /// Render indices of the features with moving line patterns.
+    animated_renders: HashSet<usize>,
+    /// State of the map the point features were decluttered for, or `None` if they must be decluttered again.
+    declutter_key: Option<DeclutterKey>,
+    /// Indices of the point features that are not rendered because they are decluttered or occluded.
+    suppressed: HashSet<usize>,
+}
+
+/// Feature that was not rendered because it was outside of the view.
+struct DeferredFeature {
+    feature_index: usize,
+    bbox: Rect,
 }
 
 struct RenderMapEntry {
-    bundle_index: usize,
-    primitive_ids: Vec<PrimitiveId>,
+    /// Bundle index and id in the bundle for every primitive of the feature.
+    primitive_ids: Vec<(usize, PrimitiveId)>,
+}
+
+/// Stage of rendering a primitive belongs to when the layer is rendered by stages.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum RenderStage {
+    /// All primitives of a feature are drawn together.
+    Any,
+    Fill,
+    Stroke,
+    Point,
+}
+
+/// Position of a bundle in the draw order of the layer. Bundles are drawn by stage first, and then by sort key of the
+/// features.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DrawOrder {
+    stage: RenderStage,
+    sort_key: i32,
 }
 
 impl FeatureRenderStore {
@@ -28,9 +63,15 @@ impl FeatureRenderStore {
             buffer_size_limit,
             render_bundles: vec![],
             packed_bundles: vec![],
+            bundle_orders: vec![],
+            draw_sequence: vec![],
             feature_render_map: HashMap::new(),
             bundle_indices_to_pack: HashSet::new(),
             next_index: 0,
+            deferred: vec![],
+            animated_renders: HashSet::new(),
+            declutter_key: None,
+            suppressed: HashSet::new(),
         }
     }
 
@@ -46,32 +87,42 @@ impl FeatureRenderStore {
         self.buffer_size_limit = limit;
     }
 
-    pub fn init_bundle(&mut self, f: impl Fn() -> RenderBundle) {
-        if !self.has_not_full_bundles() {
-            self.render_bundles.push(f());
-            self.packed_bundles.push(None);
+    /// Returns index of a not full bundle with the given draw order, creating a new bundle if there is none.
+    fn bundle_for(&mut self, order: DrawOrder, canvas: &dyn Canvas) -> usize {
+        let existing = (0..self.render_bundles.len()).rev().find(|&index| {
+            self.bundle_orders[index] == order
+                && self.render_bundles[index].approx_buffer_size() < self.buffer_size_limit
+        });
+        if let Some(index) = existing {
+            return index;
         }
-    }
 
-    fn has_not_full_bundles(&self) -> bool {
-        self.render_bundles
-            .iter()
-            .any(|bundle| bundle.approx_buffer_size() < self.buffer_size_limit)
+        let index = self.render_bundles.len();
+        self.render_bundles.push(canvas.create_bundle());
+        self.packed_bundles.push(None);
+        self.bundle_orders.push(order);
+
+        let position = self
+            .draw_sequence
+            .partition_point(|&other| self.bundle_orders[other] <= order);
+        self.draw_sequence.insert(position, index);
+
+        index
     }
 
     pub fn remove_render(&mut self, render_index: usize) {
-        if let Some(RenderMapEntry {
-            bundle_index,
-            primitive_ids,
-        }) = self.feature_render_map.remove(&render_index)
+        if let Some(RenderMapEntry { primitive_ids }) =
+            self.feature_render_map.remove(&render_index)
         {
-            for id in primitive_ids {
+            self.animated_renders.remove(&render_index);
+
+            for (bundle_index, id) in primitive_ids {
                 if let Err(err) = self.render_bundles[bundle_index].remove(id) {
                     log::warn!("Error while removing render primitive: {err:?}.")
                 }
-            }
 
-            self.bundle_indices_to_pack.insert(bundle_index);
+                self.bundle_indices_to_pack.insert(bundle_index);
+            }
         } else {
             log::error!(
                 "Tried to remove render index {render_index} that was not present in the map."
@@ -79,58 +130,202 @@ impl FeatureRenderStore {
         }
     }
 
+    /// Adds primitives of a feature to the store and returns the render index of the feature.
+    ///
+    /// Primitives of features with smaller `sort_key` are drawn first. If `by_stage` is set, the primitives are also
+    /// split by their type, so that all polygons are drawn before all lines, and all lines before all points.
+    ///
+    /// Primitives that cannot be tessellated are skipped, and `on_error` is called for each of them.
     pub fn add_primitives(
         &mut self,
         primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>>,
+        sort_key: i32,
+        by_stage: bool,
+        canvas: &dyn Canvas,
+        on_error: &dyn Fn(&TessellationError),
     ) -> usize {
-        let curr_bundle_index = self.curr_bundle_index();
-        let ids = primitives
-            .into_iter()
-            .map(|primitive| {
-                self.render_bundles[curr_bundle_index].add(primitive, self.min_resolution)
-            })
-            .collect();
+        let is_animated = is_animated(&primitives);
+        let mut ids = Vec::with_capacity(primitives.len());
+        for primitive in primitives {
+            let stage = if by_stage {
+                match &primitive {
+                    RenderPrimitive::Polygon(..) => RenderStage::Fill,
+                    RenderPrimitive::Contour(..) => RenderStage::Stroke,
+                    RenderPrimitive::Point(..) => RenderStage::Point,
+                }
+            } else {
+                RenderStage::Any
+            };
+
+            let bundle_index = self.bundle_for(DrawOrder { stage, sort_key }, canvas);
+            let bundle = &mut self.render_bundles[bundle_index];
+            let id = bundle
+                .try_add(primitive, self.min_resolution)
+                .unwrap_or_else(|err| {
+                    on_error(&err);
+                    // Empty primitive keeps the ids in line with the primitives for style updates.
+                    bundle.add_empty()
+                });
+            ids.push((bundle_index, id));
+            self.bundle_indices_to_pack.insert(bundle_index);
+        }
 
         let next_index = self.next_index;
         self.next_index += 1;
 
-        self.feature_render_map.insert(
-            next_index,
-            RenderMapEntry {
-                bundle_index: curr_bundle_index,
-                primitive_ids: ids,
-            },
-        );
-
-        self.bundle_indices_to_pack.insert(curr_bundle_index);
+        self.feature_render_map
+            .insert(next_index, RenderMapEntry { primitive_ids: ids });
+        if is_animated {
+            self.animated_renders.insert(next_index);
+        }
 
         next_index
     }
 
-    fn curr_bundle_index(&self) -> usize {
-        self.render_bundles.len() - 1
-    }
-
     pub fn update_renders(
         &mut self,
         render_index: usize,
         primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>>,
     ) {
-        let RenderMapEntry {
-            bundle_index,
-            primitive_ids,
-        } = &self.feature_render_map[&render_index];
+        let RenderMapEntry { primitive_ids } = &self.feature_render_map[&render_index];
         if primitive_ids.len() != primitives.len() {
             log::error!("Cannot update feature style. The number of primitives is not equal to what it was.")
         }
 
-        for (id, primitive) in primitive_ids.iter().zip(primitives.into_iter()) {
+        if is_animated(&primitives) {
+            self.animated_renders.insert(render_index);
+        } else {
+            self.animated_renders.remove(&render_index);
+        }
+
+        for ((bundle_index, id), primitive) in primitive_ids.iter().zip(primitives.into_iter()) {
             if let Err(err) = self.render_bundles[*bundle_index].update(*id, primitive) {
                 log::warn!("Failed to update feature style: {err:?}");
             }
+
+            self.bundle_indices_to_pack.insert(*bundle_index);
+        }
+    }
+
+    /// Applies the highlight style to the primitives of the feature, or removes the highlight if `None` is given.
+    pub fn set_highlight(&mut self, render_index: usize, style: Option<HighlightStyle>) {
+        let Some(RenderMapEntry { primitive_ids }) = self.feature_render_map.get(&render_index)
+        else {
+            return;
+        };
+
+        for (bundle_index, id) in primitive_ids {
+            if let Err(err) = self.render_bundles[*bundle_index].set_highlight(*id, style) {
+                log::warn!("Failed to highlight feature: {err:?}");
+            }
+
+            self.bundle_indices_to_pack.insert(*bundle_index);
+        }
+    }
+
+    /// Returns true if any of the features in the store is drawn with a moving line pattern, so the store must be
+    /// redrawn on every frame.
+    pub fn is_animated(&self) -> bool {
+        !self.animated_renders.is_empty()
+    }
+
+    /// Postpones rendering of the feature until the `bbox` gets into the view.
+    pub fn defer(&mut self, feature_index: usize, bbox: Rect) {
+        self.deferred.push(DeferredFeature {
+            feature_index,
+            bbox,
+        });
+    }
+
+    /// Removes deferred features that intersect the `area` from the deferred list, and returns their indices.
+    pub fn take_deferred_in(&mut self, area: Rect) -> Vec<usize> {
+        let mut indices = vec![];
+        self.deferred.retain(|deferred| {
+            if deferred.bbox.intersects(area) {
+                indices.push(deferred.feature_index);
+                false
+            } else {
+                true
+            }
+        });
+
+        indices
+    }
+
+    /// State of the map the features were decluttered for, or `None` if they were not decluttered since the last
+    /// change.
+    pub fn declutter_key(&self) -> Option<&DeclutterKey> {
+        self.declutter_key.as_ref()
+    }
+
+    /// Requests the features to be decluttered again. The features suppressed before stay suppressed until then.
+    pub fn invalidate_declutter(&mut self) {
+        self.declutter_key = None;
+    }
+
+    /// Returns true if the feature must not be rendered because it is decluttered or occluded.
+    pub fn is_suppressed(&self, feature_index: usize) -> bool {
+        self.suppressed.contains(&feature_index)
+    }
+
+    /// Replaces the set of the decluttered and occluded features. Returns the indices of the features that were suppressed by this
+    /// call, and of the features that are not suppressed anymore.
+    pub fn set_suppressed(
+        &mut self,
+        key: DeclutterKey,
+        suppressed: HashSet<usize>,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let newly_suppressed = suppressed.difference(&self.suppressed).copied().collect();
+        let released = self.suppressed.difference(&suppressed).copied().collect();
+        self.suppressed = suppressed;
+        self.declutter_key = Some(key);
+
+        (newly_suppressed, released)
+    }
+
+    /// Updates indices of the deferred and suppressed features after a feature was removed from the feature store.
+    pub fn feature_removed(&mut self, feature_index: usize) {
+        self.deferred
+            .retain(|deferred| deferred.feature_index != feature_index);
+        for deferred in &mut self.deferred {
+            if deferred.feature_index > feature_index {
+                deferred.feature_index -= 1;
+            }
+        }
+
+        self.suppressed = self
+            .suppressed
+            .iter()
+            .filter(|&&index| index != feature_index)
+            .map(|&index| {
+                if index > feature_index {
+                    index - 1
+                } else {
+                    index
+                }
+            })
+            .collect();
+    }
+
+    /// Updates indices of the deferred and suppressed features after a feature was inserted into the feature store.
+    pub fn feature_inserted(&mut self, feature_index: usize) {
+        for deferred in &mut self.deferred {
+            if deferred.feature_index >= feature_index {
+                deferred.feature_index += 1;
+            }
         }
 
-        self.bundle_indices_to_pack.insert(*bundle_index);
+        self.suppressed = self
+            .suppressed
+            .iter()
+            .map(|&index| {
+                if index >= feature_index {
+                    index + 1
+                } else {
+                    index
+                }
+            })
+            .collect();
     }
 
     pub fn pack(&mut self, canvas: &dyn Canvas) {
@@ -139,10 +334,229 @@ impl FeatureRenderStore {
         }
     }
 
+    /// Drops all the packed bundles, so that every bundle is packed again on the next call to [`Self::pack`].
+    pub fn reset_packed(&mut self) {
+        for packed in &mut self.packed_bundles {
+            *packed = None;
+        }
+        self.bundle_indices_to_pack
+            .extend(0..self.render_bundles.len());
+    }
+
+    /// Packed bundles in the order they must be drawn.
     pub fn bundles(&self) -> Vec<&dyn PackedBundle> {
-        self.packed_bundles
+        self.draw_sequence
+            .iter()
+            .filter_map(|&index| self.packed_bundles[index].as_deref())
+            .collect()
+    }
+}
+
+fn is_animated(
+    primitives: &[RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>],
+) -> bool {
+    primitives.iter().any(|primitive| match primitive {
+        RenderPrimitive::Contour(_, paint) => {
+            paint.pattern.is_some_and(|pattern| pattern.is_animated())
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_bundle::tessellating::TessellatingRenderBundle;
+    use crate::render::render_bundle::RenderBundleType;
+    use crate::render::{LineCap, LinePaint, PolygonPaint, RenderOptions, SizeUnit};
+    use crate::Color;
+    use galileo_types::cartesian::Size;
+    use galileo_types::impls::ClosedContour;
+    use std::any::Any;
+
+    struct TestCanvas;
+    struct TestPackedBundle;
+
+    impl PackedBundle for TestPackedBundle {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    impl Canvas for TestCanvas {
+        fn size(&self) -> Size {
+            Size::new(100.0, 100.0)
+        }
+
+        fn create_bundle(&self) -> RenderBundle {
+            RenderBundle(RenderBundleType::Tessellating(
+                TessellatingRenderBundle::new(),
+            ))
+        }
+
+        fn pack_bundle(&self, _bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+            Box::new(TestPackedBundle)
+        }
+
+        fn draw_bundles(&mut self, _bundles: &[&dyn PackedBundle], _options: RenderOptions) {}
+    }
+
+    fn polygon_primitives(
+    ) -> Vec<RenderPrimitive<'static, f64, Point3d, Contour<Point3d>, Polygon<Point3d>>> {
+        let points = vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+        ];
+        vec![
+            RenderPrimitive::new_polygon(
+                Polygon::new(ClosedContour::new(points.clone()), vec![]),
+                PolygonPaint {
+                    color: Color::RED,
+                    gradient: None,
+                    zoom: None,
+                },
+            ),
+            RenderPrimitive::new_contour(
+                Contour::new(points, true),
+                LinePaint {
+                    color: Color::BLACK,
+                    width: 1.0,
+                    offset: 0.0,
+                    width_unit: SizeUnit::Pixels,
+                    line_cap: LineCap::Butt,
+                    pattern: None,
+                    gradient: None,
+                    zoom: None,
+                },
+            ),
+        ]
+    }
+
+    fn draw_orders(store: &FeatureRenderStore) -> Vec<DrawOrder> {
+        store
+            .draw_sequence
             .iter()
-            .filter_map(|v| v.as_ref().map(|bundle| &**bundle))
+            .map(|&index| store.bundle_orders[index])
             .collect()
     }
+
+    #[test]
+    fn bundles_are_drawn_by_sort_key() {
+        let mut store = FeatureRenderStore::new(0, 1.0, 1000);
+        store.add_primitives(polygon_primitives(), 5, false, &TestCanvas, &|_| {});
+        store.add_primitives(polygon_primitives(), -1, false, &TestCanvas, &|_| {});
+        store.add_primitives(polygon_primitives(), 5, false, &TestCanvas, &|_| {});
+
+        let stage = RenderStage::Any;
+        assert_eq!(
+            draw_orders(&store),
+            vec![
+                DrawOrder {
+                    stage,
+                    sort_key: -1
+                },
+                DrawOrder { stage, sort_key: 5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn bundles_are_drawn_by_stage() {
+        let mut store = FeatureRenderStore::new(0, 1.0, 1000);
+        store.add_primitives(polygon_primitives(), 1, true, &TestCanvas, &|_| {});
+        let render_index =
+            store.add_primitives(polygon_primitives(), 0, true, &TestCanvas, &|_| {});
+
+        assert_eq!(
+            draw_orders(&store),
+            vec![
+                DrawOrder {
+                    stage: RenderStage::Fill,
+                    sort_key: 0
+                },
+                DrawOrder {
+                    stage: RenderStage::Fill,
+                    sort_key: 1
+                },
+                DrawOrder {
+                    stage: RenderStage::Stroke,
+                    sort_key: 0
+                },
+                DrawOrder {
+                    stage: RenderStage::Stroke,
+                    sort_key: 1
+                },
+            ]
+        );
+
+        store.remove_render(render_index);
+        store.pack(&TestCanvas);
+        assert_eq!(store.bundles().len(), 4);
+    }
+
+    #[test]
+    fn reset_packed_bundles_are_packed_again() {
+        let mut store = FeatureRenderStore::new(0, 1.0, 1000);
+        store.add_primitives(polygon_primitives(), 0, true, &TestCanvas, &|_| {});
+        store.pack(&TestCanvas);
+        assert_eq!(store.bundles().len(), 2);
+
+        store.reset_packed();
+        assert!(store.bundles().is_empty());
+
+        store.pack(&TestCanvas);
+        assert_eq!(store.bundles().len(), 2);
+    }
+
+    #[test]
+    fn deferred_features() {
+        let mut store = FeatureRenderStore::new(0, 1.0, 1000);
+        store.defer(0, Rect::new(0.0, 0.0, 1.0, 1.0));
+        store.defer(1, Rect::new(10.0, 10.0, 11.0, 11.0));
+        store.defer(2, Rect::new(20.0, 20.0, 21.0, 21.0));
+
+        store.feature_removed(0);
+        assert!(store
+            .take_deferred_in(Rect::new(-5.0, -5.0, 5.0, 5.0))
+            .is_empty());
+        assert_eq!(
+            store.take_deferred_in(Rect::new(5.0, 5.0, 25.0, 25.0)),
+            vec![0, 1]
+        );
+        assert!(store
+            .take_deferred_in(Rect::new(5.0, 5.0, 25.0, 25.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn suppressed_features() {
+        let mut store = FeatureRenderStore::new(0, 1.0, 1000);
+        assert_eq!(store.declutter_key(), None);
+
+        let key = |level| DeclutterKey {
+            level: Some(level),
+            view: None,
+        };
+        let (suppressed, released) = store.set_suppressed(key(3), HashSet::from([1, 4]));
+        assert_eq!(store.declutter_key(), Some(&key(3)));
+        assert_eq!(
+            suppressed.into_iter().collect::<HashSet<_>>(),
+            HashSet::from([1, 4])
+        );
+        assert!(released.is_empty());
+
+        store.feature_removed(1);
+        store.feature_inserted(0);
+        assert!(store.is_suppressed(4));
+        assert!(!store.is_suppressed(1));
+
+        store.invalidate_declutter();
+        assert_eq!(store.declutter_key(), None);
+        assert!(store.is_suppressed(4));
+
+        let (suppressed, released) = store.set_suppressed(key(2), HashSet::from([2]));
+        assert_eq!(suppressed, vec![2]);
+        assert_eq!(released, vec![4]);
+    }
 }
@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// Feature storage of a [FeatureLayer](super::FeatureLayer).
@@ -11,6 +12,7 @@ use std::sync::{Arc, Mutex};
 pub struct FeatureStore<F> {
     features: Vec<FeatureEntry<F>>,
     pending_updates: Arc<Mutex<Vec<FeatureUpdate>>>,
+    revision: Arc<AtomicU64>,
 }
 
 /// Immutable container for a feature in a [FeatureLayer](super::FeatureLayer).
@@ -42,6 +44,7 @@ pub struct FeatureContainerMut<'a, F> {
     feature_index: usize,
     is_updated: bool,
     pending_updates: Arc<Mutex<Vec<FeatureUpdate>>>,
+    revision: Arc<AtomicU64>,
 }
 
 impl<'a, F> FeatureContainerMut<'a, F> {
@@ -60,6 +63,7 @@ impl<'a, F> FeatureContainerMut<'a, F> {
     /// Notifies the layer that after the feature is modified, the geometry will not be changed and only the style
     /// is to be updated. If geometry might change, use [container.as_mut()](AsMut::as_mut) instead.
     pub fn edit_style(self) -> &'a mut F {
+        self.revision.fetch_add(1, Ordering::Relaxed);
         if !self.is_updated {
             self.pending_updates
                 .lock()
@@ -90,12 +94,40 @@ impl<'a, F> FeatureContainerMut<'a, F> {
             .lock()
             .expect("mutex is poisoned")
             .push(FeatureUpdate::Delete {
+                removed_index: None,
                 render_indices: to_store,
             });
 
         self.is_updated = true;
     }
 
+    /// Returns true if the feature is highlighted.
+    pub fn is_highlighted(&self) -> bool {
+        self.entry.is_highlighted
+    }
+
+    /// Highlights the feature or removes the highlight from it.
+    ///
+    /// Highlighted features are drawn with the [highlight style](super::FeatureLayer::with_highlight_style) of the
+    /// layer. The feature is not rendered again when the highlight is changed, so this is cheap enough to be called
+    /// on every cursor move.
+    pub fn set_highlighted(&mut self, highlighted: bool) {
+        if self.entry.is_highlighted == highlighted {
+            return;
+        }
+
+        self.entry.is_highlighted = highlighted;
+
+        if !self.is_updated {
+            self.pending_updates
+                .lock()
+                .expect("poisoned mutex")
+                .push(FeatureUpdate::Highlight {
+                    feature_index: self.feature_index,
+                });
+        }
+    }
+
     /// Shows the previously hidden feature.
     pub fn show(&mut self) {
         if !self.is_hidden() {
@@ -125,6 +157,7 @@ impl<'a, F> AsRef<F> for FeatureContainerMut<'a, F> {
 
 impl<'a, F> AsMut<F> for FeatureContainerMut<'a, F> {
     fn as_mut(&mut self) -> &mut F {
+        self.revision.fetch_add(1, Ordering::Relaxed);
         if !self.is_updated {
             self.pending_updates
                 .lock()
@@ -141,9 +174,22 @@ impl<'a, F> AsMut<F> for FeatureContainerMut<'a, F> {
 
 #[derive(Debug)]
 pub(super) enum FeatureUpdate {
-    Update { feature_index: usize },
-    UpdateStyle { feature_index: usize },
-    Delete { render_indices: Vec<Option<usize>> },
+    Update {
+        feature_index: usize,
+    },
+    UpdateStyle {
+        feature_index: usize,
+    },
+    Highlight {
+        feature_index: usize,
+    },
+    Delete {
+        removed_index: Option<usize>,
+        render_indices: Vec<Option<usize>>,
+    },
+    Insert {
+        inserted_index: usize,
+    },
 }
 
 impl<F> FeatureStore<F> {
@@ -158,6 +204,7 @@ impl<F> FeatureStore<F> {
                     .map(|feature_index| FeatureUpdate::Update { feature_index })
                     .collect(),
             )),
+            revision: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -165,15 +212,57 @@ impl<F> FeatureStore<F> {
     pub fn insert(&mut self, feature: F) {
         let feature_index = self.features.len();
         self.features.push(FeatureEntry::new(feature));
+        self.revision.fetch_add(1, Ordering::Relaxed);
         self.pending_updates
             .lock()
             .expect("poisoned mutex")
             .push(FeatureUpdate::Update { feature_index })
     }
 
+    /// Inserts a new feature at the given position in the store, shifting the features after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert_at(&mut self, index: usize, feature: F) {
+        self.features.insert(index, FeatureEntry::new(feature));
+        self.revision.fetch_add(1, Ordering::Relaxed);
+
+        let mut pending_updates = self.pending_updates.lock().expect("mutex is poisoned");
+        for update in pending_updates.iter_mut() {
+            match update {
+                FeatureUpdate::Update { feature_index }
+                | FeatureUpdate::UpdateStyle { feature_index }
+                | FeatureUpdate::Highlight { feature_index } => {
+                    if *feature_index >= index {
+                        *feature_index += 1;
+                    }
+                }
+                FeatureUpdate::Delete { .. } | FeatureUpdate::Insert { .. } => {}
+            }
+        }
+        pending_updates.push(FeatureUpdate::Insert {
+            inserted_index: index,
+        });
+        pending_updates.push(FeatureUpdate::Update {
+            feature_index: index,
+        });
+    }
+
+    /// Number of features in the store, including hidden ones.
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Returns true if the store has no features.
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
     /// Adds a new hidden feature to the store at the end of the list.
     pub fn insert_hidden(&mut self, feature: F) {
         self.features.push(FeatureEntry::hidden(feature));
+        self.revision.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Returns a reference to the feature. Returns `None` if a feature with the given `index` does not exist.
@@ -188,6 +277,7 @@ impl<F> FeatureStore<F> {
             feature_index: index,
             is_updated: false,
             pending_updates: self.pending_updates.clone(),
+            revision: self.revision.clone(),
         })
     }
 
@@ -200,27 +290,77 @@ impl<F> FeatureStore<F> {
         let FeatureEntry {
             feature,
             is_hidden: _is_hidden,
+            is_highlighted: _is_highlighted,
             render_indices,
         } = self.features.remove(index);
-        self.pending_updates
-            .lock()
-            .expect("mutex is poisoned")
-            .push(FeatureUpdate::Delete {
-                render_indices: render_indices.into_inner().expect("mutex is poisoned"),
-            });
+        self.revision.fetch_add(1, Ordering::Relaxed);
+
+        let mut pending_updates = self.pending_updates.lock().expect("mutex is poisoned");
+        pending_updates.retain_mut(|update| match update {
+            FeatureUpdate::Update { feature_index }
+            | FeatureUpdate::UpdateStyle { feature_index }
+            | FeatureUpdate::Highlight { feature_index } => {
+                if *feature_index == index {
+                    return false;
+                }
+
+                if *feature_index > index {
+                    *feature_index -= 1;
+                }
+
+                true
+            }
+            FeatureUpdate::Delete { .. } | FeatureUpdate::Insert { .. } => true,
+        });
+        pending_updates.push(FeatureUpdate::Delete {
+            removed_index: Some(index),
+            render_indices: render_indices.into_inner().expect("mutex is poisoned"),
+        });
 
         feature
     }
 
+    /// Removes the highlight from all the features of the store.
+    pub fn clear_highlight(&mut self) {
+        for mut feature in self.iter_mut() {
+            feature.set_highlighted(false);
+        }
+    }
+
+    /// Requests the highlight of all highlighted features to be applied again, e.g. after the highlight style is
+    /// changed.
+    pub(super) fn refresh_highlight(&self) {
+        let mut updates = self.pending_updates.lock().expect("poisoned mutex");
+        for (feature_index, entry) in self.features.iter().enumerate() {
+            if entry.is_highlighted {
+                updates.push(FeatureUpdate::Highlight { feature_index });
+            }
+        }
+    }
+
     pub(super) fn get_entry(&self, index: usize) -> Option<&FeatureEntry<F>> {
         self.features.get(index)
     }
 
+    /// Number that changes every time a feature is added, removed or can be modified. Used to find out if the data
+    /// derived from the features (e.g. attribute indices) must be recalculated.
+    pub(super) fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Relaxed)
+    }
+
     pub(super) fn drain_updates(&self) -> Vec<FeatureUpdate> {
         let mut updates = self.pending_updates.lock().expect("poisoned mutex");
         std::mem::take(&mut *updates)
     }
 
+    /// Puts back the updates that were drained but not processed. They will be returned before any updates that
+    /// were added after draining.
+    pub(super) fn return_updates(&self, mut returned: Vec<FeatureUpdate>) {
+        let mut updates = self.pending_updates.lock().expect("poisoned mutex");
+        returned.append(&mut updates);
+        *updates = returned;
+    }
+
     /// Iterates over immutable containers of the features.
     pub fn iter(&self) -> impl Iterator<Item = FeatureContainer<F>> {
         self.features
@@ -242,6 +382,7 @@ impl<F> FeatureStore<F> {
                 feature_index: index,
                 is_updated: false,
                 pending_updates: self.pending_updates.clone(),
+                revision: self.revision.clone(),
             })
     }
 }
@@ -249,6 +390,7 @@ impl<F> FeatureStore<F> {
 pub(super) struct FeatureEntry<F> {
     feature: F,
     is_hidden: bool,
+    is_highlighted: bool,
     render_indices: Mutex<Vec<Option<usize>>>,
 }
 
@@ -257,6 +399,7 @@ impl<F> FeatureEntry<F> {
         Self {
             feature,
             is_hidden: false,
+            is_highlighted: false,
             render_indices: Mutex::new(vec![]),
         }
     }
@@ -265,6 +408,7 @@ impl<F> FeatureEntry<F> {
         Self {
             feature,
             is_hidden: true,
+            is_highlighted: false,
             render_indices: Mutex::new(vec![]),
         }
     }
@@ -273,6 +417,14 @@ impl<F> FeatureEntry<F> {
         &self.feature
     }
 
+    pub fn is_hidden(&self) -> bool {
+        self.is_hidden
+    }
+
+    pub fn is_highlighted(&self) -> bool {
+        self.is_highlighted
+    }
+
     pub fn render_index(&self, render_store_id: usize) -> Option<usize> {
         self.render_indices
             .lock()
@@ -291,6 +443,15 @@ impl<F> FeatureEntry<F> {
 
         render_indices[render_store_id] = Some(render_index)
     }
+
+    /// Returns the render index of the feature in the render store and removes it from the entry.
+    pub fn take_render_index(&self, render_store_id: usize) -> Option<usize> {
+        self.render_indices
+            .lock()
+            .expect("mutex is poisoned")
+            .get_mut(render_store_id)
+            .and_then(Option::take)
+    }
 }
 
 #[cfg(test)]
@@ -322,4 +483,72 @@ mod tests {
 
         assert_eq!(store.get(0).expect("no feature"), &"F12".to_string());
     }
+
+    #[test]
+    fn highlight_changes_are_tracked() {
+        let mut store = FeatureStore::new(["F1", "F2"].into_iter().map(String::from));
+        store.drain_updates();
+
+        let mut feature = store.get_mut(1).expect("no feature");
+        feature.set_highlighted(true);
+        feature.set_highlighted(true);
+        assert!(feature.is_highlighted());
+
+        let pending_updates = store.drain_updates();
+        assert_eq!(pending_updates.len(), 1);
+        assert_matches!(
+            pending_updates[0],
+            FeatureUpdate::Highlight { feature_index: 1 }
+        );
+
+        store.clear_highlight();
+        assert!(!store.get_mut(1).expect("no feature").is_highlighted());
+        assert_eq!(store.drain_updates().len(), 1);
+    }
+
+    #[test]
+    fn returned_updates_are_shifted_on_remove() {
+        let mut store = FeatureStore::new(["F1", "F2", "F3"].into_iter().map(String::from));
+
+        let mut pending_updates = store.drain_updates();
+        let unprocessed = pending_updates.split_off(1);
+        store.return_updates(unprocessed);
+
+        store.remove(1);
+        let pending_updates = store.drain_updates();
+        assert_eq!(pending_updates.len(), 2);
+        assert_matches!(
+            pending_updates[0],
+            FeatureUpdate::Update { feature_index: 1 }
+        );
+        assert_matches!(
+            pending_updates[1],
+            FeatureUpdate::Delete {
+                removed_index: Some(1),
+                ..
+            }
+        );
+    }
+
+    #[test]
+    fn insert_at_shifts_pending_updates() {
+        let mut store = FeatureStore::new(["F1", "F2"].into_iter().map(String::from));
+        store.insert_at(1, String::from("F3"));
+
+        assert_eq!(store.get(1).map(String::as_str), Some("F3"));
+        let pending_updates = store.drain_updates();
+        assert_eq!(pending_updates.len(), 4);
+        assert_matches!(
+            pending_updates[1],
+            FeatureUpdate::Update { feature_index: 2 }
+        );
+        assert_matches!(
+            pending_updates[2],
+            FeatureUpdate::Insert { inserted_index: 1 }
+        );
+        assert_matches!(
+            pending_updates[3],
+            FeatureUpdate::Update { feature_index: 1 }
+        );
+    }
 }
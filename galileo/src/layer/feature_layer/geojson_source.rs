@@ -0,0 +1,223 @@
+use crate::error::GalileoError;
+use crate::layer::data_provider::{DefaultHttpClient, HttpClient};
+use crate::layer::feature_layer::feature_diff::apply_features;
+use crate::layer::feature_layer::{FeatureDiff, FeatureLayer, Symbol};
+use galileo_types::geometry_type::GeoSpace2d;
+use galileo_types::GeoJsonPoint;
+use geojson::feature::Id;
+use geojson::GeoJson;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Feature layer with GeoJSON features.
+pub type GeoJsonLayer<S> = FeatureLayer<GeoJsonPoint, geojson::Feature, S, GeoSpace2d>;
+
+/// Keeps the features of a [`GeoJsonLayer`] in sync with a GeoJSON document loaded from a URL.
+///
+/// On every refresh the document is loaded again and compared to the features of the layer by their `id`:
+/// * features with new ids are added to the layer,
+/// * features that changed are replaced, keeping their place in the layer,
+/// * features whose ids are not present in the document anymore are removed.
+///
+/// Only the changed features are rendered again, so the source can be used for dashboards that show frequently
+/// updated data. Features without ids cannot be matched, so they are replaced on every refresh.
+///
+/// The source can be refreshed on demand with [`GeoJsonSource::refresh`], or periodically with
+/// [`GeoJsonSource::start_refresh`].
+pub struct GeoJsonSource<S> {
+    url: String,
+    layer: Arc<RwLock<GeoJsonLayer<S>>>,
+    http_client: Arc<dyn HttpClient>,
+    /// Incremented every time the periodic refresh is started or stopped, so that the running refresh task can
+    /// notice it should stop.
+    refresh_generation: Arc<AtomicUsize>,
+}
+
+impl<S> Clone for GeoJsonSource<S> {
+    fn clone(&self) -> Self {
+        Self {
+            url: self.url.clone(),
+            layer: self.layer.clone(),
+            http_client: self.http_client.clone(),
+            refresh_generation: self.refresh_generation.clone(),
+        }
+    }
+}
+
+impl<S> GeoJsonSource<S>
+where
+    S: Symbol<geojson::Feature> + MaybeSend + MaybeSync + 'static,
+{
+    /// Creates a new source that loads the GeoJSON document from the `url` into the `layer`.
+    ///
+    /// The data is not loaded until [`GeoJsonSource::refresh`] or [`GeoJsonSource::start_refresh`] is called.
+    pub fn new(url: impl Into<String>, layer: Arc<RwLock<GeoJsonLayer<S>>>) -> Self {
+        Self {
+            url: url.into(),
+            layer,
+            http_client: Arc::new(DefaultHttpClient::new()),
+            refresh_generation: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Sets the HTTP client used to load the document.
+    pub fn with_http_client(mut self, http_client: impl HttpClient + 'static) -> Self {
+        self.http_client = Arc::new(http_client);
+        self
+    }
+
+    /// URL of the GeoJSON document.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Layer the features are loaded into.
+    pub fn layer(&self) -> &Arc<RwLock<GeoJsonLayer<S>>> {
+        &self.layer
+    }
+
+    /// Loads the document and applies the changes in it to the layer.
+    ///
+    /// The document can be a feature collection, a single feature or a geometry. Features without geometry are
+    /// skipped.
+    pub async fn refresh(&self) -> Result<FeatureDiff, GalileoError> {
+        let bytes = self.http_client.get(&self.url).await?;
+        let features = parse_features(&bytes)?;
+
+        let mut layer = self.layer.write().expect("lock is poisoned");
+        let diff = apply_features(layer.features_mut(), features, feature_key);
+
+        if !diff.is_empty() {
+            if let Some(messenger) = &*layer.messenger.read().expect("lock is poisoned") {
+                messenger.request_redraw();
+            }
+        }
+
+        Ok(diff)
+    }
+
+    /// Starts refreshing the source every `interval`. The first refresh is done immediately.
+    ///
+    /// Errors of loading the document are logged, and the source tries again after the interval. If the periodic
+    /// refresh is already running, it is restarted with the new interval.
+    pub fn start_refresh(&self, interval: Duration) {
+        let generation = self.refresh_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let source = self.clone();
+
+        crate::async_runtime::spawn(async move {
+            while source.refresh_generation.load(Ordering::Relaxed) == generation {
+                if let Err(err) = source.refresh().await {
+                    log::warn!("Failed to refresh GeoJSON source {}: {err:?}", source.url);
+                }
+
+                crate::async_runtime::sleep(interval).await;
+            }
+        });
+    }
+
+    /// Stops the periodic refresh started by [`GeoJsonSource::start_refresh`].
+    pub fn stop_refresh(&self) {
+        self.refresh_generation.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn parse_features(bytes: &[u8]) -> Result<Vec<geojson::Feature>, GalileoError> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|err| GalileoError::Generic(format!("GeoJSON is not valid UTF-8: {err}")))?;
+    let geojson: GeoJson = text
+        .parse()
+        .map_err(|err| GalileoError::Generic(format!("invalid GeoJSON: {err}")))?;
+
+    let features = match geojson {
+        GeoJson::FeatureCollection(collection) => collection.features,
+        GeoJson::Feature(feature) => vec![feature],
+        GeoJson::Geometry(geometry) => vec![geojson::Feature::from(geometry)],
+    };
+
+    Ok(features
+        .into_iter()
+        .filter(|feature| feature.geometry.is_some())
+        .collect())
+}
+
+/// Key the features are matched by.
+fn feature_key(feature: &geojson::Feature) -> Option<String> {
+    match feature.id.as_ref()? {
+        Id::String(id) => Some(id.clone()),
+        Id::Number(id) => Some(id.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::FeatureStore;
+
+    fn feature(id: Option<&str>, x: f64) -> geojson::Feature {
+        geojson::Feature {
+            bbox: None,
+            geometry: Some(geojson::Geometry::new(geojson::Value::Point(vec![x, 0.0]))),
+            id: id.map(|id| Id::String(id.to_string())),
+            properties: None,
+            foreign_members: None,
+        }
+    }
+
+    #[test]
+    fn features_are_diffed_by_id() {
+        let mut store = FeatureStore::new(
+            [
+                feature(Some("a"), 1.0),
+                feature(Some("b"), 2.0),
+                feature(None, 3.0),
+                feature(Some("c"), 4.0),
+            ]
+            .into_iter(),
+        );
+
+        let diff = apply_features(
+            &mut store,
+            vec![
+                feature(Some("c"), 4.0),
+                feature(Some("a"), 10.0),
+                feature(Some("d"), 5.0),
+            ],
+            feature_key,
+        );
+
+        assert_eq!(
+            diff,
+            FeatureDiff {
+                added: 1,
+                updated: 1,
+                removed: 2,
+            }
+        );
+
+        let ids: Vec<_> = store
+            .iter()
+            .map(|f| feature_key(f.as_ref()).unwrap())
+            .collect();
+        assert_eq!(ids, ["a", "c", "d"]);
+        assert_eq!(store.get(0), Some(&feature(Some("a"), 10.0)));
+
+        let diff = apply_features(&mut store, vec![feature(Some("a"), 10.0)], feature_key);
+        assert_eq!(diff.removed, 2);
+        assert_eq!(diff.updated, 0);
+    }
+
+    #[test]
+    fn parse_skips_features_without_geometry() {
+        let json = r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "id": 1, "geometry": {"type": "Point", "coordinates": [1, 2]}, "properties": {}},
+            {"type": "Feature", "id": 2, "geometry": null, "properties": {}}
+        ]}"#;
+
+        let features = parse_features(json.as_bytes()).unwrap();
+        assert_eq!(features.len(), 1);
+        assert_eq!(feature_key(&features[0]).as_deref(), Some("1"));
+        assert!(parse_features(b"not json").is_err());
+    }
+}
@@ -0,0 +1,337 @@
+use crate::error::GalileoError;
+use crate::layer::feature_layer::{AttributeValue, Feature, FeatureId, FeatureLayer};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint};
+use galileo_types::geometry::Geom;
+use galileo_types::geometry_type::GeoSpace2d;
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Feature layer with the items of a GeoRSS feed.
+pub type GeoRssLayer<S> = FeatureLayer<GeoPoint2d, GeoRssItem, S, GeoSpace2d>;
+
+/// Item of an RSS or Atom feed with a GeoRSS location.
+///
+/// Supported location encodings are GeoRSS Simple (`georss:point`, `georss:line`, `georss:polygon` and
+/// `georss:box`), GeoRSS GML (`georss:where` with `gml:Point`, `gml:LineString` or `gml:Polygon`) and W3C Geo
+/// (`geo:lat` and `geo:long`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoRssItem {
+    /// Identifier of the item (`guid` in RSS, `id` in Atom).
+    pub id: Option<String>,
+    /// Title of the item.
+    pub title: Option<String>,
+    /// Link to the item page.
+    pub link: Option<String>,
+    /// Description of the item (`description` in RSS, `summary` or `content` in Atom).
+    pub summary: Option<String>,
+    /// Last update date as written in the feed (`pubDate` in RSS, `updated` or `published` in Atom).
+    pub updated: Option<String>,
+    geometry: Geom<GeoPoint2d>,
+}
+
+impl GeoRssItem {
+    /// Parses all the items of the feed that have a location. Items without a location are skipped.
+    pub fn parse_feed(xml: &str) -> Result<Vec<Self>, GalileoError> {
+        let xml_error =
+            |err: quick_xml::Error| GalileoError::Generic(format!("invalid GeoRSS feed: {err}"));
+
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        // Local names of the currently open elements.
+        let mut path: Vec<String> = vec![];
+        let mut items = vec![];
+        let mut item: Option<ItemBuilder> = None;
+
+        loop {
+            match reader.read_event().map_err(xml_error)? {
+                Event::Start(element) => {
+                    let name = local_name(&element);
+                    if name == "item" || name == "entry" {
+                        item = Some(ItemBuilder::default());
+                    } else if name == "link" {
+                        if let Some(item) = &mut item {
+                            item.read_link(&element);
+                        }
+                    }
+
+                    path.push(name);
+                }
+                Event::Empty(element) => {
+                    if let (Some(item), "link") = (&mut item, local_name(&element).as_str()) {
+                        item.read_link(&element);
+                    }
+                }
+                Event::End(_) => {
+                    let Some(name) = path.pop() else {
+                        continue;
+                    };
+
+                    if name == "item" || name == "entry" {
+                        items.extend(item.take().and_then(ItemBuilder::build));
+                    }
+                }
+                Event::Text(text) => {
+                    let text = text.unescape().map_err(xml_error)?;
+                    if let Some(item) = &mut item {
+                        item.read_text(&path, text.trim())?;
+                    }
+                }
+                Event::CData(text) => {
+                    let text = String::from_utf8_lossy(&text).into_owned();
+                    if let Some(item) = &mut item {
+                        item.read_text(&path, text.trim())?;
+                    }
+                }
+                Event::Eof => return Ok(items),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Feature for GeoRssItem {
+    type Geom = Geom<GeoPoint2d>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+
+    fn id(&self) -> Option<FeatureId> {
+        self.id.as_deref().map(FeatureId::from)
+    }
+
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        let value = match name {
+            "title" => &self.title,
+            "link" => &self.link,
+            "summary" => &self.summary,
+            "updated" => &self.updated,
+            _ => return None,
+        };
+
+        Some(
+            value
+                .as_deref()
+                .map_or(AttributeValue::Null, AttributeValue::from),
+        )
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        ["title", "link", "summary", "updated"]
+            .into_iter()
+            .filter_map(|name| Some((name.to_string(), Feature::attribute(self, name)?)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+struct ItemBuilder {
+    id: Option<String>,
+    title: Option<String>,
+    link: Option<String>,
+    summary: Option<String>,
+    updated: Option<String>,
+    geometry: Option<Geom<GeoPoint2d>>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    exterior: Option<ClosedContour<GeoPoint2d>>,
+    interiors: Vec<ClosedContour<GeoPoint2d>>,
+}
+
+impl ItemBuilder {
+    fn read_link(&mut self, element: &BytesStart) {
+        let mut href = None;
+        let mut alternate = true;
+        for attribute in element.attributes().flatten() {
+            let value = String::from_utf8_lossy(&attribute.value).into_owned();
+            match attribute.key.local_name().as_ref() {
+                b"href" => href = Some(value),
+                b"rel" => alternate = value == "alternate",
+                _ => {}
+            }
+        }
+
+        if alternate && self.link.is_none() {
+            self.link = href;
+        }
+    }
+
+    fn read_text(&mut self, path: &[String], text: &str) -> Result<(), GalileoError> {
+        let Some((name, parents)) = path.split_last() else {
+            return Ok(());
+        };
+        let inside = |element: &str| parents.iter().any(|parent| parent == element);
+
+        match name.as_str() {
+            "title" => self.title = Some(text.to_string()),
+            "link" if self.link.is_none() => self.link = Some(text.to_string()),
+            "guid" | "id" => self.id = Some(text.to_string()),
+            "description" | "summary" => self.summary = Some(text.to_string()),
+            "content" if self.summary.is_none() => self.summary = Some(text.to_string()),
+            "pubDate" | "updated" => self.updated = Some(text.to_string()),
+            "published" if self.updated.is_none() => self.updated = Some(text.to_string()),
+            "point" => self.geometry = Some(Geom::Point(single_point(text)?)),
+            "line" => self.geometry = Some(Geom::Contour(Contour::open(parse_points(text)?))),
+            "polygon" => {
+                self.geometry = Some(Geom::Polygon(Polygon::new(ring(text)?, vec![])));
+            }
+            "box" => self.geometry = Some(Geom::Polygon(box_polygon(text)?)),
+            "lat" => self.lat = Some(parse_number(text)?),
+            "long" | "lon" => self.lon = Some(parse_number(text)?),
+            "pos" if inside("Point") => self.geometry = Some(Geom::Point(single_point(text)?)),
+            "posList" if inside("LineString") => {
+                self.geometry = Some(Geom::Contour(Contour::open(parse_points(text)?)));
+            }
+            "posList" if inside("interior") => self.interiors.push(ring(text)?),
+            "posList" if inside("exterior") => self.exterior = Some(ring(text)?),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn build(self) -> Option<GeoRssItem> {
+        let geometry = match (self.geometry, self.exterior) {
+            (Some(geometry), _) => geometry,
+            (None, Some(exterior)) => Geom::Polygon(Polygon::new(exterior, self.interiors)),
+            (None, None) => Geom::Point(GeoPoint2d::latlon(self.lat?, self.lon?)),
+        };
+
+        Some(GeoRssItem {
+            id: self.id,
+            title: self.title,
+            link: self.link,
+            summary: self.summary,
+            updated: self.updated,
+            geometry,
+        })
+    }
+}
+
+fn local_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).into_owned()
+}
+
+fn invalid_location(text: &str) -> GalileoError {
+    GalileoError::Generic(format!("invalid GeoRSS location: {text}"))
+}
+
+fn parse_number(text: &str) -> Result<f64, GalileoError> {
+    text.parse().map_err(|_| invalid_location(text))
+}
+
+/// Parses a list of `lat lon` pairs separated by whitespace (or commas, which some feeds use).
+fn parse_points(text: &str) -> Result<Vec<GeoPoint2d>, GalileoError> {
+    let numbers = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(parse_number)
+        .collect::<Result<Vec<_>, _>>()?;
+    if numbers.is_empty() || numbers.len() % 2 != 0 {
+        return Err(invalid_location(text));
+    }
+
+    Ok(numbers
+        .chunks(2)
+        .map(|pair| GeoPoint2d::latlon(pair[0], pair[1]))
+        .collect())
+}
+
+fn single_point(text: &str) -> Result<GeoPoint2d, GalileoError> {
+    match parse_points(text)?[..] {
+        [point] => Ok(point),
+        _ => Err(invalid_location(text)),
+    }
+}
+
+/// Parses a closed ring, removing the closing point.
+fn ring(text: &str) -> Result<ClosedContour<GeoPoint2d>, GalileoError> {
+    let mut points = parse_points(text)?;
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    if points.len() < 3 {
+        return Err(invalid_location(text));
+    }
+
+    Ok(ClosedContour::new(points))
+}
+
+/// Parses `georss:box` given as the lower and upper corners.
+fn box_polygon(text: &str) -> Result<Polygon<GeoPoint2d>, GalileoError> {
+    let [lower, upper] = parse_points(text)?[..] else {
+        return Err(invalid_location(text));
+    };
+
+    let points = vec![
+        lower,
+        GeoPoint2d::latlon(lower.lat(), upper.lon()),
+        upper,
+        GeoPoint2d::latlon(upper.lat(), lower.lon()),
+    ];
+    Ok(Polygon::new(ClosedContour::new(points), vec![]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rss_and_atom_items() {
+        let rss = r#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:georss="http://www.georss.org/georss" xmlns:geo="http://www.w3.org/2003/01/geo/wgs84_pos#">
+              <channel>
+                <title>Alerts</title>
+                <item>
+                  <title>Flood warning</title>
+                  <link>https://example.com/1</link>
+                  <description><![CDATA[<b>River</b> is rising]]></description>
+                  <georss:line>45.0 10.0 46.0 11.0</georss:line>
+                </item>
+                <item>
+                  <title>Earthquake</title>
+                  <geo:lat>35.5</geo:lat>
+                  <geo:long>139.7</geo:long>
+                </item>
+                <item><title>No location</title></item>
+              </channel>
+            </rss>"#;
+
+        let items = GeoRssItem::parse_feed(rss).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title.as_deref(), Some("Flood warning"));
+        assert_eq!(items[0].link.as_deref(), Some("https://example.com/1"));
+        assert_eq!(items[0].summary.as_deref(), Some("<b>River</b> is rising"));
+        assert!(matches!(items[0].geometry(), Geom::Contour(_)));
+        let Geom::Point(point) = items[1].geometry() else {
+            panic!("not a point");
+        };
+        assert_eq!((point.lat(), point.lon()), (35.5, 139.7));
+
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:georss="http://www.georss.org/georss"
+                           xmlns:gml="http://www.opengis.net/gml">
+              <entry>
+                <id>urn:1</id>
+                <title>Area</title>
+                <link rel="alternate" href="https://example.com/area"/>
+                <georss:where>
+                  <gml:Polygon><gml:exterior><gml:LinearRing>
+                    <gml:posList>0 0 0 1 1 1 0 0</gml:posList>
+                  </gml:LinearRing></gml:exterior></gml:Polygon>
+                </georss:where>
+              </entry>
+            </feed>"#;
+
+        let items = GeoRssItem::parse_feed(atom).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id.as_deref(), Some("urn:1"));
+        assert_eq!(items[0].link.as_deref(), Some("https://example.com/area"));
+        let Geom::Polygon(polygon) = items[0].geometry() else {
+            panic!("not a polygon");
+        };
+        assert_eq!(polygon.outer_contour.points.len(), 3);
+    }
+}
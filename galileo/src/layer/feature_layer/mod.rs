@@ -1,33 +1,83 @@
 //! [`FeatureLayer`] stores features in a [`FeatureStore`] and renders them with a [`Symbol`].
 
-use crate::layer::Layer;
+use crate::control::PointerType;
+use crate::error::GalileoError;
+use crate::layer::{HitTolerance, Layer};
 use crate::messenger::Messenger;
-use crate::render::{Canvas, RenderOptions};
+use crate::occlusion::Occlusion;
+use crate::render::render_bundle::TessellationError;
+use crate::render::{Canvas, CustomShader, HighlightStyle, RenderOptions};
 use crate::view::MapView;
+use attribute_index::AttributeIndex;
+use declutter::DeclutterKey;
+use densify::DensifyFn;
 use feature_render_store::FeatureRenderStore;
 use galileo_types::cartesian::{
     CartesianPoint2d, NewCartesianPoint2d, NewCartesianPoint3d, Point2d, Point3d, Rect,
 };
 use galileo_types::geo::impls::projection::{AddDimensionProjection, IdentityProjection};
 use galileo_types::geo::impls::GeoPoint2d;
-use galileo_types::geo::{ChainProjection, Crs, InvertedProjection, NewGeoPoint, Projection};
+use galileo_types::geo::{
+    ChainProjection, Crs, InvertedProjection, NewGeoPoint, PathType, Projection,
+};
 use galileo_types::geometry::{CartesianGeometry2d, Geom, Geometry};
 use galileo_types::geometry_type::{CartesianSpace2d, CartesianSpace3d, GeoSpace2d};
+use galileo_types::{Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
 use maybe_sync::{MaybeSend, MaybeSync};
-use num_traits::AsPrimitive;
+use num_traits::{AsPrimitive, FromPrimitive, Zero};
 use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::{Mutex, RwLock};
+use web_time::{Duration, Instant};
 
+#[cfg(feature = "arrow")]
+mod arrow_source;
+mod attribute_index;
+mod attributes;
+#[cfg(feature = "csv")]
+mod csv_source;
+mod declutter;
+mod densify;
+mod edit_history;
+mod export;
 mod feature;
+mod feature_diff;
 mod feature_render_store;
 mod feature_store;
+#[cfg(feature = "geojson")]
+mod geojson_source;
+mod georss_source;
+mod osm;
+#[cfg(feature = "osm")]
+mod osm_source;
+mod overpass_source;
+pub(crate) mod simplify;
 pub mod symbol;
+mod wfs;
+#[cfg(feature = "arrow")]
+mod wkb;
 
-pub use feature::Feature;
+#[cfg(feature = "arrow")]
+pub use arrow_source::{ArrowFeature, ArrowLayer};
+pub use attributes::AttributeValue;
+#[cfg(feature = "csv")]
+pub use csv_source::{ColumnType, CoordinateColumns, CsvFeature, CsvLayer, CsvReader};
+pub use edit_history::EditHistory;
+pub use feature::{Feature, FeatureId};
+pub use feature_diff::FeatureDiff;
 pub use feature_store::*;
+#[cfg(feature = "geojson")]
+pub use geojson_source::{GeoJsonLayer, GeoJsonSource};
+pub use georss_source::{GeoRssItem, GeoRssLayer};
+pub use osm::{OsmFeature, OsmId, OsmLayer};
+#[cfg(feature = "osm")]
+pub use osm_source::OsmReader;
+pub use overpass_source::{OverpassSource, DEFAULT_OVERPASS_URL};
 pub use symbol::Symbol;
+pub use wfs::{OgcFilter, WfsQuery};
 
 /// Feature layers render a set of [features](Feature) using [symbols](Symbol).
 ///
@@ -53,6 +103,17 @@ where
     lods: Vec<Lod>,
     messenger: RwLock<Option<Box<dyn Messenger>>>,
     options: FeatureLayerOptions,
+    processed_updates: Mutex<usize>,
+    progress_callback: Option<Box<dyn Fn(LoadProgress) + Send + Sync>>,
+    tessellation_error_callback: Option<Box<dyn Fn(usize, &TessellationError) + Send + Sync>>,
+    sort_key: Option<Box<dyn Fn(&F) -> i32 + Send + Sync>>,
+    declutter_priority: Option<Box<dyn Fn(&F) -> f64 + Send + Sync>>,
+    occlusion: Option<Occlusion>,
+    shader: Option<CustomShader>,
+    highlight_style: HighlightStyle,
+    hit_tolerance: HitTolerance,
+    attribute_indices: Mutex<HashMap<String, AttributeIndex>>,
+    densify: Option<DensifyFn<F::Geom, P>>,
 
     space: PhantomData<Space>,
 }
@@ -81,6 +142,42 @@ pub struct FeatureLayerOptions {
     /// If set to true, the layer will be rendered with anti-aliasing. It makes rendered lines look smoother but is a
     /// little less performant.
     pub use_antialiasing: bool,
+
+    /// If set to true, features that are far outside of the current view are not tessellated until the view gets
+    /// close to them. This makes initial loading of large layers much cheaper when the map is zoomed in, at the cost
+    /// of tessellating features in small portions while the map is being panned.
+    ///
+    /// Disabled by default, so all features are tessellated when they are added to the layer.
+    pub cull_offscreen_features: bool,
+
+    /// If set to true, geometries are simplified separately for every level of detail of the layer (see
+    /// [`FeatureLayer::with_lods`]) before being tessellated. Points closer than a pixel at the resolution of the lod
+    /// are merged, so large polygon datasets do not render millions of invisible vertices when zoomed out.
+    ///
+    /// Simplification is done once when the feature is rendered into the lod, not on every frame.
+    pub simplify_geometry: bool,
+
+    /// If set, the layer spends at most this time per frame on tessellating and uploading changed features. The rest
+    /// of the features are processed in the next frames, so adding millions of features to the layer does not freeze
+    /// the application. Progress of the loading can be tracked with [`FeatureLayer::with_progress_callback`].
+    ///
+    /// If not set, all the changes are processed in the frame they are requested in.
+    pub load_time_budget: Option<Duration>,
+
+    /// If set to true, the layer is drawn in stages: first the polygons of all features, then all the lines, and then
+    /// all the points. This way outlines of polygons are never covered by the fills of the neighbouring features.
+    ///
+    /// If set to false, polygons and lines are drawn feature by feature, and points are drawn after them.
+    pub render_by_stage: bool,
+
+    /// If set, point features that are closer to each other than this number of pixels are thinned out: the map is
+    /// split into a grid of cells of about this size, and only the point with the highest
+    /// [priority](FeatureLayer::with_declutter_priority) is drawn in every cell. Other features (lines, polygons,
+    /// multipoints) are always drawn.
+    ///
+    /// The points are decluttered again when the map is zoomed in or out by a factor of 2, or when the features of the
+    /// layer are changed. Panning the map does not change the visible points.
+    pub declutter_cell_size: Option<f64>,
 }
 
 impl Default for FeatureLayerOptions {
@@ -89,10 +186,41 @@ impl Default for FeatureLayerOptions {
             sort_by_depth: false,
             buffer_size_limit: 10_000_000,
             use_antialiasing: true,
+            cull_offscreen_features: false,
+            simplify_geometry: false,
+            load_time_budget: None,
+            render_by_stage: false,
+            declutter_cell_size: None,
         }
     }
 }
 
+/// Features are tessellated if they are inside the view bounding box magnified by this factor.
+const CULLING_AREA_MAGNIFICATION: f64 = 3.0;
+
+/// Number of feature updates processed between checks of the load time budget.
+const LOAD_CHUNK_SIZE: usize = 64;
+
+/// Progress of processing the features of a [`FeatureLayer`] when
+/// [`load_time_budget`](FeatureLayerOptions::load_time_budget) is set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LoadProgress {
+    /// Number of feature changes that were processed since the layer started loading.
+    pub processed: usize,
+    /// Total number of feature changes in the current loading.
+    pub total: usize,
+}
+
+impl LoadProgress {
+    /// Returns true if all the features are loaded.
+    pub fn is_complete(&self) -> bool {
+        self.processed >= self.total
+    }
+}
+
+/// Maximum deviation of a simplified geometry from the original one in pixels of the lod resolution.
+const SIMPLIFICATION_TOLERANCE: f64 = 0.5;
+
 struct Lod {
     min_resolution: f64,
     contents: Mutex<FeatureRenderStore>,
@@ -125,6 +253,17 @@ where
             symbol: style,
             crs,
             messenger: RwLock::new(None),
+            processed_updates: Mutex::new(0),
+            progress_callback: None,
+            tessellation_error_callback: None,
+            sort_key: None,
+            declutter_priority: None,
+            occlusion: None,
+            shader: None,
+            highlight_style: HighlightStyle::default(),
+            hit_tolerance: HitTolerance::default(),
+            attribute_indices: Mutex::new(HashMap::new()),
+            densify: None,
             lods: vec![Lod::new(0, 1.0, options.buffer_size_limit)],
             options,
             space: Default::default(),
@@ -133,7 +272,9 @@ where
 
     /// Creates a new layer with specified levels of detail.
     ///
-    /// Levels of details specify resolution boundaries at which feature must be rendered separately.
+    /// Levels of details specify resolution boundaries at which feature must be rendered separately. Combined with
+    /// [`FeatureLayerOptions::simplify_geometry`], this allows rendering simplified geometries when the map is
+    /// zoomed out.
     pub fn with_lods(features: Vec<F>, style: S, crs: Crs, lods: &[f64]) -> Self {
         let options = FeatureLayerOptions::default();
         let mut lods: Vec<_> = lods
@@ -148,6 +289,17 @@ where
             symbol: style,
             crs,
             messenger: RwLock::new(None),
+            processed_updates: Mutex::new(0),
+            progress_callback: None,
+            tessellation_error_callback: None,
+            sort_key: None,
+            declutter_priority: None,
+            occlusion: None,
+            shader: None,
+            highlight_style: HighlightStyle::default(),
+            hit_tolerance: HitTolerance::default(),
+            attribute_indices: Mutex::new(HashMap::new()),
+            densify: None,
             lods,
             options,
             space: Default::default(),
@@ -166,6 +318,118 @@ where
         self
     }
 
+    /// Sets a callback that is called every frame while the features of the layer are being loaded in chunks. See
+    /// [`FeatureLayerOptions::load_time_budget`].
+    pub fn with_progress_callback(
+        mut self,
+        callback: impl Fn(LoadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a callback that is called with the index of the feature and the error when a feature cannot be rendered
+    /// because its geometry or symbol is invalid (e.g. has NaN coordinates or a negative line width).
+    ///
+    /// Such features are skipped and the rest of the layer is rendered as usual. Without the callback the errors are
+    /// logged.
+    pub fn with_tessellation_error_callback(
+        mut self,
+        callback: impl Fn(usize, &TessellationError) + Send + Sync + 'static,
+    ) -> Self {
+        self.tessellation_error_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a function that returns the sort key of a feature. Features with smaller keys are drawn below the
+    /// features with larger keys. Features with the same key are drawn in the order they were added to the layer.
+    ///
+    /// The key is calculated when a feature is rendered, so to apply a change in the key, the feature must be
+    /// modified through [`FeatureContainerMut::as_mut`] rather than [`FeatureContainerMut::edit_style`].
+    ///
+    /// Features with different sort keys are stored in separate GPU buffers, so the number of distinct keys should
+    /// be kept small.
+    pub fn with_sort_key(mut self, sort_key: impl Fn(&F) -> i32 + Send + Sync + 'static) -> Self {
+        self.sort_key = Some(Box::new(sort_key));
+        self
+    }
+
+    /// Sets a function that returns the priority of a point feature when the points are decluttered (see
+    /// [`FeatureLayerOptions::declutter_cell_size`]). Of the points that are too close to each other, the one with
+    /// the highest priority is drawn. Without the function, the point that comes first in the feature store is drawn.
+    pub fn with_declutter_priority(
+        mut self,
+        priority: impl Fn(&F) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        self.declutter_priority = Some(Box::new(priority));
+        self
+    }
+
+    /// Sets the terrain and buildings that hide point features from the camera. Occluded points are not drawn, like
+    /// the [decluttered](FeatureLayerOptions::declutter_cell_size) ones. Other features are always drawn.
+    ///
+    /// Visibility of the points is checked again on every change of the map view, so this should only be used for
+    /// layers with a moderate number of points, e.g. markers or POIs.
+    pub fn with_occlusion(mut self, occlusion: Occlusion) -> Self {
+        self.occlusion = Some(occlusion);
+        self
+    }
+
+    /// Replaces the occlusion of the point features. If `None` is given, all points are drawn.
+    pub fn set_occlusion(&mut self, occlusion: Option<Occlusion>) {
+        self.occlusion = occlusion;
+        for lod in &mut self.lods {
+            lod.contents
+                .get_mut()
+                .expect("mutex is poisoned")
+                .invalidate_declutter();
+        }
+    }
+
+    /// Sets a custom shader to draw polygons and lines of the layer with. See [`CustomShader`] for details.
+    pub fn with_shader(mut self, shader: CustomShader) -> Self {
+        self.shader = Some(shader);
+        self
+    }
+
+    /// Replaces the custom shader of the layer. If `None` is given, the default shader is used.
+    pub fn set_shader(&mut self, shader: Option<CustomShader>) {
+        self.shader = shader;
+    }
+
+    /// Sets the style of the features highlighted with [`FeatureContainerMut::set_highlighted`].
+    pub fn with_highlight_style(mut self, style: HighlightStyle) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    /// Changes the style of the highlighted features.
+    pub fn set_highlight_style(&mut self, style: HighlightStyle) {
+        self.highlight_style = style;
+        self.features.refresh_highlight();
+    }
+
+    /// Style of the highlighted features.
+    pub fn highlight_style(&self) -> HighlightStyle {
+        self.highlight_style
+    }
+
+    /// Sets the tolerance used by [`FeatureLayer::get_features_at_pointer`] to find the features under the pointer.
+    pub fn with_hit_tolerance(mut self, tolerance: HitTolerance) -> Self {
+        self.hit_tolerance = tolerance;
+        self
+    }
+
+    /// Changes the hit-test tolerance of the layer.
+    pub fn set_hit_tolerance(&mut self, tolerance: HitTolerance) {
+        self.hit_tolerance = tolerance;
+    }
+
+    /// Hit-test tolerance of the layer.
+    pub fn hit_tolerance(&self) -> HitTolerance {
+        self.hit_tolerance
+    }
+
     /// Returns a reference to the feature store.
     pub fn features(&self) -> &FeatureStore<F> {
         &self.features
@@ -176,10 +440,152 @@ where
         &mut self.features
     }
 
+    /// Adds a hash index on the values of the `attribute` of the features, so that
+    /// [`FeatureLayer::find_by_attribute`] for this attribute does not check every feature of the layer.
+    ///
+    /// The values are taken from [`Feature::attribute`]. The index is updated lazily on the first lookup after the
+    /// features of the layer are changed.
+    pub fn with_attribute_index(mut self, attribute: &str) -> Self {
+        self.add_attribute_index(attribute);
+        self
+    }
+
+    /// Adds a hash index on the values of the `attribute`. See [`FeatureLayer::with_attribute_index`].
+    pub fn add_attribute_index(&mut self, attribute: &str) {
+        self.attribute_indices
+            .get_mut()
+            .expect("mutex is poisoned")
+            .entry(attribute.to_string())
+            .or_insert_with(|| AttributeIndex::new(attribute));
+    }
+
+    /// Removes the index on the values of the `attribute`.
+    pub fn remove_attribute_index(&mut self, attribute: &str) {
+        self.attribute_indices
+            .get_mut()
+            .expect("mutex is poisoned")
+            .remove(attribute);
+    }
+
+    /// Returns the indices (in the [feature store](FeatureLayer::features)) of the features that have the given
+    /// value of the `attribute`, in the order of the features in the store.
+    ///
+    /// If the attribute is [indexed](FeatureLayer::with_attribute_index), the features are found with a hash
+    /// lookup. Otherwise, all the features of the layer are checked.
+    ///
+    /// ```no_run
+    /// # use galileo::layer::feature_layer::{Feature, FeatureLayer};
+    /// # use galileo::layer::feature_layer::symbol::Symbol;
+    /// # use galileo_types::geometry::Geometry;
+    /// fn highlight<P, F, S, Space>(layer: &mut FeatureLayer<P, F, S, Space>, name: &str)
+    /// where
+    ///     F: Feature,
+    ///     F::Geom: Geometry<Point = P>,
+    ///     S: Symbol<F>,
+    /// {
+    ///     for index in layer.find_by_attribute("name", name) {
+    ///         if let Some(mut feature) = layer.features_mut().get_mut(index) {
+    ///             feature.set_highlighted(true);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn find_by_attribute(
+        &self,
+        attribute: &str,
+        value: impl Into<AttributeValue>,
+    ) -> Vec<usize> {
+        let value = value.into();
+        let mut indices = self.attribute_indices.lock().expect("mutex is poisoned");
+        if let Some(index) = indices.get_mut(attribute) {
+            return index.find(&self.features, &value);
+        }
+
+        self.features
+            .iter()
+            .filter(|feature| feature.as_ref().attribute(attribute).as_ref() == Some(&value))
+            .map(|feature| feature.index())
+            .collect()
+    }
+
     /// Returns the CRS of the layer.
     pub fn crs(&self) -> &Crs {
         &self.crs
     }
+
+    /// Replaces the features of the layer with a new snapshot of the data set.
+    ///
+    /// Features of the snapshot are matched with the features of the layer by the `key` (e.g. the id of the feature).
+    /// Only the features that were added, removed or changed (compared with `PartialEq`) are rendered again, so this
+    /// method can be used to apply periodically received full data sets to the layer. Features without a key cannot
+    /// be matched, so they are always replaced.
+    pub fn set_features<K: Hash + Eq>(
+        &mut self,
+        features: impl IntoIterator<Item = F>,
+        key: impl Fn(&F) -> Option<K>,
+    ) -> FeatureDiff
+    where
+        F: PartialEq,
+    {
+        let diff = feature_diff::apply_features(&mut self.features, features, key);
+
+        if !diff.is_empty() {
+            if let Some(messenger) = &*self.messenger.read().expect("lock is poisoned") {
+                messenger.request_redraw();
+            }
+        }
+
+        diff
+    }
+
+    /// Features with the given indices, or all the features of the layer if `selection` is `None`.
+    fn selected_features<'a>(
+        &'a self,
+        selection: Option<&'a [usize]>,
+    ) -> impl Iterator<Item = Result<&'a F, GalileoError>> + 'a {
+        let indices: Box<dyn Iterator<Item = usize>> = match selection {
+            Some(selection) => Box::new(selection.iter().copied()),
+            None => Box::new(0..self.features.len()),
+        };
+
+        indices.map(|index| {
+            self.features
+                .get(index)
+                .ok_or_else(|| GalileoError::Generic(format!("feature {index} does not exist")))
+        })
+    }
+
+    fn export_geojson(
+        &self,
+        selection: Option<&[usize]>,
+        projection: &(impl Projection<InPoint = P, OutPoint = GeoPoint2d> + ?Sized),
+    ) -> Result<String, GalileoError> {
+        let features = self
+            .selected_features(selection)
+            .map(|feature| {
+                let feature = feature?;
+                let geometry = export::feature_geometry(feature, projection)?;
+                Ok(export::geojson_feature(feature, &geometry))
+            })
+            .collect::<Result<Vec<_>, GalileoError>>()?;
+
+        serde_json::to_string(&export::geojson_feature_collection(features))
+            .map_err(|err| GalileoError::Generic(format!("failed to write GeoJSON: {err}")))
+    }
+
+    fn export_wkt(
+        &self,
+        selection: Option<&[usize]>,
+        projection: &(impl Projection<InPoint = P, OutPoint = GeoPoint2d> + ?Sized),
+    ) -> Result<Vec<String>, GalileoError> {
+        self.selected_features(selection)
+            .map(|feature| {
+                Ok(export::wkt(&export::feature_geometry(
+                    feature?, projection,
+                )?))
+            })
+            .collect()
+    }
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, GeoSpace2d>
@@ -188,6 +594,22 @@ where
     F: Feature,
     F::Geom: Geometry<Point = P>,
 {
+    /// Makes the layer draw lines and polygon edges along the paths of the given type instead of straight lines of
+    /// the map projection.
+    ///
+    /// Before projecting, points are inserted into the geometries so that no segment is longer than
+    /// `max_segment_length` meters. For example, marine charts use [`PathType::Rhumb`] to show courses with a
+    /// constant bearing correctly in projections other than Mercator, and flight routes are drawn as
+    /// [`PathType::GreatCircle`] on Mercator maps. Densification adds to the cost of rendering the layer, so the
+    /// segment length should not be much smaller than needed at the largest scale the layer is shown at.
+    pub fn with_densification(mut self, path: PathType, max_segment_length: f64) -> Self
+    where
+        F: 'static,
+    {
+        self.densify = Some(densify::densify_fn(path, max_segment_length));
+        self
+    }
+
     /// Extend (bounding rectangle) of the layer, projected into given CRS.
     ///
     /// If the layer doesn't contain any features, or if at least one of them cannot be projected into the given
@@ -200,6 +622,60 @@ where
             .filter_map(|g| g.bounding_rectangle())
             .collect()
     }
+
+    /// Writes the features of the layer as a GeoJSON `FeatureCollection` with the ids and
+    /// [attributes](Feature::attributes) of the features.
+    ///
+    /// If `selection` is given, only the features with these indices are written (e.g. the result of
+    /// [`FeatureLayer::find_by_attribute`]). Otherwise all the features of the layer are written, including hidden ones.
+    pub fn to_geojson(&self, selection: Option<&[usize]>) -> Result<String, GalileoError> {
+        self.export_geojson(
+            selection,
+            &IdentityProjection::<P, GeoPoint2d, GeoSpace2d>::new(),
+        )
+    }
+
+    /// Writes the geometries of the features as WKT strings, one per feature. See [`FeatureLayer::to_geojson`] for
+    /// the meaning of `selection`.
+    pub fn to_wkt(&self, selection: Option<&[usize]>) -> Result<Vec<String>, GalileoError> {
+        self.export_wkt(
+            selection,
+            &IdentityProjection::<P, GeoPoint2d, GeoSpace2d>::new(),
+        )
+    }
+}
+
+impl<P, F, S> FeatureLayer<P, F, S, CartesianSpace2d>
+where
+    P: NewCartesianPoint2d + 'static,
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    /// Writes the features of the layer as a GeoJSON `FeatureCollection` with the ids and
+    /// [attributes](Feature::attributes) of the features. Coordinates are converted from the CRS of the layer to
+    /// WGS84 longitude and latitude.
+    ///
+    /// If `selection` is given, only the features with these indices are written. Otherwise all the features of the
+    /// layer are written, including hidden ones.
+    pub fn to_geojson(&self, selection: Option<&[usize]>) -> Result<String, GalileoError> {
+        self.export_geojson(selection, &*self.inverted_projection()?)
+    }
+
+    /// Writes the geometries of the features as WKT strings with WGS84 coordinates, one per feature. See
+    /// [`FeatureLayer::to_geojson`] for the meaning of `selection`.
+    pub fn to_wkt(&self, selection: Option<&[usize]>) -> Result<Vec<String>, GalileoError> {
+        self.export_wkt(selection, &*self.inverted_projection()?)
+    }
+
+    fn inverted_projection(
+        &self,
+    ) -> Result<Box<dyn Projection<InPoint = P, OutPoint = GeoPoint2d>>, GalileoError> {
+        let projection = self
+            .crs
+            .get_projection::<GeoPoint2d, P>()
+            .ok_or_else(|| GalileoError::Generic("CRS of the layer cannot be inverted".into()))?;
+        Ok(Box::new(InvertedProjection::new(projection)))
+    }
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, CartesianSpace2d>
@@ -245,6 +721,42 @@ where
             .iter_mut()
             .filter(move |f| f.as_ref().geometry().is_point_inside(point, tolerance))
     }
+
+    /// Returns an iterator of features under the pointer at the `point` (in the layer's CRS). The search distance is
+    /// the [hit tolerance](FeatureLayer::with_hit_tolerance) of the layer for the given type of input at the
+    /// resolution of the `view`, so thin lines can still be picked with a finger on a touch screen.
+    pub fn get_features_at_pointer<'a>(
+        &'a self,
+        point: &'a impl CartesianPoint2d<Num = P::Num>,
+        pointer_type: PointerType,
+        view: &MapView,
+    ) -> impl Iterator<Item = FeatureContainer<'a, F>> + 'a
+    where
+        F::Geom: CartesianGeometry2d<P>,
+    {
+        let tolerance = self.pointer_tolerance(pointer_type, view);
+        self.get_features_at(point, tolerance)
+    }
+
+    /// Returns a mutable iterator of features under the pointer at the `point` (in the layer's CRS). See
+    /// [`FeatureLayer::get_features_at_pointer`].
+    pub fn get_features_at_pointer_mut<'a>(
+        &'a mut self,
+        point: &'a impl CartesianPoint2d<Num = P::Num>,
+        pointer_type: PointerType,
+        view: &MapView,
+    ) -> impl Iterator<Item = FeatureContainerMut<'a, F>> + 'a
+    where
+        F::Geom: CartesianGeometry2d<P>,
+    {
+        let tolerance = self.pointer_tolerance(pointer_type, view);
+        self.get_features_at_mut(point, tolerance)
+    }
+
+    fn pointer_tolerance(&self, pointer_type: PointerType, view: &MapView) -> P::Num {
+        P::Num::from_f64(self.hit_tolerance.map_units(pointer_type, view))
+            .unwrap_or_else(P::Num::zero)
+    }
 }
 
 impl<P, F, S, Space> FeatureLayer<P, F, S, Space>
@@ -265,38 +777,267 @@ where
         &self.lods[self.lods.len() - 1].contents
     }
 
+    fn reset_packed_bundles(&mut self) {
+        for lod in &mut self.lods {
+            lod.contents
+                .get_mut()
+                .expect("mutex is poisoned")
+                .reset_packed();
+        }
+    }
+
     fn render_with_projection<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
         view: &MapView,
         canvas: &mut dyn Canvas,
         projection: impl Deref<Target = Proj>,
     ) {
+        let cull_area = if self.options.cull_offscreen_features {
+            view.get_bbox()
+                .map(|bbox| bbox.magnify(CULLING_AREA_MAGNIFICATION))
+        } else {
+            None
+        };
+
         let updates = self.features.drain_updates();
         if !updates.is_empty() {
-            self.update_feature_renders(canvas, projection, &updates);
+            self.process_updates(canvas, &*projection, updates, cull_area);
         }
 
-        let lod = self
+        let mut lod = self
             .select_lod(view.resolution())
             .lock()
             .expect("mutex is poisoned");
 
-        canvas.draw_bundles(
-            &lod.bundles(),
-            RenderOptions {
-                antialias: self.options.use_antialiasing,
-            },
-        );
+        let cell_pixels = self.options.declutter_cell_size;
+        if cell_pixels.is_some() || self.occlusion.is_some() {
+            let key = DeclutterKey {
+                level: cell_pixels.map(|_| declutter::grid_level(view.resolution())),
+                view: self.occlusion.as_ref().map(|_| view.clone()),
+            };
+            if lod.declutter_key() != Some(&key) {
+                self.declutter(canvas, &*projection, &mut lod, key, cull_area);
+            }
+        }
+
+        if let Some(cull_area) = cull_area {
+            self.render_deferred(canvas, &*projection, &mut lod, cull_area);
+        }
+
+        if lod.is_animated() {
+            if let Some(messenger) = &*self.messenger.read().expect("lock is poisoned") {
+                messenger.request_redraw();
+            }
+        }
+
+        let options = RenderOptions {
+            antialias: self.options.use_antialiasing,
+            ..Default::default()
+        };
+        match &self.shader {
+            Some(shader) => canvas.draw_bundles_with_shader(&lod.bundles(), options, shader),
+            None => canvas.draw_bundles(&lod.bundles(), options),
+        }
+    }
+
+    fn process_updates<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        canvas: &dyn Canvas,
+        projection: &Proj,
+        mut updates: Vec<FeatureUpdate>,
+        cull_area: Option<Rect>,
+    ) {
+        let Some(budget) = self.options.load_time_budget else {
+            self.update_feature_renders(canvas, projection, &updates, cull_area);
+            self.pack_lods(canvas);
+            return;
+        };
+
+        let deadline = Instant::now() + budget;
+        let mut processed = 0;
+        while processed < updates.len() {
+            let chunk_end = (processed + LOAD_CHUNK_SIZE).min(updates.len());
+            self.update_feature_renders(
+                canvas,
+                projection,
+                &updates[processed..chunk_end],
+                cull_area,
+            );
+            processed = chunk_end;
+
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+
+        self.pack_lods(canvas);
+
+        let remaining = updates.split_off(processed);
+        let remaining_count = remaining.len();
+        if !remaining.is_empty() {
+            self.features.return_updates(remaining);
+            if let Some(messenger) = &*self.messenger.read().expect("lock is poisoned") {
+                messenger.request_redraw();
+            }
+        }
+
+        let mut total_processed = self.processed_updates.lock().expect("mutex is poisoned");
+        *total_processed += processed;
+        let progress = LoadProgress {
+            processed: *total_processed,
+            total: *total_processed + remaining_count,
+        };
+
+        if progress.is_complete() {
+            *total_processed = 0;
+        }
+
+        if let Some(callback) = &self.progress_callback {
+            callback(progress);
+        }
+    }
+
+    /// Hides point features that overlap points with higher priority or are occluded, and renders back the ones that
+    /// do not overlap and are visible anymore.
+    fn declutter<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        canvas: &dyn Canvas,
+        projection: &Proj,
+        lod: &mut FeatureRenderStore,
+        key: DeclutterKey,
+        cull_area: Option<Rect>,
+    ) {
+        let points: Vec<_> = (0..self.features.len())
+            .filter_map(|feature_index| {
+                let feature_entry = self.features.get_entry(feature_index)?;
+                if feature_entry.is_hidden() {
+                    return None;
+                }
+
+                let feature = feature_entry.feature();
+                let Some(Geom::Point(position)) = self.project_geometry(feature, projection) else {
+                    return None;
+                };
+                let priority = self
+                    .declutter_priority
+                    .as_ref()
+                    .map_or(0.0, |priority| priority(feature));
+
+                Some((feature_index, position, priority))
+            })
+            .collect();
+
+        let mut suppressed = match (key.level, self.options.declutter_cell_size) {
+            (Some(level), Some(cell_pixels)) => declutter::suppressed_points(
+                points.iter().copied(),
+                declutter::cell_size(cell_pixels, level),
+            ),
+            _ => HashSet::new(),
+        };
+
+        if let (Some(occlusion), Some(view)) = (&self.occlusion, &key.view) {
+            for (feature_index, position, _) in &points {
+                // Points outside of the culling area are not rendered anyway.
+                let is_culled = cull_area.is_some_and(|area| !area.contains(&position.xy()));
+                if suppressed.contains(feature_index) || is_culled {
+                    continue;
+                }
+
+                if !occlusion.is_visible(view, *position) {
+                    suppressed.insert(*feature_index);
+                }
+            }
+        }
+
+        let (suppressed, released) = lod.set_suppressed(key, suppressed);
+
+        for feature_index in suppressed {
+            let Some(feature_entry) = self.features.get_entry(feature_index) else {
+                continue;
+            };
+
+            if let Some(render_index) = feature_entry.take_render_index(lod.id()) {
+                lod.remove_render(render_index);
+            }
+        }
+
+        for feature_index in released {
+            let Some(feature_entry) = self.features.get_entry(feature_index) else {
+                continue;
+            };
+
+            if feature_entry.is_hidden() || feature_entry.render_index(lod.id()).is_some() {
+                continue;
+            }
+
+            self.render_feature(
+                feature_index,
+                feature_entry,
+                canvas,
+                projection,
+                lod,
+                cull_area,
+            );
+        }
+
+        lod.pack(canvas);
+    }
+
+    fn pack_lods(&self, canvas: &dyn Canvas) {
+        for lod in &self.lods {
+            lod.contents.lock().expect("mutex is poisoned").pack(canvas);
+        }
+    }
+
+    fn render_deferred<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        canvas: &dyn Canvas,
+        projection: &Proj,
+        lod: &mut FeatureRenderStore,
+        cull_area: Rect,
+    ) {
+        let indices = lod.take_deferred_in(cull_area);
+        if indices.is_empty() {
+            return;
+        }
+
+        for feature_index in indices {
+            let Some(feature_entry) = self.features.get_entry(feature_index) else {
+                continue;
+            };
+
+            if feature_entry.is_hidden() || feature_entry.render_index(lod.id()).is_some() {
+                continue;
+            }
+
+            self.render_feature(feature_index, feature_entry, canvas, projection, lod, None);
+        }
+
+        lod.pack(canvas);
     }
 
     fn update_feature_renders<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
         canvas: &dyn Canvas,
-        projection: impl Deref<Target = Proj>,
+        projection: &Proj,
         updates: &[FeatureUpdate],
+        cull_area: Option<Rect>,
     ) {
         for update in updates {
-            if let FeatureUpdate::Delete { render_indices } = update {
+            if let FeatureUpdate::Insert { inserted_index } = update {
+                for lod in &self.lods {
+                    lod.contents
+                        .lock()
+                        .expect("mutex is poisoned")
+                        .feature_inserted(*inserted_index);
+                }
+            }
+
+            if let FeatureUpdate::Delete {
+                removed_index,
+                render_indices,
+            } = update
+            {
                 for (render_index, lod_index) in render_indices
                     .iter()
                     .enumerate()
@@ -308,15 +1049,23 @@ where
                         .expect("mutex is poisoned")
                         .remove_render(render_index);
                 }
+
+                if let Some(removed_index) = removed_index {
+                    for lod in &self.lods {
+                        lod.contents
+                            .lock()
+                            .expect("mutex is poisoned")
+                            .feature_removed(*removed_index);
+                    }
+                }
             }
         }
 
         for lod in &self.lods {
             let mut lod = lod.contents.lock().expect("mutex is poisoned");
+            lod.invalidate_declutter();
 
             for update in updates {
-                lod.init_bundle(|| canvas.create_bundle());
-
                 match update {
                     FeatureUpdate::Update { feature_index } => {
                         let Some(feature_entry) = self.features.get_entry(*feature_index) else {
@@ -328,7 +1077,29 @@ where
                             lod.remove_render(render_index);
                         }
 
-                        self.render_feature(feature_entry, &*projection, &mut lod);
+                        self.render_feature(
+                            *feature_index,
+                            feature_entry,
+                            canvas,
+                            projection,
+                            &mut lod,
+                            cull_area,
+                        );
+                    }
+                    FeatureUpdate::Highlight { feature_index } => {
+                        let Some(feature_entry) = self.features.get_entry(*feature_index) else {
+                            log::warn!("Feature {feature_index} is not present in the store");
+                            continue;
+                        };
+
+                        if let Some(render_index) = feature_entry.render_index(lod.id()) {
+                            lod.set_highlight(
+                                render_index,
+                                feature_entry
+                                    .is_highlighted()
+                                    .then_some(self.highlight_style),
+                            );
+                        }
                     }
                     FeatureUpdate::UpdateStyle { feature_index } => {
                         let Some(feature_entry) = self.features.get_entry(*feature_index) else {
@@ -339,7 +1110,7 @@ where
                         if let Some(render_index) = feature_entry.render_index(lod.id()) {
                             self.update_feature(
                                 feature_entry.feature(),
-                                &*projection,
+                                projection,
                                 render_index,
                                 &mut lod,
                             );
@@ -348,26 +1119,61 @@ where
                     _ => {}
                 }
             }
-
-            lod.pack(canvas);
         }
     }
 
     fn render_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
+        feature_index: usize,
         feature_entry: &FeatureEntry<F>,
+        canvas: &dyn Canvas,
         projection: &Proj,
         lod: &mut FeatureRenderStore,
+        cull_area: Option<Rect>,
     ) {
+        if lod.is_suppressed(feature_index) {
+            // The feature might have been rendered before it was suppressed.
+            feature_entry.take_render_index(lod.id());
+            return;
+        }
+
         let feature = feature_entry.feature();
-        let Some(projected): Option<Geom<Point3d>> = feature.geometry().project(projection) else {
+        let Some(projected) = self.project_geometry(feature, projection) else {
             return;
         };
 
+        if let Some(cull_area) = cull_area {
+            if let Some(bbox) = geom_bbox(&projected) {
+                if !bbox.intersects(cull_area) {
+                    lod.defer(feature_index, bbox);
+                    return;
+                }
+            }
+        }
+
+        let projected = self.simplify_for_lod(projected, lod);
         let primitives = self
             .symbol
             .render(feature, &projected, lod.min_resolution());
-        let index = lod.add_primitives(primitives);
+        let sort_key = self
+            .sort_key
+            .as_ref()
+            .map_or(0, |sort_key| sort_key(feature));
+        let on_error = |err: &TessellationError| match &self.tessellation_error_callback {
+            Some(callback) => callback(feature_index, err),
+            None => log::warn!("Feature {feature_index} is not rendered: {err}"),
+        };
+        let index = lod.add_primitives(
+            primitives,
+            sort_key,
+            self.options.render_by_stage,
+            canvas,
+            &on_error,
+        );
+        if feature_entry.is_highlighted() {
+            lod.set_highlight(index, Some(self.highlight_style));
+        }
+
         feature_entry.set_render_index(index, lod.id());
     }
 
@@ -378,15 +1184,35 @@ where
         render_index: usize,
         lod: &mut FeatureRenderStore,
     ) {
-        let Some(projected): Option<Geom<Point3d>> = feature.geometry().project(projection) else {
+        let Some(projected) = self.project_geometry(feature, projection) else {
             return;
         };
 
+        let projected = self.simplify_for_lod(projected, lod);
         let primitives = self
             .symbol
             .render(feature, &projected, lod.min_resolution());
         lod.update_renders(render_index, primitives);
     }
+
+    fn project_geometry<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        feature: &F,
+        projection: &Proj,
+    ) -> Option<Geom<Point3d>> {
+        match &self.densify {
+            Some(densify) => densify(feature.geometry(), &|point| projection.project(point)),
+            None => feature.geometry().project(projection),
+        }
+    }
+
+    fn simplify_for_lod(&self, geom: Geom<Point3d>, lod: &FeatureRenderStore) -> Geom<Point3d> {
+        if self.options.simplify_geometry {
+            simplify::simplify_geom(&geom, lod.min_resolution() * SIMPLIFICATION_TOLERANCE)
+        } else {
+            geom
+        }
+    }
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, GeoSpace2d>
@@ -429,6 +1255,10 @@ where
         *self.messenger.write().expect("lock is poisoned") = Some(messenger);
     }
 
+    fn reset_gpu_resources(&mut self) {
+        self.reset_packed_bundles();
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -488,6 +1318,10 @@ where
         *self.messenger.write().expect("lock is poisoned") = Some(messenger);
     }
 
+    fn reset_gpu_resources(&mut self) {
+        self.reset_packed_bundles();
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -536,6 +1370,10 @@ where
         *self.messenger.write().expect("lock is poisoned") = Some(messenger);
     }
 
+    fn reset_gpu_resources(&mut self) {
+        self.reset_packed_bundles();
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -544,3 +1382,24 @@ where
         self
     }
 }
+
+/// Bounding rectangle of the projected geometry in XY plane.
+fn geom_bbox(geom: &Geom<Point3d>) -> Option<Rect> {
+    let points: Box<dyn Iterator<Item = &Point3d> + '_> = match geom {
+        Geom::Point(point) => Box::new(std::iter::once(point)),
+        Geom::MultiPoint(points) => Box::new(points.iter_points()),
+        Geom::Contour(contour) => Box::new(contour.iter_points()),
+        Geom::MultiContour(contours) => Box::new(contours.contours().flat_map(|c| c.iter_points())),
+        Geom::Polygon(polygon) => Box::new(polygon.iter_contours().flat_map(|c| c.iter_points())),
+        Geom::MultiPolygon(polygons) => Box::new(
+            polygons
+                .polygons()
+                .flat_map(|p| p.iter_contours())
+                .flat_map(|c| c.iter_points()),
+        ),
+    };
+
+    points
+        .map(|p| Rect::new(p.x, p.y, p.x, p.y))
+        .reduce(|acc, rect| acc.merge(rect))
+}
@@ -0,0 +1,320 @@
+//! OpenStreetMap features shared by the OSM data sources.
+
+use crate::layer::feature_layer::{AttributeValue, Feature, FeatureId, FeatureLayer};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::GeoPoint;
+use galileo_types::geometry::Geom;
+use galileo_types::geometry_type::GeoSpace2d;
+use galileo_types::impls::{ClosedContour, Contour, MultiContour, MultiPolygon, Polygon};
+
+/// Feature layer with OpenStreetMap features.
+pub type OsmLayer<S> = FeatureLayer<GeoPoint2d, OsmFeature, S, GeoSpace2d>;
+
+/// Keys of the tags that make a closed way an area rather than a closed line.
+const AREA_KEYS: &[&str] = &[
+    "building", "landuse", "natural", "leisure", "amenity", "place", "water", "aeroway", "shop",
+    "tourism",
+];
+
+/// Values of the `natural` tag that are lines even if the way is closed.
+const LINEAR_NATURAL: &[&str] = &["coastline", "cliff", "ridge", "tree_row"];
+
+/// Identifier of an OpenStreetMap element.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OsmId {
+    /// Node id.
+    Node(i64),
+    /// Way id.
+    Way(i64),
+    /// Relation id.
+    Relation(i64),
+}
+
+/// Feature created from an OpenStreetMap element. The tags of the element are its attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OsmFeature {
+    id: OsmId,
+    geometry: Geom<GeoPoint2d>,
+    tags: Vec<(String, String)>,
+}
+
+impl OsmFeature {
+    pub(crate) fn new(id: OsmId, geometry: Geom<GeoPoint2d>, tags: Vec<(String, String)>) -> Self {
+        Self { id, geometry, tags }
+    }
+
+    /// Id of the element the feature was created from.
+    pub fn id(&self) -> OsmId {
+        self.id
+    }
+
+    /// Value of the tag with the given key.
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Iterates over all the tags of the feature.
+    pub fn tags(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tags.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl Feature for OsmFeature {
+    type Geom = Geom<GeoPoint2d>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+
+    /// Id in `type/id` form, e.g. `way/123`, as ids of elements of different types may be the same.
+    fn id(&self) -> Option<FeatureId> {
+        let id = match self.id {
+            OsmId::Node(id) => format!("node/{id}"),
+            OsmId::Way(id) => format!("way/{id}"),
+            OsmId::Relation(id) => format!("relation/{id}"),
+        };
+        Some(id.into())
+    }
+
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        self.tag(name).map(AttributeValue::from)
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        self.tags()
+            .map(|(key, value)| (key.to_string(), value.into()))
+            .collect()
+    }
+}
+
+/// Relation types that are converted into features.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum RelationKind {
+    /// `multipolygon` and `boundary` relations.
+    Area,
+    /// `route` and `waterway` relations.
+    Lines,
+}
+
+impl RelationKind {
+    pub(crate) fn from_tags(tags: &[(String, String)]) -> Option<Self> {
+        let relation_type = tags.iter().find(|(k, _)| k == "type")?;
+        match relation_type.1.as_str() {
+            "multipolygon" | "boundary" => Some(Self::Area),
+            "route" | "waterway" => Some(Self::Lines),
+            _ => None,
+        }
+    }
+}
+
+/// Geometry of a way: a polygon if the way is closed and tagged as an area, and a line otherwise.
+pub(crate) fn way_geometry(
+    tags: &[(String, String)],
+    points: Vec<GeoPoint2d>,
+) -> Option<Geom<GeoPoint2d>> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    Some(
+        if points.len() >= 4 && points.first() == points.last() && is_area(tags) {
+            Geom::Polygon(Polygon::new(ring(points), vec![]))
+        } else {
+            Geom::Contour(Contour::open(points))
+        },
+    )
+}
+
+/// Geometry of a relation from the points of its member ways, each with a flag if the way has the `inner` role.
+pub(crate) fn relation_geometry(
+    kind: RelationKind,
+    members: Vec<(Vec<GeoPoint2d>, bool)>,
+) -> Option<Geom<GeoPoint2d>> {
+    match kind {
+        RelationKind::Area => area_geometry(members),
+        RelationKind::Lines => line_geometry(members),
+    }
+}
+
+fn is_area(tags: &[(String, String)]) -> bool {
+    for (key, value) in tags {
+        match (key.as_str(), value.as_str()) {
+            ("area", "yes") => return true,
+            ("area", "no") => return false,
+            _ => {}
+        }
+    }
+
+    tags.iter().any(|(key, value)| {
+        AREA_KEYS.contains(&key.as_str())
+            && !(key == "natural" && LINEAR_NATURAL.contains(&value.as_str()))
+    })
+}
+
+/// Converts the points of a closed way into a contour, removing the closing point.
+fn ring(mut points: Vec<GeoPoint2d>) -> ClosedContour<GeoPoint2d> {
+    points.pop();
+    ClosedContour::new(points)
+}
+
+fn area_geometry(members: Vec<(Vec<GeoPoint2d>, bool)>) -> Option<Geom<GeoPoint2d>> {
+    let (inner_parts, outer_parts): (Vec<_>, Vec<_>) =
+        members.into_iter().partition(|(_, inner)| *inner);
+    let rings = |parts: Vec<(Vec<GeoPoint2d>, bool)>| -> Vec<ClosedContour<GeoPoint2d>> {
+        assemble_rings(parts.into_iter().map(|(points, _)| points).collect())
+            .into_iter()
+            .map(ring)
+            .collect()
+    };
+
+    let outers = rings(outer_parts);
+    let mut inners: Vec<Vec<ClosedContour<GeoPoint2d>>> = vec![vec![]; outers.len()];
+    for inner in rings(inner_parts) {
+        let Some(first) = inner.points.first() else {
+            continue;
+        };
+        if let Some(index) = outers
+            .iter()
+            .position(|outer| ring_contains(&outer.points, first))
+        {
+            inners[index].push(inner);
+        }
+    }
+
+    let mut polygons: Vec<Polygon<GeoPoint2d>> = outers
+        .into_iter()
+        .zip(inners)
+        .map(|(outer, inners)| Polygon::new(outer, inners))
+        .collect();
+
+    match polygons.len() {
+        0 => None,
+        1 => polygons.pop().map(Geom::Polygon),
+        _ => Some(Geom::MultiPolygon(MultiPolygon::from(polygons))),
+    }
+}
+
+fn line_geometry(members: Vec<(Vec<GeoPoint2d>, bool)>) -> Option<Geom<GeoPoint2d>> {
+    let contours: Vec<Contour<GeoPoint2d>> = members
+        .into_iter()
+        .map(|(points, _)| points)
+        .filter(|points| points.len() >= 2)
+        .map(Contour::open)
+        .collect();
+
+    if contours.is_empty() {
+        None
+    } else {
+        Some(Geom::MultiContour(MultiContour::from(contours)))
+    }
+}
+
+/// Joins ways (given as lists of nodes) into closed rings by their end nodes. Ways that cannot be joined into a
+/// closed ring are dropped.
+fn assemble_rings<T: PartialEq + Clone>(mut ways: Vec<Vec<T>>) -> Vec<Vec<T>> {
+    let mut rings = vec![];
+    while let Some(mut ring) = ways.pop() {
+        while ring.len() > 1 && ring.first() != ring.last() {
+            let end = ring.last().expect("ring is not empty").clone();
+            let Some(index) = ways
+                .iter()
+                .position(|way| way.first() == Some(&end) || way.last() == Some(&end))
+            else {
+                break;
+            };
+
+            let mut next = ways.swap_remove(index);
+            if next.first() != Some(&end) {
+                next.reverse();
+            }
+            ring.extend_from_slice(&next[1..]);
+        }
+
+        if ring.len() >= 4 && ring.first() == ring.last() {
+            rings.push(ring);
+        }
+    }
+
+    rings
+}
+
+/// Checks if the point is inside the ring using the even-odd rule in longitude/latitude coordinates.
+fn ring_contains(ring: &[GeoPoint2d], point: &GeoPoint2d) -> bool {
+    let mut inside = false;
+    let mut prev = match ring.last() {
+        Some(p) => p,
+        None => return false,
+    };
+    for curr in ring {
+        if (curr.lat() > point.lat()) != (prev.lat() > point.lat()) {
+            let lon = prev.lon()
+                + (point.lat() - prev.lat()) / (curr.lat() - prev.lat())
+                    * (curr.lon() - prev.lon());
+            if point.lon() < lon {
+                inside = !inside;
+            }
+        }
+        prev = curr;
+    }
+
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::geo::NewGeoPoint;
+    use std::collections::HashSet;
+
+    fn tags(tags: &[(&str, &str)]) -> Vec<(String, String)> {
+        tags.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn assemble_rings_from_ways() {
+        let ways = vec![vec![1, 2, 3], vec![5, 4, 3], vec![5, 6, 1], vec![10, 11]];
+        let rings = assemble_rings(ways);
+        assert_eq!(rings.len(), 1);
+
+        let ring = &rings[0];
+        assert_eq!(ring.len(), 7);
+        assert_eq!(ring.first(), ring.last());
+        let ids: HashSet<_> = ring.iter().collect();
+        assert_eq!(ids.len(), 6);
+    }
+
+    #[test]
+    fn area_detection() {
+        assert!(is_area(&tags(&[("building", "yes")])));
+        assert!(is_area(&tags(&[
+            ("highway", "pedestrian"),
+            ("area", "yes")
+        ])));
+        assert!(!is_area(&tags(&[("highway", "pedestrian")])));
+        assert!(!is_area(&tags(&[("natural", "coastline")])));
+        assert!(!is_area(&tags(&[("leisure", "track"), ("area", "no")])));
+    }
+
+    #[test]
+    fn multipolygon_with_hole() {
+        let p = |lat: f64, lon: f64| GeoPoint2d::latlon(lat, lon);
+        let outer_a = vec![p(0.0, 0.0), p(0.0, 10.0), p(10.0, 10.0)];
+        let outer_b = vec![p(10.0, 10.0), p(10.0, 0.0), p(0.0, 0.0)];
+        let inner = vec![p(2.0, 2.0), p(2.0, 3.0), p(3.0, 3.0), p(2.0, 2.0)];
+
+        let geometry = relation_geometry(
+            RelationKind::Area,
+            vec![(outer_a, false), (inner, true), (outer_b, false)],
+        );
+        let Some(Geom::Polygon(polygon)) = geometry else {
+            panic!("not a polygon");
+        };
+        assert_eq!(polygon.outer_contour.points.len(), 4);
+        assert_eq!(polygon.inner_contours.len(), 1);
+    }
+}
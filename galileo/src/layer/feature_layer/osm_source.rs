@@ -0,0 +1,232 @@
+use crate::error::GalileoError;
+use crate::layer::feature_layer::osm::{relation_geometry, way_geometry, RelationKind};
+use crate::layer::feature_layer::{OsmFeature, OsmId};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::NewGeoPoint;
+use galileo_types::geometry::Geom;
+use osmpbf::{Element, ElementReader, RelMemberType};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+/// Reads features from OpenStreetMap `.osm.pbf` extracts.
+///
+/// * Tagged nodes become point features.
+/// * Ways become lines, or polygons if they are closed and tagged as areas (e.g. `building`, `landuse`, `area=yes`).
+/// * `multipolygon` and `boundary` relations are assembled into polygons from their `outer` and `inner` member ways,
+///   and `route` and `waterway` relations become multi-lines.
+///
+/// Only the elements with the tags matching the filter set with [`OsmReader::with_tag`] and
+/// [`OsmReader::with_tag_value`] are loaded (if no filter is set, all tagged elements are loaded). The data is read in
+/// several passes, and only the coordinates of the nodes used by the loaded features are kept in memory, so a narrow
+/// filter keeps the memory bounded even for large extracts.
+///
+/// Geometries referencing nodes or ways not present in the extract are skipped.
+#[derive(Debug, Clone)]
+pub struct OsmReader {
+    filter: Vec<(String, Option<String>)>,
+    load_points: bool,
+}
+
+impl Default for OsmReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OsmReader {
+    /// Creates a new reader that loads all tagged elements.
+    pub fn new() -> Self {
+        Self {
+            filter: vec![],
+            load_points: true,
+        }
+    }
+
+    /// Loads the elements that have a tag with the given key.
+    pub fn with_tag(mut self, key: impl Into<String>) -> Self {
+        self.filter.push((key.into(), None));
+        self
+    }
+
+    /// Loads the elements that have a tag with the given key and value.
+    pub fn with_tag_value(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.filter.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Sets whether tagged nodes are loaded as point features. True by default.
+    pub fn with_points(mut self, load_points: bool) -> Self {
+        self.load_points = load_points;
+        self
+    }
+
+    /// Reads features from the contents of a `.osm.pbf` file.
+    pub fn read(&self, data: &[u8]) -> Result<Vec<OsmFeature>, GalileoError> {
+        self.read_with(|| Ok(data))
+    }
+
+    /// Reads features from a `.osm.pbf` file.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<OsmFeature>, GalileoError> {
+        let path = path.as_ref();
+        self.read_with(|| Ok(std::io::BufReader::new(std::fs::File::open(path)?)))
+    }
+
+    /// Reads the data opened with `open` once per pass.
+    fn read_with<R: Read + Send>(
+        &self,
+        open: impl Fn() -> Result<R, GalileoError>,
+    ) -> Result<Vec<OsmFeature>, GalileoError> {
+        // Elements are sorted by type in extracts (nodes, then ways, then relations), so the elements are read in the
+        // reverse order to know which ways and nodes are needed before they are read.
+        let mut relations = vec![];
+        let mut needed_ways = HashSet::new();
+        ElementReader::new(open()?)
+            .for_each(|element| {
+                let Element::Relation(relation) = element else {
+                    return;
+                };
+                let tags = collect_tags(relation.tags());
+                let Some(kind) = RelationKind::from_tags(&tags) else {
+                    return;
+                };
+                if !self.matches(&tags) {
+                    return;
+                }
+
+                let members: Vec<(i64, bool)> = relation
+                    .members()
+                    .filter(|member| member.member_type == RelMemberType::Way)
+                    .map(|member| (member.member_id, member.role().ok() == Some("inner")))
+                    .collect();
+                needed_ways.extend(members.iter().map(|(id, _)| *id));
+                relations.push((relation.id(), kind, tags, members));
+            })
+            .map_err(pbf_error)?;
+
+        let mut ways = vec![];
+        let mut member_ways = HashMap::new();
+        let mut needed_nodes = HashSet::new();
+        ElementReader::new(open()?)
+            .for_each(|element| {
+                let Element::Way(way) = element else {
+                    return;
+                };
+                let tags = collect_tags(way.tags());
+                let is_feature = self.matches(&tags);
+                let is_member = needed_ways.contains(&way.id());
+                if !is_feature && !is_member {
+                    return;
+                }
+
+                let refs: Vec<i64> = way.refs().collect();
+                needed_nodes.extend(refs.iter().copied());
+                if is_member {
+                    member_ways.insert(way.id(), refs.clone());
+                }
+                if is_feature {
+                    ways.push((way.id(), tags, refs));
+                }
+            })
+            .map_err(pbf_error)?;
+
+        let mut features = vec![];
+        let mut nodes = HashMap::new();
+        ElementReader::new(open()?)
+            .for_each(|element| {
+                let (id, point, tags) = match element {
+                    Element::Node(node) => (
+                        node.id(),
+                        GeoPoint2d::latlon(node.lat(), node.lon()),
+                        node.tags().collect::<Vec<_>>(),
+                    ),
+                    Element::DenseNode(node) => (
+                        node.id(),
+                        GeoPoint2d::latlon(node.lat(), node.lon()),
+                        node.tags().collect::<Vec<_>>(),
+                    ),
+                    _ => return,
+                };
+
+                if needed_nodes.contains(&id) {
+                    nodes.insert(id, point);
+                }
+
+                if self.load_points && !tags.is_empty() {
+                    let tags = collect_tags(tags.into_iter());
+                    if self.matches(&tags) {
+                        features.push(OsmFeature::new(OsmId::Node(id), Geom::Point(point), tags));
+                    }
+                }
+            })
+            .map_err(pbf_error)?;
+
+        for (id, tags, refs) in ways {
+            let geometry = resolve(&refs, &nodes).and_then(|points| way_geometry(&tags, points));
+            if let Some(geometry) = geometry {
+                features.push(OsmFeature::new(OsmId::Way(id), geometry, tags));
+            }
+        }
+
+        for (id, kind, tags, members) in relations {
+            let members = members
+                .into_iter()
+                .filter_map(|(way_id, inner)| {
+                    Some((resolve(member_ways.get(&way_id)?, &nodes)?, inner))
+                })
+                .collect();
+
+            if let Some(geometry) = relation_geometry(kind, members) {
+                features.push(OsmFeature::new(OsmId::Relation(id), geometry, tags));
+            }
+        }
+
+        Ok(features)
+    }
+
+    fn matches(&self, tags: &[(String, String)]) -> bool {
+        if self.filter.is_empty() {
+            return !tags.is_empty();
+        }
+
+        self.filter.iter().any(|(key, value)| {
+            tags.iter()
+                .any(|(k, v)| k == key && (value.is_none() || value.as_deref() == Some(v.as_str())))
+        })
+    }
+}
+
+fn pbf_error(err: osmpbf::Error) -> GalileoError {
+    GalileoError::Generic(format!("failed to read OSM PBF data: {err}"))
+}
+
+fn collect_tags<'a>(tags: impl Iterator<Item = (&'a str, &'a str)>) -> Vec<(String, String)> {
+    tags.map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+fn resolve(refs: &[i64], nodes: &HashMap<i64, GeoPoint2d>) -> Option<Vec<GeoPoint2d>> {
+    refs.iter().map(|id| nodes.get(id).copied()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[(&str, &str)]) -> Vec<(String, String)> {
+        collect_tags(tags.iter().copied())
+    }
+
+    #[test]
+    fn tag_filter() {
+        let reader = OsmReader::new()
+            .with_tag("building")
+            .with_tag_value("highway", "primary");
+        assert!(reader.matches(&tags(&[("building", "house")])));
+        assert!(reader.matches(&tags(&[("highway", "primary")])));
+        assert!(!reader.matches(&tags(&[("highway", "service")])));
+        assert!(!OsmReader::new().matches(&[]));
+    }
+}
@@ -0,0 +1,282 @@
+use crate::error::GalileoError;
+use crate::layer::data_provider::{percent_encode, DefaultHttpClient, HttpClient};
+use crate::layer::feature_layer::feature_diff::apply_features;
+use crate::layer::feature_layer::osm::{relation_geometry, way_geometry, RelationKind};
+use crate::layer::feature_layer::{FeatureDiff, OsmFeature, OsmId, OsmLayer, Symbol};
+use crate::view::MapView;
+use galileo_types::cartesian::Rect;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint};
+use galileo_types::geometry::Geom;
+use maybe_sync::{MaybeSend, MaybeSync};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// Public Overpass API instance used by default.
+pub const DEFAULT_OVERPASS_URL: &str = "https://overpass-api.de/api/interpreter";
+
+/// Placeholder in the query replaced by the bounding box of the request.
+const BBOX_PLACEHOLDER: &str = "{{bbox}}";
+
+/// Loads OpenStreetMap data into an [`OsmLayer`] with an [Overpass API](https://wiki.openstreetmap.org/wiki/Overpass_API)
+/// query.
+///
+/// The query is written in Overpass QL and must request JSON output with geometries (`[out:json]` and `out geom;` or
+/// `out center;`). The query can contain `{{bbox}}` placeholder, which is replaced by the bounding box of the
+/// request (`south,west,north,east`) when the source is refreshed with [`OverpassSource::refresh_in`] or
+/// [`OverpassSource::refresh_for_view`].
+///
+/// Elements are converted into features the same way as with [`OsmReader`](super::OsmReader): tagged nodes become
+/// points, ways become lines or polygons, and multipolygon and route relations are assembled from their members.
+/// Elements with only a center (`out center;`) become points.
+///
+/// On every refresh the features of the layer are replaced with the query result. Features that did not change are
+/// kept as they are, so they are not rendered again.
+///
+/// ```no_run
+/// use galileo::layer::feature_layer::{OsmFeature, OsmLayer, OverpassSource, Symbol};
+/// use galileo::MapView;
+/// use std::sync::{Arc, RwLock};
+///
+/// # async fn refresh<S: Symbol<OsmFeature> + Send + Sync + 'static>(layer: Arc<RwLock<OsmLayer<S>>>, view: MapView) {
+/// let source = OverpassSource::new(
+///     "[out:json]; node[amenity=drinking_water]({{bbox}}); out geom;",
+///     layer,
+/// );
+/// source.refresh_for_view(&view).await.unwrap();
+/// # }
+/// ```
+pub struct OverpassSource<S> {
+    query: String,
+    endpoint: String,
+    layer: Arc<RwLock<OsmLayer<S>>>,
+    http_client: Arc<dyn HttpClient>,
+}
+
+impl<S> OverpassSource<S>
+where
+    S: Symbol<OsmFeature> + MaybeSend + MaybeSync + 'static,
+{
+    /// Creates a new source that loads the result of the `query` into the `layer` from the
+    /// [default Overpass instance](DEFAULT_OVERPASS_URL).
+    ///
+    /// The data is not loaded until the source is refreshed.
+    pub fn new(query: impl Into<String>, layer: Arc<RwLock<OsmLayer<S>>>) -> Self {
+        Self {
+            query: query.into(),
+            endpoint: DEFAULT_OVERPASS_URL.to_string(),
+            layer,
+            http_client: Arc::new(DefaultHttpClient::new()),
+        }
+    }
+
+    /// Sets the URL of the Overpass API interpreter endpoint.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Sets the HTTP client used to send the queries.
+    pub fn with_http_client(mut self, http_client: impl HttpClient + 'static) -> Self {
+        self.http_client = Arc::new(http_client);
+        self
+    }
+
+    /// Overpass QL query of the source.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Layer the features are loaded into.
+    pub fn layer(&self) -> &Arc<RwLock<OsmLayer<S>>> {
+        &self.layer
+    }
+
+    /// Runs the query and applies the result to the layer.
+    ///
+    /// Returns an error if the query contains the `{{bbox}}` placeholder.
+    pub async fn refresh(&self) -> Result<FeatureDiff, GalileoError> {
+        if self.query.contains(BBOX_PLACEHOLDER) {
+            return Err(GalileoError::Generic(
+                "the query requires a bounding box".to_string(),
+            ));
+        }
+
+        self.run(self.query.clone()).await
+    }
+
+    /// Runs the query with the given bounding box and applies the result to the layer. The coordinates of the box are
+    /// in degrees, with longitude as *x* and latitude as *y*.
+    pub async fn refresh_in(&self, bbox: Rect) -> Result<FeatureDiff, GalileoError> {
+        let bbox = format!(
+            "{},{},{},{}",
+            bbox.y_min(),
+            bbox.x_min(),
+            bbox.y_max(),
+            bbox.x_max()
+        );
+        self.run(self.query.replace(BBOX_PLACEHOLDER, &bbox)).await
+    }
+
+    /// Runs the query with the bounding box of the area visible in the `view` and applies the result to the layer.
+    pub async fn refresh_for_view(&self, view: &MapView) -> Result<FeatureDiff, GalileoError> {
+        let bbox = view_bbox(view).ok_or_else(|| {
+            GalileoError::Generic("cannot calculate the area of the view".to_string())
+        })?;
+        self.refresh_in(bbox).await
+    }
+
+    async fn run(&self, query: String) -> Result<FeatureDiff, GalileoError> {
+        let url = format!("{}?data={}", self.endpoint, percent_encode(&query));
+        let bytes = self.http_client.get(&url).await?;
+        let features = parse_features(&bytes)?;
+
+        let mut layer = self.layer.write().expect("lock is poisoned");
+        let diff = apply_features(layer.features_mut(), features, |feature| Some(feature.id()));
+
+        if !diff.is_empty() {
+            if let Some(messenger) = &*layer.messenger.read().expect("lock is poisoned") {
+                messenger.request_redraw();
+            }
+        }
+
+        Ok(diff)
+    }
+}
+
+fn view_bbox(view: &MapView) -> Option<Rect> {
+    let footprint = view.footprint()?;
+    footprint
+        .outer_contour
+        .points
+        .iter()
+        .map(|p| Rect::new(p.lon(), p.lat(), p.lon(), p.lat()))
+        .reduce(|a, b| a.merge(b))
+}
+
+#[derive(Debug, Deserialize)]
+struct OverpassResponse {
+    elements: Vec<OverpassElement>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OverpassElement {
+    #[serde(rename = "type")]
+    element_type: String,
+    id: i64,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    center: Option<LatLon>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    #[serde(default)]
+    geometry: Vec<Option<LatLon>>,
+    #[serde(default)]
+    members: Vec<OverpassMember>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OverpassMember {
+    #[serde(rename = "type")]
+    member_type: String,
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    geometry: Vec<Option<LatLon>>,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+struct LatLon {
+    lat: f64,
+    lon: f64,
+}
+
+/// Converts the geometry of a way. Ways with nodes missing from the result are skipped.
+fn points(geometry: &[Option<LatLon>]) -> Option<Vec<GeoPoint2d>> {
+    geometry
+        .iter()
+        .map(|p| p.map(|p| GeoPoint2d::latlon(p.lat, p.lon)))
+        .collect()
+}
+
+fn parse_features(bytes: &[u8]) -> Result<Vec<OsmFeature>, GalileoError> {
+    let response: OverpassResponse = serde_json::from_slice(bytes)
+        .map_err(|err| GalileoError::Generic(format!("invalid Overpass response: {err}")))?;
+
+    Ok(response
+        .elements
+        .into_iter()
+        .filter_map(element_to_feature)
+        .collect())
+}
+
+fn element_to_feature(element: OverpassElement) -> Option<OsmFeature> {
+    let tags: Vec<(String, String)> = element.tags.into_iter().collect();
+    let center = element
+        .center
+        .map(|c| Geom::Point(GeoPoint2d::latlon(c.lat, c.lon)));
+
+    let (id, geometry) = match element.element_type.as_str() {
+        "node" if !tags.is_empty() => (
+            OsmId::Node(element.id),
+            Geom::Point(GeoPoint2d::latlon(element.lat?, element.lon?)),
+        ),
+        "way" => {
+            let geometry = points(&element.geometry)
+                .and_then(|points| way_geometry(&tags, points))
+                .or(center)?;
+            (OsmId::Way(element.id), geometry)
+        }
+        "relation" => {
+            let members = element
+                .members
+                .iter()
+                .filter(|member| member.member_type == "way")
+                .filter_map(|member| Some((points(&member.geometry)?, member.role == "inner")))
+                .collect();
+            let geometry = RelationKind::from_tags(&tags)
+                .and_then(|kind| relation_geometry(kind, members))
+                .or(center)?;
+            (OsmId::Relation(element.id), geometry)
+        }
+        _ => return None,
+    };
+
+    Some(OsmFeature::new(id, geometry, tags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::Feature;
+
+    #[test]
+    fn parse_overpass_json() {
+        let json = r#"{"version": 0.6, "elements": [
+            {"type": "node", "id": 1, "lat": 50.0, "lon": 10.0, "tags": {"amenity": "drinking_water"}},
+            {"type": "node", "id": 2, "lat": 50.0, "lon": 10.0},
+            {"type": "way", "id": 3, "tags": {"building": "yes"}, "geometry": [
+                {"lat": 0.0, "lon": 0.0}, {"lat": 0.0, "lon": 1.0}, {"lat": 1.0, "lon": 1.0}, {"lat": 0.0, "lon": 0.0}
+            ]},
+            {"type": "way", "id": 4, "tags": {"highway": "primary"}, "center": {"lat": 5.0, "lon": 6.0}}
+        ]}"#;
+
+        let features = parse_features(json.as_bytes()).unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0].id(), OsmId::Node(1));
+        assert_eq!(features[0].tag("amenity"), Some("drinking_water"));
+        assert!(matches!(features[1].geometry(), Geom::Polygon(_)));
+        assert!(matches!(features[2].geometry(), Geom::Point(_)));
+
+        assert!(parse_features(b"<html>error</html>").is_err());
+    }
+
+    #[test]
+    fn query_encoding() {
+        assert_eq!(
+            percent_encode("node[a=b](1,2);out;"),
+            "node%5Ba%3Db%5D%281%2C2%29%3Bout%3B"
+        );
+    }
+}
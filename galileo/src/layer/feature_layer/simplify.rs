@@ -0,0 +1,195 @@
+//! Simplification of projected geometries for rendering at coarse resolutions.
+
+use galileo_types::cartesian::Point3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{
+    ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon,
+};
+use galileo_types::{Contour as _, MultiContour as _, MultiPoint as _, MultiPolygon as _};
+
+/// Returns a simplified copy of the geometry, in which no point deviates from the original shape by more than
+/// `tolerance` in XY plane. Uses Douglas-Peucker algorithm.
+///
+/// Closed contours are never simplified to less than 3 points, and open contours to less than 2 points. Holes of
+/// polygons that would degenerate are removed.
+pub(super) fn simplify_geom(geom: &Geom<Point3d>, tolerance: f64) -> Geom<Point3d> {
+    match geom {
+        Geom::Point(point) => Geom::Point(*point),
+        Geom::MultiPoint(points) => Geom::MultiPoint(MultiPoint::from(
+            points.iter_points().copied().collect::<Vec<_>>(),
+        )),
+        Geom::Contour(contour) => Geom::Contour(simplify_contour(contour, tolerance)),
+        Geom::MultiContour(contours) => Geom::MultiContour(MultiContour::from(
+            contours
+                .contours()
+                .map(|c| simplify_contour(c, tolerance))
+                .collect::<Vec<_>>(),
+        )),
+        Geom::Polygon(polygon) => Geom::Polygon(simplify_polygon(polygon, tolerance)),
+        Geom::MultiPolygon(polygons) => Geom::MultiPolygon(MultiPolygon::from(
+            polygons
+                .polygons()
+                .map(|p| simplify_polygon(p, tolerance))
+                .collect::<Vec<_>>(),
+        )),
+    }
+}
+
+fn simplify_contour(contour: &Contour<Point3d>, tolerance: f64) -> Contour<Point3d> {
+    let points: Vec<Point3d> = contour.iter_points().copied().collect();
+    if contour.is_closed() {
+        let simplified = simplify_closed(&points, tolerance).unwrap_or(points);
+        Contour::closed(simplified)
+    } else {
+        Contour::open(simplify_points(&points, tolerance))
+    }
+}
+
+fn simplify_polygon(polygon: &Polygon<Point3d>, tolerance: f64) -> Polygon<Point3d> {
+    let outer = &polygon.outer_contour.points;
+    let outer_contour =
+        ClosedContour::new(simplify_closed(outer, tolerance).unwrap_or_else(|| outer.clone()));
+
+    let inner_contours = polygon
+        .inner_contours
+        .iter()
+        .filter_map(|c| simplify_closed(&c.points, tolerance).map(ClosedContour::new))
+        .collect();
+
+    Polygon::new(outer_contour, inner_contours)
+}
+
+/// Simplifies a closed ring given without the closing point. Returns `None` if the ring degenerates.
+pub(crate) fn simplify_closed(points: &[Point3d], tolerance: f64) -> Option<Vec<Point3d>> {
+    if points.len() <= 3 {
+        return Some(points.to_vec());
+    }
+
+    let mut ring = points.to_vec();
+    ring.push(points[0]);
+
+    let mut simplified = simplify_points(&ring, tolerance);
+    simplified.pop();
+
+    if simplified.len() < 3 {
+        None
+    } else {
+        Some(simplified)
+    }
+}
+
+pub(crate) fn simplify_points(points: &[Point3d], tolerance: f64) -> Vec<Point3d> {
+    if points.len() <= 2 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        if end <= start + 1 {
+            continue;
+        }
+
+        let mut max_distance = 0.0;
+        let mut max_index = start;
+        for i in (start + 1)..end {
+            let distance = segment_distance(&points[i], &points[start], &points[end]);
+            if distance > max_distance {
+                max_distance = distance;
+                max_index = i;
+            }
+        }
+
+        if max_distance > tolerance {
+            keep[max_index] = true;
+            stack.push((start, max_index));
+            stack.push((max_index, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(p, keep)| keep.then_some(*p))
+        .collect()
+}
+
+/// Distance in XY plane from the point `p` to the segment `a-b`.
+fn segment_distance(p: &Point3d, a: &Point3d, b: &Point3d) -> f64 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let length_sq = dx * dx + dy * dy;
+
+    let (x, y) = if length_sq == 0.0 {
+        (a.x, a.y)
+    } else {
+        let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_sq).clamp(0.0, 1.0);
+        (a.x + t * dx, a.y + t * dy)
+    };
+
+    ((p.x - x).powi(2) + (p.y - y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(x: f64, y: f64) -> Point3d {
+        Point3d::new(x, y, 0.0)
+    }
+
+    #[test]
+    fn simplifies_almost_straight_line() {
+        let points = vec![
+            p(0.0, 0.0),
+            p(1.0, 0.1),
+            p(2.0, -0.1),
+            p(3.0, 5.0),
+            p(4.0, 0.0),
+        ];
+        let simplified = simplify_points(&points, 0.5);
+        assert_eq!(
+            simplified,
+            vec![p(0.0, 0.0), p(2.0, -0.1), p(3.0, 5.0), p(4.0, 0.0)]
+        );
+
+        let simplified = simplify_points(&points, 10.0);
+        assert_eq!(simplified, vec![p(0.0, 0.0), p(4.0, 0.0)]);
+    }
+
+    #[test]
+    fn closed_contour_does_not_degenerate() {
+        let square = vec![
+            p(0.0, 0.0),
+            p(1.0, 0.0),
+            p(1.0, 1.0),
+            p(0.5, 1.01),
+            p(0.0, 1.0),
+        ];
+        assert_eq!(
+            simplify_closed(&square, 0.1),
+            Some(vec![p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0), p(0.0, 1.0)])
+        );
+        assert_eq!(simplify_closed(&square, 100.0), None);
+    }
+
+    #[test]
+    fn degenerate_holes_are_removed() {
+        let polygon = Polygon::new(
+            ClosedContour::new(vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)]),
+            vec![ClosedContour::new(vec![
+                p(1.0, 1.0),
+                p(1.1, 1.0),
+                p(1.1, 1.1),
+                p(1.0, 1.1),
+            ])],
+        );
+
+        let simplified = simplify_polygon(&polygon, 1.0);
+        assert_eq!(simplified.outer_contour.points.len(), 4);
+        assert!(simplified.inner_contours.is_empty());
+    }
+}
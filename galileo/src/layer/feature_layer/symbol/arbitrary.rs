@@ -5,9 +5,13 @@ use galileo_types::cartesian::CartesianPoint3d;
 use galileo_types::geometry::Geom;
 use galileo_types::impls::{Contour, Polygon};
 use num_traits::AsPrimitive;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Renders any type of the geometry with the set inner symbols.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ArbitraryGeometrySymbol {
     point: CirclePointSymbol,
     contour: SimpleContourSymbol,
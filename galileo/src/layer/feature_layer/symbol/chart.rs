@@ -0,0 +1,311 @@
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::render::point_paint::{PointAlignment, PointPaint};
+use crate::render::render_bundle::RenderPrimitive;
+use crate::Color;
+use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use galileo_types::MultiPoint;
+use nalgebra::Point2;
+use num_traits::AsPrimitive;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// Maximum angle between two vertices of an arc of a ring chart segment.
+const ARC_STEP: f32 = PI / 32.0;
+
+/// Type of the chart drawn by [`ChartPointSymbol`]. All sizes are in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ChartType {
+    /// Circle divided into slices proportional to the values, starting at the top and going clockwise.
+    Pie {
+        /// Diameter of the circle.
+        diameter: f32,
+    },
+    /// Same as [`ChartType::Pie`], but with a hole in the middle.
+    Ring {
+        /// Outer diameter of the ring.
+        diameter: f32,
+        /// Diameter of the hole.
+        inner_diameter: f32,
+    },
+    /// Bars standing side by side on the point, with heights proportional to the values.
+    Bars {
+        /// Width of a single bar.
+        bar_width: f32,
+        /// Height of the bar with the value of `max_value`.
+        max_height: f32,
+        /// Value corresponding to the `max_height` of a bar. Larger values are not clipped.
+        max_value: f64,
+    },
+}
+
+/// Renders a point as a statistical chart (pie, ring or bar chart) with the segments defined by the values of the
+/// feature attributes.
+///
+/// Segment `i` of the chart is drawn with the color `i` of the symbol. If there are more values than colors, the
+/// colors are repeated. Negative values are ignored by pie and ring charts, and are drawn as bars going down by bar
+/// charts.
+///
+/// ```
+/// use galileo::symbol::{ChartPointSymbol, ChartType};
+/// use galileo::Color;
+///
+/// struct District {
+///     votes: [u32; 3],
+/// }
+///
+/// let symbol = ChartPointSymbol::new(
+///     ChartType::Pie { diameter: 30.0 },
+///     vec![Color::RED, Color::BLUE, Color::GREEN],
+///     |district: &District| district.votes.iter().map(|&v| v as f64).collect(),
+/// )
+/// .with_outline(Color::BLACK, 1.0);
+/// ```
+pub struct ChartPointSymbol<F> {
+    chart_type: ChartType,
+    colors: Vec<Color>,
+    values: Box<dyn Fn(&F) -> Vec<f64> + Send + Sync>,
+    outline: Option<(Color, f32)>,
+    alignment: PointAlignment,
+}
+
+impl<F> ChartPointSymbol<F> {
+    /// Creates a new symbol. The `values` function returns the values of the chart segments for a feature.
+    pub fn new(
+        chart_type: ChartType,
+        colors: Vec<Color>,
+        values: impl Fn(&F) -> Vec<f64> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            chart_type,
+            colors,
+            values: Box::new(values),
+            outline: None,
+            alignment: PointAlignment::Screen,
+        }
+    }
+
+    /// Draws the segments of the chart with an outline of the given color and width.
+    pub fn with_outline(mut self, color: Color, width: f32) -> Self {
+        self.outline = Some((color, width));
+        self
+    }
+
+    /// Sets the orientation of the chart in rotated and tilted views.
+    pub fn with_alignment(mut self, alignment: PointAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Type of the chart.
+    pub fn chart_type(&self) -> ChartType {
+        self.chart_type
+    }
+
+    fn segment_color(&self, index: usize) -> Color {
+        if self.colors.is_empty() {
+            return Color::BLACK;
+        }
+
+        self.colors[index % self.colors.len()]
+    }
+
+    /// Paints for all the segments of the chart.
+    fn segment_paints(&self, values: &[f64]) -> Vec<PointPaint<'static>> {
+        let paints: Vec<PointPaint> = match self.chart_type {
+            ChartType::Pie { diameter } => self
+                .slices(values)
+                .into_iter()
+                .map(|(color, start, end)| PointPaint::sector(color, diameter, start, end))
+                .collect(),
+            ChartType::Ring {
+                diameter,
+                inner_diameter,
+            } => self
+                .slices(values)
+                .into_iter()
+                .map(|(color, start, end)| {
+                    PointPaint::owned_shape(
+                        color,
+                        ring_segment(diameter / 2.0, inner_diameter / 2.0, start, end),
+                        1.0,
+                    )
+                })
+                .collect(),
+            ChartType::Bars {
+                bar_width,
+                max_height,
+                max_value,
+            } => {
+                let x_min = -bar_width * values.len() as f32 / 2.0;
+                values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, value)| value.is_finite() && **value != 0.0 && max_value > 0.0)
+                    .map(|(index, value)| {
+                        let height = (value / max_value) as f32 * max_height;
+                        let left = x_min + bar_width * index as f32;
+                        let right = left + bar_width;
+                        let bar = ClosedContour::new(vec![
+                            Point2::new(left, 0.0),
+                            Point2::new(left, height),
+                            Point2::new(right, height),
+                            Point2::new(right, 0.0),
+                        ]);
+                        PointPaint::owned_shape(self.segment_color(index), bar, 1.0)
+                    })
+                    .collect()
+            }
+        };
+
+        paints
+            .into_iter()
+            .map(|paint: PointPaint| {
+                let paint = paint.with_alignment(self.alignment);
+                match self.outline {
+                    Some((color, width)) => paint.with_outline(color, width),
+                    None => paint,
+                }
+            })
+            .collect()
+    }
+
+    /// Colors and angles of the pie slices for the values.
+    fn slices(&self, values: &[f64]) -> Vec<(Color, f32, f32)> {
+        let is_valid = |value: &f64| value.is_finite() && *value > 0.0;
+        let total: f64 = values.iter().filter(|v| is_valid(v)).sum();
+        if total <= 0.0 {
+            return vec![];
+        }
+
+        let mut slices = vec![];
+        let mut accumulated = 0.0;
+        for (index, value) in values.iter().enumerate() {
+            if !is_valid(value) {
+                continue;
+            }
+
+            let start = accumulated / total;
+            accumulated += value;
+            let end = accumulated / total;
+
+            // Slices go clockwise from the top, while the angles of the sectors go counterclockwise.
+            slices.push((
+                self.segment_color(index),
+                FRAC_PI_2 - end as f32 * TAU,
+                FRAC_PI_2 - start as f32 * TAU,
+            ));
+        }
+
+        slices
+    }
+}
+
+/// Contour of a ring segment between the given angles.
+fn ring_segment(
+    radius: f32,
+    inner_radius: f32,
+    start_angle: f32,
+    end_angle: f32,
+) -> ClosedContour<Point2<f32>> {
+    let steps = ((end_angle - start_angle).abs() / ARC_STEP).ceil().max(1.0) as usize;
+    let angle_step = (end_angle - start_angle) / steps as f32;
+    let arc = |radius: f32| {
+        (0..=steps).map(move |step| {
+            let angle = start_angle + angle_step * step as f32;
+            Point2::new(angle.cos() * radius, angle.sin() * radius)
+        })
+    };
+
+    let mut points: Vec<_> = arc(radius).collect();
+    points.extend(arc(inner_radius.clamp(0.0, radius)).rev());
+
+    ClosedContour::new(points)
+}
+
+impl<F> Symbol<F> for ChartPointSymbol<F> {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let paints = self.segment_paints(&(self.values)(feature));
+        let points: Vec<&P> = match geometry {
+            Geom::Point(point) => vec![point],
+            Geom::MultiPoint(points) => points.iter_points().collect(),
+            _ => vec![],
+        };
+
+        points
+            .into_iter()
+            .flat_map(|point| {
+                paints
+                    .iter()
+                    .map(move |paint| RenderPrimitive::new_point_ref(point, paint.clone()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::point_paint::PointShape;
+
+    #[test]
+    fn pie_slices_follow_values() {
+        let symbol = ChartPointSymbol::new(
+            ChartType::Pie { diameter: 10.0 },
+            vec![Color::RED, Color::BLUE],
+            |values: &Vec<f64>| values.clone(),
+        );
+
+        let paints = symbol.segment_paints(&[1.0, 0.0, -2.0, 3.0]);
+        assert_eq!(paints.len(), 2);
+
+        let PointShape::Sector(first) = &paints[0].shape else {
+            panic!("expected sector");
+        };
+        assert!((first.end_angle - FRAC_PI_2).abs() < 0.0001);
+        assert!((first.end_angle - first.start_angle - TAU / 4.0).abs() < 0.0001);
+        assert_eq!(first.fill.center_color, Color::RED);
+
+        let PointShape::Sector(second) = &paints[1].shape else {
+            panic!("expected sector");
+        };
+        assert!((second.end_angle - first.start_angle).abs() < 0.0001);
+        assert_eq!(second.fill.center_color, Color::BLUE);
+
+        assert!(symbol.segment_paints(&[0.0, -1.0]).is_empty());
+    }
+
+    #[test]
+    fn bars_are_centered_on_point() {
+        let symbol = ChartPointSymbol::new(
+            ChartType::Bars {
+                bar_width: 4.0,
+                max_height: 20.0,
+                max_value: 10.0,
+            },
+            vec![Color::RED],
+            |values: &Vec<f64>| values.clone(),
+        );
+
+        let paints = symbol.segment_paints(&[5.0, 10.0]);
+        assert_eq!(paints.len(), 2);
+
+        let PointShape::FreeShape { shape, .. } = &paints[0].shape else {
+            panic!("expected shape");
+        };
+        assert_eq!(shape.points[0], Point2::new(-4.0, 0.0));
+        assert_eq!(shape.points[2], Point2::new(0.0, 10.0));
+    }
+}
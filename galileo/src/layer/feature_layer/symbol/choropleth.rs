@@ -0,0 +1,459 @@
+use crate::color::palette::{BLUES, GREENS, MAGMA, RD_YL_BU, REDS, SPECTRAL, VIRIDIS, YL_OR_RD};
+use crate::color::ColorSpace;
+use crate::layer::feature_layer::symbol::{SimplePolygonSymbol, Symbol};
+use crate::render::render_bundle::RenderPrimitive;
+use crate::Color;
+use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use num_traits::AsPrimitive;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Method of splitting a range of values into classes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Classification {
+    /// Classes of equal width between the minimum and maximum values.
+    EqualInterval,
+    /// Classes with (approximately) equal number of values in each.
+    Quantiles,
+    /// Jenks natural breaks: classes that minimize the variance of the values inside the classes.
+    #[default]
+    NaturalBreaks,
+}
+
+impl Classification {
+    /// Calculates the boundaries of the classes for the given values. The result contains `classes + 1` values from the
+    /// minimum to the maximum value, where the class `i` contains the values between the boundaries `i` and `i + 1`.
+    ///
+    /// `NaN` values are ignored. Returns an empty vector if there are no values or `classes` is 0. For natural breaks,
+    /// the number of classes is limited by the number of values.
+    pub fn breaks(&self, values: &[f64], classes: usize) -> Vec<f64> {
+        let mut sorted: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+        if sorted.is_empty() || classes == 0 {
+            return vec![];
+        }
+        sorted.sort_by(f64::total_cmp);
+
+        let min = sorted[0];
+        let max = sorted[sorted.len() - 1];
+        match self {
+            Classification::EqualInterval => (0..=classes)
+                .map(|i| min + (max - min) * i as f64 / classes as f64)
+                .collect(),
+            Classification::Quantiles => (0..=classes)
+                .map(|i| quantile(&sorted, i as f64 / classes as f64))
+                .collect(),
+            Classification::NaturalBreaks => jenks_breaks(&sorted, classes.min(sorted.len())),
+        }
+    }
+}
+
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Jenks natural breaks optimization with dynamic programming.
+fn jenks_breaks(sorted: &[f64], classes: usize) -> Vec<f64> {
+    let n = sorted.len();
+    // Indices in the matrices are 1-based, as in the original algorithm description.
+    let mut lower_limits = vec![vec![0usize; classes + 1]; n + 1];
+    let mut variances = vec![vec![f64::INFINITY; classes + 1]; n + 1];
+    for class in 1..=classes {
+        lower_limits[1][class] = 1;
+        variances[1][class] = 0.0;
+    }
+
+    for l in 2..=n {
+        let mut sum = 0.0;
+        let mut sum_squares = 0.0;
+        let mut variance = 0.0;
+        for m in 1..=l {
+            let lower = l - m + 1;
+            let value = sorted[lower - 1];
+            sum += value;
+            sum_squares += value * value;
+            variance = sum_squares - sum * sum / m as f64;
+
+            let prev = lower - 1;
+            if prev != 0 {
+                for class in 2..=classes {
+                    let candidate = variance + variances[prev][class - 1];
+                    if variances[l][class] >= candidate {
+                        lower_limits[l][class] = lower;
+                        variances[l][class] = candidate;
+                    }
+                }
+            }
+        }
+
+        lower_limits[l][1] = 1;
+        variances[l][1] = variance;
+    }
+
+    let mut breaks = vec![0.0; classes + 1];
+    breaks[0] = sorted[0];
+    breaks[classes] = sorted[n - 1];
+    let mut k = n;
+    for class in (2..=classes).rev() {
+        let lower = lower_limits[k][class];
+        breaks[class - 1] = sorted[lower - 2];
+        k = lower - 1;
+    }
+
+    breaks
+}
+
+/// Sequence of colors that values are mapped to. Colors between the stops are interpolated linearly in the
+/// [color space](ColorSpace) of the ramp (RGB by default).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawColorRamp"))]
+pub struct ColorRamp {
+    stops: Vec<Color>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    color_space: ColorSpace,
+}
+
+/// Unvalidated [`ColorRamp`] used for deserialization.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawColorRamp {
+    stops: Vec<Color>,
+    #[serde(default)]
+    color_space: ColorSpace,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawColorRamp> for ColorRamp {
+    type Error = String;
+
+    fn try_from(raw: RawColorRamp) -> Result<Self, Self::Error> {
+        if raw.stops.is_empty() {
+            return Err("color ramp must have at least one stop".to_string());
+        }
+
+        Ok(Self::new(raw.stops).with_color_space(raw.color_space))
+    }
+}
+
+impl ColorRamp {
+    /// Creates a new ramp from the colors of its stops, distributed evenly along the ramp.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stops` is empty.
+    pub fn new(stops: Vec<Color>) -> Self {
+        assert!(!stops.is_empty(), "color ramp must have at least one stop");
+        Self {
+            stops,
+            color_space: ColorSpace::default(),
+        }
+    }
+
+    /// Creates a ramp from a [named palette](crate::palette::by_name).
+    pub fn by_name(name: &str) -> Option<Self> {
+        crate::palette::by_name(name).map(|stops| Self::new(stops.to_vec()))
+    }
+
+    /// Sets the color space the colors between the stops are interpolated in.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Perceptually uniform sequential ramp from dark blue to yellow.
+    pub fn viridis() -> Self {
+        Self::new(VIRIDIS.to_vec())
+    }
+
+    /// Perceptually uniform sequential ramp from black through purple to light yellow.
+    pub fn magma() -> Self {
+        Self::new(MAGMA.to_vec())
+    }
+
+    /// ColorBrewer sequential `Blues` ramp.
+    pub fn blues() -> Self {
+        Self::new(BLUES.to_vec())
+    }
+
+    /// ColorBrewer sequential `Greens` ramp.
+    pub fn greens() -> Self {
+        Self::new(GREENS.to_vec())
+    }
+
+    /// ColorBrewer sequential `Reds` ramp.
+    pub fn reds() -> Self {
+        Self::new(REDS.to_vec())
+    }
+
+    /// ColorBrewer sequential `YlOrRd` ramp.
+    pub fn yellow_orange_red() -> Self {
+        Self::new(YL_OR_RD.to_vec())
+    }
+
+    /// ColorBrewer diverging `RdYlBu` ramp.
+    pub fn red_yellow_blue() -> Self {
+        Self::new(RD_YL_BU.to_vec())
+    }
+
+    /// ColorBrewer diverging `Spectral` ramp.
+    pub fn spectral() -> Self {
+        Self::new(SPECTRAL.to_vec())
+    }
+
+    /// Returns the ramp with the colors in reverse order.
+    pub fn reversed(mut self) -> Self {
+        self.stops.reverse();
+        self
+    }
+
+    /// Colors of the stops of the ramp.
+    pub fn stops(&self) -> &[Color] {
+        &self.stops
+    }
+
+    /// Color space the colors between the stops are interpolated in.
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// Color at the position `t` along the ramp, where 0 is the first stop and 1 is the last one. Values outside of
+    /// this range are clamped.
+    pub fn sample(&self, t: f64) -> Color {
+        let position = t.clamp(0.0, 1.0) * (self.stops.len() - 1) as f64;
+        let index = (position.floor() as usize).min(self.stops.len() - 1);
+        let next = (index + 1).min(self.stops.len() - 1);
+        self.stops[index].interpolate(&self.stops[next], position - index as f64, self.color_space)
+    }
+
+    /// Returns `count` colors evenly distributed along the ramp.
+    pub fn colors(&self, count: usize) -> Vec<Color> {
+        match count {
+            0 => vec![],
+            1 => vec![self.sample(0.5)],
+            _ => (0..count)
+                .map(|i| self.sample(i as f64 / (count - 1) as f64))
+                .collect(),
+        }
+    }
+}
+
+/// Entry of the legend of a [`ChoroplethSymbol`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntry {
+    /// Lower boundary of the class.
+    pub min: f64,
+    /// Upper boundary of the class.
+    pub max: f64,
+    /// Fill color of the class.
+    pub color: Color,
+    /// Text describing the class, e.g. `10 - 20`.
+    pub label: String,
+}
+
+/// Renders polygons filled with the color of the class the value of the feature falls into.
+///
+/// ```
+/// use galileo::layer::feature_layer::symbol::{ChoroplethSymbol, Classification, ColorRamp};
+///
+/// struct Country {
+///     population: f64,
+/// }
+///
+/// let countries = vec![Country { population: 1.0e6 }, Country { population: 5.0e7 }];
+/// let symbol = ChoroplethSymbol::new(
+///     &countries,
+///     |country: &Country| Some(country.population),
+///     Classification::Quantiles,
+///     5,
+///     &ColorRamp::viridis(),
+/// );
+///
+/// assert_eq!(symbol.legend().len(), 5);
+/// ```
+pub struct ChoroplethSymbol<F> {
+    value: Box<dyn Fn(&F) -> Option<f64> + Send + Sync>,
+    breaks: Vec<f64>,
+    symbols: Vec<SimplePolygonSymbol>,
+    no_data: Option<SimplePolygonSymbol>,
+}
+
+impl<F> ChoroplethSymbol<F> {
+    /// Classifies the values of the features and assigns the colors of the ramp to the classes.
+    ///
+    /// Features for which `value` returns `None` are not rendered unless [`ChoroplethSymbol::with_no_data_color`] is
+    /// set.
+    pub fn new<'a>(
+        features: impl IntoIterator<Item = &'a F>,
+        value: impl Fn(&F) -> Option<f64> + Send + Sync + 'static,
+        classification: Classification,
+        classes: usize,
+        ramp: &ColorRamp,
+    ) -> Self
+    where
+        F: 'a,
+    {
+        let values: Vec<f64> = features.into_iter().filter_map(&value).collect();
+        let breaks = classification.breaks(&values, classes);
+        let colors = ramp.colors(breaks.len().saturating_sub(1));
+        Self::from_breaks(breaks, colors, value)
+    }
+
+    /// Creates a symbol with the given class boundaries and colors of the classes. The number of colors must be one
+    /// less than the number of boundaries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of colors does not match the number of boundaries.
+    pub fn from_breaks(
+        breaks: Vec<f64>,
+        colors: Vec<Color>,
+        value: impl Fn(&F) -> Option<f64> + Send + Sync + 'static,
+    ) -> Self {
+        assert_eq!(
+            colors.len(),
+            breaks.len().saturating_sub(1),
+            "number of colors must match the number of classes"
+        );
+
+        Self {
+            value: Box::new(value),
+            breaks,
+            symbols: colors.into_iter().map(SimplePolygonSymbol::new).collect(),
+            no_data: None,
+        }
+    }
+
+    /// Sets the outline of the polygons of all the classes.
+    pub fn with_stroke(mut self, color: Color, width: f64) -> Self {
+        for symbol in self.symbols.iter_mut().chain(self.no_data.iter_mut()) {
+            *symbol = symbol.with_stroke_color(color).with_stroke_width(width);
+        }
+        self
+    }
+
+    /// Sets the color of the features without a value.
+    pub fn with_no_data_color(mut self, color: Color) -> Self {
+        let mut symbol = SimplePolygonSymbol::new(color);
+        if let Some(class_symbol) = self.symbols.first() {
+            symbol = symbol
+                .with_stroke_color(class_symbol.stroke_color)
+                .with_stroke_width(class_symbol.stroke_width);
+        }
+        self.no_data = Some(symbol);
+        self
+    }
+
+    /// Boundaries of the classes.
+    pub fn breaks(&self) -> &[f64] {
+        &self.breaks
+    }
+
+    /// Index of the class the value falls into. Values outside of the classified range are put into the first or the
+    /// last class.
+    pub fn class_of(&self, value: f64) -> Option<usize> {
+        if self.symbols.is_empty() || value.is_nan() {
+            return None;
+        }
+
+        let inner_breaks = &self.breaks[1..self.breaks.len() - 1];
+        Some(inner_breaks.iter().filter(|b| value > **b).count())
+    }
+
+    /// Description of the classes for displaying the legend of the map.
+    pub fn legend(&self) -> Vec<LegendEntry> {
+        self.breaks
+            .windows(2)
+            .zip(&self.symbols)
+            .map(|(range, symbol)| LegendEntry {
+                min: range[0],
+                max: range[1],
+                color: symbol.fill_color,
+                label: format!("{} - {}", format_value(range[0]), format_value(range[1])),
+            })
+            .collect()
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+impl<F> Symbol<F> for ChoroplethSymbol<F> {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let symbol = match (self.value)(feature).and_then(|value| self.class_of(value)) {
+            Some(class) => &self.symbols[class],
+            None => match &self.no_data {
+                Some(symbol) => symbol,
+                None => return vec![],
+            },
+        };
+
+        symbol.render(feature, geometry, min_resolution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classification_breaks() {
+        let values = [21.0, 1.0, 2.0, 3.0, 10.0, 11.0, 12.0, 20.0, 22.0, f64::NAN];
+
+        assert_eq!(
+            Classification::NaturalBreaks.breaks(&values, 3),
+            vec![1.0, 3.0, 12.0, 22.0]
+        );
+        assert_eq!(
+            Classification::EqualInterval.breaks(&values, 3),
+            vec![1.0, 8.0, 15.0, 22.0]
+        );
+        assert_eq!(
+            Classification::Quantiles.breaks(&values, 2),
+            vec![1.0, 11.0, 22.0]
+        );
+        assert!(Classification::Quantiles.breaks(&[], 2).is_empty());
+    }
+
+    #[test]
+    fn ramp_sampling() {
+        let ramp = ColorRamp::new(vec![Color::BLACK, Color::WHITE]);
+        assert_eq!(ramp.sample(0.0), Color::BLACK);
+        assert_eq!(ramp.sample(2.0), Color::WHITE);
+        assert_eq!(ramp.sample(0.5), Color::rgba(128, 128, 128, 255));
+        assert_eq!(ramp.colors(3)[1], Color::rgba(128, 128, 128, 255));
+    }
+
+    #[test]
+    fn class_of_value() {
+        let symbol = ChoroplethSymbol::<f64>::from_breaks(
+            vec![0.0, 10.0, 20.0],
+            vec![Color::RED, Color::BLUE],
+            |v| Some(*v),
+        );
+        assert_eq!(symbol.class_of(-5.0), Some(0));
+        assert_eq!(symbol.class_of(10.0), Some(0));
+        assert_eq!(symbol.class_of(15.0), Some(1));
+        assert_eq!(symbol.class_of(100.0), Some(1));
+        assert_eq!(symbol.legend()[1].label, "10 - 20");
+    }
+}
@@ -1,26 +1,69 @@
 use crate::layer::feature_layer::symbol::Symbol;
 use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{LineCap, LinePaint};
+use crate::render::{ColorGradient, LineCap, LinePaint, LinePattern, SizeUnit};
 use crate::Color;
 use galileo_types::cartesian::CartesianPoint3d;
 use galileo_types::geometry::Geom;
 use galileo_types::impls::{Contour, Polygon};
 use galileo_types::MultiContour;
 use num_traits::AsPrimitive;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Renders a contour as a line of fixed width.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SimpleContourSymbol {
     /// Color of the line.
     pub color: Color,
-    /// Width of the line in pixels.
+    /// Width of the line in `width_unit`s.
     pub width: f64,
+    /// Units of the line width.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub width_unit: SizeUnit,
+    /// Dash or arrow pattern of the line. If not set, the line is solid.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pattern: Option<LinePattern>,
+    /// Gradient of the line color along the line. If set, it is used instead of the `color`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub gradient: Option<ColorGradient>,
 }
 
 impl SimpleContourSymbol {
     /// Creates a new instance.
     pub fn new(color: Color, width: f64) -> Self {
-        Self { color, width }
+        Self {
+            color,
+            width,
+            width_unit: SizeUnit::Pixels,
+            pattern: None,
+            gradient: None,
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the width of the line set in the given units.
+    pub fn with_width_unit(&self, width_unit: SizeUnit) -> Self {
+        Self {
+            width_unit,
+            ..*self
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the color changing along the line by the given
+    /// gradient.
+    pub fn with_gradient(&self, gradient: ColorGradient) -> Self {
+        Self {
+            gradient: Some(gradient),
+            ..*self
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given line pattern.
+    pub fn with_pattern(&self, pattern: LinePattern) -> Self {
+        Self {
+            pattern: Some(pattern),
+            ..*self
+        }
     }
 }
 
@@ -39,7 +82,11 @@ impl<F> Symbol<F> for SimpleContourSymbol {
             color: self.color,
             width: self.width,
             offset: 0.0,
+            width_unit: self.width_unit,
             line_cap: LineCap::Butt,
+            pattern: self.pattern,
+            gradient: self.gradient,
+            zoom: None,
         };
 
         match geometry {
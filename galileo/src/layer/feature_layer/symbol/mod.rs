@@ -1,18 +1,31 @@
 //! Symbols are used to render [`Features`](super::Feature) in a [`FeatureLayer`](super::FeatureLayer).
 //! [`Symbol`] trait is designed to be easy to implement, so an application may provide rendering logic for the
 //! features it uses. But a few simple implementations are provided for convenience.
+//!
+//! With the `serde` feature, the built-in symbols that do not contain closures or images can be serialized, e.g. to
+//! store styles in a database. Use [`VersionedSymbol`] to store the version of the format together with the symbol.
 
 use num_traits::AsPrimitive;
 
 mod arbitrary;
+mod chart;
+mod choropleth;
 mod contour;
 mod point;
 mod polygon;
+mod rotated;
+#[cfg(feature = "serde")]
+mod serialization;
 
 pub use arbitrary::ArbitraryGeometrySymbol;
+pub use chart::{ChartPointSymbol, ChartType};
+pub use choropleth::{ChoroplethSymbol, Classification, ColorRamp, LegendEntry};
 pub use contour::SimpleContourSymbol;
 pub use point::{CirclePointSymbol, ImagePointSymbol};
-pub use polygon::SimplePolygonSymbol;
+pub use polygon::{SimplePolygonSymbol, StrokeAlignment, StrokeCasing};
+pub use rotated::RotatedPointSymbol;
+#[cfg(feature = "serde")]
+pub use serialization::{VersionedSymbol, SYMBOL_FORMAT_VERSION};
 
 use crate::render::render_bundle::RenderPrimitive;
 use galileo_types::cartesian::CartesianPoint3d;
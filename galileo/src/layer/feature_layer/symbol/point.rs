@@ -1,13 +1,16 @@
 use crate::decoded_image::DecodedImage;
 use crate::layer::feature_layer::symbol::Symbol;
-use crate::render::point_paint::PointPaint;
+use crate::render::point_paint::{PointAlignment, PointPaint};
 use crate::render::render_bundle::RenderPrimitive;
+use crate::render::SizeUnit;
 use crate::Color;
 use galileo_types::cartesian::CartesianPoint3d;
 use galileo_types::geometry::Geom;
 use galileo_types::MultiPoint;
 use nalgebra::Vector2;
 use num_traits::AsPrimitive;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -18,17 +21,41 @@ use std::ops::Deref;
 
 /// Renders a point as a circle of fixes size.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CirclePointSymbol {
     /// Color of the circle.
     pub color: Color,
-    /// Diameter of the circle.
+    /// Diameter of the circle in `size_unit`s.
     pub size: f64,
+    /// Units of the circle diameter.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub size_unit: SizeUnit,
+    /// Orientation of the circle in rotated and tilted views.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub alignment: PointAlignment,
 }
 
 impl CirclePointSymbol {
     /// Create a new instance.
     pub fn new(color: Color, size: f64) -> Self {
-        Self { color, size }
+        Self {
+            color,
+            size,
+            size_unit: SizeUnit::Pixels,
+            alignment: PointAlignment::Screen,
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the diameter of the circle set in the given
+    /// units.
+    pub fn with_size_unit(&self, size_unit: SizeUnit) -> Self {
+        Self { size_unit, ..*self }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given orientation of the circle in rotated
+    /// and tilted views.
+    pub fn with_alignment(&self, alignment: PointAlignment) -> Self {
+        Self { alignment, ..*self }
     }
 }
 
@@ -43,7 +70,12 @@ impl<F> Symbol<F> for CirclePointSymbol {
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N> + Clone,
     {
-        let paint = PointPaint::circle(self.color, self.size as f32);
+        let paint = match self.size_unit {
+            SizeUnit::Pixels => {
+                PointPaint::circle(self.color, self.size as f32).with_alignment(self.alignment)
+            }
+            SizeUnit::MapUnits => PointPaint::map_circle(self.color, self.size),
+        };
         match geometry {
             Geom::Point(point) => vec![RenderPrimitive::new_point_ref(point, paint)],
             Geom::MultiPoint(points) => points
@@ -61,6 +93,7 @@ pub struct ImagePointSymbol {
     image: Arc<DecodedImage>,
     offset: Vector2<f32>,
     scale: f32,
+    alignment: PointAlignment,
 }
 
 impl ImagePointSymbol {
@@ -78,8 +111,15 @@ impl ImagePointSymbol {
             }),
             offset,
             scale,
+            alignment: PointAlignment::Screen,
         })
     }
+
+    /// Sets the orientation of the image in rotated and tilted views.
+    pub fn with_alignment(mut self, alignment: PointAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
 }
 
 impl<F> Symbol<F> for ImagePointSymbol {
@@ -93,7 +133,8 @@ impl<F> Symbol<F> for ImagePointSymbol {
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N> + Clone,
     {
-        let paint = PointPaint::image(self.image.clone(), self.offset, self.scale);
+        let paint = PointPaint::image(self.image.clone(), self.offset, self.scale)
+            .with_alignment(self.alignment);
 
         match geometry {
             Geom::Point(point) => vec![RenderPrimitive::new_point_ref(point, paint)],
@@ -1,25 +1,70 @@
 use crate::layer::feature_layer::symbol::Symbol;
 use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{LineCap, LinePaint, PolygonPaint};
+use crate::render::{LineCap, LinePaint, PolygonGradient, PolygonPaint, SizeUnit};
 use crate::Color;
-use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::cartesian::{CartesianPoint3d, Point2d};
+use galileo_types::contour::Contour as _;
 use galileo_types::geometry::Geom;
-use galileo_types::impls::Contour;
+use galileo_types::impls::{ClosedContour, Contour};
 use galileo_types::{MultiPolygon, Polygon};
 use num_traits::AsPrimitive;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Renders a polygon geometry as a filled polygon with an outline.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SimplePolygonSymbol {
     /// Color of the inner area of the polygon.
     pub fill_color: Color,
+    /// Gradient to fill the inner area of the polygon with instead of the `fill_color`. Coordinates of the gradient
+    /// are relative to the bounding box of the polygon: `(0.0, 0.0)` is its bottom left corner and `(1.0, 1.0)` is
+    /// the top right one. Radius of a radial gradient is relative to the larger side of the bounding box.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fill_gradient: Option<PolygonGradient>,
     /// Color of the outline.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub stroke_color: Color,
     /// Width of the outline in pixels.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub stroke_width: f64,
     /// Offset of the outline in pixels. Positive offset will move outline outside of the polygon, negative offset
     /// will move the outline inside the polygon.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub stroke_offset: f64,
+    /// Position of the outline relative to the polygon boundary.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stroke_alignment: StrokeAlignment,
+    /// Casing drawn under the outline.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub casing: Option<StrokeCasing>,
+}
+
+/// Position of a polygon outline relative to the boundary of the polygon.
+///
+/// The side of the boundary is determined for every ring of the polygon from its winding, so the alignment works the
+/// same way for clockwise and counterclockwise rings, and for the holes of the polygon.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StrokeAlignment {
+    /// Outline is centered on the boundary.
+    #[default]
+    Center,
+    /// Outline is drawn fully inside the polygon.
+    Inside,
+    /// Outline is drawn fully outside the polygon.
+    Outside,
+}
+
+/// Casing of a polygon outline: a wider line drawn under the outline, so that the outline gets borders of different
+/// color on both sides (e.g. a light road with dark edges).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StrokeCasing {
+    /// Color of the casing.
+    pub color: Color,
+    /// Width of the casing visible on each side of the outline in pixels.
+    pub width: f64,
 }
 
 impl SimplePolygonSymbol {
@@ -27,9 +72,21 @@ impl SimplePolygonSymbol {
     pub fn new(fill_color: Color) -> Self {
         Self {
             fill_color,
+            fill_gradient: None,
             stroke_color: Default::default(),
             stroke_width: 0.0,
             stroke_offset: 0.0,
+            stroke_alignment: StrokeAlignment::Center,
+            casing: None,
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given fill gradient. See
+    /// [`SimplePolygonSymbol::fill_gradient`] for the coordinates of the gradient.
+    pub fn with_fill_gradient(&self, fill_gradient: PolygonGradient) -> Self {
+        Self {
+            fill_gradient: Some(fill_gradient),
+            ..*self
         }
     }
 
@@ -57,6 +114,35 @@ impl SimplePolygonSymbol {
         }
     }
 
+    /// Creates a new instance from a copy of the current, but with the given stroke alignment.
+    pub fn with_stroke_alignment(&self, stroke_alignment: StrokeAlignment) -> Self {
+        Self {
+            stroke_alignment,
+            ..*self
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the outline casing of the given color and width.
+    pub fn with_casing(&self, color: Color, width: f64) -> Self {
+        Self {
+            casing: Some(StrokeCasing { color, width }),
+            ..*self
+        }
+    }
+
+    /// Offset of the center of the outline (and its casing) outwards from the polygon boundary.
+    fn stroke_center_offset(&self) -> f64 {
+        let casing_width = self.casing.map(|casing| casing.width).unwrap_or(0.0);
+        let total_width = self.stroke_width + 2.0 * casing_width;
+        let alignment_offset = match self.stroke_alignment {
+            StrokeAlignment::Center => 0.0,
+            StrokeAlignment::Inside => -total_width / 2.0,
+            StrokeAlignment::Outside => total_width / 2.0,
+        };
+
+        alignment_offset + self.stroke_offset
+    }
+
     fn render_poly<'a, N, P>(
         &self,
         polygon: &'a galileo_types::impls::Polygon<P>,
@@ -70,21 +156,45 @@ impl SimplePolygonSymbol {
             polygon,
             PolygonPaint {
                 color: self.fill_color,
+                gradient: self
+                    .fill_gradient
+                    .map(|gradient| to_map_coordinates(gradient, polygon)),
+                zoom: None,
             },
         ));
 
-        let line_paint = LinePaint {
-            color: self.stroke_color,
-            width: self.stroke_width,
-            offset: self.stroke_offset,
-            line_cap: LineCap::Butt,
-        };
+        let mut strokes = vec![];
+        if let Some(casing) = self.casing {
+            strokes.push((casing.color, self.stroke_width + 2.0 * casing.width));
+        }
+        strokes.push((self.stroke_color, self.stroke_width));
 
-        for contour in polygon.iter_contours() {
-            primitives.push(RenderPrimitive::new_contour(
-                contour.clone().into(),
-                line_paint,
-            ));
+        let center_offset = self.stroke_center_offset();
+        for (index, contour) in polygon.iter_contours().enumerate() {
+            // Line offset is applied to the right side of the line. The right side is outside for counterclockwise
+            // outer rings, but for clockwise holes.
+            let is_hole = index > 0;
+            let outwards = if is_counterclockwise(contour) != is_hole {
+                1.0
+            } else {
+                -1.0
+            };
+
+            for &(color, width) in &strokes {
+                primitives.push(RenderPrimitive::new_contour(
+                    contour.clone().into(),
+                    LinePaint {
+                        color,
+                        width,
+                        offset: center_offset * outwards,
+                        width_unit: SizeUnit::Pixels,
+                        line_cap: LineCap::Butt,
+                        pattern: None,
+                        gradient: None,
+                        zoom: None,
+                    },
+                ));
+            }
         }
 
         primitives
@@ -112,3 +222,67 @@ impl<F> Symbol<F> for SimplePolygonSymbol {
         }
     }
 }
+
+fn is_counterclockwise<N, P>(contour: &ClosedContour<P>) -> bool
+where
+    N: AsPrimitive<f32>,
+    P: CartesianPoint3d<Num = N>,
+{
+    let mut points = contour.iter_points_closing();
+    let Some(mut prev) = points.next() else {
+        return true;
+    };
+
+    let mut area = 0.0;
+    for p in points {
+        let (x1, y1) = (prev.x().as_() as f64, prev.y().as_() as f64);
+        let (x2, y2) = (p.x().as_() as f64, p.y().as_() as f64);
+        area += x1 * y2 - x2 * y1;
+        prev = p;
+    }
+
+    area >= 0.0
+}
+
+/// Converts the coordinates of the gradient relative to the bounding box of the polygon into map coordinates.
+fn to_map_coordinates<N, P>(
+    gradient: PolygonGradient,
+    polygon: &galileo_types::impls::Polygon<P>,
+) -> PolygonGradient
+where
+    N: AsPrimitive<f32>,
+    P: CartesianPoint3d<Num = N>,
+{
+    let (mut x_min, mut y_min, mut x_max, mut y_max) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for p in polygon.outer_contour.iter_points() {
+        let (x, y) = (p.x().as_() as f64, p.y().as_() as f64);
+        x_min = x_min.min(x);
+        y_min = y_min.min(y);
+        x_max = x_max.max(x);
+        y_max = y_max.max(y);
+    }
+
+    if x_min > x_max {
+        return gradient;
+    }
+
+    let to_map =
+        |p: Point2d| Point2d::new(x_min + p.x * (x_max - x_min), y_min + p.y * (y_max - y_min));
+
+    match gradient {
+        PolygonGradient::Linear { start, end, colors } => PolygonGradient::Linear {
+            start: to_map(start),
+            end: to_map(end),
+            colors,
+        },
+        PolygonGradient::Radial {
+            center,
+            radius,
+            colors,
+        } => PolygonGradient::Radial {
+            center: to_map(center),
+            radius: radius * (x_max - x_min).max(y_max - y_min),
+            colors,
+        },
+    }
+}
@@ -0,0 +1,147 @@
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::render::point_paint::RotationAlignment;
+use crate::render::render_bundle::RenderPrimitive;
+use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use num_traits::AsPrimitive;
+
+/// Wraps a point symbol to rotate its icons, e.g. to show wind direction or the heading of a vehicle.
+///
+/// The rotation can be fixed or taken from the attributes of every feature, and is measured clockwise in radians
+/// either from the top of the screen or from the north of the map (see [`RotationAlignment`]). It is added to
+/// the rotation of all the point primitives created by the inner symbol. Other primitives are left as they are.
+///
+/// ```
+/// use galileo::layer::feature_layer::symbol::{CirclePointSymbol, RotatedPointSymbol};
+/// use galileo::render::point_paint::RotationAlignment;
+/// use galileo::Color;
+///
+/// struct Vehicle {
+///     heading_degrees: Option<f32>,
+/// }
+///
+/// let symbol = RotatedPointSymbol::from_attribute(
+///     CirclePointSymbol::new(Color::RED, 10.0),
+///     |vehicle: &Vehicle| vehicle.heading_degrees.map(f32::to_radians),
+/// )
+/// .with_rotation_alignment(RotationAlignment::Map);
+/// ```
+pub struct RotatedPointSymbol<F, S> {
+    symbol: S,
+    rotation: Box<dyn Fn(&F) -> Option<f32> + Send + Sync>,
+    alignment: RotationAlignment,
+}
+
+impl<F, S> RotatedPointSymbol<F, S> {
+    /// Rotates all the icons of the symbol by the same angle.
+    pub fn fixed(symbol: S, rotation: f32) -> Self {
+        Self {
+            symbol,
+            rotation: Box::new(move |_| Some(rotation)),
+            alignment: RotationAlignment::Screen,
+        }
+    }
+
+    /// Rotates the icons by the angle returned by the `rotation` function for the feature. If the function returns
+    /// `None`, the icons of the feature are not rotated.
+    pub fn from_attribute(
+        symbol: S,
+        rotation: impl Fn(&F) -> Option<f32> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            symbol,
+            rotation: Box::new(rotation),
+            alignment: RotationAlignment::Screen,
+        }
+    }
+
+    /// Sets the direction from which the rotation is measured. With [`RotationAlignment::Map`] the icons rotate
+    /// together with the map.
+    pub fn with_rotation_alignment(mut self, alignment: RotationAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// The wrapped symbol.
+    pub fn symbol(&self) -> &S {
+        &self.symbol
+    }
+}
+
+impl<F, S: Symbol<F>> Symbol<F> for RotatedPointSymbol<F, S> {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let rotation = (self.rotation)(feature)
+            .filter(|angle| angle.is_finite())
+            .unwrap_or(0.0);
+
+        self.symbol
+            .render(feature, geometry, min_resolution)
+            .into_iter()
+            .map(|primitive| match primitive {
+                RenderPrimitive::Point(point, paint) => {
+                    let rotation = paint.rotation + rotation;
+                    RenderPrimitive::Point(
+                        point,
+                        paint
+                            .with_rotation(rotation)
+                            .with_rotation_alignment(self.alignment),
+                    )
+                }
+                other => other,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::symbol::CirclePointSymbol;
+    use crate::Color;
+    use galileo_types::cartesian::Point3d;
+
+    fn rotations(
+        symbol: &RotatedPointSymbol<Option<f32>, CirclePointSymbol>,
+        heading: Option<f32>,
+    ) -> Vec<f32> {
+        let geometry = Geom::Point(Point3d::new(0.0, 0.0, 0.0));
+        symbol
+            .render(&heading, &geometry, 1.0)
+            .into_iter()
+            .map(|primitive| match primitive {
+                RenderPrimitive::Point(_, paint) => {
+                    assert_eq!(paint.rotation_alignment, RotationAlignment::Map);
+                    paint.rotation
+                }
+                _ => panic!("expected point"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rotation_from_attribute() {
+        let symbol = RotatedPointSymbol::from_attribute(
+            CirclePointSymbol::new(Color::RED, 10.0),
+            |heading: &Option<f32>| *heading,
+        )
+        .with_rotation_alignment(RotationAlignment::Map);
+
+        assert_eq!(rotations(&symbol, Some(1.5)), vec![1.5]);
+        assert_eq!(rotations(&symbol, None), vec![0.0]);
+        assert_eq!(rotations(&symbol, Some(f32::NAN)), vec![0.0]);
+
+        let fixed = RotatedPointSymbol::fixed(CirclePointSymbol::new(Color::RED, 10.0), 0.5)
+            .with_rotation_alignment(RotationAlignment::Map);
+        assert_eq!(rotations(&fixed, Some(1.5)), vec![0.5]);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::error::GalileoError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Version of the symbol serialization format written by this version of the crate.
+///
+/// The version is increased when the format changes in a way that older versions of the crate cannot read. Adding new
+/// optional properties does not change the version: properties missing in older documents take their default values.
+pub const SYMBOL_FORMAT_VERSION: u32 = 1;
+
+/// Symbol stored together with the version of the serialization format, for storing styles in databases or
+/// configuration files.
+///
+/// ```
+/// use galileo::layer::feature_layer::symbol::{SimplePolygonSymbol, VersionedSymbol};
+/// use galileo::Color;
+///
+/// let symbol = SimplePolygonSymbol::new(Color::BLUE).with_stroke_width(2.0);
+/// let json = VersionedSymbol::new(symbol).to_json().unwrap();
+///
+/// let restored: SimplePolygonSymbol = VersionedSymbol::from_json(&json).unwrap();
+/// assert_eq!(restored.stroke_width, 2.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionedSymbol<S> {
+    /// Version of the format the symbol was written with.
+    pub version: u32,
+    /// The symbol.
+    pub symbol: S,
+}
+
+impl<S> VersionedSymbol<S> {
+    /// Wraps the symbol with the [current format version](SYMBOL_FORMAT_VERSION).
+    pub fn new(symbol: S) -> Self {
+        Self {
+            version: SYMBOL_FORMAT_VERSION,
+            symbol,
+        }
+    }
+
+    /// Returns the symbol, or an error if it was written with a newer format version than this crate supports.
+    pub fn into_symbol(self) -> Result<S, GalileoError> {
+        if self.version > SYMBOL_FORMAT_VERSION {
+            return Err(GalileoError::Generic(format!(
+                "symbol format version {} is not supported, the latest supported version is {SYMBOL_FORMAT_VERSION}",
+                self.version
+            )));
+        }
+
+        Ok(self.symbol)
+    }
+}
+
+impl<S: Serialize> VersionedSymbol<S> {
+    /// Serializes the symbol into a JSON string.
+    pub fn to_json(&self) -> Result<String, GalileoError> {
+        serde_json::to_string(self)
+            .map_err(|err| GalileoError::Generic(format!("failed to serialize symbol: {err}")))
+    }
+}
+
+impl<S: DeserializeOwned> VersionedSymbol<S> {
+    /// Reads a symbol from a JSON string written by [`VersionedSymbol::to_json`].
+    pub fn from_json(json: &str) -> Result<S, GalileoError> {
+        let versioned: Self = serde_json::from_str(json)
+            .map_err(|err| GalileoError::Generic(format!("invalid symbol: {err}")))?;
+        versioned.into_symbol()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::symbol::{
+        ArbitraryGeometrySymbol, ColorRamp, SimpleContourSymbol, SimplePolygonSymbol,
+        StrokeAlignment,
+    };
+    use crate::render::{ColorGradient, LinePattern};
+    use crate::Color;
+
+    #[test]
+    fn symbols_round_trip() {
+        let contour = SimpleContourSymbol::new(Color::RED, 3.0)
+            .with_pattern(LinePattern::Dash {
+                dash: 4.0,
+                gap: 2.0,
+                speed: 0.0,
+            })
+            .with_gradient(ColorGradient::two_colors(Color::RED, Color::BLUE));
+        let json = VersionedSymbol::new(contour).to_json().unwrap();
+        let restored: SimpleContourSymbol = VersionedSymbol::from_json(&json).unwrap();
+        assert_eq!(restored.pattern, contour.pattern);
+        assert_eq!(restored.gradient, contour.gradient);
+
+        let json = VersionedSymbol::new(ArbitraryGeometrySymbol::default())
+            .to_json()
+            .unwrap();
+        assert!(VersionedSymbol::<ArbitraryGeometrySymbol>::from_json(&json).is_ok());
+
+        let ramp = ColorRamp::viridis();
+        let json = serde_json::to_string(&ramp).unwrap();
+        assert_eq!(serde_json::from_str::<ColorRamp>(&json).unwrap(), ramp);
+        assert!(serde_json::from_str::<ColorRamp>(r#"{"stops": []}"#).is_err());
+    }
+
+    #[test]
+    fn missing_properties_and_versions() {
+        let json = r##"{"version": 1, "symbol": {"fill_color": "#FF0000"}}"##;
+        let symbol: SimplePolygonSymbol = VersionedSymbol::from_json(json).unwrap();
+        assert_eq!(symbol.fill_color, Color::RED);
+        assert_eq!(symbol.stroke_width, 0.0);
+        assert_eq!(symbol.stroke_alignment, StrokeAlignment::Center);
+
+        let json = r##"{"version": 2, "symbol": {"fill_color": "#FF0000"}}"##;
+        assert!(VersionedSymbol::<SimplePolygonSymbol>::from_json(json).is_err());
+    }
+}
@@ -0,0 +1,383 @@
+use crate::error::GalileoError;
+use crate::layer::data_provider::{percent_encode, HttpClient};
+use galileo_types::cartesian::Rect;
+use quick_xml::escape::escape;
+use std::fmt::Write;
+
+const FES_NAMESPACES: &str =
+    r#"xmlns:fes="http://www.opengis.net/fes/2.0" xmlns:gml="http://www.opengis.net/gml/3.2""#;
+
+/// OGC Filter Encoding 2.0 expression used to select the features returned by a WFS server.
+///
+/// Filtering on the server side means only the relevant features are transferred, which matters for large feature
+/// types.
+///
+/// ```
+/// use galileo::layer::feature_layer::OgcFilter;
+///
+/// let filter = OgcFilter::equal("country", "France").and(OgcFilter::greater("population", 100000));
+/// assert!(filter.to_xml().contains("<fes:PropertyIsGreaterThan>"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum OgcFilter {
+    /// Property is equal to the value.
+    Equal(String, String),
+    /// Property is not equal to the value.
+    NotEqual(String, String),
+    /// Property is less than the value.
+    Less(String, String),
+    /// Property is greater than the value.
+    Greater(String, String),
+    /// Property is less than or equal to the value.
+    LessOrEqual(String, String),
+    /// Property is greater than or equal to the value.
+    GreaterOrEqual(String, String),
+    /// Property matches the pattern, where `*` matches any number of characters and `.` matches a single character.
+    /// Use `!` to escape these characters.
+    Like(String, String),
+    /// Property is between the lower and upper values (inclusive).
+    Between(String, String, String),
+    /// Property is null.
+    IsNull(String),
+    /// Geometry of the feature intersects the bounding box given in the CRS with the given name (e.g.
+    /// `urn:ogc:def:crs:EPSG::3857`). If the geometry property name is not set, the default geometry is used.
+    Bbox {
+        /// Name of the geometry property.
+        property: Option<String>,
+        /// Bounding box.
+        bbox: Rect,
+        /// Name of the CRS of the bounding box.
+        srs_name: String,
+    },
+    /// All the filters match.
+    And(Vec<OgcFilter>),
+    /// Any of the filters matches.
+    Or(Vec<OgcFilter>),
+    /// The filter does not match.
+    Not(Box<OgcFilter>),
+}
+
+impl OgcFilter {
+    /// Creates [`OgcFilter::Equal`] filter.
+    pub fn equal(property: impl Into<String>, value: impl ToString) -> Self {
+        Self::Equal(property.into(), value.to_string())
+    }
+
+    /// Creates [`OgcFilter::NotEqual`] filter.
+    pub fn not_equal(property: impl Into<String>, value: impl ToString) -> Self {
+        Self::NotEqual(property.into(), value.to_string())
+    }
+
+    /// Creates [`OgcFilter::Less`] filter.
+    pub fn less(property: impl Into<String>, value: impl ToString) -> Self {
+        Self::Less(property.into(), value.to_string())
+    }
+
+    /// Creates [`OgcFilter::Greater`] filter.
+    pub fn greater(property: impl Into<String>, value: impl ToString) -> Self {
+        Self::Greater(property.into(), value.to_string())
+    }
+
+    /// Creates [`OgcFilter::Like`] filter.
+    pub fn like(property: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self::Like(property.into(), pattern.into())
+    }
+
+    /// Creates [`OgcFilter::Bbox`] filter for the default geometry property.
+    pub fn bbox(bbox: Rect, srs_name: impl Into<String>) -> Self {
+        Self::Bbox {
+            property: None,
+            bbox,
+            srs_name: srs_name.into(),
+        }
+    }
+
+    /// Combines the filter with another one, so that both must match.
+    pub fn and(self, other: OgcFilter) -> Self {
+        match self {
+            Self::And(mut filters) => {
+                filters.push(other);
+                Self::And(filters)
+            }
+            filter => Self::And(vec![filter, other]),
+        }
+    }
+
+    /// Combines the filter with another one, so that any of them must match.
+    pub fn or(self, other: OgcFilter) -> Self {
+        match self {
+            Self::Or(mut filters) => {
+                filters.push(other);
+                Self::Or(filters)
+            }
+            filter => Self::Or(vec![filter, other]),
+        }
+    }
+
+    /// Inverts the filter.
+    pub fn negate(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+
+    /// Encodes the filter as a `fes:Filter` XML document.
+    pub fn to_xml(&self) -> String {
+        let mut xml = format!("<fes:Filter {FES_NAMESPACES}>");
+        self.write_xml(&mut xml);
+        xml.push_str("</fes:Filter>");
+        xml
+    }
+
+    fn write_xml(&self, xml: &mut String) {
+        let comparison = |xml: &mut String, operator: &str, property: &str, value: &str| {
+            let _ = write!(
+                xml,
+                "<fes:{operator}><fes:ValueReference>{}</fes:ValueReference><fes:Literal>{}</fes:Literal></fes:{operator}>",
+                escape(property),
+                escape(value)
+            );
+        };
+
+        match self {
+            Self::Equal(p, v) => comparison(xml, "PropertyIsEqualTo", p, v),
+            Self::NotEqual(p, v) => comparison(xml, "PropertyIsNotEqualTo", p, v),
+            Self::Less(p, v) => comparison(xml, "PropertyIsLessThan", p, v),
+            Self::Greater(p, v) => comparison(xml, "PropertyIsGreaterThan", p, v),
+            Self::LessOrEqual(p, v) => comparison(xml, "PropertyIsLessThanOrEqualTo", p, v),
+            Self::GreaterOrEqual(p, v) => comparison(xml, "PropertyIsGreaterThanOrEqualTo", p, v),
+            Self::Like(p, pattern) => {
+                let _ = write!(
+                    xml,
+                    r#"<fes:PropertyIsLike wildCard="*" singleChar="." escapeChar="!"><fes:ValueReference>{}</fes:ValueReference><fes:Literal>{}</fes:Literal></fes:PropertyIsLike>"#,
+                    escape(p),
+                    escape(pattern)
+                );
+            }
+            Self::Between(p, lower, upper) => {
+                let _ = write!(
+                    xml,
+                    "<fes:PropertyIsBetween><fes:ValueReference>{}</fes:ValueReference><fes:LowerBoundary><fes:Literal>{}</fes:Literal></fes:LowerBoundary><fes:UpperBoundary><fes:Literal>{}</fes:Literal></fes:UpperBoundary></fes:PropertyIsBetween>",
+                    escape(p),
+                    escape(lower),
+                    escape(upper)
+                );
+            }
+            Self::IsNull(p) => {
+                let _ = write!(
+                    xml,
+                    "<fes:PropertyIsNull><fes:ValueReference>{}</fes:ValueReference></fes:PropertyIsNull>",
+                    escape(p)
+                );
+            }
+            Self::Bbox {
+                property,
+                bbox,
+                srs_name,
+            } => {
+                xml.push_str("<fes:BBOX>");
+                if let Some(property) = property {
+                    let _ = write!(
+                        xml,
+                        "<fes:ValueReference>{}</fes:ValueReference>",
+                        escape(property)
+                    );
+                }
+                let _ = write!(
+                    xml,
+                    r#"<gml:Envelope srsName="{}"><gml:lowerCorner>{} {}</gml:lowerCorner><gml:upperCorner>{} {}</gml:upperCorner></gml:Envelope></fes:BBOX>"#,
+                    escape(srs_name),
+                    bbox.x_min(),
+                    bbox.y_min(),
+                    bbox.x_max(),
+                    bbox.y_max()
+                );
+            }
+            Self::And(filters) | Self::Or(filters) => {
+                let operator = if matches!(self, Self::And(_)) {
+                    "And"
+                } else {
+                    "Or"
+                };
+                // Logical operators require at least two operands.
+                match &filters[..] {
+                    [] => {}
+                    [filter] => filter.write_xml(xml),
+                    _ => {
+                        let _ = write!(xml, "<fes:{operator}>");
+                        for filter in filters {
+                            filter.write_xml(xml);
+                        }
+                        let _ = write!(xml, "</fes:{operator}>");
+                    }
+                }
+            }
+            Self::Not(filter) => {
+                xml.push_str("<fes:Not>");
+                filter.write_xml(xml);
+                xml.push_str("</fes:Not>");
+            }
+        }
+    }
+}
+
+/// WFS 2.0 `GetFeature` request.
+///
+/// ```
+/// use galileo::layer::feature_layer::{OgcFilter, WfsQuery};
+///
+/// let query = WfsQuery::new("https://example.com/wfs", "topp:states")
+///     .with_filter(OgcFilter::equal("STATE_NAME", "Texas"))
+///     .with_count(10);
+/// assert!(query.request_url().starts_with("https://example.com/wfs?SERVICE=WFS"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfsQuery {
+    url: String,
+    type_names: String,
+    output_format: String,
+    srs_name: Option<String>,
+    count: Option<usize>,
+    property_names: Vec<String>,
+    filter: Option<OgcFilter>,
+}
+
+impl WfsQuery {
+    /// Creates a new query of the features of the given feature types from the WFS service at the `url`.
+    ///
+    /// By default, the features are requested in GeoJSON format (`application/json`).
+    pub fn new(url: impl Into<String>, type_names: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            type_names: type_names.into(),
+            output_format: "application/json".to_string(),
+            srs_name: None,
+            count: None,
+            property_names: vec![],
+            filter: None,
+        }
+    }
+
+    /// Sets the filter the features must match.
+    pub fn with_filter(mut self, filter: OgcFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Sets the maximum number of features returned.
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Sets the name of the CRS the features are returned in.
+    pub fn with_srs_name(mut self, srs_name: impl Into<String>) -> Self {
+        self.srs_name = Some(srs_name.into());
+        self
+    }
+
+    /// Sets the output format of the features.
+    pub fn with_output_format(mut self, output_format: impl Into<String>) -> Self {
+        self.output_format = output_format.into();
+        self
+    }
+
+    /// Sets the properties returned for the features. By default, all properties are returned.
+    pub fn with_property_names(
+        mut self,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.property_names = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Filter of the query.
+    pub fn filter(&self) -> Option<&OgcFilter> {
+        self.filter.as_ref()
+    }
+
+    /// URL of the `GetFeature` request.
+    pub fn request_url(&self) -> String {
+        let separator = if self.url.contains('?') { '&' } else { '?' };
+        let mut url = format!(
+            "{}{separator}SERVICE=WFS&VERSION=2.0.0&REQUEST=GetFeature&TYPENAMES={}&OUTPUTFORMAT={}",
+            self.url,
+            percent_encode(&self.type_names),
+            percent_encode(&self.output_format)
+        );
+
+        if let Some(srs_name) = &self.srs_name {
+            let _ = write!(url, "&SRSNAME={}", percent_encode(srs_name));
+        }
+        if let Some(count) = self.count {
+            let _ = write!(url, "&COUNT={count}");
+        }
+        if !self.property_names.is_empty() {
+            let _ = write!(
+                url,
+                "&PROPERTYNAME={}",
+                percent_encode(&self.property_names.join(","))
+            );
+        }
+        if let Some(filter) = &self.filter {
+            let _ = write!(url, "&FILTER={}", percent_encode(&filter.to_xml()));
+        }
+
+        url
+    }
+
+    /// Sends the request and returns the raw response.
+    pub async fn load_raw(
+        &self,
+        http_client: &dyn HttpClient,
+    ) -> Result<bytes::Bytes, GalileoError> {
+        http_client.get(&self.request_url()).await
+    }
+
+    /// Sends the request and parses the returned GeoJSON features. Features without geometry are skipped.
+    #[cfg(feature = "geojson")]
+    pub async fn load(
+        &self,
+        http_client: &dyn HttpClient,
+    ) -> Result<Vec<geojson::Feature>, GalileoError> {
+        let bytes = self.load_raw(http_client).await?;
+        super::geojson_source::parse_features(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_xml() {
+        let filter = OgcFilter::equal("name", "A & B")
+            .and(OgcFilter::bbox(
+                Rect::new(0.0, 1.0, 2.0, 3.0),
+                "urn:ogc:def:crs:EPSG::3857",
+            ))
+            .and(OgcFilter::IsNull("deleted".into()).negate());
+
+        assert_eq!(
+            filter.to_xml(),
+            format!(
+                "<fes:Filter {FES_NAMESPACES}><fes:And>\
+                <fes:PropertyIsEqualTo><fes:ValueReference>name</fes:ValueReference><fes:Literal>A &amp; B</fes:Literal></fes:PropertyIsEqualTo>\
+                <fes:BBOX><gml:Envelope srsName=\"urn:ogc:def:crs:EPSG::3857\"><gml:lowerCorner>0 1</gml:lowerCorner><gml:upperCorner>2 3</gml:upperCorner></gml:Envelope></fes:BBOX>\
+                <fes:Not><fes:PropertyIsNull><fes:ValueReference>deleted</fes:ValueReference></fes:PropertyIsNull></fes:Not>\
+                </fes:And></fes:Filter>"
+            )
+        );
+    }
+
+    #[test]
+    fn request_url() {
+        let url = WfsQuery::new("https://example.com/ows?map=test", "ns:roads")
+            .with_count(5)
+            .with_filter(OgcFilter::greater("lanes", 2))
+            .request_url();
+
+        assert!(url.starts_with("https://example.com/ows?map=test&SERVICE=WFS&VERSION=2.0.0"));
+        assert!(url.contains("&TYPENAMES=ns%3Aroads"));
+        assert!(url.contains("&COUNT=5"));
+        assert!(url.contains("&FILTER=%3Cfes%3AFilter"));
+    }
+}
@@ -0,0 +1,219 @@
+//! Reader of the Well-Known Binary geometry encoding (including the ISO and EWKB variants with *Z* and *M*
+//! coordinates, which are ignored).
+
+use crate::error::GalileoError;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{
+    ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon,
+};
+
+const EWKB_Z_FLAG: u32 = 0x8000_0000;
+const EWKB_M_FLAG: u32 = 0x4000_0000;
+const EWKB_SRID_FLAG: u32 = 0x2000_0000;
+
+/// Reads a WKB geometry, creating its points with `point(x, y)`.
+pub(crate) fn read_wkb<P>(
+    bytes: &[u8],
+    point: &impl Fn(f64, f64) -> P,
+) -> Result<Geom<P>, GalileoError> {
+    let mut reader = WkbReader {
+        bytes,
+        position: 0,
+        little_endian: true,
+    };
+    reader.read_geometry(point)
+}
+
+fn invalid_wkb() -> GalileoError {
+    GalileoError::Generic("invalid WKB geometry".to_string())
+}
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    little_endian: bool,
+}
+
+impl WkbReader<'_> {
+    fn read_geometry<P>(
+        &mut self,
+        point: &impl Fn(f64, f64) -> P,
+    ) -> Result<Geom<P>, GalileoError> {
+        let (geometry_type, dimensions) = self.read_header()?;
+        let geometry = match geometry_type {
+            1 => Geom::Point(self.read_point(dimensions, point)?),
+            2 => Geom::Contour(Contour::open(self.read_points(dimensions, point)?)),
+            3 => Geom::Polygon(self.read_polygon(dimensions, point)?),
+            4 => {
+                let count = self.read_u32()?;
+                let mut points = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (_, dimensions) = self.read_header()?;
+                    points.push(self.read_point(dimensions, point)?);
+                }
+                Geom::MultiPoint(MultiPoint::from(points))
+            }
+            5 => {
+                let count = self.read_u32()?;
+                let mut contours = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (_, dimensions) = self.read_header()?;
+                    contours.push(Contour::open(self.read_points(dimensions, point)?));
+                }
+                Geom::MultiContour(MultiContour::from(contours))
+            }
+            6 => {
+                let count = self.read_u32()?;
+                let mut polygons = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (_, dimensions) = self.read_header()?;
+                    polygons.push(self.read_polygon(dimensions, point)?);
+                }
+                Geom::MultiPolygon(MultiPolygon::from(polygons))
+            }
+            other => {
+                return Err(GalileoError::Generic(format!(
+                    "unsupported WKB geometry type: {other}"
+                )))
+            }
+        };
+
+        Ok(geometry)
+    }
+
+    /// Reads byte order and geometry type, returning the base geometry type and the number of coordinates of points.
+    fn read_header(&mut self) -> Result<(u32, usize), GalileoError> {
+        self.little_endian = match self.read_bytes::<1>()? {
+            [0] => false,
+            [1] => true,
+            _ => return Err(invalid_wkb()),
+        };
+
+        let raw_type = self.read_u32()?;
+        if raw_type & EWKB_SRID_FLAG != 0 {
+            self.read_u32()?;
+        }
+
+        let mut dimensions = 2;
+        if raw_type & EWKB_Z_FLAG != 0 {
+            dimensions += 1;
+        }
+        if raw_type & EWKB_M_FLAG != 0 {
+            dimensions += 1;
+        }
+
+        let iso_type = raw_type & 0x0fff_ffff;
+        dimensions += match iso_type / 1000 {
+            1 | 2 => 1,
+            3 => 2,
+            _ => 0,
+        };
+
+        Ok((iso_type % 1000, dimensions))
+    }
+
+    fn read_polygon<P>(
+        &mut self,
+        dimensions: usize,
+        point: &impl Fn(f64, f64) -> P,
+    ) -> Result<Polygon<P>, GalileoError> {
+        let count = self.read_u32()?;
+        let mut rings = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let mut points = self.read_points(dimensions, point)?;
+            // Rings are closed explicitly in WKB, while the closing point is implicit in `ClosedContour`.
+            if points.len() > 1 {
+                points.pop();
+            }
+            rings.push(ClosedContour::new(points));
+        }
+
+        let mut rings = rings.into_iter();
+        let outer = rings.next().ok_or_else(invalid_wkb)?;
+        Ok(Polygon::new(outer, rings.collect()))
+    }
+
+    fn read_points<P>(
+        &mut self,
+        dimensions: usize,
+        point: &impl Fn(f64, f64) -> P,
+    ) -> Result<Vec<P>, GalileoError> {
+        let count = self.read_u32()? as usize;
+        if count * dimensions * 8 > self.bytes.len() - self.position {
+            return Err(invalid_wkb());
+        }
+
+        (0..count)
+            .map(|_| self.read_point(dimensions, point))
+            .collect()
+    }
+
+    fn read_point<P>(
+        &mut self,
+        dimensions: usize,
+        point: &impl Fn(f64, f64) -> P,
+    ) -> Result<P, GalileoError> {
+        let x = self.read_f64()?;
+        let y = self.read_f64()?;
+        for _ in 2..dimensions {
+            self.read_f64()?;
+        }
+
+        Ok(point(x, y))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, GalileoError> {
+        let bytes = self.read_bytes::<4>()?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_f64(&mut self) -> Result<f64, GalileoError> {
+        let bytes = self.read_bytes::<8>()?;
+        Ok(if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N], GalileoError> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + N)
+            .ok_or_else(invalid_wkb)?;
+        self.position += N;
+
+        Ok(bytes.try_into().expect("slice has correct length"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_wkb_polygon() {
+        let mut bytes = vec![1u8];
+        bytes.extend_from_slice(&3u32.to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        for (x, y) in [(0.0f64, 0.0f64), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)] {
+            bytes.extend_from_slice(&x.to_le_bytes());
+            bytes.extend_from_slice(&y.to_le_bytes());
+        }
+
+        let Geom::Polygon(polygon) = read_wkb(&bytes, &|x, y| (x, y)).unwrap() else {
+            panic!("not a polygon");
+        };
+        assert_eq!(
+            polygon.outer_contour.points,
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]
+        );
+
+        assert!(read_wkb(&bytes[..20], &|x, y| (x, y)).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+use crate::control::{Clock, SystemClock};
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::DataProvider;
+use crate::layer::{Layer, RasterTileLayer};
+use crate::messenger::{Messenger, SharedMessenger};
+use crate::render::{Canvas, GpuMemoryBudget};
+use crate::tile_scheme::TileIndex;
+use crate::view::MapView;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use web_time::SystemTime;
+
+/// Layer that plays a set of raster tile layers as frames of an animation, e.g. a weather radar loop made of
+/// separate tile sets for each time.
+///
+/// Only the current frame is drawn. The tiles of the next frames are loaded in advance (see
+/// [`FrameSequencer::with_preload`]), and all the frames share one GPU memory budget (see
+/// [`FrameSequencer::with_memory_limit`]), so the tiles of the frames that were not shown for the longest time are
+/// dropped first. The sequencer switches the frames by itself while playing and requests redraws of the map, so no
+/// additional code is needed in the render loop.
+///
+/// ```no_run
+/// use galileo::layer::data_provider::UrlImageProvider;
+/// use galileo::layer::{FrameSequencer, RasterTileLayer};
+/// use galileo::tile_scheme::{TileIndex, TileSchema};
+///
+/// let frames = ["0900", "0910", "0920"]
+///     .into_iter()
+///     .map(|time| {
+///         RasterTileLayer::new(
+///             TileSchema::web(18),
+///             UrlImageProvider::new(move |index: &TileIndex| {
+///                 format!("https://example.com/radar/{time}/{}/{}/{}.png", index.z, index.x, index.y)
+///             }),
+///             None,
+///         )
+///     })
+///     .collect();
+///
+/// let sequencer = FrameSequencer::new(frames)
+///     .with_fps(4.0)
+///     .with_memory_limit(256 * 1024 * 1024);
+/// sequencer.play();
+/// ```
+pub struct FrameSequencer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    frames: Vec<RasterTileLayer<Provider>>,
+    fps: f64,
+    looped: bool,
+    preload: usize,
+    memory_budget: GpuMemoryBudget,
+    clock: Box<dyn Clock>,
+    messenger: Option<Arc<dyn Messenger>>,
+    state: Mutex<SequencerState>,
+}
+
+#[derive(Default)]
+struct SequencerState {
+    current: usize,
+    playback: Option<PlayState>,
+}
+
+struct PlayState {
+    frame_start: SystemTime,
+    paused_at: Option<SystemTime>,
+}
+
+impl<Provider> FrameSequencer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    /// Creates a new sequencer with the given frames. It plays 5 frames per second in a loop and preloads 2 frames
+    /// ahead of the current one.
+    ///
+    /// Fade in of the tiles is disabled for all frames, since it would make the animation flicker.
+    pub fn new(frames: Vec<RasterTileLayer<Provider>>) -> Self {
+        let mut sequencer = Self {
+            frames: vec![],
+            fps: 5.0,
+            looped: true,
+            preload: 2,
+            memory_budget: GpuMemoryBudget::unlimited(),
+            clock: Box::new(SystemClock),
+            messenger: None,
+            state: Mutex::new(SequencerState::default()),
+        };
+
+        for frame in frames {
+            sequencer.push_frame(frame);
+        }
+
+        sequencer
+    }
+
+    /// Sets the number of frames shown per second.
+    pub fn with_fps(mut self, fps: f64) -> Self {
+        self.set_fps(fps);
+        self
+    }
+
+    /// Sets whether the animation starts over after the last frame. Default is `true`.
+    pub fn with_loop(mut self, looped: bool) -> Self {
+        self.looped = looped;
+        self
+    }
+
+    /// Sets the number of frames after the current one, tiles of which are loaded in advance.
+    pub fn with_preload(mut self, count: usize) -> Self {
+        self.preload = count;
+        self
+    }
+
+    /// Limits the GPU memory used by the tiles of all frames together.
+    pub fn with_memory_limit(self, limit: usize) -> Self {
+        self.set_memory_limit(Some(limit));
+        self
+    }
+
+    /// Sets the clock used to time the animation.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Adds a frame to the end of the animation.
+    pub fn push_frame(&mut self, mut frame: RasterTileLayer<Provider>) {
+        frame.set_fade_in_duration(Duration::ZERO);
+        frame.set_memory_budget(&self.memory_budget);
+        if let Some(messenger) = &self.messenger {
+            frame.set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        }
+
+        self.frames.push(frame);
+    }
+
+    /// Frames of the animation.
+    pub fn frames(&self) -> &[RasterTileLayer<Provider>] {
+        &self.frames
+    }
+
+    /// Mutable access to the frames of the animation.
+    pub fn frames_mut(&mut self) -> &mut [RasterTileLayer<Provider>] {
+        &mut self.frames
+    }
+
+    /// Number of frames per second.
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    /// Sets the number of frames shown per second. The current frame is not changed.
+    pub fn set_fps(&mut self, fps: f64) {
+        let now = self.clock.now();
+        self.advance(now);
+        self.fps = fps.max(0.0);
+    }
+
+    /// Sets the GPU memory limit for the tiles of all frames together. `None` removes the limit.
+    pub fn set_memory_limit(&self, limit: Option<usize>) {
+        self.memory_budget.set_limit(limit);
+    }
+
+    /// Approximate size in bytes of GPU memory used by the tiles of all frames.
+    pub fn gpu_memory_usage(&self) -> usize {
+        self.memory_budget.used()
+    }
+
+    /// Starts playing the animation from the current frame.
+    pub fn play(&self) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.playback = (!self.frames.is_empty()).then_some(PlayState {
+            frame_start: now,
+            paused_at: None,
+        });
+        drop(state);
+
+        self.request_redraw();
+    }
+
+    /// Pauses the animation on the current frame.
+    pub fn pause(&self) {
+        let now = self.clock.now();
+        self.advance(now);
+        if let Some(playback) = &mut self.state.lock().expect("mutex is poisoned").playback {
+            playback.paused_at.get_or_insert(now);
+        }
+    }
+
+    /// Continues the paused animation.
+    pub fn resume(&self) {
+        let now = self.clock.now();
+        if let Some(playback) = &mut self.state.lock().expect("mutex is poisoned").playback {
+            if let Some(paused_at) = playback.paused_at.take() {
+                playback.frame_start += now.duration_since(paused_at).unwrap_or_default();
+            }
+        }
+
+        self.request_redraw();
+    }
+
+    /// Stops the animation. The current frame stays displayed.
+    pub fn stop(&self) {
+        let now = self.clock.now();
+        self.advance(now);
+        self.state.lock().expect("mutex is poisoned").playback = None;
+    }
+
+    /// Returns true if the animation is being played (including when it is paused).
+    pub fn is_playing(&self) -> bool {
+        let now = self.clock.now();
+        self.advance(now);
+        self.state
+            .lock()
+            .expect("mutex is poisoned")
+            .playback
+            .is_some()
+    }
+
+    /// Returns true if the animation is paused.
+    pub fn is_paused(&self) -> bool {
+        self.state
+            .lock()
+            .expect("mutex is poisoned")
+            .playback
+            .as_ref()
+            .is_some_and(|p| p.paused_at.is_some())
+    }
+
+    /// Index of the displayed frame.
+    pub fn current_frame(&self) -> usize {
+        let now = self.clock.now();
+        self.advance(now)
+    }
+
+    /// Displays the frame with the given index. If the animation is playing, it continues from this frame.
+    pub fn seek(&self, frame: usize) -> Result<(), GalileoError> {
+        if frame >= self.frames.len() {
+            return Err(GalileoError::Generic(format!(
+                "frame {frame} is out of range"
+            )));
+        }
+
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.current = frame;
+        if let Some(playback) = &mut state.playback {
+            playback.frame_start = playback.paused_at.unwrap_or(now);
+        }
+        drop(state);
+
+        self.request_redraw();
+        Ok(())
+    }
+
+    /// Moves the animation to the frame that should be displayed at `now` and returns its index.
+    fn advance(&self, now: SystemTime) -> usize {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let count = self.frames.len();
+        let current = state.current;
+        let Some(playback) = &mut state.playback else {
+            return current;
+        };
+        if playback.paused_at.is_some() || self.fps <= 0.0 || count == 0 {
+            return current;
+        }
+
+        let frame_duration = Duration::from_secs_f64(1.0 / self.fps);
+        let elapsed = now.duration_since(playback.frame_start).unwrap_or_default();
+        let passed = (elapsed.as_secs_f64() * self.fps) as usize;
+        if passed == 0 {
+            return current;
+        }
+
+        playback.frame_start += frame_duration * passed as u32;
+        let next = current + passed;
+        let next = if next < count {
+            next
+        } else if self.looped {
+            next % count
+        } else {
+            state.playback = None;
+            count - 1
+        };
+
+        state.current = next;
+        next
+    }
+
+    /// Frames that are loaded in advance after the given one.
+    fn preloaded_frames(&self, current: usize) -> impl Iterator<Item = usize> + '_ {
+        let count = self.frames.len();
+        (1..=self.preload.min(count.saturating_sub(1)))
+            .map(move |offset| current + offset)
+            .filter(move |frame| self.looped || *frame < count)
+            .map(move |frame| frame % count)
+    }
+
+    fn request_redraw(&self) {
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+}
+
+impl<Provider> Layer for FrameSequencer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let current = self.current_frame();
+        if let Some(frame) = self.frames.get(current) {
+            frame.render(view, canvas);
+        }
+
+        if self.is_playing() && !self.is_paused() {
+            self.request_redraw();
+        }
+    }
+
+    fn prepare(&self, view: &MapView) {
+        let current = self.current_frame();
+        if let Some(frame) = self.frames.get(current) {
+            frame.prepare(view);
+        }
+
+        for index in self.preloaded_frames(current) {
+            self.frames[index].prepare(view);
+        }
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = Arc::from(messenger);
+        for frame in &mut self.frames {
+            frame.set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        }
+
+        self.messenger = Some(messenger);
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        for frame in &mut self.frames {
+            frame.set_scale_factor(scale_factor);
+        }
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        for frame in &mut self.frames {
+            frame.reset_gpu_resources();
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::ManualClock;
+    use crate::layer::data_provider::UrlImageProvider;
+    use crate::tile_scheme::TileSchema;
+
+    fn sequencer(count: usize, clock: &ManualClock) -> FrameSequencer<UrlImageProvider<TileIndex>> {
+        let frames = (0..count)
+            .map(|_| {
+                RasterTileLayer::new(
+                    TileSchema::web(4),
+                    UrlImageProvider::new(|_: &TileIndex| String::new()),
+                    None,
+                )
+            })
+            .collect();
+        FrameSequencer::new(frames)
+            .with_fps(10.0)
+            .with_clock(clock.clone())
+    }
+
+    #[test]
+    fn plays_pauses_and_seeks() {
+        let clock = ManualClock::default();
+        let sequencer = sequencer(3, &clock);
+        sequencer.play();
+
+        clock.advance(Duration::from_millis(150));
+        assert_eq!(sequencer.current_frame(), 1);
+
+        sequencer.pause();
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(sequencer.current_frame(), 1);
+        sequencer.resume();
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(sequencer.current_frame(), 2);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(sequencer.current_frame(), 0);
+
+        sequencer.seek(2).expect("frame exists");
+        assert_eq!(sequencer.current_frame(), 2);
+        assert!(sequencer.seek(3).is_err());
+    }
+
+    #[test]
+    fn stops_at_last_frame_if_not_looped() {
+        let clock = ManualClock::default();
+        let sequencer = sequencer(3, &clock).with_loop(false);
+        assert_eq!(sequencer.preloaded_frames(1).collect::<Vec<_>>(), vec![2]);
+
+        sequencer.play();
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(sequencer.current_frame(), 2);
+        assert!(!sequencer.is_playing());
+    }
+}
@@ -0,0 +1,205 @@
+//! Display of the fences of a [`GeofenceMonitor`]. See [`GeofenceLayer`].
+
+use crate::geofence::GeofenceMonitor;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{
+    Canvas, LineCap, LinePaint, PackedBundle, PolygonPaint, RenderOptions, SizeUnit,
+};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, Projection};
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use std::any::Any;
+use std::sync::Mutex;
+
+/// Colors and width of the fences drawn by a [`GeofenceLayer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeofenceStyle {
+    /// Fill color of the fences the position is outside of.
+    pub fill: Color,
+    /// Outline color of the fences the position is outside of.
+    pub outline: Color,
+    /// Fill color of the fences the position is inside of.
+    pub active_fill: Color,
+    /// Outline color of the fences the position is inside of.
+    pub active_outline: Color,
+    /// Width of the outline in pixels.
+    pub outline_width: f64,
+}
+
+impl Default for GeofenceStyle {
+    fn default() -> Self {
+        Self {
+            fill: Color::rgba(0, 120, 220, 40),
+            outline: Color::rgba(0, 120, 220, 200),
+            active_fill: Color::rgba(230, 120, 0, 70),
+            active_outline: Color::rgba(230, 120, 0, 230),
+            outline_width: 2.0,
+        }
+    }
+}
+
+/// Layer that draws the fences of a [`GeofenceMonitor`], highlighting the ones the current position is inside of.
+///
+/// The layer shares the state with the monitor it was created from, so the fences and their highlighting are
+/// updated on the next frame after the monitor changes. Circles are approximated with the precision matching the
+/// current zoom level.
+pub struct GeofenceLayer {
+    monitor: GeofenceMonitor,
+    style: GeofenceStyle,
+    packed: Mutex<Option<(PackKey, Box<dyn PackedBundle>)>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PackKey {
+    revision: u64,
+    crs: Crs,
+    resolution_level: i32,
+}
+
+impl GeofenceLayer {
+    /// Creates a new layer displaying the fences of the monitor.
+    pub fn new(monitor: GeofenceMonitor) -> Self {
+        Self {
+            monitor,
+            style: GeofenceStyle::default(),
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Sets the style of the layer.
+    pub fn with_style(mut self, style: GeofenceStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Monitor the layer displays.
+    pub fn monitor(&self) -> &GeofenceMonitor {
+        &self.monitor
+    }
+
+    fn pack(&self, view: &MapView, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        let resolution = view.resolution();
+        let tolerance = ground_resolution(view).unwrap_or(1.0);
+
+        if let Some(projection) = view.crs().get_projection::<GeoPoint2d, Point2d>() {
+            let project = |point: &GeoPoint2d| {
+                projection
+                    .project(point)
+                    .map(|p| Point3d::new(p.x, p.y, 0.0))
+            };
+            let project_ring = |ring: &ClosedContour<GeoPoint2d>| {
+                ring.points.iter().map(project).collect::<Option<Vec<_>>>()
+            };
+
+            self.monitor.for_each_fence(|fence, is_inside| {
+                let polygon = fence.shape.to_polygon(tolerance);
+                let Some(outer) = project_ring(&polygon.outer_contour) else {
+                    return;
+                };
+                let Some(inner) = polygon
+                    .inner_contours
+                    .iter()
+                    .map(project_ring)
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return;
+                };
+
+                let (fill, outline) = if is_inside {
+                    (self.style.active_fill, self.style.active_outline)
+                } else {
+                    (self.style.fill, self.style.outline)
+                };
+
+                let rings: Vec<_> = std::iter::once(&outer).chain(&inner).cloned().collect();
+                bundle.add(
+                    RenderPrimitive::<_, _, Contour<_>, _>::new_polygon(
+                        Polygon::new(
+                            ClosedContour::new(outer),
+                            inner.into_iter().map(ClosedContour::new).collect(),
+                        ),
+                        PolygonPaint {
+                            color: fill,
+                            gradient: None,
+                            zoom: None,
+                        },
+                    ),
+                    resolution,
+                );
+
+                for ring in rings {
+                    bundle.add(
+                        RenderPrimitive::<_, _, _, Polygon<_>>::new_contour(
+                            Contour::closed(ring),
+                            LinePaint {
+                                color: outline,
+                                width: self.style.outline_width,
+                                offset: 0.0,
+                                width_unit: SizeUnit::Pixels,
+                                line_cap: LineCap::Butt,
+                                pattern: None,
+                                gradient: None,
+                                zoom: None,
+                            },
+                        ),
+                        resolution,
+                    );
+                }
+            });
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+
+    fn pack_key(&self, view: &MapView) -> PackKey {
+        PackKey {
+            revision: self.monitor.revision(),
+            crs: view.crs().clone(),
+            resolution_level: view.resolution().log2().floor() as i32,
+        }
+    }
+}
+
+impl Layer for GeofenceLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let key = self.pack_key(view);
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let is_outdated = !matches!(packed.as_ref(), Some((packed_key, _)) if *packed_key == key);
+        if is_outdated {
+            *packed = Some((key, self.pack(view, canvas)));
+        }
+
+        if let Some((_, bundle)) = packed.as_ref() {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {}
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Size of a pixel in meters in the center of the view.
+fn ground_resolution(view: &MapView) -> Option<f64> {
+    let size = view.size();
+    view.ground_resolution(Point2d::new(size.half_width(), size.half_height()))
+        .filter(|resolution| *resolution > 0.0)
+}
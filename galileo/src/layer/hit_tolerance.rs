@@ -0,0 +1,66 @@
+use crate::control::PointerType;
+use crate::view::MapView;
+
+/// Distance from the pointer within which a feature is considered to be under the pointer when hit-testing the
+/// features of a layer.
+///
+/// The tolerance is set in logical pixels separately for mouse and touch input, since a finger covers a much larger
+/// area of the screen than a mouse cursor. As the map view is measured in logical pixels (see
+/// [`Map::set_scale_factor`](crate::Map::set_scale_factor)), the tolerance has the same physical size on screens with
+/// any DPI.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HitTolerance {
+    /// Tolerance for mouse input in logical pixels.
+    pub mouse: f64,
+    /// Tolerance for touch input in logical pixels.
+    pub touch: f64,
+}
+
+impl Default for HitTolerance {
+    fn default() -> Self {
+        Self {
+            mouse: 2.0,
+            touch: 12.0,
+        }
+    }
+}
+
+impl HitTolerance {
+    /// Creates a new tolerance with the given values for mouse and touch input in logical pixels.
+    pub fn new(mouse: f64, touch: f64) -> Self {
+        Self {
+            mouse: mouse.max(0.0),
+            touch: touch.max(0.0),
+        }
+    }
+
+    /// Tolerance for the given type of input in logical pixels.
+    pub fn pixels(&self, pointer_type: PointerType) -> f64 {
+        match pointer_type {
+            PointerType::Mouse => self.mouse,
+            PointerType::Touch => self.touch,
+        }
+    }
+
+    /// Tolerance for the given type of input in map units at the resolution of the `view`.
+    pub fn map_units(&self, pointer_type: PointerType, view: &MapView) -> f64 {
+        self.pixels(pointer_type) * view.resolution()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    #[test]
+    fn touch_tolerance_is_larger_than_mouse() {
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 10.0)
+            .with_size(Size::new(100.0, 100.0));
+        let tolerance = HitTolerance::default();
+
+        assert!(tolerance.pixels(PointerType::Touch) > tolerance.pixels(PointerType::Mouse));
+        assert_eq!(tolerance.map_units(PointerType::Mouse, &view), 20.0);
+        assert_eq!(tolerance.map_units(PointerType::Touch, &view), 120.0);
+    }
+}
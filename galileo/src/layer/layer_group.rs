@@ -0,0 +1,359 @@
+#[cfg(feature = "compute")]
+use crate::error::GalileoError;
+use crate::layer::Layer;
+use crate::messenger::{Messenger, SharedMessenger};
+use crate::render::render_bundle::RenderBundle;
+#[cfg(feature = "compute")]
+use crate::render::ComputeShader;
+use crate::render::{Canvas, CustomShader, PackedBundle, RenderOptions};
+use crate::view::MapView;
+use crate::LayerCollection;
+use galileo_types::cartesian::Size;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Layer that consists of a set of child layers, managed as one entry of the map [`LayerCollection`].
+///
+/// Groups let applications model sets of layers like "Basemap", "Operational" or "Annotations": the whole group can
+/// be hidden, moved or faded in the map's layer collection, while the children can still be toggled and reordered
+/// inside the group with [`LayerGroup::layers_mut`].
+///
+/// The opacity of the group is applied to every primitive the children draw, so overlapping primitives of the children
+/// show through each other.
+///
+/// ```
+/// use galileo::layer::{LayerGroup, TestLayer};
+/// use galileo::LayerCollection;
+///
+/// let mut basemap = LayerGroup::new("Basemap")
+///     .with_layer(TestLayer("Imagery"))
+///     .with_layer(TestLayer("Roads"))
+///     .with_opacity(0.7);
+/// basemap.layers_mut().hide(1);
+///
+/// let mut layers = LayerCollection::default();
+/// layers.push(basemap);
+/// layers.push(TestLayer("Operational"));
+/// layers.swap(0, 1);
+///
+/// let group = layers[1].as_any().downcast_ref::<LayerGroup>().unwrap();
+/// assert_eq!(group.name(), "Basemap");
+/// assert_eq!(group.layers().iter_visible().count(), 1);
+/// ```
+pub struct LayerGroup {
+    name: String,
+    layers: LayerCollection,
+    opacity: f32,
+    messenger: Option<Arc<dyn Messenger>>,
+}
+
+impl LayerGroup {
+    /// Creates a new empty group with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            layers: LayerCollection::default(),
+            opacity: 1.0,
+            messenger: None,
+        }
+    }
+
+    /// Adds a layer on top of the other children of the group.
+    pub fn with_layer(mut self, layer: impl Layer + 'static) -> Self {
+        self.push(layer);
+        self
+    }
+
+    /// Sets the opacity of the group, from `0.0` (transparent) to `1.0` (opaque).
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.set_opacity(opacity);
+        self
+    }
+
+    /// Name of the group.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Changes the name of the group.
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+
+    /// Opacity of the group.
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Sets the opacity of the group, from `0.0` (transparent) to `1.0` (opaque). Values outside of this range are
+    /// clamped. A group with zero opacity is not rendered at all.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Child layers of the group, from the bottom one to the top one.
+    pub fn layers(&self) -> &LayerCollection {
+        &self.layers
+    }
+
+    /// Mutable reference to the child layers, used to toggle and reorder them.
+    ///
+    /// Layers added through this reference do not receive the messenger of the group, use [`LayerGroup::push`] for
+    /// that.
+    pub fn layers_mut(&mut self) -> &mut LayerCollection {
+        &mut self.layers
+    }
+
+    /// Adds a layer on top of the other children of the group. If the group already has a messenger, it is shared with
+    /// the layer.
+    pub fn push(&mut self, mut layer: impl Layer + 'static) {
+        if let Some(messenger) = &self.messenger {
+            layer.set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        }
+
+        self.layers.push(layer);
+    }
+
+    /// Renders the group with its opacity multiplied by `opacity`.
+    pub(crate) fn render_with_opacity(
+        &self,
+        view: &MapView,
+        canvas: &mut dyn Canvas,
+        opacity: f32,
+    ) {
+        let opacity = self.opacity * opacity;
+        if opacity <= 0.0 {
+            return;
+        }
+
+        let mut group_canvas = GroupCanvas {
+            inner: canvas,
+            opacity,
+        };
+        for layer in self.layers.iter_visible() {
+            layer.render(view, &mut group_canvas);
+        }
+    }
+}
+
+impl Layer for LayerGroup {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        self.render_with_opacity(view, canvas, 1.0);
+    }
+
+    fn prepare(&self, view: &MapView) {
+        // Children are prepared even if the group is fully transparent, so that it is ready to be faded in.
+        for layer in self.layers.iter_visible() {
+            layer.prepare(view);
+        }
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = Arc::from(messenger);
+        for layer in self.layers.iter_mut() {
+            layer.set_messenger(Box::new(SharedMessenger(messenger.clone())));
+        }
+
+        self.messenger = Some(messenger);
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        for layer in self.layers.iter_mut() {
+            layer.set_scale_factor(scale_factor);
+        }
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.reset_gpu_resources();
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Canvas that draws all bundles to the inner canvas with the opacity of the group applied.
+struct GroupCanvas<'a> {
+    inner: &'a mut dyn Canvas,
+    opacity: f32,
+}
+
+impl GroupCanvas<'_> {
+    fn options(&self, options: RenderOptions) -> RenderOptions {
+        RenderOptions {
+            opacity: options.opacity * self.opacity,
+            ..options
+        }
+    }
+}
+
+impl Canvas for GroupCanvas<'_> {
+    fn size(&self) -> Size {
+        self.inner.size()
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.inner.scale_factor()
+    }
+
+    fn dpi(&self) -> f64 {
+        self.inner.dpi()
+    }
+
+    fn create_bundle(&self) -> RenderBundle {
+        self.inner.create_bundle()
+    }
+
+    fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+        self.inner.pack_bundle(bundle)
+    }
+
+    fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions) {
+        let options = self.options(options);
+        self.inner.draw_bundles(bundles, options);
+    }
+
+    fn draw_bundles_with_shader(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        shader: &CustomShader,
+    ) {
+        let options = self.options(options);
+        self.inner
+            .draw_bundles_with_shader(bundles, options, shader);
+    }
+
+    fn draw_bundles_with_mask(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        mask: &dyn PackedBundle,
+        shader: Option<&CustomShader>,
+    ) {
+        let options = self.options(options);
+        self.inner
+            .draw_bundles_with_mask(bundles, options, mask, shader);
+    }
+
+    #[cfg(feature = "compute")]
+    fn supports_compute(&self) -> bool {
+        self.inner.supports_compute()
+    }
+
+    #[cfg(feature = "compute")]
+    fn dispatch_compute(
+        &mut self,
+        shader: &ComputeShader,
+        workgroups: [u32; 3],
+    ) -> Result<(), GalileoError> {
+        self.inner.dispatch_compute(shader, workgroups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::render_bundle::tessellating::TessellatingRenderBundle;
+    use crate::render::render_bundle::RenderBundleType;
+    use galileo_types::cartesian::Point2d;
+    use std::sync::Mutex;
+
+    struct TestPackedBundle;
+
+    impl PackedBundle for TestPackedBundle {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    /// Canvas that records the opacity of every draw call.
+    #[derive(Default)]
+    struct TestCanvas {
+        draws: Vec<f32>,
+    }
+
+    impl Canvas for TestCanvas {
+        fn size(&self) -> Size {
+            Size::new(100.0, 100.0)
+        }
+
+        fn create_bundle(&self) -> RenderBundle {
+            RenderBundle(RenderBundleType::Tessellating(
+                TessellatingRenderBundle::new(),
+            ))
+        }
+
+        fn pack_bundle(&self, _bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+            Box::new(TestPackedBundle)
+        }
+
+        fn draw_bundles(&mut self, _bundles: &[&dyn PackedBundle], options: RenderOptions) {
+            self.draws.push(options.opacity);
+        }
+    }
+
+    /// Layer that draws one empty bundle with the given opacity.
+    struct DrawLayer(f32, Arc<Mutex<usize>>);
+
+    impl Layer for DrawLayer {
+        fn render(&self, _view: &MapView, canvas: &mut dyn Canvas) {
+            let options = RenderOptions {
+                opacity: self.0,
+                ..Default::default()
+            };
+            canvas.draw_bundles(&[&TestPackedBundle], options);
+        }
+
+        fn prepare(&self, _view: &MapView) {
+            *self.1.lock().expect("mutex is poisoned") += 1;
+        }
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn renders_visible_children_with_group_opacity() {
+        let prepared = Arc::new(Mutex::new(0));
+        let mut group = LayerGroup::new("group")
+            .with_layer(DrawLayer(1.0, prepared.clone()))
+            .with_layer(DrawLayer(0.5, prepared.clone()))
+            .with_layer(DrawLayer(1.0, prepared.clone()))
+            .with_opacity(0.5);
+        group.layers_mut().hide(2);
+
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let mut canvas = TestCanvas::default();
+        group.render(&view, &mut canvas);
+        assert_eq!(canvas.draws, vec![0.5, 0.25]);
+
+        group.prepare(&view);
+        assert_eq!(*prepared.lock().expect("mutex is poisoned"), 2);
+
+        group.set_opacity(0.0);
+        let mut canvas = TestCanvas::default();
+        group.render(&view, &mut canvas);
+        assert!(canvas.draws.is_empty());
+
+        group.set_opacity(1.5);
+        assert_eq!(group.opacity(), 1.0);
+    }
+}
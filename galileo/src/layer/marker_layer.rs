@@ -0,0 +1,609 @@
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::layer::{HitTolerance, Layer};
+use crate::map::Map;
+use crate::messenger::Messenger;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::{Canvas, PackedBundle, RenderOptions};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{
+    CartesianPoint2d, CartesianPoint2dFloat, CartesianPolygon, Point2d, Point3d,
+};
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use nalgebra::Vector2;
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+/// Function called with the index and the position of a marker when it is dragged.
+pub type MarkerCallback = dyn Fn(usize, Point2d) + Send + Sync;
+
+/// Function that limits where a marker can be dragged to. It receives the index of the marker and the position the
+/// pointer moved it to, and returns the position the marker is actually moved to, or `None` to leave the marker where
+/// it is. See [`snap_to_line`] and [`clamp_to_polygon`].
+pub type MarkerConstraint = dyn Fn(usize, Point2d) -> Option<Point2d> + Send + Sync;
+
+/// Appearance of a [`Marker`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarkerStyle {
+    /// Fill color of the marker circle.
+    pub color: Color,
+    /// Diameter of the marker circle in pixels.
+    pub size: f32,
+    /// Color of the outline.
+    pub outline_color: Color,
+    /// Width of the outline in pixels. Zero width disables the outline.
+    pub outline_width: f32,
+}
+
+impl Default for MarkerStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::rgba(220, 50, 40, 255),
+            size: 16.0,
+            outline_color: Color::WHITE,
+            outline_width: 2.0,
+        }
+    }
+}
+
+/// Point of the map shown by a [`MarkerLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    /// Position of the marker in the CRS of the map.
+    pub position: Point2d,
+    /// Appearance of the marker.
+    pub style: MarkerStyle,
+    /// If false, the marker cannot be dragged by the user.
+    pub draggable: bool,
+}
+
+impl Marker {
+    /// Creates a draggable marker with the default style.
+    pub fn new(position: Point2d) -> Self {
+        Self {
+            position,
+            style: MarkerStyle::default(),
+            draggable: true,
+        }
+    }
+
+    /// Sets the style of the marker.
+    pub fn with_style(mut self, style: MarkerStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets whether the marker can be dragged by the user.
+    pub fn with_draggable(mut self, draggable: bool) -> Self {
+        self.draggable = draggable;
+        self
+    }
+}
+
+/// Layer with markers that the user can drag around the map, e.g. to pick the start and end of a route or to
+/// position a point of interest.
+///
+/// The layer is also a [`UserEventHandler`]. It is cheaply cloneable: add one clone to the map and another one to the
+/// [`EventProcessor`](crate::control::EventProcessor) before the [`MapController`](crate::control::MapController), so
+/// that dragging a marker does not pan the map. Dragging is started with the left mouse button or a touch.
+///
+/// Callbacks are called when a marker is grabbed, moved and released, and an optional [constraint](MarkerConstraint)
+/// limits where the markers can be moved.
+///
+/// ```
+/// use galileo::control::{EventProcessor, MapController};
+/// use galileo::layer::{clamp_to_polygon, Marker, MarkerLayer};
+/// use galileo_types::cartesian::Point2d;
+/// use galileo_types::impls::ClosedContour;
+///
+/// let area = ClosedContour::new(vec![
+///     Point2d::new(0.0, 0.0),
+///     Point2d::new(1000.0, 0.0),
+///     Point2d::new(1000.0, 1000.0),
+///     Point2d::new(0.0, 1000.0),
+/// ]);
+/// let markers = MarkerLayer::new(vec![Marker::new(Point2d::new(500.0, 500.0))])
+///     .with_constraint(clamp_to_polygon(area.into()))
+///     .with_drag_end(|index, position| println!("marker {index} dropped at {position:?}"));
+///
+/// let mut event_processor = EventProcessor::default();
+/// event_processor.add_handler(markers.clone());
+/// event_processor.add_handler(MapController::default());
+/// ```
+pub struct MarkerLayer {
+    state: Arc<Mutex<MarkerState>>,
+    hit_tolerance: HitTolerance,
+    on_drag_start: Option<Arc<MarkerCallback>>,
+    on_drag: Option<Arc<MarkerCallback>>,
+    on_drag_end: Option<Arc<MarkerCallback>>,
+    constraint: Option<Arc<MarkerConstraint>>,
+    packed: Mutex<Option<(u64, Box<dyn PackedBundle>)>>,
+}
+
+struct MarkerState {
+    markers: Vec<Marker>,
+    drag: Option<MarkerDrag>,
+    /// Incremented on every change of the markers.
+    revision: u64,
+    messenger: Option<Box<dyn Messenger>>,
+}
+
+/// Marker being dragged.
+struct MarkerDrag {
+    index: usize,
+    /// Offset of the marker from the pointer when it was grabbed, so that the marker does not jump to the pointer.
+    offset: Vector2<f64>,
+}
+
+impl MarkerState {
+    fn changed(&mut self) {
+        self.revision += 1;
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+}
+
+impl Clone for MarkerLayer {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            hit_tolerance: self.hit_tolerance,
+            on_drag_start: self.on_drag_start.clone(),
+            on_drag: self.on_drag.clone(),
+            on_drag_end: self.on_drag_end.clone(),
+            constraint: self.constraint.clone(),
+            packed: Mutex::new(None),
+        }
+    }
+}
+
+impl MarkerLayer {
+    /// Creates a new layer with the given markers.
+    pub fn new(markers: Vec<Marker>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MarkerState {
+                markers,
+                drag: None,
+                revision: 0,
+                messenger: None,
+            })),
+            hit_tolerance: HitTolerance::default(),
+            on_drag_start: None,
+            on_drag: None,
+            on_drag_end: None,
+            constraint: None,
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Sets the distance from the edge of a marker within which the marker can be grabbed.
+    pub fn with_hit_tolerance(mut self, tolerance: HitTolerance) -> Self {
+        self.hit_tolerance = tolerance;
+        self
+    }
+
+    /// Sets the function called when the user grabs a marker.
+    pub fn with_drag_start(
+        mut self,
+        callback: impl Fn(usize, Point2d) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_drag_start = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets the function called every time a dragged marker is moved, with the position after the constraint is
+    /// applied.
+    pub fn with_drag(mut self, callback: impl Fn(usize, Point2d) + Send + Sync + 'static) -> Self {
+        self.on_drag = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets the function called when the user releases a dragged marker.
+    pub fn with_drag_end(
+        mut self,
+        callback: impl Fn(usize, Point2d) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_drag_end = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets the function that limits where the markers can be dragged to. See [`MarkerConstraint`].
+    pub fn with_constraint(
+        mut self,
+        constraint: impl Fn(usize, Point2d) -> Option<Point2d> + Send + Sync + 'static,
+    ) -> Self {
+        self.constraint = Some(Arc::new(constraint));
+        self
+    }
+
+    /// Markers of the layer.
+    pub fn markers(&self) -> Vec<Marker> {
+        self.state
+            .lock()
+            .expect("mutex is poisoned")
+            .markers
+            .clone()
+    }
+
+    /// Marker with the given index.
+    pub fn marker(&self, index: usize) -> Option<Marker> {
+        self.state
+            .lock()
+            .expect("mutex is poisoned")
+            .markers
+            .get(index)
+            .cloned()
+    }
+
+    /// Adds a marker on top of the others. Returns the index of the marker.
+    pub fn push(&self, marker: Marker) -> usize {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.markers.push(marker);
+        state.changed();
+        state.markers.len() - 1
+    }
+
+    /// Removes the marker with the given index. If the marker is being dragged, the drag is cancelled without calling
+    /// the drag end callback.
+    pub fn remove(&self, index: usize) -> Option<Marker> {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        if index >= state.markers.len() {
+            return None;
+        }
+
+        let marker = state.markers.remove(index);
+        let dragged = state.drag.as_ref().map(|drag| drag.index);
+        if dragged == Some(index) {
+            state.drag = None;
+        } else if let Some(drag) = state.drag.as_mut().filter(|drag| drag.index > index) {
+            drag.index -= 1;
+        }
+        state.changed();
+
+        Some(marker)
+    }
+
+    /// Moves the marker with the given index. The constraint is not applied. Returns false if there is no such marker.
+    pub fn set_position(&self, index: usize, position: Point2d) -> bool {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let Some(marker) = state.markers.get_mut(index) else {
+            return false;
+        };
+
+        marker.position = position;
+        state.changed();
+        true
+    }
+
+    /// Index of the marker the user is dragging at the moment.
+    pub fn dragged(&self) -> Option<usize> {
+        self.state
+            .lock()
+            .expect("mutex is poisoned")
+            .drag
+            .as_ref()
+            .map(|drag| drag.index)
+    }
+
+    fn pack(&self, markers: &[Marker], canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        for marker in markers {
+            let style = &marker.style;
+            let mut paint = PointPaint::circle(style.color, style.size);
+            if style.outline_width > 0.0 {
+                paint = paint.with_outline(style.outline_color, style.outline_width);
+            }
+
+            bundle.add(
+                RenderPrimitive::<_, _, Contour<_>, Polygon<_>>::new_point(
+                    Point3d::new(marker.position.x, marker.position.y, 0.0),
+                    paint,
+                ),
+                1.0,
+            );
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+}
+
+impl UserEventHandler for MarkerLayer {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        match event {
+            UserEvent::DragStarted(MouseButton::Left | MouseButton::Other, e) => {
+                let mut state = self.state.lock().expect("mutex is poisoned");
+                let tolerance = self.hit_tolerance.pixels(e.pointer_type);
+                let Some(index) = marker_at(
+                    &state.markers,
+                    map.view(),
+                    e.screen_pointer_position,
+                    tolerance,
+                ) else {
+                    return EventPropagation::Propagate;
+                };
+
+                let position = state.markers[index].position;
+                let offset = e
+                    .map_pointer_position
+                    .map_or(Vector2::zeros(), |pointer| position - pointer);
+                state.drag = Some(MarkerDrag { index, offset });
+                drop(state);
+
+                if let Some(callback) = &self.on_drag_start {
+                    callback(index, position);
+                }
+
+                EventPropagation::Consume
+            }
+            UserEvent::Drag(_, _, e) => {
+                let mut state = self.state.lock().expect("mutex is poisoned");
+                let Some(drag) = &state.drag else {
+                    return EventPropagation::Propagate;
+                };
+                let index = drag.index;
+
+                let Some(pointer) = e.map_pointer_position else {
+                    return EventPropagation::Consume;
+                };
+                let proposed = pointer + drag.offset;
+                let position = match &self.constraint {
+                    Some(constraint) => match constraint(index, proposed) {
+                        Some(position) => position,
+                        None => return EventPropagation::Consume,
+                    },
+                    None => proposed,
+                };
+
+                state.markers[index].position = position;
+                state.changed();
+                drop(state);
+
+                map.redraw();
+                if let Some(callback) = &self.on_drag {
+                    callback(index, position);
+                }
+
+                EventPropagation::Consume
+            }
+            UserEvent::DragEnded(..) => {
+                let mut state = self.state.lock().expect("mutex is poisoned");
+                let Some(drag) = state.drag.take() else {
+                    return EventPropagation::Propagate;
+                };
+                let position = state.markers[drag.index].position;
+                drop(state);
+
+                if let Some(callback) = &self.on_drag_end {
+                    callback(drag.index, position);
+                }
+
+                EventPropagation::Consume
+            }
+            _ => EventPropagation::Propagate,
+        }
+    }
+}
+
+impl Layer for MarkerLayer {
+    fn render(&self, _view: &MapView, canvas: &mut dyn Canvas) {
+        let state = self.state.lock().expect("mutex is poisoned");
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let is_outdated =
+            !matches!(packed.as_ref(), Some((revision, _)) if *revision == state.revision);
+        if is_outdated {
+            *packed = Some((state.revision, self.pack(&state.markers, canvas)));
+        }
+        drop(state);
+
+        if let Some((_, bundle)) = packed.as_ref() {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {}
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.state.lock().expect("mutex is poisoned").messenger = Some(messenger);
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Returns the index of the topmost draggable marker under the given screen position.
+fn marker_at(
+    markers: &[Marker],
+    view: &MapView,
+    screen_position: Point2d,
+    tolerance: f64,
+) -> Option<usize> {
+    markers
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, marker)| {
+            if !marker.draggable {
+                return None;
+            }
+
+            let position = view.map_to_screen(marker.position).position()?;
+            let radius = marker.style.size as f64 / 2.0 + tolerance;
+            (position.distance(&screen_position) <= radius).then_some(index)
+        })
+}
+
+/// Constraint that keeps the markers on the line (e.g. a road), moving them to the nearest point of the line.
+pub fn snap_to_line(
+    line: impl IntoIterator<Item = Point2d>,
+) -> impl Fn(usize, Point2d) -> Option<Point2d> + Send + Sync + 'static {
+    let points: Vec<_> = line.into_iter().collect();
+    move |_, position| nearest_point(position, points.windows(2).map(|s| (s[0], s[1])))
+}
+
+/// Constraint that keeps the markers inside the polygon. Markers dragged outside of it are moved to the nearest point
+/// of its boundary.
+pub fn clamp_to_polygon(
+    polygon: Polygon<Point2d>,
+) -> impl Fn(usize, Point2d) -> Option<Point2d> + Send + Sync + 'static {
+    move |_, position| {
+        if polygon.contains_point(&position) {
+            return Some(position);
+        }
+
+        let contours = std::iter::once(&polygon.outer_contour).chain(&polygon.inner_contours);
+        nearest_point(position, contours.flat_map(contour_segments))
+    }
+}
+
+fn contour_segments(
+    contour: &ClosedContour<Point2d>,
+) -> impl Iterator<Item = (Point2d, Point2d)> + '_ {
+    let points = &contour.points;
+    (0..points.len()).map(move |i| (points[i], points[(i + 1) % points.len()]))
+}
+
+/// The point of the segments closest to the `position`, or `None` if there are no segments.
+fn nearest_point(
+    position: Point2d,
+    segments: impl Iterator<Item = (Point2d, Point2d)>,
+) -> Option<Point2d> {
+    segments
+        .map(|(a, b)| {
+            let ab = b - a;
+            let length_sq = ab.norm_squared();
+            let t = if length_sq > 0.0 {
+                ((position - a).dot(&ab) / length_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            a + ab * t
+        })
+        .min_by(|a, b| position.distance_sq(a).total_cmp(&position.distance_sq(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{MouseButtonsState, MouseEvent};
+    use crate::messenger::DummyMessenger;
+    use galileo_types::cartesian::Size;
+    use galileo_types::geo::Crs;
+    use std::sync::RwLock;
+
+    fn test_map() -> Map {
+        Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(200.0, 200.0)),
+            vec![],
+            None::<DummyMessenger>,
+        )
+    }
+
+    fn mouse_event(map: &Map, screen_position: Point2d) -> MouseEvent {
+        MouseEvent {
+            screen_pointer_position: screen_position,
+            physical_pointer_position: screen_position,
+            map_pointer_position: map.view().screen_to_map(screen_position),
+            map_crs: Crs::EPSG3857,
+            buttons: MouseButtonsState::default(),
+            motion: Default::default(),
+            pointer_type: Default::default(),
+        }
+    }
+
+    #[test]
+    fn marker_is_dragged_with_constraint() {
+        let mut map = test_map();
+        let log = Arc::new(RwLock::new(vec![]));
+        let log_start = log.clone();
+        let log_end = log.clone();
+        let layer = MarkerLayer::new(vec![
+            Marker::new(Point2d::new(0.0, 0.0)),
+            Marker::new(Point2d::new(50.0, 0.0)).with_draggable(false),
+        ])
+        .with_constraint(snap_to_line([
+            Point2d::new(-100.0, 0.0),
+            Point2d::new(100.0, 0.0),
+        ]))
+        .with_drag_start(move |index, _| log_start.write().unwrap().push(("start", index)))
+        .with_drag_end(move |index, _| log_end.write().unwrap().push(("end", index)));
+
+        // Non-draggable markers and empty places are not grabbed.
+        let event = UserEvent::DragStarted(
+            MouseButton::Left,
+            mouse_event(&map, Point2d::new(150.0, 100.0)),
+        );
+        assert!(matches!(
+            layer.handle(&event, &mut map),
+            EventPropagation::Propagate
+        ));
+        assert_eq!(layer.dragged(), None);
+
+        // The marker is grabbed 3 pixels off its center, and keeps this offset from the pointer.
+        let event = UserEvent::DragStarted(
+            MouseButton::Left,
+            mouse_event(&map, Point2d::new(103.0, 100.0)),
+        );
+        assert!(matches!(
+            layer.handle(&event, &mut map),
+            EventPropagation::Consume
+        ));
+        assert_eq!(layer.dragged(), Some(0));
+
+        let event = UserEvent::Drag(
+            MouseButton::Left,
+            Vector2::new(20.0, 30.0),
+            mouse_event(&map, Point2d::new(123.0, 130.0)),
+        );
+        assert!(matches!(
+            layer.handle(&event, &mut map),
+            EventPropagation::Consume
+        ));
+        assert_eq!(layer.marker(0).unwrap().position, Point2d::new(20.0, 0.0));
+
+        let event = UserEvent::DragEnded(
+            MouseButton::Left,
+            mouse_event(&map, Point2d::new(123.0, 130.0)),
+        );
+        assert!(matches!(
+            layer.handle(&event, &mut map),
+            EventPropagation::Consume
+        ));
+        assert_eq!(layer.dragged(), None);
+        assert_eq!(*log.read().unwrap(), vec![("start", 0), ("end", 0)]);
+    }
+
+    #[test]
+    fn polygon_constraint_clamps_to_boundary() {
+        let square = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(10.0, 0.0),
+            Point2d::new(10.0, 10.0),
+            Point2d::new(0.0, 10.0),
+        ]);
+        let constraint = clamp_to_polygon(square.into());
+
+        assert_eq!(
+            constraint(0, Point2d::new(5.0, 5.0)),
+            Some(Point2d::new(5.0, 5.0))
+        );
+        assert_eq!(
+            constraint(0, Point2d::new(15.0, 5.0)),
+            Some(Point2d::new(10.0, 5.0))
+        );
+        assert_eq!(
+            constraint(0, Point2d::new(-5.0, -5.0)),
+            Some(Point2d::new(0.0, 0.0))
+        );
+    }
+}
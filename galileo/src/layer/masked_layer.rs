@@ -0,0 +1,168 @@
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{Canvas, CustomShader, PackedBundle, PolygonPaint, RenderOptions};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d, Size};
+use galileo_types::impls::{Contour, Polygon};
+use std::any::Any;
+use std::sync::Mutex;
+
+/// Layer that renders another layer only inside the area of a mask.
+///
+/// The mask is a set of polygons in the CRS of the map. Everything the inner layer draws outside of the mask polygons
+/// is cut off, so, for example, satellite imagery can be shown only inside the borders of a selected country, or a
+/// circular "spyglass" following the cursor can reveal a different basemap. If the mask is empty, nothing is drawn.
+///
+/// The mask is applied by the rendering backend when the inner layer draws its bundles, so it works with any layer.
+pub struct MaskedLayer<L> {
+    layer: L,
+    mask: Vec<Polygon<Point2d>>,
+    packed_mask: Mutex<Option<Box<dyn PackedBundle>>>,
+}
+
+impl<L: Layer> MaskedLayer<L> {
+    /// Creates a new layer that renders the `layer` clipped by the `mask` polygons.
+    pub fn new(layer: L, mask: Vec<Polygon<Point2d>>) -> Self {
+        Self {
+            layer,
+            mask,
+            packed_mask: Mutex::new(None),
+        }
+    }
+
+    /// Polygons of the mask.
+    pub fn mask(&self) -> &[Polygon<Point2d>] {
+        &self.mask
+    }
+
+    /// Replaces the mask polygons. The new mask is applied on the next redraw of the map.
+    pub fn set_mask(&mut self, mask: Vec<Polygon<Point2d>>) {
+        self.mask = mask;
+        *self.packed_mask.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    /// Returns a reference to the inner layer.
+    pub fn inner(&self) -> &L {
+        &self.layer
+    }
+
+    /// Returns a mutable reference to the inner layer.
+    pub fn inner_mut(&mut self) -> &mut L {
+        &mut self.layer
+    }
+
+    fn pack_mask(&self, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        for polygon in &self.mask {
+            let polygon = polygon.cast_points(|p| Point3d::new(p.x, p.y, 0.0));
+            bundle.add(
+                RenderPrimitive::<_, _, Contour<_>, _>::new_polygon(
+                    polygon,
+                    PolygonPaint {
+                        color: Color::BLACK,
+                        gradient: None,
+                        zoom: None,
+                    },
+                ),
+                1.0,
+            );
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+}
+
+impl<L: Layer + 'static> Layer for MaskedLayer<L> {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if self.mask.is_empty() {
+            return;
+        }
+
+        let mut packed_mask = self.packed_mask.lock().expect("mutex is poisoned");
+        let mask = packed_mask.get_or_insert_with(|| self.pack_mask(canvas));
+
+        let mut masked_canvas = MaskedCanvas {
+            inner: canvas,
+            mask: &**mask,
+        };
+        self.layer.render(view, &mut masked_canvas);
+    }
+
+    fn prepare(&self, view: &MapView) {
+        self.layer.prepare(view);
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.layer.set_messenger(messenger);
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.layer.set_scale_factor(scale_factor);
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed_mask.get_mut().expect("mutex is poisoned") = None;
+        self.layer.reset_gpu_resources();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Canvas that draws all bundles to the inner canvas with the mask applied.
+struct MaskedCanvas<'a> {
+    inner: &'a mut dyn Canvas,
+    mask: &'a dyn PackedBundle,
+}
+
+impl<'a> Canvas for MaskedCanvas<'a> {
+    fn size(&self) -> Size {
+        self.inner.size()
+    }
+
+    fn create_bundle(&self) -> RenderBundle {
+        self.inner.create_bundle()
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.inner.scale_factor()
+    }
+
+    fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+        self.inner.pack_bundle(bundle)
+    }
+
+    fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions) {
+        self.inner
+            .draw_bundles_with_mask(bundles, options, self.mask, None);
+    }
+
+    fn draw_bundles_with_shader(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        shader: &CustomShader,
+    ) {
+        self.inner
+            .draw_bundles_with_mask(bundles, options, self.mask, Some(shader));
+    }
+
+    fn draw_bundles_with_mask(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        _mask: &dyn PackedBundle,
+        shader: Option<&CustomShader>,
+    ) {
+        // Only one level of masking is supported, the outer mask takes precedence.
+        self.inner
+            .draw_bundles_with_mask(bundles, options, self.mask, shader);
+    }
+}
@@ -0,0 +1,825 @@
+//! Overlay of the MGRS grid. See [`MgrsGridLayer`].
+
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{Canvas, LineCap, LinePaint, PackedBundle, RenderOptions, SizeUnit};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d};
+use galileo_types::geo::format::{GridZone, MgrsCoordinate, UtmCoordinate};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint, Projection};
+use galileo_types::impls::{Contour, Polygon};
+use std::any::Any;
+use std::sync::Mutex;
+
+/// Distances between the grid lines in meters, from the coarsest (100 km squares) to the finest (1 m cells of a
+/// 10-digit MGRS reference).
+const GRID_STEPS: [f64; 6] = [100_000.0, 10_000.0, 1_000.0, 100.0, 10.0, 1.0];
+/// Approximate width of a UTM zone at the equator in meters.
+const ZONE_WIDTH: f64 = 667_000.0;
+const SQUARE_SIZE: f64 = 100_000.0;
+const FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+/// Number of segments a line is split into inside a grid zone, so that it follows the curvature of the grid in the
+/// projection of the map.
+const LINE_SEGMENTS: usize = 16;
+
+/// Colors and widths of the lines of an [`MgrsGridLayer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MgrsGridStyle {
+    /// Color of the boundaries of the grid zones.
+    pub zone_color: Color,
+    /// Width of the boundaries of the grid zones in pixels.
+    pub zone_width: f64,
+    /// Color of the boundaries of the 100 km squares.
+    pub square_color: Color,
+    /// Width of the boundaries of the 100 km squares in pixels.
+    pub square_width: f64,
+    /// Color of the finer grid lines.
+    pub line_color: Color,
+    /// Width of the finer grid lines in pixels.
+    pub line_width: f64,
+}
+
+impl Default for MgrsGridStyle {
+    fn default() -> Self {
+        Self {
+            zone_color: Color::rgba(200, 0, 0, 220),
+            zone_width: 2.0,
+            square_color: Color::rgba(0, 0, 160, 200),
+            square_width: 1.5,
+            line_color: Color::rgba(0, 0, 160, 140),
+            line_width: 1.0,
+        }
+    }
+}
+
+/// Kind of a [`GridLabel`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GridLabelKind {
+    /// Grid zone designator, e.g. `33U`, placed at the center of the visible part of the zone.
+    Zone,
+    /// Letters of a 100 km square, e.g. `VR`, placed at the center of the square.
+    Square,
+    /// Easting of a grid line, placed where the line crosses the bottom edge of the screen.
+    Easting,
+    /// Northing of a grid line, placed where the line crosses the left edge of the screen.
+    Northing,
+}
+
+/// Label of the MGRS grid calculated by [`MgrsGridLayer::labels`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridLabel {
+    /// Text of the label.
+    pub text: String,
+    /// Position of the label on the screen, in pixels from the top-left corner.
+    pub position: Point2d,
+    /// Kind of the label.
+    pub kind: GridLabelKind,
+}
+
+/// Layer that draws the grid of the Military Grid Reference System (and so of UTM) over the map: boundaries of the
+/// grid zones, of the 100 km squares and finer grid lines.
+///
+/// The distance between the finer lines is chosen by the zoom level of the map, so that the lines are at least the
+/// [minimum spacing](MgrsGridLayer::with_min_spacing) apart on the screen, from 100 km down to 1 m. Positions can be
+/// read off the grid in the same precision with [`CoordinateFormat::Mgrs`](galileo_types::geo::format::CoordinateFormat).
+///
+/// The layer draws only the lines. The labels of the zones, squares and lines are drawn by the application at the
+/// positions returned by [`MgrsGridLayer::labels`].
+///
+/// ```
+/// use galileo::layer::MgrsGridLayer;
+///
+/// let layer = MgrsGridLayer::new().with_min_spacing(100.0);
+/// ```
+pub struct MgrsGridLayer {
+    style: MgrsGridStyle,
+    min_spacing: f64,
+    packed: Mutex<Option<(MapView, Box<dyn PackedBundle>)>>,
+}
+
+impl Default for MgrsGridLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MgrsGridLayer {
+    /// Creates a new layer with the default style.
+    pub fn new() -> Self {
+        Self {
+            style: MgrsGridStyle::default(),
+            min_spacing: 80.0,
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Sets the style of the grid lines.
+    pub fn with_style(mut self, style: MgrsGridStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the minimum distance between grid lines on the screen in pixels. Default is 80.
+    pub fn with_min_spacing(mut self, min_spacing: f64) -> Self {
+        self.min_spacing = min_spacing.max(1.0);
+        self
+    }
+
+    /// Distance between the grid lines in meters for the given view. Returns `None` if the map is zoomed out so far
+    /// that even the 100 km squares are too small, in which case only the grid zones are drawn.
+    pub fn grid_step(&self, view: &MapView) -> Option<f64> {
+        let resolution = ground_resolution(view)?;
+        GRID_STEPS
+            .into_iter()
+            .take_while(|step| step / resolution >= self.min_spacing)
+            .last()
+    }
+
+    /// Calculates the labels of the grid for the given view.
+    ///
+    /// Zone designators are given if the zones are wide enough on the screen, the letters of the 100 km squares if
+    /// the grid step is 100 km, and the eastings and northings of the finer lines (in the digits of the MGRS
+    /// reference of the grid step, e.g. `58` for the line at 58 km of the square in the 1 km grid) otherwise.
+    pub fn labels(&self, view: &MapView) -> Vec<GridLabel> {
+        let Some(grid) = self.grid(view) else {
+            return vec![];
+        };
+
+        let to_screen = |point: &GeoPoint2d| {
+            let projected = grid.projection.project(point)?;
+            view.map_to_screen(projected).on_screen()
+        };
+        let mut labels = vec![];
+
+        if grid.show_zone_labels {
+            for (zone, rect) in &grid.zones {
+                if let Some(position) = to_screen(&rect.center()) {
+                    labels.push(GridLabel {
+                        text: zone.to_string(),
+                        position,
+                        kind: GridLabelKind::Zone,
+                    });
+                }
+            }
+        }
+
+        match grid.step {
+            Some(step) if step >= SQUARE_SIZE => {
+                for (zone, rect) in &grid.zones {
+                    for (center, text) in square_centers(zone, rect) {
+                        if let Some(position) = to_screen(&center) {
+                            labels.push(GridLabel {
+                                text,
+                                position,
+                                kind: GridLabelKind::Square,
+                            });
+                        }
+                    }
+                }
+            }
+            Some(step) => {
+                let size = view.size();
+                for line in &grid.lines {
+                    let Some(value) = line.value else {
+                        continue;
+                    };
+
+                    let screen: Vec<Point2d> = line
+                        .points
+                        .iter()
+                        .filter_map(|p| view.map_to_screen(grid.projection.project(p)?).position())
+                        .collect();
+                    let (kind, position) = if line.is_easting {
+                        (
+                            GridLabelKind::Easting,
+                            edge_crossing(&screen, |p| p.y, size.height(), |p| p.x, size.width()),
+                        )
+                    } else {
+                        (
+                            GridLabelKind::Northing,
+                            edge_crossing(&screen, |p| p.x, 0.0, |p| p.y, size.height()),
+                        )
+                    };
+
+                    if let Some(position) = position {
+                        labels.push(GridLabel {
+                            text: line_label(value, step),
+                            position,
+                            kind,
+                        });
+                    }
+                }
+            }
+            None => {}
+        }
+
+        labels
+    }
+
+    fn grid(&self, view: &MapView) -> Option<Grid> {
+        let projection = view.crs().get_projection::<GeoPoint2d, Point2d>()?;
+        let bounds = visible_bounds(view)?;
+        let resolution = ground_resolution(view)?;
+        let step = self.grid_step(view);
+
+        let mut zones = vec![];
+        for zone in GridZone::all() {
+            for visible in &bounds {
+                if let Some(rect) = GeoRect::of_zone(&zone).intersection(visible) {
+                    zones.push((zone, rect));
+                }
+            }
+        }
+
+        let mut lines = vec![];
+        for (zone, rect) in &zones {
+            let zone_rect = GeoRect::of_zone(zone);
+            let mut boundaries = vec![zone_rect.west_edge(), zone_rect.south_edge()];
+            if zone.band == 'X' {
+                boundaries.push(zone_rect.north_edge());
+            }
+
+            for boundary in boundaries {
+                for points in clip_line(&boundary, rect) {
+                    lines.push(GridLine {
+                        points,
+                        kind: LineKind::Zone,
+                        is_easting: false,
+                        value: None,
+                    });
+                }
+            }
+
+            if let Some(step) = step {
+                grid_lines(zone, rect, step, &mut lines);
+            }
+        }
+
+        Some(Grid {
+            projection,
+            step,
+            show_zone_labels: ZONE_WIDTH / resolution >= self.min_spacing,
+            zones,
+            lines,
+        })
+    }
+
+    fn pack(&self, view: &MapView, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        if let Some(grid) = self.grid(view) {
+            for line in &grid.lines {
+                add_line(&mut bundle, view, &grid, line, &self.style);
+            }
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+}
+
+impl Layer for MgrsGridLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let is_outdated = !matches!(packed.as_ref(), Some((packed_view, _)) if packed_view == view);
+        if is_outdated {
+            *packed = Some((view.clone(), self.pack(view, canvas)));
+        }
+
+        if let Some((_, bundle)) = packed.as_ref() {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {}
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Lines of the grid inside the visible part of the map.
+struct Grid {
+    projection: Box<dyn Projection<InPoint = GeoPoint2d, OutPoint = Point2d>>,
+    step: Option<f64>,
+    show_zone_labels: bool,
+    /// Grid zones with their visible parts.
+    zones: Vec<(GridZone, GeoRect)>,
+    lines: Vec<GridLine>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum LineKind {
+    Zone,
+    Square,
+    Minor,
+}
+
+struct GridLine {
+    points: Vec<GeoPoint2d>,
+    kind: LineKind,
+    /// Whether the line has constant easting (goes north) or constant northing (goes east).
+    is_easting: bool,
+    /// Easting or northing of the line in meters. Northings in the southern hemisphere include the false northing.
+    value: Option<f64>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct GeoRect {
+    lat_min: f64,
+    lat_max: f64,
+    lon_min: f64,
+    lon_max: f64,
+}
+
+impl GeoRect {
+    fn of_zone(zone: &GridZone) -> Self {
+        Self {
+            lat_min: zone.lat_min,
+            lat_max: zone.lat_max,
+            lon_min: zone.lon_min,
+            lon_max: zone.lon_max,
+        }
+    }
+
+    fn contains(&self, point: &GeoPoint2d) -> bool {
+        (self.lat_min..=self.lat_max).contains(&point.lat())
+            && (self.lon_min..=self.lon_max).contains(&point.lon())
+    }
+
+    fn intersection(&self, other: &GeoRect) -> Option<GeoRect> {
+        let rect = GeoRect {
+            lat_min: self.lat_min.max(other.lat_min),
+            lat_max: self.lat_max.min(other.lat_max),
+            lon_min: self.lon_min.max(other.lon_min),
+            lon_max: self.lon_max.min(other.lon_max),
+        };
+
+        (rect.lat_min < rect.lat_max && rect.lon_min < rect.lon_max).then_some(rect)
+    }
+
+    fn center(&self) -> GeoPoint2d {
+        GeoPoint2d::latlon(
+            (self.lat_min + self.lat_max) / 2.0,
+            (self.lon_min + self.lon_max) / 2.0,
+        )
+    }
+
+    fn west_edge(&self) -> Vec<GeoPoint2d> {
+        sample(LINE_SEGMENTS, |t| {
+            GeoPoint2d::latlon(lerp(self.lat_min, self.lat_max, t), self.lon_min)
+        })
+    }
+
+    fn south_edge(&self) -> Vec<GeoPoint2d> {
+        sample(LINE_SEGMENTS, |t| {
+            GeoPoint2d::latlon(self.lat_min, lerp(self.lon_min, self.lon_max, t))
+        })
+    }
+
+    fn north_edge(&self) -> Vec<GeoPoint2d> {
+        sample(LINE_SEGMENTS, |t| {
+            GeoPoint2d::latlon(self.lat_max, lerp(self.lon_min, self.lon_max, t))
+        })
+    }
+
+    fn boundary(&self) -> impl Iterator<Item = GeoPoint2d> {
+        let east = GeoRect {
+            lon_min: self.lon_max,
+            ..*self
+        };
+        let north = GeoRect {
+            lat_min: self.lat_max,
+            ..*self
+        };
+        [
+            self.west_edge(),
+            self.south_edge(),
+            east.west_edge(),
+            north.south_edge(),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Part of the segment inside the rectangle (Liang–Barsky clipping). The ends of the segment are returned
+    /// unchanged if they are inside.
+    fn clip_segment(&self, from: GeoPoint2d, to: GeoPoint2d) -> Option<(GeoPoint2d, GeoPoint2d)> {
+        let (dx, dy) = (to.lon() - from.lon(), to.lat() - from.lat());
+        let (mut t_min, mut t_max) = (0.0, 1.0);
+        for (p, q) in [
+            (-dx, from.lon() - self.lon_min),
+            (dx, self.lon_max - from.lon()),
+            (-dy, from.lat() - self.lat_min),
+            (dy, self.lat_max - from.lat()),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+
+            let t = q / p;
+            if p < 0.0 {
+                if t > t_max {
+                    return None;
+                }
+                t_min = f64::max(t_min, t);
+            } else {
+                if t < t_min {
+                    return None;
+                }
+                t_max = f64::min(t_max, t);
+            }
+        }
+
+        let at = |t: f64| {
+            if t <= 0.0 {
+                from
+            } else if t >= 1.0 {
+                to
+            } else {
+                GeoPoint2d::latlon(from.lat() + dy * t, from.lon() + dx * t)
+            }
+        };
+        Some((at(t_min), at(t_max)))
+    }
+}
+
+/// Ground resolution at the center of the view in meters per pixel.
+fn ground_resolution(view: &MapView) -> Option<f64> {
+    let size = view.size();
+    view.ground_resolution(Point2d::new(size.half_width(), size.half_height()))
+        .filter(|resolution| *resolution > 0.0)
+}
+
+/// Visible part of the map as geographic rectangles: one, or two if the view crosses the antimeridian.
+fn visible_bounds(view: &MapView) -> Option<Vec<GeoRect>> {
+    let footprint = view.footprint()?;
+    let points = &footprint.outer_contour.points;
+    let reference = points.first()?.lon();
+
+    let (mut lat_min, mut lat_max) = (f64::INFINITY, f64::NEG_INFINITY);
+    let (mut lon_min, mut lon_max) = (f64::INFINITY, f64::NEG_INFINITY);
+    for point in points {
+        let lon = reference + normalize_longitude(point.lon() - reference);
+        lat_min = lat_min.min(point.lat());
+        lat_max = lat_max.max(point.lat());
+        lon_min = lon_min.min(lon);
+        lon_max = lon_max.max(lon);
+    }
+
+    let rect = |lon_min: f64, lon_max: f64| GeoRect {
+        lat_min,
+        lat_max,
+        lon_min,
+        lon_max,
+    };
+
+    if lon_max - lon_min >= 360.0 {
+        return Some(vec![rect(-180.0, 180.0)]);
+    }
+
+    let shift = ((lon_min + 180.0) / 360.0).floor() * 360.0;
+    let (lon_min, lon_max) = (lon_min - shift, lon_max - shift);
+    if lon_max > 180.0 {
+        Some(vec![rect(lon_min, 180.0), rect(-180.0, lon_max - 360.0)])
+    } else {
+        Some(vec![rect(lon_min, lon_max)])
+    }
+}
+
+fn normalize_longitude(lon: f64) -> f64 {
+    (lon + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+fn sample<T>(segments: usize, f: impl Fn(f64) -> T) -> Vec<T> {
+    (0..=segments)
+        .map(|i| f(i as f64 / segments as f64))
+        .collect()
+}
+
+/// Splits the polyline into the parts inside the rectangle.
+fn clip_line(points: &[GeoPoint2d], rect: &GeoRect) -> Vec<Vec<GeoPoint2d>> {
+    let mut lines = vec![];
+    let mut current: Vec<GeoPoint2d> = vec![];
+    let mut flush = |current: &mut Vec<GeoPoint2d>| {
+        if current.len() > 1 {
+            lines.push(std::mem::take(current));
+        } else {
+            current.clear();
+        }
+    };
+
+    for segment in points.windows(2) {
+        let Some((from, to)) = rect.clip_segment(segment[0], segment[1]) else {
+            flush(&mut current);
+            continue;
+        };
+
+        if current.last() != Some(&from) {
+            flush(&mut current);
+            current.push(from);
+        }
+        current.push(to);
+
+        if to != segment[1] {
+            flush(&mut current);
+        }
+    }
+
+    flush(&mut current);
+    lines
+}
+
+/// UTM position in the zone with the northing counted from the equator (negative in the southern hemisphere), so
+/// that the grid lines continue across the equator.
+fn utm_position(zone: &GridZone, point: &GeoPoint2d) -> Option<(f64, f64)> {
+    let utm = UtmCoordinate::from_geo_in_zone(point, zone.zone)?;
+    let northing = if utm.northern {
+        utm.northing
+    } else {
+        utm.northing - FALSE_NORTHING_SOUTH
+    };
+    Some((utm.easting, northing))
+}
+
+fn utm_to_geo(zone: &GridZone, easting: f64, northing: f64) -> Option<GeoPoint2d> {
+    let point = UtmCoordinate {
+        zone: zone.zone,
+        northern: northing >= 0.0,
+        easting,
+        northing: if northing >= 0.0 {
+            northing
+        } else {
+            northing + FALSE_NORTHING_SOUTH
+        },
+    }
+    .to_geo()?;
+
+    // Keep the points east of the antimeridian next to the zone, so that clipping does not connect them across
+    // the whole map.
+    let center = (zone.lon_min + zone.lon_max) / 2.0;
+    Some(GeoPoint2d::latlon(
+        point.lat(),
+        center + normalize_longitude(point.lon() - center),
+    ))
+}
+
+/// Ranges of eastings and northings (see [`utm_position`]) covered by the part of the grid zone.
+fn utm_extent(zone: &GridZone, rect: &GeoRect) -> Option<[(f64, f64); 2]> {
+    let (mut e_min, mut e_max) = (f64::INFINITY, f64::NEG_INFINITY);
+    let (mut n_min, mut n_max) = (f64::INFINITY, f64::NEG_INFINITY);
+    for point in rect.boundary() {
+        let Some((easting, northing)) = utm_position(zone, &point) else {
+            continue;
+        };
+        e_min = e_min.min(easting);
+        e_max = e_max.max(easting);
+        n_min = n_min.min(northing);
+        n_max = n_max.max(northing);
+    }
+
+    (e_min <= e_max && n_min <= n_max).then_some([(e_min, e_max), (n_min, n_max)])
+}
+
+/// Adds the easting and northing lines with the given step inside the visible part of the grid zone.
+fn grid_lines(zone: &GridZone, rect: &GeoRect, step: f64, lines: &mut Vec<GridLine>) {
+    let Some([(e_min, e_max), (n_min, n_max)]) = utm_extent(zone, rect) else {
+        return;
+    };
+
+    let kind = |value: f64| {
+        if (value / SQUARE_SIZE).fract().abs() < 1e-9 {
+            LineKind::Square
+        } else {
+            LineKind::Minor
+        }
+    };
+
+    for is_easting in [true, false] {
+        let (min, max, along_min, along_max) = if is_easting {
+            (e_min, e_max, n_min, n_max)
+        } else {
+            (n_min, n_max, e_min, e_max)
+        };
+
+        let mut index = (min / step).ceil();
+        while index * step <= max {
+            let value = index * step;
+            index += 1.0;
+
+            let points: Option<Vec<GeoPoint2d>> = (0..=LINE_SEGMENTS)
+                .map(|i| {
+                    let along = lerp(along_min, along_max, i as f64 / LINE_SEGMENTS as f64);
+                    if is_easting {
+                        utm_to_geo(zone, value, along)
+                    } else {
+                        utm_to_geo(zone, along, value)
+                    }
+                })
+                .collect();
+            let Some(points) = points else {
+                continue;
+            };
+
+            for points in clip_line(&points, rect) {
+                lines.push(GridLine {
+                    points,
+                    kind: kind(value),
+                    is_easting,
+                    value: Some(if value < 0.0 {
+                        value + FALSE_NORTHING_SOUTH
+                    } else {
+                        value
+                    }),
+                });
+            }
+        }
+    }
+}
+
+/// Centers of the 100 km squares inside the visible part of the zone with the letters of the squares.
+fn square_centers(zone: &GridZone, rect: &GeoRect) -> Vec<(GeoPoint2d, String)> {
+    let Some([(e_min, e_max), (n_min, n_max)]) = utm_extent(zone, rect) else {
+        return vec![];
+    };
+
+    let squares = |min: f64, max: f64| {
+        ((min / SQUARE_SIZE).floor() as i64)..=((max / SQUARE_SIZE).floor() as i64)
+    };
+    let mut centers = vec![];
+    for column in squares(e_min, e_max) {
+        for row in squares(n_min, n_max) {
+            let easting = (column as f64 + 0.5) * SQUARE_SIZE;
+            let northing = (row as f64 + 0.5) * SQUARE_SIZE;
+            let Some(center) = utm_to_geo(zone, easting, northing) else {
+                continue;
+            };
+            if !rect.contains(&center) {
+                continue;
+            }
+
+            let mgrs = UtmCoordinate::from_geo_in_zone(&center, zone.zone)
+                .and_then(|utm| MgrsCoordinate::from_utm(&utm, zone.band, 0));
+            if let Some(mgrs) = mgrs {
+                centers.push((center, mgrs.square.iter().collect()));
+            }
+        }
+    }
+
+    centers
+}
+
+/// Text of the label of the grid line: the digits of the MGRS reference of the line at the precision of the step.
+fn line_label(value: f64, step: f64) -> String {
+    let digits = (SQUARE_SIZE / step).log10().round() as usize;
+    let number = ((value % SQUARE_SIZE) / step).round() as u64;
+    format!("{number:0digits$}")
+}
+
+/// First point where the screen polyline crosses the line `coord(p) == edge`, if the crossing is within `0..=extent`
+/// along the other axis.
+fn edge_crossing(
+    points: &[Point2d],
+    coord: impl Fn(&Point2d) -> f64,
+    edge: f64,
+    other: impl Fn(&Point2d) -> f64,
+    extent: f64,
+) -> Option<Point2d> {
+    points.windows(2).find_map(|segment| {
+        let (a, b) = (coord(&segment[0]) - edge, coord(&segment[1]) - edge);
+        if a * b > 0.0 || a == b {
+            return None;
+        }
+
+        let t = a / (a - b);
+        let position = segment[0] + (segment[1] - segment[0]) * t;
+        (0.0..=extent)
+            .contains(&other(&position))
+            .then_some(position)
+    })
+}
+
+fn add_line(
+    bundle: &mut RenderBundle,
+    view: &MapView,
+    grid: &Grid,
+    line: &GridLine,
+    style: &MgrsGridStyle,
+) {
+    let points: Option<Vec<Point3d>> = line
+        .points
+        .iter()
+        .map(|p| {
+            let p = grid.projection.project(p)?;
+            Some(Point3d::new(p.x, p.y, 0.0))
+        })
+        .collect();
+    let Some(points) = points else {
+        return;
+    };
+
+    let (color, width) = match line.kind {
+        LineKind::Zone => (style.zone_color, style.zone_width),
+        LineKind::Square => (style.square_color, style.square_width),
+        LineKind::Minor => (style.line_color, style.line_width),
+    };
+
+    bundle.add(
+        RenderPrimitive::<_, _, _, Polygon<_>>::new_contour(
+            Contour::open(points),
+            LinePaint {
+                color,
+                width,
+                offset: 0.0,
+                width_unit: SizeUnit::Pixels,
+                line_cap: LineCap::Butt,
+                pattern: None,
+                gradient: None,
+                zoom: None,
+            },
+        ),
+        view.resolution(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::Size;
+
+    fn prague_view(resolution: f64) -> MapView {
+        MapView::new(&GeoPoint2d::latlon(50.087, 14.4208), resolution)
+            .with_size(Size::new(800.0, 600.0))
+    }
+
+    #[test]
+    fn grid_step_follows_zoom() {
+        let layer = MgrsGridLayer::new();
+
+        // Web Mercator resolution is about 1.56 times the ground resolution at 50°.
+        assert_eq!(layer.grid_step(&prague_view(15.0)), Some(1_000.0));
+        assert_eq!(layer.grid_step(&prague_view(1.5)), Some(100.0));
+        assert_eq!(layer.grid_step(&prague_view(1_500.0)), Some(100_000.0));
+        assert_eq!(layer.grid_step(&prague_view(20_000.0)), None);
+    }
+
+    #[test]
+    fn labels_of_lines_and_zones() {
+        let layer = MgrsGridLayer::new();
+
+        let labels = layer.labels(&prague_view(15.0));
+        let eastings: Vec<&str> = labels
+            .iter()
+            .filter(|label| label.kind == GridLabelKind::Easting)
+            .map(|label| label.text.as_str())
+            .collect();
+        assert!(eastings.contains(&"58"), "{eastings:?}");
+        assert!(labels
+            .iter()
+            .filter(|label| label.kind != GridLabelKind::Zone)
+            .all(|label| label.text.len() == 2));
+
+        let labels = layer.labels(&prague_view(1_500.0));
+        assert!(labels
+            .iter()
+            .any(|label| label.kind == GridLabelKind::Zone && label.text == "33U"));
+        assert!(labels
+            .iter()
+            .any(|label| label.kind == GridLabelKind::Square && label.text == "VR"));
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_zone() {
+        let zone = GridZone::all()
+            .find(|zone| zone.zone == 33 && zone.band == 'U')
+            .expect("zone exists");
+        let rect = GeoRect::of_zone(&zone);
+
+        let mut lines = vec![];
+        grid_lines(&zone, &rect, SQUARE_SIZE, &mut lines);
+        assert!(!lines.is_empty());
+        for point in lines.iter().flat_map(|line| &line.points) {
+            assert!((12.0 - 1e-9..=18.0 + 1e-9).contains(&point.lon()));
+            assert!((48.0 - 1e-9..=56.0 + 1e-9).contains(&point.lat()));
+        }
+        assert!(lines.iter().all(|line| line.kind == LineKind::Square));
+    }
+}
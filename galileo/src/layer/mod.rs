@@ -7,22 +7,72 @@ use maybe_sync::{MaybeSend, MaybeSync};
 use std::any::Any;
 use std::sync::{Arc, RwLock};
 
+mod aggregation_layer;
+mod annotation_layer;
+mod atmosphere_layer;
+mod basemap_switcher;
 pub mod data_provider;
 pub mod feature_layer;
+mod frame_sequencer;
+mod geofence_layer;
+mod hit_tolerance;
+mod layer_group;
+mod marker_layer;
+mod masked_layer;
+mod mgrs_grid_layer;
 mod raster_tile_layer;
+mod retry_policy;
+mod surface_layer;
+mod tile_load_monitor;
+mod trajectory_layer;
+mod vector_field_layer;
 pub mod vector_tile_layer;
 
+pub use aggregation_layer::{Aggregate, AggregationLayer, Bin, BinShape, BinSize};
+pub use annotation_layer::{
+    Annotation, AnnotationLayer, AnnotationStyle, Arrow, TextBox, TextBoxPlacement,
+};
+pub use atmosphere_layer::AtmosphereLayer;
+pub use basemap_switcher::BasemapSwitcher;
 pub use feature_layer::FeatureLayer;
+pub use frame_sequencer::FrameSequencer;
+pub use geofence_layer::{GeofenceLayer, GeofenceStyle};
+pub use hit_tolerance::HitTolerance;
+pub use layer_group::LayerGroup;
+pub use marker_layer::{
+    clamp_to_polygon, snap_to_line, Marker, MarkerCallback, MarkerConstraint, MarkerLayer,
+    MarkerStyle,
+};
+pub use masked_layer::MaskedLayer;
+pub use mgrs_grid_layer::{GridLabel, GridLabelKind, MgrsGridLayer, MgrsGridStyle};
 pub use raster_tile_layer::RasterTileLayer;
+pub use retry_policy::RetryPolicy;
+pub use surface_layer::SurfaceLayer;
+pub use tile_load_monitor::{TileLoadEvent, TileLoadMonitor, TileLoadProgress};
+pub use trajectory_layer::{Timeline, Track, TrajectoryLayer, TrajectoryStyle};
+pub use vector_field_layer::{VectorField, VectorFieldLayer};
 pub use vector_tile_layer::VectorTileLayer;
 
 /// Layers specify a data source and the way the data should be rendered to the map.
 ///
-/// There are currently 3 types of layers:
+/// There are currently 16 types of layers:
 /// * [`RasterTileLayer`] - downloads prerendered tiles from an Internet source and draws them as is.
 /// * [`VectorTileLayer`] - downloads vector tiles (in MVT format) from an Internet source and draws them using the
 ///   provided stylesheet.
 /// * [`FeatureLayer`] - draws custom set of geographic objects with the given [`feature_layer::Symbol`];
+/// * [`MaskedLayer`] - draws another layer only inside the area of a polygon mask;
+/// * [`AnnotationLayer`] - draws text boxes, callouts and arrows anchored to map coordinates;
+/// * [`FrameSequencer`] - plays a set of raster tile layers as frames of an animation;
+/// * [`AtmosphereLayer`] - draws the sky and the fog near the horizon of tilted views;
+/// * [`MgrsGridLayer`] - draws the MGRS/UTM grid with the precision adapted to the zoom level;
+/// * [`GeofenceLayer`] - draws the fences of a [`GeofenceMonitor`](crate::geofence::GeofenceMonitor);
+/// * [`VectorFieldLayer`] - draws a gridded vector field (wind, currents etc.) as arrows;
+/// * [`AggregationLayer`] - draws point features aggregated into hexagonal or square bins;
+/// * [`SurfaceLayer`] - draws a surface interpolated from scattered measurements, with isolines;
+/// * [`TrajectoryLayer`] - plays timestamped tracks with fading trails;
+/// * [`MarkerLayer`] - draws markers that can be dragged with the mouse;
+/// * [`LayerGroup`] - combines several layers into one that can be toggled, reordered and faded as a unit;
+/// * [`BasemapSwitcher`] - shows one of a set of alternative basemaps and cross-fades between them.
 pub trait Layer: MaybeSend + MaybeSync {
     /// Renders the layer to the given canvas.
     fn render(&self, view: &MapView, canvas: &mut dyn Canvas);
@@ -32,6 +82,13 @@ pub trait Layer: MaybeSend + MaybeSync {
     /// Sets the messenger for the layer. Messenger is used to notify the application when the layer thinks it should
     /// be updated on the screen.
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>);
+    /// Notifies the layer about the scale factor (the number of physical pixels in one logical pixel) of the surface
+    /// the layer is rendered to. Layers can use it to choose the data for high-DPI screens.
+    fn set_scale_factor(&mut self, _scale_factor: f64) {}
+    /// Drops everything the layer has uploaded to the GPU (packed bundles), so that it is packed again on the next
+    /// render. Called by [`Map::reset_gpu_resources`](crate::Map::reset_gpu_resources) after the renderer recovered
+    /// from a GPU device loss, since the resources of the lost device cannot be drawn anymore.
+    fn reset_gpu_resources(&mut self) {}
     /// A map stores layers as trait objects. This method can be used to convert the trait object into the concrete type.
     fn as_any(&self) -> &dyn Any;
     /// A map stores layers as trait objects. This method can be used to convert the trait object into the concrete type.
@@ -55,6 +112,18 @@ impl<T: Layer + 'static> Layer for Arc<RwLock<T>> {
             .set_messenger(messenger)
     }
 
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.write()
+            .expect("lock is poisoned")
+            .set_scale_factor(scale_factor)
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        self.write()
+            .expect("lock is poisoned")
+            .reset_gpu_resources()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -0,0 +1,957 @@
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::DataProvider;
+use crate::layer::{RetryPolicy, TileLoadMonitor};
+use crate::messenger::Messenger;
+use crate::render::render_bundle::RenderBundle;
+use crate::render::{
+    Canvas, GpuMemoryBudget, ImagePaint, MemoryTracker, PackedBundle, PrimitiveId, RenderOptions,
+};
+use crate::tile_scheme::{TileIndex, TileSchema};
+use crate::view::MapView;
+use maybe_sync::{MaybeSend, MaybeSync, Mutex};
+use std::any::Any;
+use std::collections::HashSet;
+use std::sync::Arc;
+use time::{Frame, Playback, TileCache, TimeDimension};
+use web_time::{Duration, Instant, SystemTime};
+
+use super::Layer;
+
+mod time;
+
+/// Raster tile layers load prerender tile sets using [`Provider`](DataProvider) and render them to the map.
+///
+/// When the map is zoomed in beyond the last level of the tile schema, or the source does not have some of the tiles
+/// (see [`TileSchema::with_max_z`]), the tiles of the lower levels are displayed upscaled instead.
+///
+/// A layer can have a time dimension (see [`RasterTileLayer::with_time_steps`]), e.g. for weather radar or satellite
+/// loops. Then it displays one time step at a time, preloads the tiles of the adjacent steps, and cross-fades between
+/// the steps when the time is changed.
+pub struct RasterTileLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    tile_provider: Arc<Provider>,
+    tile_scheme: TileSchema,
+    base_tile_scheme: TileSchema,
+    source_variants: Vec<(f64, Arc<Provider>)>,
+    scale_factor: f64,
+    fade_in_duration: Duration,
+    tiles: Arc<TileCache>,
+    prev_drawn_tiles: Mutex<Vec<TileIndex>>,
+    messenger: Option<Arc<dyn Messenger>>,
+    memory: MemoryTracker<TileIndex>,
+    budget: GpuMemoryBudget,
+    load_monitor: TileLoadMonitor,
+    retry_policy: RetryPolicy,
+    previous_source: Mutex<Option<PreviousSource<Provider>>>,
+    /// Incremented when the packed bundles of all tiles become invalid and must be packed again.
+    pack_generation: usize,
+    time: Option<TimeDimension<Provider>>,
+}
+
+/// Source of the layer before it was replaced with [`RasterTileLayer::set_source`]. Its tiles are drawn under the
+/// tiles of the new source until the new tiles are loaded and faded in.
+struct PreviousSource<Provider> {
+    /// Tiles of the previous source stay tracked by the memory budget until the source is dropped.
+    frame: Frame<Provider>,
+    drawn_tiles: Vec<TileIndex>,
+}
+
+/// Everything needed to load a tile in a background task.
+struct TileLoader<Provider> {
+    tile_provider: Arc<Provider>,
+    tiles: Arc<TileCache>,
+    memory: MemoryTracker<TileIndex>,
+    messenger: Option<Arc<dyn Messenger>>,
+    load_monitor: TileLoadMonitor,
+    retry_policy: RetryPolicy,
+    previous: Option<(Arc<Provider>, Arc<TileCache>)>,
+}
+
+enum TileState {
+    Loading,
+    Loaded(Mutex<DecodedImage>),
+    Rendered(Box<Mutex<RenderedTile>>),
+    /// Tile failed to load and should not be requested again until the given time.
+    Error(Instant),
+}
+
+struct RenderedTile {
+    render_bundle: RenderBundle,
+    packed_bundle: Box<dyn PackedBundle>,
+    first_drawn: SystemTime,
+    is_opaque: bool,
+    primitive_id: PrimitiveId,
+    pack_generation: usize,
+    /// Size of the tile image in GPU memory.
+    image_size: usize,
+}
+
+impl<Provider> RasterTileLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    /// Creates anew layer.
+    pub fn new(
+        tile_scheme: TileSchema,
+        tile_provider: Provider,
+        messenger: Option<Arc<dyn Messenger>>,
+    ) -> Self {
+        let tile_provider = Arc::new(tile_provider);
+        let budget = GpuMemoryBudget::unlimited();
+        let frame = Frame::new(tile_provider.clone(), &budget);
+        Self {
+            source_variants: vec![(1.0, tile_provider)],
+            tile_provider: frame.tile_provider,
+            base_tile_scheme: tile_scheme.clone(),
+            tile_scheme,
+            scale_factor: 1.0,
+            prev_drawn_tiles: Mutex::new(vec![]),
+            fade_in_duration: Duration::from_millis(300),
+            tiles: frame.tiles,
+            messenger,
+            memory: frame.memory,
+            budget,
+            load_monitor: TileLoadMonitor::new(),
+            retry_policy: RetryPolicy::default(),
+            previous_source: Mutex::new(None),
+            pack_generation: 0,
+            time: None,
+        }
+    }
+
+    /// Replaces the source of the tiles, e.g. to switch between basemaps.
+    ///
+    /// The tiles of the previous source are displayed until the tiles of the new source are loaded, and the new tiles
+    /// fade in over them (see [`RasterTileLayer::set_fade_in_duration`]). Tiles for which both sources return the same
+    /// [cache key](DataProvider::cache_key), e.g. the same URL, are taken from the previous source without loading
+    /// them again.
+    ///
+    /// The high-DPI variants of the previous source added with [`RasterTileLayer::with_source_variant`] and the time
+    /// steps set with [`RasterTileLayer::with_time_steps`] are removed.
+    pub fn set_source(&mut self, tile_provider: Provider) {
+        self.time = None;
+        let tile_provider = Arc::new(tile_provider);
+        self.source_variants = vec![(1.0, tile_provider.clone())];
+        self.tile_scheme = self.base_tile_scheme.clone();
+        self.replace_source(tile_provider);
+    }
+
+    fn replace_source(&mut self, tile_provider: Arc<Provider>) {
+        self.replace_frame(Frame::new(tile_provider, &self.budget));
+    }
+
+    /// Replaces the source and the tiles of the layer. The tiles of the previous source are drawn under the new tiles
+    /// until the new tiles are faded in.
+    fn replace_frame(&mut self, frame: Frame<Provider>) {
+        let previous = PreviousSource {
+            frame: Frame {
+                tile_provider: std::mem::replace(&mut self.tile_provider, frame.tile_provider),
+                tiles: std::mem::replace(&mut self.tiles, frame.tiles),
+                memory: std::mem::replace(&mut self.memory, frame.memory),
+            },
+            drawn_tiles: std::mem::take(&mut *self.prev_drawn_tiles.lock()),
+        };
+        *self.previous_source.lock() = Some(previous);
+
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Source of the tiles.
+    pub fn source(&self) -> &Provider {
+        &self.tile_provider
+    }
+
+    /// Adds a variant of the tile source for high-DPI screens, e.g. a source of `@2x` or 512-pixel tiles with
+    /// `scale == 2.0`.
+    ///
+    /// The tiles of the variant must cover the same areas as the tiles of the layer's tile schema, and be `scale`
+    /// times larger in pixels (see [`TileSchema::scaled`]). The layer uses the variant with the smallest scale that is
+    /// not less than the scale factor of the surface (see [`Layer::set_scale_factor`]), or the variant with the largest
+    /// scale if the surface scale factor is larger than all of them.
+    ///
+    /// ```no_run
+    /// use galileo::layer::data_provider::UrlImageProvider;
+    /// use galileo::layer::RasterTileLayer;
+    /// use galileo::tile_scheme::{TileIndex, TileSchema};
+    ///
+    /// let layer = RasterTileLayer::new(
+    ///     TileSchema::web(18),
+    ///     UrlImageProvider::new(|index: &TileIndex| {
+    ///         format!("https://example.com/{}/{}/{}.png", index.z, index.x, index.y)
+    ///     }),
+    ///     None,
+    /// )
+    /// .with_source_variant(
+    ///     2.0,
+    ///     UrlImageProvider::new(|index: &TileIndex| {
+    ///         format!("https://example.com/{}/{}/{}@2x.png", index.z, index.x, index.y)
+    ///     }),
+    /// );
+    /// ```
+    pub fn with_source_variant(mut self, scale: f64, tile_provider: Provider) -> Self {
+        if self.time.is_some() {
+            log::warn!("Source variants are not supported for layers with time steps");
+            return self;
+        }
+
+        self.source_variants
+            .retain(|(variant_scale, _)| *variant_scale != scale);
+        self.source_variants.push((scale, Arc::new(tile_provider)));
+        self.source_variants
+            .sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        self.select_source_variant();
+        self
+    }
+
+    /// Adds a time dimension to the layer. The `provider` function creates the tile source for a time step, e.g. by
+    /// substituting the time into a [`TileUrlTemplate`](crate::layer::data_provider::TileUrlTemplate) or the `TIME`
+    /// parameter of a [`WmsTileSource`](crate::layer::data_provider::WmsTileSource).
+    ///
+    /// The layer displays the first step. The tiles of the adjacent steps are loaded in advance (see
+    /// [`RasterTileLayer::set_time_preload`]), so that switching between the steps is fast. The high-DPI variants of
+    /// the source (see [`RasterTileLayer::with_source_variant`]) are not used with time steps.
+    ///
+    /// ```no_run
+    /// use galileo::layer::data_provider::{TileUrlTemplate, UrlImageProvider};
+    /// use galileo::layer::RasterTileLayer;
+    /// use galileo::tile_scheme::TileSchema;
+    /// use std::time::Duration;
+    ///
+    /// let template = TileUrlTemplate::new("https://example.com/radar/{time}/{z}/{x}/{y}.png");
+    /// let steps = vec!["2024-06-01T12:00Z".to_string(), "2024-06-01T12:10Z".to_string()];
+    /// let mut layer = RasterTileLayer::new(
+    ///     TileSchema::web(18),
+    ///     UrlImageProvider::new(template.source(None)),
+    ///     None,
+    /// )
+    /// .with_time_steps(steps, move |time| UrlImageProvider::new(template.source(Some(time))));
+    ///
+    /// layer.play_time(Duration::from_millis(500), true);
+    /// // In the render loop:
+    /// layer.update_time();
+    /// ```
+    pub fn with_time_steps(
+        mut self,
+        steps: Vec<String>,
+        provider: impl Fn(&str) -> Provider + Send + Sync + 'static,
+    ) -> Self {
+        if steps.is_empty() {
+            self.time = None;
+            return self;
+        }
+
+        let mut time = TimeDimension::new(steps, provider, &self.budget);
+        let frame = time.frame(0);
+        time.set_preload(1);
+
+        self.source_variants.clear();
+        self.tile_scheme = self.base_tile_scheme.clone();
+        self.tile_provider = frame.tile_provider;
+        self.tiles = frame.tiles;
+        self.memory = frame.memory;
+        self.time = Some(time);
+        self
+    }
+
+    /// Time steps of the layer. Empty if the layer has no time dimension.
+    pub fn time_steps(&self) -> &[String] {
+        self.time.as_ref().map(|t| t.steps()).unwrap_or_default()
+    }
+
+    /// Index of the displayed time step.
+    pub fn time_step(&self) -> Option<usize> {
+        self.time.as_ref().map(|t| t.current())
+    }
+
+    /// Displayed time step.
+    pub fn current_time(&self) -> Option<&str> {
+        let time = self.time.as_ref()?;
+        Some(&time.steps()[time.current()])
+    }
+
+    /// Sets the number of time steps before and after the displayed one, tiles of which are loaded in advance.
+    /// Default is 1.
+    pub fn set_time_preload(&mut self, count: usize) {
+        if let Some(time) = &mut self.time {
+            time.set_preload(count);
+        }
+    }
+
+    /// Displays the time step with the given index. The new tiles are faded in over the tiles of the previous step
+    /// (see [`RasterTileLayer::set_fade_in_duration`]).
+    pub fn set_time_step(&mut self, step: usize) -> Result<(), GalileoError> {
+        let Some(time) = &mut self.time else {
+            return Err(GalileoError::Generic("layer has no time dimension".into()));
+        };
+        if step >= time.steps().len() {
+            return Err(GalileoError::Generic(format!(
+                "time step {step} is out of range"
+            )));
+        }
+        if step == time.current() {
+            return Ok(());
+        }
+
+        let current = Frame {
+            tile_provider: self.tile_provider.clone(),
+            tiles: self.tiles.clone(),
+            memory: self.memory.clone(),
+        };
+        let frame = time.switch(step, current);
+
+        // Tiles of the new step that were displayed before are faded in again to cross-fade with the previous step.
+        let now = SystemTime::now();
+        for index in self.prev_drawn_tiles.lock().iter() {
+            if let Some(tile) = frame.tiles.get(index) {
+                if let TileState::Rendered(rendered) = &*tile {
+                    let mut rendered = rendered.lock();
+                    rendered.first_drawn = now;
+                    rendered.is_opaque = false;
+                }
+            }
+        }
+
+        self.replace_frame(frame);
+        Ok(())
+    }
+
+    /// Displays the time step with the given value.
+    pub fn set_time(&mut self, time: &str) -> Result<(), GalileoError> {
+        let step = self
+            .time_steps()
+            .iter()
+            .position(|t| t == time)
+            .ok_or_else(|| GalileoError::Generic(format!("unknown time step {time}")))?;
+        self.set_time_step(step)
+    }
+
+    /// Starts switching the time steps automatically, one step every `frame_duration`. The steps are switched by
+    /// [`RasterTileLayer::update_time`], which should be called before every frame is rendered.
+    pub fn play_time(&mut self, frame_duration: Duration, looped: bool) {
+        if let Some(time) = &mut self.time {
+            time.playback = Some(Playback::new(frame_duration, looped, SystemTime::now()));
+        }
+    }
+
+    /// Pauses the playback of the time steps.
+    pub fn pause_time(&mut self) {
+        if let Some(playback) = self.time.as_mut().and_then(|t| t.playback.as_mut()) {
+            playback.pause(SystemTime::now());
+        }
+    }
+
+    /// Continues the paused playback of the time steps.
+    pub fn resume_time(&mut self) {
+        if let Some(playback) = self.time.as_mut().and_then(|t| t.playback.as_mut()) {
+            playback.resume(SystemTime::now());
+        }
+    }
+
+    /// Stops the playback of the time steps. The current step stays displayed.
+    pub fn stop_time(&mut self) {
+        if let Some(time) = &mut self.time {
+            time.playback = None;
+        }
+    }
+
+    /// Returns true if the time steps are being played (including when the playback is paused).
+    pub fn is_time_playing(&self) -> bool {
+        self.time.as_ref().is_some_and(|t| t.playback.is_some())
+    }
+
+    /// Switches to the time step that should be displayed according to the playback started with
+    /// [`RasterTileLayer::play_time`]. Returns true if the playback continues.
+    pub fn update_time(&mut self) -> bool {
+        let Some(time) = &mut self.time else {
+            return false;
+        };
+        let current = time.current();
+        let count = time.steps().len();
+        let Some(playback) = &mut time.playback else {
+            return false;
+        };
+        if playback.is_paused() {
+            return true;
+        }
+
+        let next = playback.advance(current, count, SystemTime::now());
+        let is_playing = next.is_some();
+        if !is_playing {
+            time.playback = None;
+        }
+
+        let step = next.unwrap_or(count - 1);
+        if step != current {
+            if let Err(err) = self.set_time_step(step) {
+                log::warn!("Failed to switch time step: {err}");
+            }
+        }
+
+        is_playing
+    }
+
+    /// Scale factor of the surface the layer is rendered to.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    fn select_source_variant(&mut self) {
+        const SCALE_TOLERANCE: f64 = 0.01;
+
+        let Some((scale, tile_provider)) = self
+            .source_variants
+            .iter()
+            .find(|(scale, _)| *scale >= self.scale_factor - SCALE_TOLERANCE)
+            .or_else(|| self.source_variants.last())
+            .cloned()
+        else {
+            return;
+        };
+
+        if Arc::ptr_eq(&tile_provider, &self.tile_provider) {
+            return;
+        }
+
+        self.tile_scheme = self.base_tile_scheme.scaled(scale);
+        self.replace_source(tile_provider);
+    }
+
+    /// Sets the policy of retrying to load the tiles that failed to load.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Monitor reporting the progress and errors of loading the tiles of the layer.
+    pub fn load_monitor(&self) -> &TileLoadMonitor {
+        &self.load_monitor
+    }
+
+    /// Sets the GPU memory budget for the tiles of the layer. The budget can be shared with other layers.
+    ///
+    /// By default, each layer uses its own unlimited budget.
+    pub fn set_memory_budget(&mut self, budget: &GpuMemoryBudget) {
+        self.budget = budget.clone();
+        for frame in self.frames() {
+            frame.memory.set_budget(budget);
+        }
+        if let Some(time) = &mut self.time {
+            time.set_budget(budget);
+        }
+    }
+
+    /// Approximate size in bytes of GPU memory used by the rendered tiles of the layer, including the tiles of the
+    /// previous source that are still displayed and the tiles of the preloaded time steps.
+    pub fn gpu_memory_usage(&self) -> usize {
+        self.frames().iter().map(|frame| frame.memory.used()).sum()
+    }
+
+    fn remove_evicted_tiles(&self) {
+        for frame in self.frames() {
+            frame.remove_evicted_tiles();
+        }
+    }
+
+    /// The current frame, the frame of the previous source and the frames of the preloaded time steps, each one once.
+    fn frames(&self) -> Vec<Frame<Provider>> {
+        let mut frames = vec![Frame {
+            tile_provider: self.tile_provider.clone(),
+            tiles: self.tiles.clone(),
+            memory: self.memory.clone(),
+        }];
+
+        let previous = self
+            .previous_source
+            .lock()
+            .as_ref()
+            .map(|previous| previous.frame.clone());
+        let preloaded = self.time.iter().flat_map(|t| t.preloaded_frames().cloned());
+        for frame in previous.into_iter().chain(preloaded) {
+            if !frames.iter().any(|f| Arc::ptr_eq(&f.tiles, &frame.tiles)) {
+                frames.push(frame);
+            }
+        }
+
+        frames
+    }
+
+    /// Sets fade in duration for newly loaded tiles.
+    pub fn set_fade_in_duration(&mut self, duration: Duration) {
+        self.fade_in_duration = duration;
+    }
+
+    fn get_tiles_to_draw(&self, view: &MapView) -> Vec<(TileIndex, Arc<TileState>)> {
+        let mut tiles = vec![];
+        let Some(tile_iter) = self.tile_scheme.iter_tiles(view) else {
+            return vec![];
+        };
+
+        let mut to_substitute = vec![];
+        for index in tile_iter {
+            self.tiles.get(&index);
+
+            match self.tiles.get(&index) {
+                None => to_substitute.push(index),
+                Some(tile_state) => match &*tile_state.clone() {
+                    TileState::Rendered(tile) => {
+                        if !tile.lock().is_opaque {
+                            to_substitute.push(index);
+                        }
+
+                        tiles.push((index, tile_state));
+                    }
+                    TileState::Loaded(_) => {
+                        to_substitute.push(index);
+                        tiles.push((index, tile_state));
+                    }
+                    _ => to_substitute.push(index),
+                },
+            }
+        }
+
+        let prev_drawn = self.prev_drawn_tiles.lock();
+        let mut substitute_indices: HashSet<_> = tiles.iter().map(|(index, _)| *index).collect();
+        let mut substitute_tiles = vec![];
+        for index in to_substitute {
+            let mut next_level = index;
+            let mut substituted = false;
+
+            while let Some(subst) = self.tile_scheme.get_substitutes(next_level) {
+                let mut need_more = false;
+                for substitute_index in subst {
+                    // todo: this will not work correctly if a tile is substituted by more then 1 tile
+                    next_level = substitute_index;
+
+                    if let Some(tile) = self.tiles.get(&substitute_index) {
+                        // Loaded substitutes are rendered together with the tiles of the view, so that a tile of a
+                        // lower level loaded instead of a missing one is displayed upscaled.
+                        if matches!(*tile, TileState::Rendered(_) | TileState::Loaded(_))
+                            && !substitute_indices.contains(&substitute_index)
+                        {
+                            substitute_tiles.push((substitute_index, tile.clone()));
+                            substitute_indices.insert(substitute_index);
+                        }
+
+                        match &*tile {
+                            TileState::Rendered(rendered) => {
+                                if !rendered.lock().is_opaque {
+                                    need_more = true;
+                                }
+                            }
+                            _ => need_more = true,
+                        }
+                    } else {
+                        need_more = true;
+                    }
+                }
+
+                if !need_more {
+                    substituted = true;
+                    break;
+                }
+            }
+
+            if !substituted {
+                let Some(required_bbox) = self.tile_scheme.tile_bbox(index) else {
+                    continue;
+                };
+                for prev in prev_drawn.iter() {
+                    let Some(prev_bbox) = self.tile_scheme.tile_bbox(*prev) else {
+                        continue;
+                    };
+                    if !substitute_indices.contains(prev) && prev_bbox.intersects(required_bbox) {
+                        substitute_indices.insert(*prev);
+                        let Some(tile) = self.tiles.get(prev) else {
+                            continue;
+                        };
+                        substitute_tiles.push((*prev, tile));
+                    }
+                }
+            }
+        }
+
+        substitute_tiles.sort_unstable_by(|(index_a, _), (index_b, _)| index_a.z.cmp(&index_b.z));
+        substitute_tiles.append(&mut tiles);
+        substitute_tiles.dedup_by(|a, b| a.0 == b.0);
+        substitute_tiles
+    }
+
+    fn prepare_tile_renders(&self, tiles: &[(TileIndex, Arc<TileState>)], canvas: &mut dyn Canvas) {
+        let mut requires_redraw = false;
+
+        let now = SystemTime::now();
+        for (index, tile) in tiles {
+            match &**tile {
+                TileState::Rendered(rendered) => {
+                    let mut rendered = rendered.lock();
+                    if rendered.is_opaque && rendered.pack_generation == self.pack_generation {
+                        continue;
+                    }
+
+                    let first_drawn = rendered.first_drawn;
+                    let primitive_id = rendered.primitive_id;
+
+                    let since_drawn = now
+                        .duration_since(first_drawn)
+                        .unwrap_or(Duration::from_millis(0));
+                    let opacity = if self.fade_in_duration.is_zero() {
+                        255
+                    } else {
+                        ((since_drawn.as_secs_f64() / self.fade_in_duration.as_secs_f64()).min(1.0)
+                            * 255.0) as u8
+                    };
+
+                    let is_opaque = opacity == 255;
+                    if !is_opaque {
+                        requires_redraw = true;
+                    }
+
+                    if let Err(err) = rendered
+                        .render_bundle
+                        .modify_image(primitive_id, ImagePaint { opacity })
+                    {
+                        log::warn!("Failed to update image style: {err}");
+                    }
+
+                    let packed = canvas.pack_bundle(&rendered.render_bundle);
+                    rendered.packed_bundle = packed;
+                    rendered.is_opaque = is_opaque;
+                    rendered.pack_generation = self.pack_generation;
+                }
+                TileState::Loaded(decoded_image) => {
+                    let mut bundle = canvas.create_bundle();
+                    let mut decoded_image = decoded_image.lock();
+
+                    let owned = std::mem::replace(
+                        &mut *decoded_image,
+                        DecodedImage {
+                            bytes: vec![],
+                            dimensions: (0, 0),
+                        },
+                    );
+
+                    let opacity = if self.fade_in_duration.is_zero() {
+                        255
+                    } else {
+                        0
+                    };
+                    let image_size = owned.bytes.len();
+
+                    let Some(tile_bbox) = self.tile_scheme.tile_bbox(*index) else {
+                        log::warn!("Failed to get bbox for tile {index:?}");
+                        continue;
+                    };
+
+                    let id = bundle.add_image(
+                        owned,
+                        tile_bbox.into_quadrangle(),
+                        ImagePaint { opacity },
+                    );
+                    let packed = canvas.pack_bundle(&bundle);
+                    // The tile is tracked before it is inserted, so that it is untracked if the cache evicts it
+                    // right away.
+                    self.memory.insert(*index, image_size);
+                    self.tiles.insert(
+                        *index,
+                        Arc::new(TileState::Rendered(Box::new(Mutex::new(RenderedTile {
+                            render_bundle: bundle,
+                            packed_bundle: packed,
+                            first_drawn: now,
+                            is_opaque: false,
+                            primitive_id: id,
+                            pack_generation: self.pack_generation,
+                            image_size,
+                        })))),
+                    );
+
+                    requires_redraw = true;
+                }
+                _ => {}
+            }
+        }
+
+        if requires_redraw {
+            if let Some(messenger) = &self.messenger {
+                messenger.request_redraw();
+            }
+        }
+    }
+
+    fn tile_loader(&self) -> TileLoader<Provider> {
+        TileLoader {
+            tile_provider: self.tile_provider.clone(),
+            tiles: self.tiles.clone(),
+            memory: self.memory.clone(),
+            messenger: self.messenger.clone(),
+            load_monitor: self.load_monitor.clone(),
+            retry_policy: self.retry_policy,
+            previous: self.previous_source.lock().as_ref().map(|previous| {
+                (
+                    previous.frame.tile_provider.clone(),
+                    previous.frame.tiles.clone(),
+                )
+            }),
+        }
+    }
+
+    /// Returns true if all the tiles of the view are loaded and fully faded in.
+    fn is_view_complete(&self, view: &MapView) -> bool {
+        let Some(mut tile_iter) = self.tile_scheme.iter_tiles(view) else {
+            return true;
+        };
+
+        tile_iter.all(|index| match self.tiles.get(&index).as_deref() {
+            Some(TileState::Rendered(rendered)) => rendered.lock().is_opaque,
+            Some(TileState::Error(_)) => true,
+            _ => false,
+        })
+    }
+
+    /// Tiles of the previous source that should be drawn under the tiles of the current source.
+    fn previous_tiles_to_draw(&self, view: &MapView) -> Vec<Arc<TileState>> {
+        let mut previous_source = self.previous_source.lock();
+        let Some(previous) = &*previous_source else {
+            return vec![];
+        };
+
+        if self.is_view_complete(view) {
+            *previous_source = None;
+            return vec![];
+        }
+
+        previous
+            .drawn_tiles
+            .iter()
+            .filter_map(|index| previous.frame.tiles.get(index))
+            .collect()
+    }
+
+    async fn load_tile(index: TileIndex, loader: TileLoader<Provider>) {
+        let TileLoader {
+            tile_provider,
+            tiles,
+            memory,
+            messenger,
+            load_monitor,
+            retry_policy,
+            previous,
+        } = loader;
+
+        if let Some(tile) = tiles.get(&index) {
+            if matches!(&*tile, TileState::Error(retry_at) if *retry_at <= Instant::now()) {
+                tiles.remove(&index);
+            }
+        }
+
+        match tiles.get_value_or_guard_async(&index).await {
+            Ok(_) => {}
+            Err(guard) => {
+                if let Some(tile) = previous.and_then(|(previous_provider, previous_tiles)| {
+                    Self::reusable_tile(index, &tile_provider, &previous_provider, &previous_tiles)
+                }) {
+                    // The tile is shared with the previous source, so it is counted by both until the previous
+                    // source is dropped.
+                    if let TileState::Rendered(rendered) = &*tile {
+                        let image_size = rendered.lock().image_size;
+                        memory.insert(index, image_size);
+                    }
+                    let _ = guard.insert(tile);
+                    if let Some(messenger) = messenger {
+                        messenger.request_redraw();
+                    }
+
+                    return;
+                }
+
+                let _ = guard.insert(Arc::new(TileState::Loading));
+                load_monitor.requested(index);
+
+                let mut attempt = 1;
+                let load_result = loop {
+                    match tile_provider.load(&index, ()).await {
+                        Err(err) if retry_policy.should_retry(&err, attempt) => {
+                            log::info!("Failed to load tile {index:?}, retrying: {err:?}");
+                            load_monitor.retrying(index, err);
+                            crate::async_runtime::sleep(retry_policy.retry_delay(attempt)).await;
+                            attempt += 1;
+                        }
+                        result => break result,
+                    }
+                };
+
+                match load_result {
+                    Ok(decoded_image) => {
+                        if let Some(v) = tiles.get(&index) {
+                            if matches!(*v, TileState::Rendered(_)) {
+                                log::error!("This should not happen to {index:?}");
+                            }
+                        }
+
+                        tiles.insert(
+                            index,
+                            Arc::new(TileState::Loaded(Mutex::new(decoded_image))),
+                        );
+                        load_monitor.loaded(index);
+
+                        if let Some(messenger) = messenger {
+                            messenger.request_redraw();
+                        }
+                    }
+                    Err(err) => {
+                        let retry_at = Instant::now() + retry_policy.failed_tile_ttl(&err);
+                        tiles.insert(index, Arc::new(TileState::Error(retry_at)));
+                        load_monitor.failed(index, err);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the tile of the previous source if the current source would load the same tile.
+    fn reusable_tile(
+        index: TileIndex,
+        tile_provider: &Provider,
+        previous_provider: &Provider,
+        previous_tiles: &TileCache,
+    ) -> Option<Arc<TileState>> {
+        let key = tile_provider.cache_key(&index)?;
+        if previous_provider.cache_key(&index)? != key {
+            return None;
+        }
+
+        previous_tiles
+            .get(&index)
+            .filter(|tile| matches!(**tile, TileState::Loaded(_) | TileState::Rendered(_)))
+    }
+
+    /// For a tile that failed to load, returns the closest tile of the lower levels that did not fail. It is loaded
+    /// and displayed upscaled instead of the missing tile, e.g. when the map is zoomed in beyond the maximum level
+    /// available in the source.
+    fn fallback_tile(&self, index: TileIndex) -> Option<TileIndex> {
+        let mut fallback = index;
+        while matches!(
+            self.tiles.get(&fallback).as_deref(),
+            Some(TileState::Error(_))
+        ) {
+            fallback = self.tile_scheme.get_substitutes(fallback)?.next()?;
+        }
+
+        (fallback != index).then_some(fallback)
+    }
+
+    /// Indices of the tiles that should be loaded to display the `view`.
+    fn tiles_to_load(&self, view: &MapView) -> Vec<TileIndex> {
+        let Some(iter) = self.tile_scheme.iter_tiles(view) else {
+            return vec![];
+        };
+
+        let mut indices: Vec<_> = iter.collect();
+        let fallbacks: HashSet<_> = indices
+            .iter()
+            .filter_map(|index| self.fallback_tile(*index))
+            .collect();
+        indices.extend(fallbacks);
+
+        indices
+    }
+
+    /// Preload tiles for the given `view`.
+    pub async fn load_tiles(&self, view: &MapView) {
+        for index in self.tiles_to_load(view) {
+            Self::load_tile(index, self.tile_loader()).await;
+        }
+    }
+}
+
+impl<Provider> Layer for RasterTileLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        self.remove_evicted_tiles();
+
+        let tiles = self.get_tiles_to_draw(view);
+        self.prepare_tile_renders(&tiles, canvas);
+
+        let updated_tiles: Vec<_> = tiles
+            .iter()
+            .filter_map(|(index, _)| self.tiles.get(index))
+            .collect();
+        for (index, _) in &tiles {
+            self.memory.touch(index);
+        }
+
+        let previous_tiles = self.previous_tiles_to_draw(view);
+
+        let mut to_draw = Vec::new();
+        for tile in previous_tiles.iter().chain(&updated_tiles) {
+            if let TileState::Rendered(rendered) = tile.as_ref() {
+                to_draw.push(rendered.lock());
+            }
+        }
+
+        canvas.draw_bundles(
+            &to_draw
+                .iter()
+                .map(|guard| &*guard.packed_bundle)
+                .collect::<Vec<_>>(),
+            RenderOptions::default(),
+        );
+        *self.prev_drawn_tiles.lock() = tiles.iter().map(|(index, _)| *index).collect();
+    }
+
+    fn prepare(&self, view: &MapView) {
+        let indices = self.tiles_to_load(view);
+        for index in &indices {
+            let loader = self.tile_loader();
+            let index = *index;
+            crate::async_runtime::spawn(async move {
+                Self::load_tile(index, loader).await;
+            });
+        }
+
+        for frame in self.time.iter().flat_map(|t| t.preloaded_frames()) {
+            for index in &indices {
+                let loader = TileLoader {
+                    tile_provider: frame.tile_provider.clone(),
+                    tiles: frame.tiles.clone(),
+                    memory: frame.memory.clone(),
+                    previous: None,
+                    ..self.tile_loader()
+                };
+                let index = *index;
+                crate::async_runtime::spawn(async move {
+                    Self::load_tile(index, loader).await;
+                });
+            }
+        }
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.messenger = Some(Arc::from(messenger));
+    }
+
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        self.select_source_variant();
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        // Rendered tiles keep their images in the render bundles, so they are packed again when they are drawn next
+        // time. Tiles of the previous source are not repacked, so they are dropped.
+        self.pack_generation += 1;
+        *self.previous_source.lock() = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,268 @@
+use super::TileState;
+use crate::render::{GpuMemoryBudget, MemoryTracker, TrackedCache};
+use crate::tile_scheme::TileIndex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use web_time::{Duration, SystemTime};
+
+pub(super) type TileCache = TrackedCache<TileIndex, Arc<TileState>>;
+
+/// Maximum number of tiles in the cache of a frame.
+const TILE_CACHE_CAPACITY: usize = 5000;
+
+/// Tile source of one time step together with the tiles loaded from it.
+pub(super) struct Frame<Provider> {
+    pub(super) tile_provider: Arc<Provider>,
+    pub(super) tiles: Arc<TileCache>,
+    /// GPU memory used by the rendered tiles of the cache. Tiles evicted by the cache stop being tracked, and all the
+    /// tiles stop being tracked when the frame and its cache are dropped.
+    pub(super) memory: MemoryTracker<TileIndex>,
+}
+
+impl<Provider> Frame<Provider> {
+    /// Creates a frame with an empty tile cache, the memory of which is counted by the `budget`.
+    pub(super) fn new(tile_provider: Arc<Provider>, budget: &GpuMemoryBudget) -> Self {
+        let memory = MemoryTracker::new(budget);
+        Self {
+            tile_provider,
+            tiles: Arc::new(memory.cache(TILE_CACHE_CAPACITY)),
+            memory,
+        }
+    }
+
+    /// Removes the tiles evicted by the memory budget from the cache.
+    pub(super) fn remove_evicted_tiles(&self) {
+        for index in self.memory.take_evicted() {
+            self.tiles.remove(&index);
+        }
+    }
+}
+
+impl<Provider> Clone for Frame<Provider> {
+    fn clone(&self) -> Self {
+        Self {
+            tile_provider: self.tile_provider.clone(),
+            tiles: self.tiles.clone(),
+            memory: self.memory.clone(),
+        }
+    }
+}
+
+type ProviderFactory<Provider> = Box<dyn Fn(&str) -> Provider + Send + Sync>;
+
+/// Time steps of a raster tile layer and the frames of the steps around the current one.
+pub(super) struct TimeDimension<Provider> {
+    steps: Vec<String>,
+    factory: ProviderFactory<Provider>,
+    current: usize,
+    preload: usize,
+    frames: HashMap<usize, Frame<Provider>>,
+    budget: GpuMemoryBudget,
+    pub(super) playback: Option<Playback>,
+}
+
+impl<Provider> TimeDimension<Provider> {
+    pub(super) fn new(
+        steps: Vec<String>,
+        factory: impl Fn(&str) -> Provider + Send + Sync + 'static,
+        budget: &GpuMemoryBudget,
+    ) -> Self {
+        Self {
+            steps,
+            factory: Box::new(factory),
+            current: 0,
+            preload: 1,
+            frames: HashMap::new(),
+            budget: budget.clone(),
+            playback: None,
+        }
+    }
+
+    pub(super) fn steps(&self) -> &[String] {
+        &self.steps
+    }
+
+    pub(super) fn current(&self) -> usize {
+        self.current
+    }
+
+    pub(super) fn set_preload(&mut self, preload: usize) {
+        self.preload = preload;
+        self.update_frames();
+    }
+
+    /// Moves the tiles of the stored frames, and of the frames created later, to another memory budget.
+    pub(super) fn set_budget(&mut self, budget: &GpuMemoryBudget) {
+        self.budget = budget.clone();
+        for frame in self.frames.values() {
+            frame.memory.set_budget(budget);
+        }
+    }
+
+    /// Makes the given step current, storing the frame of the previous step to be reused later. Returns the frame of
+    /// the new step.
+    pub(super) fn switch(&mut self, step: usize, previous: Frame<Provider>) -> Frame<Provider> {
+        self.frames.insert(self.current, previous);
+        self.current = step;
+        let frame = self.frame(step);
+        self.update_frames();
+        frame
+    }
+
+    /// Returns the frame of the step, creating it if it is not stored yet.
+    pub(super) fn frame(&mut self, step: usize) -> Frame<Provider> {
+        let factory = &self.factory;
+        let budget = &self.budget;
+        let time = &self.steps[step];
+        self.frames
+            .entry(step)
+            .or_insert_with(|| Frame::new(Arc::new(factory(time)), budget))
+            .clone()
+    }
+
+    /// Frames of the steps that should be loaded in advance.
+    pub(super) fn preloaded_frames(&self) -> impl Iterator<Item = &Frame<Provider>> {
+        self.preloaded_steps()
+            .filter_map(|step| self.frames.get(&step))
+    }
+
+    /// Steps around the current one (in both directions, wrapping around the ends) within the preload distance.
+    fn preloaded_steps(&self) -> impl Iterator<Item = usize> + '_ {
+        let len = self.steps.len();
+        let preload = self.preload.min(len / 2);
+        (1..=preload).flat_map(move |offset| {
+            [
+                (self.current + offset) % len,
+                (self.current + len - offset) % len,
+            ]
+        })
+    }
+
+    /// Creates the frames of the preloaded steps and drops all the other ones.
+    fn update_frames(&mut self) {
+        let preloaded: Vec<_> = self.preloaded_steps().collect();
+        self.frames.retain(|step, _| preloaded.contains(step));
+        for step in preloaded {
+            self.frame(step);
+        }
+    }
+}
+
+/// State of automatic switching of the time steps.
+pub(super) struct Playback {
+    frame_duration: Duration,
+    looped: bool,
+    frame_start: SystemTime,
+    paused_at: Option<SystemTime>,
+}
+
+impl Playback {
+    pub(super) fn new(frame_duration: Duration, looped: bool, now: SystemTime) -> Self {
+        Self {
+            frame_duration,
+            looped,
+            frame_start: now,
+            paused_at: None,
+        }
+    }
+
+    pub(super) fn pause(&mut self, now: SystemTime) {
+        self.paused_at.get_or_insert(now);
+    }
+
+    pub(super) fn resume(&mut self, now: SystemTime) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.frame_start += now.duration_since(paused_at).unwrap_or_default();
+        }
+    }
+
+    pub(super) fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Returns the step that should be displayed at `now`, or `None` if the playback reached the last step and is
+    /// not looped.
+    pub(super) fn advance(
+        &mut self,
+        current: usize,
+        count: usize,
+        now: SystemTime,
+    ) -> Option<usize> {
+        if self.paused_at.is_some() || self.frame_duration.is_zero() || count == 0 {
+            return Some(current);
+        }
+
+        let elapsed = now.duration_since(self.frame_start).unwrap_or_default();
+        let frames = (elapsed.as_secs_f64() / self.frame_duration.as_secs_f64()) as usize;
+        self.frame_start += self.frame_duration * frames as u32;
+
+        let next = current + frames;
+        if next < count {
+            Some(next)
+        } else if self.looped {
+            Some(next % count)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn playback_advances_by_frame_duration() {
+        let mut playback = Playback::new(Duration::from_millis(100), true, at(0));
+        assert_eq!(playback.advance(0, 3, at(50)), Some(0));
+        assert_eq!(playback.advance(0, 3, at(120)), Some(1));
+        assert_eq!(playback.advance(1, 3, at(210)), Some(2));
+        assert_eq!(playback.advance(2, 3, at(300)), Some(0));
+
+        playback.pause(at(300));
+        assert_eq!(playback.advance(0, 3, at(1000)), Some(0));
+        playback.resume(at(1000));
+        assert_eq!(playback.advance(0, 3, at(1050)), Some(0));
+        assert_eq!(playback.advance(0, 3, at(1100)), Some(1));
+    }
+
+    #[test]
+    fn playback_stops_at_the_end_if_not_looped() {
+        let mut playback = Playback::new(Duration::from_millis(100), false, at(0));
+        assert_eq!(playback.advance(1, 3, at(100)), Some(2));
+        assert_eq!(playback.advance(2, 3, at(200)), None);
+    }
+
+    #[test]
+    fn adjacent_steps_are_preloaded() {
+        let steps = (0..5).map(|i| i.to_string()).collect();
+        let mut time = TimeDimension::new(
+            steps,
+            |time: &str| time.to_string(),
+            &GpuMemoryBudget::unlimited(),
+        );
+        time.set_preload(1);
+
+        let mut preloaded: Vec<_> = time
+            .preloaded_frames()
+            .map(|f| f.tile_provider.to_string())
+            .collect();
+        preloaded.sort();
+        assert_eq!(preloaded, vec!["1", "4"]);
+
+        let current = time.frame(0);
+        let frame = time.switch(2, current);
+        assert_eq!(*frame.tile_provider, "2");
+
+        let mut preloaded: Vec<_> = time
+            .preloaded_frames()
+            .map(|f| f.tile_provider.to_string())
+            .collect();
+        preloaded.sort();
+        assert_eq!(preloaded, vec!["1", "3"]);
+    }
+}
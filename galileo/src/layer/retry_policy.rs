@@ -0,0 +1,129 @@
+use crate::error::GalileoError;
+use serde::{Deserialize, Serialize};
+use web_time::Duration;
+
+/// Specifies how tile layers handle the tiles that fail to load.
+///
+/// Transient errors (see [`GalileoError::is_transient`]) are retried right away with exponentially growing delays
+/// between the attempts. When all attempts fail, the tile is not requested again until `max_delay` passes.
+///
+/// Tiles that do not exist in the source ([`GalileoError::NotFound`]) and tiles that failed with other permanent errors
+/// are not retried. They are remembered as missing for `missing_tile_ttl`, so that the source is not asked for them on
+/// every change of the view.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts to load a tile, including the first one. `1` disables retrying.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+    /// Factor the delay is multiplied by after each failed retry.
+    pub backoff_factor: f64,
+    /// Maximum delay between attempts.
+    pub max_delay: Duration,
+    /// Time during which the tiles that failed with a permanent error are not requested again.
+    pub missing_tile_ttl: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(500),
+            backoff_factor: 2.0,
+            max_delay: Duration::from_secs(30),
+            missing_tile_ttl: Duration::from_secs(600),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries loading a tile in the background. Failed tiles are requested again after
+    /// `max_delay` or `missing_tile_ttl` as with the default policy.
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the maximum number of attempts to load a tile.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the delay before the first retry and the factor it is multiplied by after each retry.
+    pub fn with_backoff(mut self, initial_delay: Duration, backoff_factor: f64) -> Self {
+        self.initial_delay = initial_delay;
+        self.backoff_factor = backoff_factor;
+        self
+    }
+
+    /// Sets the maximum delay between attempts.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets the time during which the tiles that failed with a permanent error are not requested again.
+    pub fn with_missing_tile_ttl(mut self, ttl: Duration) -> Self {
+        self.missing_tile_ttl = ttl;
+        self
+    }
+
+    /// Returns true if loading should be attempted again after the `attempt`-th attempt (starting from 1) failed
+    /// with the `error`.
+    pub fn should_retry(&self, error: &GalileoError, attempt: u32) -> bool {
+        error.is_transient() && attempt < self.max_attempts
+    }
+
+    /// Delay before the next attempt after the `attempt`-th attempt (starting from 1) failed.
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        let factor = self
+            .backoff_factor
+            .max(1.0)
+            .powi(attempt.saturating_sub(1) as i32);
+        let delay = self.initial_delay.as_secs_f64() * factor;
+        Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()))
+    }
+
+    /// Time during which a tile that failed to load with the `error` should not be requested again.
+    pub fn failed_tile_ttl(&self, error: &GalileoError) -> Duration {
+        if error.is_transient() {
+            self.max_delay
+        } else {
+            self.missing_tile_ttl
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_grows_exponentially_up_to_max() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_secs(1), 3.0)
+            .with_max_delay(Duration::from_secs(20));
+
+        assert_eq!(policy.retry_delay(1), Duration::from_secs(1));
+        assert_eq!(policy.retry_delay(2), Duration::from_secs(3));
+        assert_eq!(policy.retry_delay(3), Duration::from_secs(9));
+        assert_eq!(policy.retry_delay(4), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let policy = RetryPolicy::default().with_max_attempts(2);
+
+        assert!(policy.should_retry(&GalileoError::IO, 1));
+        assert!(!policy.should_retry(&GalileoError::IO, 2));
+        assert!(!policy.should_retry(&GalileoError::NotFound, 1));
+        assert_eq!(
+            policy.failed_tile_ttl(&GalileoError::NotFound),
+            policy.missing_tile_ttl
+        );
+        assert!(!RetryPolicy::no_retry().should_retry(&GalileoError::IO, 1));
+    }
+}
@@ -0,0 +1,270 @@
+//! Smooth surface interpolated from scattered measurements, with isolines. See [`SurfaceLayer`].
+
+use crate::elevation::ElevationTile;
+use crate::interpolation::{isolines, Idw, Isoline};
+use crate::layer::data_provider::ColorMap;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{
+    Canvas, ImagePaint, LineCap, LinePaint, PackedBundle, RenderOptions, SizeUnit,
+};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d, Rect};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, Projection};
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::Contour as _;
+use std::any::Any;
+use std::sync::Mutex;
+
+/// Layer that interpolates scattered measurements (e.g. readings of a sensor network, or travel times to get
+/// isochrones) into a smooth surface with [`Idw`], and draws it as a colored raster and/or isolines.
+///
+/// The surface is calculated in the CRS of the map over the extent of the measurements, expanded by a margin, and
+/// is recalculated only when the CRS of the map changes. Both the coloring and the isolines are optional: without
+/// a [color map](SurfaceLayer::with_color_map) only the isolines are drawn, without
+/// [levels](SurfaceLayer::with_levels) only the colored surface.
+///
+/// ```
+/// use galileo::layer::data_provider::ColorMap;
+/// use galileo::layer::SurfaceLayer;
+/// use galileo::Color;
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::NewGeoPoint;
+///
+/// let temperatures = vec![
+///     (GeoPoint2d::latlon(52.52, 13.40), 21.5),
+///     (GeoPoint2d::latlon(52.45, 13.30), 19.0),
+///     (GeoPoint2d::latlon(52.55, 13.55), 23.2),
+/// ];
+///
+/// let layer = SurfaceLayer::new(temperatures)
+///     .with_color_map(ColorMap::continuous(vec![(18.0, Color::BLUE), (24.0, Color::RED)]))
+///     .with_levels(vec![19.0, 20.0, 21.0, 22.0, 23.0])
+///     .with_line_style(Color::BLACK, 1.0);
+/// ```
+pub struct SurfaceLayer {
+    measurements: Vec<(GeoPoint2d, f64)>,
+    idw: Idw,
+    resolution: u32,
+    margin: f64,
+    color_map: Option<ColorMap>,
+    opacity: u8,
+    levels: Vec<f64>,
+    line_color: Color,
+    line_width: f64,
+    surface: Mutex<Option<(Crs, Option<Surface>)>>,
+    packed: Mutex<Option<(Crs, Box<dyn PackedBundle>)>>,
+}
+
+/// Interpolated surface in the CRS of the map.
+#[derive(Debug, Clone)]
+struct Surface {
+    bounds: Rect,
+    grid: ElevationTile,
+    isolines: Vec<Isoline>,
+}
+
+impl SurfaceLayer {
+    /// Creates a new layer from the `(position, value)` measurements, drawing black isolines without the colored
+    /// surface until it is configured otherwise.
+    pub fn new(measurements: Vec<(GeoPoint2d, f64)>) -> Self {
+        Self {
+            measurements,
+            idw: Idw::default(),
+            resolution: 256,
+            margin: 0.1,
+            color_map: None,
+            opacity: 200,
+            levels: vec![],
+            line_color: Color::BLACK,
+            line_width: 1.0,
+            surface: Mutex::new(None),
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Sets the interpolation parameters.
+    pub fn with_idw(mut self, idw: Idw) -> Self {
+        self.idw = idw;
+        self
+    }
+
+    /// Sets the number of cells of the surface grid along its longer side.
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution.max(2);
+        self
+    }
+
+    /// Sets the margin added around the extent of the measurements, as a fraction of its size.
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin.max(0.0);
+        self
+    }
+
+    /// Draws the surface colored with the color map.
+    pub fn with_color_map(mut self, color_map: ColorMap) -> Self {
+        self.color_map = Some(color_map);
+        self
+    }
+
+    /// Sets the opacity of the colored surface, 255 being fully opaque.
+    pub fn with_opacity(mut self, opacity: u8) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Sets the values the isolines are drawn for.
+    pub fn with_levels(mut self, levels: Vec<f64>) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Sets the color and width in pixels of the isolines.
+    pub fn with_line_style(mut self, color: Color, width: f64) -> Self {
+        self.line_color = color;
+        self.line_width = width;
+        self
+    }
+
+    /// Measurements the surface is interpolated from.
+    pub fn measurements(&self) -> &[(GeoPoint2d, f64)] {
+        &self.measurements
+    }
+
+    /// Replaces the measurements. The surface is recalculated on the next render.
+    pub fn set_measurements(&mut self, measurements: Vec<(GeoPoint2d, f64)>) {
+        self.measurements = measurements;
+        *self.surface.get_mut().expect("mutex is poisoned") = None;
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    /// Isolines of the surface in the given CRS, e.g. to export them or to place labels along them.
+    pub fn isolines(&self, crs: &Crs) -> Vec<Isoline> {
+        self.with_surface(crs, |surface| {
+            surface
+                .map(|surface| surface.isolines.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    fn with_surface<T>(&self, crs: &Crs, f: impl FnOnce(Option<&Surface>) -> T) -> T {
+        let mut surface = self.surface.lock().expect("mutex is poisoned");
+        if !matches!(surface.as_ref(), Some((surface_crs, _)) if surface_crs == crs) {
+            *surface = Some((crs.clone(), self.calculate(crs)));
+        }
+
+        f(surface.as_ref().and_then(|(_, surface)| surface.as_ref()))
+    }
+
+    fn calculate(&self, crs: &Crs) -> Option<Surface> {
+        let projection = crs.get_projection::<GeoPoint2d, Point2d>()?;
+        let points: Vec<(Point2d, f64)> = self
+            .measurements
+            .iter()
+            .filter_map(|(position, value)| Some((projection.project(position)?, *value)))
+            .collect();
+
+        let extent = Rect::from_points(points.iter().map(|(point, _)| point))?;
+        let margin = extent.width().max(extent.height()) * self.margin;
+        // A single measurement or measurements along a line still get a visible square around them.
+        let margin = if margin > 0.0 { margin } else { 1000.0 };
+        let bounds = Rect::new(
+            extent.x_min() - margin,
+            extent.y_min() - margin,
+            extent.x_max() + margin,
+            extent.y_max() + margin,
+        );
+
+        let cell_size = bounds.width().max(bounds.height()) / self.resolution as f64;
+        let width = ((bounds.width() / cell_size).round() as u32).max(2);
+        let height = ((bounds.height() / cell_size).round() as u32).max(2);
+        let grid = self.idw.interpolate(&points, bounds, width, height).ok()?;
+        let isolines = isolines(&grid, bounds, &self.levels);
+
+        Some(Surface {
+            bounds,
+            grid,
+            isolines,
+        })
+    }
+
+    fn pack(&self, view: &MapView, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        let resolution = view.resolution();
+
+        self.with_surface(view.crs(), |surface| {
+            let Some(surface) = surface else {
+                return;
+            };
+
+            if let Some(color_map) = &self.color_map {
+                bundle.add_image(
+                    color_map.apply(&surface.grid),
+                    surface.bounds.into_quadrangle(),
+                    ImagePaint {
+                        opacity: self.opacity,
+                    },
+                );
+            }
+
+            let paint = LinePaint {
+                color: self.line_color,
+                width: self.line_width,
+                offset: 0.0,
+                width_unit: SizeUnit::Pixels,
+                line_cap: LineCap::Round,
+                pattern: None,
+                gradient: None,
+                zoom: None,
+            };
+            for line in surface.isolines.iter().flat_map(|isoline| &isoline.lines) {
+                let points = line
+                    .iter_points()
+                    .map(|p| Point3d::new(p.x, p.y, 0.0))
+                    .collect();
+                bundle.add(
+                    RenderPrimitive::<_, _, _, Polygon<_>>::new_contour(
+                        Contour::new(points, line.is_closed()),
+                        paint,
+                    ),
+                    resolution,
+                );
+            }
+        });
+
+        canvas.pack_bundle(&bundle)
+    }
+}
+
+impl Layer for SurfaceLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let is_outdated = !matches!(packed.as_ref(), Some((crs, _)) if crs == view.crs());
+        if is_outdated {
+            *packed = Some((view.crs().clone(), self.pack(view, canvas)));
+        }
+
+        if let Some((_, bundle)) = packed.as_ref() {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {}
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,262 @@
+use crate::error::GalileoError;
+use crate::tile_scheme::TileIndex;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Event of loading a tile by a tile layer.
+#[derive(Debug, Clone)]
+pub enum TileLoadEvent {
+    /// Loading of the tile was started.
+    Requested(TileIndex),
+    /// The tile was loaded successfully.
+    Loaded(TileIndex),
+    /// The tile failed to load.
+    Failed {
+        /// Index of the tile.
+        index: TileIndex,
+        /// Error the loading failed with.
+        error: Arc<GalileoError>,
+        /// Number of the earlier failed attempts to load the tile.
+        retry_count: u32,
+        /// If true, the layer will try to load the tile again, and the tile is still counted as pending.
+        will_retry: bool,
+    },
+}
+
+/// Progress of loading the tiles of a layer.
+///
+/// The counters are reset when a tile is requested after all the earlier requested tiles were finished loading, so
+/// the progress describes the tiles requested for the latest view changes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TileLoadProgress {
+    /// Number of the requested tiles.
+    pub requested: usize,
+    /// Number of the tiles loaded successfully.
+    pub loaded: usize,
+    /// Number of the tiles that failed to load.
+    pub failed: usize,
+}
+
+impl TileLoadProgress {
+    /// Number of the tiles that are still loading.
+    pub fn pending(&self) -> usize {
+        self.requested.saturating_sub(self.loaded + self.failed)
+    }
+
+    /// Returns true if any of the tiles are still loading.
+    pub fn is_loading(&self) -> bool {
+        self.pending() > 0
+    }
+
+    /// Part of the requested tiles that finished loading, successfully or not, from `0.0` to `1.0`. If no tiles were
+    /// requested, returns `1.0`.
+    pub fn loading_fraction(&self) -> f64 {
+        if self.requested == 0 {
+            return 1.0;
+        }
+
+        (self.requested - self.pending()) as f64 / self.requested as f64
+    }
+}
+
+type TileLoadListener = Box<dyn Fn(&TileLoadEvent) + MaybeSend + MaybeSync>;
+
+#[derive(Default)]
+struct MonitorState {
+    progress: TileLoadProgress,
+    pending: HashSet<TileIndex>,
+    failures: HashMap<TileIndex, u32>,
+}
+
+/// Reports the progress and errors of loading the tiles of a tile layer, so that applications can show loading
+/// indicators and notify users about network problems.
+///
+/// The monitor can be cloned, and all the clones report the events of the same layer.
+///
+/// ```no_run
+/// # use galileo::layer::{RasterTileLayer, TileLoadEvent};
+/// # use galileo::layer::data_provider::UrlImageProvider;
+/// # use galileo::tile_scheme::TileIndex;
+/// # fn get_layer() -> RasterTileLayer<UrlImageProvider<TileIndex>> { unimplemented!() }
+/// let layer = get_layer();
+/// layer.load_monitor().on_event(|event| {
+///     if let TileLoadEvent::Failed { index, error, .. } = event {
+///         eprintln!("Failed to load tile {index:?}: {error}");
+///     }
+/// });
+///
+/// let progress = layer.load_monitor().progress();
+/// println!("Loaded {:.0}%", progress.loading_fraction() * 100.0);
+/// ```
+#[derive(Clone, Default)]
+pub struct TileLoadMonitor {
+    state: Arc<Mutex<MonitorState>>,
+    listeners: Arc<RwLock<Vec<TileLoadListener>>>,
+}
+
+impl TileLoadMonitor {
+    /// Creates a new monitor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the listener that is called on every tile load event. The listener can be called from background threads.
+    pub fn on_event(&self, listener: impl Fn(&TileLoadEvent) + MaybeSend + MaybeSync + 'static) {
+        self.listeners
+            .write()
+            .expect("lock is poisoned")
+            .push(Box::new(listener));
+    }
+
+    /// Current progress of loading the tiles.
+    pub fn progress(&self) -> TileLoadProgress {
+        self.state.lock().expect("mutex is poisoned").progress
+    }
+
+    /// Number of the failed attempts to load the tile since the last successful load.
+    pub fn failure_count(&self, index: TileIndex) -> u32 {
+        self.state
+            .lock()
+            .expect("mutex is poisoned")
+            .failures
+            .get(&index)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn requested(&self, index: TileIndex) {
+        {
+            let mut state = self.state.lock().expect("mutex is poisoned");
+            if state.pending.is_empty() {
+                state.progress = TileLoadProgress::default();
+            }
+
+            if !state.pending.insert(index) {
+                return;
+            }
+
+            state.progress.requested += 1;
+        }
+
+        self.notify(&TileLoadEvent::Requested(index));
+    }
+
+    pub(crate) fn loaded(&self, index: TileIndex) {
+        {
+            let mut state = self.state.lock().expect("mutex is poisoned");
+            state.failures.remove(&index);
+            if state.pending.remove(&index) {
+                state.progress.loaded += 1;
+            }
+        }
+
+        self.notify(&TileLoadEvent::Loaded(index));
+    }
+
+    pub(crate) fn failed(&self, index: TileIndex, error: GalileoError) {
+        self.report_failure(index, error, false);
+    }
+
+    /// Reports a failed attempt to load a tile that will be retried.
+    pub(crate) fn retrying(&self, index: TileIndex, error: GalileoError) {
+        self.report_failure(index, error, true);
+    }
+
+    fn report_failure(&self, index: TileIndex, error: GalileoError, will_retry: bool) {
+        let retry_count = {
+            let mut state = self.state.lock().expect("mutex is poisoned");
+            if !will_retry && state.pending.remove(&index) {
+                state.progress.failed += 1;
+            }
+
+            let failures = state.failures.entry(index).or_default();
+            *failures += 1;
+            *failures - 1
+        };
+
+        self.notify(&TileLoadEvent::Failed {
+            index,
+            error: Arc::new(error),
+            retry_count,
+            will_retry,
+        });
+    }
+
+    fn notify(&self, event: &TileLoadEvent) {
+        for listener in self.listeners.read().expect("lock is poisoned").iter() {
+            listener(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn index(x: i32) -> TileIndex {
+        TileIndex {
+            z: 1,
+            x,
+            y: 0,
+            display_x: x,
+        }
+    }
+
+    #[test]
+    fn progress_is_tracked_per_batch() {
+        let monitor = TileLoadMonitor::new();
+        assert_eq!(monitor.progress().loading_fraction(), 1.0);
+
+        monitor.requested(index(0));
+        monitor.requested(index(1));
+        monitor.requested(index(1));
+        monitor.loaded(index(0));
+
+        let progress = monitor.progress();
+        assert_eq!(progress.requested, 2);
+        assert_eq!(progress.pending(), 1);
+        assert_eq!(progress.loading_fraction(), 0.5);
+
+        monitor.failed(index(1), GalileoError::IO);
+        assert!(!monitor.progress().is_loading());
+        assert_eq!(monitor.progress().failed, 1);
+
+        monitor.requested(index(2));
+        assert_eq!(
+            monitor.progress(),
+            TileLoadProgress {
+                requested: 1,
+                loaded: 0,
+                failed: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn failures_are_counted_until_success() {
+        let monitor = TileLoadMonitor::new();
+        let last_retry_count = Arc::new(AtomicUsize::new(usize::MAX));
+        let counter = last_retry_count.clone();
+        monitor.on_event(move |event| {
+            if let TileLoadEvent::Failed { retry_count, .. } = event {
+                counter.store(*retry_count as usize, Ordering::Relaxed);
+            }
+        });
+
+        monitor.requested(index(0));
+        monitor.failed(index(0), GalileoError::IO);
+        assert_eq!(last_retry_count.load(Ordering::Relaxed), 0);
+
+        monitor.requested(index(0));
+        monitor.retrying(index(0), GalileoError::IO);
+        assert_eq!(last_retry_count.load(Ordering::Relaxed), 1);
+        assert!(monitor.progress().is_loading());
+        assert_eq!(monitor.failure_count(index(0)), 2);
+
+        monitor.requested(index(0));
+        monitor.loaded(index(0));
+        assert_eq!(monitor.failure_count(index(0)), 0);
+    }
+}
@@ -0,0 +1,638 @@
+//! Playback of timestamped tracks. See [`TrajectoryLayer`].
+
+use crate::control::{Clock, SystemClock};
+use crate::error::GalileoError;
+use crate::layer::data_provider::ColorMap;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{Canvas, LineCap, LinePaint, PackedBundle, RenderOptions, SizeUnit};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, PathType, Projection};
+use galileo_types::impls::{Contour, Polygon};
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+use web_time::{Duration, SystemTime};
+
+/// Path of a moving object with the time of every vertex.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    points: Vec<(GeoPoint2d, SystemTime)>,
+}
+
+impl Track {
+    /// Creates a new track from its vertices. Returns an error if the track is empty or the times of the vertices
+    /// decrease.
+    pub fn new(points: Vec<(GeoPoint2d, SystemTime)>) -> Result<Self, GalileoError> {
+        if points.is_empty() {
+            return Err(GalileoError::Generic("track has no points".into()));
+        }
+        if let Some(index) = points.windows(2).position(|pair| pair[1].1 < pair[0].1) {
+            return Err(GalileoError::Generic(format!(
+                "time of the track point {} is earlier than of the previous one",
+                index + 1
+            )));
+        }
+
+        Ok(Self { points })
+    }
+
+    /// Vertices of the track with their times.
+    pub fn points(&self) -> &[(GeoPoint2d, SystemTime)] {
+        &self.points
+    }
+
+    /// Time of the first vertex.
+    pub fn start_time(&self) -> SystemTime {
+        self.points[0].1
+    }
+
+    /// Time of the last vertex.
+    pub fn end_time(&self) -> SystemTime {
+        self.points[self.points.len() - 1].1
+    }
+
+    /// Position of the object at the given time, interpolated along the great circle between the vertices. Returns
+    /// `None` if the time is outside the track.
+    pub fn position_at(&self, time: SystemTime) -> Option<GeoPoint2d> {
+        if time < self.start_time() || time > self.end_time() {
+            return None;
+        }
+
+        let next = self.points.partition_point(|(_, t)| *t <= time);
+        if next == self.points.len() {
+            return Some(self.points[next - 1].0);
+        }
+
+        Some(self.interpolate(next - 1, time))
+    }
+
+    /// Average speed on the segment starting at the vertex with the given index, in meters per second. Returns `None`
+    /// if there is no such segment or both its vertices have the same time.
+    pub fn speed(&self, segment: usize) -> Option<f64> {
+        let (from, from_time) = self.points.get(segment)?;
+        let (to, to_time) = self.points.get(segment + 1)?;
+        let duration = to_time.duration_since(*from_time).ok()?.as_secs_f64();
+
+        (duration > 0.0).then(|| PathType::GreatCircle.distance(from, to) / duration)
+    }
+
+    /// Position on the segment starting at the vertex `segment` at the given time.
+    fn interpolate(&self, segment: usize, time: SystemTime) -> GeoPoint2d {
+        let (from, from_time) = self.points[segment];
+        let (to, to_time) = self.points[segment + 1];
+        let duration = to_time.duration_since(from_time).unwrap_or_default();
+        if duration.is_zero() {
+            return to;
+        }
+
+        let fraction = time
+            .duration_since(from_time)
+            .unwrap_or_default()
+            .as_secs_f64()
+            / duration.as_secs_f64();
+        PathType::GreatCircle.interpolate(&from, &to, fraction.clamp(0.0, 1.0))
+    }
+
+    /// Parts of the segments of the track between `from` and `to`.
+    fn trail(&self, from: SystemTime, to: SystemTime) -> Vec<TrailSegment> {
+        let mut segments = vec![];
+        for (index, pair) in self.points.windows(2).enumerate() {
+            let (start_time, end_time) = (pair[0].1.max(from), pair[1].1.min(to));
+            if start_time >= end_time {
+                continue;
+            }
+
+            let duration = end_time.duration_since(start_time).unwrap_or_default();
+            segments.push(TrailSegment {
+                from: self.interpolate(index, start_time),
+                to: self.interpolate(index, end_time),
+                time: start_time + duration / 2,
+                speed: self.speed(index),
+            });
+        }
+
+        segments
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct TrailSegment {
+    from: GeoPoint2d,
+    to: GeoPoint2d,
+    /// Middle time of the segment, used to fade it.
+    time: SystemTime,
+    speed: Option<f64>,
+}
+
+/// Time cursor of a [`TrajectoryLayer`], which can be played like a video or moved by a time slider.
+///
+/// The timeline is cheap to clone, and all the clones share the same cursor. Giving clones of one timeline to several
+/// layers plays them in sync, and a UI slider can hold another clone to [seek](Timeline::seek_fraction).
+#[derive(Clone)]
+pub struct Timeline {
+    state: Arc<Mutex<TimelineState>>,
+    clock: Arc<dyn Clock>,
+}
+
+struct TimelineState {
+    start: SystemTime,
+    end: SystemTime,
+    cursor: SystemTime,
+    speed: f64,
+    looped: bool,
+    /// Clock time the cursor was last moved at while playing.
+    played_at: Option<SystemTime>,
+}
+
+impl Timeline {
+    /// Creates a new stopped timeline from `start` to `end` with the cursor at the start. It plays at the real speed
+    /// and does not loop.
+    pub fn new(start: SystemTime, end: SystemTime) -> Self {
+        let end = end.max(start);
+        Self {
+            state: Arc::new(Mutex::new(TimelineState {
+                start,
+                end,
+                cursor: start,
+                speed: 1.0,
+                looped: false,
+                played_at: None,
+            })),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Creates a timeline covering all the tracks. Returns `None` if there are no tracks.
+    pub fn covering(tracks: &[Track]) -> Option<Self> {
+        let start = tracks.iter().map(Track::start_time).min()?;
+        let end = tracks.iter().map(Track::end_time).max()?;
+        Some(Self::new(start, end))
+    }
+
+    /// Sets how many seconds of the tracks are played in one second, e.g. `60.0` plays an hour in a minute.
+    pub fn with_speed(self, speed: f64) -> Self {
+        self.set_speed(speed);
+        self
+    }
+
+    /// Sets whether the playback starts over after reaching the end.
+    pub fn with_loop(self, looped: bool) -> Self {
+        self.state.lock().expect("mutex is poisoned").looped = looped;
+        self
+    }
+
+    /// Sets the clock used to time the playback.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Sets the playback speed (see [`Timeline::with_speed`]).
+    pub fn set_speed(&self, speed: f64) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.advance(now);
+        state.speed = speed.max(0.0);
+    }
+
+    /// Starts the playback from the current time. If the cursor is at the end, the playback starts from the beginning.
+    pub fn play(&self) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.advance(now);
+        if state.cursor >= state.end {
+            state.cursor = state.start;
+        }
+        state.played_at = Some(now);
+    }
+
+    /// Pauses the playback at the current time.
+    pub fn pause(&self) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.advance(now);
+        state.played_at = None;
+    }
+
+    /// Returns true if the timeline is playing.
+    pub fn is_playing(&self) -> bool {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.advance(now);
+        state.played_at.is_some()
+    }
+
+    /// Moves the cursor to the given time, limited to the range of the timeline. The playback, if started,
+    /// continues from there.
+    pub fn seek(&self, time: SystemTime) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.cursor = time.clamp(state.start, state.end);
+        if state.played_at.is_some() {
+            state.played_at = Some(now);
+        }
+    }
+
+    /// Moves the cursor to the given fraction of the timeline, from `0.0` at the start to `1.0` at the end. This is
+    /// convenient for time sliders.
+    pub fn seek_fraction(&self, fraction: f64) {
+        let (start, end) = self.range();
+        let duration = end.duration_since(start).unwrap_or_default();
+        self.seek(start + duration.mul_f64(fraction.clamp(0.0, 1.0)));
+    }
+
+    /// Current time of the cursor.
+    pub fn current_time(&self) -> SystemTime {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.advance(now);
+        state.cursor
+    }
+
+    /// Position of the cursor as a fraction of the timeline, from `0.0` at the start to `1.0` at the end.
+    pub fn fraction(&self) -> f64 {
+        let time = self.current_time();
+        let (start, end) = self.range();
+        let duration = end.duration_since(start).unwrap_or_default().as_secs_f64();
+        if duration == 0.0 {
+            return 1.0;
+        }
+
+        time.duration_since(start).unwrap_or_default().as_secs_f64() / duration
+    }
+
+    /// Start and end of the timeline.
+    pub fn range(&self) -> (SystemTime, SystemTime) {
+        let state = self.state.lock().expect("mutex is poisoned");
+        (state.start, state.end)
+    }
+
+    /// Changes the range of the timeline, e.g. after new tracks were added. The cursor is moved into the new range.
+    pub fn set_range(&self, start: SystemTime, end: SystemTime) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.advance(now);
+        state.start = start;
+        state.end = end.max(start);
+        state.cursor = state.cursor.clamp(state.start, state.end);
+    }
+}
+
+impl TimelineState {
+    /// Moves the cursor of a playing timeline to the clock time `now`.
+    fn advance(&mut self, now: SystemTime) {
+        let Some(played_at) = self.played_at else {
+            return;
+        };
+
+        let elapsed = now.duration_since(played_at).unwrap_or_default();
+        self.played_at = Some(now);
+        self.cursor += elapsed.mul_f64(self.speed);
+        if self.cursor <= self.end {
+            return;
+        }
+
+        let duration = self.end.duration_since(self.start).unwrap_or_default();
+        if self.looped && !duration.is_zero() {
+            let overshoot = self.cursor.duration_since(self.end).unwrap_or_default();
+            self.cursor = self.start
+                + Duration::from_secs_f64(overshoot.as_secs_f64() % duration.as_secs_f64());
+        } else {
+            self.cursor = self.end;
+            self.played_at = None;
+        }
+    }
+}
+
+/// Appearance of the tracks of a [`TrajectoryLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrajectoryStyle {
+    /// Color of the trail, if it is not colored by speed.
+    pub color: Color,
+    /// Colors of the trail segments by the speed in meters per second.
+    pub speed_colors: Option<ColorMap>,
+    /// Width of the trail in pixels.
+    pub width: f64,
+    /// Length of the trail behind the current position. If `None`, the whole track up to the cursor is drawn.
+    pub trail: Option<Duration>,
+    /// Whether the trail becomes transparent towards its end. Only applies to the trails of limited length.
+    pub fade: bool,
+    /// Color of the marker at the current position.
+    pub head_color: Color,
+    /// Diameter of the marker at the current position in pixels. `0.0` hides the marker.
+    pub head_size: f64,
+}
+
+impl Default for TrajectoryStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::rgba(0, 90, 200, 255),
+            speed_colors: None,
+            width: 3.0,
+            trail: Some(Duration::from_secs(600)),
+            fade: true,
+            head_color: Color::rgba(220, 30, 30, 255),
+            head_size: 10.0,
+        }
+    }
+}
+
+/// Layer that plays timestamped tracks: each object is drawn at its position at the current time of the
+/// [`Timeline`], with a trail behind it that can fade out and be colored by the speed of the object.
+///
+/// All the tracks of the layer share one timeline, so they are played in sync. Several layers can also share a
+/// timeline (see [`TrajectoryLayer::with_timeline`]). While the timeline is playing, the layer requests the redraws
+/// of the map by itself. Objects are only drawn while the cursor is inside their track.
+///
+/// ```
+/// use galileo::layer::{Timeline, Track, TrajectoryLayer};
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::NewGeoPoint;
+/// use web_time::{Duration, SystemTime};
+///
+/// let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let ferry = Track::new(vec![
+///     (GeoPoint2d::latlon(60.17, 24.95), start),
+///     (GeoPoint2d::latlon(59.44, 24.75), start + Duration::from_secs(2 * 3600)),
+/// ])
+/// .expect("valid track");
+///
+/// let layer = TrajectoryLayer::new(vec![ferry]);
+/// let timeline = layer.timeline().clone().with_speed(120.0);
+/// timeline.play();
+/// ```
+pub struct TrajectoryLayer {
+    tracks: Vec<Track>,
+    timeline: Timeline,
+    style: TrajectoryStyle,
+    messenger: Option<Box<dyn Messenger>>,
+    packed: Mutex<Option<(PackKey, Box<dyn PackedBundle>)>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PackKey {
+    crs: Crs,
+    time: SystemTime,
+}
+
+impl TrajectoryLayer {
+    /// Creates a new layer with a stopped timeline covering all the tracks.
+    pub fn new(tracks: Vec<Track>) -> Self {
+        let timeline = Timeline::covering(&tracks)
+            .unwrap_or_else(|| Timeline::new(SystemTime::UNIX_EPOCH, SystemTime::UNIX_EPOCH));
+        Self {
+            tracks,
+            timeline,
+            style: TrajectoryStyle::default(),
+            messenger: None,
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Uses the given timeline instead of the own one, e.g. to play the layer in sync with another one.
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = timeline;
+        self
+    }
+
+    /// Sets the style of the layer.
+    pub fn with_style(mut self, style: TrajectoryStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Timeline of the layer.
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Tracks of the layer.
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    /// Adds a track to the layer. The range of the timeline is not changed.
+    pub fn push_track(&mut self, track: Track) {
+        self.tracks.push(track);
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    /// Positions of all the objects at the current time of the timeline, as indices of their tracks and positions.
+    pub fn positions(&self) -> Vec<(usize, GeoPoint2d)> {
+        let time = self.timeline.current_time();
+        self.tracks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, track)| Some((index, track.position_at(time)?)))
+            .collect()
+    }
+
+    fn pack(&self, view: &MapView, time: SystemTime, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        let resolution = view.resolution();
+        let Some(projection) = view.crs().get_projection::<GeoPoint2d, Point2d>() else {
+            return canvas.pack_bundle(&bundle);
+        };
+        let project = |point: &GeoPoint2d| {
+            projection
+                .project(point)
+                .map(|p| Point3d::new(p.x, p.y, 0.0))
+        };
+
+        let trail_start = match self.style.trail {
+            Some(trail) => time.checked_sub(trail).unwrap_or(SystemTime::UNIX_EPOCH),
+            None => SystemTime::UNIX_EPOCH,
+        };
+
+        for track in &self.tracks {
+            for segment in track.trail(trail_start, time) {
+                let (Some(from), Some(to)) = (project(&segment.from), project(&segment.to)) else {
+                    continue;
+                };
+
+                bundle.add(
+                    RenderPrimitive::<_, _, _, Polygon<_>>::new_contour(
+                        Contour::open(vec![from, to]),
+                        LinePaint {
+                            color: self.segment_color(&segment, time),
+                            width: self.style.width,
+                            offset: 0.0,
+                            width_unit: SizeUnit::Pixels,
+                            line_cap: LineCap::Round,
+                            pattern: None,
+                            gradient: None,
+                            zoom: None,
+                        },
+                    ),
+                    resolution,
+                );
+            }
+
+            if self.style.head_size > 0.0 {
+                if let Some(head) = track.position_at(time).and_then(|p| project(&p)) {
+                    bundle.add(
+                        RenderPrimitive::<_, _, Contour<_>, Polygon<_>>::new_point(
+                            head,
+                            PointPaint::circle(self.style.head_color, self.style.head_size as f32),
+                        ),
+                        resolution,
+                    );
+                }
+            }
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+
+    fn segment_color(&self, segment: &TrailSegment, time: SystemTime) -> Color {
+        let color = match (&self.style.speed_colors, segment.speed) {
+            (Some(colors), Some(speed)) => colors.color_of(speed as f32),
+            _ => self.style.color,
+        };
+
+        match self.style.trail {
+            Some(trail) if self.style.fade && !trail.is_zero() => {
+                let age = time.duration_since(segment.time).unwrap_or_default();
+                color.with_opacity(1.0 - age.as_secs_f64() / trail.as_secs_f64())
+            }
+            _ => color,
+        }
+    }
+}
+
+impl Layer for TrajectoryLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let time = self.timeline.current_time();
+        let key = PackKey {
+            crs: view.crs().clone(),
+            time,
+        };
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let is_outdated = !matches!(packed.as_ref(), Some((packed_key, _)) if *packed_key == key);
+        if is_outdated {
+            *packed = Some((key, self.pack(view, time, canvas)));
+        }
+
+        if let Some((_, bundle)) = packed.as_ref() {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+
+        if self.timeline.is_playing() {
+            if let Some(messenger) = &self.messenger {
+                messenger.request_redraw();
+            }
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {}
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.messenger = Some(messenger);
+    }
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::ManualClock;
+    use galileo_types::geo::{GeoPoint, NewGeoPoint};
+
+    fn at(seconds: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + seconds)
+    }
+
+    fn track() -> Track {
+        Track::new(vec![
+            (GeoPoint2d::latlon(0.0, 0.0), at(0)),
+            (GeoPoint2d::latlon(0.0, 1.0), at(100)),
+            (GeoPoint2d::latlon(1.0, 1.0), at(300)),
+        ])
+        .expect("valid track")
+    }
+
+    #[test]
+    fn track_interpolates_positions() {
+        assert!(Track::new(vec![]).is_err());
+        assert!(Track::new(vec![
+            (GeoPoint2d::latlon(0.0, 0.0), at(10)),
+            (GeoPoint2d::latlon(0.0, 1.0), at(5)),
+        ])
+        .is_err());
+
+        let track = track();
+        let middle = track.position_at(at(50)).expect("inside track");
+        assert!((middle.lon() - 0.5).abs() < 1e-9);
+        assert_eq!(
+            track.position_at(at(300)),
+            Some(GeoPoint2d::latlon(1.0, 1.0))
+        );
+        assert_eq!(track.position_at(at(301)), None);
+
+        // A degree of the equator in 100 seconds.
+        let speed = track.speed(0).expect("has speed");
+        assert!((speed - 1111.95).abs() < 1.0, "{speed}");
+        assert!(track.speed(2).is_none());
+    }
+
+    #[test]
+    fn trail_is_clipped_to_time_window() {
+        let trail = track().trail(at(50), at(200));
+        assert_eq!(trail.len(), 2);
+        assert!((trail[0].from.lon() - 0.5).abs() < 1e-9);
+        assert_eq!(trail[0].to, GeoPoint2d::latlon(0.0, 1.0));
+        assert_eq!(trail[0].time, at(75));
+        assert!((trail[1].to.lat() - 0.5).abs() < 1e-9);
+        assert!(trail[1].speed.expect("has speed") < trail[0].speed.expect("has speed"));
+    }
+
+    #[test]
+    fn timeline_plays_and_loops() {
+        let clock = ManualClock::new(at(0));
+        let timeline = Timeline::new(at(0), at(100))
+            .with_speed(10.0)
+            .with_clock(clock.clone());
+        let synced = timeline.clone();
+
+        timeline.play();
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(synced.current_time(), at(30));
+
+        timeline.pause();
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(synced.current_time(), at(30));
+
+        synced.seek_fraction(0.5);
+        assert_eq!(timeline.current_time(), at(50));
+
+        timeline.play();
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(timeline.current_time(), at(100));
+        assert!(!timeline.is_playing());
+
+        let looped = Timeline::new(at(0), at(100))
+            .with_loop(true)
+            .with_speed(10.0)
+            .with_clock(clock.clone());
+        looped.play();
+        clock.advance(Duration::from_secs(12));
+        assert_eq!(looped.current_time(), at(20));
+        assert!(looped.is_playing());
+    }
+}
@@ -0,0 +1,610 @@
+//! Arrows showing the direction and magnitude of a gridded vector field. See [`VectorFieldLayer`].
+
+use crate::elevation::ElevationTile;
+use crate::error::GalileoError;
+use crate::layer::data_provider::ColorMap;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{Canvas, LineCap, LinePaint, PackedBundle, RenderOptions, SizeUnit};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d, Rect};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint, Projection};
+use galileo_types::impls::{Contour, Polygon};
+use std::any::Any;
+use std::sync::Mutex;
+
+/// Maximum number of arrows drawn in one frame, so that a tilted view looking at the horizon does not produce
+/// millions of them.
+const MAX_ARROWS: usize = 20_000;
+/// Finest subdivision of a grid cell when the map is zoomed in beyond the resolution of the field.
+const MIN_STRIDE_LEVEL: i32 = -6;
+/// Length of the arrow head relative to the length of the arrow.
+const HEAD_LENGTH: f64 = 0.35;
+
+/// Regular grid of 2D vectors (e.g. wind, currents or a gradient) in geographic coordinates.
+///
+/// The vectors are given by their eastward (`u`) and northward (`v`) components, cell by cell, row by row starting
+/// from the north-west corner. The `bounds` are the geographic extent of the grid with the longitude as `x` and the
+/// latitude as `y`, and the value of a cell belongs to its center, the same way as the pixels of a raster. `NaN`
+/// components mark the cells without data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorField {
+    width: u32,
+    height: u32,
+    bounds: Rect,
+    u: Vec<f32>,
+    v: Vec<f32>,
+}
+
+impl VectorField {
+    /// Creates a new field from the eastward and northward components of the vectors. Returns an error if the number
+    /// of values does not match the dimensions.
+    pub fn new(
+        width: u32,
+        height: u32,
+        bounds: Rect,
+        u: Vec<f32>,
+        v: Vec<f32>,
+    ) -> Result<Self, GalileoError> {
+        let expected = width as usize * height as usize;
+        if width == 0 || height == 0 || u.len() != expected || v.len() != expected {
+            return Err(GalileoError::Generic(format!(
+                "expected {expected} values of each component for {width}x{height} vector field, but got {} and {}",
+                u.len(),
+                v.len()
+            )));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            bounds,
+            u,
+            v,
+        })
+    }
+
+    /// Creates a new field from the directions and magnitudes of the vectors. Directions are in degrees clockwise from
+    /// the north and point to where the vector goes (note that meteorological wind direction is where the wind comes
+    /// from, so it must be turned by 180°).
+    pub fn from_polar(
+        width: u32,
+        height: u32,
+        bounds: Rect,
+        directions: &[f32],
+        magnitudes: &[f32],
+    ) -> Result<Self, GalileoError> {
+        if directions.len() != magnitudes.len() {
+            return Err(GalileoError::Generic(format!(
+                "got {} directions, but {} magnitudes",
+                directions.len(),
+                magnitudes.len()
+            )));
+        }
+
+        let (u, v) = directions
+            .iter()
+            .zip(magnitudes)
+            .map(|(direction, magnitude)| {
+                let (sin, cos) = direction.to_radians().sin_cos();
+                (magnitude * sin, magnitude * cos)
+            })
+            .unzip();
+
+        Self::new(width, height, bounds, u, v)
+    }
+
+    /// Creates a new field from a two-band raster, given as the grids of the eastward and northward components.
+    pub fn from_bands(
+        bounds: Rect,
+        u: &ElevationTile,
+        v: &ElevationTile,
+    ) -> Result<Self, GalileoError> {
+        if u.width() != v.width() || u.height() != v.height() {
+            return Err(GalileoError::Generic(format!(
+                "bands of a vector field must have the same size, but got {}x{} and {}x{}",
+                u.width(),
+                u.height(),
+                v.width(),
+                v.height()
+            )));
+        }
+
+        Self::new(
+            u.width(),
+            u.height(),
+            bounds,
+            u.values().to_vec(),
+            v.values().to_vec(),
+        )
+    }
+
+    /// Width of the grid.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the grid.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Geographic extent of the grid, with the longitude as `x` and the latitude as `y`.
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Eastward and northward components of the given cell. Returns `None` if the cell is outside the grid or has no
+    /// data.
+    pub fn get(&self, x: u32, y: u32) -> Option<(f32, f32)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let index = (y * self.width + x) as usize;
+        let (u, v) = (self.u[index], self.v[index]);
+        (!u.is_nan() && !v.is_nan()).then_some((u, v))
+    }
+
+    /// Bilinearly interpolated vector at the given position. Returns `None` if the position is outside the grid or
+    /// next to a cell without data.
+    pub fn sample(&self, position: &impl GeoPoint<Num = f64>) -> Option<(f32, f32)> {
+        let (x, y) = self.to_grid(position.lon(), position.lat());
+        if x < 0.0 || y < 0.0 || x > self.width as f64 || y > self.height as f64 {
+            return None;
+        }
+
+        self.sample_grid(x, y)
+    }
+
+    /// Largest magnitude of the vectors of the field.
+    pub fn max_magnitude(&self) -> f32 {
+        self.u
+            .iter()
+            .zip(&self.v)
+            .map(|(u, v)| u.hypot(*v))
+            .filter(|magnitude| !magnitude.is_nan())
+            .fold(0.0, f32::max)
+    }
+
+    fn cell_size(&self) -> (f64, f64) {
+        (
+            self.bounds.width() / self.width as f64,
+            self.bounds.height() / self.height as f64,
+        )
+    }
+
+    /// Position in grid coordinates, where `(0.0, 0.0)` is the north-west corner and the centers of the cells are at
+    /// half-integer coordinates.
+    fn to_grid(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (cell_width, cell_height) = self.cell_size();
+        (
+            (lon - self.bounds.x_min()) / cell_width,
+            (self.bounds.y_max() - lat) / cell_height,
+        )
+    }
+
+    fn to_geo(&self, x: f64, y: f64) -> GeoPoint2d {
+        let (cell_width, cell_height) = self.cell_size();
+        GeoPoint2d::latlon(
+            self.bounds.y_max() - y * cell_height,
+            self.bounds.x_min() + x * cell_width,
+        )
+    }
+
+    fn sample_grid(&self, x: f64, y: f64) -> Option<(f32, f32)> {
+        let x = (x - 0.5).clamp(0.0, (self.width - 1) as f64);
+        let y = (y - 0.5).clamp(0.0, (self.height - 1) as f64);
+
+        let x0 = x.floor() as u32;
+        let y0 = y.floor() as u32;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let dx = (x - x0 as f64) as f32;
+        let dy = (y - y0 as f64) as f32;
+
+        // Cells with zero weight may have no data, so that the values at the edge of a gap are still sampled.
+        let corners = [
+            (x0, y0, (1.0 - dx) * (1.0 - dy)),
+            (x1, y0, dx * (1.0 - dy)),
+            (x0, y1, (1.0 - dx) * dy),
+            (x1, y1, dx * dy),
+        ];
+        let mut sample = (0.0, 0.0);
+        for (x, y, weight) in corners {
+            if weight > 0.0 {
+                let (u, v) = self.get(x, y)?;
+                sample.0 += u * weight;
+                sample.1 += v * weight;
+            }
+        }
+
+        Some(sample)
+    }
+}
+
+/// Layer that draws a [`VectorField`] as a grid of arrows.
+///
+/// The arrows point in the direction of the vectors, and their length and color depend on the magnitude. The
+/// density of the arrows adapts to the zoom level: when zoomed out, only every 2nd, 4th, 8th etc. cell of the field
+/// gets an arrow, and when zoomed in beyond the resolution of the field, the arrows are placed between the cells with
+/// interpolated values, so that the arrows are always at least [`spacing`](VectorFieldLayer::with_spacing) pixels
+/// apart. The arrows stay at the same places while the map is panned.
+///
+/// ```
+/// use galileo::layer::data_provider::ColorMap;
+/// use galileo::layer::{VectorField, VectorFieldLayer};
+/// use galileo::Color;
+/// use galileo_types::cartesian::Rect;
+///
+/// // Constant 10 m/s wind blowing to the north-east over the North Sea.
+/// let field = VectorField::from_polar(
+///     20,
+///     10,
+///     Rect::new(0.0, 52.0, 10.0, 57.0),
+///     &[45.0; 200],
+///     &[10.0; 200],
+/// )
+/// .expect("valid field");
+///
+/// let layer = VectorFieldLayer::new(field)
+///     .with_spacing(50.0)
+///     .with_color_map(ColorMap::continuous(vec![
+///         (0.0, Color::BLUE),
+///         (20.0, Color::RED),
+///     ]));
+/// ```
+pub struct VectorFieldLayer {
+    field: VectorField,
+    spacing: f64,
+    color: Color,
+    color_map: Option<ColorMap>,
+    min_length: f64,
+    max_length: f64,
+    max_magnitude: f32,
+    line_width: f64,
+    packed: Mutex<Option<(MapView, Box<dyn PackedBundle>)>>,
+}
+
+impl VectorFieldLayer {
+    /// Creates a new layer with black arrows, 40 pixels apart. The longest arrow has the largest magnitude of the
+    /// field.
+    pub fn new(field: VectorField) -> Self {
+        let max_magnitude = field.max_magnitude();
+        Self {
+            field,
+            spacing: 40.0,
+            color: Color::BLACK,
+            color_map: None,
+            min_length: 8.0,
+            max_length: 32.0,
+            max_magnitude,
+            line_width: 1.5,
+            packed: Mutex::new(None),
+        }
+    }
+
+    /// Sets the minimum distance between the arrows in pixels.
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing.max(1.0);
+        self
+    }
+
+    /// Sets the color of all the arrows. The color is not used if a color map is set.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Colors the arrows by the magnitude of the vectors.
+    pub fn with_color_map(mut self, color_map: ColorMap) -> Self {
+        self.color_map = Some(color_map);
+        self
+    }
+
+    /// Sets the lengths in pixels of the arrows for zero and for the [maximum](Self::with_max_magnitude) magnitude.
+    /// The length changes linearly between them.
+    pub fn with_arrow_length(mut self, min_length: f64, max_length: f64) -> Self {
+        self.min_length = min_length;
+        self.max_length = max_length;
+        self
+    }
+
+    /// Sets the magnitude of the longest arrow. Arrows of larger vectors are not longer than that.
+    pub fn with_max_magnitude(mut self, max_magnitude: f32) -> Self {
+        self.max_magnitude = max_magnitude;
+        self
+    }
+
+    /// Sets the width of the arrow lines in pixels.
+    pub fn with_line_width(mut self, line_width: f64) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Field displayed by the layer.
+    pub fn field(&self) -> &VectorField {
+        &self.field
+    }
+
+    fn pack(&self, view: &MapView, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let mut bundle = canvas.create_bundle();
+        let resolution = view.resolution();
+
+        if let Some(projection) = view.crs().get_projection::<GeoPoint2d, Point2d>() {
+            if let Some(stride) = self.stride(view, &*projection) {
+                for (x, y) in self.visible_positions(view, stride) {
+                    let position = self.field.to_geo(x, y);
+                    let Some((u, v)) = self.field.sample_grid(x, y) else {
+                        continue;
+                    };
+                    let Some(arrow) = self.arrow(&position, u, v, resolution, &*projection) else {
+                        continue;
+                    };
+
+                    let paint = LinePaint {
+                        color: arrow.color,
+                        width: self.line_width,
+                        offset: 0.0,
+                        width_unit: SizeUnit::Pixels,
+                        line_cap: LineCap::Round,
+                        pattern: None,
+                        gradient: None,
+                        zoom: None,
+                    };
+                    for line in [arrow.shaft, arrow.head] {
+                        bundle.add(
+                            RenderPrimitive::<_, _, _, Polygon<_>>::new_contour(
+                                Contour::open(line),
+                                paint,
+                            ),
+                            resolution,
+                        );
+                    }
+                }
+            }
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+
+    /// Distance between the arrows in grid cells, a power of 2.
+    fn stride(
+        &self,
+        view: &MapView,
+        projection: &dyn Projection<InPoint = GeoPoint2d, OutPoint = Point2d>,
+    ) -> Option<f64> {
+        let field = &self.field;
+        let center = view.position().unwrap_or_else(|| field.to_geo(0.0, 0.0));
+        let (x, y) = field.to_grid(center.lon(), center.lat());
+        let x = x.clamp(0.0, field.width as f64 - 1.0);
+        let y = y.clamp(0.0, field.height as f64 - 1.0);
+
+        let origin = projection.project(&field.to_geo(x, y))?;
+        let cell_length = |dx, dy| {
+            let corner = projection.project(&field.to_geo(x + dx, y + dy))?;
+            Some(((corner.x - origin.x).hypot(corner.y - origin.y)) / view.resolution())
+        };
+        let pixels_per_cell = cell_length(1.0, 0.0)?.min(cell_length(0.0, 1.0)?);
+
+        Some(2f64.powi(stride_level(pixels_per_cell, self.spacing)))
+    }
+
+    /// Positions of the arrows in grid coordinates inside the visible part of the map.
+    fn visible_positions(&self, view: &MapView, stride: f64) -> Vec<(f64, f64)> {
+        let field = &self.field;
+        let (mut x_min, mut y_min) = (0.0, 0.0);
+        let (mut x_max, mut y_max) = (field.width as f64, field.height as f64);
+        if let Some(footprint) = view.footprint() {
+            let (mut lon_min, mut lat_min) = (f64::INFINITY, f64::INFINITY);
+            let (mut lon_max, mut lat_max) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+            for point in &footprint.outer_contour.points {
+                lon_min = lon_min.min(point.lon());
+                lon_max = lon_max.max(point.lon());
+                lat_min = lat_min.min(point.lat());
+                lat_max = lat_max.max(point.lat());
+            }
+
+            // A footprint crossing the antimeridian looks like it spans the whole world, so it is not limited.
+            if lon_max - lon_min < 180.0 {
+                let (west, north) = field.to_grid(lon_min, lat_max);
+                let (east, south) = field.to_grid(lon_max, lat_min);
+                x_min = west.max(x_min);
+                x_max = east.min(x_max);
+                y_min = north.max(y_min);
+                y_max = south.min(y_max);
+            }
+        }
+
+        let first = |min: f64| ((min - 0.5) / stride).ceil().max(0.0);
+        let (x_first, y_first) = (first(x_min), first(y_min));
+        let mut positions = vec![];
+        let mut j = y_first;
+        while 0.5 + j * stride <= y_max {
+            let mut i = x_first;
+            while 0.5 + i * stride <= x_max {
+                if positions.len() == MAX_ARROWS {
+                    return positions;
+                }
+
+                positions.push((0.5 + i * stride, 0.5 + j * stride));
+                i += 1.0;
+            }
+            j += 1.0;
+        }
+
+        positions
+    }
+
+    fn arrow(
+        &self,
+        position: &GeoPoint2d,
+        u: f32,
+        v: f32,
+        resolution: f64,
+        projection: &dyn Projection<InPoint = GeoPoint2d, OutPoint = Point2d>,
+    ) -> Option<Arrow> {
+        let magnitude = u.hypot(v);
+        if magnitude <= 0.0 {
+            return None;
+        }
+
+        // The direction in the map is found by projecting a point slightly moved along the vector, since the north
+        // does not point up everywhere in every projection.
+        let step = 1e-4 / magnitude as f64;
+        let lat_scale = position.lat().to_radians().cos().max(1e-6);
+        let center = projection.project(position)?;
+        let moved = projection.project(&GeoPoint2d::latlon(
+            position.lat() + v as f64 * step,
+            position.lon() + u as f64 * step / lat_scale,
+        ))?;
+        let (dx, dy) = (moved.x - center.x, moved.y - center.y);
+        let length = dx.hypot(dy);
+        if length == 0.0 {
+            return None;
+        }
+        let (dx, dy) = (dx / length, dy / length);
+
+        let ratio = if self.max_magnitude > 0.0 {
+            (magnitude / self.max_magnitude).min(1.0) as f64
+        } else {
+            1.0
+        };
+        let half =
+            (self.min_length + (self.max_length - self.min_length) * ratio) * resolution / 2.0;
+        let head = half * 2.0 * HEAD_LENGTH;
+
+        let point = |along: f64, across: f64| {
+            Point3d::new(
+                center.x + dx * along - dy * across,
+                center.y + dy * along + dx * across,
+                0.0,
+            )
+        };
+        let color = match &self.color_map {
+            Some(color_map) => color_map.color_of(magnitude),
+            None => self.color,
+        };
+
+        Some(Arrow {
+            shaft: vec![point(-half, 0.0), point(half, 0.0)],
+            head: vec![
+                point(half - head, head / 2.0),
+                point(half, 0.0),
+                point(half - head, -head / 2.0),
+            ],
+            color,
+        })
+    }
+}
+
+impl Layer for VectorFieldLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let is_outdated = !matches!(packed.as_ref(), Some((packed_view, _)) if packed_view == view);
+        if is_outdated {
+            *packed = Some((view.clone(), self.pack(view, canvas)));
+        }
+
+        if let Some((_, bundle)) = packed.as_ref() {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {}
+
+    fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+    fn reset_gpu_resources(&mut self) {
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+struct Arrow {
+    shaft: Vec<Point3d>,
+    head: Vec<Point3d>,
+    color: Color,
+}
+
+/// Power of 2 of the smallest distance between the arrows in grid cells, that is at least `spacing` pixels.
+fn stride_level(pixels_per_cell: f64, spacing: f64) -> i32 {
+    if pixels_per_cell <= 0.0 || !pixels_per_cell.is_finite() {
+        return 0;
+    }
+
+    ((spacing / pixels_per_cell).log2().ceil() as i32).max(MIN_STRIDE_LEVEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field() -> VectorField {
+        VectorField::new(
+            2,
+            2,
+            Rect::new(0.0, 0.0, 2.0, 2.0),
+            vec![1.0, 3.0, 1.0, f32::NAN],
+            vec![0.0, 0.0, 2.0, 2.0],
+        )
+        .expect("valid field")
+    }
+
+    #[test]
+    fn validates_dimensions() {
+        assert!(VectorField::new(
+            2,
+            2,
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            vec![0.0; 4],
+            vec![0.0; 3]
+        )
+        .is_err());
+        assert!(VectorField::from_polar(1, 1, Rect::new(0.0, 0.0, 1.0, 1.0), &[0.0], &[]).is_err());
+
+        let field = VectorField::from_polar(1, 1, Rect::new(0.0, 0.0, 1.0, 1.0), &[90.0], &[2.0])
+            .expect("valid field");
+        let (u, v) = field.get(0, 0).expect("has data");
+        assert!((u - 2.0).abs() < 1e-6);
+        assert!(v.abs() < 1e-6);
+    }
+
+    #[test]
+    fn samples_between_cells() {
+        let field = field();
+        // North-west cell center.
+        assert_eq!(
+            field.sample(&GeoPoint2d::latlon(1.5, 0.5)),
+            Some((1.0, 0.0))
+        );
+        // Between the two northern cells.
+        assert_eq!(
+            field.sample(&GeoPoint2d::latlon(1.5, 1.0)),
+            Some((2.0, 0.0))
+        );
+        // Next to the cell without data.
+        assert_eq!(field.sample(&GeoPoint2d::latlon(0.5, 1.0)), None);
+        assert_eq!(field.sample(&GeoPoint2d::latlon(3.0, 1.0)), None);
+        assert!((field.max_magnitude() - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stride_adapts_to_zoom() {
+        assert_eq!(stride_level(40.0, 40.0), 0);
+        assert_eq!(stride_level(10.0, 40.0), 2);
+        assert_eq!(stride_level(11.0, 40.0), 2);
+        assert_eq!(stride_level(100.0, 40.0), -1);
+        assert_eq!(stride_level(1e6, 40.0), MIN_STRIDE_LEVEL);
+    }
+}
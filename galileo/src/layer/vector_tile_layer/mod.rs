@@ -1,10 +1,13 @@
 //! [Vector tile layers](VectorTileLayer) load prepared vector tiles using a [data provider](VectorTileProvider)
 //! and draw them to the map with the given [`VectorTileStyle`].
 
-use crate::layer::Layer;
+use crate::control::PointerType;
+use crate::layer::{HitTolerance, Layer, TileLoadMonitor};
 use crate::messenger::Messenger;
-use crate::render::{Canvas, PackedBundle, RenderOptions};
-use crate::tile_scheme::TileSchema;
+use crate::render::{
+    Canvas, CustomShader, GpuMemoryBudget, HighlightStyle, PackedBundle, RenderOptions,
+};
+use crate::tile_scheme::{TileIndex, TileSchema};
 use crate::view::MapView;
 use nalgebra::Point2;
 use std::any::Any;
@@ -20,30 +23,57 @@ pub mod style;
 pub mod tile_provider;
 mod vector_tile;
 
+use vector_tile::FeatureHighlight;
 pub use vector_tile::VectorTile;
 
 /// Vector tile layers use [`Providers`](VectorTileProvider) to load prepared vector tiles, and then render them using
 /// specified [styles](VectorTileStyle).
+///
+/// When the map is zoomed in beyond the last level of the tile schema, or the source does not have some of the tiles
+/// (see [`TileSchema::with_max_z`]), the tiles of the lower levels are displayed upscaled instead. Since line widths
+/// are set in pixels, they stay the same when a tile is upscaled.
 pub struct VectorTileLayer<Provider: VectorTileProvider> {
     tile_provider: Provider,
     tile_scheme: TileSchema,
     style: VectorTileStyle,
+    shader: Option<CustomShader>,
+    highlight: FeatureHighlight,
+    hit_tolerance: HitTolerance,
 }
 
 impl<Provider: VectorTileProvider + 'static> Layer for VectorTileLayer<Provider> {
     fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
         let mut tiles_store = self.tile_provider.read();
+        tiles_store.remove_evicted();
         let tiles = self.get_tiles_to_draw(view, &mut tiles_store, canvas);
         let to_render: Vec<&dyn PackedBundle> = tiles.iter().map(|v| &*v.bundle).collect();
 
-        canvas.draw_bundles(&to_render, RenderOptions::default());
+        match &self.shader {
+            Some(shader) => {
+                canvas.draw_bundles_with_shader(&to_render, RenderOptions::default(), shader)
+            }
+            None => canvas.draw_bundles(&to_render, RenderOptions::default()),
+        }
     }
 
     fn prepare(&self, view: &MapView) {
-        if let Some(iter) = self.tile_scheme.iter_tiles(view) {
-            for index in iter {
-                self.tile_provider.load_tile(index, &self.style);
-            }
+        let Some(iter) = self.tile_scheme.iter_tiles(view) else {
+            return;
+        };
+
+        let indices: Vec<_> = iter.collect();
+        let fallbacks: HashSet<_> = {
+            let tiles_store = self.tile_provider.read();
+            indices
+                .iter()
+                .filter_map(|index| self.fallback_tile(*index, &tiles_store))
+                .collect()
+        };
+
+        // Fallback tiles are loaded with the current style too, so that their style is updated when they are
+        // displayed instead of the missing tiles.
+        for index in indices.into_iter().chain(fallbacks) {
+            self.tile_provider.load_tile(index, &self.style);
         }
     }
 
@@ -51,6 +81,10 @@ impl<Provider: VectorTileProvider + 'static> Layer for VectorTileLayer<Provider>
         self.tile_provider.set_messenger(messenger);
     }
 
+    fn reset_gpu_resources(&mut self) {
+        self.highlight.pack_generation += 1;
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -61,6 +95,48 @@ impl<Provider: VectorTileProvider + 'static> Layer for VectorTileLayer<Provider>
 }
 
 impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
+    /// Sets a custom shader to draw polygons and lines of the tiles with. See [`CustomShader`] for details.
+    pub fn with_shader(mut self, shader: CustomShader) -> Self {
+        self.shader = Some(shader);
+        self
+    }
+
+    /// Replaces the custom shader of the layer. If `None` is given, the default shader is used.
+    pub fn set_shader(&mut self, shader: Option<CustomShader>) {
+        self.shader = shader;
+    }
+
+    /// Sets the style of the features highlighted with [`VectorTileLayer::set_highlighted_features`].
+    pub fn with_highlight_style(mut self, style: HighlightStyle) -> Self {
+        self.set_highlight_style(style);
+        self
+    }
+
+    /// Changes the style of the highlighted features.
+    pub fn set_highlight_style(&mut self, style: HighlightStyle) {
+        self.highlight.style = style;
+        self.highlight.version += 1;
+    }
+
+    /// Highlights the features with the given ids in all the tiles of the layer, removing the highlight from all the
+    /// other features. Ids of the features can be obtained with [`VectorTileLayer::get_features_at`].
+    ///
+    /// Tiles are not decoded again when the highlight changes, only the colors of the features are updated, so this
+    /// is cheap enough to be called on every cursor move. Features without ids in the tile data cannot be
+    /// highlighted.
+    pub fn set_highlighted_features(&mut self, ids: impl IntoIterator<Item = u64>) {
+        let ids: HashSet<u64> = ids.into_iter().collect();
+        if ids != self.highlight.ids {
+            self.highlight.ids = ids;
+            self.highlight.version += 1;
+        }
+    }
+
+    /// Ids of the highlighted features.
+    pub fn highlighted_features(&self) -> &HashSet<u64> {
+        &self.highlight.ids
+    }
+
     /// Style of the layer.
     pub fn style(&self) -> &VectorTileStyle {
         &self.style
@@ -76,6 +152,9 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
             tile_provider,
             tile_scheme,
             style,
+            shader: None,
+            highlight: FeatureHighlight::default(),
+            hit_tolerance: HitTolerance::default(),
         }
     }
 
@@ -93,14 +172,15 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
         let indices: Vec<_> = tile_iter.collect();
 
         for index in &indices {
-            tiles_store.pack(*index, canvas);
+            tiles_store.pack(*index, self.highlight.pack_generation, canvas);
         }
 
+        let mut to_draw = vec![];
         let mut to_substitute = vec![];
         for index in &indices {
             match tiles_store.get_tile(*index) {
                 None => to_substitute.push(*index),
-                Some(v) => tiles.push((*index, v)),
+                Some(_) => to_draw.push(*index),
             }
         }
 
@@ -113,9 +193,10 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
                     None => break,
                 };
 
-                if let Some(tile) = tiles_store.get_tile(substitute_index) {
+                tiles_store.pack(substitute_index, self.highlight.pack_generation, canvas);
+                if tiles_store.get_tile(substitute_index).is_some() {
                     if !substitute_indices.contains(&substitute_index) {
-                        tiles.push((substitute_index, tile));
+                        to_draw.push(substitute_index);
                         substitute_indices.insert(substitute_index);
                     }
 
@@ -124,22 +205,105 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
             }
         }
 
-        tiles.sort_unstable_by(|(index_a, _), (index_b, _)| index_a.z.cmp(&index_b.z));
-        tiles.into_iter().map(|(_, tile)| tile).collect()
+        for index in &to_draw {
+            tiles_store.update_highlight(*index, &self.highlight, canvas);
+        }
+
+        to_draw.sort_unstable_by(|index_a, index_b| index_a.z.cmp(&index_b.z));
+        for index in to_draw {
+            if let Some(tile) = tiles_store.get_tile(index) {
+                tiles.push(tile);
+            }
+        }
+
+        tiles
     }
 
-    /// Change style of the layer and redraw it.
-    pub fn update_style(&mut self, style: VectorTileStyle) {
+    /// For a tile that failed to load, returns the closest tile of the lower levels that did not fail. It is loaded
+    /// and displayed upscaled instead of the missing tile, e.g. when the map is zoomed in beyond the maximum level
+    /// available in the source.
+    fn fallback_tile(&self, index: TileIndex, tiles_store: &LockedTileStore) -> Option<TileIndex> {
+        let mut fallback = index;
+        while tiles_store.is_failed(fallback) {
+            fallback = self.tile_scheme.get_substitutes(fallback)?.next()?;
+        }
+
+        (fallback != index).then_some(fallback)
+    }
+
+    /// Sets the GPU memory budget for the tiles of the layer. The budget can be shared with other layers.
+    ///
+    /// By default, each layer uses its own unlimited budget.
+    pub fn set_memory_budget(&self, budget: &GpuMemoryBudget) {
+        self.tile_provider.set_memory_budget(budget);
+    }
+
+    /// Approximate size in bytes of GPU memory used by the packed tiles of the layer.
+    pub fn gpu_memory_usage(&self) -> usize {
+        self.tile_provider.gpu_memory_usage()
+    }
+
+    /// Monitor reporting the progress and errors of loading the tiles of the layer.
+    pub fn load_monitor(&self) -> &TileLoadMonitor {
+        self.tile_provider.load_monitor()
+    }
+
+    /// Replaces the style of the layer at runtime.
+    ///
+    /// The tiles are rendered with the new style in the background, and until a tile is ready, it is displayed with
+    /// the previous style, so the map does not go blank while the style is changing.
+    pub fn set_style(&mut self, style: VectorTileStyle) {
         self.style = style;
         self.tile_provider.update_style();
     }
 
-    /// Returns features, visible in the layer at the given point with the given map view.
+    /// Change style of the layer and redraw it.
+    #[deprecated(note = "use `set_style` instead")]
+    pub fn update_style(&mut self, style: VectorTileStyle) {
+        self.set_style(style);
+    }
+
+    /// Sets the tolerance used to find the features under the pointer.
+    pub fn with_hit_tolerance(mut self, tolerance: HitTolerance) -> Self {
+        self.hit_tolerance = tolerance;
+        self
+    }
+
+    /// Changes the hit-test tolerance of the layer.
+    pub fn set_hit_tolerance(&mut self, tolerance: HitTolerance) {
+        self.hit_tolerance = tolerance;
+    }
+
+    /// Hit-test tolerance of the layer.
+    pub fn hit_tolerance(&self) -> HitTolerance {
+        self.hit_tolerance
+    }
+
+    /// Returns features, visible in the layer at the given point with the given map view. The mouse
+    /// [hit tolerance](VectorTileLayer::with_hit_tolerance) of the layer is used.
+    ///
+    /// Tile layers that are not [used by the style](VectorTileStyle::uses_layer) are not decoded, so their features
+    /// are never returned.
     pub fn get_features_at(
         &self,
         point: &impl CartesianPoint2d<Num = f64>,
         view: &MapView,
     ) -> Vec<(String, MvtFeature)> {
+        self.get_features_at_pointer(point, PointerType::Mouse, view)
+    }
+
+    /// Returns features, visible in the layer under the pointer of the given type at the given point. Touch input
+    /// uses a larger tolerance than mouse, so that thin lines can be picked with a finger.
+    ///
+    /// As with [`get_features_at`](VectorTileLayer::get_features_at), features of the tile layers that are not used
+    /// by the style are not returned.
+    pub fn get_features_at_pointer(
+        &self,
+        point: &impl CartesianPoint2d<Num = f64>,
+        pointer_type: PointerType,
+        view: &MapView,
+    ) -> Vec<(String, MvtFeature)> {
+        let tolerance_map_units = self.hit_tolerance.map_units(pointer_type, view);
         let tile_store = self.tile_provider.read();
         let mut features = vec![];
         if let Some(iter) = self.tile_scheme.iter_tiles(view) {
@@ -158,7 +322,7 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
                     ((tile_bbox.y_max() - point.y()) / tile_resolution) as f32,
                 );
 
-                let tolerance = (view.resolution() / tile_resolution) as f32 * 2.0;
+                let tolerance = (tolerance_map_units / tile_resolution) as f32;
 
                 if let Some(mvt_tile) = tile_store.get_mvt_tile(index) {
                     for layer in &mvt_tile.layers {
@@ -1,7 +1,8 @@
 //! See [`VectorTileStyle`].
 
-use crate::Color;
-use galileo_mvt::MvtFeature;
+use crate::render::ZoomInterpolation;
+use crate::{Color, ColorSpace};
+use galileo_mvt::{MvtFeature, MvtValue};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -19,6 +20,36 @@ pub struct VectorTileStyle {
 
     /// Background color of tiles.
     pub background: Color,
+
+    /// Specifies how the geometries are clipped at the tile edges.
+    #[serde(default)]
+    pub clipping: TileClipping,
+}
+
+/// Specifies how the geometries of a vector tile are clipped to the extent of the tile.
+///
+/// Vector tiles usually contain the geometries that cross the tile edges with some buffer around the tile. The
+/// geometries are cut at the edge of the buffer area before they are tessellated, and then the tile is drawn only
+/// inside its own extent, so that features crossing the tile edges are not drawn twice by the adjacent tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TileClipping {
+    /// Width of the area around the tile, in pixels of the tile, in which the geometries are kept when they are cut.
+    /// It should be larger than a half of the widest line of the style, so that the lines are drawn with full width
+    /// and proper joins up to the tile edges.
+    pub buffer: f64,
+    /// Distance in pixels of the tile by which the drawing area of the tile extends over the tile edges. A small
+    /// overlap (e.g. `0.5`) hides hairline gaps between the tiles that can appear with anti-aliasing, at the cost of
+    /// semi-transparent fills being drawn twice along the tile edges.
+    pub overlap: f64,
+}
+
+impl Default for TileClipping {
+    fn default() -> Self {
+        Self {
+            buffer: 16.0,
+            overlap: 0.0,
+        }
+    }
 }
 
 impl VectorTileStyle {
@@ -32,11 +63,25 @@ impl VectorTileStyle {
             layer_name_check_passed
                 && (rule.properties.is_empty()
                     || rule.properties.iter().all(|(key, value)| {
-                        feature.properties.get(key).map(|v| v.to_string())
-                            == Some(value.to_string())
+                        feature.properties.get(key).is_some_and(|v| match v {
+                            MvtValue::String(v) => v == value,
+                            v => v.to_string() == *value,
+                        })
                     }))
         })
     }
+
+    /// Returns true if features of the given tile layer can be drawn with this style. Layers that are not used by
+    /// the style are skipped when decoding tiles, so their features cannot be found with
+    /// [`VectorTileLayer::get_features_at`](super::VectorTileLayer::get_features_at) either. To make features of a
+    /// layer searchable without drawing them, add a rule for the layer with an empty symbol.
+    pub fn uses_layer(&self, layer_name: &str) -> bool {
+        !self.default_symbol.is_empty()
+            || self.rules.iter().any(|rule| match &rule.layer_name {
+                Some(name) => name == layer_name,
+                None => true,
+            })
+    }
 }
 
 /// A rule that specifies what kind of features can be drawing with the given symbol.
@@ -68,9 +113,18 @@ impl VectorTileSymbol {
         Self {
             point: None,
             line: None,
-            polygon: Some(VectorTilePolygonSymbol { fill_color: color }),
+            polygon: Some(VectorTilePolygonSymbol {
+                fill_color: color,
+                fill_color_stops: None,
+                opacity_stops: None,
+            }),
         }
     }
+
+    /// Returns true if the symbol does not draw any geometry type.
+    pub fn is_empty(&self) -> bool {
+        self.point.is_none() && self.line.is_none() && self.polygon.is_none()
+    }
 }
 
 /// Symbol for point geometries.
@@ -89,6 +143,60 @@ pub struct VectorTileLineSymbol {
     pub width: f64,
     /// Color of the line in pixels.
     pub stroke_color: Color,
+    /// If set, the width of the line changes with the zoom level and `width` is not used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width_stops: Option<ZoomStops<f64>>,
+    /// If set, the color of the line changes with the zoom level and `stroke_color` is not used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke_color_stops: Option<ZoomStops<Color>>,
+    /// Opacity of the line from `0.0` to `1.0` changing with the zoom level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity_stops: Option<ZoomStops<f64>>,
+}
+
+impl VectorTileLineSymbol {
+    /// Zoom interpolation of the symbol for a tile at the level `z` with the given resolution. Returns `None` if the
+    /// symbol does not change with the zoom level.
+    pub fn zoom_interpolation(&self, z: u32, resolution: f64) -> Option<ZoomInterpolation> {
+        let zoom_range = ZoomRange::new(
+            z,
+            resolution,
+            [
+                self.width_stops.as_ref().map(ZoomStops::zoom_levels),
+                self.stroke_color_stops.as_ref().map(ZoomStops::zoom_levels),
+                self.opacity_stops.as_ref().map(ZoomStops::zoom_levels),
+            ],
+        )?;
+
+        let width = |zoom| {
+            self.width_stops
+                .as_ref()
+                .and_then(|stops| stops.value_at(zoom))
+                .unwrap_or(self.width)
+        };
+        let color = |zoom| {
+            let color = self
+                .stroke_color_stops
+                .as_ref()
+                .and_then(|stops| stops.value_at(zoom))
+                .unwrap_or(self.stroke_color);
+            let opacity = self
+                .opacity_stops
+                .as_ref()
+                .and_then(|stops| stops.value_at(zoom))
+                .unwrap_or(1.0);
+            color.with_opacity(opacity)
+        };
+
+        Some(ZoomInterpolation {
+            from_resolution: zoom_range.from_resolution,
+            to_resolution: zoom_range.to_resolution,
+            from_color: color(zoom_range.from),
+            to_color: color(zoom_range.to),
+            from_width: width(zoom_range.from),
+            to_width: width(zoom_range.to),
+        })
+    }
 }
 
 /// Symbol for polygon geometries.
@@ -96,4 +204,243 @@ pub struct VectorTileLineSymbol {
 pub struct VectorTilePolygonSymbol {
     /// Color of the fill of polygon.
     pub fill_color: Color,
+    /// If set, the fill color changes with the zoom level and `fill_color` is not used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill_color_stops: Option<ZoomStops<Color>>,
+    /// Opacity of the fill from `0.0` to `1.0` changing with the zoom level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity_stops: Option<ZoomStops<f64>>,
+}
+
+impl VectorTilePolygonSymbol {
+    /// Zoom interpolation of the symbol for a tile at the level `z` with the given resolution. Returns `None` if the
+    /// symbol does not change with the zoom level.
+    pub fn zoom_interpolation(&self, z: u32, resolution: f64) -> Option<ZoomInterpolation> {
+        let zoom_range = ZoomRange::new(
+            z,
+            resolution,
+            [
+                self.fill_color_stops.as_ref().map(ZoomStops::zoom_levels),
+                self.opacity_stops.as_ref().map(ZoomStops::zoom_levels),
+            ],
+        )?;
+
+        let color = |zoom| {
+            let color = self
+                .fill_color_stops
+                .as_ref()
+                .and_then(|stops| stops.value_at(zoom))
+                .unwrap_or(self.fill_color);
+            let opacity = self
+                .opacity_stops
+                .as_ref()
+                .and_then(|stops| stops.value_at(zoom))
+                .unwrap_or(1.0);
+            color.with_opacity(opacity)
+        };
+
+        Some(ZoomInterpolation {
+            from_resolution: zoom_range.from_resolution,
+            to_resolution: zoom_range.to_resolution,
+            from_color: color(zoom_range.from),
+            to_color: color(zoom_range.to),
+            from_width: 0.0,
+            to_width: 0.0,
+        })
+    }
+}
+
+/// Value of a style property that changes with the zoom level of the map, given as `[zoom, value]` pairs.
+///
+/// Zoom levels are the `z` indices of the tile schema of the layer, and can be fractional. Between the stops the value
+/// is interpolated linearly; before the first and after the last stop the value of the closest stop is used.
+///
+/// The interpolation is done by the renderer while the map is zoomed, so the tiles do not need to be tessellated
+/// again. Every tile stores the values at the two stops around its own zoom level, so the tile schema must have
+/// resolutions changing by a factor of two between the levels, and stops between two consecutive levels are skipped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    from = "Vec<(f64, T)>",
+    into = "Vec<(f64, T)>",
+    bound(serialize = "T: Serialize + Clone")
+)]
+pub struct ZoomStops<T> {
+    stops: Vec<(f64, T)>,
+}
+
+impl<T> From<Vec<(f64, T)>> for ZoomStops<T> {
+    fn from(stops: Vec<(f64, T)>) -> Self {
+        Self::new(stops)
+    }
+}
+
+impl<T> From<ZoomStops<T>> for Vec<(f64, T)> {
+    fn from(value: ZoomStops<T>) -> Self {
+        value.stops
+    }
+}
+
+impl<T> ZoomStops<T> {
+    /// Creates a new set of stops from `(zoom, value)` pairs. The stops are sorted by their zoom levels.
+    pub fn new(mut stops: Vec<(f64, T)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Stops of the property.
+    pub fn stops(&self) -> &[(f64, T)] {
+        &self.stops
+    }
+
+    fn zoom_levels(&self) -> Vec<f64> {
+        self.stops.iter().map(|(zoom, _)| *zoom).collect()
+    }
+}
+
+impl<T: ZoomValue> ZoomStops<T> {
+    /// Value of the property at the given zoom level. Returns `None` if there are no stops.
+    pub fn value_at(&self, zoom: f64) -> Option<T> {
+        let stops = &self.stops;
+        let next_index = stops.partition_point(|(stop_zoom, _)| *stop_zoom <= zoom);
+        if next_index == 0 {
+            return stops.first().map(|(_, value)| *value);
+        }
+        if next_index == stops.len() {
+            return stops.last().map(|(_, value)| *value);
+        }
+
+        let (from_zoom, from) = stops[next_index - 1];
+        let (to_zoom, to) = stops[next_index];
+        Some(from.interpolate(to, (zoom - from_zoom) / (to_zoom - from_zoom)))
+    }
+}
+
+/// Value of a style property that can be interpolated between zoom stops.
+pub trait ZoomValue: Copy {
+    /// Interpolates between this value (`t == 0`) and the `other` one (`t == 1`).
+    fn interpolate(self, other: Self, t: f64) -> Self;
+}
+
+impl ZoomValue for f64 {
+    fn interpolate(self, other: Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl ZoomValue for Color {
+    fn interpolate(self, other: Self, t: f64) -> Self {
+        Color::interpolate(&self, &other, t, ColorSpace::Rgb)
+    }
+}
+
+/// Pair of zoom levels between which the style of a tile is interpolated.
+struct ZoomRange {
+    from: f64,
+    to: f64,
+    from_resolution: f64,
+    to_resolution: f64,
+}
+
+impl ZoomRange {
+    /// Selects the closest stops below and above the middle of the zoom levels, in which the tile at the level `z` is
+    /// displayed (from `z - 1` to `z`). Returns `None` if there are no stops.
+    fn new<const N: usize>(z: u32, resolution: f64, levels: [Option<Vec<f64>>; N]) -> Option<Self> {
+        let mut levels: Vec<f64> = levels.into_iter().flatten().flatten().collect();
+        if levels.is_empty() {
+            return None;
+        }
+        levels.sort_by(f64::total_cmp);
+
+        let middle = z as f64 - 0.5;
+        let next_index = levels.partition_point(|level| *level <= middle);
+        let from = levels[next_index.saturating_sub(1)];
+        let to = levels[next_index.min(levels.len() - 1)];
+
+        let resolution_at = |zoom: f64| resolution * 2f64.powf(z as f64 - zoom);
+        Some(Self {
+            from,
+            to,
+            from_resolution: resolution_at(from),
+            to_resolution: resolution_at(to),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn only_layers_with_rules_are_used() {
+        let mut style = VectorTileStyle {
+            rules: vec![StyleRule {
+                layer_name: Some("roads".into()),
+                properties: HashMap::new(),
+                symbol: VectorTileSymbol::default(),
+            }],
+            ..Default::default()
+        };
+        assert!(style.uses_layer("roads"));
+        assert!(!style.uses_layer("water"));
+
+        style.default_symbol = VectorTileSymbol::polygon(Color::BLACK);
+        assert!(style.uses_layer("water"));
+
+        style.default_symbol = VectorTileSymbol::default();
+        style.rules[0].layer_name = None;
+        assert!(style.uses_layer("water"));
+    }
+
+    #[test]
+    fn zoom_stops_value() {
+        let stops = ZoomStops::new(vec![(10.0, 4.0), (5.0, 1.0)]);
+        assert_eq!(stops.value_at(0.0), Some(1.0));
+        assert_eq!(stops.value_at(7.5), Some(2.5));
+        assert_eq!(stops.value_at(12.0), Some(4.0));
+        assert_eq!(ZoomStops::<f64>::new(vec![]).value_at(1.0), None);
+    }
+
+    #[test]
+    fn stops_deserialize_as_pairs() {
+        let symbol: VectorTileLineSymbol = serde_json::from_str(
+            r##"{"width": 1.0, "stroke_color": "#000000", "width_stops": [[5, 1.0], [10, 4.0]]}"##,
+        )
+        .unwrap();
+        assert_eq!(
+            symbol.width_stops.unwrap().stops(),
+            &[(5.0, 1.0), (10.0, 4.0)]
+        );
+        assert!(symbol.stroke_color_stops.is_none());
+    }
+
+    #[test]
+    fn line_zoom_interpolation() {
+        let symbol = VectorTileLineSymbol {
+            width: 1.0,
+            stroke_color: Color::BLACK,
+            width_stops: Some(ZoomStops::new(vec![(5.0, 1.0), (10.0, 6.0)])),
+            stroke_color_stops: None,
+            opacity_stops: Some(ZoomStops::new(vec![(6.0, 0.0), (8.0, 1.0)])),
+        };
+
+        // Tile at level 7 is displayed from zoom 6 to 7, between the opacity stops at 6 and 8.
+        let interpolation = symbol.zoom_interpolation(7, 100.0).unwrap();
+        assert_eq!(interpolation.from_resolution, 200.0);
+        assert_eq!(interpolation.to_resolution, 50.0);
+        assert_abs_diff_eq!(interpolation.from_width, 2.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(interpolation.to_width, 4.0, epsilon = 1e-9);
+        assert_eq!(interpolation.from_color, Color::BLACK.with_alpha(0));
+        assert_eq!(interpolation.to_color, Color::BLACK);
+
+        // Halfway between the stops in zoom levels.
+        assert_abs_diff_eq!(interpolation.width_at(100.0), 3.0, epsilon = 1e-9);
+
+        let no_stops = VectorTileLineSymbol {
+            width_stops: None,
+            opacity_stops: None,
+            ..symbol
+        };
+        assert!(no_stops.zoom_interpolation(7, 100.0).is_none());
+    }
 }
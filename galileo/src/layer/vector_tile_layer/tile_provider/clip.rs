@@ -0,0 +1,255 @@
+//! Clipping of the vector tile geometries to the extent of the tile.
+
+use galileo_types::cartesian::{Point3d, Rect};
+use std::ops::Range;
+
+/// Splits the polyline into the parts that are inside the `rect`. The points of the parts are written into `points`,
+/// and the ranges of each part in `points` into `parts`. Both vectors are cleared before clipping.
+pub(crate) fn clip_line(
+    line: impl Iterator<Item = Point3d>,
+    rect: Rect,
+    points: &mut Vec<Point3d>,
+    parts: &mut Vec<Range<usize>>,
+) {
+    points.clear();
+    parts.clear();
+
+    let mut part_start = 0;
+    let mut prev: Option<Point3d> = None;
+    for point in line {
+        let Some(from) = prev.replace(point) else {
+            if rect_contains(rect, &point) {
+                points.push(point);
+            }
+            continue;
+        };
+
+        let Some((start, end)) = clip_segment(from, point, rect) else {
+            continue;
+        };
+
+        let continues_part = points.len() > part_start && points.last() == Some(&start);
+        if !continues_part {
+            if points.len() - part_start > 1 {
+                parts.push(part_start..points.len());
+            } else {
+                points.truncate(part_start);
+            }
+
+            part_start = points.len();
+            points.push(start);
+        }
+
+        points.push(end);
+    }
+
+    if points.len() - part_start > 1 {
+        parts.push(part_start..points.len());
+    } else {
+        points.truncate(part_start);
+    }
+}
+
+/// Clips the closed contour to the `rect` with Sutherland-Hodgman algorithm. The result is written into `out`. The
+/// `scratch` buffer is used for the intermediate results.
+///
+/// Parts of the contour outside the rectangle are replaced with the segments along the rectangle border, so the
+/// clipped contour can be filled the same way as the original one inside the rectangle.
+pub(crate) fn clip_contour(
+    contour: impl Iterator<Item = Point3d>,
+    rect: Rect,
+    out: &mut Vec<Point3d>,
+    scratch: &mut Vec<Point3d>,
+) {
+    out.clear();
+    out.extend(contour);
+
+    for edge in [Edge::Left, Edge::Right, Edge::Bottom, Edge::Top] {
+        if out.is_empty() {
+            return;
+        }
+
+        std::mem::swap(out, scratch);
+        out.clear();
+
+        let Some(&last) = scratch.last() else {
+            continue;
+        };
+        let mut prev = last;
+        for &point in scratch.iter() {
+            let prev_inside = edge.is_inside(rect, &prev);
+            let inside = edge.is_inside(rect, &point);
+            if inside != prev_inside {
+                out.push(edge.intersection(rect, prev, point));
+            }
+            if inside {
+                out.push(point);
+            }
+
+            prev = point;
+        }
+    }
+
+    if out.len() < 3 {
+        out.clear();
+    }
+}
+
+fn rect_contains(rect: Rect, point: &Point3d) -> bool {
+    point.x >= rect.x_min()
+        && point.x <= rect.x_max()
+        && point.y >= rect.y_min()
+        && point.y <= rect.y_max()
+}
+
+/// Clips the segment with Liang-Barsky algorithm.
+fn clip_segment(from: Point3d, to: Point3d, rect: Rect) -> Option<(Point3d, Point3d)> {
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+
+    let mut t_min = 0.0f64;
+    let mut t_max = 1.0f64;
+    for (p, q) in [
+        (-dx, from.x - rect.x_min()),
+        (dx, rect.x_max() - from.x),
+        (-dy, from.y - rect.y_min()),
+        (dy, rect.y_max() - from.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t_min = t_min.max(t);
+            } else {
+                t_max = t_max.min(t);
+            }
+        }
+    }
+
+    if t_min > t_max {
+        return None;
+    }
+
+    let at = |t: f64| {
+        if t == 0.0 {
+            from
+        } else if t == 1.0 {
+            to
+        } else {
+            Point3d::new(
+                from.x + dx * t,
+                from.y + dy * t,
+                from.z + (to.z - from.z) * t,
+            )
+        }
+    };
+
+    Some((at(t_min), at(t_max)))
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Edge {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+impl Edge {
+    fn is_inside(&self, rect: Rect, point: &Point3d) -> bool {
+        match self {
+            Edge::Left => point.x >= rect.x_min(),
+            Edge::Right => point.x <= rect.x_max(),
+            Edge::Bottom => point.y >= rect.y_min(),
+            Edge::Top => point.y <= rect.y_max(),
+        }
+    }
+
+    fn intersection(&self, rect: Rect, from: Point3d, to: Point3d) -> Point3d {
+        let t = match self {
+            Edge::Left => (rect.x_min() - from.x) / (to.x - from.x),
+            Edge::Right => (rect.x_max() - from.x) / (to.x - from.x),
+            Edge::Bottom => (rect.y_min() - from.y) / (to.y - from.y),
+            Edge::Top => (rect.y_max() - from.y) / (to.y - from.y),
+        };
+
+        Point3d::new(
+            from.x + (to.x - from.x) * t,
+            from.y + (to.y - from.y) * t,
+            from.z + (to.z - from.z) * t,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(f64, f64)]) -> impl Iterator<Item = Point3d> + '_ {
+        coords.iter().map(|&(x, y)| Point3d::new(x, y, 0.0))
+    }
+
+    #[test]
+    fn line_is_split_into_parts_inside_rect() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let mut out = vec![];
+        let mut parts = vec![];
+
+        clip_line(
+            points(&[
+                (-5.0, 5.0),
+                (5.0, 5.0),
+                (5.0, 15.0),
+                (8.0, 15.0),
+                (8.0, 5.0),
+            ]),
+            rect,
+            &mut out,
+            &mut parts,
+        );
+
+        assert_eq!(parts, vec![0..3, 3..5]);
+        assert_eq!(out[0], Point3d::new(0.0, 5.0, 0.0));
+        assert_eq!(out[2], Point3d::new(5.0, 10.0, 0.0));
+        assert_eq!(out[3], Point3d::new(8.0, 10.0, 0.0));
+        assert_eq!(out[4], Point3d::new(8.0, 5.0, 0.0));
+
+        clip_line(
+            points(&[(-5.0, 5.0), (-5.0, 15.0)]),
+            rect,
+            &mut out,
+            &mut parts,
+        );
+        assert!(parts.is_empty());
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn contour_is_clipped_to_rect() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let mut out = vec![];
+        let mut scratch = vec![];
+
+        clip_contour(
+            points(&[(-5.0, -5.0), (-5.0, 5.0), (5.0, 5.0), (5.0, -5.0)]),
+            rect,
+            &mut out,
+            &mut scratch,
+        );
+        assert_eq!(out.len(), 4);
+        assert!(out.iter().all(|p| rect_contains(rect, p)));
+        assert!(out.contains(&Point3d::new(5.0, 5.0, 0.0)));
+        assert!(out.contains(&Point3d::new(0.0, 0.0, 0.0)));
+
+        clip_contour(
+            points(&[(20.0, 20.0), (20.0, 30.0), (30.0, 30.0)]),
+            rect,
+            &mut out,
+            &mut scratch,
+        );
+        assert!(out.is_empty());
+    }
+}
@@ -0,0 +1,535 @@
+//! On-the-fly slicing of large feature sets into vector tiles.
+
+use crate::error::GalileoError;
+use crate::layer::data_provider::TileGenerator;
+use crate::layer::feature_layer::simplify::{simplify_closed, simplify_points};
+use crate::layer::vector_tile_layer::tile_provider::clip::{clip_contour, clip_line};
+use crate::tile_scheme::TileIndex;
+use galileo_mvt::{MvtFeature, MvtGeometry, MvtLayer, MvtTile, MvtValue};
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Point3d, Rect};
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use galileo_types::{Contour as _, MultiContour as _, MultiPoint as _, MultiPolygon as _};
+use nalgebra::Point2;
+use quick_cache::sync::Cache;
+use std::collections::{BTreeSet, HashMap};
+
+/// Extent of the generated tiles, as written into [`MvtLayer::size`].
+const TILE_EXTENT: u32 = 4096;
+/// Features that would be put into more cells of the index are stored in a separate list instead.
+const MAX_CELLS_PER_FEATURE: usize = 64;
+
+/// Feature to be sliced into tiles by a [`FeatureTiler`].
+pub struct TiledFeature {
+    /// Identifier of the feature, used to highlight the feature in a [`VectorTileLayer`](crate::layer::VectorTileLayer).
+    pub id: Option<u64>,
+    /// Geometry of the feature in the CRS of the tile schema.
+    pub geometry: Geom<Point2d>,
+    /// Properties of the feature used by the style rules.
+    pub properties: HashMap<String, MvtValue>,
+}
+
+impl TiledFeature {
+    /// Creates a new feature without id and properties.
+    pub fn new(geometry: Geom<Point2d>) -> Self {
+        Self {
+            id: None,
+            geometry,
+            properties: HashMap::new(),
+        }
+    }
+
+    /// Sets the id of the feature.
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Adds a property to the feature.
+    pub fn with_property(mut self, key: impl Into<String>, value: MvtValue) -> Self {
+        self.properties.insert(key.into(), value);
+        self
+    }
+}
+
+/// Tile generator that slices a large set of features into vector tiles on the fly, like `geojson-vt` does.
+///
+/// Displaying hundreds of thousands of features with a [`FeatureLayer`](crate::layer::FeatureLayer) is slow, because
+/// every feature is tessellated at full detail. The tiler instead prepares only the tiles that are displayed: the
+/// features are clipped to the tile, simplified with the tolerance matching the resolution of the tile, and features
+/// too small to be visible at the tile resolution are dropped. The tiles are then rendered by a
+/// [`VectorTileLayer`](crate::layer::VectorTileLayer) with a [style](crate::layer::vector_tile_layer::style), and the
+/// generated tiles are cached, so panning over the already visited area does not slice the features again.
+///
+/// All the features are put into a single layer of the tile with the name given to the tiler.
+///
+/// ```no_run
+/// use galileo::layer::data_provider::ProceduralTileProvider;
+/// use galileo::layer::vector_tile_layer::tile_provider::{FeatureTiler, ThreadedProvider, TiledFeature};
+/// use galileo::render::render_bundle::RenderBundle;
+/// use galileo::tile_scheme::TileSchema;
+/// use galileo_types::cartesian::Point2d;
+/// use galileo_types::geometry::Geom;
+///
+/// # fn run(empty_bundle: RenderBundle) {
+/// let features = (0..1_000_000).map(|i| {
+///     let point = Point2d::new((i % 1000) as f64 * 1000.0, (i / 1000) as f64 * 1000.0);
+///     TiledFeature::new(Geom::Point(point)).with_id(i)
+/// });
+///
+/// let tile_schema = TileSchema::web(18);
+/// let tiler = FeatureTiler::new("points", features);
+/// let provider = ThreadedProvider::new(
+///     None,
+///     tile_schema.clone(),
+///     ProceduralTileProvider::new(tiler, tile_schema),
+///     empty_bundle,
+/// );
+/// # }
+/// ```
+pub struct FeatureTiler {
+    layer_name: String,
+    features: Vec<SourceFeature>,
+    index: GridIndex,
+    property_names: Vec<String>,
+    tile_size: u32,
+    tolerance: f64,
+    buffer: f64,
+    cache: Cache<TileIndex, MvtTile>,
+}
+
+impl FeatureTiler {
+    const DEFAULT_TILE_SIZE: u32 = 256;
+    const DEFAULT_TOLERANCE: f64 = 0.5;
+    const DEFAULT_BUFFER: f64 = 16.0;
+    const DEFAULT_CACHE_CAPACITY: usize = 512;
+
+    /// Creates a new tiler with the given features. The features are put into the tile layer with the name
+    /// `layer_name`.
+    ///
+    /// Features without points are ignored.
+    pub fn new(
+        layer_name: impl Into<String>,
+        features: impl IntoIterator<Item = TiledFeature>,
+    ) -> Self {
+        let features: Vec<SourceFeature> = features
+            .into_iter()
+            .filter_map(SourceFeature::new)
+            .collect();
+        let property_names = features
+            .iter()
+            .flat_map(|feature| feature.properties.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let index = GridIndex::new(&features);
+
+        Self {
+            layer_name: layer_name.into(),
+            features,
+            index,
+            property_names,
+            tile_size: Self::DEFAULT_TILE_SIZE,
+            tolerance: Self::DEFAULT_TOLERANCE,
+            buffer: Self::DEFAULT_BUFFER,
+            cache: Cache::new(Self::DEFAULT_CACHE_CAPACITY),
+        }
+    }
+
+    /// Sets the size of the tiles in pixels. It must be the same as the tile size of the tile schema of the layer.
+    /// Default is 256.
+    pub fn with_tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size.max(1);
+        self
+    }
+
+    /// Sets the maximum deviation of the simplified geometries from the original ones, in pixels. Default is 0.5.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.max(0.0);
+        self
+    }
+
+    /// Sets the width of the area around the tile, in pixels, that is included into the tile. It should not be less
+    /// than the clipping buffer of the style of the layer. Default is 16.
+    pub fn with_buffer(mut self, buffer: f64) -> Self {
+        self.buffer = buffer.max(0.0);
+        self
+    }
+
+    /// Sets the maximum number of the generated tiles kept in the cache. Default is 512.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache = Cache::new(capacity.max(1));
+        self
+    }
+
+    /// Number of features in the tiler.
+    pub fn feature_count(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Removes all the generated tiles from the cache.
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// Slices the features into the tile covering the `bbox`.
+    pub fn slice(&self, bbox: Rect) -> MvtTile {
+        let pixel_size = bbox.width() / self.tile_size as f64;
+        let tolerance = self.tolerance * pixel_size;
+        let area = bbox.shrink(-self.buffer * pixel_size);
+        let to_tile = |p: &Point3d| {
+            Point2::new(
+                ((p.x - bbox.x_min()) / bbox.width()) as f32,
+                ((bbox.y_max() - p.y) / bbox.height()) as f32,
+            )
+        };
+
+        let mut scratch = Scratch::default();
+        let mut features = vec![];
+        for feature_index in self.index.query(area) {
+            let feature = &self.features[feature_index];
+            if !feature.bbox.intersects(area) {
+                continue;
+            }
+
+            let is_point = matches!(feature.geometry, SourceGeometry::Points(_));
+            if !is_point && feature.bbox.width() < tolerance && feature.bbox.height() < tolerance {
+                continue;
+            }
+
+            let Some(geometry) = feature
+                .geometry
+                .slice(area, tolerance, &to_tile, &mut scratch)
+            else {
+                continue;
+            };
+
+            features.push(MvtFeature {
+                id: feature.id,
+                properties: feature.properties.clone(),
+                geometry,
+            });
+        }
+
+        MvtTile {
+            layers: vec![MvtLayer {
+                name: self.layer_name.clone(),
+                features,
+                properties: self.property_names.clone(),
+                size: TILE_EXTENT,
+            }],
+        }
+    }
+}
+
+impl TileGenerator for FeatureTiler {
+    type Tile = MvtTile;
+
+    fn generate(&self, index: TileIndex, bbox: Rect) -> Result<MvtTile, GalileoError> {
+        if let Some(tile) = self.cache.get(&index) {
+            return Ok(tile);
+        }
+
+        let tile = self.slice(bbox);
+        self.cache.insert(index, tile.clone());
+        Ok(tile)
+    }
+}
+
+struct SourceFeature {
+    id: Option<u64>,
+    properties: HashMap<String, MvtValue>,
+    geometry: SourceGeometry,
+    bbox: Rect,
+}
+
+impl SourceFeature {
+    fn new(feature: TiledFeature) -> Option<Self> {
+        let geometry = SourceGeometry::new(&feature.geometry);
+        let bbox = geometry
+            .points()
+            .map(|p| Rect::new(p.x, p.y, p.x, p.y))
+            .reduce(|a, b| a.merge(b))?;
+
+        Some(Self {
+            id: feature.id,
+            properties: feature.properties,
+            geometry,
+            bbox,
+        })
+    }
+}
+
+/// Geometry of a feature as plain lists of points. Rings of polygons do not repeat the first point at the end.
+enum SourceGeometry {
+    Points(Vec<Point3d>),
+    Lines(Vec<Vec<Point3d>>),
+    Polygons(Vec<Vec<Vec<Point3d>>>),
+}
+
+impl SourceGeometry {
+    fn new(geometry: &Geom<Point2d>) -> Self {
+        let point = |p: &Point2d| Point3d::new(p.x(), p.y(), 0.0);
+        let line = |contour: &Contour<Point2d>| {
+            let mut points: Vec<Point3d> = contour.iter_points().map(point).collect();
+            if contour.is_closed() {
+                if let Some(first) = points.first().copied() {
+                    points.push(first);
+                }
+            }
+            points
+        };
+        let polygon = |polygon: &Polygon<Point2d>| {
+            std::iter::once(&polygon.outer_contour)
+                .chain(&polygon.inner_contours)
+                .map(|ring| ring.points.iter().map(point).collect())
+                .collect()
+        };
+
+        match geometry {
+            Geom::Point(p) => Self::Points(vec![point(p)]),
+            Geom::MultiPoint(points) => Self::Points(points.iter_points().map(point).collect()),
+            Geom::Contour(contour) => Self::Lines(vec![line(contour)]),
+            Geom::MultiContour(contours) => Self::Lines(contours.contours().map(line).collect()),
+            Geom::Polygon(p) => Self::Polygons(vec![polygon(p)]),
+            Geom::MultiPolygon(polygons) => {
+                Self::Polygons(polygons.polygons().map(polygon).collect())
+            }
+        }
+    }
+
+    fn points(&self) -> Box<dyn Iterator<Item = &Point3d> + '_> {
+        match self {
+            Self::Points(points) => Box::new(points.iter()),
+            Self::Lines(lines) => Box::new(lines.iter().flatten()),
+            Self::Polygons(polygons) => Box::new(polygons.iter().flatten().flatten()),
+        }
+    }
+
+    /// Clips the geometry to the `area` and simplifies it. Returns `None` if nothing is left.
+    fn slice(
+        &self,
+        area: Rect,
+        tolerance: f64,
+        to_tile: &impl Fn(&Point3d) -> Point2<f32>,
+        scratch: &mut Scratch,
+    ) -> Option<MvtGeometry> {
+        match self {
+            Self::Points(points) => {
+                let points: Vec<_> = points
+                    .iter()
+                    .filter(|p| area.contains(&Point2d::new(p.x, p.y)))
+                    .map(to_tile)
+                    .collect();
+                (!points.is_empty()).then_some(MvtGeometry::Point(points))
+            }
+            Self::Lines(lines) => {
+                let mut contours = vec![];
+                for line in lines {
+                    clip_line(
+                        line.iter().copied(),
+                        area,
+                        &mut scratch.clipped,
+                        &mut scratch.parts,
+                    );
+                    for part in &scratch.parts {
+                        let simplified = simplify_points(&scratch.clipped[part.clone()], tolerance);
+                        contours.push(Contour::open(simplified.iter().map(to_tile).collect()));
+                    }
+                }
+
+                (!contours.is_empty()).then_some(MvtGeometry::LineString(contours))
+            }
+            Self::Polygons(polygons) => {
+                let mut result = vec![];
+                'polygons: for rings in polygons {
+                    let mut contours = vec![];
+                    for (ring_index, ring) in rings.iter().enumerate() {
+                        clip_contour(
+                            ring.iter().copied(),
+                            area,
+                            &mut scratch.clipped,
+                            &mut scratch.clip_buffer,
+                        );
+
+                        let simplified = if scratch.clipped.len() < 3 {
+                            None
+                        } else {
+                            simplify_closed(&scratch.clipped, tolerance)
+                        };
+
+                        match simplified {
+                            Some(points) => contours
+                                .push(ClosedContour::new(points.iter().map(to_tile).collect())),
+                            // Outer ring is outside the area or degenerated, so is the whole polygon.
+                            None if ring_index == 0 => continue 'polygons,
+                            None => {}
+                        }
+                    }
+
+                    let mut contours = contours.into_iter();
+                    if let Some(outer) = contours.next() {
+                        result.push(Polygon::new(outer, contours.collect()));
+                    }
+                }
+
+                (!result.is_empty()).then_some(MvtGeometry::Polygon(result))
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Scratch {
+    clipped: Vec<Point3d>,
+    clip_buffer: Vec<Point3d>,
+    parts: Vec<std::ops::Range<usize>>,
+}
+
+/// Uniform grid over the bounding box of all features, with the indices of the features intersecting each cell.
+struct GridIndex {
+    bounds: Rect,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    /// Features covering too many cells, which are checked on every query.
+    large: Vec<usize>,
+    feature_count: usize,
+}
+
+impl GridIndex {
+    fn new(features: &[SourceFeature]) -> Self {
+        let bounds = features
+            .iter()
+            .map(|f| f.bbox)
+            .reduce(|a, b| a.merge(b))
+            .unwrap_or_else(|| Rect::new(0.0, 0.0, 0.0, 0.0));
+        let side = ((features.len() as f64 / 16.0).sqrt().ceil() as usize).clamp(1, 1024);
+
+        let mut index = Self {
+            bounds,
+            columns: side,
+            rows: side,
+            cells: vec![vec![]; side * side],
+            large: vec![],
+            feature_count: features.len(),
+        };
+
+        for (feature_index, feature) in features.iter().enumerate() {
+            let (columns, rows) = index.cell_range(feature.bbox);
+            if columns.len() * rows.len() > MAX_CELLS_PER_FEATURE {
+                index.large.push(feature_index);
+                continue;
+            }
+
+            for row in rows {
+                for column in columns.clone() {
+                    index.cells[row * index.columns + column].push(feature_index);
+                }
+            }
+        }
+
+        index
+    }
+
+    fn cell_range(&self, bbox: Rect) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let cell = |value: f64, min: f64, size: f64, count: usize| {
+            if size <= 0.0 {
+                return 0;
+            }
+            (((value - min) / size * count as f64).floor().max(0.0) as usize).min(count - 1)
+        };
+
+        let width = self.bounds.width();
+        let height = self.bounds.height();
+        let x_min = cell(bbox.x_min(), self.bounds.x_min(), width, self.columns);
+        let x_max = cell(bbox.x_max(), self.bounds.x_min(), width, self.columns);
+        let y_min = cell(bbox.y_min(), self.bounds.y_min(), height, self.rows);
+        let y_max = cell(bbox.y_max(), self.bounds.y_min(), height, self.rows);
+
+        (x_min..x_max + 1, y_min..y_max + 1)
+    }
+
+    /// Indices of the features that may intersect the `area`, sorted and without duplicates.
+    fn query(&self, area: Rect) -> Vec<usize> {
+        if self.feature_count == 0 || !self.bounds.intersects(area) {
+            return vec![];
+        }
+
+        let (columns, rows) = self.cell_range(area);
+        if columns.len() == self.columns && rows.len() == self.rows {
+            return (0..self.feature_count).collect();
+        }
+
+        let mut result = self.large.clone();
+        for row in rows {
+            for column in columns.clone() {
+                result.extend_from_slice(&self.cells[row * self.columns + column]);
+            }
+        }
+
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f64, y: f64, size: f64) -> Geom<Point2d> {
+        Geom::Polygon(Polygon::new(
+            ClosedContour::new(vec![
+                Point2d::new(x, y),
+                Point2d::new(x + size, y),
+                Point2d::new(x + size, y + size),
+                Point2d::new(x, y + size),
+            ]),
+            vec![],
+        ))
+    }
+
+    #[test]
+    fn features_are_clipped_to_tile() {
+        let tiler = FeatureTiler::new(
+            "test",
+            [
+                TiledFeature::new(square(-50.0, -50.0, 100.0)).with_id(1),
+                TiledFeature::new(Geom::Point(Point2d::new(75.0, 25.0))).with_id(2),
+                TiledFeature::new(Geom::Point(Point2d::new(500.0, 500.0))).with_id(3),
+            ],
+        )
+        .with_buffer(0.0);
+
+        let tile = tiler.slice(Rect::new(0.0, 0.0, 100.0, 100.0));
+        let features = &tile.layers[0].features;
+        assert_eq!(features.len(), 2);
+
+        let MvtGeometry::Polygon(polygons) = &features[0].geometry else {
+            panic!("not a polygon");
+        };
+        for point in &polygons[0].outer_contour.points {
+            assert!((0.0..=1.0).contains(&point.x) && (0.0..=1.0).contains(&point.y));
+        }
+
+        let MvtGeometry::Point(points) = &features[1].geometry else {
+            panic!("not a point");
+        };
+        assert_eq!(points[0], Point2::new(0.75, 0.75));
+    }
+
+    #[test]
+    fn small_features_are_dropped_at_low_resolution() {
+        let features = (0..100).map(|i| TiledFeature::new(square(i as f64 * 10.0, 0.0, 1.0)));
+        let tiler = FeatureTiler::new("test", features).with_tile_size(100);
+
+        // 10 units per pixel: the squares are smaller than the tolerance.
+        let tile = tiler.slice(Rect::new(0.0, 0.0, 1000.0, 1000.0));
+        assert!(tile.layers[0].features.is_empty());
+
+        // 0.1 unit per pixel: only the squares inside the tile and its 1.6 units buffer are included.
+        let tile = tiler.slice(Rect::new(0.0, 0.0, 10.0, 10.0));
+        assert_eq!(tile.layers[0].features.len(), 2);
+    }
+}
@@ -1,15 +1,16 @@
 //! Vector tile layer tile providers
 
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
-use crate::layer::vector_tile_layer::vector_tile::VectorTile;
+use crate::layer::vector_tile_layer::vector_tile::{FeatureHighlight, VectorTile};
+use crate::layer::TileLoadMonitor;
 use crate::messenger::Messenger;
 use crate::render::render_bundle::RenderBundle;
-use crate::render::Canvas;
+use crate::render::{Canvas, GpuMemoryBudget, MemoryTracker, TrackedUnsyncCache};
 use crate::tile_scheme::TileIndex;
 use galileo_mvt::MvtTile;
 use maybe_sync::{MaybeSend, MaybeSync};
-use quick_cache::unsync::Cache;
 use std::sync::MutexGuard;
+use web_time::Instant;
 
 #[cfg(target_arch = "wasm32")]
 mod web_worker_provider;
@@ -21,8 +22,11 @@ mod threaded_provider;
 #[cfg(not(target_arch = "wasm32"))]
 pub use threaded_provider::ThreadedProvider;
 
+mod clip;
+mod feature_tiler;
+pub use feature_tiler::{FeatureTiler, TiledFeature};
 mod vt_processor;
-pub use vt_processor::{VectorTileDecodeContext, VtProcessor};
+pub use vt_processor::{FeaturePrimitives, VectorTileDecodeContext, VtProcessor};
 
 /// Vector tile provider.
 pub trait VectorTileProvider: MaybeSend + MaybeSync {
@@ -34,15 +38,34 @@ pub trait VectorTileProvider: MaybeSend + MaybeSync {
     fn read(&self) -> LockedTileStore;
     /// Set a messenger to notify the application when a new tile is loaded.
     fn set_messenger(&self, messenger: Box<dyn Messenger>);
+    /// Sets the GPU memory budget for the packed tiles of the provider.
+    fn set_memory_budget(&self, budget: &GpuMemoryBudget);
+    /// Approximate size in bytes of GPU memory used by the packed tiles of the provider.
+    fn gpu_memory_usage(&self) -> usize;
+    /// Monitor reporting the progress and errors of loading the tiles.
+    fn load_monitor(&self) -> &TileLoadMonitor;
+}
+
+/// Maximum number of tiles in the store of a provider.
+const TILE_STORE_CAPACITY: usize = 1000;
+
+/// Store of the tiles of a provider. Packed tiles evicted by the capacity of the store stop being tracked by the
+/// memory tracker of the provider.
+type TileStore = TrackedUnsyncCache<TileIndex, TileState>;
+
+fn new_tile_store(memory: &MemoryTracker<TileIndex>) -> TileStore {
+    memory.unsync_cache(TILE_STORE_CAPACITY)
 }
 
 /// Lock of the tile store. Only one lock can be held at a time.
 pub struct LockedTileStore<'a> {
-    guard: MutexGuard<'a, Cache<TileIndex, TileState>>,
+    guard: MutexGuard<'a, TileStore>,
+    memory: &'a MemoryTracker<TileIndex>,
 }
 
 impl<'a> LockedTileStore<'a> {
-    /// Returns a raw MVT tile by the index.
+    /// Returns a raw MVT tile by the index. The tile contains only the layers used by the style the tile was loaded
+    /// with.
     ///
     /// Returns `None` if the tile with the given index is not in the store.
     pub fn get_mvt_tile(&'a self, index: TileIndex) -> Option<&'a MvtTile> {
@@ -58,19 +81,29 @@ impl<'a> LockedTileStore<'a> {
     /// Packs the tile with the given index using the `canvas`.
     ///
     /// If tile does not exist, does nothing.
-    pub fn pack(&mut self, index: TileIndex, canvas: &dyn Canvas) {
+    pub fn pack(&mut self, index: TileIndex, pack_generation: usize, canvas: &dyn Canvas) {
         if self.needs_packing(&index) {
             let tile_state = self.guard.remove(&index);
             match tile_state {
                 Some((_, TileState::Loaded(tile))) => {
-                    let UnpackedVectorTile { bundle, mvt_tile } = *tile;
-                    let packed = canvas.pack_bundle(&bundle);
+                    let UnpackedVectorTile {
+                        bundle,
+                        mvt_tile,
+                        feature_primitives,
+                    } = *tile;
+                    let size = bundle.approx_buffer_size();
+                    // The tile is tracked before it is inserted, so that it is untracked if the store evicts it
+                    // right away.
+                    self.memory.insert(index, size);
                     self.guard.insert(
                         index,
-                        TileState::Packed(VectorTile {
+                        TileState::Packed(VectorTile::new(
                             mvt_tile,
-                            bundle: packed,
-                        }),
+                            bundle,
+                            feature_primitives,
+                            pack_generation,
+                            canvas,
+                        )),
                     );
                 }
                 _ => {
@@ -82,6 +115,7 @@ impl<'a> LockedTileStore<'a> {
 
     /// Returns a tile with the given index, if the tile was loaded and packed.
     pub fn get_tile(&'a self, index: TileIndex) -> Option<&'a VectorTile> {
+        self.memory.touch(&index);
         self.guard.get(&index).and_then(|v| match v {
             TileState::Packed(tile) | TileState::Outdated(tile) | TileState::Updating(tile) => {
                 Some(tile)
@@ -90,6 +124,34 @@ impl<'a> LockedTileStore<'a> {
         })
     }
 
+    /// Applies the feature highlight to the tile with the given index, if the tile is packed.
+    pub(crate) fn update_highlight(
+        &mut self,
+        index: TileIndex,
+        highlight: &FeatureHighlight,
+        canvas: &dyn Canvas,
+    ) {
+        if let Some(mut tile_state) = self.guard.get_mut(&index) {
+            if let TileState::Packed(tile) | TileState::Outdated(tile) | TileState::Updating(tile) =
+                &mut *tile_state
+            {
+                tile.update_highlight(highlight, canvas);
+            }
+        }
+    }
+
+    /// Removes the tiles evicted from the store by the GPU memory budget.
+    pub(crate) fn remove_evicted(&mut self) {
+        for index in self.memory.take_evicted() {
+            self.guard.remove(&index);
+        }
+    }
+
+    /// Returns true if the tile with the given index failed to load.
+    pub(crate) fn is_failed(&self, index: TileIndex) -> bool {
+        matches!(self.guard.peek(&index), Some(TileState::Error(_)))
+    }
+
     fn needs_packing(&self, index: &TileIndex) -> bool {
         self.guard
             .get(index)
@@ -100,6 +162,7 @@ impl<'a> LockedTileStore<'a> {
 struct UnpackedVectorTile {
     mvt_tile: MvtTile,
     bundle: RenderBundle,
+    feature_primitives: FeaturePrimitives,
 }
 
 enum TileState {
@@ -108,5 +171,6 @@ enum TileState {
     Outdated(VectorTile),
     Updating(VectorTile),
     Packed(VectorTile),
-    Error,
+    /// Tile failed to load and should not be requested again until the given time.
+    Error(Instant),
 }
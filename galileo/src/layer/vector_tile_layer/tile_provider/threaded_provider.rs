@@ -1,23 +1,27 @@
 use crate::error::GalileoError;
 use crate::layer::data_provider::DataProvider;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
-use crate::layer::vector_tile_layer::tile_provider::vt_processor::VectorTileDecodeContext;
+use crate::layer::vector_tile_layer::tile_provider::vt_processor::{
+    FeaturePrimitives, VectorTileDecodeContext,
+};
 use crate::layer::vector_tile_layer::tile_provider::{
-    LockedTileStore, TileState, UnpackedVectorTile, VectorTileProvider,
+    new_tile_store, LockedTileStore, TileState, TileStore, UnpackedVectorTile, VectorTileProvider,
 };
+use crate::layer::{RetryPolicy, TileLoadMonitor};
 use crate::messenger::Messenger;
 use crate::render::render_bundle::RenderBundle;
+use crate::render::{GpuMemoryBudget, MemoryTracker};
 use crate::tile_scheme::{TileIndex, TileSchema};
 use bytes::Bytes;
 use galileo_mvt::MvtTile;
 use maybe_sync::{MaybeSend, MaybeSync};
-use quick_cache::unsync::Cache;
 use std::sync::{Arc, Mutex, RwLock};
+use web_time::Instant;
 
 /// Provider that uses background threads to load, decode and pack vector tiles.
 pub struct ThreadedProvider<Provider>
 where
-    Provider: DataProvider<TileIndex, (RenderBundle, MvtTile), VectorTileDecodeContext>
+    Provider: DataProvider<TileIndex, (RenderBundle, MvtTile, FeaturePrimitives), VectorTileDecodeContext>
         + MaybeSend
         + MaybeSync
         + 'static,
@@ -25,13 +29,16 @@ where
     messenger: Arc<RwLock<Option<Box<dyn Messenger>>>>,
     tile_schema: TileSchema,
     data_provider: Arc<Provider>,
-    tiles: Arc<Mutex<Cache<TileIndex, TileState>>>,
+    tiles: Arc<Mutex<TileStore>>,
     empty_bundle: RenderBundle,
+    memory: MemoryTracker<TileIndex>,
+    load_monitor: TileLoadMonitor,
+    retry_policy: RetryPolicy,
 }
 
 impl<Provider> Clone for ThreadedProvider<Provider>
 where
-    Provider: DataProvider<TileIndex, (RenderBundle, MvtTile), VectorTileDecodeContext>
+    Provider: DataProvider<TileIndex, (RenderBundle, MvtTile, FeaturePrimitives), VectorTileDecodeContext>
         + MaybeSend
         + MaybeSync
         + 'static,
@@ -43,13 +50,16 @@ where
             data_provider: self.data_provider.clone(),
             tiles: self.tiles.clone(),
             empty_bundle: self.empty_bundle.clone(),
+            memory: self.memory.clone(),
+            load_monitor: self.load_monitor.clone(),
+            retry_policy: self.retry_policy,
         }
     }
 }
 
 impl<Provider> VectorTileProvider for ThreadedProvider<Provider>
 where
-    Provider: DataProvider<TileIndex, (RenderBundle, MvtTile), VectorTileDecodeContext>
+    Provider: DataProvider<TileIndex, (RenderBundle, MvtTile, FeaturePrimitives), VectorTileDecodeContext>
         + MaybeSend
         + MaybeSync
         + 'static,
@@ -69,8 +79,8 @@ where
                 continue;
             };
             let tile_state = &mut *entry;
-            if matches!(*tile_state, TileState::Loaded(_)) {
-                let TileState::Packed(tile) = std::mem::replace(tile_state, TileState::Error)
+            if matches!(*tile_state, TileState::Packed(_)) {
+                let TileState::Packed(tile) = std::mem::replace(tile_state, TileState::Loading)
                 else {
                     log::error!("Type of value changed unexpectedly during updating style.");
                     continue;
@@ -88,17 +98,30 @@ where
     fn read(&self) -> LockedTileStore {
         LockedTileStore {
             guard: self.tiles.lock().expect("tile store mutex is poisoned"),
+            memory: &self.memory,
         }
     }
 
     fn set_messenger(&self, messenger: Box<dyn Messenger>) {
         *self.messenger.write().expect("lock is poisoned") = Some(messenger)
     }
+
+    fn set_memory_budget(&self, budget: &GpuMemoryBudget) {
+        self.memory.set_budget(budget);
+    }
+
+    fn gpu_memory_usage(&self) -> usize {
+        self.memory.used()
+    }
+
+    fn load_monitor(&self) -> &TileLoadMonitor {
+        &self.load_monitor
+    }
 }
 
 impl<Provider> ThreadedProvider<Provider>
 where
-    Provider: DataProvider<TileIndex, (RenderBundle, MvtTile), VectorTileDecodeContext>
+    Provider: DataProvider<TileIndex, (RenderBundle, MvtTile, FeaturePrimitives), VectorTileDecodeContext>
         + MaybeSend
         + MaybeSync
         + 'static,
@@ -110,17 +133,32 @@ where
         data_provider: Provider,
         empty_bundle: RenderBundle,
     ) -> Self {
+        let memory = MemoryTracker::new(&GpuMemoryBudget::unlimited());
         Self {
             messenger: Arc::new(RwLock::new(messenger)),
             tile_schema: tile_scheme,
             data_provider: Arc::new(data_provider),
-            tiles: Arc::new(Mutex::new(Cache::new(1000))),
+            tiles: Arc::new(Mutex::new(new_tile_store(&memory))),
             empty_bundle,
+            memory,
+            load_monitor: TileLoadMonitor::new(),
+            retry_policy: RetryPolicy::default(),
         }
     }
 
+    /// Sets the policy of retrying to load the tiles that failed to load.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     fn set_loading_state(&self, index: TileIndex) -> bool {
         let mut tiles = self.tiles.lock().expect("tile store mutex is poisoned");
+        if matches!(tiles.peek(&index), Some(TileState::Error(retry_at)) if *retry_at <= Instant::now())
+        {
+            tiles.remove(&index);
+        }
+
         let has_entry = tiles.peek(&index).is_some();
         if has_entry {
             if let Some(mut entry) = tiles.get_mut(&index) {
@@ -129,7 +167,7 @@ where
                     return false;
                 }
 
-                let TileState::Outdated(tile) = std::mem::replace(value, TileState::Error) else {
+                let TileState::Outdated(tile) = std::mem::replace(value, TileState::Loading) else {
                     log::error!("Type of value changed unexpectedly during loading.");
                     return false;
                 };
@@ -146,11 +184,16 @@ where
     fn load_tile_internal(&self, index: TileIndex, style: &VectorTileStyle) {
         let provider: ThreadedProvider<Provider> = (*self).clone();
         let style = style.clone();
+        self.load_monitor.requested(index);
         crate::async_runtime::spawn(async move {
             match provider.clone().load_tile_async(index, style).await {
                 Ok(tile) => {
                     let mut tiles = provider.tiles.lock().expect("tile store mutex is poisoned");
                     tiles.insert(index, TileState::Loaded(Box::new(tile)));
+                    // The packed tile this one replaces, if it was reloaded, is dropped.
+                    provider.memory.remove(&index);
+                    drop(tiles);
+                    provider.load_monitor.loaded(index);
                     if let Some(messenger) = &*provider
                         .messenger
                         .read()
@@ -162,7 +205,11 @@ where
                 Err(err) => {
                     log::info!("Failed to load tile: {err:?}");
                     let mut tiles = provider.tiles.lock().expect("tile store mutex is poisoned");
-                    tiles.insert(index, TileState::Error);
+                    let retry_at = Instant::now() + provider.retry_policy.failed_tile_ttl(&err);
+                    tiles.insert(index, TileState::Error(retry_at));
+                    provider.memory.remove(&index);
+                    drop(tiles);
+                    provider.load_monitor.failed(index, err);
                 }
             }
         });
@@ -174,7 +221,7 @@ where
         style: VectorTileStyle,
     ) -> Result<UnpackedVectorTile, GalileoError> {
         let bytes = self.download_tile(index).await?;
-        tokio::task::spawn_blocking(move || self.try_prepare_tile(bytes, index, &style))
+        crate::async_runtime::spawn_blocking(move || self.try_prepare_tile(bytes, index, &style))
             .await
             .unwrap_or_else(|err| {
                 Err(GalileoError::Generic(format!(
@@ -196,12 +243,27 @@ where
             tile_schema: self.tile_schema.clone(),
             bundle,
         };
-        let (bundle, mvt_tile) = self.data_provider.decode(bytes, context)?;
+        let (bundle, mvt_tile, feature_primitives) = self.data_provider.decode(bytes, context)?;
 
-        Ok(UnpackedVectorTile { bundle, mvt_tile })
+        Ok(UnpackedVectorTile {
+            bundle,
+            mvt_tile,
+            feature_primitives,
+        })
     }
 
     async fn download_tile(&self, index: TileIndex) -> Result<Bytes, GalileoError> {
-        self.data_provider.load_raw(&index).await
+        let mut attempt = 1;
+        loop {
+            match self.data_provider.load_raw(&index).await {
+                Err(err) if self.retry_policy.should_retry(&err, attempt) => {
+                    log::info!("Failed to load tile {index:?}, retrying: {err:?}");
+                    self.load_monitor.retrying(index, err);
+                    crate::async_runtime::sleep(self.retry_policy.retry_delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
     }
 }
@@ -1,8 +1,9 @@
 use crate::error::GalileoError;
 use crate::layer::data_provider::DataProcessor;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
+use crate::layer::vector_tile_layer::tile_provider::clip::{clip_contour, clip_line};
 use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
-use crate::render::{LineCap, LinePaint, PolygonPaint};
+use crate::render::{LineCap, LinePaint, PolygonPaint, PrimitiveId, SizeUnit};
 use crate::tile_scheme::TileIndex;
 use crate::TileSchema;
 use bytes::Bytes;
@@ -11,6 +12,11 @@ use galileo_types::cartesian::{CartesianPoint2d, Point3d, Rect};
 use galileo_types::impls::{ClosedContour, Polygon};
 use galileo_types::Contour;
 use num_traits::ToPrimitive;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Ids of the render primitives of a tile's features by the feature ids. Features without ids are not included.
+pub type FeaturePrimitives = HashMap<u64, Vec<PrimitiveId>>;
 
 /// Data processor that decodes vector tiles.
 pub struct VtProcessor {}
@@ -29,7 +35,7 @@ pub struct VectorTileDecodeContext {
 
 impl DataProcessor for VtProcessor {
     type Input = Bytes;
-    type Output = (RenderBundle, MvtTile);
+    type Output = (RenderBundle, MvtTile, FeaturePrimitives);
     type Context = VectorTileDecodeContext;
 
     fn process(
@@ -37,27 +43,28 @@ impl DataProcessor for VtProcessor {
         input: Self::Input,
         context: Self::Context,
     ) -> Result<Self::Output, GalileoError> {
-        let mvt_tile = MvtTile::decode(input, false)?;
         let VectorTileDecodeContext {
             mut bundle,
             index,
             style,
             tile_schema: tile_scheme,
         } = context;
-        Self::prepare(&mvt_tile, &mut bundle, index, &style, &tile_scheme)?;
+        let mvt_tile = MvtTile::decode_filtered(input, false, |name| style.uses_layer(name))?;
+        let feature_primitives =
+            Self::prepare(&mvt_tile, &mut bundle, index, &style, &tile_scheme)?;
 
-        Ok((bundle, mvt_tile))
+        Ok((bundle, mvt_tile, feature_primitives))
     }
 }
 
 impl VtProcessor {
-    fn prepare(
+    pub(crate) fn prepare(
         mvt_tile: &MvtTile,
         bundle: &mut RenderBundle,
         index: TileIndex,
         style: &VectorTileStyle,
         tile_scheme: &TileSchema,
-    ) -> Result<(), GalileoError> {
+    ) -> Result<FeaturePrimitives, GalileoError> {
         let bbox = tile_scheme
             .tile_bbox(index)
             .ok_or_else(|| GalileoError::Generic("cannot get tile bbox".into()))?;
@@ -66,12 +73,16 @@ impl VtProcessor {
         })?;
         let tile_resolution = lod_resolution * tile_scheme.tile_width() as f64;
 
+        let draw_area = bbox.shrink(-style.clipping.overlap.max(0.0) * lod_resolution);
+        let cut_area = bbox
+            .shrink(-style.clipping.buffer.max(style.clipping.overlap).max(0.0) * lod_resolution);
+
         let bounds = Polygon::new(
             ClosedContour::new(vec![
-                Point3d::new(bbox.x_min(), bbox.y_min(), 0.0),
-                Point3d::new(bbox.x_min(), bbox.y_max(), 0.0),
-                Point3d::new(bbox.x_max(), bbox.y_max(), 0.0),
-                Point3d::new(bbox.x_max(), bbox.y_min(), 0.0),
+                Point3d::new(draw_area.x_min(), draw_area.y_min(), 0.0),
+                Point3d::new(draw_area.x_min(), draw_area.y_max(), 0.0),
+                Point3d::new(draw_area.x_max(), draw_area.y_max(), 0.0),
+                Point3d::new(draw_area.x_max(), draw_area.y_min(), 0.0),
             ]),
             vec![],
         );
@@ -82,83 +93,142 @@ impl VtProcessor {
                 &bounds,
                 PolygonPaint {
                     color: style.background,
+                    gradient: None,
+                    zoom: None,
                 },
             ),
             lod_resolution,
         );
 
+        let mut scratch = GeometryScratch::default();
+        let mut feature_primitives = FeaturePrimitives::new();
         for layer in &mvt_tile.layers {
             for feature in &layer.features {
+                let mut primitive_ids = vec![];
                 match &feature.geometry {
                     MvtGeometry::Point(_points) => {
                         // todo
                         continue;
                     }
                     MvtGeometry::LineString(contours) => {
-                        if let Some(paint) = Self::get_line_symbol(style, &layer.name, feature) {
+                        if let Some(paint) = Self::get_line_symbol(
+                            style,
+                            &layer.name,
+                            feature,
+                            index,
+                            lod_resolution,
+                        ) {
                             for contour in contours {
-                                bundle.add(
-                                    RenderPrimitive::<_, _, _, Polygon<_>>::new_contour_ref(
-                                        &galileo_types::impls::Contour::new(
-                                            contour
-                                                .iter_points()
-                                                .map(|p| {
-                                                    Self::transform_point(p, bbox, tile_resolution)
-                                                })
-                                                .collect(),
-                                            false,
-                                        ),
-                                        paint,
-                                    ),
-                                    lod_resolution,
+                                clip_line(
+                                    contour
+                                        .iter_points()
+                                        .map(|p| Self::transform_point(p, bbox, tile_resolution)),
+                                    cut_area,
+                                    &mut scratch.clipped,
+                                    &mut scratch.parts,
                                 );
+                                for part in &scratch.parts {
+                                    scratch
+                                        .line
+                                        .set(scratch.clipped[part.clone()].iter().copied());
+                                    primitive_ids.push(bundle.add(
+                                        RenderPrimitive::<_, _, _, Polygon<_>>::new_contour_ref(
+                                            &scratch.line,
+                                            paint,
+                                        ),
+                                        lod_resolution,
+                                    ));
+                                }
                             }
                         }
                     }
                     MvtGeometry::Polygon(polygons) => {
-                        if let Some(paint) = Self::get_polygon_symbol(style, &layer.name, feature) {
+                        if let Some(paint) = Self::get_polygon_symbol(
+                            style,
+                            &layer.name,
+                            feature,
+                            index,
+                            lod_resolution,
+                        ) {
                             for polygon in polygons {
-                                bundle.add(
-                                    RenderPrimitive::<_, _, galileo_types::impls::Contour<_>, _>::new_polygon_ref(
-                                        &polygon.cast_points(|p| {
+                                scratch.polygon.clear();
+                                for contour in std::iter::once(&polygon.outer_contour)
+                                    .chain(&polygon.inner_contours)
+                                {
+                                    clip_contour(
+                                        contour.iter_points().map(|p| {
                                             Self::transform_point(p, bbox, tile_resolution)
                                         }),
+                                        cut_area,
+                                        &mut scratch.clipped,
+                                        &mut scratch.clip_buffer,
+                                    );
+
+                                    if scratch.clipped.is_empty() {
+                                        if scratch.polygon.len == 0 {
+                                            // Outer contour is outside the tile, so is the whole polygon.
+                                            break;
+                                        }
+
+                                        continue;
+                                    }
+
+                                    scratch
+                                        .polygon
+                                        .push_contour(scratch.clipped.iter().copied());
+                                }
+
+                                if scratch.polygon.len == 0 {
+                                    continue;
+                                }
+
+                                primitive_ids.push(bundle.add(
+                                    RenderPrimitive::<_, _, galileo_types::impls::Contour<_>, _>::new_polygon_ref(
+                                        &scratch.polygon,
                                         paint,
                                     ),
                                     lod_resolution,
-                                );
+                                ));
                             }
                         }
                     }
                 }
+
+                if let Some(id) = feature.id {
+                    if !primitive_ids.is_empty() {
+                        feature_primitives
+                            .entry(id)
+                            .or_default()
+                            .extend(primitive_ids);
+                    }
+                }
             }
         }
 
-        Ok(())
+        Ok(feature_primitives)
     }
 
     fn get_line_symbol(
         style: &VectorTileStyle,
         layer_name: &str,
         feature: &MvtFeature,
+        index: TileIndex,
+        lod_resolution: f64,
     ) -> Option<LinePaint> {
-        let Some(rule) = style.get_style_rule(layer_name, feature) else {
-            let symbol = style.default_symbol.line.as_ref()?;
-            return Some(LinePaint {
-                width: symbol.width,
-                color: symbol.stroke_color,
-                offset: 0.0,
-                line_cap: LineCap::Butt,
-            });
+        let symbol = match style.get_style_rule(layer_name, feature) {
+            Some(rule) => rule.symbol.line.as_ref()?,
+            None => style.default_symbol.line.as_ref()?,
         };
 
-        let symbol = rule.symbol.line.as_ref()?;
-
         Some(LinePaint {
             width: symbol.width,
             color: symbol.stroke_color,
             offset: 0.0,
+            width_unit: SizeUnit::Pixels,
             line_cap: LineCap::Butt,
+            pattern: None,
+            gradient: None,
+            zoom: symbol.zoom_interpolation(index.z, lod_resolution),
         })
     }
 
@@ -166,15 +236,18 @@ impl VtProcessor {
         style: &VectorTileStyle,
         layer_name: &str,
         feature: &MvtFeature,
+        index: TileIndex,
+        lod_resolution: f64,
     ) -> Option<PolygonPaint> {
-        let Some(rule) = style.get_style_rule(layer_name, feature) else {
-            return Some(PolygonPaint {
-                color: style.default_symbol.polygon.as_ref()?.fill_color,
-            });
+        let symbol = match style.get_style_rule(layer_name, feature) {
+            Some(rule) => rule.symbol.polygon.as_ref()?,
+            None => style.default_symbol.polygon.as_ref()?,
         };
 
         Some(PolygonPaint {
-            color: rule.symbol.polygon.as_ref()?.fill_color,
+            color: symbol.fill_color,
+            gradient: None,
+            zoom: symbol.zoom_interpolation(index.z, lod_resolution),
         })
     }
 
@@ -188,3 +261,77 @@ impl VtProcessor {
         Point3d::new(x, y, 0.0)
     }
 }
+
+/// Buffers for the transformed geometries, reused for all features of a tile so that adding a feature to the bundle
+/// does not allocate new vectors for its contours.
+#[derive(Default)]
+struct GeometryScratch {
+    line: ScratchContour,
+    polygon: ScratchPolygon,
+    /// Points of the geometry clipped to the tile.
+    clipped: Vec<Point3d>,
+    /// Ranges of the parts of a clipped line in `clipped`.
+    parts: Vec<Range<usize>>,
+    clip_buffer: Vec<Point3d>,
+}
+
+/// Open contour with a reusable points buffer.
+#[derive(Debug, Clone, Default)]
+struct ScratchContour {
+    points: Vec<Point3d>,
+}
+
+impl ScratchContour {
+    fn set(&mut self, points: impl Iterator<Item = Point3d>) {
+        self.points.clear();
+        self.points.extend(points);
+    }
+}
+
+impl Contour for ScratchContour {
+    type Point = Point3d;
+
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    fn iter_points(&self) -> impl Iterator<Item = &'_ Self::Point> {
+        self.points.iter()
+    }
+}
+
+/// Polygon that keeps the buffers of its contours after being cleared. The first contour is the outer one.
+#[derive(Debug, Clone, Default)]
+struct ScratchPolygon {
+    contours: Vec<ClosedContour<Point3d>>,
+    len: usize,
+}
+
+impl ScratchPolygon {
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn push_contour(&mut self, points: impl Iterator<Item = Point3d>) {
+        if self.len == self.contours.len() {
+            self.contours.push(ClosedContour::new(vec![]));
+        }
+
+        let contour = &mut self.contours[self.len];
+        contour.points.clear();
+        contour.points.extend(points);
+        self.len += 1;
+    }
+}
+
+impl galileo_types::Polygon for ScratchPolygon {
+    type Contour = ClosedContour<Point3d>;
+
+    fn outer_contour(&self) -> &Self::Contour {
+        &self.contours[0]
+    }
+
+    fn inner_contours(&self) -> impl Iterator<Item = &'_ Self::Contour> {
+        self.contours[1..self.len].iter()
+    }
+}
@@ -3,24 +3,26 @@ use crate::layer::data_provider::UrlDataProvider;
 use crate::layer::data_provider::{DataProvider, UrlSource};
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::layer::vector_tile_layer::tile_provider::vt_processor::{
-    VectorTileDecodeContext, VtProcessor,
+    FeaturePrimitives, VectorTileDecodeContext, VtProcessor,
 };
 use crate::layer::vector_tile_layer::tile_provider::{
-    LockedTileStore, TileState, UnpackedVectorTile, VectorTileProvider,
+    new_tile_store, LockedTileStore, TileState, TileStore, UnpackedVectorTile, VectorTileProvider,
 };
+use crate::layer::{RetryPolicy, TileLoadMonitor};
 use crate::messenger::Messenger;
 use crate::render::render_bundle::tessellating::serialization::TessellatingRenderBundleBytes;
 use crate::render::render_bundle::tessellating::TessellatingRenderBundle;
 use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+use crate::render::{GpuMemoryBudget, MemoryTracker};
 use crate::tile_scheme::{TileIndex, TileSchema};
 use galileo_mvt::MvtTile;
-use quick_cache::unsync::Cache;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use wasm_bindgen::prelude::*;
+use web_time::{Duration, Instant};
 
 const WORKER_URL: &str = "./vt_worker.js";
 const READY_MESSAGE: f64 = 42.0;
@@ -29,10 +31,13 @@ const READY_MESSAGE: f64 = 42.0;
 pub struct WebWorkerVectorTileProvider {
     worker_pool: Vec<Rc<RefCell<WorkerState>>>,
     next_worker: AtomicUsize,
-    tiles: Arc<Mutex<Cache<TileIndex, TileState>>>,
+    tiles: Arc<Mutex<TileStore>>,
     messenger: Arc<RwLock<Option<Box<dyn Messenger>>>>,
     tile_source: Box<dyn UrlSource<TileIndex>>,
     tile_scheme: TileSchema,
+    memory: MemoryTracker<TileIndex>,
+    load_monitor: TileLoadMonitor,
+    retry_policy: RetryPolicy,
 }
 
 struct WorkerState {
@@ -55,7 +60,7 @@ impl VectorTileProvider for WebWorkerVectorTileProvider {
             };
             let tile_state = &mut *entry;
             if matches!(*tile_state, TileState::Packed(_)) {
-                let TileState::Packed(tile) = std::mem::replace(tile_state, TileState::Error)
+                let TileState::Packed(tile) = std::mem::replace(tile_state, TileState::Loading)
                 else {
                     log::error!("Type of value changed unexpectedly during updating style.");
                     continue;
@@ -72,12 +77,27 @@ impl VectorTileProvider for WebWorkerVectorTileProvider {
 
     fn read(&self) -> LockedTileStore {
         let guard = self.tiles.lock().expect("tile store mutex is poisoned");
-        LockedTileStore { guard }
+        LockedTileStore {
+            guard,
+            memory: &self.memory,
+        }
     }
 
     fn set_messenger(&self, messenger: Box<dyn Messenger>) {
         *self.messenger.write().unwrap() = Some(messenger.into())
     }
+
+    fn set_memory_budget(&self, budget: &GpuMemoryBudget) {
+        self.memory.set_budget(budget);
+    }
+
+    fn gpu_memory_usage(&self) -> usize {
+        self.memory.used()
+    }
+
+    fn load_monitor(&self) -> &TileLoadMonitor {
+        &self.load_monitor
+    }
 }
 
 impl WebWorkerVectorTileProvider {
@@ -88,13 +108,17 @@ impl WebWorkerVectorTileProvider {
         source: impl UrlSource<TileIndex> + 'static,
         tile_scheme: TileSchema,
     ) -> Self {
+        let memory = MemoryTracker::new(&GpuMemoryBudget::unlimited());
         let mut provider = Self {
             worker_pool: Vec::with_capacity(pool_size),
             next_worker: Default::default(),
-            tiles: Arc::new(Mutex::new(Cache::new(1000))),
+            tiles: Arc::new(Mutex::new(new_tile_store(&memory))),
             messenger: Arc::new(RwLock::new(messenger)),
             tile_source: Box::new(source),
             tile_scheme,
+            memory,
+            load_monitor: TileLoadMonitor::new(),
+            retry_policy: RetryPolicy::default(),
         };
 
         for _ in 0..pool_size {
@@ -104,6 +128,13 @@ impl WebWorkerVectorTileProvider {
         provider
     }
 
+    /// Sets the policy of retrying to load the tiles that failed to load. The retries are done by the workers, so
+    /// they are not reported by the [load monitor](VectorTileProvider::load_monitor).
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     fn load(&self, index: TileIndex, style: VectorTileStyle) {
         if self
             .worker_pool
@@ -120,6 +151,8 @@ impl WebWorkerVectorTileProvider {
             return;
         }
 
+        self.load_monitor.requested(index);
+
         let url = (*self.tile_source)(&index);
         loop {
             let worker_index =
@@ -135,6 +168,7 @@ impl WebWorkerVectorTileProvider {
                 url,
                 style,
                 tile_scheme: self.tile_scheme.clone(),
+                retry_policy: self.retry_policy,
             };
 
             state
@@ -147,6 +181,11 @@ impl WebWorkerVectorTileProvider {
 
     fn set_loading_state(&self, index: TileIndex) -> bool {
         let mut tiles = self.tiles.lock().expect("tile store mutex is poisoned");
+        if matches!(tiles.peek(&index), Some(TileState::Error(retry_at)) if *retry_at <= Instant::now())
+        {
+            tiles.remove(&index);
+        }
+
         let has_entry = tiles.peek(&index).is_some();
         if has_entry {
             if let Some(mut entry) = tiles.get_mut(&index) {
@@ -155,7 +194,7 @@ impl WebWorkerVectorTileProvider {
                     return false;
                 }
 
-                let TileState::Outdated(tile) = std::mem::replace(value, TileState::Error) else {
+                let TileState::Outdated(tile) = std::mem::replace(value, TileState::Loading) else {
                     log::error!("Type of value changed unexpectedly during loading.");
                     return false;
                 };
@@ -179,6 +218,8 @@ impl WebWorkerVectorTileProvider {
 
         let tiles_store = self.tiles.clone();
         let messenger = self.messenger.clone();
+        let load_monitor = self.load_monitor.clone();
+        let memory = self.memory.clone();
         let callback: Closure<dyn FnMut(web_sys::MessageEvent)> =
             Closure::new(move |event: web_sys::MessageEvent| {
                 if let Some(message) = event.data().as_f64() {
@@ -207,11 +248,26 @@ impl WebWorkerVectorTileProvider {
                         let mut store = tiles_store.lock().expect("tiles mutex is poisoned");
                         match result {
                             Ok(decoded_vector_tile) => {
-                                store_vector_tile(decoded_vector_tile, &mut store, &messenger)
+                                let index = decoded_vector_tile.index;
+                                store_vector_tile(
+                                    decoded_vector_tile,
+                                    &mut store,
+                                    &memory,
+                                    &messenger,
+                                );
+                                drop(store);
+                                load_monitor.loaded(index);
                             }
-                            Err((index, message)) => {
+                            Err(TileLoadError {
+                                index,
+                                message,
+                                retry_after,
+                            }) => {
                                 log::info!("Failed to load tile {index:?}: {message}");
-                                store.insert(index, TileState::Error);
+                                store.insert(index, TileState::Error(Instant::now() + retry_after));
+                                memory.remove(&index);
+                                drop(store);
+                                load_monitor.failed(index, GalileoError::Generic(message));
                             }
                         }
                     }
@@ -231,7 +287,8 @@ impl WebWorkerVectorTileProvider {
 
 fn store_vector_tile(
     decoded_vector_tile: DecodedVectorTile,
-    store: &mut Cache<TileIndex, TileState>,
+    store: &mut TileStore,
+    memory: &MemoryTracker<TileIndex>,
     messenger: &Arc<RwLock<Option<Box<dyn Messenger>>>>,
 ) {
     log::info!("Storing tile");
@@ -239,6 +296,7 @@ fn store_vector_tile(
         index,
         mvt_tile,
         bundle_bytes,
+        feature_primitives,
     } = decoded_vector_tile;
     match store.get(&index) {
         Some(TileState::Loading | TileState::Updating(_)) => {
@@ -250,8 +308,14 @@ fn store_vector_tile(
             let mvt_tile = MvtTile::decode(bytes::Bytes::from(mvt_tile), true).unwrap();
             store.insert(
                 index,
-                TileState::Loaded(Box::new(UnpackedVectorTile { bundle, mvt_tile })),
+                TileState::Loaded(Box::new(UnpackedVectorTile {
+                    bundle,
+                    mvt_tile,
+                    feature_primitives,
+                })),
             );
+            // The packed tile this one replaces, if it was reloaded, is dropped.
+            memory.remove(&index);
 
             if let Some(messenger) = &(*messenger.read().unwrap()) {
                 messenger.request_redraw();
@@ -263,7 +327,15 @@ fn store_vector_tile(
 
 #[derive(Debug, Serialize, Deserialize)]
 enum WorkerOutput {
-    VectorTile(Result<DecodedVectorTile, (TileIndex, String)>),
+    VectorTile(Result<DecodedVectorTile, TileLoadError>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TileLoadError {
+    index: TileIndex,
+    message: String,
+    /// Time after which the tile can be requested again.
+    retry_after: Duration,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -273,6 +345,7 @@ struct DecodedVectorTile {
     mvt_tile: Vec<u8>,
     #[serde(with = "serde_bytes")]
     bundle_bytes: Vec<u8>,
+    feature_primitives: FeaturePrimitives,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -281,6 +354,7 @@ pub struct LoadTilePayload {
     url: String,
     style: VectorTileStyle,
     tile_scheme: TileSchema,
+    retry_policy: RetryPolicy,
 }
 
 #[wasm_bindgen]
@@ -305,9 +379,12 @@ fn vt_data_provider() -> UrlDataProvider<str, VtProcessor> {
 pub async fn load_tile(data: JsValue) -> JsValue {
     let payload: LoadTilePayload = serde_wasm_bindgen::from_value(data).unwrap();
     let index = payload.index;
-    let result = try_load_tile(payload)
-        .await
-        .map_err(|err| (index, format!("{err:?}")));
+    let retry_policy = payload.retry_policy;
+    let result = try_load_tile(payload).await.map_err(|err| TileLoadError {
+        index,
+        message: format!("{err:?}"),
+        retry_after: retry_policy.failed_tile_ttl(&err),
+    });
 
     let output = WorkerOutput::VectorTile(result);
     serde_wasm_bindgen::to_value(&output).unwrap()
@@ -324,8 +401,18 @@ async fn try_load_tile(payload: LoadTilePayload) -> Result<DecodedVectorTile, Ga
         )),
     };
 
-    let bytes = data_provider.load_raw(&payload.url).await?;
-    let (bundle, _) = data_provider.decode(bytes.clone(), context)?;
+    let mut attempt = 1;
+    let bytes = loop {
+        match data_provider.load_raw(&payload.url).await {
+            Err(err) if payload.retry_policy.should_retry(&err, attempt) => {
+                log::info!("Failed to load tile {:?}, retrying: {err:?}", payload.index);
+                crate::async_runtime::sleep(payload.retry_policy.retry_delay(attempt)).await;
+                attempt += 1;
+            }
+            result => break result?,
+        }
+    };
+    let (bundle, _, feature_primitives) = data_provider.decode(bytes.clone(), context)?;
     let RenderBundleType::Tessellating(bundle) = bundle.0;
 
     let serialized = bincode::serialize(&bundle.into_bytes()).unwrap();
@@ -334,5 +421,6 @@ async fn try_load_tile(payload: LoadTilePayload) -> Result<DecodedVectorTile, Ga
         index: payload.index,
         mvt_tile: bytes.to_vec(),
         bundle_bytes: serialized,
+        feature_primitives,
     })
 }
@@ -1,5 +1,8 @@
-use crate::render::PackedBundle;
+use crate::layer::vector_tile_layer::tile_provider::FeaturePrimitives;
+use crate::render::render_bundle::RenderBundle;
+use crate::render::{Canvas, HighlightStyle, PackedBundle};
 use galileo_mvt::MvtTile;
+use std::collections::HashSet;
 
 /// Decoded and packed vector tile.
 pub struct VectorTile {
@@ -7,4 +10,87 @@ pub struct VectorTile {
     pub mvt_tile: MvtTile,
     /// Packed render bundle to draw this tile.
     pub bundle: Box<dyn PackedBundle>,
+    /// Unpacked render bundle, kept to change the highlight of the features without decoding the tile again.
+    pub(crate) render_bundle: RenderBundle,
+    pub(crate) feature_primitives: FeaturePrimitives,
+    /// Ids of the features of the tile that are currently highlighted.
+    highlighted: HashSet<u64>,
+    /// Version of the [`FeatureHighlight`] that was applied to the tile.
+    highlight_version: usize,
+    /// Generation of the GPU resources the tile was packed for.
+    pack_generation: usize,
+}
+
+/// Set of the highlighted vector tile features shared by all the tiles of a layer.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FeatureHighlight {
+    pub ids: HashSet<u64>,
+    pub style: HighlightStyle,
+    /// Incremented on every change of the highlight, so that tiles can skip updating if nothing changed.
+    pub version: usize,
+    /// Incremented when the packed bundles of all tiles become invalid (e.g. after the GPU device was lost), so that
+    /// the tiles are packed again.
+    pub pack_generation: usize,
+}
+
+impl VectorTile {
+    pub(crate) fn new(
+        mvt_tile: MvtTile,
+        render_bundle: RenderBundle,
+        feature_primitives: FeaturePrimitives,
+        pack_generation: usize,
+        canvas: &dyn Canvas,
+    ) -> Self {
+        Self {
+            mvt_tile,
+            bundle: canvas.pack_bundle(&render_bundle),
+            render_bundle,
+            feature_primitives,
+            highlighted: HashSet::new(),
+            highlight_version: 0,
+            pack_generation,
+        }
+    }
+
+    /// Applies the highlight to the features of the tile and packs the tile again if anything changed.
+    pub(crate) fn update_highlight(&mut self, highlight: &FeatureHighlight, canvas: &dyn Canvas) {
+        let outdated_pack = self.pack_generation != highlight.pack_generation;
+        if self.highlight_version == highlight.version && !outdated_pack {
+            return;
+        }
+
+        self.highlight_version = highlight.version;
+        self.pack_generation = highlight.pack_generation;
+
+        let mut changed = outdated_pack;
+        for id in std::mem::take(&mut self.highlighted) {
+            changed |= self.set_highlight(id, None);
+        }
+
+        for id in &highlight.ids {
+            if self.set_highlight(*id, Some(highlight.style)) {
+                self.highlighted.insert(*id);
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.bundle = canvas.pack_bundle(&self.render_bundle);
+        }
+    }
+
+    /// Returns false if the tile has no feature with the given id.
+    fn set_highlight(&mut self, id: u64, style: Option<HighlightStyle>) -> bool {
+        let Some(primitive_ids) = self.feature_primitives.get(&id) else {
+            return false;
+        };
+
+        for primitive_id in primitive_ids {
+            if let Err(err) = self.render_bundle.set_highlight(*primitive_id, style) {
+                log::warn!("Failed to highlight vector tile feature: {err:?}");
+            }
+        }
+
+        true
+    }
 }
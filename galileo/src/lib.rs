@@ -68,15 +68,20 @@
 #![warn(clippy::unwrap_used)]
 #![warn(missing_docs)]
 
-pub(crate) mod async_runtime;
+pub mod async_runtime;
 mod color;
 pub mod control;
 pub(crate) mod decoded_image;
+pub mod elevation;
 pub mod error;
+pub mod geofence;
+pub mod interpolation;
+pub mod label;
 pub mod layer;
 mod lod;
 mod map;
 mod messenger;
+pub mod occlusion;
 mod platform;
 pub mod render;
 pub mod tile_scheme;
@@ -90,13 +95,14 @@ mod galileo_map;
 #[cfg(all(feature = "winit", feature = "wgpu"))]
 pub use galileo_map::{GalileoMap, MapBuilder};
 
-pub use color::Color;
+pub use color::{palette, Color, ColorSpace};
+pub use decoded_image::DecodedImage;
 pub use layer::feature_layer::symbol;
 pub use lod::Lod;
-pub use map::{LayerCollection, Map};
+pub use map::{Bookmarks, Easing, LayerCollection, Map, Tour, TourPlayer, TourStep};
 pub use messenger::{DummyMessenger, Messenger};
 pub use tile_scheme::TileSchema;
-pub use view::MapView;
+pub use view::{Camera, Eye, EyeFov, Frustum, MapView, ScreenPosition};
 
 // Reexport galileo_types
 pub use galileo_types;
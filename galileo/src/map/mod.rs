@@ -1,12 +1,17 @@
 use crate::layer::Layer;
 use crate::messenger::Messenger;
+use crate::render::BASE_DPI;
 use crate::view::MapView;
 use galileo_types::cartesian::Size;
 use std::time::Duration;
 use web_time::SystemTime;
 
+mod easing;
 mod layer_collection;
+mod tour;
+pub use easing::Easing;
 pub use layer_collection::LayerCollection;
+pub use tour::{Bookmarks, Tour, TourPlayer, TourStep};
 
 const FRAME_DURATION: Duration = Duration::from_millis(16);
 
@@ -15,6 +20,7 @@ pub struct Map {
     view: MapView,
     layers: LayerCollection,
     messenger: Option<Box<dyn Messenger>>,
+    scale_factor: f64,
     animation: Option<AnimationParameters>,
 }
 
@@ -23,6 +29,7 @@ struct AnimationParameters {
     end_view: MapView,
     start_time: SystemTime,
     duration: Duration,
+    easing: Easing,
 }
 
 impl Map {
@@ -41,6 +48,7 @@ impl Map {
             view,
             layers: layers.into(),
             messenger,
+            scale_factor: 1.0,
             animation: None,
         }
     }
@@ -102,7 +110,9 @@ impl Map {
                 .expect("the value was removed unexpectedly");
             self.view = animation.end_view;
         } else {
-            self.view = animation.start_view.interpolate(&animation.end_view, k);
+            self.view = animation
+                .start_view
+                .interpolate(&animation.end_view, animation.easing.apply(k));
         }
 
         self.redraw();
@@ -118,15 +128,69 @@ impl Map {
 
     /// Request a gradual change of the map view to the specified view.
     pub fn animate_to(&mut self, target: MapView, duration: Duration) {
+        self.animate_to_with_easing(target, duration, Easing::Linear);
+    }
+
+    /// Request a gradual change of the map view to the specified view, with the speed of the change following the
+    /// `easing` curve.
+    pub fn animate_to_with_easing(&mut self, target: MapView, duration: Duration, easing: Easing) {
         self.animation = Some(AnimationParameters {
             start_view: self.view.clone(),
             end_view: target,
             start_time: SystemTime::now() - FRAME_DURATION,
             duration,
+            easing,
         });
     }
 
-    /// Set the size of the map.
+    /// Stops the animation started with [`Map::animate_to`], leaving the map at its current view.
+    pub fn stop_animation(&mut self) {
+        self.animation = None;
+    }
+
+    /// Scale factor of the surface the map is rendered to.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Effective resolution of the surface the map is rendered to, in dots per inch.
+    pub fn dpi(&self) -> f64 {
+        BASE_DPI * self.scale_factor
+    }
+
+    /// Sets the scale factor (the number of physical pixels in one logical pixel) of the surface the map is rendered
+    /// to, and passes it to all the layers of the map with [`Layer::set_scale_factor`].
+    ///
+    /// To keep the physical size of symbols and labels the same on all screens, the size of the map view should be
+    /// set in logical pixels (see [`Map::set_size`]). The renderers then scale all sizes given in pixels by the ratio
+    /// of the render target size to the view size.
+    ///
+    /// Layers added to the map after this call are not notified automatically.
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+        for layer in self.layers.iter_mut() {
+            layer.set_scale_factor(scale_factor);
+        }
+
+        self.redraw();
+    }
+
+    /// Makes all the layers of the map drop their GPU resources with [`Layer::reset_gpu_resources`], and requests a
+    /// redraw to upload them again.
+    ///
+    /// Must be called after the map is rendered with a new GPU device, e.g. after
+    /// [`WgpuRenderer::recover_with_window`](crate::render::WgpuRenderer::recover_with_window) or when a new renderer
+    /// is created on application resume.
+    pub fn reset_gpu_resources(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.reset_gpu_resources();
+        }
+
+        self.redraw();
+    }
+
+    /// Set the size of the map. If the size is given in logical pixels while the render target has more physical
+    /// pixels (e.g. on a high-DPI screen), everything sized in pixels is scaled up accordingly when rendered.
     pub fn set_size(&mut self, new_size: Size) {
         self.view = self.view.with_size(new_size);
     }
@@ -0,0 +1,396 @@
+use crate::control::{Clock, SystemClock};
+use crate::error::GalileoError;
+use crate::map::{Easing, Map};
+use crate::view::MapView;
+use std::time::Duration;
+use web_time::SystemTime;
+
+/// Named map views that can be returned to later, or played as a [`Tour`].
+///
+/// Bookmarks keep the order they were added in. Adding a bookmark with an existing name replaces the view of the
+/// bookmark without changing its position in the list.
+#[derive(Debug, Clone, Default)]
+pub struct Bookmarks {
+    items: Vec<(String, MapView)>,
+}
+
+impl Bookmarks {
+    /// Creates an empty list of bookmarks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the view with the given name.
+    pub fn add(&mut self, name: impl Into<String>, view: MapView) {
+        let name = name.into();
+        match self.items.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, stored)) => *stored = view,
+            None => self.items.push((name, view)),
+        }
+    }
+
+    /// View stored with the given name.
+    pub fn get(&self, name: &str) -> Option<&MapView> {
+        self.items.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// Removes the bookmark with the given name and returns its view.
+    pub fn remove(&mut self, name: &str) -> Option<MapView> {
+        let index = self.items.iter().position(|(n, _)| n == name)?;
+        Some(self.items.remove(index).1)
+    }
+
+    /// Names of the bookmarks in the order they were added.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().map(|(n, _)| n.as_str())
+    }
+
+    /// Number of bookmarks.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if there are no bookmarks.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Moves the map to the bookmarked view with an animation. Returns false if there is no bookmark with this name.
+    pub fn fly_to(&self, name: &str, map: &mut Map, duration: Duration) -> bool {
+        let Some(view) = self.get(name) else {
+            return false;
+        };
+
+        map.animate_to_with_easing(
+            view.with_size(map.view().size()),
+            duration,
+            Easing::EaseInOut,
+        );
+        true
+    }
+}
+
+/// Step of a [`Tour`]: the camera flies to the view of the step and stays there for the pause.
+#[derive(Debug, Clone)]
+pub struct TourStep {
+    /// Target view of the step.
+    pub view: MapView,
+    /// Name of the step, e.g. to show a caption while the step is played.
+    pub name: Option<String>,
+    /// Duration of the flight to the view.
+    pub duration: Duration,
+    /// Speed curve of the flight.
+    pub easing: Easing,
+    /// Time the camera stays at the view before the next step.
+    pub pause: Duration,
+}
+
+impl TourStep {
+    /// Creates a step with a 2 second flight and a 2 second pause.
+    pub fn new(view: MapView) -> Self {
+        Self {
+            view,
+            name: None,
+            duration: Duration::from_secs(2),
+            easing: Easing::EaseInOut,
+            pause: Duration::from_secs(2),
+        }
+    }
+
+    /// Sets the name of the step.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the duration of the flight to the view.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Sets the speed curve of the flight.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Sets the time the camera stays at the view.
+    pub fn with_pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+}
+
+/// Sequence of views played as an animation by a [`TourPlayer`], e.g. for kiosks and presentations.
+#[derive(Debug, Clone, Default)]
+pub struct Tour {
+    steps: Vec<TourStep>,
+    looped: bool,
+}
+
+impl Tour {
+    /// Creates a tour with the given steps.
+    pub fn new(steps: Vec<TourStep>) -> Self {
+        Self {
+            steps,
+            looped: false,
+        }
+    }
+
+    /// Creates a tour going through the bookmarks with the given names. Returns an error if any of the bookmarks does
+    /// not exist.
+    pub fn from_bookmarks<'a>(
+        bookmarks: &Bookmarks,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, GalileoError> {
+        let steps = names
+            .into_iter()
+            .map(|name| {
+                let view = bookmarks.get(name).ok_or_else(|| {
+                    GalileoError::Generic(format!("bookmark '{name}' does not exist"))
+                })?;
+                Ok(TourStep::new(view.clone()).with_name(name))
+            })
+            .collect::<Result<_, GalileoError>>()?;
+
+        Ok(Self::new(steps))
+    }
+
+    /// Adds a step to the end of the tour.
+    pub fn with_step(mut self, step: TourStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Makes the tour start over after the last step.
+    pub fn with_loop(mut self, looped: bool) -> Self {
+        self.looped = looped;
+        self
+    }
+
+    /// Steps of the tour.
+    pub fn steps(&self) -> &[TourStep] {
+        &self.steps
+    }
+
+    /// Mutable access to the steps of the tour, e.g. to change the timing of all steps.
+    pub fn steps_mut(&mut self) -> &mut [TourStep] {
+        &mut self.steps
+    }
+}
+
+/// Plays a [`Tour`] on a map.
+///
+/// The player changes the map view only in [`TourPlayer::update`], which should be called before every frame while
+/// the tour is playing.
+///
+/// ```no_run
+/// use galileo::{Bookmarks, Map, Tour, TourPlayer};
+///
+/// # fn run(map: &mut Map, bookmarks: Bookmarks) -> Result<(), galileo::error::GalileoError> {
+/// let tour = Tour::from_bookmarks(&bookmarks, ["harbour", "old town", "castle"])?.with_loop(true);
+/// let mut player = TourPlayer::new(tour);
+/// player.play(map);
+///
+/// // In the render loop:
+/// if player.update(map) {
+///     map.redraw();
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TourPlayer {
+    tour: Tour,
+    clock: Box<dyn Clock>,
+    state: Option<PlayState>,
+}
+
+struct PlayState {
+    step: usize,
+    start_view: MapView,
+    step_start: SystemTime,
+    paused_at: Option<SystemTime>,
+}
+
+impl TourPlayer {
+    /// Creates a player for the tour.
+    pub fn new(tour: Tour) -> Self {
+        Self {
+            tour,
+            clock: Box::new(SystemClock),
+            state: None,
+        }
+    }
+
+    /// Sets the clock used to time the tour.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// The tour being played.
+    pub fn tour(&self) -> &Tour {
+        &self.tour
+    }
+
+    /// Starts the tour from the first step. The camera flies to the first view from the current view of the map.
+    pub fn play(&mut self, map: &Map) {
+        self.state = (!self.tour.steps.is_empty()).then(|| PlayState {
+            step: 0,
+            start_view: map.view().clone(),
+            step_start: self.clock.now(),
+            paused_at: None,
+        });
+    }
+
+    /// Pauses the tour. The camera stays where it is until [`TourPlayer::resume`] is called.
+    pub fn pause(&mut self) {
+        let now = self.clock.now();
+        if let Some(state) = &mut self.state {
+            state.paused_at.get_or_insert(now);
+        }
+    }
+
+    /// Continues the paused tour.
+    pub fn resume(&mut self) {
+        let now = self.clock.now();
+        if let Some(state) = &mut self.state {
+            if let Some(paused_at) = state.paused_at.take() {
+                state.step_start += now.duration_since(paused_at).unwrap_or_default();
+            }
+        }
+    }
+
+    /// Stops the tour. The camera stays where it is.
+    pub fn stop(&mut self) {
+        self.state = None;
+    }
+
+    /// Returns true if the tour is being played (including when it is paused).
+    pub fn is_playing(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Returns true if the tour is paused.
+    pub fn is_paused(&self) -> bool {
+        self.state.as_ref().is_some_and(|s| s.paused_at.is_some())
+    }
+
+    /// Step that is being played.
+    pub fn current_step(&self) -> Option<&TourStep> {
+        self.tour.steps.get(self.state.as_ref()?.step)
+    }
+
+    /// Moves the camera according to the current time. Returns true if the tour is still playing.
+    pub fn update(&mut self, map: &mut Map) -> bool {
+        let now = self.clock.now();
+        let size = map.view().size();
+        let Some(state) = &mut self.state else {
+            return false;
+        };
+        if state.paused_at.is_some() {
+            return true;
+        }
+
+        loop {
+            let step = &self.tour.steps[state.step];
+            let target = step.view.with_size(size);
+            let elapsed = now.duration_since(state.step_start).unwrap_or_default();
+
+            if elapsed < step.duration {
+                let k = elapsed.as_secs_f64() / step.duration.as_secs_f64();
+                map.set_view(state.start_view.interpolate(&target, step.easing.apply(k)));
+                return true;
+            }
+
+            if elapsed < step.duration + step.pause {
+                if map.view() != &target {
+                    map.set_view(target);
+                }
+                return true;
+            }
+
+            state.step_start += step.duration + step.pause;
+            state.start_view = target.clone();
+            state.step += 1;
+            if state.step == self.tour.steps.len() {
+                if !self.tour.looped {
+                    map.set_view(target);
+                    self.state = None;
+                    return false;
+                }
+                state.step = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::ManualClock;
+    use crate::messenger::DummyMessenger;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    fn view(x: f64) -> MapView {
+        MapView::new_projected(&Point2d::new(x, 0.0), 1.0).with_size(Size::new(100.0, 100.0))
+    }
+
+    fn center(map: &Map) -> f64 {
+        map.view()
+            .screen_to_map(Point2d::new(50.0, 50.0))
+            .expect("view is valid")
+            .x
+    }
+
+    #[test]
+    fn tour_plays_steps_with_pauses() {
+        let mut map = Map::new(view(0.0), vec![], None::<DummyMessenger>);
+        let mut bookmarks = Bookmarks::new();
+        bookmarks.add("a", view(100.0));
+        bookmarks.add("b", view(200.0));
+        assert!(Tour::from_bookmarks(&bookmarks, ["a", "c"]).is_err());
+
+        let mut tour = Tour::from_bookmarks(&bookmarks, ["a", "b"]).expect("bookmarks exist");
+        for step in tour.steps_mut() {
+            step.easing = Easing::Linear;
+            step.duration = Duration::from_secs(1);
+            step.pause = Duration::from_secs(1);
+        }
+
+        let clock = ManualClock::default();
+        let mut player = TourPlayer::new(tour).with_clock(clock.clone());
+        player.play(&map);
+
+        clock.advance(Duration::from_millis(500));
+        assert!(player.update(&mut map));
+        assert!((center(&map) - 50.0).abs() < 1e-6);
+
+        player.pause();
+        clock.advance(Duration::from_secs(10));
+        assert!(player.update(&mut map));
+        player.resume();
+
+        clock.advance(Duration::from_millis(1000));
+        assert!(player.update(&mut map));
+        assert!((center(&map) - 100.0).abs() < 1e-6);
+        assert_eq!(
+            player.current_step().and_then(|s| s.name.as_deref()),
+            Some("a")
+        );
+
+        clock.advance(Duration::from_millis(1000));
+        assert!(player.update(&mut map));
+        assert!((center(&map) - 150.0).abs() < 1e-6);
+        assert_eq!(
+            player.current_step().and_then(|s| s.name.as_deref()),
+            Some("b")
+        );
+
+        clock.advance(Duration::from_secs(5));
+        assert!(!player.update(&mut map));
+        assert!((center(&map) - 200.0).abs() < 1e-6);
+        assert!(!player.is_playing());
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 /// Messenger used to notifiy application when the map requires update.
 pub trait Messenger: Send + Sync {
     /// Notifies the application that the map requires an update.
@@ -11,3 +13,12 @@ impl Messenger for DummyMessenger {
         // do nothing
     }
 }
+
+/// Messenger of a layer shared by the layers it consists of (e.g. frames of a sequencer or children of a group).
+pub(crate) struct SharedMessenger(pub Arc<dyn Messenger>);
+
+impl Messenger for SharedMessenger {
+    fn request_redraw(&self) {
+        self.0.request_redraw();
+    }
+}
@@ -0,0 +1,369 @@
+//! Visibility of map points from the camera when the map has terrain or 3D buildings. See [`Occlusion`].
+
+use crate::elevation::ElevationTile;
+use crate::view::MapView;
+use galileo_types::cartesian::{CartesianPolygon, Point2d, Point3d, Rect};
+use galileo_types::impls::Polygon;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::sync::Arc;
+
+/// Maximum number of samples taken along a line of sight.
+const MAX_SAMPLES: usize = 512;
+
+/// 3D surface that can hide map points from the camera, e.g. terrain or extruded buildings.
+///
+/// Heights are given in the units of the map CRS, same as the *Z* coordinate of the [`Camera`](crate::Camera).
+pub trait Occluder: MaybeSend + MaybeSync {
+    /// Height of the surface at the given point of the map, or `None` if the occluder does not cover the point.
+    fn height_at(&self, point: Point2d) -> Option<f64>;
+    /// Area of the map covered by the occluder.
+    fn bounds(&self) -> Rect;
+    /// Maximum height of the surface. Lines of sight above this height are not checked against the occluder.
+    fn max_height(&self) -> f64;
+}
+
+/// Terrain surface given by an elevation grid covering a rectangle of the map.
+#[derive(Debug, Clone)]
+pub struct TerrainOccluder {
+    tile: Arc<ElevationTile>,
+    bounds: Rect,
+    vertical_scale: f64,
+}
+
+impl TerrainOccluder {
+    /// Creates a terrain from the elevation grid stretched over the `bounds` in the CRS of the map. The top row of the
+    /// grid is at the top (`y_max`) of the bounds.
+    pub fn new(tile: impl Into<Arc<ElevationTile>>, bounds: Rect) -> Self {
+        Self {
+            tile: tile.into(),
+            bounds,
+            vertical_scale: 1.0,
+        }
+    }
+
+    /// Sets the number of map units in one unit of elevation, e.g. to convert meters of elevation into the units of a
+    /// projection that is not conformal in scale, or to exaggerate the relief. Default is 1.
+    pub fn with_vertical_scale(mut self, vertical_scale: f64) -> Self {
+        self.vertical_scale = vertical_scale;
+        self
+    }
+}
+
+impl Occluder for TerrainOccluder {
+    fn height_at(&self, point: Point2d) -> Option<f64> {
+        if !self.bounds.contains(&point) {
+            return None;
+        }
+
+        let u = (point.x - self.bounds.x_min()) / self.bounds.width();
+        let v = (self.bounds.y_max() - point.y) / self.bounds.height();
+        let height = self.tile.sample(u, v);
+
+        height
+            .is_finite()
+            .then(|| height as f64 * self.vertical_scale)
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn max_height(&self) -> f64 {
+        self.tile.range().1 as f64 * self.vertical_scale
+    }
+}
+
+/// Buildings or other objects given by their footprints extruded to a height.
+#[derive(Debug, Clone)]
+pub struct ExtrusionOccluder {
+    footprints: Vec<(Polygon<Point2d>, Rect, f64)>,
+    bounds: Rect,
+    max_height: f64,
+}
+
+impl ExtrusionOccluder {
+    /// Creates an occluder from `(footprint, height)` pairs in the CRS of the map. Footprints without points are
+    /// ignored.
+    pub fn new(footprints: impl IntoIterator<Item = (Polygon<Point2d>, f64)>) -> Self {
+        let footprints: Vec<_> = footprints
+            .into_iter()
+            .filter_map(|(footprint, height)| {
+                let bounds = Rect::from_points(footprint.outer_contour.points.iter())?;
+                Some((footprint, bounds, height))
+            })
+            .collect();
+        let bounds = footprints
+            .iter()
+            .map(|(_, bounds, _)| *bounds)
+            .reduce(|acc, bounds| acc.merge(bounds))
+            .unwrap_or(Rect::new(0.0, 0.0, 0.0, 0.0));
+        let max_height = footprints
+            .iter()
+            .map(|(_, _, height)| *height)
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        Self {
+            footprints,
+            bounds,
+            max_height,
+        }
+    }
+}
+
+impl Occluder for ExtrusionOccluder {
+    fn height_at(&self, point: Point2d) -> Option<f64> {
+        self.footprints
+            .iter()
+            .filter(|(footprint, bounds, _)| {
+                bounds.contains(&point) && footprint.contains_point(&point)
+            })
+            .map(|(_, _, height)| *height)
+            .reduce(f64::max)
+    }
+
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn max_height(&self) -> f64 {
+        self.max_height
+    }
+}
+
+/// Coarse visibility query that finds out if points of the map are hidden from the camera by terrain or buildings,
+/// so that labels and markers behind hills are hidden or faded instead of floating through them.
+///
+/// The line of sight from the camera to a point is sampled every few pixels, and the point is considered hidden if
+/// any sample is below the surface of one of the [occluders](Occluder). Points near the edge of a ridge are faded
+/// out gradually (see [`Occlusion::with_fade`]).
+///
+/// ```
+/// use galileo::elevation::ElevationTile;
+/// use galileo::occlusion::{Occlusion, TerrainOccluder};
+/// use galileo::MapView;
+/// use galileo_types::cartesian::{Point2d, Point3d, Rect, Size};
+///
+/// // A 1000 m high wall along the x axis.
+/// let wall = ElevationTile::new(1, 3, vec![0.0, 1000.0, 0.0]).unwrap();
+/// let occlusion = Occlusion::new()
+///     .with_occluder(TerrainOccluder::new(wall, Rect::new(-5000.0, -300.0, 5000.0, 300.0)));
+///
+/// let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 10.0)
+///     .with_size(Size::new(800.0, 600.0))
+///     .with_rotation_x(60f64.to_radians());
+///
+/// // The camera is south of the wall, so the points behind it are hidden.
+/// assert!(occlusion.is_visible(&view, Point3d::new(0.0, -1000.0, 0.0)));
+/// assert!(!occlusion.is_visible(&view, Point3d::new(0.0, 1000.0, 0.0)));
+/// ```
+#[derive(Clone)]
+pub struct Occlusion {
+    occluders: Vec<Arc<dyn Occluder>>,
+    step: f64,
+    fade: f64,
+}
+
+impl Default for Occlusion {
+    fn default() -> Self {
+        Self {
+            occluders: vec![],
+            step: 4.0,
+            fade: 8.0,
+        }
+    }
+}
+
+impl Occlusion {
+    /// Creates a query without occluders, that considers all points visible.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an occluder.
+    pub fn with_occluder(mut self, occluder: impl Occluder + 'static) -> Self {
+        self.occluders.push(Arc::new(occluder));
+        self
+    }
+
+    /// Sets the distance between the samples along the line of sight, in pixels of the map view. Larger steps are
+    /// faster but can miss thin obstacles. Default is 4.
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = step.max(0.1);
+        self
+    }
+
+    /// Sets the depth (in pixels of the map view) the line of sight must go under the surface for the point to be
+    /// fully hidden. Points whose line of sight goes less deep are partially visible. Zero disables fading. Default
+    /// is 8.
+    pub fn with_fade(mut self, fade: f64) -> Self {
+        self.fade = fade.max(0.0);
+        self
+    }
+
+    /// Returns true if there are no occluders.
+    pub fn is_empty(&self) -> bool {
+        self.occluders.is_empty()
+    }
+
+    /// Height of the highest occluder surface at the point, or `None` if no occluder covers the point.
+    pub fn surface_height(&self, point: Point2d) -> Option<f64> {
+        self.occluders
+            .iter()
+            .filter_map(|occluder| occluder.height_at(point))
+            .reduce(f64::max)
+    }
+
+    /// Visibility of the point from the camera of the view, from `0.0` for hidden points to `1.0` for fully visible
+    /// ones. Labels and markers can be drawn with this value as their opacity.
+    ///
+    /// Points below the surface are lifted onto it, so points without elevation (e.g. labels of a 2D layer) are
+    /// considered to lie on the terrain. If the view has no camera (e.g. has zero size), all points are visible.
+    pub fn visibility(&self, view: &MapView, point: Point3d) -> f64 {
+        if self.occluders.is_empty() {
+            return 1.0;
+        }
+
+        let Some(camera) = view.camera() else {
+            return 1.0;
+        };
+
+        let surface = self.surface_height(Point2d::new(point.x, point.y));
+        let target = Point3d::new(
+            point.x,
+            point.y,
+            surface.map_or(point.z, |z| z.max(point.z)),
+        );
+        let eye = Point3d::new(camera.eye.x, camera.eye.y, camera.eye.z);
+
+        let step = self.step * view.resolution();
+        let length = (target.x - eye.x).hypot(target.y - eye.y);
+        // Samples closer than one step to the point are skipped, as they belong to the surface the point lies on.
+        if length <= step {
+            return 1.0;
+        }
+
+        let ray_bounds = Rect::new(
+            eye.x.min(target.x),
+            eye.y.min(target.y),
+            eye.x.max(target.x),
+            eye.y.max(target.y),
+        );
+        let occluders: Vec<_> = self
+            .occluders
+            .iter()
+            .filter(|occluder| {
+                eye.z.min(target.z) <= occluder.max_height()
+                    && occluder.bounds().intersects(ray_bounds)
+            })
+            .collect();
+        if occluders.is_empty() {
+            return 1.0;
+        }
+
+        let samples = ((length / step).ceil() as usize).min(MAX_SAMPLES);
+        let t_max = 1.0 - step / length;
+        let mut depth = 0.0f64;
+        for i in 0..samples {
+            let t = t_max * i as f64 / samples as f64;
+            let sample = eye + (target - eye) * t;
+            let position = Point2d::new(sample.x, sample.y);
+            let surface = occluders
+                .iter()
+                .filter_map(|occluder| occluder.height_at(position))
+                .reduce(f64::max);
+            if let Some(surface) = surface {
+                depth = depth.max(surface - sample.z);
+            }
+        }
+
+        let fade = self.fade * view.resolution();
+        if depth <= 0.0 {
+            1.0
+        } else if fade > 0.0 {
+            (1.0 - depth / fade).max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns true if the point is at least half visible (see [`Occlusion::visibility`]).
+    pub fn is_visible(&self, view: &MapView, point: Point3d) -> bool {
+        self.visibility(view, point) >= 0.5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::Size;
+    use galileo_types::impls::ClosedContour;
+
+    fn tilted_view() -> MapView {
+        MapView::new_projected(&Point2d::new(0.0, 0.0), 10.0)
+            .with_size(Size::new(800.0, 600.0))
+            .with_rotation_x(60f64.to_radians())
+    }
+
+    fn square(x: f64, y: f64, half_size: f64) -> Polygon<Point2d> {
+        ClosedContour::new(vec![
+            Point2d::new(x - half_size, y - half_size),
+            Point2d::new(x + half_size, y - half_size),
+            Point2d::new(x + half_size, y + half_size),
+            Point2d::new(x - half_size, y + half_size),
+        ])
+        .into()
+    }
+
+    #[test]
+    fn building_hides_points_behind_it() {
+        let occlusion = Occlusion::new().with_occluder(ExtrusionOccluder::new(vec![(
+            square(0.0, 0.0, 200.0),
+            1000.0,
+        )]));
+        let view = tilted_view();
+
+        assert!(occlusion.is_visible(&view, Point3d::new(0.0, -1000.0, 0.0)));
+        assert_eq!(
+            occlusion.visibility(&view, Point3d::new(0.0, 1000.0, 0.0)),
+            0.0
+        );
+        // The roof of the building itself is visible.
+        assert!(occlusion.is_visible(&view, Point3d::new(0.0, 0.0, 0.0)));
+        // Points high above the building are visible.
+        assert!(occlusion.is_visible(&view, Point3d::new(0.0, 1000.0, 5000.0)));
+    }
+
+    #[test]
+    fn top_down_view_has_no_occlusion() {
+        let occlusion = Occlusion::new().with_occluder(ExtrusionOccluder::new(vec![(
+            square(0.0, 0.0, 200.0),
+            1000.0,
+        )]));
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 10.0)
+            .with_size(Size::new(800.0, 600.0));
+
+        assert!(occlusion.is_visible(&view, Point3d::new(0.0, 400.0, 0.0)));
+        assert_eq!(
+            occlusion.surface_height(Point2d::new(0.0, 0.0)),
+            Some(1000.0)
+        );
+        assert_eq!(occlusion.surface_height(Point2d::new(0.0, 400.0)), None);
+    }
+
+    #[test]
+    fn points_near_ridge_are_faded() {
+        let ridge = ElevationTile::new(1, 3, vec![0.0, 1000.0, 0.0]).unwrap();
+        let terrain = TerrainOccluder::new(ridge, Rect::new(-5000.0, -300.0, 5000.0, 300.0));
+        let view = tilted_view();
+
+        let hard = Occlusion::new()
+            .with_occluder(terrain.clone())
+            .with_fade(0.0);
+        let soft = Occlusion::new().with_occluder(terrain).with_fade(1000.0);
+        let point = Point3d::new(0.0, 1000.0, 0.0);
+
+        assert_eq!(hard.visibility(&view, point), 0.0);
+        let faded = soft.visibility(&view, point);
+        assert!(faded > 0.0 && faded < 1.0, "{faded}");
+    }
+}
@@ -1,5 +1,6 @@
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
+use crate::layer::data_provider::HttpClient;
 use crate::platform::PlatformService;
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -24,22 +25,28 @@ impl PlatformService for NativePlatformService {
     }
 
     async fn load_image_url(&self, url: &str) -> Result<DecodedImage, GalileoError> {
-        let image_source = self.load_from_web(url).await?;
+        let image_source = HttpClient::get(&self.http_client, url).await?;
         DecodedImage::new(&image_source)
     }
 
     async fn load_bytes_from_url(&self, url: &str) -> Result<Bytes, GalileoError> {
-        self.load_from_web(url).await
+        HttpClient::get(&self.http_client, url).await
     }
 }
 
-impl NativePlatformService {
-    async fn load_from_web(&self, url: &str) -> Result<Bytes, GalileoError> {
-        let response = self.http_client.get(url).send().await?;
-        if !response.status().is_success() {
+#[async_trait]
+impl HttpClient for reqwest::Client {
+    async fn get(&self, url: &str) -> Result<Bytes, GalileoError> {
+        let response = reqwest::Client::get(self, url).send().await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::GONE {
+            return Err(GalileoError::NotFound);
+        }
+
+        if !status.is_success() {
             info!(
                 "Failed to load {url}: {}, {:?}",
-                response.status(),
+                status,
                 response.text().await
             );
             return Err(GalileoError::IO);
@@ -19,6 +19,7 @@ impl MapBuilder {
             view: None,
             layers: vec![],
             event_handlers: vec![],
+            interaction: Default::default(),
             window: None,
             event_loop: None,
         }
@@ -91,6 +91,14 @@ impl PlatformService for WebPlatformService {
 
         assert!(resp_value.is_instance_of::<Response>());
         let resp: Response = resp_value.dyn_into()?;
+        if resp.status() == 404 || resp.status() == 410 {
+            return Err(GalileoError::NotFound);
+        }
+
+        if !resp.ok() {
+            log::info!("Failed to load {url}: {}", resp.status());
+            return Err(GalileoError::IO);
+        }
 
         let bytes_val = JsFuture::from(resp.array_buffer()?).await?;
         let array = Uint8Array::new(&bytes_val);
@@ -47,6 +47,7 @@ impl MapBuilder {
             view: None,
             layers: vec![],
             event_handlers: vec![],
+            interaction: Default::default(),
             window: None,
             event_loop: None,
         }
@@ -79,7 +80,7 @@ impl MapBuilder {
 
         let _ = window.request_inner_size(PhysicalSize { width, height });
 
-        sleep(1).await;
+        crate::async_runtime::sleep(std::time::Duration::from_millis(1)).await;
 
         self.window = Some(window);
         self.event_loop = Some(event_loop);
@@ -109,16 +110,3 @@ impl MapBuilder {
         self
     }
 }
-
-async fn sleep(duration: i32) {
-    let mut cb = |resolve: js_sys::Function, _reject: js_sys::Function| {
-        web_sys::window()
-            .unwrap()
-            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, duration)
-            .unwrap();
-    };
-
-    let p = js_sys::Promise::new(&mut cb);
-
-    wasm_bindgen_futures::JsFuture::from(p).await.unwrap();
-}
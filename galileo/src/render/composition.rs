@@ -0,0 +1,647 @@
+//! Composition of a printable page with a map and the elements around it.
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::map::Map;
+use crate::render::{SoftwareRenderer, BASE_DPI};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Rect, Size};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint};
+
+const MM_PER_INCH: f64 = 25.4;
+
+// Sizes of the page elements in logical pixels (1/96 inch).
+const TITLE_FONT_SIZE: f64 = 24.0;
+const TEXT_FONT_SIZE: f64 = 12.0;
+const GAP: f64 = 8.0;
+const ELEMENT_OFFSET: f64 = 12.0;
+const FRAME_WIDTH: f64 = 1.0;
+const LEGEND_WIDTH: f64 = 160.0;
+const LEGEND_ROW_HEIGHT: f64 = 20.0;
+const LEGEND_SWATCH_SIZE: f64 = 14.0;
+const SCALE_BAR_HEIGHT: f64 = 6.0;
+const SCALE_BAR_SEGMENTS: usize = 4;
+const NORTH_ARROW_SIZE: f64 = 32.0;
+
+/// Number of samples per pixel along each axis used to smooth the edges of the page elements.
+const SAMPLES: usize = 4;
+
+/// Function that draws a text with the given font size (in pixels of the page image) and color into an image.
+pub type TextRenderer = Box<dyn Fn(&str, f64, Color) -> Option<DecodedImage> + Send + Sync>;
+
+/// Entry of the legend of a [`PrintComposition`]: a color swatch with a label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntry {
+    /// Text of the entry.
+    pub label: String,
+    /// Color of the swatch.
+    pub color: Color,
+}
+
+impl LegendEntry {
+    /// Creates a new entry.
+    pub fn new(label: impl Into<String>, color: Color) -> Self {
+        Self {
+            label: label.into(),
+            color,
+        }
+    }
+}
+
+/// Page with a map rendered at the given paper size and DPI, for reports and printed maps.
+///
+/// The page consists of the map frame and optional elements around it: a title above the map, a legend to the right
+/// of it, and a scale bar and a north arrow drawn over the bottom-left and top-right corners of the map. All the
+/// elements are sized for the paper, so symbols, lines and the elements have the same physical size at any DPI.
+///
+/// The map is rendered with the [`SoftwareRenderer`], so no GPU is required. By default the map frame shows the same
+/// area as the view of the map. Layers that load their data for the view (e.g. tile layers) must have it loaded
+/// before the page is rendered: call [`PrintComposition::prepare`] and wait until the layers are ready.
+///
+/// Galileo does not render texts by itself, so the title and the labels are drawn with the function set by
+/// [`PrintComposition::with_text_renderer`]. Without it the texts are left out, but the space for them is still
+/// reserved.
+///
+/// ```
+/// use galileo::galileo_types::cartesian::{Point2d, Size};
+/// use galileo::render::{LegendEntry, PrintComposition};
+/// use galileo::{Color, DummyMessenger, Map, MapView};
+///
+/// let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 100.0)
+///     .with_size(Size::new(800.0, 600.0));
+/// let map = Map::new(view, vec![], None::<DummyMessenger>);
+///
+/// let composition = PrintComposition::new(148.0, 105.0, 150.0)
+///     .with_title("Land use")
+///     .with_legend(vec![LegendEntry::new("Forest", Color::GREEN)])
+///     .with_scale(50_000.0);
+///
+/// let image = composition.render(&map).expect("page is too small");
+/// assert_eq!(image.dimensions, (874, 620));
+/// ```
+pub struct PrintComposition {
+    page_size: Size,
+    dpi: f64,
+    margin: f64,
+    background: Color,
+    title: Option<String>,
+    legend: Vec<LegendEntry>,
+    scale_bar: bool,
+    north_arrow: bool,
+    scale: Option<f64>,
+    text_renderer: Option<TextRenderer>,
+}
+
+impl PrintComposition {
+    /// Creates a page of the given size in millimeters, rendered with the given number of dots per inch. The page has
+    /// 10 mm margins, a scale bar and a north arrow.
+    pub fn new(width_mm: f64, height_mm: f64, dpi: f64) -> Self {
+        Self {
+            page_size: Size::new(width_mm, height_mm),
+            dpi,
+            margin: 10.0,
+            background: Color::WHITE,
+            title: None,
+            legend: vec![],
+            scale_bar: true,
+            north_arrow: true,
+            scale: None,
+            text_renderer: None,
+        }
+    }
+
+    /// Sets the margins of the page in millimeters.
+    pub fn with_margin(mut self, margin_mm: f64) -> Self {
+        self.margin = margin_mm.max(0.0);
+        self
+    }
+
+    /// Sets the color of the page and the background of the map.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Sets the title displayed above the map.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the entries of the legend displayed to the right of the map. No legend is displayed if the list is empty.
+    pub fn with_legend(mut self, entries: Vec<LegendEntry>) -> Self {
+        self.legend = entries;
+        self
+    }
+
+    /// Sets whether the scale bar is displayed.
+    pub fn with_scale_bar(mut self, show: bool) -> Self {
+        self.scale_bar = show;
+        self
+    }
+
+    /// Sets whether the north arrow is displayed.
+    pub fn with_north_arrow(mut self, show: bool) -> Self {
+        self.north_arrow = show;
+        self
+    }
+
+    /// Renders the map at the given scale (e.g. `25_000.0` for 1:25 000) at the center of the map frame, instead of
+    /// showing the area of the map view.
+    pub fn with_scale(mut self, denominator: f64) -> Self {
+        self.scale = Some(denominator);
+        self
+    }
+
+    /// Sets the function used to draw the title and the labels.
+    pub fn with_text_renderer(
+        mut self,
+        renderer: impl Fn(&str, f64, Color) -> Option<DecodedImage> + Send + Sync + 'static,
+    ) -> Self {
+        self.text_renderer = Some(Box::new(renderer));
+        self
+    }
+
+    /// Size of the page image in pixels.
+    pub fn image_size(&self) -> Size<u32> {
+        let to_pixels = |mm: f64| (mm / MM_PER_INCH * self.dpi).round().max(0.0) as u32;
+        Size::new(
+            to_pixels(self.page_size.width()),
+            to_pixels(self.page_size.height()),
+        )
+    }
+
+    /// View with which the map is rendered into the map frame of the page, based on the given view of the map.
+    ///
+    /// The size of the returned view is given in logical pixels, so that the symbols of the map have the same size on
+    /// paper as on a screen with [`BASE_DPI`].
+    pub fn map_view(&self, view: &MapView) -> Result<MapView, GalileoError> {
+        let frame = self.layout()?.map;
+        let size = Size::new(self.logical(frame.width()), self.logical(frame.height()));
+
+        let view_size = view.size();
+        let fit = if view_size.width() > 0.0 && view_size.height() > 0.0 {
+            (view_size.width() / size.width()).max(view_size.height() / size.height())
+        } else {
+            1.0
+        };
+        let fitted = view
+            .with_size(size)
+            .with_resolution(view.resolution() * fit);
+
+        let Some(denominator) = self.scale else {
+            return Ok(fitted);
+        };
+
+        // Ground distance covered by a logical pixel of paper at the given scale.
+        let target = denominator * MM_PER_INCH / 1000.0 / BASE_DPI;
+        let center = Point2d::new(size.width() / 2.0, size.height() / 2.0);
+        let ground = fitted
+            .ground_resolution(center)
+            .unwrap_or(fitted.resolution());
+
+        Ok(fitted.with_resolution(fitted.resolution() * target / ground))
+    }
+
+    /// Calls [`Layer::prepare`](crate::layer::Layer::prepare) for all the visible layers of the map with the view
+    /// of the map frame, so that they start loading the data needed to render the page.
+    pub fn prepare(&self, map: &Map) -> Result<(), GalileoError> {
+        let view = self.map_view(map.view())?;
+        for layer in map.layers().iter_visible() {
+            layer.prepare(&view);
+        }
+
+        Ok(())
+    }
+
+    /// Renders the page with the layers of the map into an image.
+    pub fn render(&self, map: &Map) -> Result<DecodedImage, GalileoError> {
+        let layout = self.layout()?;
+        let view = self.map_view(map.view())?;
+        let size = self.image_size();
+        let mut page = Page::new(
+            size.width() as usize,
+            size.height() as usize,
+            self.background,
+        );
+
+        let frame = layout.map;
+        let (frame_width, frame_height) = (frame.width() as u32, frame.height() as u32);
+        let mut renderer = SoftwareRenderer::new(Size::new(frame_width, frame_height));
+        renderer.set_background(self.background);
+        renderer.render_view(map, &view);
+        let map_image = DecodedImage::from_rgba(renderer.get_image(), frame_width, frame_height)?;
+        page.draw_image(&map_image, frame.x_min(), frame.y_min());
+        page.stroke_rect(frame, self.px(FRAME_WIDTH), Color::BLACK);
+
+        if self.scale_bar {
+            self.draw_scale_bar(&mut page, &view, frame);
+        }
+
+        if self.north_arrow {
+            self.draw_north_arrow(&mut page, &view, frame);
+        }
+
+        if let (Some(title), Some(area)) = (&self.title, layout.title) {
+            if let Some(text) = self.text_image(title, TITLE_FONT_SIZE) {
+                let (width, height) = image_size(&text);
+                let x = area.center().x - width / 2.0;
+                let y = area.center().y - height / 2.0;
+                page.draw_image(&text, x, y);
+            }
+        }
+
+        if let Some(area) = layout.legend {
+            self.draw_legend(&mut page, area);
+        }
+
+        Ok(page.into_image())
+    }
+
+    fn layout(&self) -> Result<Layout, GalileoError> {
+        let size = self.image_size();
+        let margin = (self.margin / MM_PER_INCH * self.dpi).round();
+        let (width, height) = (size.width() as f64, size.height() as f64);
+
+        let mut top = margin;
+        let mut right = width - margin;
+        let bottom = height - margin;
+
+        let title = self.title.as_ref().map(|_| {
+            let area = Rect::new(margin, top, right, top + self.px(TITLE_FONT_SIZE * 1.6));
+            top = area.y_max() + self.px(GAP);
+            area
+        });
+
+        let legend = (!self.legend.is_empty()).then(|| {
+            let area = Rect::new(right - self.px(LEGEND_WIDTH), top, right, bottom);
+            right = area.x_min() - self.px(GAP);
+            area
+        });
+
+        let map = Rect::new(margin, top.round(), right.round(), bottom);
+        if map.width() < 1.0 || map.height() < 1.0 {
+            return Err(GalileoError::Generic(
+                "page is too small to fit the map with the composition elements".into(),
+            ));
+        }
+
+        Ok(Layout { title, legend, map })
+    }
+
+    /// Converts logical pixels into the pixels of the page image.
+    fn px(&self, logical: f64) -> f64 {
+        logical * self.dpi / BASE_DPI
+    }
+
+    /// Converts the pixels of the page image into logical pixels.
+    fn logical(&self, px: f64) -> f64 {
+        px * BASE_DPI / self.dpi
+    }
+
+    fn text_image(&self, text: &str, font_size: f64) -> Option<DecodedImage> {
+        let renderer = self.text_renderer.as_ref()?;
+        renderer(text, self.px(font_size), Color::BLACK)
+    }
+
+    fn draw_scale_bar(&self, page: &mut Page, view: &MapView, frame: Rect) {
+        let size = view.size();
+        let center = Point2d::new(size.width() / 2.0, size.height() / 2.0);
+        let Some(ground_resolution) = view.ground_resolution(center) else {
+            return;
+        };
+
+        let length = nice_length(size.width() / 4.0 * ground_resolution);
+        let segment_width = self.px(length / ground_resolution) / SCALE_BAR_SEGMENTS as f64;
+        let height = self.px(SCALE_BAR_HEIGHT);
+        let x = frame.x_min() + self.px(ELEMENT_OFFSET);
+        let y = frame.y_max() - self.px(ELEMENT_OFFSET) - height;
+
+        for segment in 0..SCALE_BAR_SEGMENTS {
+            let color = if segment % 2 == 0 {
+                Color::BLACK
+            } else {
+                Color::WHITE
+            };
+            let x_min = x + segment_width * segment as f64;
+            page.fill_rect(
+                Rect::new(x_min, y, x_min + segment_width, y + height),
+                color,
+            );
+        }
+
+        let bar = Rect::new(
+            x,
+            y,
+            x + segment_width * SCALE_BAR_SEGMENTS as f64,
+            y + height,
+        );
+        page.stroke_rect(bar, self.px(FRAME_WIDTH), Color::BLACK);
+
+        if let Some(text) = self.text_image(&format_distance(length), TEXT_FONT_SIZE) {
+            let (_, text_height) = image_size(&text);
+            page.draw_image(&text, x, y - self.px(GAP / 2.0) - text_height);
+        }
+    }
+
+    fn draw_north_arrow(&self, page: &mut Page, view: &MapView, frame: Rect) {
+        let angle = north_direction(view).unwrap_or(-view.rotation_z());
+        let size = self.px(NORTH_ARROW_SIZE);
+        let center = [
+            frame.x_max() - self.px(ELEMENT_OFFSET) - size / 2.0,
+            frame.y_min() + self.px(ELEMENT_OFFSET) + size / 2.0,
+        ];
+
+        // Arrow pointing up, rotated clockwise by the angle.
+        let (sin, cos) = angle.sin_cos();
+        let point = |x: f64, y: f64| {
+            let (x, y) = (x * size, y * size);
+            [center[0] + x * cos - y * sin, center[1] + x * sin + y * cos]
+        };
+        let tip = point(0.0, -0.5);
+        let notch = point(0.0, 0.25);
+
+        page.fill_polygon(&[tip, point(-0.3, 0.5), notch], Color::BLACK);
+        page.fill_polygon(&[tip, notch, point(0.3, 0.5)], Color::rgb(150, 150, 150));
+    }
+
+    fn draw_legend(&self, page: &mut Page, area: Rect) {
+        let row_height = self.px(LEGEND_ROW_HEIGHT);
+        let swatch_size = self.px(LEGEND_SWATCH_SIZE);
+
+        for (index, entry) in self.legend.iter().enumerate() {
+            let row_center = area.y_min() + row_height * (index as f64 + 0.5);
+            if row_center + row_height / 2.0 > area.y_max() {
+                break;
+            }
+
+            let swatch_y = row_center - swatch_size / 2.0;
+            let swatch = Rect::new(
+                area.x_min(),
+                swatch_y,
+                area.x_min() + swatch_size,
+                swatch_y + swatch_size,
+            );
+            page.fill_rect(swatch, entry.color);
+            page.stroke_rect(swatch, self.px(FRAME_WIDTH), Color::BLACK);
+
+            if let Some(text) = self.text_image(&entry.label, TEXT_FONT_SIZE) {
+                let (_, text_height) = image_size(&text);
+                page.draw_image(
+                    &text,
+                    swatch.x_max() + self.px(GAP),
+                    row_center - text_height / 2.0,
+                );
+            }
+        }
+    }
+}
+
+/// Areas of the page elements in pixels of the page image.
+struct Layout {
+    title: Option<Rect>,
+    legend: Option<Rect>,
+    map: Rect,
+}
+
+/// Largest round length (1, 2 or 5 multiplied by a power of 10) not exceeding `max_length`.
+fn nice_length(max_length: f64) -> f64 {
+    let magnitude = 10f64.powf(max_length.log10().floor());
+    [5.0, 2.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|&length| length <= max_length)
+        .unwrap_or(magnitude)
+}
+
+fn format_distance(meters: f64) -> String {
+    if meters >= 1000.0 {
+        format!("{} km", meters / 1000.0)
+    } else {
+        format!("{meters} m")
+    }
+}
+
+/// Angle of the direction to the north at the center of the view, measured clockwise from the top of the screen.
+fn north_direction(view: &MapView) -> Option<f64> {
+    let size = view.size();
+    let geo = view.screen_to_map_geo(Point2d::new(size.width() / 2.0, size.height() / 2.0))?;
+    let north = GeoPoint2d::latlon((geo.lat() + 0.01).min(90.0), geo.lon());
+    let projection = view.crs().get_projection::<GeoPoint2d, Point2d>()?;
+
+    let from = view.map_to_screen(projection.project(&geo)?).position()?;
+    let to = view.map_to_screen(projection.project(&north)?).position()?;
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    if dx == 0.0 && dy == 0.0 {
+        return None;
+    }
+
+    Some(dx.atan2(-dy))
+}
+
+fn image_size(image: &DecodedImage) -> (f64, f64) {
+    (image.dimensions.0 as f64, image.dimensions.1 as f64)
+}
+
+/// Page image with simple drawing operations for the composition elements.
+struct Page {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Page {
+    fn new(width: usize, height: usize, background: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    fn blend(&mut self, x: usize, y: usize, color: Color, coverage: f64) {
+        let pixel = &mut self.pixels[y * self.width + x];
+        *pixel = color.with_opacity(coverage).over(pixel);
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let [a, b, c, d] = rect.into_quadrangle();
+        self.fill_polygon(&[a, b, c, d].map(|p| [p.x, p.y]), color);
+    }
+
+    fn stroke_rect(&mut self, rect: Rect, width: f64, color: Color) {
+        let (x_min, y_min, x_max, y_max) = (rect.x_min(), rect.y_min(), rect.x_max(), rect.y_max());
+        self.fill_rect(Rect::new(x_min, y_min, x_max, y_min + width), color);
+        self.fill_rect(Rect::new(x_min, y_max - width, x_max, y_max), color);
+        self.fill_rect(
+            Rect::new(x_min, y_min + width, x_min + width, y_max - width),
+            color,
+        );
+        self.fill_rect(
+            Rect::new(x_max - width, y_min + width, x_max, y_max - width),
+            color,
+        );
+    }
+
+    /// Fills a convex polygon, with the edges smoothed by supersampling.
+    fn fill_polygon(&mut self, points: &[[f64; 2]], color: Color) {
+        let bound = |axis: usize, f: fn(f64, f64) -> f64, init: f64| {
+            points.iter().map(|p| p[axis]).fold(init, f)
+        };
+        let x_from = bound(0, f64::min, f64::MAX).floor().max(0.0) as usize;
+        let y_from = bound(1, f64::min, f64::MAX).floor().max(0.0) as usize;
+        let x_to = (bound(0, f64::max, f64::MIN).ceil().max(0.0) as usize).min(self.width);
+        let y_to = (bound(1, f64::max, f64::MIN).ceil().max(0.0) as usize).min(self.height);
+
+        for y in y_from..y_to {
+            for x in x_from..x_to {
+                let mut covered = 0;
+                for sy in 0..SAMPLES {
+                    for sx in 0..SAMPLES {
+                        let sample = [
+                            x as f64 + (sx as f64 + 0.5) / SAMPLES as f64,
+                            y as f64 + (sy as f64 + 0.5) / SAMPLES as f64,
+                        ];
+                        if is_inside_convex(points, sample) {
+                            covered += 1;
+                        }
+                    }
+                }
+
+                if covered > 0 {
+                    self.blend(x, y, color, covered as f64 / (SAMPLES * SAMPLES) as f64);
+                }
+            }
+        }
+    }
+
+    fn draw_image(&mut self, image: &DecodedImage, x: f64, y: f64) {
+        let (x, y) = (x.round() as i64, y.round() as i64);
+        let (width, height) = (image.dimensions.0 as i64, image.dimensions.1 as i64);
+
+        for row in 0..height {
+            for column in 0..width {
+                let (page_x, page_y) = (x + column, y + row);
+                if page_x < 0
+                    || page_y < 0
+                    || page_x >= self.width as i64
+                    || page_y >= self.height as i64
+                {
+                    continue;
+                }
+
+                let offset = ((row * width + column) * 4) as usize;
+                let Some(&[r, g, b, a]) = image.bytes.get(offset..offset + 4) else {
+                    return;
+                };
+                self.blend(
+                    page_x as usize,
+                    page_y as usize,
+                    Color::rgba(r, g, b, a),
+                    1.0,
+                );
+            }
+        }
+    }
+
+    fn into_image(self) -> DecodedImage {
+        DecodedImage {
+            bytes: self.pixels.iter().flat_map(|c| c.to_u8_array()).collect(),
+            dimensions: (self.width as u32, self.height as u32),
+        }
+    }
+}
+
+fn is_inside_convex(points: &[[f64; 2]], p: [f64; 2]) -> bool {
+    let mut sign = 0.0;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        let cross = (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0]);
+        if cross != 0.0 {
+            if sign * cross < 0.0 {
+                return false;
+            }
+            sign = cross;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DummyMessenger;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn map() -> Map {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(200.0, 100.0));
+        Map::new(view, vec![], None::<DummyMessenger>)
+    }
+
+    fn pixel(image: &DecodedImage, x: f64, y: f64) -> [u8; 4] {
+        let offset = (y as usize * image.dimensions.0 as usize + x as usize) * 4;
+        [0, 1, 2, 3].map(|i| image.bytes[offset + i])
+    }
+
+    #[test]
+    fn page_layout_at_print_dpi() {
+        let texts = Arc::new(AtomicUsize::new(0));
+        let counter = texts.clone();
+        let composition = PrintComposition::new(100.0, 50.0, 192.0)
+            .with_background(Color::rgb(250, 250, 250))
+            .with_title("Title")
+            .with_legend(vec![LegendEntry::new("Water", Color::BLUE)])
+            .with_text_renderer(move |_, size, _| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                DecodedImage::from_rgba(vec![0; 16], 2, 2)
+                    .ok()
+                    .filter(|_| size > 0.0)
+            });
+
+        let image = composition.render(&map()).unwrap();
+        assert_eq!(image.dimensions, (756, 378));
+        // Title, legend label and scale bar label.
+        assert_eq!(texts.load(Ordering::Relaxed), 3);
+
+        let layout = composition.layout().unwrap();
+        let legend = layout.legend.unwrap();
+        assert_eq!(legend.x_max(), 756.0 - 76.0);
+        assert_eq!(
+            pixel(&image, legend.x_min() + 14.0, legend.y_min() + 20.0),
+            [0, 0, 255, 255]
+        );
+
+        let frame = layout.map;
+        assert_eq!(frame.y_min(), 76.0 + 77.0 + 16.0);
+        assert_eq!(
+            pixel(&image, frame.x_min(), frame.center().y),
+            [0, 0, 0, 255]
+        );
+        assert_eq!(pixel(&image, 10.0, 10.0), [250, 250, 250, 255]);
+
+        // Symbols are scaled by 2 at 192 DPI, so the view is half the size of the frame.
+        let view = composition.map_view(map().view()).unwrap();
+        assert_eq!(view.size().width(), frame.width() / 2.0);
+    }
+
+    #[test]
+    fn map_scale() {
+        let composition = PrintComposition::new(100.0, 100.0, 300.0).with_scale(9600.0);
+        let view = composition.map_view(map().view()).unwrap();
+
+        // 1 inch on paper is 96 logical pixels and 9600 inches (243.84 m) on the ground.
+        let center = Point2d::new(view.size().width() / 2.0, view.size().height() / 2.0);
+        let ground_resolution = view.ground_resolution(center).unwrap();
+        assert!((ground_resolution - 2.54).abs() < 1e-6);
+
+        assert_eq!(nice_length(0.9), 0.5);
+        assert_eq!(nice_length(1750.0), 1000.0);
+        assert_eq!(nice_length(270.0), 200.0);
+        assert_eq!(format_distance(2000.0), "2 km");
+    }
+}
@@ -0,0 +1,337 @@
+use crate::error::GalileoError;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+static NEXT_COMPUTE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Function that does the work of a [`ComputeShader`] on CPU when the canvas cannot run compute shaders. It receives
+/// the contents of the buffers of the shader in the order they were added, and the number of workgroups the shader
+/// was dispatched with.
+pub type ComputeFallback = dyn Fn(&mut [&mut [u8]], [u32; 3]) + Send + Sync;
+
+/// Storage buffer used by a [`ComputeShader`].
+///
+/// The buffer keeps a copy of its contents on CPU. Data written with [`ComputeBuffer::write`] is uploaded to GPU
+/// before the next dispatch of a shader using the buffer. Results of the GPU computation are copied back to CPU only
+/// for buffers created [with readback](ComputeBuffer::with_readback), and they arrive asynchronously, usually a frame
+/// or two after the dispatch. Use [`ComputeBuffer::revision`] to find out if new results are available.
+///
+/// Clones of a buffer share the same contents.
+#[derive(Debug, Clone)]
+pub struct ComputeBuffer {
+    id: u64,
+    readback: bool,
+    state: Arc<Mutex<BufferState>>,
+}
+
+#[derive(Debug)]
+struct BufferState {
+    data: Vec<u8>,
+    /// Set if the data was changed on CPU and must be uploaded to GPU.
+    upload: bool,
+    revision: u64,
+}
+
+impl ComputeBuffer {
+    /// Creates a new buffer with the given initial contents. The size of the buffer cannot be changed later.
+    pub fn new(data: &[u8]) -> Self {
+        Self {
+            id: NEXT_COMPUTE_ID.fetch_add(1, Ordering::Relaxed),
+            readback: false,
+            state: Arc::new(Mutex::new(BufferState {
+                data: data.to_vec(),
+                upload: true,
+                revision: 0,
+            })),
+        }
+    }
+
+    /// Creates a new buffer of `size` bytes filled with zeros.
+    pub fn zeroed(size: usize) -> Self {
+        Self::new(&vec![0; size])
+    }
+
+    /// Copies the contents of the buffer back to CPU after every dispatch, so they can be accessed with
+    /// [`ComputeBuffer::read`].
+    pub fn with_readback(mut self) -> Self {
+        self.readback = true;
+        self
+    }
+
+    /// Size of the buffer in bytes.
+    pub fn size(&self) -> usize {
+        self.state.lock().expect("mutex is poisoned").data.len()
+    }
+
+    /// Replaces the contents of the buffer. The new contents are used by the next dispatch.
+    ///
+    /// Returns an error if the size of the data differs from the size of the buffer.
+    pub fn write(&self, data: &[u8]) -> Result<(), GalileoError> {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        if state.data.len() != data.len() {
+            return Err(GalileoError::Generic(format!(
+                "compute buffer size is {}, but {} bytes given",
+                state.data.len(),
+                data.len()
+            )));
+        }
+
+        state.data.copy_from_slice(data);
+        state.upload = true;
+        Ok(())
+    }
+
+    /// Contents of the buffer as last known on CPU: the written data or the results of the last computation that were
+    /// read back.
+    pub fn read(&self) -> Vec<u8> {
+        self.state.lock().expect("mutex is poisoned").data.clone()
+    }
+
+    /// Number that is incremented every time the results of a computation are written into the buffer.
+    pub fn revision(&self) -> u64 {
+        self.state.lock().expect("mutex is poisoned").revision
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub(crate) fn is_readback(&self) -> bool {
+        self.readback
+    }
+
+    /// Returns the contents of the buffer if they must be uploaded to GPU. If `force` is set, the contents are
+    /// returned anyway, e.g. when the GPU buffer was just created.
+    pub(crate) fn take_upload(&self, force: bool) -> Option<Vec<u8>> {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        if !state.upload && !force {
+            return None;
+        }
+
+        state.upload = false;
+        Some(state.data.clone())
+    }
+
+    /// Stores the results of a computation. Data longer than the buffer (e.g. padding of the GPU buffer) is ignored.
+    pub(crate) fn set_computed(&self, data: &[u8]) {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let len = state.data.len().min(data.len());
+        state.data[..len].copy_from_slice(&data[..len]);
+        state.revision += 1;
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakComputeBuffer {
+        WeakComputeBuffer {
+            id: self.id,
+            readback: self.readback,
+            state: Arc::downgrade(&self.state),
+        }
+    }
+}
+
+/// Reference to a [`ComputeBuffer`] that does not keep it alive. Used by the renderers to release GPU resources of
+/// dropped buffers.
+#[derive(Debug, Clone)]
+pub(crate) struct WeakComputeBuffer {
+    id: u64,
+    readback: bool,
+    state: Weak<Mutex<BufferState>>,
+}
+
+impl WeakComputeBuffer {
+    pub fn upgrade(&self) -> Option<ComputeBuffer> {
+        Some(ComputeBuffer {
+            id: self.id,
+            readback: self.readback,
+            state: self.state.upgrade()?,
+        })
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.state.strong_count() == 0
+    }
+}
+
+/// WGSL compute shader that a layer can run with [`Canvas::dispatch_compute`](super::Canvas::dispatch_compute),
+/// e.g. to accumulate a heatmap, advect particles or calculate terrain normals.
+///
+/// The shader module must contain `cs_main` compute entry point. Buffers of the shader are bound in bind group `0`,
+/// at the bindings equal to the order they were added with [`ComputeShader::with_buffer`], as read-write storage
+/// buffers:
+///
+/// ```wgsl
+/// @group(0) @binding(0)
+/// var<storage, read_write> values: array<f32>;
+///
+/// @compute @workgroup_size(64)
+/// fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+///     if (id.x < arrayLength(&values)) {
+///         values[id.x] = values[id.x] * 2.0;
+///     }
+/// }
+/// ```
+///
+/// Compute shaders are only run by the `wgpu` renderer on backends that support them (not on WebGL and GL). Other
+/// canvases run the [fallback](ComputeShader::with_fallback) function of the shader instead, so layers using compute
+/// shaders should provide one to work everywhere. Errors in the shader source are reported by `wgpu` as validation
+/// errors when the shader is first dispatched.
+///
+/// Compute shaders are available with the `compute` feature of the crate.
+#[derive(Clone)]
+pub struct ComputeShader {
+    id: u64,
+    source: Arc<str>,
+    buffers: Vec<ComputeBuffer>,
+    fallback: Option<Arc<ComputeFallback>>,
+}
+
+impl ComputeShader {
+    /// Creates a new shader from WGSL source.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            id: NEXT_COMPUTE_ID.fetch_add(1, Ordering::Relaxed),
+            source: source.into().into(),
+            buffers: vec![],
+            fallback: None,
+        }
+    }
+
+    /// Adds a storage buffer to the shader at the next binding.
+    pub fn with_buffer(mut self, buffer: ComputeBuffer) -> Self {
+        self.buffers.push(buffer);
+        self
+    }
+
+    /// Sets the function that does the work of the shader on CPU when the canvas cannot run compute shaders.
+    pub fn with_fallback(
+        mut self,
+        fallback: impl Fn(&mut [&mut [u8]], [u32; 3]) + Send + Sync + 'static,
+    ) -> Self {
+        self.fallback = Some(Arc::new(fallback));
+        self
+    }
+
+    /// Source code of the shader.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Buffers of the shader in the order of their bindings.
+    pub fn buffers(&self) -> &[ComputeBuffer] {
+        &self.buffers
+    }
+
+    /// Returns true if the shader has a CPU fallback.
+    pub fn has_fallback(&self) -> bool {
+        self.fallback.is_some()
+    }
+
+    /// Unique id of the shader. Clones of a shader share the same id.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns an error if the same buffer is bound to the shader more than once.
+    pub(crate) fn validate_buffers(&self) -> Result<(), GalileoError> {
+        for (index, buffer) in self.buffers.iter().enumerate() {
+            if self.buffers[..index]
+                .iter()
+                .any(|other| other.id() == buffer.id())
+            {
+                return Err(GalileoError::Generic(format!(
+                    "buffer at binding {index} is bound to the shader more than once"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the fallback function on the CPU contents of the buffers.
+    ///
+    /// Returns an error if the shader does not have a fallback, or if the same buffer is bound to the shader twice.
+    pub(crate) fn run_fallback(&self, workgroups: [u32; 3]) -> Result<(), GalileoError> {
+        let Some(fallback) = &self.fallback else {
+            return Err(GalileoError::Generic(
+                "compute shaders are not supported by the canvas, and the shader has no fallback"
+                    .into(),
+            ));
+        };
+
+        self.validate_buffers()?;
+
+        let mut data: Vec<Vec<u8>> = self.buffers.iter().map(ComputeBuffer::read).collect();
+        let mut slices: Vec<&mut [u8]> = data.iter_mut().map(Vec::as_mut_slice).collect();
+        fallback(&mut slices, workgroups);
+
+        for (buffer, data) in self.buffers.iter().zip(&data) {
+            buffer.set_computed(data);
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for ComputeShader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComputeShader")
+            .field("id", &self.id)
+            .field("source", &self.source)
+            .field("buffers", &self.buffers)
+            .field("has_fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_checks_size() {
+        let buffer = ComputeBuffer::zeroed(8);
+        assert!(buffer.write(&[1; 4]).is_err());
+        assert!(buffer.write(&[1; 8]).is_ok());
+
+        assert_eq!(buffer.take_upload(false), Some(vec![1; 8]));
+        assert_eq!(buffer.take_upload(false), None);
+        assert_eq!(buffer.take_upload(true), Some(vec![1; 8]));
+    }
+
+    #[test]
+    fn fallback_updates_buffers() {
+        let input = ComputeBuffer::new(&[1, 2, 3, 4]);
+        let output = ComputeBuffer::zeroed(4).with_readback();
+        let shader = ComputeShader::new("")
+            .with_buffer(input.clone())
+            .with_buffer(output.clone())
+            .with_fallback(|buffers, workgroups| {
+                let (input, output) = buffers.split_at_mut(1);
+                for (out, value) in output[0].iter_mut().zip(input[0].iter()) {
+                    *out = value * workgroups[0] as u8;
+                }
+            });
+
+        shader.run_fallback([2, 1, 1]).unwrap();
+        assert_eq!(output.read(), vec![2, 4, 6, 8]);
+        assert_eq!(output.revision(), 1);
+
+        let without_fallback = ComputeShader::new("").with_buffer(output.clone());
+        assert!(without_fallback.run_fallback([1, 1, 1]).is_err());
+
+        let duplicated = shader.clone().with_buffer(input);
+        assert!(duplicated.run_fallback([1, 1, 1]).is_err());
+    }
+
+    #[test]
+    fn weak_buffer_does_not_keep_buffer_alive() {
+        let buffer = ComputeBuffer::zeroed(4);
+        let weak = buffer.downgrade();
+        assert_eq!(weak.upgrade().map(|b| b.id()), Some(buffer.id()));
+
+        drop(buffer);
+        assert!(weak.is_dropped());
+        assert!(weak.upgrade().is_none());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::error::GalileoError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+static NEXT_SHADER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// WGSL shader that replaces the default shader of map-referenced primitives (polygons and lines) of a layer.
+///
+/// The shader module must contain `vs_main` vertex entry point and `fs_main` fragment entry point. The easiest way to
+/// write a custom shader is to start with [`CustomShader::DEFAULT_MAP_REF_SOURCE`] and modify it.
+///
+/// # Inputs
+///
+/// Vertex buffer of the shader has the following layout:
+///
+/// ```wgsl
+/// struct VertexInput {
+///     // Position of the vertex in map coordinates.
+///     @location(0) position: vec3<f32>,
+///     // sRGB encoded color of the primitive, with components in [0.0, 1.0] range.
+///     @location(1) color: vec4<f32>,
+///     // Offset of the vertex from the position in pixels, used to give lines their width.
+///     @location(2) norm: vec2<f32>,
+///     // Maximum length of the offset in map units.
+///     @location(3) norm_limit: f32,
+///     // Distance from the start of the line in map units and position across the line from -1.0 to 1.0.
+///     // Zero for polygons.
+///     @location(4) line_position: vec2<f32>,
+///     // Line pattern (length, gap, speed, kind), see `line_pattern_alpha` below. Zero for solid lines and polygons.
+///     @location(5) pattern: vec4<f32>,
+///     // Color at the second resolution of the zoom interpolation. Same as `color` if there is no interpolation.
+///     @location(6) zoom_color: vec4<f32>,
+///     // Zoom interpolation parameters, see `zoom_factor` below.
+///     @location(7) zoom: vec4<f32>,
+/// }
+/// ```
+///
+/// Bind group `0` contains the view transformation uniform at binding `0`, visible in vertex and fragment stages:
+///
+/// ```wgsl
+/// struct ViewUniform {
+///     // Transforms map coordinates into clip coordinates.
+///     view_proj: mat4x4<f32>,
+///     // Rotation of the view.
+///     view_rotation: mat4x4<f32>,
+///     // (1.0 / width, 1.0 / height) of the render target in pixels.
+///     inv_screen_size: vec2<f32>,
+///     // Resolution of the view (map units per pixel).
+///     resolution: f32,
+///     // 1.0 if the render target stores sRGB encoded values without automatic conversion, 0.0 otherwise.
+///     encode_srgb: f32,
+///     // Time in seconds since the renderer was created, used to animate line patterns.
+///     time: f32,
+///     // Rotation of the map around the vertical axis in radians.
+///     rotation_z: f32,
+///     // Opacity the bundles are drawn with (see `RenderOptions::opacity`).
+///     opacity: f32,
+/// }
+///
+/// @group(0) @binding(0)
+/// var<uniform> transform: ViewUniform;
+/// ```
+///
+/// If the shader is created [with uniform data](CustomShader::with_uniform), bind group `1` contains a uniform
+/// buffer with this data at binding `0`, visible in both vertex and fragment stages. The layout of the data must
+/// match the declaration of the uniform in the shader, including WGSL alignment rules.
+///
+/// # Colors
+///
+/// The shader source is prepended with color conversion functions, so they don't need to be declared:
+///
+/// * `linear_color(color: vec4<f32>) -> vec4<f32>` converts sRGB encoded color (e.g. the vertex color) into linear
+///   space;
+/// * `output_color(color: vec4<f32>, encode_srgb: f32) -> vec4<f32>` converts linear color into the value to be
+///   written into the render target. Fragment shaders should return `output_color(color, transform.encode_srgb)`
+///   to be displayed correctly with any render target format;
+/// * `with_opacity(color: vec4<f32>, opacity: f32) -> vec4<f32>` multiplies the alpha of the color by the opacity.
+///   Shaders should apply it with `transform.opacity` before returning the color to respect the
+///   [opacity](crate::render::RenderOptions::opacity) of the draw call.
+///
+/// # Line patterns
+///
+/// Function `line_pattern_alpha(line_position: vec2<f32>, pattern: vec4<f32>, resolution: f32, time: f32) -> f32` is
+/// also available. It returns `0.0` for the fragments in the gaps of a [dashed or arrowed](crate::render::LinePattern)
+/// line and `1.0` otherwise.
+///
+/// # Zoom interpolation
+///
+/// Function `zoom_factor(zoom: vec4<f32>, resolution: f32) -> f32` returns the factor of the
+/// [zoom interpolation](crate::render::ZoomInterpolation) of the vertex at the given resolution. The color of the
+/// vertex is `mix(color, zoom_color, factor)`, and its `norm` is scaled by `mix(zoom.z, zoom.w, factor)`.
+///
+/// The output of the fragment shader is blended with the render target using alpha blending.
+///
+/// Custom shaders are only used by GPU rendering backends. Other backends render the primitives with their default
+/// pipelines. Errors in the shader source are reported by `wgpu` as validation errors when the layer is first
+/// rendered.
+#[derive(Debug, Clone)]
+pub struct CustomShader {
+    id: u64,
+    source: Arc<str>,
+    uniform: Option<Arc<Mutex<Vec<u8>>>>,
+}
+
+impl CustomShader {
+    /// Source of the default shader for map-referenced primitives.
+    pub const DEFAULT_MAP_REF_SOURCE: &'static str =
+        include_str!("wgpu/pipelines/shaders/map_ref.wgsl");
+
+    /// Creates a new shader from WGSL source.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            id: NEXT_SHADER_ID.fetch_add(1, Ordering::Relaxed),
+            source: source.into().into(),
+            uniform: None,
+        }
+    }
+
+    /// Adds a uniform buffer with the given initial data to the shader. The size of the buffer cannot be changed
+    /// later.
+    pub fn with_uniform(mut self, data: &[u8]) -> Self {
+        self.uniform = Some(Arc::new(Mutex::new(data.to_vec())));
+        self
+    }
+
+    /// Source code of the shader.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Replaces the contents of the uniform buffer. The new contents are used starting from the next frame.
+    ///
+    /// Returns an error if the shader does not have a uniform buffer, or if the size of the data differs from the
+    /// initial one.
+    pub fn set_uniform(&self, data: &[u8]) -> Result<(), GalileoError> {
+        let Some(uniform) = &self.uniform else {
+            return Err(GalileoError::Generic(
+                "shader does not have a uniform buffer".into(),
+            ));
+        };
+
+        let mut uniform = uniform.lock().expect("mutex is poisoned");
+        if uniform.len() != data.len() {
+            return Err(GalileoError::Generic(format!(
+                "uniform buffer size is {}, but {} bytes given",
+                uniform.len(),
+                data.len()
+            )));
+        }
+
+        uniform.copy_from_slice(data);
+        Ok(())
+    }
+
+    /// Unique id of the shader. Clones of a shader share the same id.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Current contents of the uniform buffer.
+    pub(crate) fn uniform_data(&self) -> Option<Vec<u8>> {
+        self.uniform
+            .as_ref()
+            .map(|uniform| uniform.lock().expect("mutex is poisoned").clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_uniform_checks_size() {
+        let shader = CustomShader::new(CustomShader::DEFAULT_MAP_REF_SOURCE);
+        assert!(shader.set_uniform(&[0; 16]).is_err());
+
+        let shader = shader.with_uniform(&[0; 16]);
+        let clone = shader.clone();
+        assert!(shader.set_uniform(&[1; 16]).is_ok());
+        assert!(shader.set_uniform(&[1; 8]).is_err());
+
+        assert_eq!(clone.id(), shader.id());
+        assert_eq!(clone.uniform_data(), Some(vec![1; 16]));
+    }
+}
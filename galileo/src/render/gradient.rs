@@ -0,0 +1,216 @@
+//! Color gradients for filling polygons and lines.
+
+use crate::error::GalileoError;
+use crate::Color;
+use galileo_types::cartesian::{CartesianPoint2d, Point2d};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Gradient of colors defined by a list of color stops.
+///
+/// Offsets of the stops are values from `0.0` to `1.0` along the gradient. Colors between the stops are interpolated
+/// linearly; before the first and after the last stop the color of the closest stop is used.
+///
+/// Gradients are applied to the vertices of tessellated primitives, and the colors are interpolated between the
+/// vertices. So a gradient with more than two stops, or a radial gradient, is only an approximation on a large
+/// polygon with few vertices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "Vec<(f32, Color)>", into = "Vec<(f32, Color)>")
+)]
+pub struct ColorGradient {
+    stops: [(f32, Color); Self::MAX_STOPS],
+    stop_count: usize,
+}
+
+impl ColorGradient {
+    /// Maximum number of stops in a gradient.
+    pub const MAX_STOPS: usize = 8;
+
+    /// Creates a gradient from `(offset, color)` pairs. The stops are sorted by their offsets.
+    ///
+    /// Returns an error if no stops are given, or if there are more than [`ColorGradient::MAX_STOPS`] of them.
+    pub fn new(stops: &[(f32, Color)]) -> Result<Self, GalileoError> {
+        if stops.is_empty() || stops.len() > Self::MAX_STOPS {
+            return Err(GalileoError::Generic(format!(
+                "gradient must have from 1 to {} stops, but {} given",
+                Self::MAX_STOPS,
+                stops.len()
+            )));
+        }
+
+        let mut sorted = [(0.0, Color::TRANSPARENT); Self::MAX_STOPS];
+        sorted[..stops.len()].copy_from_slice(stops);
+        sorted[..stops.len()].sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(Self {
+            stops: sorted,
+            stop_count: stops.len(),
+        })
+    }
+
+    /// Creates a gradient from the `start` color to the `end` color.
+    pub fn two_colors(start: Color, end: Color) -> Self {
+        let mut stops = [(0.0, Color::TRANSPARENT); Self::MAX_STOPS];
+        stops[0] = (0.0, start);
+        stops[1] = (1.0, end);
+
+        Self {
+            stops,
+            stop_count: 2,
+        }
+    }
+
+    /// Stops of the gradient sorted by offset.
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.stops[..self.stop_count]
+    }
+
+    /// Color of the gradient at the given offset.
+    pub fn color_at(&self, offset: f32) -> Color {
+        let stops = self.stops();
+        let next_index = stops.partition_point(|(stop_offset, _)| *stop_offset <= offset);
+        if next_index == 0 {
+            return stops[0].1;
+        }
+        if next_index == stops.len() {
+            return stops[stops.len() - 1].1;
+        }
+
+        let (from_offset, from) = stops[next_index - 1];
+        let (to_offset, to) = stops[next_index];
+        let t = (offset - from_offset) / (to_offset - from_offset);
+
+        let from = from.to_u8_array();
+        let to = to.to_u8_array();
+        let [r, g, b, a] = [0, 1, 2, 3]
+            .map(|i| (from[i] as f32 + (to[i] as f32 - from[i] as f32) * t).round() as u8);
+
+        Color::rgba(r, g, b, a)
+    }
+}
+
+impl TryFrom<Vec<(f32, Color)>> for ColorGradient {
+    type Error = GalileoError;
+
+    fn try_from(stops: Vec<(f32, Color)>) -> Result<Self, Self::Error> {
+        Self::new(&stops)
+    }
+}
+
+impl From<ColorGradient> for Vec<(f32, Color)> {
+    fn from(gradient: ColorGradient) -> Self {
+        gradient.stops().to_vec()
+    }
+}
+
+/// Gradient fill of a polygon. Coordinates are given in the CRS of the map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PolygonGradient {
+    /// Colors change along the line from the `start` point (offset `0.0`) to the `end` point (offset `1.0`).
+    Linear {
+        /// Start point of the gradient.
+        start: Point2d,
+        /// End point of the gradient.
+        end: Point2d,
+        /// Colors of the gradient.
+        colors: ColorGradient,
+    },
+    /// Colors change with the distance from the `center` point (offset `0.0`) to the circle of the given `radius`
+    /// (offset `1.0`).
+    Radial {
+        /// Center of the gradient.
+        center: Point2d,
+        /// Radius of the gradient.
+        radius: f64,
+        /// Colors of the gradient.
+        colors: ColorGradient,
+    },
+}
+
+impl PolygonGradient {
+    /// Color of the gradient at the given point.
+    pub fn color_at(&self, point: &impl CartesianPoint2d<Num = f64>) -> Color {
+        match self {
+            PolygonGradient::Linear { start, end, colors } => {
+                let (dx, dy) = (end.x() - start.x(), end.y() - start.y());
+                let length_sq = dx * dx + dy * dy;
+                if length_sq == 0.0 {
+                    return colors.color_at(0.0);
+                }
+
+                let offset =
+                    ((point.x() - start.x()) * dx + (point.y() - start.y()) * dy) / length_sq;
+                colors.color_at(offset as f32)
+            }
+            PolygonGradient::Radial {
+                center,
+                radius,
+                colors,
+            } => {
+                if *radius <= 0.0 {
+                    return colors.color_at(1.0);
+                }
+
+                let distance =
+                    ((point.x() - center.x()).powi(2) + (point.y() - center.y()).powi(2)).sqrt();
+                colors.color_at((distance / radius) as f32)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_at_interpolates_stops() {
+        let gradient = ColorGradient::new(&[
+            (1.0, Color::BLUE),
+            (0.0, Color::rgba(0, 0, 0, 255)),
+            (0.5, Color::rgba(200, 0, 0, 255)),
+        ])
+        .unwrap();
+
+        assert_eq!(gradient.stops()[0].0, 0.0);
+        assert_eq!(gradient.color_at(-1.0), Color::rgba(0, 0, 0, 255));
+        assert_eq!(gradient.color_at(0.25), Color::rgba(100, 0, 0, 255));
+        assert_eq!(gradient.color_at(0.5), Color::rgba(200, 0, 0, 255));
+        assert_eq!(gradient.color_at(2.0), Color::BLUE);
+    }
+
+    #[test]
+    fn gradient_stop_count_is_checked() {
+        assert!(ColorGradient::new(&[]).is_err());
+        assert!(ColorGradient::new(&[(0.0, Color::RED); ColorGradient::MAX_STOPS + 1]).is_err());
+    }
+
+    #[test]
+    fn polygon_gradient_color() {
+        let colors =
+            ColorGradient::two_colors(Color::rgba(0, 0, 0, 255), Color::rgba(200, 0, 0, 255));
+        let linear = PolygonGradient::Linear {
+            start: Point2d::new(0.0, 0.0),
+            end: Point2d::new(10.0, 0.0),
+            colors,
+        };
+        assert_eq!(
+            linear.color_at(&Point2d::new(5.0, 7.0)),
+            Color::rgba(100, 0, 0, 255)
+        );
+
+        let radial = PolygonGradient::Radial {
+            center: Point2d::new(0.0, 0.0),
+            radius: 10.0,
+            colors,
+        };
+        assert_eq!(
+            radial.color_at(&Point2d::new(3.0, 4.0)),
+            Color::rgba(100, 0, 0, 255)
+        );
+    }
+}
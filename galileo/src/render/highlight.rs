@@ -0,0 +1,87 @@
+//! Emphasis of highlighted features.
+
+use crate::Color;
+
+/// Style used to emphasize highlighted primitives, e.g. a feature under the cursor or a selected feature.
+///
+/// Highlighting changes only the colors of already tessellated primitives, so it can be turned on and off every frame
+/// without rendering the features again. Images are not affected by highlighting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HighlightStyle {
+    /// Factor the color channels are multiplied by. Values above `1.0` make the highlighted primitives lighter,
+    /// values below `1.0` make them darker.
+    pub brightness: f32,
+    /// Color that is mixed into the color of the primitives after the brightness is applied. Alpha channel of the
+    /// tint sets the amount of mixing, so a fully opaque tint replaces the color of the primitive.
+    pub tint: Color,
+    /// Minimum opacity of the highlighted primitives from `0.0` to `1.0`. Allows making transparent fills visible
+    /// when highlighted.
+    pub min_opacity: f32,
+}
+
+impl Default for HighlightStyle {
+    fn default() -> Self {
+        Self {
+            brightness: 1.25,
+            tint: Color::rgba(255, 255, 0, 64),
+            min_opacity: 0.0,
+        }
+    }
+}
+
+impl HighlightStyle {
+    /// Returns the emphasized version of the `color`.
+    pub fn apply(&self, color: Color) -> Color {
+        let [r, g, b, a] = self
+            .apply_f32(color.to_f32_array())
+            .map(|channel| (channel * 255.0).round() as u8);
+        Color::rgba(r, g, b, a)
+    }
+
+    /// Same as [`HighlightStyle::apply`], but for the colors stored in vertex buffers.
+    pub(crate) fn apply_f32(&self, color: [f32; 4]) -> [f32; 4] {
+        let [tint_r, tint_g, tint_b, amount] = self.tint.to_f32_array();
+        let mix = |channel: f32, tint: f32| {
+            let bright = (channel * self.brightness).clamp(0.0, 1.0);
+            bright + (tint - bright) * amount
+        };
+
+        [
+            mix(color[0], tint_r),
+            mix(color[1], tint_g),
+            mix(color[2], tint_b),
+            color[3].max(self.min_opacity.clamp(0.0, 1.0)),
+        ]
+    }
+
+    /// Same as [`HighlightStyle::apply`], but for the colors stored as bytes.
+    pub(crate) fn apply_u8(&self, color: [u8; 4]) -> [u8; 4] {
+        let [r, g, b, a] = color;
+        self.apply(Color::rgba(r, g, b, a)).to_u8_array()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_mixes_tint() {
+        let style = HighlightStyle {
+            brightness: 2.0,
+            tint: Color::rgba(255, 255, 255, 0),
+            min_opacity: 0.5,
+        };
+        assert_eq!(
+            style.apply(Color::rgba(100, 200, 0, 0)),
+            Color::rgba(200, 255, 0, 128)
+        );
+
+        let style = HighlightStyle {
+            brightness: 1.0,
+            tint: Color::RED,
+            min_opacity: 0.0,
+        };
+        assert_eq!(style.apply(Color::BLUE), Color::RED);
+    }
+}
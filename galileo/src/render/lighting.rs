@@ -0,0 +1,238 @@
+use crate::decoded_image::DecodedImage;
+use crate::elevation::ElevationTile;
+use crate::Color;
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Position of the sun in the sky.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SunPosition {
+    /// Direction to the sun in degrees clockwise from the north.
+    pub azimuth: f64,
+    /// Angle of the sun above the horizon in degrees. Negative if the sun is below the horizon.
+    pub altitude: f64,
+}
+
+impl SunPosition {
+    /// Creates a position with the given azimuth and altitude in degrees.
+    pub fn new(azimuth: f64, altitude: f64) -> Self {
+        Self { azimuth, altitude }
+    }
+
+    /// Computes the position of the sun at the given time as seen from the point with the given latitude and
+    /// longitude (in degrees). The precision of the computation is within a fraction of a degree, which is more
+    /// than enough for lighting.
+    pub fn at(time: SystemTime, lat: f64, lon: f64) -> Self {
+        const J2000_UNIX_SECONDS: f64 = 946_728_000.0;
+
+        let unix_seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs_f64(),
+            Err(err) => -err.duration().as_secs_f64(),
+        };
+        let days = (unix_seconds - J2000_UNIX_SECONDS) / 86400.0;
+
+        let mean_anomaly = (357.5291 + 0.98560028 * days).to_radians();
+        let center = (1.9148 * mean_anomaly.sin()
+            + 0.02 * (2.0 * mean_anomaly).sin()
+            + 0.0003 * (3.0 * mean_anomaly).sin())
+        .to_radians();
+        let perihelion = 102.9372f64.to_radians();
+        let ecliptic_longitude = mean_anomaly + center + perihelion + PI;
+        let obliquity = 23.4397f64.to_radians();
+
+        let declination = (obliquity.sin() * ecliptic_longitude.sin()).asin();
+        let right_ascension =
+            (ecliptic_longitude.sin() * obliquity.cos()).atan2(ecliptic_longitude.cos());
+
+        let sidereal_time = (280.16 + 360.9856235 * days + lon).to_radians();
+        let hour_angle = sidereal_time - right_ascension;
+        let lat = lat.to_radians();
+
+        let altitude = (lat.sin() * declination.sin()
+            + lat.cos() * declination.cos() * hour_angle.cos())
+        .asin();
+        // Azimuth measured from the south towards the west.
+        let azimuth = hour_angle
+            .sin()
+            .atan2(hour_angle.cos() * lat.sin() - declination.tan() * lat.cos());
+
+        Self {
+            azimuth: (azimuth.to_degrees() + 180.0).rem_euclid(360.0),
+            altitude: altitude.to_degrees(),
+        }
+    }
+
+    /// Returns true if the sun is above the horizon.
+    pub fn is_above_horizon(&self) -> bool {
+        self.altitude > 0.0
+    }
+
+    /// Unit vector pointing to the sun, with X axis pointing to the east, Y axis to the north and Z axis up.
+    pub fn direction(&self) -> Vector3<f64> {
+        let azimuth = self.azimuth.to_radians();
+        let altitude = self.altitude.to_radians();
+        Vector3::new(
+            azimuth.sin() * altitude.cos(),
+            azimuth.cos() * altitude.cos(),
+            altitude.sin(),
+        )
+    }
+}
+
+impl Default for SunPosition {
+    /// Sun in the north-west at 45 degrees over the horizon, the conventional light source of shaded relief maps.
+    fn default() -> Self {
+        Self::new(315.0, 45.0)
+    }
+}
+
+/// Lighting model for 3D content: surfaces are lit by the sun depending on the angle between the surface and the
+/// direction to the sun, and by the ambient light that lights all surfaces equally.
+///
+/// Surfaces that face away from the sun, and all surfaces when the sun is below the horizon, are in shadow and only
+/// get the ambient light darkened by `shadow_darkening`.
+///
+/// ```
+/// use galileo::render::{Lighting, SunPosition};
+/// use galileo::Color;
+/// use nalgebra::Vector3;
+///
+/// let lighting = Lighting::default().with_sun(SunPosition::new(180.0, 60.0));
+/// let roof = lighting.shade(Color::rgba(200, 100, 100, 255), Vector3::new(0.0, 0.0, 1.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Lighting {
+    /// Position of the sun.
+    pub sun: SunPosition,
+    /// Intensity of the ambient light, from `0.0` to `1.0`.
+    pub ambient: f32,
+    /// Intensity of the sun light on a surface perpendicular to the sun direction, from `0.0` to `1.0`.
+    pub diffuse: f32,
+    /// Part of the ambient light that does not reach the surfaces in shadow, from `0.0` to `1.0`.
+    pub shadow_darkening: f32,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            sun: SunPosition::default(),
+            ambient: 0.4,
+            diffuse: 0.6,
+            shadow_darkening: 0.3,
+        }
+    }
+}
+
+impl Lighting {
+    /// Sets the position of the sun.
+    pub fn with_sun(mut self, sun: SunPosition) -> Self {
+        self.sun = sun;
+        self
+    }
+
+    /// Sets the position of the sun computed for the given time and location (see [`SunPosition::at`]).
+    pub fn at_time(self, time: SystemTime, lat: f64, lon: f64) -> Self {
+        self.with_sun(SunPosition::at(time, lat, lon))
+    }
+
+    /// Light intensity of a surface with the given normal vector, where `1.0` means the full color of the surface.
+    /// The normal vector uses the same axes as [`SunPosition::direction`] and does not need to be normalized.
+    pub fn intensity(&self, normal: Vector3<f64>) -> f32 {
+        let shadow = self.ambient * (1.0 - self.shadow_darkening);
+        let Some(normal) = normal.try_normalize(f64::EPSILON) else {
+            return self.ambient;
+        };
+
+        if !self.sun.is_above_horizon() {
+            return shadow;
+        }
+
+        let cos = normal.dot(&self.sun.direction()) as f32;
+        if cos <= 0.0 {
+            shadow
+        } else {
+            (self.ambient + self.diffuse * cos).min(1.0)
+        }
+    }
+
+    /// Applies the light to the color of a surface with the given normal vector. Alpha channel is not changed.
+    pub fn shade(&self, color: Color, normal: Vector3<f64>) -> Color {
+        let intensity = self.intensity(normal);
+        let [r, g, b, a] = color.to_u8_array();
+        let apply = |value: u8| (value as f32 * intensity).round().clamp(0.0, 255.0) as u8;
+
+        Color::rgba(apply(r), apply(g), apply(b), a)
+    }
+
+    /// Renders shaded relief of the elevation tile: a black image with the opacity of each pixel showing how dark
+    /// the terrain is in this point. Such image can be drawn over other layers to make the terrain visible.
+    ///
+    /// `cell_size` is the size of a cell of the tile in the same units as the elevation values (e.g. meters), and
+    /// `z_factor` exaggerates the relief.
+    pub fn hillshade(&self, tile: &ElevationTile, cell_size: f64, z_factor: f64) -> DecodedImage {
+        let (width, height) = (tile.width(), tile.height());
+        let value = |x: i64, y: i64| {
+            let x = x.clamp(0, width as i64 - 1) as u32;
+            let y = y.clamp(0, height as i64 - 1) as u32;
+            tile.get(x, y).unwrap_or(0.0) as f64 * z_factor
+        };
+
+        let max_intensity = (self.ambient + self.diffuse).min(1.0);
+        let mut bytes = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                // Rows go from north to south, so the north direction is towards the smaller y.
+                let dz_dx = (value(x + 1, y) - value(x - 1, y)) / (2.0 * cell_size);
+                let dz_dy = (value(x, y - 1) - value(x, y + 1)) / (2.0 * cell_size);
+                let intensity = self.intensity(Vector3::new(-dz_dx, -dz_dy, 1.0));
+                let darkness = (1.0 - intensity / max_intensity).clamp(0.0, 1.0);
+
+                bytes.extend_from_slice(&[0, 0, 0, (darkness * 255.0).round() as u8]);
+            }
+        }
+
+        DecodedImage {
+            bytes,
+            dimensions: (width, height),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web_time::Duration;
+
+    #[test]
+    fn sun_position_at_equinox() {
+        // 2024-03-20 12:00 UTC.
+        let noon = UNIX_EPOCH + Duration::from_secs(1_710_936_000);
+
+        let zenith = SunPosition::at(noon, 0.0, 0.0);
+        assert!(zenith.altitude > 85.0, "{zenith:?}");
+
+        let north = SunPosition::at(noon, 50.0, 0.0);
+        assert!((north.altitude - 40.0).abs() < 2.0, "{north:?}");
+        assert!((north.azimuth - 180.0).abs() < 5.0, "{north:?}");
+
+        assert!(!SunPosition::at(noon, 0.0, 180.0).is_above_horizon());
+    }
+
+    #[test]
+    fn surfaces_facing_away_from_sun_are_in_shadow() {
+        let lighting = Lighting::default().with_sun(SunPosition::new(90.0, 45.0));
+
+        let towards = lighting.intensity(Vector3::new(1.0, 0.0, 1.0));
+        let flat = lighting.intensity(Vector3::new(0.0, 0.0, 1.0));
+        let away = lighting.intensity(Vector3::new(-1.0, 0.0, 0.0));
+
+        assert!(towards > flat);
+        assert_eq!(away, lighting.ambient * (1.0 - lighting.shadow_darkening));
+        assert_eq!(
+            lighting.shade(Color::rgba(100, 100, 100, 50), Vector3::new(-1.0, 0.0, 0.0)),
+            Color::rgba(28, 28, 28, 50)
+        );
+    }
+}
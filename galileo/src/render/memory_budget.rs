@@ -0,0 +1,448 @@
+//! GPU memory budget shared by the tile caches of the layers.
+
+use quick_cache::{DefaultHashBuilder, Lifecycle, UnitWeighter};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+/// Budget of GPU memory that can be used by the caches of packed tiles of the layers.
+///
+/// A budget can be shared between several layers (raster and vector tile layers). When the total size of the
+/// cached tiles exceeds the limit, the least recently drawn tiles are evicted from the caches of all the layers
+/// sharing the budget, regardless of which layer they belong to.
+///
+/// The limit should be large enough to hold all the tiles visible on the screen at the same time, otherwise
+/// visible tiles will be evicted and reloaded on every frame.
+///
+/// ```
+/// use galileo::render::GpuMemoryBudget;
+///
+/// let budget = GpuMemoryBudget::new(256 * 1024 * 1024);
+/// assert_eq!(budget.used(), 0);
+/// assert_eq!(budget.limit(), Some(256 * 1024 * 1024));
+/// ```
+#[derive(Clone)]
+pub struct GpuMemoryBudget {
+    shared: Arc<BudgetShared>,
+}
+
+struct BudgetShared {
+    // 0 means unlimited
+    limit: AtomicUsize,
+    tick: AtomicU64,
+    next_consumer_id: AtomicUsize,
+    consumers: Mutex<Vec<(usize, Weak<Mutex<dyn EvictionSource>>)>>,
+}
+
+trait EvictionSource: Send {
+    fn used(&self) -> usize;
+    fn oldest(&self) -> Option<u64>;
+    fn evict_oldest(&mut self) -> usize;
+}
+
+impl GpuMemoryBudget {
+    /// Creates a new budget with the given limit in bytes.
+    pub fn new(limit: usize) -> Self {
+        let budget = Self::unlimited();
+        budget.set_limit(Some(limit));
+        budget
+    }
+
+    /// Creates a budget without a limit. Usage is still tracked and can be queried.
+    pub fn unlimited() -> Self {
+        Self {
+            shared: Arc::new(BudgetShared {
+                limit: AtomicUsize::new(0),
+                tick: AtomicU64::new(0),
+                next_consumer_id: AtomicUsize::new(0),
+                consumers: Mutex::new(vec![]),
+            }),
+        }
+    }
+
+    /// Limit of the budget in bytes, `None` if the budget is unlimited.
+    pub fn limit(&self) -> Option<usize> {
+        match self.shared.limit.load(Ordering::Relaxed) {
+            0 => None,
+            v => Some(v),
+        }
+    }
+
+    /// Changes the limit of the budget. If the current usage exceeds the new limit, tiles are evicted immediately.
+    pub fn set_limit(&self, limit: Option<usize>) {
+        let value = match limit {
+            Some(limit) => limit.max(1),
+            None => 0,
+        };
+        self.shared.limit.store(value, Ordering::Relaxed);
+        self.enforce();
+    }
+
+    /// Approximate size in bytes of GPU memory currently used by all the layers sharing this budget.
+    pub fn used(&self) -> usize {
+        self.live_consumers()
+            .iter()
+            .map(|(_, c)| c.lock().expect("mutex is poisoned").used())
+            .sum()
+    }
+
+    fn next_tick(&self) -> u64 {
+        self.shared.tick.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn register(&self, source: Weak<Mutex<dyn EvictionSource>>) -> usize {
+        let id = self.shared.next_consumer_id.fetch_add(1, Ordering::Relaxed);
+        self.shared
+            .consumers
+            .lock()
+            .expect("mutex is poisoned")
+            .push((id, source));
+        id
+    }
+
+    fn unregister(&self, id: usize) {
+        self.shared
+            .consumers
+            .lock()
+            .expect("mutex is poisoned")
+            .retain(|(consumer_id, _)| *consumer_id != id);
+    }
+
+    fn live_consumers(&self) -> Vec<(usize, Arc<Mutex<dyn EvictionSource>>)> {
+        let mut consumers = self.shared.consumers.lock().expect("mutex is poisoned");
+        consumers.retain(|(_, c)| c.strong_count() > 0);
+        consumers
+            .iter()
+            .filter_map(|(id, c)| c.upgrade().map(|c| (*id, c)))
+            .collect()
+    }
+
+    fn enforce(&self) {
+        let Some(limit) = self.limit() else {
+            return;
+        };
+
+        let consumers = self.live_consumers();
+        loop {
+            let used: usize = consumers
+                .iter()
+                .map(|(_, c)| c.lock().expect("mutex is poisoned").used())
+                .sum();
+            if used <= limit {
+                break;
+            }
+
+            let oldest = consumers
+                .iter()
+                .filter_map(|(_, c)| {
+                    c.lock()
+                        .expect("mutex is poisoned")
+                        .oldest()
+                        .map(|tick| (tick, c))
+                })
+                .min_by_key(|(tick, _)| *tick);
+
+            let Some((_, consumer)) = oldest else {
+                break;
+            };
+
+            consumer.lock().expect("mutex is poisoned").evict_oldest();
+        }
+    }
+}
+
+impl Default for GpuMemoryBudget {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+/// Tracks sizes and usage of the entries of a single cache, and collects the keys that were evicted by the budget.
+///
+/// The owner of the cache must remove the evicted entries returned by [`MemoryTracker::take_evicted`].
+pub(crate) struct MemoryTracker<K> {
+    state: Arc<Mutex<TrackerState<K>>>,
+    registration: Arc<Mutex<(GpuMemoryBudget, usize)>>,
+}
+
+impl<K> Clone for MemoryTracker<K> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            registration: self.registration.clone(),
+        }
+    }
+}
+
+struct TrackerState<K> {
+    entries: HashMap<K, TrackedEntry>,
+    used: usize,
+    evicted: Vec<K>,
+}
+
+struct TrackedEntry {
+    size: usize,
+    last_used: u64,
+}
+
+impl<K: Hash + Eq + Copy + Send + 'static> MemoryTracker<K> {
+    pub fn new(budget: &GpuMemoryBudget) -> Self {
+        let state = Arc::new(Mutex::new(TrackerState {
+            entries: HashMap::new(),
+            used: 0,
+            evicted: vec![],
+        }));
+        let id = budget.register(Self::as_source(&state));
+
+        Self {
+            state,
+            registration: Arc::new(Mutex::new((budget.clone(), id))),
+        }
+    }
+
+    fn as_source(state: &Arc<Mutex<TrackerState<K>>>) -> Weak<Mutex<dyn EvictionSource>> {
+        let source: Arc<Mutex<dyn EvictionSource>> = state.clone();
+        Arc::downgrade(&source)
+    }
+
+    fn budget(&self) -> GpuMemoryBudget {
+        self.registration
+            .lock()
+            .expect("mutex is poisoned")
+            .0
+            .clone()
+    }
+
+    /// Moves the tracked entries to another budget.
+    pub fn set_budget(&self, budget: &GpuMemoryBudget) {
+        {
+            let mut registration = self.registration.lock().expect("mutex is poisoned");
+            let (old_budget, old_id) = &*registration;
+            old_budget.unregister(*old_id);
+
+            let id = budget.register(Self::as_source(&self.state));
+            *registration = (budget.clone(), id);
+        }
+
+        budget.enforce();
+    }
+
+    /// Records an entry with the given size. If the entry already exists, its size is updated.
+    pub fn insert(&self, key: K, size: usize) {
+        let budget = self.budget();
+        {
+            let mut state = self.state.lock().expect("mutex is poisoned");
+            let last_used = budget.next_tick();
+            if let Some(prev) = state.entries.insert(key, TrackedEntry { size, last_used }) {
+                state.used -= prev.size;
+            }
+            state.used += size;
+        }
+
+        budget.enforce();
+    }
+
+    /// Stops tracking the entry, e.g. when it was replaced in the cache with an entry that does not use GPU memory.
+    pub fn remove(&self, key: &K) {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        if let Some(entry) = state.entries.remove(key) {
+            state.used -= entry.size;
+        }
+    }
+
+    /// Cache lifecycle that stops tracking the entries the cache evicts because of its capacity. Caches created with
+    /// it do not need to be scanned for the entries that left them.
+    fn lifecycle(&self) -> UntrackEvicted<K> {
+        UntrackEvicted(self.clone())
+    }
+
+    /// Creates a sync cache for `capacity` entries that stops tracking the entries it evicts.
+    pub fn cache<V: Clone>(&self, capacity: usize) -> TrackedCache<K, V> {
+        quick_cache::sync::Cache::with(
+            capacity,
+            capacity as u64,
+            UnitWeighter,
+            DefaultHashBuilder::default(),
+            self.lifecycle(),
+        )
+    }
+
+    /// Creates an unsync cache for `capacity` entries that stops tracking the entries it evicts.
+    pub fn unsync_cache<V>(&self, capacity: usize) -> TrackedUnsyncCache<K, V> {
+        quick_cache::unsync::Cache::with(
+            capacity,
+            capacity as u64,
+            UnitWeighter,
+            DefaultHashBuilder::default(),
+            self.lifecycle(),
+        )
+    }
+
+    /// Marks the entry as recently used.
+    pub fn touch(&self, key: &K) {
+        let tick = self.budget().next_tick();
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        if let Some(entry) = state.entries.get_mut(key) {
+            entry.last_used = tick;
+        }
+    }
+
+    /// Returns the keys of the entries evicted by the budget since the last call.
+    pub fn take_evicted(&self) -> Vec<K> {
+        std::mem::take(&mut self.state.lock().expect("mutex is poisoned").evicted)
+    }
+
+    /// Total size of the tracked entries in bytes.
+    pub fn used(&self) -> usize {
+        self.state.lock().expect("mutex is poisoned").used
+    }
+}
+
+/// Cache lifecycle of the caches created by [`MemoryTracker::cache`] and [`MemoryTracker::unsync_cache`].
+pub(crate) struct UntrackEvicted<K>(MemoryTracker<K>);
+
+impl<K> Clone for UntrackEvicted<K> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K: Hash + Eq + Copy + Send + 'static, V> Lifecycle<K, V> for UntrackEvicted<K> {
+    type RequestState = ();
+
+    fn begin_request(&self) -> Self::RequestState {}
+
+    fn on_evict(&self, _state: &mut Self::RequestState, key: K, _value: V) {
+        self.0.remove(&key);
+    }
+}
+
+/// Sync cache that stops tracking the entries it evicts, see [`MemoryTracker::cache`].
+pub(crate) type TrackedCache<K, V> =
+    quick_cache::sync::Cache<K, V, UnitWeighter, DefaultHashBuilder, UntrackEvicted<K>>;
+
+/// Unsync cache that stops tracking the entries it evicts, see [`MemoryTracker::unsync_cache`].
+pub(crate) type TrackedUnsyncCache<K, V> =
+    quick_cache::unsync::Cache<K, V, UnitWeighter, DefaultHashBuilder, UntrackEvicted<K>>;
+
+impl<K> Drop for MemoryTracker<K> {
+    fn drop(&mut self) {
+        if Arc::strong_count(&self.registration) == 1 {
+            if let Ok(registration) = self.registration.lock() {
+                let (budget, id) = &*registration;
+                budget.unregister(*id);
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Copy + Send> EvictionSource for TrackerState<K> {
+    fn used(&self) -> usize {
+        self.used
+    }
+
+    fn oldest(&self) -> Option<u64> {
+        self.entries.values().map(|entry| entry.last_used).min()
+    }
+
+    fn evict_oldest(&mut self) -> usize {
+        let Some(key) = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| *key)
+        else {
+            return 0;
+        };
+
+        let size = self.entries.remove(&key).map(|e| e.size).unwrap_or(0);
+        self.used -= size;
+        self.evicted.push(key);
+
+        size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used_across_trackers() {
+        let budget = GpuMemoryBudget::new(100);
+        let first = MemoryTracker::new(&budget);
+        let second = MemoryTracker::new(&budget);
+
+        first.insert(1, 40);
+        second.insert(2, 40);
+        first.touch(&1);
+        assert_eq!(budget.used(), 80);
+
+        second.insert(3, 40);
+        assert_eq!(budget.used(), 80);
+        assert!(first.take_evicted().is_empty());
+        assert_eq!(second.take_evicted(), vec![2]);
+    }
+
+    #[test]
+    fn lowering_limit_evicts_entries() {
+        let budget = GpuMemoryBudget::unlimited();
+        let tracker = MemoryTracker::new(&budget);
+        tracker.insert(1, 10);
+        tracker.insert(2, 10);
+        tracker.insert(3, 10);
+        assert_eq!(budget.used(), 30);
+
+        budget.set_limit(Some(15));
+        assert_eq!(tracker.take_evicted(), vec![1, 2]);
+        assert_eq!(tracker.used(), 10);
+    }
+
+    #[test]
+    fn set_budget_moves_usage() {
+        let old_budget = GpuMemoryBudget::unlimited();
+        let new_budget = GpuMemoryBudget::unlimited();
+        let tracker = MemoryTracker::new(&old_budget);
+        tracker.insert(1, 10);
+
+        tracker.set_budget(&new_budget);
+        assert_eq!(old_budget.used(), 0);
+        assert_eq!(new_budget.used(), 10);
+    }
+
+    #[test]
+    fn entries_removed_from_cache_are_not_counted() {
+        let budget = GpuMemoryBudget::new(1000);
+        let tracker = MemoryTracker::new(&budget);
+        let mut cache = tracker.unsync_cache(2);
+        for key in 0..10 {
+            tracker.insert(key, 10);
+            cache.insert(key, ());
+        }
+
+        assert!(cache.len() < 10);
+        assert_eq!(budget.used(), cache.len() * 10);
+
+        tracker.remove(&9);
+        tracker.remove(&9);
+        assert_eq!(
+            tracker.used(),
+            (0..9).filter(|key| cache.peek(key).is_some()).count() * 10
+        );
+        assert!(tracker.take_evicted().is_empty());
+
+        drop(tracker);
+        drop(cache);
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn dropped_tracker_is_not_counted() {
+        let budget = GpuMemoryBudget::unlimited();
+        let tracker = MemoryTracker::new(&budget);
+        tracker.insert(1, 10);
+        drop(tracker);
+        assert_eq!(budget.used(), 0);
+    }
+}
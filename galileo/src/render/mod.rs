@@ -2,30 +2,90 @@
 //!
 //! The backends use [`Canvas`] instances to render map layers to the render target (screen, image, etc.).
 //!
-//! At this point only [`WgpuRenderer`] is implemented.
+//! Two backends are implemented: `WgpuRenderer` that renders the map on GPU with `wgpu` crate, and
+//! [`SoftwareRenderer`] that rasterizes the map on CPU for environments without a GPU.
 
+use crate::control::{Clock, SystemClock};
+#[cfg(feature = "compute")]
+use crate::error::GalileoError;
+use crate::view::MapView;
 use crate::Color;
 use galileo_types::cartesian::Size;
 use maybe_sync::{MaybeSend, MaybeSync};
 use render_bundle::RenderBundle;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
+use web_time::SystemTime;
 
 #[cfg(feature = "wgpu")]
 mod wgpu;
 #[cfg(feature = "wgpu")]
-pub use wgpu::WgpuRenderer;
+pub use wgpu::{RecoveryEvent, StereoTarget, TargetColorSpace, WgpuRenderer};
 
+mod composition;
+#[cfg(feature = "compute")]
+mod compute;
+mod custom_shader;
+mod gradient;
+mod highlight;
+mod lighting;
+mod memory_budget;
 pub mod point_paint;
 pub mod render_bundle;
+mod software;
+mod zoom_interpolation;
+
+pub use composition::{LegendEntry, PrintComposition, TextRenderer};
+#[cfg(feature = "compute")]
+pub use compute::{ComputeBuffer, ComputeFallback, ComputeShader};
+pub use custom_shader::CustomShader;
+pub use gradient::{ColorGradient, PolygonGradient};
+pub use highlight::HighlightStyle;
+pub use lighting::{Lighting, SunPosition};
+pub use memory_budget::GpuMemoryBudget;
+pub(crate) use memory_budget::{MemoryTracker, TrackedCache, TrackedUnsyncCache};
+pub use software::SoftwareRenderer;
+pub use zoom_interpolation::ZoomInterpolation;
 
 /// Id of a rendering primitive
-#[derive(Debug, Copy, Clone, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PrimitiveId(usize);
 
 pub(crate) trait Renderer: MaybeSend + MaybeSync {
     fn as_any(&self) -> &dyn Any;
 }
 
+/// Source of the time for animated primitives of a renderer.
+pub(crate) struct AnimationClock {
+    clock: Box<dyn Clock>,
+    start: SystemTime,
+}
+
+impl AnimationClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        let start = clock.now();
+        Self {
+            clock: Box::new(clock),
+            start,
+        }
+    }
+
+    /// Seconds since the clock was created.
+    pub fn time(&self) -> f32 {
+        self.clock
+            .now()
+            .duration_since(self.start)
+            .unwrap_or_default()
+            .as_secs_f32()
+    }
+}
+
+impl Default for AnimationClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
 /// Canvas that a layer can be rendered to.
 ///
 /// As layers can contain a lot of data, canvases use two-step process for rendering.
@@ -41,12 +101,87 @@ pub(crate) trait Renderer: MaybeSend + MaybeSync {
 pub trait Canvas {
     /// Size of the drawing area.
     fn size(&self) -> Size;
+    /// Number of physical pixels of the render target in one pixel of the map view. All sizes in pixels (line widths,
+    /// point sizes, offsets) are given in the pixels of the map view and are scaled by this factor when drawn, so they
+    /// keep their physical size on high-DPI screens.
+    ///
+    /// Layers can use it to rasterize images (e.g. text) at the resolution of the screen.
+    fn scale_factor(&self) -> f64 {
+        1.0
+    }
+    /// Effective resolution of the render target in dots per inch, assuming [`BASE_DPI`] for the scale factor of 1.
+    fn dpi(&self) -> f64 {
+        BASE_DPI * self.scale_factor()
+    }
     /// Creates a new render bundle.
     fn create_bundle(&self) -> RenderBundle;
     /// Packs a bundle to make it ready for be rendered with [`Canvas::draw_bundles`] method.
     fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle>;
     /// Render the bundles.
     fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions);
+    /// Render the bundles, drawing map-referenced primitives (polygons and lines) with the given custom shader.
+    ///
+    /// Canvases that do not support custom shaders draw the bundles the same way as [`Canvas::draw_bundles`].
+    fn draw_bundles_with_shader(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        shader: &CustomShader,
+    ) {
+        let _ = shader;
+        self.draw_bundles(bundles, options);
+    }
+    /// Render the bundles only inside the area covered by the polygons and lines of the `mask` bundle. Points and
+    /// images of the mask bundle are ignored. If a `shader` is given, map-referenced primitives are drawn with it as in
+    /// [`Canvas::draw_bundles_with_shader`].
+    ///
+    /// Canvases that do not support masking draw the bundles without the mask.
+    fn draw_bundles_with_mask(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        mask: &dyn PackedBundle,
+        shader: Option<&CustomShader>,
+    ) {
+        let _ = mask;
+        match shader {
+            Some(shader) => self.draw_bundles_with_shader(bundles, options, shader),
+            None => self.draw_bundles(bundles, options),
+        }
+    }
+    /// Returns true if the canvas runs compute shaders on GPU. If false, [`Canvas::dispatch_compute`] runs the
+    /// fallbacks of the shaders.
+    #[cfg(feature = "compute")]
+    fn supports_compute(&self) -> bool {
+        false
+    }
+    /// Runs the compute shader with the given number of workgroups. The work is done before the bundles drawn after
+    /// this call.
+    ///
+    /// Canvases that cannot run compute shaders run the [fallback](ComputeShader::with_fallback) of the shader
+    /// instead, and return an error if the shader does not have one.
+    #[cfg(feature = "compute")]
+    fn dispatch_compute(
+        &mut self,
+        shader: &ComputeShader,
+        workgroups: [u32; 3],
+    ) -> Result<(), GalileoError> {
+        shader.run_fallback(workgroups)
+    }
+}
+
+/// Resolution of a screen with the scale factor of 1, in dots per inch.
+pub const BASE_DPI: f64 = 96.0;
+
+/// Number of render target pixels in one pixel of the map view. If the view has no size, the view is considered to
+/// cover the whole target with the scale factor of 1.
+pub(crate) fn view_scale_factor(view: &MapView, target_width: f64) -> f64 {
+    let view_width = view.size().width();
+    if view_width > 0.0 && target_width > 0.0 {
+        target_width / view_width
+    } else {
+        1.0
+    }
 }
 
 /// Packed render bundle ready to be drawn.
@@ -58,13 +193,21 @@ pub trait PackedBundle: MaybeSend + MaybeSync {
 /// Rendering options.
 #[derive(Debug, Copy, Clone)]
 pub struct RenderOptions {
-    /// If set to true, the primitives will be drawn using antialiasing (multisampling).
+    /// If set to true, the primitives will be drawn using antialiasing. Depending on the renderer configuration,
+    /// this is done either with multisampling or by smoothing the edges of the lines in the shader.
     pub antialias: bool,
+    /// Multiplier of the alpha channel of the drawn primitives, from `0.0` (transparent) to `1.0` (opaque). The
+    /// opacity is applied to every primitive separately, so overlapping primitives of the same bundles show through
+    /// each other.
+    pub opacity: f32,
 }
 
 impl Default for RenderOptions {
     fn default() -> Self {
-        Self { antialias: true }
+        Self {
+            antialias: true,
+            opacity: 1.0,
+        }
     }
 }
 
@@ -73,6 +216,11 @@ impl Default for RenderOptions {
 pub struct PolygonPaint {
     /// Fill color of the polygon.
     pub color: Color,
+    /// Gradient to fill the polygon with instead of the `color`.
+    pub gradient: Option<PolygonGradient>,
+    /// Change of the fill color with the resolution of the map. If set, it is used instead of the `color` and the
+    /// `gradient`.
+    pub zoom: Option<ZoomInterpolation>,
 }
 
 /// Parameter to draw a line primitive with.
@@ -80,17 +228,94 @@ pub struct PolygonPaint {
 pub struct LinePaint {
     /// Color of the line.
     pub color: Color,
-    /// Width of the line in pixels.
+    /// Width of the line in `width_unit`s.
     pub width: f64,
-    /// Offset of the line in pixels. The line is offset to the right side if the positive value is given, and to the
-    /// left otherwise.
+    /// Offset of the line in `width_unit`s. The line is offset to the right side if the positive value is given, and
+    /// to the left otherwise.
     pub offset: f64,
+    /// Units of the `width` and `offset` of the line.
+    pub width_unit: SizeUnit,
     /// Type of the cap of the line.
     pub line_cap: LineCap,
+    /// Pattern to draw the line with. If not set, the line is solid.
+    pub pattern: Option<LinePattern>,
+    /// Gradient of the line color from the start (offset `0.0`) to the end (offset `1.0`) of the line. If set,
+    /// it is used instead of the `color`.
+    pub gradient: Option<ColorGradient>,
+    /// Change of the color and the width of the line with the resolution of the map. If set, it is used instead of
+    /// the `color`, the `gradient` and the `width`.
+    pub zoom: Option<ZoomInterpolation>,
+}
+
+/// Units of the size of a primitive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SizeUnit {
+    /// Size is set in pixels and stays the same on the screen at any resolution of the map.
+    #[default]
+    Pixels,
+    /// Size is set in units of the map CRS (e.g. meters for Web Mercator). The primitive is scaled together with the
+    /// map when zooming, and is foreshortened when the map is tilted, like any other map-referenced geometry. Use it
+    /// to show objects of known physical size, like road casings or pipelines.
+    MapUnits,
+}
+
+/// Pattern of a line, optionally moving along the line to show the direction of a flow.
+///
+/// Lengths of the pattern are set in pixels. The `speed` of the pattern is set in pixels per second; positive values
+/// move the pattern in the direction of the line, negative values move it backwards. Time of the animation is taken
+/// from the clock of the renderer, and layers drawing moving patterns request redraw of the map on every frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LinePattern {
+    /// Dashes of `dash` length separated by gaps of `gap` length.
+    Dash {
+        /// Length of a dash in pixels.
+        dash: f64,
+        /// Length of a gap between dashes in pixels.
+        gap: f64,
+        /// Speed of the dashes moving along the line in pixels per second.
+        speed: f64,
+    },
+    /// Arrowheads pointing in the direction of the line, as wide as the line and `length` pixels long, separated by
+    /// gaps of `gap` length. The line itself is not drawn between the arrows, so to show arrows on top of a line,
+    /// draw the line separately.
+    Arrows {
+        /// Length of an arrowhead in pixels.
+        length: f64,
+        /// Length of a gap between arrowheads in pixels.
+        gap: f64,
+        /// Speed of the arrows moving along the line in pixels per second.
+        speed: f64,
+    },
+}
+
+impl LinePattern {
+    /// Returns true if the pattern moves along the line.
+    pub fn is_animated(&self) -> bool {
+        match self {
+            LinePattern::Dash { speed, .. } | LinePattern::Arrows { speed, .. } => *speed != 0.0,
+        }
+    }
+
+    /// Pattern parameters as stored in the vertices: `[length, gap, speed, kind]`. Kind is `1.0` for dashes and
+    /// `2.0` for arrows; all zeros mean a solid line.
+    pub(crate) fn to_vertex_params(pattern: Option<Self>) -> [f32; 4] {
+        match pattern {
+            None => [0.0; 4],
+            Some(LinePattern::Dash { dash, gap, speed }) => {
+                [dash as f32, gap as f32, speed as f32, 1.0]
+            }
+            Some(LinePattern::Arrows { length, gap, speed }) => {
+                [length as f32, gap as f32, speed as f32, 2.0]
+            }
+        }
+    }
 }
 
 /// Cap (end point) style of the line.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum LineCap {
     /// Half-circle cap.
     Round,
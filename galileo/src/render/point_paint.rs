@@ -1,10 +1,13 @@
 //! [`PointPaint`] specifies the way a point should be drawn to the map.
 
 use crate::decoded_image::DecodedImage;
-use crate::render::{LineCap, LinePaint};
+use crate::render::{LineCap, LinePaint, SizeUnit};
 use crate::Color;
 use galileo_types::impls::ClosedContour;
 use nalgebra::{Point2, Vector2};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::sync::Arc;
 
 /// Specifies the way a point should be drawn to the map.
@@ -12,6 +15,33 @@ use std::sync::Arc;
 pub struct PointPaint<'a> {
     pub(crate) shape: PointShape<'a>,
     pub(crate) offset: Vector2<f32>,
+    pub(crate) alignment: PointAlignment,
+    pub(crate) rotation: f32,
+    pub(crate) rotation_alignment: RotationAlignment,
+}
+
+/// Orientation of a point symbol when the map is rotated or tilted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PointAlignment {
+    /// The symbol always faces the viewer and keeps its orientation on the screen (billboard).
+    #[default]
+    Screen,
+    /// The symbol lies flat on the map plane: it rotates together with the map and is foreshortened when the map is
+    /// tilted. The size of the symbol is still set in pixels, like the width of the lines.
+    Map,
+}
+
+/// Direction from which the rotation angle of a point symbol is measured.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RotationAlignment {
+    /// Angle is measured from the top of the screen, so the symbol keeps its orientation when the map is rotated.
+    #[default]
+    Screen,
+    /// Angle is measured from the north (*+Y* direction) of the map, so the symbol rotates together with the map.
+    /// Use it for the symbols showing directions on the map, e.g. wind direction or vehicle heading.
+    Map,
 }
 
 impl<'a> PointPaint<'a> {
@@ -19,6 +49,9 @@ impl<'a> PointPaint<'a> {
     pub fn circle(color: Color, diameter: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            alignment: PointAlignment::Screen,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
             shape: PointShape::Circle {
                 fill: color.into(),
                 radius: diameter / 2.0,
@@ -27,10 +60,29 @@ impl<'a> PointPaint<'a> {
         }
     }
 
+    /// Creates a paint that draws a circle with the diameter set in map units. Unlike [`PointPaint::circle`], the
+    /// circle is scaled with the map when zooming and is foreshortened when the map is tilted.
+    pub fn map_circle(color: Color, diameter: f64) -> Self {
+        Self {
+            offset: Vector2::default(),
+            alignment: PointAlignment::Screen,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
+            shape: PointShape::MapCircle {
+                fill: color,
+                radius: diameter / 2.0,
+                outline: None,
+            },
+        }
+    }
+
     /// Creates a paint that draws a sector of a circle of fixed diameter (in pixels) not dependant on map resolution.
     pub fn sector(color: Color, diameter: f32, start_angle: f32, end_angle: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            alignment: PointAlignment::Screen,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
             shape: PointShape::Sector(SectorParameters {
                 fill: color.into(),
                 radius: diameter / 2.0,
@@ -45,6 +97,9 @@ impl<'a> PointPaint<'a> {
     pub fn square(color: Color, size: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            alignment: PointAlignment::Screen,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
             shape: PointShape::Square {
                 fill: color,
                 size,
@@ -57,6 +112,9 @@ impl<'a> PointPaint<'a> {
     pub fn dot(color: Color) -> Self {
         Self {
             offset: Vector2::default(),
+            alignment: PointAlignment::Screen,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
             shape: PointShape::Dot { color },
         }
     }
@@ -65,11 +123,31 @@ impl<'a> PointPaint<'a> {
     pub fn shape(color: Color, contour: &'a ClosedContour<Point2<f32>>, scale: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            alignment: PointAlignment::Screen,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
             shape: PointShape::FreeShape {
                 fill: color,
                 scale,
                 outline: None,
-                shape: contour,
+                shape: Cow::Borrowed(contour),
+            },
+        }
+    }
+
+    /// Creates a paint that draws a given shape (in screen coordinates), taking the ownership of the shape contour.
+    /// Use it for the shapes that are generated for every feature, e.g. chart symbols.
+    pub fn owned_shape(color: Color, contour: ClosedContour<Point2<f32>>, scale: f32) -> Self {
+        Self {
+            offset: Vector2::default(),
+            alignment: PointAlignment::Screen,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
+            shape: PointShape::FreeShape {
+                fill: color,
+                scale,
+                outline: None,
+                shape: Cow::Owned(contour),
             },
         }
     }
@@ -81,6 +159,9 @@ impl<'a> PointPaint<'a> {
         let height = image.dimensions.1 as f32 * scale;
         Self {
             offset,
+            alignment: PointAlignment::Screen,
+            rotation: 0.0,
+            rotation_alignment: RotationAlignment::Screen,
             shape: PointShape::Image {
                 image,
                 opacity: 255,
@@ -90,17 +171,53 @@ impl<'a> PointPaint<'a> {
         }
     }
 
+    /// Sets the orientation of the symbol in rotated and tilted views. Single pixel dots are always drawn the same
+    /// way.
+    pub fn with_alignment(mut self, alignment: PointAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets the rotation of the symbol around its anchor point in radians, clockwise. Circles and single pixel dots
+    /// look the same with any rotation.
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets the direction from which the [rotation](PointPaint::with_rotation) of the symbol is measured.
+    ///
+    /// Symbols with [`PointAlignment::Map`] lie on the map and always rotate with it, so for them the angle is
+    /// measured from the north of the map regardless of this setting.
+    pub fn with_rotation_alignment(mut self, alignment: RotationAlignment) -> Self {
+        self.rotation_alignment = alignment;
+        self
+    }
+
+    /// Rotation stored in the vertices of the symbol: the angle in radians, and `1.0` if the angle is measured from
+    /// the north of the map (the renderer then adds the rotation of the view to it), or `0.0` otherwise.
+    pub(crate) fn rotation_vertex_params(&self) -> [f32; 2] {
+        let follows_map = self.alignment == PointAlignment::Screen
+            && self.rotation_alignment == RotationAlignment::Map;
+        [self.rotation, if follows_map { 1.0 } else { 0.0 }]
+    }
+
     /// Sets an outline for the symbol (if applicable).
     pub fn with_outline(mut self, color: Color, width: f32) -> Self {
         match &mut self.shape {
             PointShape::Circle { outline, .. }
+            | PointShape::MapCircle { outline, .. }
             | PointShape::Square { outline, .. }
             | PointShape::FreeShape { outline, .. } => {
                 *outline = Some(LinePaint {
                     color,
                     width: width as f64,
                     offset: 0.0,
+                    width_unit: SizeUnit::Pixels,
                     line_cap: LineCap::Round,
+                    pattern: None,
+                    gradient: None,
+                    zoom: None,
                 })
             }
             _ => {}
@@ -120,6 +237,11 @@ pub(crate) enum PointShape<'a> {
         radius: f32,
         outline: Option<LinePaint>,
     },
+    MapCircle {
+        fill: Color,
+        radius: f64,
+        outline: Option<LinePaint>,
+    },
     Sector(SectorParameters),
     Square {
         fill: Color,
@@ -130,7 +252,7 @@ pub(crate) enum PointShape<'a> {
         fill: Color,
         scale: f32,
         outline: Option<LinePaint>,
-        shape: &'a ClosedContour<Point2<f32>>,
+        shape: Cow<'a, ClosedContour<Point2<f32>>>,
     },
     Image {
         image: Arc<DecodedImage>,
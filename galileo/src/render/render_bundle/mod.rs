@@ -4,13 +4,14 @@ use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
 use crate::render::point_paint::PointPaint;
 use crate::render::render_bundle::tessellating::TessellatingRenderBundle;
-use crate::render::{ImagePaint, LinePaint, PolygonPaint, PrimitiveId};
+use crate::render::{HighlightStyle, ImagePaint, LinePaint, PolygonPaint, PrimitiveId};
 use crate::view::MapView;
 use galileo_types::cartesian::{CartesianPoint3d, Point2d};
 use galileo_types::contour::Contour;
 use galileo_types::Polygon;
 use num_traits::AsPrimitive;
 use std::borrow::Cow;
+use thiserror::Error;
 
 pub(crate) mod tessellating;
 
@@ -58,6 +59,9 @@ impl RenderBundle {
 
     /// Adds a primitive to the bundle and returns the id of the given primitive in the bundle. The returned id can
     /// then be used to update or remove the primitive.
+    ///
+    /// If the primitive cannot be tessellated (see [`RenderBundle::try_add`]), the error is logged and an empty
+    /// primitive is added instead, so the returned id is always valid.
     pub fn add<N, P, C, Poly>(
         &mut self,
         primitive: RenderPrimitive<N, P, C, Poly>,
@@ -75,6 +79,34 @@ impl RenderBundle {
         }
     }
 
+    /// Adds a primitive to the bundle, returning an error if the primitive cannot be tessellated, e.g. if it has NaN
+    /// coordinates, an empty contour or a negative line width.
+    ///
+    /// If an error is returned, the bundle is left exactly as it was before the call.
+    pub fn try_add<N, P, C, Poly>(
+        &mut self,
+        primitive: RenderPrimitive<N, P, C, Poly>,
+        min_resolution: f64,
+    ) -> Result<PrimitiveId, TessellationError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+        C: Contour<Point = P> + Clone,
+        Poly: Polygon + Clone,
+        Poly::Contour: Contour<Point = P>,
+    {
+        match &mut self.0 {
+            RenderBundleType::Tessellating(inner) => inner.try_add(primitive, min_resolution),
+        }
+    }
+
+    /// Adds a primitive that has nothing to draw. Used in place of primitives that failed to tessellate.
+    pub(crate) fn add_empty(&mut self) -> PrimitiveId {
+        match &mut self.0 {
+            RenderBundleType::Tessellating(inner) => inner.add_empty(),
+        }
+    }
+
     /// Removes the primitive from the bundle.
     pub fn remove(&mut self, primitive_id: PrimitiveId) -> Result<(), GalileoError> {
         match &mut self.0 {
@@ -108,6 +140,21 @@ impl RenderBundle {
         }
     }
 
+    /// Highlights the primitive with the given style, or removes the highlight if `None` is given.
+    ///
+    /// Only the colors of the primitive are changed, so this is as cheap as [`RenderBundle::update`], and the
+    /// original colors are restored when the highlight is removed. The bundle must be packed again for the change to
+    /// be displayed.
+    pub fn set_highlight(
+        &mut self,
+        primitive_id: PrimitiveId,
+        style: Option<HighlightStyle>,
+    ) -> Result<(), GalileoError> {
+        match &mut self.0 {
+            RenderBundleType::Tessellating(inner) => inner.set_highlight(primitive_id, style),
+        }
+    }
+
     /// Returns true if the bundle has not primitives added.
     pub fn is_empty(&self) -> bool {
         match &self.0 {
@@ -130,6 +177,27 @@ impl RenderBundle {
     }
 }
 
+/// Error of converting a render primitive into triangles.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TessellationError {
+    /// Some coordinates of the geometry are NaN or infinite, or become infinite when converted to the bundle
+    /// resolution.
+    #[error("geometry has non-finite coordinates")]
+    NonFiniteCoordinates,
+    /// Geometry or one of its contours has no points.
+    #[error("geometry is empty")]
+    EmptyGeometry,
+    /// Paint has invalid parameters, e.g. a negative line width.
+    #[error("invalid paint: {0}")]
+    InvalidPaint(String),
+    /// Tessellator could not process the geometry.
+    #[error("tessellation failed: {0}")]
+    Tessellator(String),
+    /// Tessellator panicked while processing the geometry.
+    #[error("tessellator panicked")]
+    Panicked,
+}
+
 /// Rendering primitive.
 pub enum RenderPrimitive<'a, N, P, C, Poly>
 where
@@ -1,11 +1,16 @@
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
-use crate::render::point_paint::{CircleFill, PointPaint, PointShape, SectorParameters};
-use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{ImagePaint, LinePaint, PolygonPaint, PrimitiveId};
+use crate::render::point_paint::{
+    CircleFill, PointAlignment, PointPaint, PointShape, SectorParameters,
+};
+use crate::render::render_bundle::{RenderPrimitive, TessellationError};
+use crate::render::{
+    ColorGradient, HighlightStyle, ImagePaint, LinePaint, LinePattern, PolygonGradient,
+    PolygonPaint, PrimitiveId, SizeUnit, ZoomInterpolation,
+};
 use crate::view::MapView;
 use crate::Color;
-use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2d, Point3d};
+use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2d, Point3d, Rect};
 use galileo_types::contour::Contour;
 use galileo_types::impls::ClosedContour;
 use galileo_types::Polygon;
@@ -18,25 +23,78 @@ use lyon::path::builder::PathBuilder;
 use lyon::path::path::BuilderWithAttributes;
 use lyon::path::{EndpointId, Path};
 use lyon::tessellation::VertexSource;
-use nalgebra::{Point2, Vector2};
+use nalgebra::{Point2, Rotation2, Vector2};
 use num_traits::AsPrimitive;
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::mem::size_of;
 use std::ops::Range;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 
+/// Number of segments of the polygon approximating a circle with the radius in map units.
+const MAP_CIRCLE_SEGMENTS: usize = 64;
+
 #[derive(Debug, Clone)]
 pub(crate) struct TessellatingRenderBundle {
     pub poly_tessellation: VertexBuffers<PolyVertex, u32>,
     pub points: Vec<PointInstance>,
+    pub circles: Vec<CircleInstance>,
     pub screen_ref: ScreenRefTessellation,
     pub images: Vec<(usize, [ImageVertex; 4])>,
+    pub image_points: Vec<ImageInstance>,
     pub clip_area: Option<VertexBuffers<PolyVertex, u32>>,
     pub image_store: Vec<Arc<DecodedImage>>,
     pub primitives: Vec<PrimitiveInfo>,
     vacant_ids: Vec<usize>,
     buffer_size: usize,
+    scratch: TessellationScratch,
+    /// Highlight styles of the highlighted primitives by their ids, with the colors the primitives had before being
+    /// highlighted.
+    highlighted: HashMap<usize, (HighlightStyle, OriginalColors)>,
+}
+
+/// Colors of a primitive before it was highlighted.
+#[derive(Debug, Clone)]
+enum OriginalColors {
+    /// Colors at both ends of the zoom interpolation.
+    MapRef(Vec<([f32; 4], [f32; 4])>),
+    ScreenRef(Vec<[u8; 4]>),
+    Dot([u8; 4]),
+    Circle([[u8; 4]; 3]),
+    /// Primitive has no colors that can be highlighted.
+    None,
+}
+
+/// Tessellators reused for all primitives added to a bundle. Tessellators keep their internal buffers between calls,
+/// so reusing them avoids allocating new buffers for every feature.
+pub(crate) struct TessellationScratch {
+    fill: FillTessellator,
+    stroke: StrokeTessellator,
+}
+
+impl Default for TessellationScratch {
+    fn default() -> Self {
+        Self {
+            fill: FillTessellator::new(),
+            stroke: StrokeTessellator::new(),
+        }
+    }
+}
+
+impl Clone for TessellationScratch {
+    fn clone(&self) -> Self {
+        // Scratch buffers contain no data that must be preserved.
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for TessellationScratch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TessellationScratch")
+            .finish_non_exhaustive()
+    }
 }
 
 pub(crate) type ScreenRefTessellation = VertexBuffers<ScreenRefVertex, u32>;
@@ -44,9 +102,11 @@ pub(crate) type ScreenRefTessellation = VertexBuffers<ScreenRefVertex, u32>;
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub(crate) struct ScreenRefVertex {
-    position: [f32; 3],
-    normal: [f32; 2],
-    color: [u8; 4],
+    pub position: [f32; 3],
+    pub normal: [f32; 2],
+    pub color: [u8; 4],
+    /// Rotation of the normal, see [`PointPaint::rotation_vertex_params`].
+    pub rotation: [f32; 2],
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -55,7 +115,24 @@ pub(crate) enum PrimitiveInfo {
     MapRef { vertex_range: Range<usize> },
     ScreenRef { vertex_range: Range<usize> },
     Dot { point_index: usize },
+    Circle { circle_index: usize },
     Image { image_index: usize },
+    ImagePoint { instance_index: usize },
+}
+
+/// Sizes of the buffers of a bundle before a primitive is added. Used to remove the partially tessellated primitive
+/// if tessellation fails.
+struct BufferMarks {
+    poly_vertices: usize,
+    poly_indices: usize,
+    screen_ref_vertices: usize,
+    screen_ref_indices: usize,
+    points: usize,
+    circles: usize,
+    images: usize,
+    image_points: usize,
+    image_store: usize,
+    buffer_size: usize,
 }
 
 impl Default for TessellatingRenderBundle {
@@ -69,13 +146,17 @@ impl TessellatingRenderBundle {
         Self {
             poly_tessellation: VertexBuffers::new(),
             points: Vec::new(),
+            circles: Vec::new(),
             screen_ref: VertexBuffers::new(),
             images: Vec::new(),
+            image_points: Vec::new(),
             primitives: Vec::new(),
             clip_area: None,
             image_store: Vec::new(),
             vacant_ids: vec![],
             buffer_size: 0,
+            scratch: TessellationScratch::default(),
+            highlighted: HashMap::new(),
         }
     }
 
@@ -91,13 +172,19 @@ impl TessellatingRenderBundle {
         Poly::Contour: Contour<Point = P>,
     {
         let mut tessellation = VertexBuffers::new();
-        Self::tessellate_polygon(
+        if let Err(err) = Self::tessellate_polygon(
             polygon,
             PolygonPaint {
                 color: Color::BLACK,
+                gradient: None,
+                zoom: None,
             },
             &mut tessellation,
-        );
+            &mut self.scratch.fill,
+        ) {
+            log::error!("Failed to tessellate clip area: {err}");
+            return;
+        }
 
         self.buffer_size += tessellation.vertices.len() * std::mem::size_of::<PolyVertex>()
             + tessellation.indices.len() * std::mem::size_of::<u32>();
@@ -125,24 +212,32 @@ impl TessellatingRenderBundle {
                     opacity,
                     tex_coords: [0.0, 1.0],
                     offset: [0.0, 0.0],
+                    map_aligned: 0.0,
+                    rotation: [0.0, 0.0],
                 },
                 ImageVertex {
                     position: [vertices[1].x() as f32, vertices[1].y() as f32],
                     opacity,
                     tex_coords: [0.0, 0.0],
                     offset: [0.0, 0.0],
+                    map_aligned: 0.0,
+                    rotation: [0.0, 0.0],
                 },
                 ImageVertex {
                     position: [vertices[3].x() as f32, vertices[3].y() as f32],
                     opacity,
                     tex_coords: [1.0, 1.0],
                     offset: [0.0, 0.0],
+                    map_aligned: 0.0,
+                    rotation: [0.0, 0.0],
                 },
                 ImageVertex {
                     position: [vertices[2].x() as f32, vertices[2].y() as f32],
                     opacity,
                     tex_coords: [1.0, 0.0],
                     offset: [0.0, 0.0],
+                    map_aligned: 0.0,
+                    rotation: [0.0, 0.0],
                 },
             ],
         ));
@@ -161,52 +256,40 @@ impl TessellatingRenderBundle {
         width: f32,
         height: f32,
         offset: Vector2<f32>,
+        alignment: PointAlignment,
+        rotation: [f32; 2],
     ) -> PrimitiveInfo
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
     {
-        let opacity = opacity as f32 / 255.0;
-        let image_index = self.images.len();
-
-        self.buffer_size += image.bytes.len() + size_of::<ImageVertex>() * 4;
+        let map_aligned = match alignment {
+            PointAlignment::Screen => 0.0,
+            PointAlignment::Map => 1.0,
+        };
 
-        let position = [position.x().as_(), position.y().as_()];
-        let offset_x = -offset[0] * width;
-        let offset_y = offset[1] * height;
+        // The image data is stored once for all the points using it.
+        let image_size = image.bytes.len();
+        let store_len = self.image_store.len();
+        let texture_index = self.add_image_to_store(image);
+        if texture_index == store_len {
+            self.buffer_size += image_size;
+        }
 
-        let index = self.add_image_to_store(image);
-        self.images.push((
-            index,
-            [
-                ImageVertex {
-                    position,
-                    opacity,
-                    tex_coords: [0.0, 1.0],
-                    offset: [offset_x, offset_y - height],
-                },
-                ImageVertex {
-                    position,
-                    opacity,
-                    tex_coords: [0.0, 0.0],
-                    offset: [offset_x, offset_y],
-                },
-                ImageVertex {
-                    position,
-                    opacity,
-                    tex_coords: [1.0, 1.0],
-                    offset: [offset_x + width, offset_y - height],
-                },
-                ImageVertex {
-                    position,
-                    opacity,
-                    tex_coords: [1.0, 0.0],
-                    offset: [offset_x + width, offset_y],
-                },
-            ],
-        ));
+        self.image_points.push(ImageInstance {
+            position: [position.x().as_(), position.y().as_()],
+            offset: [-offset[0] * width, offset[1] * height],
+            size: [width, height],
+            rotation,
+            opacity: opacity as f32 / 255.0,
+            map_aligned,
+            texture_index: texture_index as u32,
+        });
+        self.buffer_size += size_of::<ImageInstance>();
 
-        PrimitiveInfo::Image { image_index }
+        PrimitiveInfo::ImagePoint {
+            instance_index: self.image_points.len() - 1,
+        }
     }
 
     fn add_primitive_info(&mut self, info: PrimitiveInfo) -> PrimitiveId {
@@ -244,17 +327,93 @@ impl TessellatingRenderBundle {
         Poly: Polygon + Clone,
         Poly::Contour: Contour<Point = P>,
     {
-        match primitive {
-            RenderPrimitive::Point(point, paint) => self.add_point::<N, P>(point.borrow(), paint),
+        match self.try_add(primitive, min_resolution) {
+            Ok(id) => id,
+            Err(err) => {
+                log::warn!("Primitive is not rendered: {err}");
+                self.add_empty()
+            }
+        }
+    }
+
+    /// Adds a primitive that has nothing to draw, to keep the ids of a failed primitive valid.
+    pub fn add_empty(&mut self) -> PrimitiveId {
+        self.add_primitive_info(PrimitiveInfo::Vacant)
+    }
+
+    pub fn try_add<N, P, C, Poly>(
+        &mut self,
+        primitive: RenderPrimitive<N, P, C, Poly>,
+        min_resolution: f64,
+    ) -> Result<PrimitiveId, TessellationError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+        C: Contour<Point = P> + Clone,
+        Poly: Polygon + Clone,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let marks = self.buffer_marks();
+
+        // Lyon is not expected to panic after the input is validated, but a panic in the tessellator must not bring
+        // down the render loop, so it is treated as any other tessellation error.
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| match primitive {
+            RenderPrimitive::Point(point, paint) => {
+                self.add_point::<N, P>(point.borrow(), paint, min_resolution)
+            }
             RenderPrimitive::Contour(contour, paint) => {
                 self.add_line::<N, P, C>(contour.borrow(), paint, min_resolution)
             }
             RenderPrimitive::Polygon(polygon, paint) => {
                 self.add_polygon::<N, P, Poly>(polygon.borrow(), paint, min_resolution)
             }
+        }))
+        .unwrap_or_else(|_| {
+            // Internal state of the tessellators is unknown after a panic.
+            self.scratch = TessellationScratch::default();
+            Err(TessellationError::Panicked)
+        });
+
+        match result {
+            Ok(info) => Ok(self.add_primitive_info(info)),
+            Err(err) => {
+                self.rollback(marks);
+                Err(err)
+            }
+        }
+    }
+
+    fn buffer_marks(&self) -> BufferMarks {
+        BufferMarks {
+            poly_vertices: self.poly_tessellation.vertices.len(),
+            poly_indices: self.poly_tessellation.indices.len(),
+            screen_ref_vertices: self.screen_ref.vertices.len(),
+            screen_ref_indices: self.screen_ref.indices.len(),
+            points: self.points.len(),
+            circles: self.circles.len(),
+            images: self.images.len(),
+            image_points: self.image_points.len(),
+            image_store: self.image_store.len(),
+            buffer_size: self.buffer_size,
         }
     }
 
+    /// Removes everything added to the buffers after the marks were taken.
+    fn rollback(&mut self, marks: BufferMarks) {
+        self.poly_tessellation
+            .vertices
+            .truncate(marks.poly_vertices);
+        self.poly_tessellation.indices.truncate(marks.poly_indices);
+        self.screen_ref.vertices.truncate(marks.screen_ref_vertices);
+        self.screen_ref.indices.truncate(marks.screen_ref_indices);
+        self.points.truncate(marks.points);
+        self.circles.truncate(marks.circles);
+        self.images.truncate(marks.images);
+        self.image_points.truncate(marks.image_points);
+        self.image_store.truncate(marks.image_store);
+        self.buffer_size = marks.buffer_size;
+    }
+
     pub fn update<N, P, C, Poly>(
         &mut self,
         primitive_id: PrimitiveId,
@@ -274,13 +433,129 @@ impl TessellatingRenderBundle {
         }
 
         let info = &self.primitives[primitive_id.0];
+        let highlight = self.highlighted.remove(&primitive_id.0);
 
-        match info {
+        let result = match info {
             PrimitiveInfo::MapRef { vertex_range } => {
                 self.update_map_ref(vertex_range.clone(), primitive)
             }
             PrimitiveInfo::Vacant => Ok(()),
-            _ => todo!(),
+            _ => Err(GalileoError::Generic(
+                "only lines and polygons can be updated".into(),
+            )),
+        };
+
+        if let Some((style, original)) = highlight {
+            if result.is_ok() {
+                // Colors were replaced by the update, so the new ones must be saved and highlighted again.
+                self.highlight(primitive_id.0, style);
+            } else {
+                self.highlighted.insert(primitive_id.0, (style, original));
+            }
+        }
+
+        result
+    }
+
+    pub fn set_highlight(
+        &mut self,
+        primitive_id: PrimitiveId,
+        style: Option<HighlightStyle>,
+    ) -> Result<(), GalileoError> {
+        if primitive_id.0 >= self.primitives.len() {
+            return Err(GalileoError::Generic(
+                "no primitive with the given id".into(),
+            ));
+        }
+
+        if let Some((_, original)) = self.highlighted.remove(&primitive_id.0) {
+            self.restore_colors(primitive_id.0, original);
+        }
+
+        if let Some(style) = style {
+            self.highlight(primitive_id.0, style);
+        }
+
+        Ok(())
+    }
+
+    /// Applies the highlight style to the colors of the primitive, saving the original colors.
+    fn highlight(&mut self, id: usize, style: HighlightStyle) {
+        let original = match &self.primitives[id] {
+            PrimitiveInfo::MapRef { vertex_range } => {
+                let vertices = &mut self.poly_tessellation.vertices[vertex_range.clone()];
+                let colors = vertices
+                    .iter()
+                    .map(|vertex| (vertex.color, vertex.zoom_color))
+                    .collect();
+                for vertex in vertices {
+                    vertex.color = style.apply_f32(vertex.color);
+                    vertex.zoom_color = style.apply_f32(vertex.zoom_color);
+                }
+
+                OriginalColors::MapRef(colors)
+            }
+            PrimitiveInfo::ScreenRef { vertex_range } => {
+                let vertices = &mut self.screen_ref.vertices[vertex_range.clone()];
+                let colors = vertices.iter().map(|vertex| vertex.color).collect();
+                for vertex in vertices {
+                    vertex.color = style.apply_u8(vertex.color);
+                }
+
+                OriginalColors::ScreenRef(colors)
+            }
+            PrimitiveInfo::Dot { point_index } => {
+                let point = &mut self.points[*point_index];
+                let color = point.color;
+                point.color = style.apply_u8(color);
+
+                OriginalColors::Dot(color)
+            }
+            PrimitiveInfo::Circle { circle_index } => {
+                let circle = &mut self.circles[*circle_index];
+                let colors = [circle.center_color, circle.side_color, circle.outline_color];
+                circle.center_color = style.apply_u8(colors[0]);
+                circle.side_color = style.apply_u8(colors[1]);
+                circle.outline_color = style.apply_u8(colors[2]);
+
+                OriginalColors::Circle(colors)
+            }
+            PrimitiveInfo::Image { .. }
+            | PrimitiveInfo::ImagePoint { .. }
+            | PrimitiveInfo::Vacant => OriginalColors::None,
+        };
+
+        self.highlighted.insert(id, (style, original));
+    }
+
+    fn restore_colors(&mut self, id: usize, original: OriginalColors) {
+        match (&self.primitives[id], original) {
+            (PrimitiveInfo::MapRef { vertex_range }, OriginalColors::MapRef(colors)) => {
+                for (vertex, (color, zoom_color)) in self.poly_tessellation.vertices
+                    [vertex_range.clone()]
+                .iter_mut()
+                .zip(colors)
+                {
+                    vertex.color = color;
+                    vertex.zoom_color = zoom_color;
+                }
+            }
+            (PrimitiveInfo::ScreenRef { vertex_range }, OriginalColors::ScreenRef(colors)) => {
+                for (vertex, color) in self.screen_ref.vertices[vertex_range.clone()]
+                    .iter_mut()
+                    .zip(colors)
+                {
+                    vertex.color = color;
+                }
+            }
+            (PrimitiveInfo::Dot { point_index }, OriginalColors::Dot(color)) => {
+                self.points[*point_index].color = color;
+            }
+            (PrimitiveInfo::Circle { circle_index }, OriginalColors::Circle(colors)) => {
+                let circle = &mut self.circles[*circle_index];
+                [circle.center_color, circle.side_color, circle.outline_color] = colors;
+            }
+            _ => {}
         }
     }
 
@@ -292,12 +567,15 @@ impl TessellatingRenderBundle {
         }
 
         let info = std::mem::replace(&mut self.primitives[primitive_id.0], PrimitiveInfo::Vacant);
+        self.highlighted.remove(&primitive_id.0);
 
         match info {
             PrimitiveInfo::MapRef { vertex_range } => self.remove_map_ref(vertex_range),
             PrimitiveInfo::ScreenRef { vertex_range } => self.remove_screen_ref(vertex_range),
             PrimitiveInfo::Dot { point_index } => self.remove_dot(point_index),
+            PrimitiveInfo::Circle { circle_index } => self.remove_circle(circle_index),
             PrimitiveInfo::Image { image_index } => self.remove_image(image_index),
+            PrimitiveInfo::ImagePoint { instance_index } => self.remove_image_point(instance_index),
             PrimitiveInfo::Vacant => Ok(()),
         }
     }
@@ -326,6 +604,30 @@ impl TessellatingRenderBundle {
         }
     }
 
+    fn remove_image_point(&mut self, index: usize) -> Result<(), GalileoError> {
+        if index >= self.image_points.len() {
+            Err(GalileoError::Generic("index out of bounds".into()))
+        } else {
+            // The image stays in the store, as other points may use it.
+            self.image_points.remove(index);
+
+            self.buffer_size -= size_of::<ImageInstance>();
+
+            for info in &mut self.primitives {
+                match info {
+                    PrimitiveInfo::ImagePoint {
+                        ref mut instance_index,
+                    } if *instance_index > index => {
+                        *instance_index -= 1;
+                    }
+                    _ => {}
+                }
+            }
+
+            Ok(())
+        }
+    }
+
     fn remove_dot(&mut self, index: usize) -> Result<(), GalileoError> {
         if index >= self.points.len() {
             Err(GalileoError::Generic("index out of bounds".into()))
@@ -349,6 +651,29 @@ impl TessellatingRenderBundle {
         }
     }
 
+    fn remove_circle(&mut self, index: usize) -> Result<(), GalileoError> {
+        if index >= self.circles.len() {
+            Err(GalileoError::Generic("index out of bounds".into()))
+        } else {
+            self.circles.remove(index);
+
+            self.buffer_size -= size_of::<CircleInstance>();
+
+            for info in &mut self.primitives {
+                match info {
+                    PrimitiveInfo::Circle {
+                        ref mut circle_index,
+                    } if *circle_index > index => {
+                        *circle_index -= 1;
+                    }
+                    _ => {}
+                }
+            }
+
+            Ok(())
+        }
+    }
+
     fn remove_screen_ref(&mut self, range: Range<usize>) -> Result<(), GalileoError> {
         let removed_index_count =
             Self::remove_from_tessellation(&mut self.screen_ref, range.clone())?;
@@ -423,11 +748,32 @@ impl TessellatingRenderBundle {
         Ok(length_before - length_after)
     }
 
-    pub fn add_point<N, P>(&mut self, point: &P, paint: PointPaint) -> PrimitiveId
+    fn add_point<N, P>(
+        &mut self,
+        point: &P,
+        paint: PointPaint,
+        min_resolution: f64,
+    ) -> Result<PrimitiveInfo, TessellationError>
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
     {
+        check_position(point)?;
+        check_point_paint(&paint)?;
+
+        if paint.alignment == PointAlignment::Map {
+            if let Some(vertex_range) = self.add_map_aligned_point(point, &paint)? {
+                if paint.rotation != 0.0 {
+                    let rotation = Rotation2::new(-paint.rotation);
+                    for vertex in &mut self.poly_tessellation.vertices[vertex_range.clone()] {
+                        vertex.normal = (rotation * Vector2::from(vertex.normal)).into();
+                    }
+                }
+
+                return Ok(PrimitiveInfo::MapRef { vertex_range });
+            }
+        }
+
         let start_index = self.screen_ref.vertices.len();
         let info = match &paint.shape {
             PointShape::Dot { color } => {
@@ -448,6 +794,8 @@ impl TessellatingRenderBundle {
                 *width,
                 *height,
                 paint.offset,
+                paint.alignment,
+                paint.rotation_vertex_params(),
             ),
             PointShape::Circle {
                 fill,
@@ -455,12 +803,25 @@ impl TessellatingRenderBundle {
                 outline,
             } => {
                 self.add_circle(point, *fill, *radius, *outline, paint.offset);
-                PrimitiveInfo::ScreenRef {
-                    vertex_range: start_index..self.screen_ref.vertices.len(),
+                PrimitiveInfo::Circle {
+                    circle_index: self.circles.len() - 1,
                 }
             }
+            PointShape::MapCircle {
+                fill,
+                radius,
+                outline,
+            } => PrimitiveInfo::MapRef {
+                vertex_range: self.add_map_circle(
+                    point,
+                    *fill,
+                    *radius,
+                    *outline,
+                    min_resolution,
+                )?,
+            },
             PointShape::Sector(parameters) => {
-                self.add_circle_sector(point, *parameters, paint.offset);
+                self.add_circle_sector(point, *parameters, paint.offset)?;
                 PrimitiveInfo::ScreenRef {
                     vertex_range: start_index..self.screen_ref.vertices.len(),
                 }
@@ -470,7 +831,7 @@ impl TessellatingRenderBundle {
                 size,
                 outline,
             } => {
-                self.add_shape(point, *fill, *size, *outline, &square_shape(), paint.offset);
+                self.add_shape(point, *fill, *size, *outline, &square_shape(), paint.offset)?;
                 PrimitiveInfo::ScreenRef {
                     vertex_range: start_index..self.screen_ref.vertices.len(),
                 }
@@ -481,30 +842,37 @@ impl TessellatingRenderBundle {
                 outline,
                 shape,
             } => {
-                self.add_shape(point, *fill, *scale, *outline, shape, paint.offset);
+                self.add_shape(point, *fill, *scale, *outline, shape.as_ref(), paint.offset)?;
                 PrimitiveInfo::ScreenRef {
                     vertex_range: start_index..self.screen_ref.vertices.len(),
                 }
             }
         };
 
-        self.add_primitive_info(info)
+        if let PrimitiveInfo::ScreenRef { vertex_range } = &info {
+            let rotation = paint.rotation_vertex_params();
+            for vertex in &mut self.screen_ref.vertices[vertex_range.clone()] {
+                vertex.rotation = rotation;
+            }
+        }
+
+        Ok(info)
     }
 
-    pub fn add_line<N, P, C>(
+    fn add_line<N, P, C>(
         &mut self,
         line: &C,
         paint: LinePaint,
         min_resolution: f64,
-    ) -> PrimitiveId
+    ) -> Result<PrimitiveInfo, TessellationError>
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
         C: Contour<Point = P>,
     {
-        let range = self.add_line_lod(line, paint, min_resolution);
+        let range = self.add_line_lod(line, paint, min_resolution)?;
 
-        self.add_primitive_info(PrimitiveInfo::MapRef {
+        Ok(PrimitiveInfo::MapRef {
             vertex_range: range,
         })
     }
@@ -514,7 +882,7 @@ impl TessellatingRenderBundle {
         line: &C,
         paint: LinePaint,
         min_resolution: f64,
-    ) -> Range<usize>
+    ) -> Result<Range<usize>, TessellationError>
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
@@ -525,78 +893,105 @@ impl TessellatingRenderBundle {
         let mut iterator = line.iter_points();
 
         let Some(first_point) = iterator.next() else {
-            return 0..0;
+            return Err(TessellationError::EmptyGeometry);
         };
 
-        let _ = path_builder.begin(
-            point(
-                first_point.x().as_() / min_resolution as f32,
-                first_point.y().as_() / min_resolution as f32,
-            ),
-            &[first_point.z().as_()],
-        );
+        let scaled = |p: &P| -> Result<_, TessellationError> {
+            let [x, y, z] = check_position(p)?;
+            let scaled = point(x / min_resolution as f32, y / min_resolution as f32);
+            if scaled.x.is_finite() && scaled.y.is_finite() {
+                Ok((scaled, z))
+            } else {
+                Err(TessellationError::NonFiniteCoordinates)
+            }
+        };
 
+        let (first, z) = scaled(first_point)?;
+        let _ = path_builder.begin(first, &[z]);
+
+        let mut length = 0.0;
+        let mut prev = first;
         for p in iterator {
-            let _ = path_builder.line_to(
-                point(
-                    p.x().as_() / min_resolution as f32,
-                    p.y().as_() / min_resolution as f32,
-                ),
-                &[p.z().as_()],
-            );
+            let (next, z) = scaled(p)?;
+            let _ = path_builder.line_to(next, &[z]);
+            length += (next - prev).length();
+            prev = next;
+        }
+
+        if line.is_closed() {
+            length += (first - prev).length();
         }
 
         path_builder.end(line.is_closed());
         let path = path_builder.build();
 
+        // Widths in map units are converted into the units of the path, so that lines are tessellated with their
+        // actual width and not extended in the shader.
+        // Lines with zoom interpolation are tessellated with the largest width and scaled down in the shader.
+        let paint_width = paint.zoom.map_or(paint.width, |zoom| zoom.max_width());
+        let (width, offset) = match paint.width_unit {
+            SizeUnit::Pixels => (paint_width, paint.offset),
+            SizeUnit::MapUnits => (paint_width / min_resolution, paint.offset / min_resolution),
+        };
+        check_size("line width", width)?;
+        if !offset.is_finite() {
+            return Err(TessellationError::InvalidPaint(format!(
+                "line offset must be finite, but is {offset}"
+            )));
+        }
+
         let vertex_constructor = LineVertexConstructor {
-            width: paint.width as f32,
-            offset: paint.offset as f32,
-            color: paint.color.to_f32_array(),
+            width: width as f32,
+            offset: offset as f32,
+            map_units: paint.width_unit == SizeUnit::MapUnits,
+            color: paint.color,
+            gradient: paint.gradient,
+            zoom: paint.zoom,
+            length,
             resolution: min_resolution as f32,
+            pattern: LinePattern::to_vertex_params(paint.pattern),
             path: &path,
         };
 
-        let mut tesselator = StrokeTessellator::new();
         let start_index = tessellation.vertices.len();
         let start_index_count = tessellation.indices.len();
 
-        if let Err(err) = tesselator.tessellate_path(
-            &path,
-            &StrokeOptions::DEFAULT
-                .with_line_cap(paint.line_cap.into())
-                .with_line_width(paint.width as f32)
-                .with_miter_limit(1.0)
-                .with_tolerance(0.1)
-                .with_line_join(LineJoin::Round),
-            &mut BuffersBuilder::new(tessellation, vertex_constructor),
-        ) {
-            log::error!("Tessellation failed: {err}");
-            return 0..0;
-        }
+        self.scratch
+            .stroke
+            .tessellate_path(
+                &path,
+                &StrokeOptions::DEFAULT
+                    .with_line_cap(paint.line_cap.into())
+                    .with_line_width(width as f32)
+                    .with_miter_limit(1.0)
+                    .with_tolerance(0.1)
+                    .with_line_join(LineJoin::Round),
+                &mut BuffersBuilder::new(tessellation, vertex_constructor),
+            )
+            .map_err(tessellator_error)?;
 
         let end_index = tessellation.vertices.len();
 
         self.buffer_size += (end_index - start_index) * size_of::<PolyVertex>();
         self.buffer_size += (tessellation.indices.len() - start_index_count) * size_of::<u32>();
 
-        start_index..end_index
+        Ok(start_index..end_index)
     }
 
-    pub fn add_polygon<N, P, Poly>(
+    fn add_polygon<N, P, Poly>(
         &mut self,
         polygon: &Poly,
         paint: PolygonPaint,
         min_resolution: f64,
-    ) -> PrimitiveId
+    ) -> Result<PrimitiveInfo, TessellationError>
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
         Poly: Polygon,
         Poly::Contour: Contour<Point = P>,
     {
-        let vertex_range = self.add_polygon_lod(polygon, paint, min_resolution as f32);
-        self.add_primitive_info(PrimitiveInfo::MapRef { vertex_range })
+        let vertex_range = self.add_polygon_lod(polygon, paint, min_resolution as f32)?;
+        Ok(PrimitiveInfo::MapRef { vertex_range })
     }
 
     pub fn modify_image(&mut self, id: PrimitiveId, paint: ImagePaint) -> Result<(), GalileoError> {
@@ -614,6 +1009,13 @@ impl TessellatingRenderBundle {
                     vertex.opacity = paint.opacity as f32 / 255.0;
                 }
             }
+            PrimitiveInfo::ImagePoint { instance_index } => {
+                let instance = self
+                    .image_points
+                    .get_mut(*instance_index)
+                    .ok_or(GalileoError::Generic("invalid image id".into()))?;
+                instance.opacity = paint.opacity as f32 / 255.0;
+            }
             _ => return Err(GalileoError::Generic("invalid primitive type".into())),
         }
 
@@ -632,18 +1034,44 @@ impl TessellatingRenderBundle {
         Poly: Polygon + Clone,
         Poly::Contour: Contour<Point = P>,
     {
-        let color = match primitive {
-            RenderPrimitive::Contour(_, LinePaint { color, .. })
-            | RenderPrimitive::Polygon(_, PolygonPaint { color }) => color,
+        let vertices = &mut self.poly_tessellation.vertices[range];
+        match primitive {
+            RenderPrimitive::Contour(
+                _,
+                LinePaint {
+                    color,
+                    gradient,
+                    zoom,
+                    ..
+                },
+            ) => {
+                let length = vertices
+                    .iter()
+                    .map(|vertex| vertex.line_position[0])
+                    .fold(0.0, f32::max);
+                for vertex in vertices {
+                    let color = line_color(color, gradient, vertex.line_position[0], length);
+                    vertex.set_zoom_colors(color, zoom);
+                }
+            }
+            RenderPrimitive::Polygon(
+                _,
+                PolygonPaint {
+                    color,
+                    gradient,
+                    zoom,
+                },
+            ) => {
+                for vertex in vertices {
+                    let color = polygon_color(color, gradient, vertex.position);
+                    vertex.set_zoom_colors(color, zoom);
+                }
+            }
             _ => {
                 return Err(GalileoError::Generic(
                     "expected line or polygon primitive, but got a point".into(),
                 ));
             }
-        };
-
-        for vertex in &mut self.poly_tessellation.vertices[range] {
-            vertex.color = color.to_f32_array();
         }
 
         Ok(())
@@ -654,7 +1082,7 @@ impl TessellatingRenderBundle {
         polygon: &Poly,
         paint: PolygonPaint,
         _min_resolution: f32,
-    ) -> Range<usize>
+    ) -> Result<Range<usize>, TessellationError>
     where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
@@ -665,25 +1093,71 @@ impl TessellatingRenderBundle {
         let start_index = lod.vertices.len();
         let start_index_count = lod.indices.len();
 
-        Self::tessellate_polygon(polygon, paint, lod);
+        Self::tessellate_polygon(polygon, paint, lod, &mut self.scratch.fill)?;
 
         let end_index = lod.vertices.len();
 
         self.buffer_size += (end_index - start_index) * size_of::<PolyVertex>();
         self.buffer_size += (lod.indices.len() - start_index_count) * size_of::<u32>();
 
-        start_index..end_index
+        Ok(start_index..end_index)
     }
 
     pub fn is_empty(&self) -> bool {
         self.primitives.is_empty()
     }
 
+    /// Calculates the area of the map that the bundle can be drawn to. Returns `None` if the bundle contains no
+    /// geometries.
+    pub fn bounds(&self) -> Option<BundleBounds> {
+        let mut bounds = BundleBoundsBuilder::default();
+
+        for vertex in &self.poly_tessellation.vertices {
+            bounds.add(vertex.position[0], vertex.position[1], vertex.position[2]);
+            bounds.add_margin(vertex.normal);
+        }
+
+        for vertex in &self.screen_ref.vertices {
+            bounds.add(vertex.position[0], vertex.position[1], vertex.position[2]);
+            bounds.add_margin(vertex.normal);
+        }
+
+        for point in &self.points {
+            bounds.add(point.position[0], point.position[1], point.position[2]);
+            bounds.add_extent(BundleBoundsBuilder::DOT_SIZE);
+        }
+
+        for circle in &self.circles {
+            bounds.add(circle.position[0], circle.position[1], circle.position[2]);
+            bounds.add_extent(
+                circle.offset[0].hypot(circle.offset[1]) + circle.radius + circle.outline_width,
+            );
+        }
+
+        for (_, vertices) in &self.images {
+            for vertex in vertices {
+                bounds.add(vertex.position[0], vertex.position[1], 0.0);
+                bounds.add_margin(vertex.offset);
+            }
+        }
+
+        for instance in &self.image_points {
+            bounds.add(instance.position[0], instance.position[1], 0.0);
+            for vertex in instance.vertices() {
+                bounds.add_margin(vertex.offset);
+            }
+        }
+
+        bounds.build()
+    }
+
     fn tessellate_polygon<N, P, Poly>(
         polygon: &Poly,
         paint: PolygonPaint,
         tessellation: &mut VertexBuffers<PolyVertex, u32>,
-    ) where
+        tessellator: &mut FillTessellator,
+    ) -> Result<(), TessellationError>
+    where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
         Poly: Polygon,
@@ -693,17 +1167,16 @@ impl TessellatingRenderBundle {
         for contour in polygon.iter_contours() {
             let mut iterator = contour.iter_points();
 
-            if let Some(first_point) = iterator.next() {
-                let _ = path_builder.begin(
-                    point(first_point.x().as_(), first_point.y().as_()),
-                    &[first_point.z().as_()],
-                );
-            } else {
-                return;
-            }
+            let Some(first_point) = iterator.next() else {
+                return Err(TessellationError::EmptyGeometry);
+            };
+
+            let [x, y, z] = check_position(first_point)?;
+            let _ = path_builder.begin(point(x, y), &[z]);
 
             for p in iterator {
-                let _ = path_builder.line_to(point(p.x().as_(), p.y().as_()), &[p.z().as_()]);
+                let [x, y, z] = check_position(p)?;
+                let _ = path_builder.line_to(point(x, y), &[z]);
             }
 
             path_builder.end(true);
@@ -712,17 +1185,20 @@ impl TessellatingRenderBundle {
         let path = path_builder.build();
 
         let vertex_constructor = PolygonVertexConstructor {
-            color: paint.color.to_f32_array(),
+            color: paint.color,
+            gradient: paint.gradient,
+            zoom: paint.zoom,
         };
-        let mut tesselator = FillTessellator::new();
 
-        if let Err(err) = tesselator.tessellate(
-            &path,
-            &FillOptions::DEFAULT,
-            &mut BuffersBuilder::new(tessellation, vertex_constructor),
-        ) {
-            log::error!("Tessellation failed: {err:?}");
-        }
+        tessellator
+            .tessellate(
+                &path,
+                &FillOptions::DEFAULT,
+                &mut BuffersBuilder::new(tessellation, vertex_constructor),
+            )
+            .map_err(tessellator_error)?;
+
+        Ok(())
     }
 
     pub fn add_shape<N, P>(
@@ -733,12 +1209,13 @@ impl TessellatingRenderBundle {
         outline: Option<LinePaint>,
         shape: &ClosedContour<Point2<f32>>,
         offset: Vector2<f32>,
-    ) where
+    ) -> Result<(), TessellationError>
+    where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
     {
         let mut path_builder = BuilderWithAttributes::new(0);
-        build_contour_path(&mut path_builder, shape, scale);
+        build_contour_path(&mut path_builder, shape, scale)?;
         let path = path_builder.build();
 
         let start_vertex_count = self.screen_ref.vertices.len();
@@ -751,14 +1228,14 @@ impl TessellatingRenderBundle {
                 offset,
             };
 
-            if let Err(err) = StrokeTessellator::new().tessellate(
-                &path,
-                &StrokeOptions::DEFAULT.with_line_width(outline.width as f32 * 2.0),
-                &mut BuffersBuilder::new(&mut self.screen_ref, vertex_constructor),
-            ) {
-                log::warn!("Shape tessellation failed: {err:?}");
-                return;
-            }
+            self.scratch
+                .stroke
+                .tessellate(
+                    &path,
+                    &StrokeOptions::DEFAULT.with_line_width(outline.width as f32 * 2.0),
+                    &mut BuffersBuilder::new(&mut self.screen_ref, vertex_constructor),
+                )
+                .map_err(tessellator_error)?;
         }
 
         if !fill.is_transparent() {
@@ -768,19 +1245,22 @@ impl TessellatingRenderBundle {
                 offset,
             };
 
-            if let Err(err) = FillTessellator::new().tessellate(
-                &path,
-                &FillOptions::DEFAULT,
-                &mut BuffersBuilder::new(&mut self.screen_ref, vertex_constructor),
-            ) {
-                log::warn!("Shape tessellation failed: {err:?}");
-            }
+            self.scratch
+                .fill
+                .tessellate(
+                    &path,
+                    &FillOptions::DEFAULT,
+                    &mut BuffersBuilder::new(&mut self.screen_ref, vertex_constructor),
+                )
+                .map_err(tessellator_error)?;
         }
 
         self.buffer_size += (self.screen_ref.vertices.len() - start_vertex_count)
             * std::mem::size_of::<ScreenRefVertex>();
         self.buffer_size +=
             (self.screen_ref.indices.len() - start_index_count) * std::mem::size_of::<u32>();
+
+        Ok(())
     }
 
     fn add_circle<N, P>(
@@ -794,17 +1274,256 @@ impl TessellatingRenderBundle {
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
     {
-        self.add_circle_sector(
-            position,
-            SectorParameters {
-                fill,
+        let (outline_color, outline_width) = match outline {
+            Some(outline) => (outline.color, outline.width as f32),
+            None => (Color::TRANSPARENT, 0.0),
+        };
+
+        self.circles.push(CircleInstance {
+            position: [position.x().as_(), position.y().as_(), position.z().as_()],
+            offset: [offset.x, offset.y],
+            radius,
+            outline_width,
+            center_color: fill.center_color.to_u8_array(),
+            side_color: fill.side_color.to_u8_array(),
+            outline_color: outline_color.to_u8_array(),
+        });
+
+        self.buffer_size += size_of::<CircleInstance>();
+    }
+
+    /// Adds a point symbol lying flat on the map plane. Such symbols are tessellated as map-referenced primitives
+    /// with all the vertices at the point position, and the shape of the symbol set by the vertex normals in pixels,
+    /// same as the width of the lines.
+    ///
+    /// Returns `None` if the shape of the symbol does not depend on the alignment.
+    fn add_map_aligned_point<N, P>(
+        &mut self,
+        position: &P,
+        paint: &PointPaint,
+    ) -> Result<Option<Range<usize>>, TessellationError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+    {
+        let start_index = self.poly_tessellation.vertices.len();
+        let start_index_count = self.poly_tessellation.indices.len();
+        let position = [position.x().as_(), position.y().as_(), position.z().as_()];
+        let offset = paint.offset;
+
+        match &paint.shape {
+            PointShape::Circle {
+                fill,
                 radius,
-                start_angle: 0.0,
-                end_angle: std::f32::consts::PI * 2.0,
                 outline,
+            } => {
+                let contour = get_circle_sector(*radius, 0.0, std::f32::consts::TAU);
+                self.add_map_aligned_fan(position, *fill, &contour, offset);
+                if let Some(outline) = outline {
+                    self.add_map_aligned_shape(
+                        position,
+                        Color::TRANSPARENT,
+                        1.0,
+                        Some(*outline),
+                        &ClosedContour::new(contour),
+                        offset,
+                    )?;
+                }
+            }
+            PointShape::Sector(SectorParameters {
+                fill,
+                radius,
+                start_angle,
+                end_angle,
+                outline,
+            }) => {
+                let mut contour = get_circle_sector(*radius, *start_angle, *end_angle);
+                self.add_map_aligned_fan(position, *fill, &contour, offset);
+                if let Some(outline) = outline {
+                    contour.push(Point2::new(0.0, 0.0));
+                    self.add_map_aligned_shape(
+                        position,
+                        Color::TRANSPARENT,
+                        1.0,
+                        Some(*outline),
+                        &ClosedContour::new(contour),
+                        offset,
+                    )?;
+                }
+            }
+            PointShape::Square {
+                fill,
+                size,
+                outline,
+            } => self.add_map_aligned_shape(
+                position,
+                *fill,
+                *size,
+                *outline,
+                &square_shape(),
+                offset,
+            )?,
+            PointShape::FreeShape {
+                fill,
+                scale,
+                outline,
+                shape,
+            } => self.add_map_aligned_shape(
+                position,
+                *fill,
+                *scale,
+                *outline,
+                shape.as_ref(),
+                offset,
+            )?,
+            PointShape::Dot { .. } | PointShape::MapCircle { .. } | PointShape::Image { .. } => {
+                return Ok(None)
+            }
+        }
+
+        let end_index = self.poly_tessellation.vertices.len();
+        self.buffer_size += (end_index - start_index) * size_of::<PolyVertex>();
+        self.buffer_size +=
+            (self.poly_tessellation.indices.len() - start_index_count) * size_of::<u32>();
+
+        Ok(Some(start_index..end_index))
+    }
+
+    /// Adds a triangle fan from the point to the given contour, with the colors changing from the center to the
+    /// sides as in circle symbols.
+    fn add_map_aligned_fan(
+        &mut self,
+        position: [f32; 3],
+        fill: CircleFill,
+        contour: &[Point2<f32>],
+        offset: Vector2<f32>,
+    ) {
+        if contour.len() < 2 {
+            return;
+        }
+
+        let vertex = |normal: Point2<f32>, color: Color| PolyVertex {
+            position,
+            color: color.to_f32_array(),
+            normal: [normal.x + offset.x, normal.y + offset.y],
+            norm_limit: f32::MAX,
+            line_position: Default::default(),
+            pattern: Default::default(),
+            zoom_color: color.to_f32_array(),
+            zoom: ZoomInterpolation::NONE,
+        };
+
+        let tessellation = &mut self.poly_tessellation;
+        let center_index = tessellation.vertices.len() as u32;
+        tessellation
+            .vertices
+            .push(vertex(Point2::new(0.0, 0.0), fill.center_color));
+        for (index, point) in contour.iter().enumerate() {
+            tessellation.vertices.push(vertex(*point, fill.side_color));
+            if index > 0 {
+                let index = center_index + index as u32;
+                tessellation
+                    .indices
+                    .extend_from_slice(&[center_index, index, index + 1]);
+            }
+        }
+    }
+
+    /// Same as [`TessellatingRenderBundle::add_shape`], but the shape lies flat on the map.
+    fn add_map_aligned_shape(
+        &mut self,
+        position: [f32; 3],
+        fill: Color,
+        scale: f32,
+        outline: Option<LinePaint>,
+        shape: &ClosedContour<Point2<f32>>,
+        offset: Vector2<f32>,
+    ) -> Result<(), TessellationError> {
+        let mut path_builder = BuilderWithAttributes::new(0);
+        build_contour_path(&mut path_builder, shape, scale)?;
+        let path = path_builder.build();
+
+        if let Some(outline) = outline {
+            let vertex_constructor = MapAlignedVertexConstructor {
+                color: outline.color.to_f32_array(),
+                position,
+                offset,
+            };
+
+            self.scratch
+                .stroke
+                .tessellate(
+                    &path,
+                    &StrokeOptions::DEFAULT.with_line_width(outline.width as f32 * 2.0),
+                    &mut BuffersBuilder::new(&mut self.poly_tessellation, vertex_constructor),
+                )
+                .map_err(tessellator_error)?;
+        }
+
+        if !fill.is_transparent() {
+            let vertex_constructor = MapAlignedVertexConstructor {
+                color: fill.to_f32_array(),
+                position,
+                offset,
+            };
+
+            self.scratch
+                .fill
+                .tessellate(
+                    &path,
+                    &FillOptions::DEFAULT,
+                    &mut BuffersBuilder::new(&mut self.poly_tessellation, vertex_constructor),
+                )
+                .map_err(tessellator_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a circle with the radius in map units as a polygon with an optional outline.
+    fn add_map_circle<N, P>(
+        &mut self,
+        position: &P,
+        fill: Color,
+        radius: f64,
+        outline: Option<LinePaint>,
+        min_resolution: f64,
+    ) -> Result<Range<usize>, TessellationError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+    {
+        let (x, y, z) = (
+            position.x().as_() as f64,
+            position.y().as_() as f64,
+            position.z().as_() as f64,
+        );
+        let contour = ClosedContour::new(
+            (0..MAP_CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let angle = std::f64::consts::TAU * i as f64 / MAP_CIRCLE_SEGMENTS as f64;
+                    Point3d::new(x + radius * angle.cos(), y + radius * angle.sin(), z)
+                })
+                .collect(),
+        );
+
+        let start_index = self.poly_tessellation.vertices.len();
+        let polygon = galileo_types::impls::Polygon::new(contour.clone(), vec![]);
+        self.add_polygon_lod(
+            &polygon,
+            PolygonPaint {
+                color: fill,
+                gradient: None,
+                zoom: None,
             },
-            offset,
-        )
+            min_resolution as f32,
+        )?;
+
+        if let Some(outline) = outline {
+            self.add_line_lod(&contour, outline, min_resolution)?;
+        }
+
+        Ok(start_index..self.poly_tessellation.vertices.len())
     }
 
     fn add_circle_sector<N, P>(
@@ -812,7 +1531,8 @@ impl TessellatingRenderBundle {
         position: &P,
         parameters: SectorParameters,
         offset: Vector2<f32>,
-    ) where
+    ) -> Result<(), TessellationError>
+    where
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
     {
@@ -832,6 +1552,7 @@ impl TessellatingRenderBundle {
             position: [position.x().as_(), position.y().as_(), position.z().as_()],
             normal: [offset.x, offset.y],
             color: fill.center_color.to_u8_array(),
+            rotation: [0.0, 0.0],
         };
 
         let is_full_circle = (dr - std::f32::consts::PI * 2.0).abs() < TOLERANCE;
@@ -855,6 +1576,7 @@ impl TessellatingRenderBundle {
                 position: [position.x().as_(), position.y().as_(), position.z().as_()],
                 normal: (point + offset).coords.into(),
                 color: fill.side_color.to_u8_array(),
+                rotation: [0.0, 0.0],
             });
         }
 
@@ -878,13 +1600,15 @@ impl TessellatingRenderBundle {
                 outline,
                 &ClosedContour::new(contour),
                 offset,
-            );
+            )?;
         }
 
         self.buffer_size += (self.screen_ref.vertices.len() - start_vertex_count)
             * std::mem::size_of::<ScreenRefVertex>();
         self.buffer_size +=
             (self.screen_ref.indices.len() - start_index_count) * std::mem::size_of::<u32>();
+
+        Ok(())
     }
 
     fn add_dot<P, N>(&mut self, point: &P, color: Color, offset: Vector2<f32>)
@@ -930,6 +1654,18 @@ impl TessellatingRenderBundle {
 
             projected_b.z.total_cmp(&projected_a.z)
         });
+
+        let depth = |instance: &ImageInstance| {
+            let point = Point3d::new(
+                instance.position[0] as f64,
+                instance.position[1] as f64,
+                0.0,
+            )
+            .to_homogeneous();
+            (transform * point).z
+        };
+        self.image_points
+            .sort_by(|a, b| depth(b).total_cmp(&depth(a)));
     }
 }
 
@@ -953,7 +1689,8 @@ fn get_circle_sector(radius: f32, start_angle: f32, end_angle: f32) -> Vec<Point
         ((dr / std::f32::consts::PI * 2.0) * circle_steps_count).ceil() as usize;
     let angle_step = (end_angle - start_angle) / segment_steps_count as f32;
 
-    for step in 0..segment_steps_count {
+    // The end point is included, so that adjacent sectors do not leave a gap between them.
+    for step in 0..=segment_steps_count {
         let angle = start_angle + angle_step * step as f32;
         let x = angle.cos() * radius;
         let y = angle.sin() * radius;
@@ -976,45 +1713,160 @@ fn build_contour_path(
     path_builder: &mut impl PathBuilder,
     contour: &impl Contour<Point = Point2<f32>>,
     scale: f32,
-) -> Option<()> {
+) -> Result<(), TessellationError> {
+    let scaled = |p: &Point2<f32>| {
+        let scaled = point(p.x() * scale, p.y() * scale);
+        if scaled.x.is_finite() && scaled.y.is_finite() {
+            Ok(scaled)
+        } else {
+            Err(TessellationError::NonFiniteCoordinates)
+        }
+    };
+
     let mut iterator = contour.iter_points();
+    let Some(first_point) = iterator.next() else {
+        return Err(TessellationError::EmptyGeometry);
+    };
 
-    if let Some(first_point) = iterator.next() {
-        let _ = path_builder.begin(point(first_point.x() * scale, first_point.y() * scale), &[]);
+    let _ = path_builder.begin(scaled(first_point)?, &[]);
+    for p in iterator {
+        let _ = path_builder.line_to(scaled(p)?, &[]);
+    }
+
+    path_builder.end(contour.is_closed());
+
+    Ok(())
+}
+
+/// Returns the coordinates of the point converted to `f32`, or an error if any of them is NaN or infinite.
+fn check_position<N, P>(position: &P) -> Result<[f32; 3], TessellationError>
+where
+    N: AsPrimitive<f32>,
+    P: CartesianPoint3d<Num = N>,
+{
+    let coordinates = [position.x().as_(), position.y().as_(), position.z().as_()];
+    if coordinates.iter().all(|c| c.is_finite()) {
+        Ok(coordinates)
     } else {
-        return None;
+        Err(TessellationError::NonFiniteCoordinates)
     }
+}
 
-    for p in iterator {
-        let _ = path_builder.line_to(point(p.x() * scale, p.y() * scale), &[]);
+/// Checks that a size parameter of a paint is finite and not negative.
+fn check_size(name: &str, value: impl Into<f64>) -> Result<(), TessellationError> {
+    let value = value.into();
+    if value.is_finite() && value >= 0.0 {
+        Ok(())
+    } else {
+        Err(TessellationError::InvalidPaint(format!(
+            "{name} must be a finite non-negative number, but is {value}"
+        )))
     }
+}
 
-    path_builder.end(contour.is_closed());
+fn check_point_paint(paint: &PointPaint) -> Result<(), TessellationError> {
+    if !paint.offset.x.is_finite() || !paint.offset.y.is_finite() {
+        return Err(TessellationError::InvalidPaint(
+            "point offset must be finite".into(),
+        ));
+    }
+
+    if !paint.rotation.is_finite() {
+        return Err(TessellationError::InvalidPaint(
+            "point rotation must be finite".into(),
+        ));
+    }
+
+    let outline = match &paint.shape {
+        PointShape::Dot { .. } => None,
+        PointShape::Image { width, height, .. } => {
+            check_size("image width", *width)?;
+            check_size("image height", *height)?;
+            None
+        }
+        PointShape::Circle {
+            radius, outline, ..
+        } => {
+            check_size("circle radius", *radius)?;
+            outline.as_ref()
+        }
+        PointShape::MapCircle {
+            radius, outline, ..
+        } => {
+            check_size("circle radius", *radius)?;
+            outline.as_ref()
+        }
+        PointShape::Sector(SectorParameters {
+            radius,
+            start_angle,
+            end_angle,
+            outline,
+            ..
+        }) => {
+            check_size("sector radius", *radius)?;
+            if !start_angle.is_finite() || !end_angle.is_finite() {
+                return Err(TessellationError::InvalidPaint(
+                    "sector angles must be finite".into(),
+                ));
+            }
+            outline.as_ref()
+        }
+        PointShape::Square { size, outline, .. } => {
+            check_size("square size", *size)?;
+            outline.as_ref()
+        }
+        PointShape::FreeShape { scale, outline, .. } => {
+            check_size("shape scale", *scale)?;
+            outline.as_ref()
+        }
+    };
 
-    Some(())
+    match outline {
+        Some(outline) => check_size("outline width", outline.width),
+        None => Ok(()),
+    }
+}
+
+fn tessellator_error(err: lyon::tessellation::TessellationError) -> TessellationError {
+    TessellationError::Tessellator(format!("{err:?}"))
 }
 
 #[allow(dead_code)]
 struct LineVertexConstructor<'a> {
     width: f32,
     offset: f32,
-    color: [f32; 4],
+    /// If set, the vertices are moved to the edges of the line in map coordinates instead of being offset in the
+    /// shader.
+    map_units: bool,
+    color: Color,
+    gradient: Option<ColorGradient>,
+    zoom: Option<ZoomInterpolation>,
+    /// Length of the path.
+    length: f32,
     resolution: f32,
+    pattern: [f32; 4],
     path: &'a Path,
 }
 
 impl<'a> StrokeVertexConstructor<PolyVertex> for LineVertexConstructor<'a> {
     fn new_vertex(&mut self, mut vertex: StrokeVertex) -> PolyVertex {
         let position = vertex.position_on_path();
-        let offset = match vertex.side() {
-            Side::Negative => -self.offset,
-            Side::Positive => self.offset,
+        let (offset, side) = match vertex.side() {
+            Side::Negative => (-self.offset, -1.0),
+            Side::Positive => (self.offset, 1.0),
         };
 
-        let normal = [
+        let mut normal = [
             vertex.normal().x * (vertex.line_width() / 2.0 + offset),
             vertex.normal().y * (vertex.line_width() / 2.0 + offset),
         ];
+        let position = if self.map_units {
+            let position = position + lyon::math::vector(normal[0], normal[1]);
+            normal = [0.0, 0.0];
+            position
+        } else {
+            position
+        };
 
         let norm_limit = if let VertexSource::Endpoint { id } = vertex.source() {
             let mut prev_id = id.0.saturating_sub(1);
@@ -1036,34 +1888,114 @@ impl<'a> StrokeVertexConstructor<PolyVertex> for LineVertexConstructor<'a> {
             f32::MAX
         };
 
-        PolyVertex {
+        let mut poly_vertex = PolyVertex {
             position: [
                 position.x * self.resolution,
                 position.y * self.resolution,
                 vertex.interpolated_attributes()[0],
             ],
-            color: self.color,
+            color: Default::default(),
             normal,
             norm_limit,
-        }
+            line_position: [vertex.advancement() * self.resolution, side],
+            pattern: self.pattern,
+            zoom_color: Default::default(),
+            zoom: ZoomInterpolation::NONE,
+        };
+        poly_vertex.set_zoom(
+            line_color(self.color, self.gradient, vertex.advancement(), self.length),
+            self.zoom,
+        );
+
+        poly_vertex
     }
 }
 
 struct PolygonVertexConstructor {
-    color: [f32; 4],
+    color: Color,
+    gradient: Option<PolygonGradient>,
+    zoom: Option<ZoomInterpolation>,
 }
 
 impl FillVertexConstructor<PolyVertex> for PolygonVertexConstructor {
     fn new_vertex(&mut self, vertex: FillVertex) -> PolyVertex {
-        PolyVertex {
-            position: [vertex.position().x, vertex.position().y, 0.0],
-            color: self.color,
+        let position = [vertex.position().x, vertex.position().y, 0.0];
+        let mut vertex = PolyVertex {
+            position,
+            color: Default::default(),
             normal: Default::default(),
             norm_limit: 1.0,
+            line_position: Default::default(),
+            pattern: Default::default(),
+            zoom_color: Default::default(),
+            zoom: ZoomInterpolation::NONE,
+        };
+        vertex.set_zoom(
+            polygon_color(self.color, self.gradient, position),
+            self.zoom,
+        );
+
+        vertex
+    }
+}
+
+/// Color of a line vertex at the given distance from the start of the line.
+fn line_color(
+    color: Color,
+    gradient: Option<ColorGradient>,
+    distance: f32,
+    length: f32,
+) -> [f32; 4] {
+    match gradient {
+        Some(gradient) if length > 0.0 => gradient.color_at(distance / length).to_f32_array(),
+        _ => color.to_f32_array(),
+    }
+}
+
+/// Color of a polygon vertex at the given position in map coordinates.
+fn polygon_color(color: Color, gradient: Option<PolygonGradient>, position: [f32; 3]) -> [f32; 4] {
+    match gradient {
+        Some(gradient) => gradient
+            .color_at(&Point2d::new(position[0] as f64, position[1] as f64))
+            .to_f32_array(),
+        None => color.to_f32_array(),
+    }
+}
+
+/// Creates map-referenced vertices at the position of a point symbol lying flat on the map.
+struct MapAlignedVertexConstructor {
+    color: [f32; 4],
+    position: [f32; 3],
+    offset: Vector2<f32>,
+}
+
+impl MapAlignedVertexConstructor {
+    fn create_vertex(&self, position: lyon::math::Point) -> PolyVertex {
+        PolyVertex {
+            position: self.position,
+            color: self.color,
+            normal: [position.x + self.offset.x, position.y + self.offset.y],
+            norm_limit: f32::MAX,
+            line_position: Default::default(),
+            pattern: Default::default(),
+            zoom_color: self.color,
+            zoom: ZoomInterpolation::NONE,
         }
     }
 }
 
+impl StrokeVertexConstructor<PolyVertex> for MapAlignedVertexConstructor {
+    fn new_vertex(&mut self, vertex: StrokeVertex) -> PolyVertex {
+        self.create_vertex(vertex.position())
+    }
+}
+
+impl FillVertexConstructor<PolyVertex> for MapAlignedVertexConstructor {
+    fn new_vertex(&mut self, vertex: FillVertex) -> PolyVertex {
+        self.create_vertex(vertex.position())
+    }
+}
+
 struct ScreenRefVertexConstructor {
     color: [u8; 4],
     position: [f32; 3],
@@ -1076,6 +2008,7 @@ impl ScreenRefVertexConstructor {
             position: self.position,
             normal: [position.x + self.offset.x, position.y + self.offset.y],
             color: self.color,
+            rotation: [0.0, 0.0],
         }
     }
 }
@@ -1099,6 +2032,43 @@ pub(crate) struct PolyVertex {
     pub color: [f32; 4],
     pub normal: [f32; 2],
     pub norm_limit: f32,
+    /// Distance from the start of the line in map units, and the side of the line (`-1.0` or `1.0`) of the vertex.
+    /// Zero for polygons.
+    pub line_position: [f32; 2],
+    /// Line pattern parameters, see [`LinePattern::to_vertex_params`].
+    pub pattern: [f32; 4],
+    /// Color at the second resolution of the zoom interpolation. Same as `color` if there is no interpolation.
+    pub zoom_color: [f32; 4],
+    /// Zoom interpolation parameters, see [`ZoomInterpolation::vertex_params`].
+    pub zoom: [f32; 4],
+}
+
+impl PolyVertex {
+    /// Sets the colors and the zoom interpolation parameters of the vertex. If the interpolation is set, its colors
+    /// are used instead of the given `color`.
+    fn set_zoom(&mut self, color: [f32; 4], zoom: Option<ZoomInterpolation>) {
+        match zoom {
+            Some(zoom) => {
+                self.color = zoom.from_color.to_f32_array();
+                self.zoom_color = zoom.to_color.to_f32_array();
+                self.zoom = zoom.vertex_params();
+            }
+            None => {
+                self.color = color;
+                self.zoom_color = color;
+                self.zoom = ZoomInterpolation::NONE;
+            }
+        }
+    }
+
+    /// Same as [`PolyVertex::set_zoom`], but keeps the width scales of the vertex, as the primitive is not
+    /// tessellated again.
+    fn set_zoom_colors(&mut self, color: [f32; 4], zoom: Option<ZoomInterpolation>) {
+        let scales = [self.zoom[2], self.zoom[3]];
+        self.set_zoom(color, zoom);
+        self.zoom[2] = scales[0];
+        self.zoom[3] = scales[1];
+    }
 }
 
 #[repr(C)]
@@ -1108,6 +2078,20 @@ pub(crate) struct PointInstance {
     pub color: [u8; 4],
 }
 
+/// Instance of a circle point symbol. Circles are drawn as screen-aligned quads, with the shape of the circle
+/// calculated in the fragment shader, so only one instance is stored per circle independent of its size.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct CircleInstance {
+    pub position: [f32; 3],
+    pub offset: [f32; 2],
+    pub radius: f32,
+    pub outline_width: f32,
+    pub center_color: [u8; 4],
+    pub side_color: [u8; 4],
+    pub outline_color: [u8; 4],
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct ImageVertex {
@@ -1115,6 +2099,140 @@ pub(crate) struct ImageVertex {
     pub opacity: f32,
     pub tex_coords: [f32; 2],
     pub offset: [f32; 2],
+    /// `1.0` if the image lies flat on the map plane, `0.0` if it faces the viewer.
+    pub map_aligned: f32,
+    /// Rotation of the offset, see [`PointPaint::rotation_vertex_params`].
+    pub rotation: [f32; 2],
+}
+
+/// Instance of an image point symbol. All instances using the same texture are drawn with one draw call, with the
+/// corners of the image quad calculated in the vertex shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct ImageInstance {
+    pub position: [f32; 2],
+    /// Offset of the top left corner of the image from the point in pixels, with `y` going up.
+    pub offset: [f32; 2],
+    pub size: [f32; 2],
+    /// Rotation of the offset, see [`PointPaint::rotation_vertex_params`].
+    pub rotation: [f32; 2],
+    pub opacity: f32,
+    /// `1.0` if the image lies flat on the map plane, `0.0` if it faces the viewer.
+    pub map_aligned: f32,
+    /// Index of the image in the image store of the bundle.
+    pub texture_index: u32,
+}
+
+impl ImageInstance {
+    /// Corners of the image quad, in the same order as the vertices of the map-referenced images.
+    pub fn vertices(&self) -> [ImageVertex; 4] {
+        [[0.0, 1.0], [0.0, 0.0], [1.0, 1.0], [1.0, 0.0]].map(|tex_coords: [f32; 2]| ImageVertex {
+            position: self.position,
+            opacity: self.opacity,
+            tex_coords,
+            offset: [
+                self.offset[0] + tex_coords[0] * self.size[0],
+                self.offset[1] - tex_coords[1] * self.size[1],
+            ],
+            map_aligned: self.map_aligned,
+            rotation: self.rotation,
+        })
+    }
+}
+
+/// Area of the map covered by a render bundle. Used to skip drawing of the bundles that are outside of the view.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct BundleBounds {
+    /// Bounding rectangle of the anchor points of all the primitives in the bundle, in map units.
+    pub rect: Rect,
+    /// Largest distance in pixels from the anchor point of a primitive to its screen-referenced parts (line widths,
+    /// point symbols). Zero if the bundle has no such parts.
+    pub screen_margin: f64,
+    /// Smallest `z` coordinate of the primitives.
+    pub z_min: f64,
+    /// Largest `z` coordinate of the primitives.
+    pub z_max: f64,
+}
+
+impl BundleBounds {
+    /// Magnification of the screen margin for views tilted with `rotation_x`, as resolution is not uniform over the
+    /// screen in that case.
+    const TILTED_MARGIN_FACTOR: f64 = 4.0;
+
+    /// Returns true if any part of the bundle can be visible with the given view.
+    ///
+    /// The bounding box of the bundle is checked against the [frustum](MapView::frustum) of the view, so bundles
+    /// outside of the rotated or tilted screen are skipped, while the ones in its corners are kept.
+    pub fn is_visible(&self, view: &MapView) -> bool {
+        let Some(frustum) = view.frustum() else {
+            return true;
+        };
+
+        let mut margin = self.screen_margin * view.resolution();
+        if view.rotation_x() != 0.0 {
+            margin *= Self::TILTED_MARGIN_FACTOR;
+        }
+
+        frustum.intersects_box(
+            &Point3d::new(
+                self.rect.x_min() - margin,
+                self.rect.y_min() - margin,
+                self.z_min - margin,
+            ),
+            &Point3d::new(
+                self.rect.x_max() + margin,
+                self.rect.y_max() + margin,
+                self.z_max + margin,
+            ),
+        )
+    }
+}
+
+#[derive(Default)]
+struct BundleBoundsBuilder {
+    rect: Option<Rect>,
+    screen_margin: f32,
+    z_range: Option<(f32, f32)>,
+}
+
+impl BundleBoundsBuilder {
+    /// Size of a dot point in pixels. Dots are drawn as single pixels.
+    const DOT_SIZE: f32 = 1.0;
+
+    fn add(&mut self, x: f32, y: f32, z: f32) {
+        let (x, y) = (x as f64, y as f64);
+        let point_rect = Rect::new(x, y, x, y);
+        self.rect = Some(match self.rect {
+            Some(rect) => rect.merge(point_rect),
+            None => point_rect,
+        });
+
+        self.z_range = Some(match self.z_range {
+            Some((z_min, z_max)) => (z_min.min(z), z_max.max(z)),
+            None => (z, z),
+        });
+    }
+
+    /// Adds a screen-referenced vertex at the given offset in pixels from its anchor point. The length of the offset
+    /// is used, so that the margin covers the vertex rotated by any angle.
+    fn add_margin(&mut self, offset: [f32; 2]) {
+        self.add_extent(offset[0].hypot(offset[1]));
+    }
+
+    fn add_extent(&mut self, extent: f32) {
+        if extent.is_finite() {
+            self.screen_margin = self.screen_margin.max(extent);
+        }
+    }
+
+    fn build(self) -> Option<BundleBounds> {
+        Some(BundleBounds {
+            rect: self.rect?,
+            screen_margin: self.screen_margin as f64,
+            z_min: self.z_range.map_or(0.0, |(z_min, _)| z_min as f64),
+            z_max: self.z_range.map_or(0.0, |(_, z_max)| z_max as f64),
+        })
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -1125,6 +2243,18 @@ mod tests {
     use super::*;
 
     type C = galileo_types::impls::Contour<Point3d>;
+    type Poly = galileo_types::impls::Polygon<Point3d>;
+
+    fn add_point(
+        bundle: &mut TessellatingRenderBundle,
+        point: &Point3d,
+        paint: PointPaint,
+    ) -> PrimitiveId {
+        bundle.add(
+            RenderPrimitive::<_, _, C, Poly>::new_point_ref(point, paint),
+            1.0,
+        )
+    }
 
     #[test]
     fn remove_map_ref() {
@@ -1137,8 +2267,14 @@ mod tests {
         ]);
         let paint1 = PolygonPaint {
             color: Color::BLACK,
+            gradient: None,
+            zoom: None,
+        };
+        let paint2 = PolygonPaint {
+            color: Color::RED,
+            gradient: None,
+            zoom: None,
         };
-        let paint2 = PolygonPaint { color: Color::RED };
 
         let _id0 = bundle.add(
             RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint1),
@@ -1175,4 +2311,498 @@ mod tests {
 
         assert_eq!(vertex_range.end, vertex_count);
     }
+
+    #[test]
+    fn highlight_restores_colors() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let polygon = galileo_types::impls::Polygon::from(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+        ]);
+        let paint = PolygonPaint {
+            color: Color::BLUE,
+            gradient: None,
+            zoom: None,
+        };
+        let polygon_id = bundle.add(
+            RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint),
+            1.0,
+        );
+        let point = Point3d::new(0.0, 0.0, 0.0);
+        let circle_id = add_point(&mut bundle, &point, PointPaint::circle(Color::BLUE, 4.0));
+
+        let style = HighlightStyle {
+            brightness: 1.0,
+            tint: Color::RED,
+            min_opacity: 0.0,
+        };
+        bundle.set_highlight(polygon_id, Some(style)).unwrap();
+        bundle.set_highlight(circle_id, Some(style)).unwrap();
+        assert!(bundle
+            .poly_tessellation
+            .vertices
+            .iter()
+            .all(|v| v.color == Color::RED.to_f32_array()));
+        assert_eq!(bundle.circles[0].center_color, Color::RED.to_u8_array());
+
+        // Style update of a highlighted primitive keeps it highlighted.
+        let paint = PolygonPaint {
+            color: Color::GREEN,
+            gradient: None,
+            zoom: None,
+        };
+        bundle
+            .update(
+                polygon_id,
+                RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint),
+            )
+            .unwrap();
+        assert!(bundle
+            .poly_tessellation
+            .vertices
+            .iter()
+            .all(|v| v.color == Color::RED.to_f32_array()));
+
+        bundle.set_highlight(polygon_id, None).unwrap();
+        bundle.set_highlight(circle_id, None).unwrap();
+        assert!(bundle
+            .poly_tessellation
+            .vertices
+            .iter()
+            .all(|v| v.color == Color::GREEN.to_f32_array()));
+        assert_eq!(bundle.circles[0].center_color, Color::BLUE.to_u8_array());
+    }
+
+    #[test]
+    fn line_width_in_map_units() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let line = C::new(
+            vec![Point3d::new(0.0, 0.0, 0.0), Point3d::new(100.0, 0.0, 0.0)],
+            false,
+        );
+        let paint = LinePaint {
+            color: Color::BLACK,
+            width: 10.0,
+            offset: 0.0,
+            width_unit: SizeUnit::MapUnits,
+            line_cap: crate::render::LineCap::Butt,
+            pattern: None,
+            gradient: None,
+            zoom: None,
+        };
+        bundle.add(
+            RenderPrimitive::<_, _, C, galileo_types::impls::Polygon<_>>::new_contour_ref(
+                &line, paint,
+            ),
+            2.0,
+        );
+
+        let vertices = &bundle.poly_tessellation.vertices;
+        assert!(!vertices.is_empty());
+        for vertex in vertices {
+            assert_eq!(vertex.normal, [0.0, 0.0]);
+            assert!((vertex.position[1].abs() - 5.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn map_aligned_circle_is_map_referenced() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let point = Point3d::new(1.0, 2.0, 0.0);
+        let paint = PointPaint::circle(Color::RED, 10.0).with_alignment(PointAlignment::Map);
+        let id = add_point(&mut bundle, &point, paint);
+
+        assert!(bundle.circles.is_empty());
+        assert!(matches!(
+            bundle.primitives[id.0],
+            PrimitiveInfo::MapRef { .. }
+        ));
+
+        let vertices = &bundle.poly_tessellation.vertices;
+        assert!(vertices.iter().all(|v| v.position == [1.0, 2.0, 0.0]));
+        assert_eq!(vertices[0].normal, [0.0, 0.0]);
+        for vertex in &vertices[1..] {
+            let length = (vertex.normal[0].powi(2) + vertex.normal[1].powi(2)).sqrt();
+            assert!((length - 5.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn rotated_point_symbols() {
+        use crate::render::point_paint::RotationAlignment;
+
+        let point = Point3d::new(0.0, 0.0, 0.0);
+        let rotation = std::f32::consts::FRAC_PI_2;
+
+        // Screen symbols store the rotation in the vertices to apply it in the shader.
+        let mut bundle = TessellatingRenderBundle::new();
+        let paint = PointPaint::square(Color::RED, 10.0)
+            .with_rotation(rotation)
+            .with_rotation_alignment(RotationAlignment::Map);
+        add_point(&mut bundle, &point, paint);
+        assert!(!bundle.screen_ref.vertices.is_empty());
+        assert!(bundle
+            .screen_ref
+            .vertices
+            .iter()
+            .all(|v| v.rotation == [rotation, 1.0]));
+
+        // Flat symbols are rotated on the map when tessellated.
+        let mut bundle = TessellatingRenderBundle::new();
+        let arrow = ClosedContour::new(vec![
+            Point2::new(0.0, 10.0),
+            Point2::new(-1.0, 0.0),
+            Point2::new(1.0, 0.0),
+        ]);
+        let paint = PointPaint::shape(Color::RED, &arrow, 1.0)
+            .with_alignment(PointAlignment::Map)
+            .with_rotation(rotation);
+        add_point(&mut bundle, &point, paint);
+        let tip = bundle
+            .poly_tessellation
+            .vertices
+            .iter()
+            .max_by(|a, b| a.normal[0].total_cmp(&b.normal[0]))
+            .unwrap();
+        assert!((tip.normal[0] - 10.0).abs() < 0.001);
+        assert!(tip.normal[1].abs() < 0.001);
+    }
+
+    #[test]
+    fn map_circle_is_tessellated_in_map_units() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let point = Point3d::new(10.0, 20.0, 0.0);
+        let id = add_point(&mut bundle, &point, PointPaint::map_circle(Color::RED, 8.0));
+
+        assert!(bundle.circles.is_empty());
+        assert!(matches!(
+            bundle.primitives[id.0],
+            PrimitiveInfo::MapRef { .. }
+        ));
+        for vertex in &bundle.poly_tessellation.vertices {
+            let distance =
+                ((vertex.position[0] - 10.0).powi(2) + (vertex.position[1] - 20.0).powi(2)).sqrt();
+            assert!((distance - 4.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn bundle_bounds_visibility() {
+        let mut bundle = TessellatingRenderBundle::new();
+        assert!(bundle.bounds().is_none());
+
+        let polygon = galileo_types::impls::Polygon::from(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(10.0, 0.0, 0.0),
+            Point3d::new(10.0, 10.0, 0.0),
+            Point3d::new(0.0, 10.0, 0.0),
+        ]);
+        bundle.add(
+            RenderPrimitive::<_, _, C, _>::new_polygon_ref(
+                &polygon,
+                PolygonPaint {
+                    color: Color::BLACK,
+                    gradient: None,
+                    zoom: None,
+                },
+            ),
+            1.0,
+        );
+
+        let bounds = bundle.bounds().unwrap();
+        assert_eq!(bounds.rect, Rect::new(0.0, 0.0, 10.0, 10.0));
+        assert_eq!((bounds.z_min, bounds.z_max), (0.0, 0.0));
+
+        let size = galileo_types::cartesian::Size::new(100.0, 100.0);
+        let view = MapView::new_projected(&Point2d::new(5.0, 5.0), 1.0).with_size(size);
+        assert!(bounds.is_visible(&view));
+
+        let view = MapView::new_projected(&Point2d::new(1000.0, 1000.0), 1.0).with_size(size);
+        assert!(!bounds.is_visible(&view));
+
+        // The bundle is inside the bounding rectangle of the rotated view, but outside of the screen.
+        let view = MapView::new_projected(&Point2d::new(-60.0, -60.0), 1.0)
+            .with_size(size)
+            .with_rotation_z(std::f64::consts::FRAC_PI_4);
+        assert!(bounds.rect.intersects(view.get_bbox().unwrap()));
+        assert!(!bounds.is_visible(&view));
+    }
+
+    #[test]
+    fn bundle_bounds_margin_fits_symbols() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let point = Point3d::new(0.0, 0.0, 0.0);
+
+        add_point(&mut bundle, &point, PointPaint::dot(Color::RED));
+        assert_eq!(bundle.bounds().unwrap().screen_margin, 1.0);
+
+        add_point(&mut bundle, &point, PointPaint::circle(Color::RED, 40.0));
+        let bounds = bundle.bounds().unwrap();
+        assert_eq!(bounds.screen_margin, 20.0);
+
+        // The left edge of the view is 15 pixels from the center of the circle.
+        let size = galileo_types::cartesian::Size::new(100.0, 100.0);
+        let view = MapView::new_projected(&Point2d::new(65.0, 0.0), 1.0).with_size(size);
+        assert!(bounds.is_visible(&view));
+
+        let view = MapView::new_projected(&Point2d::new(75.0, 0.0), 1.0).with_size(size);
+        assert!(!bounds.is_visible(&view));
+
+        // Rotated symbols can reach as far as the length of the offset.
+        let mut builder = BundleBoundsBuilder::default();
+        builder.add(0.0, 0.0, 0.0);
+        builder.add_margin([3.0, -4.0]);
+        assert_eq!(builder.build().unwrap().screen_margin, 5.0);
+    }
+
+    #[test]
+    fn circles_are_instanced() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let point = Point3d::new(1.0, 2.0, 0.0);
+
+        let id1 = add_point(&mut bundle, &point, PointPaint::circle(Color::RED, 10.0));
+        let id2 = add_point(
+            &mut bundle,
+            &point,
+            PointPaint::circle(Color::BLUE, 20.0).with_outline(Color::BLACK, 2.0),
+        );
+
+        assert_eq!(bundle.circles.len(), 2);
+        assert!(bundle.screen_ref.vertices.is_empty());
+        assert_eq!(bundle.circles[1].radius, 10.0);
+        assert_eq!(bundle.circles[1].outline_width, 2.0);
+
+        bundle.remove(id1).unwrap();
+        assert_eq!(bundle.circles.len(), 1);
+        assert_eq!(bundle.circles[0].center_color, Color::BLUE.to_u8_array());
+        assert!(matches!(
+            bundle.primitives[id2.0],
+            PrimitiveInfo::Circle { circle_index: 0 }
+        ));
+    }
+
+    #[test]
+    fn image_points_are_instanced() {
+        let image = Arc::new(DecodedImage::from_rgba(vec![255; 2 * 4 * 4], 2, 4).unwrap());
+        let other = Arc::new(DecodedImage::from_rgba(vec![255; 4], 1, 1).unwrap());
+        let mut bundle = TessellatingRenderBundle::new();
+        let point = Point3d::new(1.0, 2.0, 0.0);
+
+        let paint = PointPaint::image(image.clone(), Vector2::new(0.5, 1.0), 2.0);
+        let id1 = add_point(&mut bundle, &point, paint.clone());
+        let id2 = add_point(&mut bundle, &point, paint);
+        let id3 = add_point(
+            &mut bundle,
+            &point,
+            PointPaint::image(other, Vector2::new(0.0, 0.0), 1.0),
+        );
+
+        assert!(bundle.images.is_empty());
+        assert_eq!(bundle.image_points.len(), 3);
+        assert_eq!(bundle.image_store.len(), 2);
+        assert_eq!(bundle.image_points[0].position, [1.0, 2.0]);
+        assert_eq!(bundle.image_points[0].size, [4.0, 8.0]);
+        assert_eq!(bundle.image_points[0].offset, [-2.0, 8.0]);
+        assert_eq!(bundle.image_points[1].texture_index, 0);
+        assert_eq!(bundle.image_points[2].texture_index, 1);
+
+        let corners = bundle.image_points[0].vertices().map(|v| v.offset);
+        assert_eq!(corners, [[-2.0, 0.0], [-2.0, 8.0], [2.0, 0.0], [2.0, 8.0]]);
+
+        bundle.remove(id1).unwrap();
+        assert_eq!(bundle.image_points.len(), 2);
+        assert_eq!(bundle.image_store.len(), 2);
+        assert!(matches!(
+            bundle.primitives[id2.0],
+            PrimitiveInfo::ImagePoint { instance_index: 0 }
+        ));
+        assert!(matches!(
+            bundle.primitives[id3.0],
+            PrimitiveInfo::ImagePoint { instance_index: 1 }
+        ));
+    }
+
+    #[test]
+    fn invalid_primitive_is_skipped() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let square = ClosedContour::new(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+            Point3d::new(0.0, 1.0, 0.0),
+        ]);
+        let paint = PolygonPaint {
+            color: Color::BLACK,
+            gradient: None,
+            zoom: None,
+        };
+        bundle.add(
+            RenderPrimitive::<_, _, C, _>::new_polygon(Poly::new(square.clone(), vec![]), paint),
+            1.0,
+        );
+        let vertex_count = bundle.poly_tessellation.vertices.len();
+        let buffer_size = bundle.approx_buffer_size();
+
+        let with_nan_hole = Poly::new(
+            square,
+            vec![ClosedContour::new(vec![
+                Point3d::new(0.2, 0.2, 0.0),
+                Point3d::new(f64::NAN, 0.5, 0.0),
+                Point3d::new(0.5, 0.2, 0.0),
+            ])],
+        );
+        let result = bundle.try_add(
+            RenderPrimitive::<_, _, C, _>::new_polygon_ref(&with_nan_hole, paint),
+            1.0,
+        );
+        assert_eq!(result, Err(TessellationError::NonFiniteCoordinates));
+        assert_eq!(bundle.poly_tessellation.vertices.len(), vertex_count);
+        assert_eq!(bundle.approx_buffer_size(), buffer_size);
+
+        let point = Point3d::new(0.0, 0.0, 0.0);
+        let id = add_point(
+            &mut bundle,
+            &point,
+            PointPaint::circle(Color::RED, 4.0).with_outline(Color::BLACK, -1.0),
+        );
+        assert!(bundle.circles.is_empty());
+        assert!(bundle.remove(id).is_ok());
+    }
+
+    /// Checks that all indices of the bundle point to existing vertices.
+    fn assert_consistent(bundle: &TessellatingRenderBundle) {
+        let poly_vertices = bundle.poly_tessellation.vertices.len() as u32;
+        assert!(bundle
+            .poly_tessellation
+            .indices
+            .iter()
+            .all(|i| *i < poly_vertices));
+        let screen_ref_vertices = bundle.screen_ref.vertices.len() as u32;
+        assert!(bundle
+            .screen_ref
+            .indices
+            .iter()
+            .all(|i| *i < screen_ref_vertices));
+    }
+
+    mod random_input {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn coordinate() -> impl Strategy<Value = f64> {
+            prop_oneof![
+                8 => -1e6..1e6f64,
+                1 => Just(0.0),
+                1 => Just(f64::NAN),
+                1 => Just(f64::INFINITY),
+                1 => Just(f64::MAX),
+            ]
+        }
+
+        fn points(max_count: usize) -> impl Strategy<Value = Vec<Point3d>> {
+            proptest::collection::vec(
+                (coordinate(), coordinate(), coordinate())
+                    .prop_map(|(x, y, z)| Point3d::new(x, y, z)),
+                0..max_count,
+            )
+        }
+
+        fn resolution() -> impl Strategy<Value = f64> {
+            prop_oneof![1e-6..1e3f64, Just(0.0), Just(-1.0), Just(f64::NAN)]
+        }
+
+        fn size() -> impl Strategy<Value = f64> {
+            prop_oneof![
+                0.0..50.0f64,
+                Just(-1.0),
+                Just(f64::NAN),
+                Just(f64::INFINITY)
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn random_polygons(
+                outer in points(30),
+                holes in proptest::collection::vec(points(10), 0..3),
+                resolution in resolution(),
+            ) {
+                let mut bundle = TessellatingRenderBundle::new();
+                let polygon = Poly::new(
+                    ClosedContour::new(outer),
+                    holes.into_iter().map(ClosedContour::new).collect(),
+                );
+                let paint = PolygonPaint { color: Color::BLACK, gradient: None, zoom: None };
+                let result = bundle.try_add(
+                    RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint),
+                    resolution,
+                );
+
+                prop_assert_ne!(result, Err(TessellationError::Panicked));
+                assert_consistent(&bundle);
+            }
+
+            #[test]
+            fn random_lines(
+                points in points(30),
+                is_closed: bool,
+                width in size(),
+                offset in size(),
+                map_units: bool,
+                resolution in resolution(),
+            ) {
+                let mut bundle = TessellatingRenderBundle::new();
+                let line = C::new(points, is_closed);
+                let paint = LinePaint {
+                    color: Color::BLACK,
+                    width,
+                    offset,
+                    width_unit: if map_units { SizeUnit::MapUnits } else { SizeUnit::Pixels },
+                    line_cap: crate::render::LineCap::Round,
+                    pattern: None,
+                    gradient: None,
+                    zoom: None,
+                };
+                let result = bundle.try_add(
+                    RenderPrimitive::<_, _, C, Poly>::new_contour_ref(&line, paint),
+                    resolution,
+                );
+
+                prop_assert_ne!(result, Err(TessellationError::Panicked));
+                assert_consistent(&bundle);
+            }
+
+            #[test]
+            fn random_shapes(
+                position in points(2),
+                shape in proptest::collection::vec((-1e3..1e3f32, -1e3..1e3f32), 0..20),
+                scale in size(),
+                outline in size(),
+                map_aligned: bool,
+            ) {
+                let mut bundle = TessellatingRenderBundle::new();
+                let position = position
+                    .first()
+                    .copied()
+                    .unwrap_or_else(|| Point3d::new(0.0, 0.0, 0.0));
+                let contour = ClosedContour::new(
+                    shape.into_iter().map(|(x, y)| Point2::new(x, y)).collect(),
+                );
+                let mut paint = PointPaint::owned_shape(Color::BLUE, contour, scale as f32)
+                    .with_outline(Color::BLACK, outline as f32);
+                if map_aligned {
+                    paint = paint.with_alignment(PointAlignment::Map);
+                }
+
+                let result = bundle.try_add(
+                    RenderPrimitive::<_, _, C, Poly>::new_point_ref(&position, paint),
+                    1.0,
+                );
+
+                prop_assert_ne!(result, Err(TessellationError::Panicked));
+                assert_consistent(&bundle);
+            }
+        }
+    }
 }
@@ -11,8 +11,10 @@ use std::sync::Arc;
 pub(crate) struct TessellatingRenderBundleBytes {
     pub poly_tessellation: PolyVertexBuffersBytes,
     pub points: Vec<u32>,
+    pub circles: Vec<u32>,
     pub screen_ref: ScreenRefVertexBuffersBytes,
     pub images: Vec<ImageBytes>,
+    pub image_points: Vec<u32>,
     pub primitives: Vec<PrimitiveInfo>,
     pub image_store: Vec<(u32, u32, Vec<u8>)>,
     pub clip_area: Option<PolyVertexBuffersBytes>,
@@ -85,6 +87,7 @@ impl TessellatingRenderBundle {
         let converted = TessellatingRenderBundleBytes {
             poly_tessellation: self.poly_tessellation.into(),
             points: bytemuck::cast_vec(self.points),
+            circles: bytemuck::cast_vec(self.circles),
             screen_ref: self.screen_ref.into(),
             images: self
                 .images
@@ -94,6 +97,7 @@ impl TessellatingRenderBundle {
                     vertices: bytemuck::cast_vec(vertices.to_vec()),
                 })
                 .collect(),
+            image_points: bytemuck::cast_vec(self.image_points),
             primitives: self.primitives,
             image_store: self
                 .image_store
@@ -111,6 +115,7 @@ impl TessellatingRenderBundle {
         Self {
             poly_tessellation: bundle.poly_tessellation.into_typed_unchecked(),
             points: bytemuck::cast_vec(bundle.points),
+            circles: bytemuck::cast_vec(bundle.circles),
             screen_ref: bundle.screen_ref.into_typed_unchecked(),
             images: bundle
                 .images
@@ -128,6 +133,7 @@ impl TessellatingRenderBundle {
                     },
                 )
                 .collect(),
+            image_points: bytemuck::cast_vec(bundle.image_points),
             primitives: bundle.primitives,
             image_store: bundle
                 .image_store
@@ -142,6 +148,8 @@ impl TessellatingRenderBundle {
             clip_area: bundle.clip_area.map(|v| v.into_typed_unchecked()),
             buffer_size: bundle.bundle_size,
             vacant_ids: vec![],
+            scratch: Default::default(),
+            highlighted: Default::default(),
         }
     }
 }
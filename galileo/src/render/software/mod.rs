@@ -0,0 +1,840 @@
+//! Rendering backend that draws the map on CPU, without a GPU device.
+
+use crate::control::Clock;
+use crate::decoded_image::DecodedImage;
+use crate::layer::Layer;
+use crate::map::Map;
+use crate::render::render_bundle::tessellating::{
+    BundleBounds, CircleInstance, ImageInstance, ImageVertex, PointInstance, PolyVertex,
+    ScreenRefTessellation, TessellatingRenderBundle,
+};
+use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::Size;
+use lyon::tessellation::VertexBuffers;
+use nalgebra::{Matrix4, Rotation3, Vector3, Vector4};
+use rasterizer::{linear_color, srgb_to_linear, Framebuffer};
+use std::any::Any;
+
+use super::{
+    view_scale_factor, zoom_interpolation, AnimationClock, Canvas, CustomShader, PackedBundle,
+    RenderOptions, Renderer,
+};
+
+mod rasterizer;
+
+const DEFAULT_BACKGROUND: Color = Color::WHITE;
+
+/// Index order of the image quad vertices, same as used by the `wgpu` image pipeline.
+const IMAGE_INDICES: [usize; 6] = [1, 0, 2, 1, 2, 3];
+
+/// Render backend that rasterizes the map on CPU.
+///
+/// It does not require a GPU or a window, so it can be used to render maps in headless environments: golden image
+/// tests on CI machines or static map images on servers. The output follows the `wgpu` pipelines of
+/// [`WgpuRenderer`](super::WgpuRenderer) closely, but is not guaranteed to be pixel-identical to it, so image
+/// comparisons should allow small differences on the edges of the primitives.
+///
+/// ```
+/// use galileo::galileo_types::cartesian::{Point2d, Size};
+/// use galileo::render::SoftwareRenderer;
+/// use galileo::{Color, DummyMessenger, Map, MapView};
+///
+/// let size = Size::new(64, 32);
+/// let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+///     .with_size(Size::new(64.0, 32.0));
+/// let map = Map::new(view, vec![], None::<DummyMessenger>);
+///
+/// let mut renderer = SoftwareRenderer::new(size);
+/// renderer.set_background(Color::BLACK);
+/// renderer.render(&map);
+///
+/// let image = renderer.get_image();
+/// assert_eq!(image.len(), 64 * 32 * 4);
+/// assert_eq!(&image[0..4], &[0, 0, 0, 255]);
+/// ```
+pub struct SoftwareRenderer {
+    framebuffer: Framebuffer,
+    size: Size<u32>,
+    background: Color,
+    clock: AnimationClock,
+}
+
+impl Renderer for SoftwareRenderer {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl SoftwareRenderer {
+    /// Creates a new renderer with the render target of the given size in pixels.
+    pub fn new(size: Size<u32>) -> Self {
+        let mut framebuffer = Framebuffer::new(size);
+        framebuffer.clear(DEFAULT_BACKGROUND);
+
+        Self {
+            framebuffer,
+            size,
+            background: DEFAULT_BACKGROUND,
+            clock: AnimationClock::default(),
+        }
+    }
+
+    /// Set the background color for the map.
+    pub fn set_background(&mut self, color: Color) {
+        self.background = color;
+    }
+
+    /// Sets the clock used to animate line patterns. Rendering with a manually controlled clock makes images of
+    /// animated maps reproducible.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = AnimationClock::new(clock);
+    }
+
+    /// Changes the size of the render target. The content of the target is cleared.
+    pub fn resize(&mut self, new_size: Size<u32>) {
+        self.size = new_size;
+        self.framebuffer = Framebuffer::new(new_size);
+        self.framebuffer.clear(self.background);
+    }
+
+    /// Returns the size of the rendering area.
+    pub fn size(&self) -> Size {
+        Size::new(self.size.width() as f64, self.size.height() as f64)
+    }
+
+    /// Renders the map.
+    pub fn render(&mut self, map: &Map) {
+        self.render_view(map, map.view());
+    }
+
+    /// Renders the layers of the map with the given view instead of the view of the map.
+    pub(crate) fn render_view(&mut self, map: &Map, view: &MapView) {
+        self.render_layers(map.layers().iter_visible(), view);
+    }
+
+    /// Renders the given layers with the view, as if they were the only visible layers of a map.
+    pub(crate) fn render_layers<'a>(
+        &mut self,
+        layers: impl IntoIterator<Item = &'a dyn Layer>,
+        view: &MapView,
+    ) {
+        self.framebuffer.clear(self.background);
+
+        let time = self.clock.time();
+        for layer in layers {
+            let Some(mut canvas) = SoftwareCanvas::new(&mut self.framebuffer, view.clone(), time)
+            else {
+                log::warn!("Layer cannot be rendered to the map view.");
+                return;
+            };
+
+            layer.render(view, &mut canvas);
+        }
+    }
+
+    /// Returns the image of the last render operation as RGBA8 bytes, row by row starting from the top.
+    pub fn get_image(&self) -> Vec<u8> {
+        self.framebuffer.to_rgba8()
+    }
+}
+
+fn create_bundle() -> RenderBundle {
+    RenderBundle(RenderBundleType::Tessellating(
+        TessellatingRenderBundle::new(),
+    ))
+}
+
+struct SoftwareCanvas<'a> {
+    framebuffer: &'a mut Framebuffer,
+    map_view: MapView,
+    transform: ViewTransform,
+}
+
+impl<'a> SoftwareCanvas<'a> {
+    fn new(framebuffer: &'a mut Framebuffer, map_view: MapView, time: f32) -> Option<Self> {
+        let transform = ViewTransform::new(
+            &map_view,
+            framebuffer.width() as f64,
+            framebuffer.height() as f64,
+            time,
+        )?;
+
+        Some(Self {
+            framebuffer,
+            map_view,
+            transform,
+        })
+    }
+
+    fn draw_bundle(&mut self, bundle: &SoftwarePackedBundle, antialias: bool) {
+        if let Some(clip_area) = &bundle.clip_area {
+            let triangles = triangles(&clip_area.indices)
+                .filter_map(|t| self.transform.map_ref_triangle(&clip_area.vertices, t))
+                .map(|(positions, _)| positions);
+            self.framebuffer.set_clip(triangles, antialias);
+        }
+
+        for (image_index, vertices) in &bundle.images {
+            if let Some(texture) = bundle.textures.get(*image_index) {
+                self.draw_image(texture, vertices, antialias);
+            }
+        }
+
+        for instance in &bundle.image_points {
+            if let Some(texture) = bundle.textures.get(instance.texture_index as usize) {
+                self.draw_image(texture, &instance.vertices(), antialias);
+            }
+        }
+
+        let map_ref = &bundle.map_ref;
+        for triangle in triangles(&map_ref.indices) {
+            let Some((positions, vertices)) =
+                self.transform.map_ref_triangle(&map_ref.vertices, triangle)
+            else {
+                continue;
+            };
+
+            let colors = vertices.map(|v| {
+                let t = self.transform.zoom_factor(v.zoom) as f32;
+                linear_color([0, 1, 2, 3].map(|i| v.color[i] + (v.zoom_color[i] - v.color[i]) * t))
+            });
+            let line_positions = vertices.map(|v| v.line_position);
+            let pattern = vertices[0].pattern;
+            let (resolution, time) = (self.transform.resolution as f32, self.transform.time);
+            self.framebuffer
+                .fill_triangle(positions, antialias, |bary| {
+                    let line_position =
+                        [0, 1].map(|c| (0..3).map(|i| line_positions[i][c] * bary[i] as f32).sum());
+                    (line_pattern_alpha(line_position, pattern, resolution, time) > 0.0)
+                        .then(|| interpolate(colors, bary))
+                });
+        }
+
+        let screen_ref = &bundle.screen_ref;
+        for triangle in triangles(&screen_ref.indices) {
+            let Some(vertices) = triangle
+                .iter()
+                .map(|&index| screen_ref.vertices.get(index))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+
+            let Some(positions) = vertices
+                .iter()
+                .map(|v| {
+                    let normal = self.transform.rotate_offset(v.normal, v.rotation);
+                    self.transform.screen_ref(v.position, normal)
+                })
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+
+            let colors =
+                [0, 1, 2].map(|i| linear_color(vertices[i].color.map(|c| c as f32 / 255.0)));
+            self.framebuffer.fill_triangle(
+                [positions[0], positions[1], positions[2]],
+                antialias,
+                |bary| Some(interpolate(colors, bary)),
+            );
+        }
+
+        for circle in &bundle.circles {
+            self.draw_circle(circle, antialias);
+        }
+
+        for point in &bundle.points {
+            if let Some([x, y]) = self.transform.screen_ref(point.position, [0.0, 0.0]) {
+                self.framebuffer.fill_pixel(
+                    x,
+                    y,
+                    linear_color(point.color.map(|c| c as f32 / 255.0)),
+                );
+            }
+        }
+
+        self.framebuffer.reset_clip();
+    }
+
+    fn draw_image(&mut self, texture: &Texture, vertices: &[ImageVertex; 4], antialias: bool) {
+        let Some(projected) = vertices
+            .iter()
+            .map(|v| {
+                let clip = self.transform.project([v.position[0], v.position[1], 0.0]);
+                let offset = self.transform.rotate_offset(v.offset, v.rotation);
+                if v.map_aligned > 0.5 {
+                    let position = self
+                        .transform
+                        .to_screen(clip + self.transform.map_offset(clip, offset, 1.0))?;
+                    return Some((position, clip.w));
+                }
+
+                let [x, y] = self.transform.to_screen(clip)?;
+                let scale = self.transform.scale_factor;
+                Some((
+                    [x + offset[0] as f64 * scale, y - offset[1] as f64 * scale],
+                    clip.w,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+
+        for triangle in IMAGE_INDICES.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+            let positions = [projected[a].0, projected[b].0, projected[c].0];
+            let w = [projected[a].1, projected[b].1, projected[c].1];
+            let tex_coords = [a, b, c].map(|i| vertices[i].tex_coords);
+            let opacity = [a, b, c].map(|i| vertices[i].opacity);
+
+            self.framebuffer
+                .fill_triangle(positions, antialias, |bary| {
+                    // Texture coordinates are interpolated with perspective correction, as GPU does.
+                    let corrected = [0, 1, 2].map(|i| bary[i] / w[i]);
+                    let sum: f64 = corrected.iter().sum();
+                    let bary = corrected.map(|v| v / sum);
+
+                    let u = (0..3).map(|i| tex_coords[i][0] as f64 * bary[i]).sum();
+                    let v = (0..3).map(|i| tex_coords[i][1] as f64 * bary[i]).sum();
+                    let opacity: f64 = (0..3).map(|i| opacity[i] as f64 * bary[i]).sum();
+
+                    let mut color = texture.sample(u, v);
+                    color[3] *= opacity as f32;
+                    (color[3] != 0.0).then_some(color)
+                });
+        }
+    }
+
+    fn draw_circle(&mut self, circle: &CircleInstance, antialias: bool) {
+        let Some([x, y]) = self.transform.screen_ref(circle.position, circle.offset) else {
+            return;
+        };
+
+        let scale = self.transform.scale_factor as f32;
+        let radius = circle.radius * scale;
+        let outline_width = circle.outline_width * scale;
+
+        // One extra pixel for the smoothed edge.
+        let extent = (radius + outline_width + 1.0) as f64;
+        let corners = [
+            [x - extent, y - extent],
+            [x + extent, y - extent],
+            [x + extent, y + extent],
+            [x - extent, y + extent],
+        ];
+
+        let center_color = linear_color(circle.center_color.map(|c| c as f32 / 255.0));
+        let side_color = linear_color(circle.side_color.map(|c| c as f32 / 255.0));
+        let outline_color = linear_color(circle.outline_color.map(|c| c as f32 / 255.0));
+
+        for triangle in [[0, 1, 2], [0, 2, 3]] {
+            let positions = triangle.map(|i| corners[i]);
+            self.framebuffer
+                .fill_triangle(positions, antialias, |bary| {
+                    let px = (0..3).map(|i| positions[i][0] * bary[i]).sum::<f64>();
+                    let py = (0..3).map(|i| positions[i][1] * bary[i]).sum::<f64>();
+                    let distance = ((px - x).powi(2) + (py - y).powi(2)).sqrt() as f32;
+
+                    let t = (distance / radius.max(0.0001)).clamp(0.0, 1.0);
+                    let mut fill =
+                        [0, 1, 2, 3].map(|i| center_color[i] * (1.0 - t) + side_color[i] * t);
+                    fill[3] *= (radius - distance + 0.5).clamp(0.0, 1.0);
+
+                    let outline_alpha = if outline_width > 0.0 {
+                        let to_edge = outline_width - (distance - radius).abs();
+                        outline_color[3] * (to_edge + 0.5).clamp(0.0, 1.0)
+                    } else {
+                        0.0
+                    };
+
+                    let alpha = outline_alpha + fill[3] * (1.0 - outline_alpha);
+                    if alpha <= 0.0 {
+                        return None;
+                    }
+
+                    let channel = |i: usize| {
+                        (outline_color[i] * outline_alpha
+                            + fill[i] * fill[3] * (1.0 - outline_alpha))
+                            / alpha
+                    };
+                    Some([channel(0), channel(1), channel(2), alpha])
+                });
+        }
+    }
+}
+
+impl<'a> Canvas for SoftwareCanvas<'a> {
+    fn size(&self) -> Size {
+        Size::new(
+            self.framebuffer.width() as f64,
+            self.framebuffer.height() as f64,
+        )
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.transform.scale_factor
+    }
+
+    fn create_bundle(&self) -> RenderBundle {
+        create_bundle()
+    }
+
+    fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+        match bundle {
+            RenderBundle(RenderBundleType::Tessellating(inner)) => {
+                Box::new(SoftwarePackedBundle::new(inner))
+            }
+        }
+    }
+
+    fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions) {
+        self.framebuffer.set_opacity(options.opacity);
+        for bundle in bundles {
+            if let Some(cast) = bundle.as_any().downcast_ref::<SoftwarePackedBundle>() {
+                if !cast.is_visible(&self.map_view) {
+                    continue;
+                }
+
+                self.draw_bundle(cast, options.antialias);
+            }
+        }
+        self.framebuffer.set_opacity(1.0);
+    }
+
+    fn draw_bundles_with_mask(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        mask: &dyn PackedBundle,
+        _shader: Option<&CustomShader>,
+    ) {
+        let Some(mask) = mask.as_any().downcast_ref::<SoftwarePackedBundle>() else {
+            self.draw_bundles(bundles, options);
+            return;
+        };
+
+        let map_ref = &mask.map_ref;
+        let triangles = triangles(&map_ref.indices)
+            .filter_map(|t| self.transform.map_ref_triangle(&map_ref.vertices, t))
+            .map(|(positions, _)| positions);
+        self.framebuffer
+            .set_layer_mask(triangles, options.antialias);
+        self.draw_bundles(bundles, options);
+        self.framebuffer.reset_layer_mask();
+    }
+}
+
+/// Parameters of the view transformation, same as the view uniform of the `wgpu` shaders.
+struct ViewTransform {
+    view_proj: Matrix4<f64>,
+    view_rotation: Matrix4<f64>,
+    width: f64,
+    height: f64,
+    /// Number of target pixels in one pixel of the map view.
+    scale_factor: f64,
+    resolution: f64,
+    rotation_z: f64,
+    time: f32,
+}
+
+impl ViewTransform {
+    fn new(map_view: &MapView, width: f64, height: f64, time: f32) -> Option<Self> {
+        let view_rotation = Rotation3::new(Vector3::new(
+            map_view.rotation_x(),
+            0.0,
+            -map_view.rotation_z(),
+        ))
+        .to_homogeneous();
+
+        Some(Self {
+            view_proj: map_view.map_to_scene_transform()?,
+            view_rotation,
+            width,
+            height,
+            scale_factor: view_scale_factor(map_view, width),
+            resolution: map_view.resolution(),
+            rotation_z: map_view.rotation_z(),
+            time,
+        })
+    }
+
+    fn project(&self, position: [f32; 3]) -> Vector4<f64> {
+        self.view_proj
+            * Vector4::new(
+                position[0] as f64,
+                position[1] as f64,
+                position[2] as f64,
+                1.0,
+            )
+    }
+
+    /// Converts clip coordinates into pixel coordinates. Returns `None` for the points behind the camera.
+    fn to_screen(&self, clip: Vector4<f64>) -> Option<[f64; 2]> {
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        Some([
+            (clip.x / clip.w + 1.0) / 2.0 * self.width,
+            (1.0 - clip.y / clip.w) / 2.0 * self.height,
+        ])
+    }
+
+    /// Screen position of the map point moved by the offset in pixels (with *Y* going from bottom to top).
+    fn screen_ref(&self, position: [f32; 3], offset: [f32; 2]) -> Option<[f64; 2]> {
+        let [x, y] = self.to_screen(self.project(position))?;
+        Some([
+            x + offset[0] as f64 * self.scale_factor,
+            y - offset[1] as f64 * self.scale_factor,
+        ])
+    }
+
+    /// Rotates the offset of a point symbol vertex clockwise, same as `rotate_offset` function of the shaders.
+    fn rotate_offset(&self, offset: [f32; 2], rotation: [f32; 2]) -> [f32; 2] {
+        let angle = rotation[0] as f64 - rotation[1] as f64 * self.rotation_z;
+        let (sin, cos) = angle.sin_cos();
+        let [x, y] = offset.map(|v| v as f64);
+        [(x * cos + y * sin) as f32, (y * cos - x * sin) as f32]
+    }
+
+    fn map_ref(&self, vertex: &PolyVertex) -> Option<[f64; 2]> {
+        let position = self.project(vertex.position);
+        let zoom = self.zoom_factor(vertex.zoom) as f32;
+        let width_scale = vertex.zoom[2] + (vertex.zoom[3] - vertex.zoom[2]) * zoom;
+        let offset = vertex.normal.map(|v| v * width_scale);
+        let normal = [offset[0] as f64, offset[1] as f64];
+        let norm_length = (normal[0] * normal[0] + normal[1] * normal[1]).sqrt() * self.resolution;
+
+        let norm_limit = vertex.norm_limit as f64;
+        let limit = if norm_length > norm_limit {
+            norm_limit / norm_length
+        } else {
+            1.0
+        };
+
+        self.to_screen(position + self.map_offset(position, offset, limit))
+    }
+
+    /// Zoom interpolation factor of a vertex with the given interpolation parameters at the view resolution.
+    fn zoom_factor(&self, zoom: [f32; 4]) -> f64 {
+        zoom_interpolation::factor(zoom[0] as f64, zoom[1] as f64, self.resolution.log2())
+    }
+
+    /// Offset in clip coordinates of a vertex moved by `offset` pixels along the map plane from the projected
+    /// `position`.
+    fn map_offset(&self, position: Vector4<f64>, offset: [f32; 2], scale: f64) -> Vector4<f64> {
+        // Row vector multiplied by the matrix in the shader.
+        self.view_rotation.transpose()
+            * Vector4::new(
+                offset[0] as f64 * self.scale_factor / self.width * scale * position.w * 2.0,
+                offset[1] as f64 * self.scale_factor / self.height * scale * position.w * 2.0,
+                0.0,
+                0.0,
+            )
+    }
+
+    fn map_ref_triangle<'b>(
+        &self,
+        vertices: &'b [PolyVertex],
+        triangle: [usize; 3],
+    ) -> Option<([[f64; 2]; 3], [&'b PolyVertex; 3])> {
+        let vertices = [
+            vertices.get(triangle[0])?,
+            vertices.get(triangle[1])?,
+            vertices.get(triangle[2])?,
+        ];
+        let positions = [
+            self.map_ref(vertices[0])?,
+            self.map_ref(vertices[1])?,
+            self.map_ref(vertices[2])?,
+        ];
+
+        Some((positions, vertices))
+    }
+}
+
+fn triangles(indices: &[u32]) -> impl Iterator<Item = [usize; 3]> + '_ {
+    indices
+        .chunks_exact(3)
+        .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+}
+
+/// Same as `line_pattern_alpha` function of the `wgpu` shaders.
+fn line_pattern_alpha(
+    line_position: [f32; 2],
+    pattern: [f32; 4],
+    resolution: f32,
+    time: f32,
+) -> f32 {
+    let [length, gap, speed, kind] = pattern;
+    let period = length + gap;
+    if kind == 0.0 || period <= 0.0 {
+        return 1.0;
+    }
+
+    let distance = line_position[0] / resolution - speed * time;
+    let position = distance.rem_euclid(period);
+    if position > length {
+        return 0.0;
+    }
+
+    if kind == 1.0 || line_position[1].abs() <= 1.0 - position / length {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn interpolate(colors: [[f32; 4]; 3], bary: [f64; 3]) -> [f32; 4] {
+    [0, 1, 2, 3].map(|channel| (0..3).map(|i| colors[i][channel] * bary[i] as f32).sum())
+}
+
+/// Image converted to linear colors for sampling.
+struct Texture {
+    width: usize,
+    height: usize,
+    texels: Vec<[f32; 4]>,
+}
+
+impl Texture {
+    fn new(image: &DecodedImage) -> Self {
+        let (width, height) = (image.dimensions.0 as usize, image.dimensions.1 as usize);
+        let texels = image
+            .bytes
+            .chunks_exact(4)
+            .map(|texel| {
+                [
+                    srgb_to_linear(texel[0] as f32 / 255.0),
+                    srgb_to_linear(texel[1] as f32 / 255.0),
+                    srgb_to_linear(texel[2] as f32 / 255.0),
+                    texel[3] as f32 / 255.0,
+                ]
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            texels,
+        }
+    }
+
+    /// Samples the texture with linear filtering and clamping to edge.
+    fn sample(&self, u: f64, v: f64) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 || self.texels.len() < self.width * self.height {
+            return [0.0; 4];
+        }
+
+        let x = (u * self.width as f64 - 0.5).clamp(0.0, (self.width - 1) as f64);
+        let y = (v * self.height as f64 - 0.5).clamp(0.0, (self.height - 1) as f64);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = ((x - x0 as f64) as f32, (y - y0 as f64) as f32);
+
+        let texel = |x: usize, y: usize| self.texels[y * self.width + x];
+        let (t00, t10, t01, t11) = (texel(x0, y0), texel(x1, y0), texel(x0, y1), texel(x1, y1));
+
+        [0, 1, 2, 3].map(|i| {
+            let top = t00[i] * (1.0 - fx) + t10[i] * fx;
+            let bottom = t01[i] * (1.0 - fx) + t11[i] * fx;
+            top * (1.0 - fy) + bottom * fy
+        })
+    }
+}
+
+struct SoftwarePackedBundle {
+    clip_area: Option<VertexBuffers<PolyVertex, u32>>,
+    map_ref: VertexBuffers<PolyVertex, u32>,
+    screen_ref: ScreenRefTessellation,
+    points: Vec<PointInstance>,
+    circles: Vec<CircleInstance>,
+    images: Vec<(usize, [ImageVertex; 4])>,
+    image_points: Vec<ImageInstance>,
+    textures: Vec<Texture>,
+    bounds: Option<BundleBounds>,
+}
+
+impl SoftwarePackedBundle {
+    fn new(bundle: &TessellatingRenderBundle) -> Self {
+        Self {
+            clip_area: bundle.clip_area.clone(),
+            map_ref: bundle.poly_tessellation.clone(),
+            screen_ref: bundle.screen_ref.clone(),
+            points: bundle.points.clone(),
+            circles: bundle.circles.clone(),
+            images: bundle.images.clone(),
+            image_points: bundle.image_points.clone(),
+            textures: bundle
+                .image_store
+                .iter()
+                .map(|image| Texture::new(image))
+                .collect(),
+            bounds: bundle.bounds(),
+        }
+    }
+
+    fn is_visible(&self, view: &MapView) -> bool {
+        self.bounds
+            .map(|bounds| bounds.is_visible(view))
+            .unwrap_or(true)
+    }
+}
+
+impl PackedBundle for SoftwarePackedBundle {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::point_paint::PointPaint;
+    use crate::render::render_bundle::RenderPrimitive;
+    use crate::render::PolygonPaint;
+    use galileo_types::cartesian::{Point2d, Point3d};
+    use galileo_types::impls::{ClosedContour, Contour, Polygon};
+
+    fn square(size: f64) -> Polygon<Point3d> {
+        Polygon::new(
+            ClosedContour::new(vec![
+                Point3d::new(-size, -size, 0.0),
+                Point3d::new(-size, size, 0.0),
+                Point3d::new(size, size, 0.0),
+                Point3d::new(size, -size, 0.0),
+            ]),
+            vec![],
+        )
+    }
+
+    fn render(bundle: &RenderBundle) -> Vec<u8> {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let mut framebuffer = Framebuffer::new(Size::new(100, 100));
+        framebuffer.clear(Color::BLACK);
+
+        let mut canvas = SoftwareCanvas::new(&mut framebuffer, view, 0.0).unwrap();
+        let packed = canvas.pack_bundle(bundle);
+        canvas.draw_bundles(
+            &[&*packed],
+            RenderOptions {
+                antialias: false,
+                ..Default::default()
+            },
+        );
+
+        framebuffer.to_rgba8()
+    }
+
+    fn render_scaled(bundle: &RenderBundle, scale_factor: f64) -> Vec<u8> {
+        let view_size = 100.0 / scale_factor;
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), scale_factor)
+            .with_size(Size::new(view_size, view_size));
+        let mut framebuffer = Framebuffer::new(Size::new(100, 100));
+        framebuffer.clear(Color::BLACK);
+
+        let mut canvas = SoftwareCanvas::new(&mut framebuffer, view, 0.0).unwrap();
+        assert_eq!(canvas.scale_factor(), scale_factor);
+        let packed = canvas.pack_bundle(bundle);
+        canvas.draw_bundles(
+            &[&*packed],
+            RenderOptions {
+                antialias: false,
+                ..Default::default()
+            },
+        );
+
+        framebuffer.to_rgba8()
+    }
+
+    fn pixel(image: &[u8], x: usize, y: usize) -> &[u8] {
+        let offset = (y * 100 + x) * 4;
+        &image[offset..offset + 4]
+    }
+
+    #[test]
+    fn renders_polygon() {
+        let mut bundle = create_bundle();
+        bundle.add(
+            RenderPrimitive::<_, _, Contour<_>, _>::new_polygon(
+                square(20.0),
+                PolygonPaint {
+                    color: Color::RED,
+                    gradient: None,
+                    zoom: None,
+                },
+            ),
+            1.0,
+        );
+
+        let image = render(&bundle);
+        assert_eq!(pixel(&image, 50, 50), &[255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 31, 68), &[255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 0, 0), &[0, 0, 0, 255]);
+        assert_eq!(pixel(&image, 75, 50), &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn pixel_sizes_are_scaled_by_view_scale_factor() {
+        let mut bundle = create_bundle();
+        bundle.add(
+            RenderPrimitive::<_, _, Contour<_>, Polygon<_>>::new_point(
+                Point3d::new(0.0, 0.0, 0.0),
+                PointPaint::square(Color::RED, 10.0),
+            ),
+            1.0,
+        );
+
+        let image = render_scaled(&bundle, 1.0);
+        assert_eq!(pixel(&image, 53, 50), &[255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 58, 50), &[0, 0, 0, 255]);
+
+        let image = render_scaled(&bundle, 2.0);
+        assert_eq!(pixel(&image, 58, 50), &[255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 62, 50), &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn clip_area_is_applied() {
+        let mut bundle = create_bundle();
+        bundle.clip_area(&square(10.0));
+        bundle.add(
+            RenderPrimitive::<_, _, Contour<_>, _>::new_polygon(
+                square(40.0),
+                PolygonPaint {
+                    color: Color::BLUE,
+                    gradient: None,
+                    zoom: None,
+                },
+            ),
+            1.0,
+        );
+
+        let image = render(&bundle);
+        assert_eq!(pixel(&image, 50, 50), &[0, 0, 255, 255]);
+        assert_eq!(pixel(&image, 20, 50), &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn line_pattern_alpha_follows_pattern() {
+        let dash = [4.0, 2.0, 0.0, 1.0];
+        assert_eq!(line_pattern_alpha([1.0, 0.0], dash, 1.0, 0.0), 1.0);
+        assert_eq!(line_pattern_alpha([5.0, 0.0], dash, 1.0, 0.0), 0.0);
+        assert_eq!(line_pattern_alpha([7.0, 0.0], dash, 1.0, 0.0), 1.0);
+        assert_eq!(line_pattern_alpha([10.0, 0.0], dash, 2.0, 0.0), 0.0);
+
+        let moving = [4.0, 2.0, 1.0, 1.0];
+        assert_eq!(line_pattern_alpha([5.0, 0.0], moving, 1.0, 2.0), 1.0);
+
+        let arrows = [4.0, 2.0, 0.0, 2.0];
+        assert_eq!(line_pattern_alpha([1.0, 0.5], arrows, 1.0, 0.0), 1.0);
+        assert_eq!(line_pattern_alpha([3.0, 0.5], arrows, 1.0, 0.0), 0.0);
+        assert_eq!(line_pattern_alpha([3.0, 0.0], arrows, 1.0, 0.0), 1.0);
+
+        assert_eq!(line_pattern_alpha([5.0, 0.0], [0.0; 4], 1.0, 0.0), 1.0);
+    }
+}
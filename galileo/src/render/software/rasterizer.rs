@@ -0,0 +1,440 @@
+//! Rasterization of triangles into a multisampled framebuffer.
+//!
+//! Colors are stored in linear space with straight alpha and blended the same way as the `wgpu` pipelines do with
+//! `ALPHA_BLENDING` state. When the image is read, the colors are encoded into sRGB, as `wgpu` does for its
+//! `Rgba8UnormSrgb` render target.
+
+use crate::Color;
+use galileo_types::cartesian::Size;
+
+/// Number of samples stored per pixel.
+const SAMPLE_COUNT: usize = 4;
+
+/// Positions of the samples inside a pixel, same as the standard 4x MSAA pattern of GPUs.
+const MSAA_SAMPLES: [[f64; 2]; SAMPLE_COUNT] = [
+    [0.375, 0.125],
+    [0.875, 0.375],
+    [0.125, 0.625],
+    [0.625, 0.875],
+];
+
+/// Without antialiasing only the center of the pixel is tested, and the result is written into all its samples.
+const CENTER_SAMPLE: [[f64; 2]; 1] = [[0.5, 0.5]];
+
+/// Samples of a pixel covered by a primitive.
+type Coverage = [bool; SAMPLE_COUNT];
+
+pub(super) struct Framebuffer {
+    width: usize,
+    height: usize,
+    samples: Vec<[f32; 4]>,
+    clip_mask: Option<Vec<bool>>,
+    layer_mask: Option<Vec<bool>>,
+    opacity: f32,
+}
+
+impl Framebuffer {
+    pub fn new(size: Size<u32>) -> Self {
+        let width = size.width() as usize;
+        let height = size.height() as usize;
+        Self {
+            width,
+            height,
+            samples: vec![[0.0; 4]; width * height * SAMPLE_COUNT],
+            clip_mask: None,
+            layer_mask: None,
+            opacity: 1.0,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        self.samples.fill(color.to_linear_f32_array());
+        self.clip_mask = None;
+        self.layer_mask = None;
+    }
+
+    /// Restricts all the following drawing to the area of the given triangles until [`Framebuffer::reset_clip`] is
+    /// called.
+    pub fn set_clip(&mut self, triangles: impl Iterator<Item = [[f64; 2]; 3]>, antialias: bool) {
+        self.clip_mask = Some(self.rasterize_mask(triangles, antialias));
+    }
+
+    pub fn reset_clip(&mut self) {
+        self.clip_mask = None;
+    }
+
+    /// Restricts all the following drawing to the area of the given triangles until
+    /// [`Framebuffer::reset_layer_mask`] is called. Unlike [`Framebuffer::set_clip`], the layer mask is applied in
+    /// addition to the clip area of the bundles.
+    pub fn set_layer_mask(
+        &mut self,
+        triangles: impl Iterator<Item = [[f64; 2]; 3]>,
+        antialias: bool,
+    ) {
+        self.layer_mask = Some(self.rasterize_mask(triangles, antialias));
+    }
+
+    pub fn reset_layer_mask(&mut self) {
+        self.layer_mask = None;
+    }
+
+    /// Multiplies the alpha of all the following drawn primitives by `opacity`.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    fn rasterize_mask(
+        &self,
+        triangles: impl Iterator<Item = [[f64; 2]; 3]>,
+        antialias: bool,
+    ) -> Vec<bool> {
+        let mut mask = vec![false; self.samples.len()];
+        for triangle in triangles {
+            rasterize(
+                triangle,
+                self.width,
+                self.height,
+                antialias,
+                |pixel, coverage, _| {
+                    for (sample, covered) in coverage.into_iter().enumerate() {
+                        if covered {
+                            mask[pixel * SAMPLE_COUNT + sample] = true;
+                        }
+                    }
+                },
+            );
+        }
+
+        mask
+    }
+
+    /// Fills the triangle. The `shader` is called for every pixel touched by the triangle with the barycentric
+    /// coordinates of the pixel center, and returns the color of the pixel or `None` if the pixel must be discarded.
+    pub fn fill_triangle(
+        &mut self,
+        triangle: [[f64; 2]; 3],
+        antialias: bool,
+        mut shader: impl FnMut([f64; 3]) -> Option<[f32; 4]>,
+    ) {
+        let (width, height) = (self.width, self.height);
+        rasterize(
+            triangle,
+            width,
+            height,
+            antialias,
+            |pixel, coverage, bary| {
+                if let Some(color) = shader(bary) {
+                    self.blend(pixel, coverage, color);
+                }
+            },
+        );
+    }
+
+    /// Blends the color into all samples of a single pixel.
+    pub fn fill_pixel(&mut self, x: f64, y: f64, color: [f32; 4]) {
+        if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+            return;
+        }
+
+        let pixel = y as usize * self.width + x as usize;
+        self.blend(pixel, [true; SAMPLE_COUNT], color);
+    }
+
+    fn blend(&mut self, pixel: usize, coverage: Coverage, color: [f32; 4]) {
+        let [r, g, b, a] = color.map(|c| c.clamp(0.0, 1.0));
+        let a = a * self.opacity;
+        for (sample, covered) in coverage.into_iter().enumerate() {
+            let index = pixel * SAMPLE_COUNT + sample;
+            let masked = [&self.clip_mask, &self.layer_mask]
+                .into_iter()
+                .any(|mask| mask.as_ref().is_some_and(|mask| !mask[index]));
+            if !covered || masked {
+                continue;
+            }
+
+            let dst = &mut self.samples[index];
+            dst[0] = r * a + dst[0] * (1.0 - a);
+            dst[1] = g * a + dst[1] * (1.0 - a);
+            dst[2] = b * a + dst[2] * (1.0 - a);
+            dst[3] = a + dst[3] * (1.0 - a);
+        }
+    }
+
+    /// Resolves the samples into RGBA8 image with sRGB encoded colors.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.width * self.height * 4);
+        for pixel in self.samples.chunks_exact(SAMPLE_COUNT) {
+            let mut sum = [0.0; 4];
+            for sample in pixel {
+                for (channel, value) in sum.iter_mut().zip(sample) {
+                    *channel += value;
+                }
+            }
+
+            let [r, g, b, a] = sum.map(|c| c / SAMPLE_COUNT as f32);
+            bytes.extend([
+                to_u8(linear_to_srgb(r)),
+                to_u8(linear_to_srgb(g)),
+                to_u8(linear_to_srgb(b)),
+                to_u8(a),
+            ]);
+        }
+
+        bytes
+    }
+}
+
+/// Calls `f` for every pixel of the framebuffer that has samples covered by the triangle. The arguments of `f` are
+/// index of the pixel, covered samples and barycentric coordinates of the pixel center.
+///
+/// Samples lying exactly on an edge shared by two triangles are given to only one of them, so semi-transparent
+/// primitives are not blended twice along the tessellation seams.
+fn rasterize(
+    triangle: [[f64; 2]; 3],
+    width: usize,
+    height: usize,
+    antialias: bool,
+    mut f: impl FnMut(usize, Coverage, [f64; 3]),
+) {
+    let [a, b, c] = triangle;
+    let area = edge(a, b, c);
+    if area == 0.0 || !area.is_finite() {
+        return;
+    }
+
+    // Bring the triangle to positive orientation, keeping track of the vertex order for barycentric coordinates.
+    let (vertices, order) = if area > 0.0 {
+        ([a, b, c], [0, 1, 2])
+    } else {
+        ([a, c, b], [0, 2, 1])
+    };
+    let area = area.abs();
+
+    let x_min = vertices.iter().map(|v| v[0]).fold(f64::INFINITY, f64::min);
+    let x_max = vertices
+        .iter()
+        .map(|v| v[0])
+        .fold(f64::NEG_INFINITY, f64::max);
+    let y_min = vertices.iter().map(|v| v[1]).fold(f64::INFINITY, f64::min);
+    let y_max = vertices
+        .iter()
+        .map(|v| v[1])
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let x_from = x_min.floor().max(0.0) as usize;
+    let x_to = (x_max.ceil().max(0.0) as usize).min(width);
+    let y_from = y_min.floor().max(0.0) as usize;
+    let y_to = (y_max.ceil().max(0.0) as usize).min(height);
+
+    let sample_positions: &[[f64; 2]] = if antialias {
+        &MSAA_SAMPLES
+    } else {
+        &CENTER_SAMPLE
+    };
+
+    for y in y_from..y_to {
+        for x in x_from..x_to {
+            let mut coverage = [false; SAMPLE_COUNT];
+            let mut any = false;
+            for (i, [dx, dy]) in sample_positions.iter().enumerate() {
+                if is_inside(&vertices, [x as f64 + dx, y as f64 + dy]) {
+                    any = true;
+                    if antialias {
+                        coverage[i] = true;
+                    } else {
+                        coverage = [true; SAMPLE_COUNT];
+                    }
+                }
+            }
+
+            if !any {
+                continue;
+            }
+
+            let center = [x as f64 + 0.5, y as f64 + 0.5];
+            let weights = [
+                edge(vertices[1], vertices[2], center) / area,
+                edge(vertices[2], vertices[0], center) / area,
+                edge(vertices[0], vertices[1], center) / area,
+            ];
+            let mut bary = [0.0; 3];
+            for (weight, index) in weights.into_iter().zip(order) {
+                bary[index] = weight;
+            }
+
+            f(y * width + x, coverage, bary);
+        }
+    }
+}
+
+fn edge(a: [f64; 2], b: [f64; 2], p: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+fn is_inside(vertices: &[[f64; 2]; 3], p: [f64; 2]) -> bool {
+    (0..3).all(|i| {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % 3];
+        let value = edge(a, b, p);
+        // Two triangles sharing an edge traverse it in opposite directions, so only one of them owns the samples
+        // lying exactly on it.
+        value > 0.0 || (value == 0.0 && (b[1] > a[1] || (b[1] == a[1] && b[0] < a[0])))
+    })
+}
+
+/// Converts sRGB encoded color with components in [0.0, 1.0] range into linear space.
+pub(super) fn linear_color(color: [f32; 4]) -> [f32; 4] {
+    let [r, g, b, a] = color;
+    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+}
+
+pub(super) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(framebuffer: &Framebuffer, x: usize, y: usize) -> [u8; 4] {
+        let bytes = framebuffer.to_rgba8();
+        let offset = (y * framebuffer.width() + x) * 4;
+        [
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ]
+    }
+
+    #[test]
+    fn shared_edge_is_blended_once() {
+        let mut framebuffer = Framebuffer::new(Size::new(4, 4));
+        framebuffer.clear(Color::BLACK);
+
+        let half_white = Some([1.0, 1.0, 1.0, 0.5]);
+        for antialias in [false, true] {
+            framebuffer.clear(Color::BLACK);
+            framebuffer.fill_triangle([[0.0, 0.0], [4.0, 0.0], [4.0, 4.0]], antialias, |_| {
+                half_white
+            });
+            framebuffer.fill_triangle([[0.0, 0.0], [4.0, 4.0], [0.0, 4.0]], antialias, |_| {
+                half_white
+            });
+
+            let first = pixel(&framebuffer, 0, 0);
+            for y in 0..4 {
+                for x in 0..4 {
+                    assert_eq!(pixel(&framebuffer, x, y), first);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clip_mask_limits_drawing() {
+        let mut framebuffer = Framebuffer::new(Size::new(4, 4));
+        framebuffer.clear(Color::BLACK);
+        framebuffer.set_clip(
+            [
+                [[0.0, 0.0], [2.0, 0.0], [2.0, 4.0]],
+                [[0.0, 0.0], [2.0, 4.0], [0.0, 4.0]],
+            ]
+            .into_iter(),
+            false,
+        );
+
+        let white = Some([1.0, 1.0, 1.0, 1.0]);
+        framebuffer.fill_triangle([[0.0, 0.0], [4.0, 0.0], [4.0, 4.0]], false, |_| white);
+        framebuffer.fill_triangle([[0.0, 0.0], [4.0, 4.0], [0.0, 4.0]], false, |_| white);
+
+        assert_eq!(pixel(&framebuffer, 1, 1), [255, 255, 255, 255]);
+        assert_eq!(pixel(&framebuffer, 3, 1), [0, 0, 0, 255]);
+
+        framebuffer.reset_clip();
+        framebuffer.fill_pixel(3.5, 1.5, [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(pixel(&framebuffer, 3, 1), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn layer_mask_intersects_clip_area() {
+        let mut framebuffer = Framebuffer::new(Size::new(4, 4));
+        framebuffer.clear(Color::BLACK);
+
+        // Left half of the framebuffer.
+        framebuffer.set_layer_mask(
+            [
+                [[0.0, 0.0], [2.0, 0.0], [2.0, 4.0]],
+                [[0.0, 0.0], [2.0, 4.0], [0.0, 4.0]],
+            ]
+            .into_iter(),
+            false,
+        );
+        // Top half of the framebuffer.
+        framebuffer.set_clip(
+            [
+                [[0.0, 0.0], [4.0, 0.0], [4.0, 2.0]],
+                [[0.0, 0.0], [4.0, 2.0], [0.0, 2.0]],
+            ]
+            .into_iter(),
+            false,
+        );
+
+        for y in 0..4 {
+            for x in 0..4 {
+                framebuffer.fill_pixel(x as f64 + 0.5, y as f64 + 0.5, [1.0, 1.0, 1.0, 1.0]);
+            }
+        }
+
+        assert_eq!(pixel(&framebuffer, 1, 1), [255, 255, 255, 255]);
+        assert_eq!(pixel(&framebuffer, 3, 1), [0, 0, 0, 255]);
+        assert_eq!(pixel(&framebuffer, 1, 3), [0, 0, 0, 255]);
+
+        framebuffer.reset_clip();
+        framebuffer.fill_pixel(1.5, 3.5, [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(pixel(&framebuffer, 1, 3), [255, 255, 255, 255]);
+
+        framebuffer.reset_layer_mask();
+        framebuffer.fill_pixel(3.5, 3.5, [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(pixel(&framebuffer, 3, 3), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn opacity_scales_alpha() {
+        let mut framebuffer = Framebuffer::new(Size::new(2, 1));
+        framebuffer.clear(Color::BLACK);
+
+        framebuffer.set_opacity(0.0);
+        framebuffer.fill_pixel(0.5, 0.5, [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(pixel(&framebuffer, 0, 0), [0, 0, 0, 255]);
+
+        framebuffer.set_opacity(0.5);
+        framebuffer.fill_pixel(1.5, 0.5, [1.0, 1.0, 1.0, 1.0]);
+        let [r, ..] = pixel(&framebuffer, 1, 0);
+        assert!(r > 0 && r < 255);
+    }
+}
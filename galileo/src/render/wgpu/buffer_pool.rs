@@ -0,0 +1,287 @@
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use wgpu::{Buffer, BufferAddress, BufferSlice, BufferUsages, Device, Queue};
+
+/// Default size of a single pooled block. Bundles larger than this get a dedicated block.
+const DEFAULT_BLOCK_SIZE: BufferAddress = 16 * 1024 * 1024;
+
+/// Pool of large GPU buffers that vertex and index data of packed bundles is sub-allocated from.
+///
+/// When a packed bundle is dropped (e.g. a tile is evicted from the cache), its ranges are
+/// returned to the pool and reused by the next bundles, so fast zooming does not create and
+/// destroy a buffer per tile.
+pub(crate) struct BufferPool {
+    usage: BufferUsages,
+    block_size: BufferAddress,
+    blocks: Arc<Mutex<Vec<PoolBlock>>>,
+}
+
+struct PoolBlock {
+    buffer: Arc<Buffer>,
+    allocator: RangeAllocator,
+}
+
+/// A range of a pooled buffer. The range is released back to the pool on drop.
+pub(crate) struct PooledBuffer {
+    buffer: Arc<Buffer>,
+    range: Range<BufferAddress>,
+    block_index: usize,
+    blocks: Arc<Mutex<Vec<PoolBlock>>>,
+}
+
+impl BufferPool {
+    pub fn new(usage: BufferUsages) -> Self {
+        Self {
+            usage: usage | BufferUsages::COPY_DST,
+            block_size: DEFAULT_BLOCK_SIZE,
+            blocks: Default::default(),
+        }
+    }
+
+    /// Copies the `contents` into a free range of the pool, allocating a new block if needed.
+    pub fn allocate(&self, device: &Device, queue: &Queue, contents: &[u8]) -> PooledBuffer {
+        let size = align_to(
+            (contents.len() as BufferAddress).max(wgpu::COPY_BUFFER_ALIGNMENT),
+            wgpu::COPY_BUFFER_ALIGNMENT,
+        );
+
+        let mut blocks = self.blocks.lock().expect("mutex is poisoned");
+        let allocated = blocks.iter_mut().enumerate().find_map(|(index, block)| {
+            block
+                .allocator
+                .allocate(size)
+                .map(|range| (index, block.buffer.clone(), range))
+        });
+
+        let (block_index, buffer, range) = match allocated {
+            Some(v) => v,
+            None => {
+                let block_size = self.block_size.max(size);
+                let buffer = Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Pooled buffer"),
+                    size: block_size,
+                    usage: self.usage,
+                    mapped_at_creation: false,
+                }));
+                let mut allocator = RangeAllocator::new(block_size);
+                let range = allocator
+                    .allocate(size)
+                    .expect("new block must fit the allocation");
+
+                let block = PoolBlock {
+                    buffer: buffer.clone(),
+                    allocator,
+                };
+
+                let index = match blocks.iter().position(|b| b.allocator.is_released()) {
+                    Some(index) => {
+                        blocks[index] = block;
+                        index
+                    }
+                    None => {
+                        blocks.push(block);
+                        blocks.len() - 1
+                    }
+                };
+
+                (index, buffer, range)
+            }
+        };
+
+        drop(blocks);
+
+        if !contents.is_empty() {
+            let write_size = align_to(contents.len() as BufferAddress, wgpu::COPY_BUFFER_ALIGNMENT);
+            if write_size == contents.len() as BufferAddress {
+                queue.write_buffer(&buffer, range.start, contents);
+            } else {
+                let mut padded = contents.to_vec();
+                padded.resize(write_size as usize, 0);
+                queue.write_buffer(&buffer, range.start, &padded);
+            }
+        }
+
+        PooledBuffer {
+            buffer,
+            range,
+            block_index,
+            blocks: self.blocks.clone(),
+        }
+    }
+
+    /// Releases the blocks that have no live allocations in them.
+    pub fn trim(&self) {
+        let mut blocks = self.blocks.lock().expect("mutex is poisoned");
+        for block in blocks.iter_mut() {
+            if block.allocator.is_empty() && !block.allocator.is_released() {
+                block.buffer.destroy();
+                block.allocator.release();
+            }
+        }
+    }
+
+    /// Total size of GPU memory held by the pool in bytes.
+    pub fn allocated_size(&self) -> BufferAddress {
+        let blocks = self.blocks.lock().expect("mutex is poisoned");
+        blocks
+            .iter()
+            .filter(|b| !b.allocator.is_released())
+            .map(|b| b.allocator.capacity())
+            .sum()
+    }
+}
+
+impl PooledBuffer {
+    pub fn slice(&self) -> BufferSlice {
+        self.buffer.slice(self.range.clone())
+    }
+
+    /// Size of the allocated range in bytes.
+    pub fn size(&self) -> BufferAddress {
+        self.range.end - self.range.start
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        let Ok(mut blocks) = self.blocks.lock() else {
+            return;
+        };
+
+        if let Some(block) = blocks.get_mut(self.block_index) {
+            if Arc::ptr_eq(&block.buffer, &self.buffer) {
+                block.allocator.free(self.range.clone());
+            }
+        }
+    }
+}
+
+fn align_to(value: BufferAddress, alignment: BufferAddress) -> BufferAddress {
+    value.div_ceil(alignment) * alignment
+}
+
+/// First-fit allocator of ranges inside a fixed capacity, merging adjacent free ranges on free.
+#[derive(Debug)]
+struct RangeAllocator {
+    capacity: BufferAddress,
+    free: Vec<Range<BufferAddress>>,
+    released: bool,
+}
+
+impl RangeAllocator {
+    fn new(capacity: BufferAddress) -> Self {
+        Self {
+            capacity,
+            free: vec![0..capacity],
+            released: false,
+        }
+    }
+
+    fn capacity(&self) -> BufferAddress {
+        self.capacity
+    }
+
+    fn allocate(&mut self, size: BufferAddress) -> Option<Range<BufferAddress>> {
+        if self.released {
+            return None;
+        }
+
+        let index = self
+            .free
+            .iter()
+            .position(|range| range.end - range.start >= size)?;
+        let range = &mut self.free[index];
+        let allocated = range.start..(range.start + size);
+        range.start += size;
+
+        if range.start == range.end {
+            self.free.remove(index);
+        }
+
+        Some(allocated)
+    }
+
+    fn free(&mut self, range: Range<BufferAddress>) {
+        if self.released || range.start == range.end {
+            return;
+        }
+
+        let index = self
+            .free
+            .iter()
+            .position(|r| r.start > range.start)
+            .unwrap_or(self.free.len());
+        self.free.insert(index, range);
+
+        if index + 1 < self.free.len() && self.free[index].end == self.free[index + 1].start {
+            self.free[index].end = self.free[index + 1].end;
+            self.free.remove(index + 1);
+        }
+
+        if index > 0 && self.free[index - 1].end == self.free[index].start {
+            self.free[index - 1].end = self.free[index].end;
+            self.free.remove(index);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.free.len() == 1 && self.free[0] == (0..self.capacity)
+    }
+
+    fn release(&mut self) {
+        self.released = true;
+        self.free.clear();
+    }
+
+    fn is_released(&self) -> bool {
+        self.released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocate_and_free_reuses_ranges() {
+        let mut allocator = RangeAllocator::new(100);
+        let a = allocator.allocate(40).unwrap();
+        let b = allocator.allocate(40).unwrap();
+        assert_eq!(a, 0..40);
+        assert_eq!(b, 40..80);
+        assert!(allocator.allocate(40).is_none());
+
+        allocator.free(a);
+        assert_eq!(allocator.allocate(30).unwrap(), 0..30);
+    }
+
+    #[test]
+    fn free_merges_adjacent_ranges() {
+        let mut allocator = RangeAllocator::new(90);
+        let a = allocator.allocate(30).unwrap();
+        let b = allocator.allocate(30).unwrap();
+        let c = allocator.allocate(30).unwrap();
+
+        allocator.free(a);
+        allocator.free(c);
+        assert_eq!(allocator.free.len(), 2);
+
+        allocator.free(b);
+        assert!(allocator.is_empty());
+        assert_eq!(allocator.allocate(90).unwrap(), 0..90);
+    }
+
+    #[test]
+    fn released_allocator_does_not_allocate() {
+        let mut allocator = RangeAllocator::new(10);
+        allocator.release();
+        assert!(allocator.allocate(1).is_none());
+    }
+
+    #[test]
+    fn align_to_rounds_up() {
+        assert_eq!(align_to(0, 4), 0);
+        assert_eq!(align_to(1, 4), 4);
+        assert_eq!(align_to(8, 4), 8);
+    }
+}
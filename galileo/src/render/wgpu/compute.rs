@@ -0,0 +1,277 @@
+use crate::error::GalileoError;
+use crate::render::compute::{ComputeBuffer, ComputeShader, WeakComputeBuffer};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use wgpu::{BindGroupLayout, Buffer, BufferUsages, ComputePipeline, Device, Queue};
+
+/// GPU resources of the compute shaders and their buffers.
+#[derive(Default)]
+pub(super) struct ComputeContext {
+    /// Pipelines by the id of the shader and the number of its buffers.
+    pipelines: Mutex<HashMap<(u64, usize), Arc<WgpuComputePipeline>>>,
+    buffers: Mutex<HashMap<u64, Arc<WgpuComputeBuffer>>>,
+}
+
+struct WgpuComputePipeline {
+    pipeline: ComputePipeline,
+    layout: BindGroupLayout,
+}
+
+struct WgpuComputeBuffer {
+    owner: WeakComputeBuffer,
+    storage: Buffer,
+    staging: Option<Arc<Buffer>>,
+    /// Set while the staging buffer is mapped to read the results. No new results are copied into the staging
+    /// buffer until it is unmapped.
+    reading: Arc<AtomicBool>,
+}
+
+/// Returns true if the device was created with the limits that allow running compute shaders. WebGL and GL devices
+/// are created with zero compute limits.
+pub(super) fn supports_compute(device: &Device) -> bool {
+    let limits = device.limits();
+    limits.max_compute_workgroups_per_dimension > 0
+        && limits.max_storage_buffers_per_shader_stage > 0
+}
+
+impl ComputeContext {
+    pub fn dispatch(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        shader: &ComputeShader,
+        workgroups: [u32; 3],
+    ) -> Result<(), GalileoError> {
+        let limits = device.limits();
+        if workgroups
+            .iter()
+            .any(|&count| count > limits.max_compute_workgroups_per_dimension)
+        {
+            return Err(GalileoError::Generic(format!(
+                "number of workgroups {workgroups:?} exceeds the device limit of {}",
+                limits.max_compute_workgroups_per_dimension
+            )));
+        }
+
+        if shader.buffers().len() > limits.max_storage_buffers_per_shader_stage as usize {
+            return Err(GalileoError::Generic(format!(
+                "shader has {} buffers, but the device supports only {}",
+                shader.buffers().len(),
+                limits.max_storage_buffers_per_shader_stage
+            )));
+        }
+
+        shader.validate_buffers()?;
+
+        // Delivers the results of the previous dispatches that are ready.
+        device.poll(wgpu::Maintain::Poll);
+        self.release_dropped_buffers();
+
+        let pipeline = self.pipeline(device, shader);
+        let buffers: Vec<_> = shader
+            .buffers()
+            .iter()
+            .map(|buffer| self.buffer(device, queue, buffer))
+            .collect();
+
+        let entries: Vec<_> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.storage.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Compute bind group"),
+            layout: &pipeline.layout,
+            entries: &entries,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Compute Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Compute Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline.pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            let [x, y, z] = workgroups;
+            compute_pass.dispatch_workgroups(x, y, z);
+        }
+
+        let mut to_read = vec![];
+        for buffer in &buffers {
+            let Some(staging) = &buffer.staging else {
+                continue;
+            };
+
+            if !buffer.reading.swap(true, Ordering::AcqRel) {
+                encoder.copy_buffer_to_buffer(&buffer.storage, 0, staging, 0, staging.size());
+                to_read.push(buffer);
+            }
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        for buffer in to_read {
+            buffer.read_back();
+        }
+
+        Ok(())
+    }
+
+    fn pipeline(&self, device: &Device, shader: &ComputeShader) -> Arc<WgpuComputePipeline> {
+        self.pipelines
+            .lock()
+            .expect("mutex is poisoned")
+            .entry((shader.id(), shader.buffers().len()))
+            .or_insert_with(|| Arc::new(WgpuComputePipeline::create(device, shader)))
+            .clone()
+    }
+
+    /// Returns the GPU buffer for the compute buffer, creating it if needed, and uploads the new contents of the
+    /// buffer to it.
+    fn buffer(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        buffer: &ComputeBuffer,
+    ) -> Arc<WgpuComputeBuffer> {
+        let mut buffers = self.buffers.lock().expect("mutex is poisoned");
+        let is_new = !buffers.contains_key(&buffer.id());
+        let gpu_buffer = buffers
+            .entry(buffer.id())
+            .or_insert_with(|| Arc::new(WgpuComputeBuffer::create(device, buffer)))
+            .clone();
+
+        if let Some(mut data) = buffer.take_upload(is_new) {
+            data.resize(padded_size(data.len()), 0);
+            queue.write_buffer(&gpu_buffer.storage, 0, &data);
+        }
+
+        gpu_buffer
+    }
+
+    fn release_dropped_buffers(&self) {
+        self.buffers
+            .lock()
+            .expect("mutex is poisoned")
+            .retain(|_, buffer| !buffer.owner.is_dropped());
+    }
+}
+
+impl WgpuComputePipeline {
+    fn create(device: &Device, shader: &ComputeShader) -> Self {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Compute shader"),
+            source: wgpu::ShaderSource::Wgsl(shader.source().into()),
+        });
+
+        let entries: Vec<_> = (0..shader.buffers().len())
+            .map(|binding| wgpu::BindGroupLayoutEntry {
+                binding: binding as u32,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            })
+            .collect();
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Compute bind group layout"),
+            entries: &entries,
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Compute pipeline layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Compute pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: "cs_main",
+        });
+
+        Self { pipeline, layout }
+    }
+}
+
+impl WgpuComputeBuffer {
+    fn create(device: &Device, buffer: &ComputeBuffer) -> Self {
+        let size = padded_size(buffer.size()) as wgpu::BufferAddress;
+        let storage = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Compute storage buffer"),
+            size,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = buffer.is_readback().then(|| {
+            Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Compute staging buffer"),
+                size,
+                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }))
+        });
+
+        Self {
+            owner: buffer.downgrade(),
+            storage,
+            staging,
+            reading: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Maps the staging buffer and writes its contents into the compute buffer when the mapping is done.
+    fn read_back(&self) {
+        let Some(staging) = &self.staging else {
+            return;
+        };
+
+        let mapped = staging.clone();
+        let owner = self.owner.clone();
+        let reading = self.reading.clone();
+        staging
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                match result {
+                    Ok(()) => {
+                        let data = mapped.slice(..).get_mapped_range().to_vec();
+                        mapped.unmap();
+                        if let Some(owner) = owner.upgrade() {
+                            owner.set_computed(&data);
+                        }
+                    }
+                    Err(err) => log::warn!("Failed to read compute buffer: {err:?}"),
+                }
+
+                reading.store(false, Ordering::Release);
+            });
+    }
+}
+
+/// Storage buffers must not be empty, and their size must be a multiple of 4 bytes.
+fn padded_size(size: usize) -> usize {
+    size.max(1).div_ceil(wgpu::COPY_BUFFER_ALIGNMENT as usize)
+        * wgpu::COPY_BUFFER_ALIGNMENT as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_sizes_are_padded() {
+        assert_eq!(padded_size(0), 4);
+        assert_eq!(padded_size(4), 4);
+        assert_eq!(padded_size(5), 8);
+    }
+}
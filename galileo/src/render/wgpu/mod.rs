@@ -1,52 +1,163 @@
 use cfg_if::cfg_if;
-use galileo_types::cartesian::Size;
+use galileo_types::cartesian::{Rect, Size};
 use lyon::tessellation::VertexBuffers;
 use nalgebra::{Rotation3, Vector3};
 use std::any::Any;
 use std::mem::size_of;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use wgpu::util::DeviceExt;
 use wgpu::{
-    Adapter, Buffer, BufferAddress, BufferDescriptor, BufferUsages, Device, Extent3d,
-    ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, Queue,
+    Adapter, BindGroup, BufferAddress, BufferDescriptor, BufferUsages, Device, DeviceLostReason,
+    Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, Queue,
     RenderPassDepthStencilAttachment, StoreOp, Surface, SurfaceConfiguration, SurfaceError,
     SurfaceTexture, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat,
     TextureUsages, TextureView, TextureViewDescriptor, WasmNotSendSync,
 };
 
+use crate::control::Clock;
 use crate::error::GalileoError;
 use crate::layer::Layer;
 use crate::map::Map;
 use crate::render::render_bundle::tessellating::{
-    PointInstance, PolyVertex, TessellatingRenderBundle,
+    BundleBounds, PointInstance, PolyVertex, TessellatingRenderBundle,
 };
 use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+use crate::render::wgpu::buffer_pool::{BufferPool, PooledBuffer};
 use crate::render::wgpu::pipelines::image::WgpuImage;
 use crate::render::wgpu::pipelines::Pipelines;
 use crate::view::MapView;
 use crate::Color;
 
-use super::{Canvas, PackedBundle, RenderOptions, Renderer};
+#[cfg(feature = "compute")]
+use super::ComputeShader;
+use super::{
+    view_scale_factor, AnimationClock, Canvas, CustomShader, PackedBundle, RenderOptions, Renderer,
+};
 
+mod buffer_pool;
+#[cfg(feature = "compute")]
+mod compute;
 mod pipelines;
 
 const DEFAULT_BACKGROUND: Color = Color::WHITE;
 const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth24PlusStencil8;
-const TARGET_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+const DEFAULT_MSAA_SAMPLE_COUNT: u32 = 4;
+/// Sample counts that can be supported by a device.
+const MSAA_SAMPLE_COUNTS: [u32; 5] = [1, 2, 4, 8, 16];
+/// Sample counts that are supported by all devices for the formats used by the renderer.
+const GUARANTEED_MSAA_SAMPLE_COUNTS: [u32; 2] = [1, 4];
+
+/// Color space of the images produced by [`WgpuRenderer`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TargetColorSpace {
+    /// Standard sRGB color space with 8 bits per channel. Supported by all devices and surfaces.
+    #[default]
+    Srgb,
+    /// Extended linear sRGB color space with 16-bit float channels (`Rgba16Float` render target).
+    ///
+    /// Colors are stored without sRGB encoding, and values outside of the [0.0, 1.0] range can be represented.
+    /// Displays and compositors that support HDR or wide-gamut output show such surfaces without clipping. Use this
+    /// color space also to render images that will be composited into HDR scenes by other applications.
+    ExtendedSrgb,
+}
+
+impl TargetColorSpace {
+    fn texture_format(&self) -> TextureFormat {
+        match self {
+            TargetColorSpace::Srgb => TextureFormat::Rgba8UnormSrgb,
+            TargetColorSpace::ExtendedSrgb => TextureFormat::Rgba16Float,
+        }
+    }
+
+    /// Selects a format with this color space from the formats supported by a surface.
+    fn surface_format(&self, formats: &[TextureFormat]) -> Option<TextureFormat> {
+        match self {
+            TargetColorSpace::Srgb => formats
+                .iter()
+                .copied()
+                .find(|f| f.is_srgb())
+                .or(formats.first().copied()),
+            TargetColorSpace::ExtendedSrgb => formats
+                .contains(&TextureFormat::Rgba16Float)
+                .then_some(TextureFormat::Rgba16Float),
+        }
+    }
+}
+
+/// Returns true if the format stores sRGB encoded values but does not convert linear shader output into sRGB
+/// automatically, so the shaders must do the encoding.
+fn needs_srgb_encoding(format: TextureFormat) -> bool {
+    !format.is_srgb()
+        && !matches!(
+            format,
+            TextureFormat::Rgba16Float | TextureFormat::Rgba32Float
+        )
+}
+
+/// Recovery of [`WgpuRenderer`] from a loss of its render target or GPU device. See
+/// [`WgpuRenderer::set_recovery_callback`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RecoveryEvent {
+    /// The surface was lost or became outdated (e.g. the window was minimized or moved to another display) and was
+    /// configured again. All GPU resources are still valid, so the application does not need to do anything.
+    SurfaceReconfigured,
+    /// The GPU device was lost (e.g. after a driver reset) and a new device was created. The layers must upload their
+    /// resources again with [`Map::reset_gpu_resources`].
+    DeviceRecreated,
+}
+
+/// Render target of the eye views drawn by [`WgpuRenderer::render_stereo`].
+#[derive(Debug, Copy, Clone)]
+pub enum StereoTarget<'a> {
+    /// Both eyes are drawn into the same texture: the left eye into the left half and the right eye into the right
+    /// half of it. The texture must have the size of the renderer, and the eye views should have half of its width.
+    SideBySide(&'a TextureView),
+    /// Each eye is drawn into its own texture, e.g. into the swapchain images of an OpenXR session. Both textures
+    /// must have the size of the renderer.
+    Separate {
+        /// Texture of the left eye.
+        left: &'a TextureView,
+        /// Texture of the right eye.
+        right: &'a TextureView,
+    },
+}
 
 /// Render backend that uses `wgpu` crate to render the map.
+///
+/// # Surface and device loss
+///
+/// If the window surface is lost or becomes outdated, the renderer configures it again and retries the frame
+/// automatically.
+///
+/// The GPU device can be lost too, for example when the graphics driver is reset on Windows, or when an Android
+/// application is resumed. After that [`WgpuRenderer::is_device_lost`] returns `true`, and nothing is rendered until
+/// the renderer is recovered with [`WgpuRenderer::recover_with_window`] (or [`WgpuRenderer::recover`] for texture
+/// render targets). Packed bundles of the old device cannot be drawn by the new one, so the application must call
+/// [`Map::reset_gpu_resources`] after the recovery.
 pub struct WgpuRenderer {
     device: Arc<Device>,
     queue: Arc<Queue>,
+    adapter: Option<Adapter>,
     render_set: Option<RenderSet>,
     background: Color,
+    vertex_pool: BufferPool,
+    index_pool: BufferPool,
+    msaa_sample_count: u32,
+    supported_msaa_sample_counts: Vec<u32>,
+    color_space: TargetColorSpace,
+    clock: AnimationClock,
+    device_lost: Arc<AtomicBool>,
+    recovery_callback: Option<Box<dyn Fn(RecoveryEvent) + Send + Sync>>,
+    #[cfg(feature = "compute")]
+    compute: compute::ComputeContext,
 }
 
 struct RenderSet {
     render_target: RenderTarget,
     pipelines: Pipelines,
-    multisampling_view: TextureView,
-    stencil_view_multisample: TextureView,
+    multisampling_view: Option<TextureView>,
+    stencil_view_multisample: Option<TextureView>,
     stencil_view: TextureView,
 }
 
@@ -99,7 +210,7 @@ impl RenderTarget {
     fn format(&self) -> TextureFormat {
         match &self {
             RenderTarget::Surface { config, .. } => config.format,
-            RenderTarget::Texture(_, _) => TARGET_TEXTURE_FORMAT,
+            RenderTarget::Texture(texture, _) => texture.format(),
         }
     }
 }
@@ -124,13 +235,30 @@ impl WgpuRenderer {
             })
             .await?;
 
-        let (device, queue) = Self::create_device(&adapter).await;
+        let (device, queue) = Self::create_device(&adapter)
+            .await
+            .expect("Failed to obtain WGPU device");
+        let device_lost = Self::watch_device_loss(&device);
+        let color_space = TargetColorSpace::default();
+        let supported_msaa_sample_counts =
+            Self::query_msaa_sample_counts(&adapter, color_space.texture_format());
 
         Some(Self {
             device: Arc::new(device),
             queue: Arc::new(queue),
+            adapter: Some(adapter),
             render_set: None,
             background: DEFAULT_BACKGROUND,
+            vertex_pool: BufferPool::new(BufferUsages::VERTEX),
+            index_pool: BufferPool::new(BufferUsages::INDEX),
+            msaa_sample_count: DEFAULT_MSAA_SAMPLE_COUNT,
+            supported_msaa_sample_counts,
+            color_space,
+            clock: AnimationClock::default(),
+            device_lost,
+            recovery_callback: None,
+            #[cfg(feature = "compute")]
+            compute: Default::default(),
         })
     }
 
@@ -145,12 +273,13 @@ impl WgpuRenderer {
     }
 
     fn init_target_texture(&mut self, size: Size<u32>) {
-        let target_texture = Self::create_target_texture(&self.device, size);
+        let target_texture =
+            Self::create_target_texture(&self.device, size, self.color_space.texture_format());
         let render_target = RenderTarget::Texture(target_texture, size);
         self.init_render_set(render_target);
     }
 
-    fn create_target_texture(device: &Device, size: Size<u32>) -> Texture {
+    fn create_target_texture(device: &Device, size: Size<u32>, format: TextureFormat) -> Texture {
         device.create_texture(&TextureDescriptor {
             label: Some("Render target texture"),
             size: Extent3d {
@@ -161,7 +290,7 @@ impl WgpuRenderer {
             mip_level_count: 1,
             sample_count: 1,
             dimension: TextureDimension::D2,
-            format: TARGET_TEXTURE_FORMAT,
+            format,
             usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
             view_formats: &[],
         })
@@ -180,7 +309,7 @@ impl WgpuRenderer {
                 let pipelines = if new_target.format() == render_target.format() {
                     pipelines
                 } else {
-                    Pipelines::create(&self.device, new_target.format())
+                    Pipelines::create(&self.device, new_target.format(), self.msaa_sample_count)
                 };
 
                 self.render_set = Some(RenderSet {
@@ -199,11 +328,11 @@ impl WgpuRenderer {
         let size = render_target.size();
         let format = render_target.format();
 
-        let multisampling_view = Self::create_multisample_texture(&self.device, size, format);
-        let stencil_view_multisample = Self::create_stencil_texture(&self.device, size, 4);
+        let (multisampling_view, stencil_view_multisample) =
+            Self::create_multisample_views(&self.device, size, format, self.msaa_sample_count);
         let stencil_view = Self::create_stencil_texture(&self.device, size, 1);
 
-        let pipelines = Pipelines::create(&self.device, format);
+        let pipelines = Pipelines::create(&self.device, format, self.msaa_sample_count);
 
         RenderSet {
             render_target,
@@ -226,18 +355,26 @@ impl WgpuRenderer {
             + 'static,
     {
         let (surface, adapter) = Self::get_window_surface(window).await?;
-        let (device, queue) = Self::create_device(&adapter).await;
+        let (device, queue) = Self::create_device(&adapter)
+            .await
+            .expect("Failed to obtain WGPU device");
 
-        let config = Self::get_surface_configuration(&surface, &adapter, size);
+        let config =
+            Self::get_surface_configuration(&surface, &adapter, size, TargetColorSpace::default());
         log::info!("Configuring surface with size {size:?}");
         surface.configure(&device, &config);
 
-        Some(Self::new_with_device_and_surface(
+        let format = config.format;
+        let mut renderer = Self::new_with_device_and_surface(
             Arc::new(device),
             Arc::new(surface),
             Arc::new(queue),
             config,
-        ))
+        );
+        renderer.adapter = Some(adapter);
+        renderer.update_msaa_sample_counts(format);
+
+        Some(renderer)
     }
 
     /// Creates a wgpu surface for the given window.
@@ -275,13 +412,11 @@ impl WgpuRenderer {
         surface: &Surface,
         adapter: &Adapter,
         size: Size<u32>,
+        color_space: TargetColorSpace,
     ) -> SurfaceConfiguration {
         let surface_caps = surface.get_capabilities(adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .find(|f| f.is_srgb())
+        let surface_format = color_space
+            .surface_format(&surface_caps.formats)
             .unwrap_or(surface_caps.formats[0]);
 
         SurfaceConfiguration {
@@ -297,18 +432,38 @@ impl WgpuRenderer {
     }
 
     /// Creates a new renderer from the initialized wgpu structs.
+    ///
+    /// The renderer sets the device lost callback of the `device` to detect the device loss (see
+    /// [`WgpuRenderer::is_device_lost`]), replacing the callback set before.
     pub fn new_with_device_and_surface(
         device: Arc<Device>,
         surface: Arc<Surface<'static>>,
         queue: Arc<Queue>,
         config: SurfaceConfiguration,
     ) -> Self {
+        let color_space = if config.format == TextureFormat::Rgba16Float {
+            TargetColorSpace::ExtendedSrgb
+        } else {
+            TargetColorSpace::Srgb
+        };
         let render_target = RenderTarget::Surface { surface, config };
+        let device_lost = Self::watch_device_loss(&device);
         let mut renderer = Self {
             device,
             queue,
+            adapter: None,
             render_set: None,
             background: DEFAULT_BACKGROUND,
+            vertex_pool: BufferPool::new(BufferUsages::VERTEX),
+            index_pool: BufferPool::new(BufferUsages::INDEX),
+            msaa_sample_count: DEFAULT_MSAA_SAMPLE_COUNT,
+            supported_msaa_sample_counts: GUARANTEED_MSAA_SAMPLE_COUNTS.to_vec(),
+            color_space,
+            clock: AnimationClock::default(),
+            device_lost,
+            recovery_callback: None,
+            #[cfg(feature = "compute")]
+            compute: Default::default(),
         };
         renderer.init_render_set(render_target);
 
@@ -320,11 +475,306 @@ impl WgpuRenderer {
         self.background = color;
     }
 
+    /// Sets the clock that drives animated primitives, like moving [line patterns](super::LinePattern). The time
+    /// of the animations starts from zero when the clock is set.
+    ///
+    /// By default the system time is used.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = AnimationClock::new(clock);
+    }
+
+    /// Color space of the render target.
+    pub fn color_space(&self) -> TargetColorSpace {
+        self.color_space
+    }
+
+    /// Returns true if the given color space can be set for the current render target with
+    /// [`WgpuRenderer::set_color_space`].
+    ///
+    /// If the renderer was created from an existing device with [`WgpuRenderer::new_with_device_and_surface`], the
+    /// adapter capabilities are not known, and only the current color space is reported as supported.
+    pub fn supports_color_space(&self, color_space: TargetColorSpace) -> bool {
+        let Some(adapter) = &self.adapter else {
+            return color_space == self.color_space;
+        };
+
+        match &self.render_set {
+            Some(RenderSet {
+                render_target: RenderTarget::Surface { surface, .. },
+                ..
+            }) => color_space
+                .surface_format(&surface.get_capabilities(adapter).formats)
+                .is_some(),
+            _ => adapter
+                .get_texture_format_features(color_space.texture_format())
+                .allowed_usages
+                .contains(TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC),
+        }
+    }
+
+    /// Sets the color space of the render target. The surface or the target texture is reconfigured with the
+    /// format of the new color space.
+    ///
+    /// Colors of the layers and raster tiles are always treated as sRGB encoded, and are converted by the renderer
+    /// into the target color space, so the map looks the same in all color spaces on a display that supports them.
+    ///
+    /// Returns an error if the color space is not supported by the render target (see
+    /// [`WgpuRenderer::supports_color_space`]).
+    pub fn set_color_space(&mut self, color_space: TargetColorSpace) -> Result<(), GalileoError> {
+        if color_space == self.color_space {
+            return Ok(());
+        }
+
+        if !self.supports_color_space(color_space) {
+            return Err(GalileoError::Generic(format!(
+                "color space {color_space:?} is not supported by the render target"
+            )));
+        }
+
+        self.color_space = color_space;
+        let Some(render_set) = self.render_set.take() else {
+            return Ok(());
+        };
+
+        let render_target = match render_set.render_target {
+            RenderTarget::Surface {
+                mut config,
+                surface,
+            } => {
+                if let Some(format) = self.adapter.as_ref().and_then(|adapter| {
+                    color_space.surface_format(&surface.get_capabilities(adapter).formats)
+                }) {
+                    config.format = format;
+                }
+                surface.configure(&self.device, &config);
+                RenderTarget::Surface { config, surface }
+            }
+            RenderTarget::Texture(_, size) => RenderTarget::Texture(
+                Self::create_target_texture(&self.device, size, color_space.texture_format()),
+                size,
+            ),
+        };
+
+        let format = render_target.format();
+        self.render_set = Some(self.create_render_set(render_target));
+        self.update_msaa_sample_counts(format);
+
+        Ok(())
+    }
+
+    /// Number of samples per pixel used to draw primitives with [`RenderOptions::antialias`] set. The value of `1`
+    /// means that multisampling is turned off.
+    pub fn msaa_sample_count(&self) -> u32 {
+        self.msaa_sample_count
+    }
+
+    /// Sample counts that can be set with [`WgpuRenderer::set_msaa_sample_count`] for the current render target.
+    ///
+    /// If the renderer was created from an existing device with [`WgpuRenderer::new_with_device_and_surface`], the
+    /// adapter capabilities are not known, and only the counts supported by all devices (`1` and `4`) are returned.
+    pub fn supported_msaa_sample_counts(&self) -> &[u32] {
+        &self.supported_msaa_sample_counts
+    }
+
+    /// Sets the number of samples per pixel for multisample anti-aliasing.
+    ///
+    /// Higher values give smoother edges of the primitives at the cost of GPU memory and fill rate. If the count of
+    /// `1` is set, multisampling is turned off, and the edges of lines drawn with [`RenderOptions::antialias`] are
+    /// smoothed by the shader instead. Polygon edges are not smoothed in this case.
+    ///
+    /// Returns an error if the count is not supported by the device (see
+    /// [`WgpuRenderer::supported_msaa_sample_counts`]).
+    pub fn set_msaa_sample_count(&mut self, count: u32) -> Result<(), GalileoError> {
+        if !self.supported_msaa_sample_counts.contains(&count) {
+            return Err(GalileoError::Generic(format!(
+                "MSAA sample count {count} is not supported by the device"
+            )));
+        }
+
+        if count != self.msaa_sample_count {
+            self.msaa_sample_count = count;
+            self.recreate_render_set();
+        }
+
+        Ok(())
+    }
+
+    fn query_msaa_sample_counts(adapter: &Adapter, format: TextureFormat) -> Vec<u32> {
+        let color_flags = adapter.get_texture_format_features(format).flags;
+        let depth_flags = adapter.get_texture_format_features(DEPTH_FORMAT).flags;
+
+        MSAA_SAMPLE_COUNTS
+            .into_iter()
+            .filter(|&count| {
+                color_flags.sample_count_supported(count)
+                    && depth_flags.sample_count_supported(count)
+            })
+            .collect()
+    }
+
+    /// Updates the supported sample counts for a new render target, and falls back to the highest supported count
+    /// if the current one is not supported by it.
+    fn update_msaa_sample_counts(&mut self, format: TextureFormat) {
+        let Some(adapter) = &self.adapter else {
+            return;
+        };
+
+        self.supported_msaa_sample_counts = Self::query_msaa_sample_counts(adapter, format);
+        if self
+            .supported_msaa_sample_counts
+            .contains(&self.msaa_sample_count)
+        {
+            return;
+        }
+
+        let fallback = self
+            .supported_msaa_sample_counts
+            .iter()
+            .copied()
+            .filter(|&count| count <= self.msaa_sample_count)
+            .max()
+            .unwrap_or(1);
+        log::info!(
+            "MSAA sample count {} is not supported, using {fallback}",
+            self.msaa_sample_count
+        );
+        self.msaa_sample_count = fallback;
+        self.recreate_render_set();
+    }
+
+    fn recreate_render_set(&mut self) {
+        if let Some(render_set) = self.render_set.take() {
+            self.render_set = Some(self.create_render_set(render_set.render_target));
+        }
+    }
+
+    /// Returns the total size in bytes of the GPU buffers held by the renderer for vertex and index data.
+    pub fn buffer_pool_size(&self) -> u64 {
+        self.vertex_pool.allocated_size() + self.index_pool.allocated_size()
+    }
+
+    /// Releases pooled GPU buffers that are not used by any packed bundle anymore.
+    ///
+    /// Vertex and index data of packed bundles is sub-allocated from large pooled buffers, which are kept
+    /// alive after the bundles are dropped to be reused by new bundles. Call this method to return that
+    /// memory to the GPU, e.g. after a large layer was removed from the map.
+    pub fn trim_buffer_pools(&self) {
+        self.vertex_pool.trim();
+        self.index_pool.trim();
+    }
+
     /// Returns `true` if the renderer can be used to draw to.
     pub fn initialized(&self) -> bool {
         self.render_set.is_some()
     }
 
+    /// Returns `true` if the GPU device of the renderer was lost. Nothing is rendered until the renderer is recovered
+    /// with [`WgpuRenderer::recover_with_window`] or [`WgpuRenderer::recover`].
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Acquire)
+    }
+
+    /// Sets a callback that is called every time the renderer recovers from a loss of the surface or the GPU device.
+    pub fn set_recovery_callback(
+        &mut self,
+        callback: impl Fn(RecoveryEvent) + Send + Sync + 'static,
+    ) {
+        self.recovery_callback = Some(Box::new(callback));
+    }
+
+    fn emit_recovery(&self, event: RecoveryEvent) {
+        log::info!("Renderer recovered: {event:?}");
+        if let Some(callback) = &self.recovery_callback {
+            callback(event);
+        }
+    }
+
+    /// Creates a new GPU device and a new surface for the window, e.g. after the previous device was lost (see
+    /// [`WgpuRenderer::is_device_lost`]). All the settings of the renderer are preserved.
+    ///
+    /// After the recovery [`Map::reset_gpu_resources`] must be called, since the packed bundles of the layers belong
+    /// to the old device.
+    pub async fn recover_with_window<W>(
+        &mut self,
+        window: Arc<W>,
+        size: Size<u32>,
+    ) -> Result<(), GalileoError>
+    where
+        W: raw_window_handle::HasWindowHandle
+            + raw_window_handle::HasDisplayHandle
+            + WasmNotSendSync
+            + 'static,
+    {
+        let Some((surface, adapter)) = Self::get_window_surface(window).await else {
+            return Err(GalileoError::Generic("Failed to create surface".into()));
+        };
+
+        self.replace_device(&adapter).await?;
+        self.init_with_surface(surface, adapter, size);
+        self.emit_recovery(RecoveryEvent::DeviceRecreated);
+
+        Ok(())
+    }
+
+    /// Creates a new GPU device and a new target texture of the same size, e.g. after the previous device was lost
+    /// (see [`WgpuRenderer::is_device_lost`]).
+    ///
+    /// Renderers drawing to a window surface cannot be recovered without the window, so an error is returned for
+    /// them. Use [`WgpuRenderer::recover_with_window`] instead.
+    pub async fn recover(&mut self) -> Result<(), GalileoError> {
+        let size = match &self.render_set {
+            Some(RenderSet {
+                render_target: RenderTarget::Texture(_, size),
+                ..
+            }) => Some(*size),
+            Some(_) => {
+                return Err(GalileoError::Generic(
+                    "renderer with a surface render target must be recovered with a window".into(),
+                ))
+            }
+            None => None,
+        };
+
+        let instance = Self::create_instance();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| GalileoError::Generic("Failed to acquire device adapter".into()))?;
+
+        self.replace_device(&adapter).await?;
+        self.adapter = Some(adapter);
+        self.update_msaa_sample_counts(self.color_space.texture_format());
+        if let Some(size) = size {
+            self.init_target_texture(size);
+        }
+
+        self.emit_recovery(RecoveryEvent::DeviceRecreated);
+
+        Ok(())
+    }
+
+    /// Replaces the device and drops all the resources created with the old one.
+    async fn replace_device(&mut self, adapter: &Adapter) -> Result<(), GalileoError> {
+        let (device, queue) = Self::create_device(adapter).await?;
+
+        self.device_lost = Self::watch_device_loss(&device);
+        self.device = Arc::new(device);
+        self.queue = Arc::new(queue);
+        self.vertex_pool = BufferPool::new(BufferUsages::VERTEX);
+        self.index_pool = BufferPool::new(BufferUsages::INDEX);
+        self.render_set = None;
+        #[cfg(feature = "compute")]
+        {
+            self.compute = Default::default();
+        }
+
+        Ok(())
+    }
+
     fn create_instance() -> wgpu::Instance {
         cfg_if! {
             if #[cfg(target_os = "android")] {
@@ -342,7 +792,7 @@ impl WgpuRenderer {
         })
     }
 
-    async fn create_device(adapter: &Adapter) -> (Device, Queue) {
+    async fn create_device(adapter: &Adapter) -> Result<(Device, Queue), GalileoError> {
         adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
@@ -360,13 +810,56 @@ impl WgpuRenderer {
                 None,
             )
             .await
-            .expect("Failed to obtain WGPU device")
+            .map_err(|err| GalileoError::Generic(format!("failed to obtain WGPU device: {err}")))
+    }
+
+    /// Sets the device lost callback of the device, returning the flag that is set when the device is lost.
+    fn watch_device_loss(device: &Device) -> Arc<AtomicBool> {
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let flag = device_lost.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            // These are reported when the device is dropped by the renderer itself, e.g. after a recovery.
+            if matches!(
+                reason,
+                DeviceLostReason::Dropped | DeviceLostReason::ReplacedCallback
+            ) {
+                return;
+            }
+
+            log::error!("GPU device is lost ({reason:?}): {message}");
+            flag.store(true, Ordering::Release);
+        });
+
+        device_lost
+    }
+
+    /// Creates multisampled color and stencil views for the current sample count, or `None` if multisampling is off.
+    fn create_multisample_views(
+        device: &Device,
+        size: Size<u32>,
+        format: TextureFormat,
+        sample_count: u32,
+    ) -> (Option<TextureView>, Option<TextureView>) {
+        if sample_count == 1 {
+            return (None, None);
+        }
+
+        (
+            Some(Self::create_multisample_texture(
+                device,
+                size,
+                format,
+                sample_count,
+            )),
+            Some(Self::create_stencil_texture(device, size, sample_count)),
+        )
     }
 
     fn create_multisample_texture(
         device: &Device,
         size: Size<u32>,
         format: TextureFormat,
+        sample_count: u32,
     ) -> TextureView {
         let multisampling_texture = device.create_texture(&TextureDescriptor {
             label: Some("Multisampling texture"),
@@ -376,7 +869,7 @@ impl WgpuRenderer {
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
-            sample_count: 4,
+            sample_count,
             dimension: TextureDimension::D2,
             format,
             usage: TextureUsages::RENDER_ATTACHMENT,
@@ -439,8 +932,18 @@ impl WgpuRenderer {
         adapter: Adapter,
         size: Size<u32>,
     ) {
-        let config = Self::get_surface_configuration(&surface, &adapter, size);
+        let config = Self::get_surface_configuration(&surface, &adapter, size, self.color_space);
         surface.configure(&self.device, &config);
+        if self.color_space.surface_format(&[config.format]).is_none() {
+            log::info!(
+                "Color space {:?} is not supported by the surface, using sRGB",
+                self.color_space
+            );
+            self.color_space = TargetColorSpace::Srgb;
+        }
+
+        self.adapter = Some(adapter);
+        self.update_msaa_sample_counts(config.format);
 
         let render_target = RenderTarget::Surface {
             surface: Arc::new(surface),
@@ -470,37 +973,47 @@ impl WgpuRenderer {
                     surface.configure(&self.device, config);
                 }
                 RenderTarget::Texture(texture, size) => {
-                    *texture = Self::create_target_texture(&self.device, *size);
+                    *texture = Self::create_target_texture(&self.device, *size, format);
                     *size = new_size
                 }
             }
 
-            render_set.multisampling_view =
-                Self::create_multisample_texture(&self.device, new_size, format);
-            render_set.stencil_view_multisample =
-                Self::create_stencil_texture(&self.device, new_size, 4);
+            (
+                render_set.multisampling_view,
+                render_set.stencil_view_multisample,
+            ) = Self::create_multisample_views(
+                &self.device,
+                new_size,
+                format,
+                self.msaa_sample_count,
+            );
             render_set.stencil_view = Self::create_stencil_texture(&self.device, new_size, 1);
         }
     }
 
     fn target_format(&self) -> TextureFormat {
         match &self.render_set {
-            Some(RenderSet {
-                render_target: RenderTarget::Surface { config, .. },
-                ..
-            }) => config.format,
-            _ => TARGET_TEXTURE_FORMAT,
+            Some(render_set) => render_set.render_target.format(),
+            None => self.color_space.texture_format(),
         }
     }
 
     /// Returns the image of the last render operation.
+    ///
+    /// For [`TargetColorSpace::Srgb`] the image contains 4 bytes per pixel (sRGB encoded RGBA), and for
+    /// [`TargetColorSpace::ExtendedSrgb`] 8 bytes per pixel (linear RGBA as little-endian `f16` values).
     pub async fn get_image(&self) -> Result<Vec<u8>, SurfaceError> {
         let Some(render_set) = &self.render_set else {
             return Err(SurfaceError::Lost);
         };
 
         let size = render_set.render_target.size();
-        let buffer_size = (size.width() * size.height() * size_of::<u32>() as u32) as BufferAddress;
+        let bytes_per_pixel = render_set
+            .render_target
+            .format()
+            .block_copy_size(None)
+            .unwrap_or(size_of::<u32>() as u32);
+        let buffer_size = (size.width() * size.height() * bytes_per_pixel) as BufferAddress;
         let buffer_desc = BufferDescriptor {
             size: buffer_size,
             usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
@@ -525,7 +1038,7 @@ impl WgpuRenderer {
                 buffer: &buffer,
                 layout: ImageDataLayout {
                     offset: 0,
-                    bytes_per_row: Some(size_of::<u32>() as u32 * size.width()),
+                    bytes_per_row: Some(bytes_per_pixel * size.width()),
                     rows_per_image: Some(size.height()),
                 },
             },
@@ -566,6 +1079,61 @@ impl WgpuRenderer {
 
     /// Renders the map to the given texture.
     pub fn render_to_texture_view(&self, map: &Map, view: &TextureView) {
+        if self.render_set.is_none() {
+            return;
+        }
+
+        self.clear_texture_view(view);
+        self.render_map(map, map.view(), view, None);
+    }
+
+    /// Renders the map from two eye views, e.g. for a VR headset or a stereo display.
+    ///
+    /// The views are usually created from the view of the map with [`MapView::with_eye`] or
+    /// [`MapView::stereo_pair`]. Layers are prepared and loaded for the view of the map, so its field of view should
+    /// cover both eyes.
+    pub fn render_stereo(
+        &self,
+        map: &Map,
+        left: &MapView,
+        right: &MapView,
+        target: StereoTarget<'_>,
+    ) {
+        if self.render_set.is_none() {
+            return;
+        }
+
+        match target {
+            StereoTarget::SideBySide(view) => {
+                let size = self.size();
+                let half_width = (size.width() / 2.0).floor();
+                self.clear_texture_view(view);
+                self.render_map(
+                    map,
+                    left,
+                    view,
+                    Some(Rect::new(0.0, 0.0, half_width, size.height())),
+                );
+                self.render_map(
+                    map,
+                    right,
+                    view,
+                    Some(Rect::new(half_width, 0.0, size.width(), size.height())),
+                );
+            }
+            StereoTarget::Separate {
+                left: left_view,
+                right: right_view,
+            } => {
+                self.clear_texture_view(left_view);
+                self.render_map(map, left, left_view, None);
+                self.clear_texture_view(right_view);
+                self.render_map(map, right, right_view, None);
+            }
+        }
+    }
+
+    fn clear_texture_view(&self, view: &TextureView) {
         if let Some(render_set) = &self.render_set {
             let mut encoder = self
                 .device
@@ -574,12 +1142,20 @@ impl WgpuRenderer {
                 });
 
             {
-                let background = self.background.to_f32_array();
+                let background = if needs_srgb_encoding(render_set.render_target.format()) {
+                    self.background.to_f32_array()
+                } else {
+                    self.background.to_linear_f32_array()
+                };
+                let (target_view, resolve_target) = match &render_set.multisampling_view {
+                    Some(multisampling_view) => (multisampling_view, Some(view)),
+                    None => (view, None),
+                };
                 let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("Render Pass"),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &render_set.multisampling_view,
-                        resolve_target: Some(view),
+                        view: target_view,
+                        resolve_target,
                         ops: wgpu::Operations {
                             load: wgpu::LoadOp::Clear(wgpu::Color {
                                 r: background[0] as f64,
@@ -597,20 +1173,43 @@ impl WgpuRenderer {
             }
 
             self.queue.submit(std::iter::once(encoder.finish()));
-        } else {
-            return;
         }
-
-        self.render_map(map, view);
     }
 
     /// Renders the map.
+    ///
+    /// If the surface is lost or outdated, it is configured again and the frame is retried once. If the retry fails
+    /// too, the error is returned and the frame is not drawn, so the caller should request another redraw later. If the
+    /// GPU device is lost, [`SurfaceError::Lost`] is returned until the renderer is recovered (see
+    /// [`WgpuRenderer::is_device_lost`]).
     pub fn render(&self, map: &Map) -> Result<(), SurfaceError> {
         let Some(render_set) = &self.render_set else {
             return Ok(());
         };
 
-        let texture = render_set.render_target.texture()?;
+        if self.is_device_lost() {
+            return Err(SurfaceError::Lost);
+        }
+
+        let texture = match render_set.render_target.texture() {
+            Ok(texture) => texture,
+            Err(SurfaceError::Lost | SurfaceError::Outdated) => {
+                self.reconfigure_surface(&render_set.render_target);
+                match render_set.render_target.texture() {
+                    Ok(texture) => {
+                        self.emit_recovery(RecoveryEvent::SurfaceReconfigured);
+                        texture
+                    }
+                    Err(err) => {
+                        log::warn!(
+                            "Failed to get the surface texture after reconfiguring it: {err:?}"
+                        );
+                        return Err(err);
+                    }
+                }
+            }
+            Err(err) => return Err(err),
+        };
         let view = texture.view();
 
         self.render_to_texture_view(map, &view);
@@ -620,18 +1219,38 @@ impl WgpuRenderer {
         Ok(())
     }
 
-    fn render_map(&self, map: &Map, texture_view: &TextureView) {
-        let view = map.view();
+    fn reconfigure_surface(&self, render_target: &RenderTarget) {
+        if let RenderTarget::Surface { config, surface } = render_target {
+            log::info!("Surface is lost or outdated, configuring it again");
+            surface.configure(&self.device, config);
+        }
+    }
+
+    fn render_map(
+        &self,
+        map: &Map,
+        view: &MapView,
+        texture_view: &TextureView,
+        viewport: Option<Rect>,
+    ) {
         for layer in map.layers().iter_visible() {
-            self.render_layer(layer, view, texture_view);
+            self.render_layer(layer, view, texture_view, viewport);
         }
     }
 
-    fn render_layer(&self, layer: &dyn Layer, view: &MapView, texture_view: &TextureView) {
+    fn render_layer(
+        &self,
+        layer: &dyn Layer,
+        view: &MapView,
+        texture_view: &TextureView,
+        viewport: Option<Rect>,
+    ) {
         let Some(render_set) = &self.render_set else {
             return;
         };
-        let Some(mut canvas) = WgpuCanvas::new(self, render_set, texture_view, view.clone()) else {
+        let Some(mut canvas) =
+            WgpuCanvas::new(self, render_set, texture_view, view.clone(), viewport)
+        else {
             log::warn!("Layer cannot be rendered to the map view.");
             return;
         };
@@ -639,6 +1258,13 @@ impl WgpuRenderer {
         layer.render(view, &mut canvas);
     }
 
+    /// Returns true if the device of the renderer can run [compute shaders](ComputeShader). Devices of the WebGL and
+    /// GL backends cannot, and the fallbacks of the shaders are run instead.
+    #[cfg(feature = "compute")]
+    pub fn supports_compute(&self) -> bool {
+        compute::supports_compute(&self.device)
+    }
+
     /// Returns the size of the rendering area.
     pub fn size(&self) -> Size {
         let size = match &self.render_set {
@@ -661,6 +1287,10 @@ struct WgpuCanvas<'a> {
     renderer: &'a WgpuRenderer,
     render_set: &'a RenderSet,
     view: &'a TextureView,
+    map_view: MapView,
+    scale_factor: f64,
+    size: Size,
+    viewport: Option<Rect>,
 }
 
 impl<'a> WgpuCanvas<'a> {
@@ -669,6 +1299,7 @@ impl<'a> WgpuCanvas<'a> {
         render_set: &'a RenderSet,
         view: &'a TextureView,
         map_view: MapView,
+        viewport: Option<Rect>,
     ) -> Option<Self> {
         let rotation_mtx = Rotation3::new(Vector3::new(
             map_view.rotation_x(),
@@ -676,18 +1307,32 @@ impl<'a> WgpuCanvas<'a> {
             -map_view.rotation_z(),
         ))
         .to_homogeneous();
+        let target_size = match viewport {
+            Some(viewport) => Size::new(viewport.width(), viewport.height()),
+            None => renderer.size(),
+        };
+        let scale_factor = view_scale_factor(&map_view, target_size.width());
         renderer.queue.write_buffer(
             render_set.pipelines.map_view_buffer(),
             0,
             bytemuck::cast_slice(&[ViewUniform {
                 view_proj: map_view.map_to_scene_mtx()?,
                 view_rotation: rotation_mtx.cast::<f32>().data.0,
+                // Pixel sizes of the primitives are in the pixels of the map view.
                 inv_screen_size: [
-                    1.0 / renderer.size().width() as f32,
-                    1.0 / renderer.size().height() as f32,
+                    (scale_factor / target_size.width()) as f32,
+                    (scale_factor / target_size.height()) as f32,
                 ],
                 resolution: map_view.resolution() as f32,
-                _padding: [0.0; 1],
+                encode_srgb: if needs_srgb_encoding(render_set.render_target.format()) {
+                    1.0
+                } else {
+                    0.0
+                },
+                time: renderer.clock.time(),
+                rotation_z: map_view.rotation_z() as f32,
+                opacity: 1.0,
+                _padding: 0.0,
             }]),
         );
 
@@ -695,13 +1340,21 @@ impl<'a> WgpuCanvas<'a> {
             renderer,
             render_set,
             view,
+            map_view,
+            scale_factor,
+            size: target_size,
+            viewport,
         })
     }
 }
 
 impl<'a> Canvas for WgpuCanvas<'a> {
     fn size(&self) -> Size {
-        self.renderer.size()
+        self.size
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor
     }
 
     fn create_bundle(&self) -> RenderBundle {
@@ -717,6 +1370,68 @@ impl<'a> Canvas for WgpuCanvas<'a> {
     }
 
     fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions) {
+        self.draw(bundles, options, None, None);
+    }
+
+    fn draw_bundles_with_shader(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        shader: &CustomShader,
+    ) {
+        self.draw(bundles, options, Some(shader), None);
+    }
+
+    fn draw_bundles_with_mask(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        mask: &dyn PackedBundle,
+        shader: Option<&CustomShader>,
+    ) {
+        self.draw(bundles, options, shader, Some(mask));
+    }
+
+    #[cfg(feature = "compute")]
+    fn supports_compute(&self) -> bool {
+        self.renderer.supports_compute()
+    }
+
+    #[cfg(feature = "compute")]
+    fn dispatch_compute(
+        &mut self,
+        shader: &ComputeShader,
+        workgroups: [u32; 3],
+    ) -> Result<(), GalileoError> {
+        if !self.renderer.supports_compute() {
+            return shader.run_fallback(workgroups);
+        }
+
+        self.renderer.compute.dispatch(
+            &self.renderer.device,
+            &self.renderer.queue,
+            shader,
+            workgroups,
+        )
+    }
+}
+
+impl<'a> WgpuCanvas<'a> {
+    fn draw(
+        &mut self,
+        bundles: &[&dyn PackedBundle],
+        options: RenderOptions,
+        shader: Option<&CustomShader>,
+        mask: Option<&dyn PackedBundle>,
+    ) {
+        let custom_pipeline = shader.map(|shader| {
+            self.render_set.pipelines.custom_pipeline(
+                &self.renderer.device,
+                &self.renderer.queue,
+                shader,
+            )
+        });
+
         let mut encoder =
             self.renderer
                 .device
@@ -725,14 +1440,17 @@ impl<'a> Canvas for WgpuCanvas<'a> {
                 });
 
         {
-            let (view, resolve_target, depth_view) = if options.antialias {
-                (
-                    &self.render_set.multisampling_view,
+            let (view, resolve_target, depth_view) = match (
+                options.antialias,
+                &self.render_set.multisampling_view,
+                &self.render_set.stencil_view_multisample,
+            ) {
+                (true, Some(multisampling_view), Some(stencil_view_multisample)) => (
+                    multisampling_view,
                     Some(self.view),
-                    &self.render_set.stencil_view_multisample,
-                )
-            } else {
-                (self.view, None, &self.render_set.stencil_view)
+                    stencil_view_multisample,
+                ),
+                _ => (self.view, None, &self.render_set.stencil_view),
             };
 
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -760,15 +1478,51 @@ impl<'a> Canvas for WgpuCanvas<'a> {
                 occlusion_query_set: None,
             });
 
+            if let Some(viewport) = self.viewport {
+                render_pass.set_viewport(
+                    viewport.x_min() as f32,
+                    viewport.y_min() as f32,
+                    viewport.width() as f32,
+                    viewport.height() as f32,
+                    0.0,
+                    1.0,
+                );
+            }
+
+            let clip_level = match mask
+                .and_then(|mask| mask.as_any().downcast_ref::<WgpuPackedBundle>())
+            {
+                Some(mask) => self
+                    .render_set
+                    .pipelines
+                    .apply_mask(&mut render_pass, mask, options),
+                None => 0,
+            };
+
             for bundle in bundles {
                 if let Some(cast) = bundle.as_any().downcast_ref::<WgpuPackedBundle>() {
-                    self.render_set
-                        .pipelines
-                        .render(&mut render_pass, cast, options);
+                    if !cast.is_visible(&self.map_view) {
+                        continue;
+                    }
+
+                    self.render_set.pipelines.render(
+                        &mut render_pass,
+                        cast,
+                        options,
+                        custom_pipeline.as_deref(),
+                        clip_level,
+                    );
                 }
             }
         }
 
+        // Uniform buffer writes are applied before the commands of the next submission, so each draw call gets its own
+        // opacity.
+        self.renderer.queue.write_buffer(
+            self.render_set.pipelines.map_view_buffer(),
+            std::mem::offset_of!(ViewUniform, opacity) as wgpu::BufferAddress,
+            bytemuck::cast_slice(&[options.opacity.clamp(0.0, 1.0)]),
+        );
         self.renderer
             .queue
             .submit(std::iter::once(encoder.finish()));
@@ -780,26 +1534,40 @@ struct WgpuPackedBundle {
     map_ref_buffers: WgpuPolygonBuffers,
     screen_ref_buffers: Option<ScreenRefBuffers>,
     dot_buffers: Option<WgpuDotBuffers>,
+    circle_buffers: Option<WgpuCircleBuffers>,
     image_buffers: Vec<WgpuImage>,
+    image_point_buffers: Option<WgpuImagePointBuffers>,
+    bounds: Option<BundleBounds>,
 }
 
 struct WgpuPolygonBuffers {
-    vertex: Buffer,
-    index: Buffer,
+    vertex: PooledBuffer,
+    index: PooledBuffer,
     index_count: u32,
 }
 
 struct ScreenRefBuffers {
-    vertex: Buffer,
-    index: Buffer,
+    vertex: PooledBuffer,
+    index: PooledBuffer,
     index_count: u32,
 }
 
 struct WgpuDotBuffers {
-    buffer: Buffer,
+    buffer: PooledBuffer,
     point_count: u32,
 }
 
+struct WgpuCircleBuffers {
+    buffer: PooledBuffer,
+    circle_count: u32,
+}
+
+struct WgpuImagePointBuffers {
+    buffer: PooledBuffer,
+    /// Runs of consecutive instances using the same texture. Each run is drawn with one draw call.
+    batches: Vec<(Arc<BindGroup>, Range<u32>)>,
+}
+
 impl WgpuPackedBundle {
     fn new(
         bundle: &TessellatingRenderBundle,
@@ -809,8 +1577,10 @@ impl WgpuPackedBundle {
         let TessellatingRenderBundle {
             poly_tessellation,
             points,
+            circles,
             screen_ref,
             images,
+            image_points,
             clip_area,
             image_store,
             ..
@@ -823,21 +1593,16 @@ impl WgpuPackedBundle {
         let poly_buffers = Self::write_poly_buffers(poly_tessellation, renderer);
 
         let screen_ref_buffers = if !screen_ref.vertices.is_empty() {
-            let index = renderer
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    contents: bytemuck::cast_slice(&screen_ref.indices),
-                    usage: wgpu::BufferUsages::INDEX,
-                });
-
-            let vertex = renderer
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: None,
-                    usage: wgpu::BufferUsages::VERTEX,
-                    contents: bytemuck::cast_slice(&screen_ref.vertices),
-                });
+            let index = renderer.index_pool.allocate(
+                &renderer.device,
+                &renderer.queue,
+                bytemuck::cast_slice(&screen_ref.indices),
+            );
+            let vertex = renderer.vertex_pool.allocate(
+                &renderer.device,
+                &renderer.queue,
+                bytemuck::cast_slice(&screen_ref.vertices),
+            );
 
             Some(ScreenRefBuffers {
                 index,
@@ -851,14 +1616,11 @@ impl WgpuPackedBundle {
         let dot_buffers = if points.is_empty() {
             None
         } else {
-            let point_instance_buffer =
-                renderer
-                    .device
-                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: None,
-                        usage: wgpu::BufferUsages::VERTEX,
-                        contents: bytemuck::cast_slice(points),
-                    });
+            let point_instance_buffer = renderer.vertex_pool.allocate(
+                &renderer.device,
+                &renderer.queue,
+                bytemuck::cast_slice(points),
+            );
             let count = points.len();
             Some(WgpuDotBuffers {
                 buffer: point_instance_buffer,
@@ -866,6 +1628,20 @@ impl WgpuPackedBundle {
             })
         };
 
+        let circle_buffers = if circles.is_empty() {
+            None
+        } else {
+            let buffer = renderer.vertex_pool.allocate(
+                &renderer.device,
+                &renderer.queue,
+                bytemuck::cast_slice(circles),
+            );
+            Some(WgpuCircleBuffers {
+                buffer,
+                circle_count: circles.len() as u32,
+            })
+        };
+
         let textures: Vec<_> = image_store
             .iter()
             .map(|decoded_image| {
@@ -887,15 +1663,45 @@ impl WgpuPackedBundle {
             image_buffers.push(image);
         }
 
+        let image_point_buffers = if image_points.is_empty() {
+            None
+        } else {
+            let buffer = renderer.vertex_pool.allocate(
+                &renderer.device,
+                &renderer.queue,
+                bytemuck::cast_slice(image_points),
+            );
+
+            // Instances are not reordered, so that overlapping images are drawn in the order they were added.
+            let mut batches = vec![];
+            let mut start = 0;
+            for run in image_points.chunk_by(|a, b| a.texture_index == b.texture_index) {
+                let end = start + run.len() as u32;
+                batches.push((textures[run[0].texture_index as usize].clone(), start..end));
+                start = end;
+            }
+
+            Some(WgpuImagePointBuffers { buffer, batches })
+        };
+
         Self {
             clip_area_buffers,
             map_ref_buffers: poly_buffers,
             image_buffers,
+            image_point_buffers,
             screen_ref_buffers,
             dot_buffers,
+            circle_buffers,
+            bounds: bundle.bounds(),
         }
     }
 
+    fn is_visible(&self, view: &MapView) -> bool {
+        self.bounds
+            .map(|bounds| bounds.is_visible(view))
+            .unwrap_or(true)
+    }
+
     fn write_poly_buffers(
         tessellation: &VertexBuffers<PolyVertex, u32>,
         renderer: &WgpuRenderer,
@@ -904,20 +1710,11 @@ impl WgpuPackedBundle {
         let bytes = bytemuck::cast_slice(&tessellation.vertices);
 
         let index = renderer
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents: index_bytes,
-                usage: wgpu::BufferUsages::INDEX,
-            });
-
+            .index_pool
+            .allocate(&renderer.device, &renderer.queue, index_bytes);
         let vertex = renderer
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-                contents: bytes,
-            });
+            .vertex_pool
+            .allocate(&renderer.device, &renderer.queue, bytes);
 
         WgpuPolygonBuffers {
             index,
@@ -940,7 +1737,11 @@ struct ViewUniform {
     view_rotation: [[f32; 4]; 4],
     inv_screen_size: [f32; 2],
     resolution: f32,
-    _padding: [f32; 1],
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
+    _padding: f32,
 }
 
 impl PointInstance {
@@ -991,6 +1792,44 @@ impl PolyVertex {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Float32,
                 },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 4]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<f32>()) as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 4]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<f32>()
+                        + size_of::<[f32; 2]>()) as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 4]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<f32>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<[f32; 4]>()) as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>()
+                        + size_of::<[f32; 4]>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<f32>()
+                        + size_of::<[f32; 2]>()
+                        + size_of::<[f32; 4]>() * 2)
+                        as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
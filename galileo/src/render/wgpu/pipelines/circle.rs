@@ -0,0 +1,116 @@
+use crate::render::render_bundle::tessellating::CircleInstance;
+use crate::render::wgpu::pipelines;
+use crate::render::wgpu::pipelines::{default_pipeline_descriptor, default_targets};
+use crate::render::wgpu::WgpuCircleBuffers;
+use crate::render::RenderOptions;
+use std::mem::size_of;
+use wgpu::{BindGroupLayout, Device, RenderPass, RenderPipeline, TextureFormat};
+
+/// Number of vertices in the quad drawn for every circle instance.
+const QUAD_VERTEX_COUNT: u32 = 6;
+
+pub struct CirclePipeline {
+    wgpu_pipeline: RenderPipeline,
+    wgpu_pipeline_antialias: RenderPipeline,
+}
+
+impl CirclePipeline {
+    pub fn create(
+        device: &Device,
+        format: TextureFormat,
+        map_view_layout: &BindGroupLayout,
+        sample_count: u32,
+    ) -> Self {
+        let buffers = [CircleInstance::wgpu_desc()];
+        let shader = pipelines::create_shader_module(
+            device,
+            "circle",
+            include_str!("./shaders/circle.wgsl"),
+        );
+
+        let targets = default_targets(format);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[map_view_layout],
+            push_constant_ranges: &[],
+        });
+
+        let wgpu_pipeline = device.create_render_pipeline(&default_pipeline_descriptor(
+            &layout, &shader, &targets, &buffers, 1,
+        ));
+        let wgpu_pipeline_antialias = device.create_render_pipeline(&default_pipeline_descriptor(
+            &layout,
+            &shader,
+            &targets,
+            &buffers,
+            sample_count,
+        ));
+
+        Self {
+            wgpu_pipeline,
+            wgpu_pipeline_antialias,
+        }
+    }
+
+    pub fn render<'a>(
+        &'a self,
+        buffers: &'a WgpuCircleBuffers,
+        render_pass: &mut RenderPass<'a>,
+        render_options: RenderOptions,
+    ) {
+        if render_options.antialias {
+            render_pass.set_pipeline(&self.wgpu_pipeline_antialias);
+        } else {
+            render_pass.set_pipeline(&self.wgpu_pipeline);
+        }
+
+        render_pass.set_vertex_buffer(0, buffers.buffer.slice());
+        render_pass.draw(0..QUAD_VERTEX_COUNT, 0..buffers.circle_count);
+    }
+}
+
+impl CircleInstance {
+    fn wgpu_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<CircleInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 5]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 7]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Uint8x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 7]>() + size_of::<[u8; 4]>()) as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Uint8x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 7]>() + size_of::<[u8; 8]>()) as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Uint8x4,
+                },
+            ],
+        }
+    }
+}
@@ -1,35 +1,43 @@
 use crate::render::render_bundle::tessellating::PolyVertex;
+use crate::render::wgpu::pipelines;
 use crate::render::wgpu::pipelines::{default_pipeline_descriptor, default_targets};
 use crate::render::wgpu::{WgpuPolygonBuffers, DEPTH_FORMAT};
 use crate::render::RenderOptions;
 use wgpu::{
-    BindGroupLayout, CompareFunction, DepthStencilState, Device, RenderPass, RenderPipeline,
-    RenderPipelineDescriptor, StencilFaceState, StencilOperation, StencilState, TextureFormat,
+    BindGroupLayout, ColorWrites, CompareFunction, DepthStencilState, Device, RenderPass,
+    RenderPipeline, RenderPipelineDescriptor, StencilFaceState, StencilOperation, StencilState,
+    TextureFormat,
 };
 
+/// Restricts drawing to the area of a polygon using the stencil buffer.
+///
+/// The stencil value of a pixel is the number of clip areas the pixel is inside of. Clipping increments the value
+/// inside the clip area for the pixels that are inside all the current clip areas, so nested clip areas are
+/// intersected. Primitives are drawn only where the stencil value equals the current clip level.
 pub struct ClipPipeline {
+    clip: StencilPipelines,
+    unclip: StencilPipelines,
+}
+
+struct StencilPipelines {
     wgpu_pipeline: RenderPipeline,
     wgpu_pipeline_antialias: RenderPipeline,
 }
 
 impl ClipPipeline {
-    const UNCLIP_REFERENCE: u32 = 0;
-    const CLIP_STENCIL_VALUE: u32 = 1;
-
     pub fn create(
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
     ) -> Self {
         let buffers = [PolyVertex::wgpu_desc()];
-        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/map_ref.wgsl"));
+        let shader = pipelines::create_shader_module(
+            device,
+            "map_ref",
+            include_str!("./shaders/map_ref.wgsl"),
+        );
 
-        let clip_stencil_state = StencilFaceState {
-            compare: CompareFunction::Never,
-            fail_op: StencilOperation::Replace,
-            depth_fail_op: StencilOperation::Keep,
-            pass_op: StencilOperation::Keep,
-        };
         let targets = default_targets(format);
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
@@ -37,57 +45,89 @@ impl ClipPipeline {
             push_constant_ranges: &[],
         });
 
-        let depth_stencil = Some(DepthStencilState {
-            format: DEPTH_FORMAT,
-            depth_write_enabled: false,
-            depth_compare: CompareFunction::Always,
-            stencil: StencilState {
-                front: clip_stencil_state,
-                back: clip_stencil_state,
-                read_mask: 0xff,
-                write_mask: 0xff,
-            },
-            bias: Default::default(),
-        });
+        let create_pipelines = |pass_op| {
+            let stencil_state = StencilFaceState {
+                compare: CompareFunction::Equal,
+                fail_op: StencilOperation::Keep,
+                depth_fail_op: StencilOperation::Keep,
+                pass_op,
+            };
+            let depth_stencil = Some(DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Always,
+                stencil: StencilState {
+                    front: stencil_state,
+                    back: stencil_state,
+                    read_mask: 0xff,
+                    write_mask: 0xff,
+                },
+                bias: Default::default(),
+            });
 
-        let wgpu_pipeline_antialias = device.create_render_pipeline(&RenderPipelineDescriptor {
-            depth_stencil: depth_stencil.clone(),
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, true)
-        });
-        let wgpu_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
-            depth_stencil,
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
-        });
+            // Clip areas only change the stencil buffer.
+            let mut targets = targets.clone();
+            for target in targets.iter_mut().flatten() {
+                target.write_mask = ColorWrites::empty();
+            }
+
+            StencilPipelines {
+                wgpu_pipeline_antialias: device.create_render_pipeline(&RenderPipelineDescriptor {
+                    depth_stencil: depth_stencil.clone(),
+                    ..default_pipeline_descriptor(
+                        &layout,
+                        &shader,
+                        &targets,
+                        &buffers,
+                        sample_count,
+                    )
+                }),
+                wgpu_pipeline: device.create_render_pipeline(&RenderPipelineDescriptor {
+                    depth_stencil,
+                    ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1)
+                }),
+            }
+        };
 
         Self {
-            wgpu_pipeline,
-            wgpu_pipeline_antialias,
+            clip: create_pipelines(StencilOperation::IncrementClamp),
+            unclip: create_pipelines(StencilOperation::DecrementClamp),
         }
     }
 
+    /// Restricts the following drawing to the intersection of the clip area and the current clip level, and returns
+    /// the new clip level.
     pub fn clip<'a>(
         &'a self,
         buffers: &'a WgpuPolygonBuffers,
         render_pass: &mut RenderPass<'a>,
         render_options: RenderOptions,
-    ) {
-        self.render(
-            buffers,
-            render_pass,
-            Self::CLIP_STENCIL_VALUE,
-            render_options,
-        );
+        level: u32,
+    ) -> u32 {
+        self.clip
+            .render(buffers, render_pass, level, render_options);
+        render_pass.set_stencil_reference(level + 1);
+
+        level + 1
     }
 
+    /// Reverts the [`ClipPipeline::clip`] call with the same clip area, and returns the previous clip level.
     pub fn unclip<'a>(
         &'a self,
         buffers: &'a WgpuPolygonBuffers,
         render_pass: &mut RenderPass<'a>,
         render_options: RenderOptions,
-    ) {
-        self.render(buffers, render_pass, Self::UNCLIP_REFERENCE, render_options);
+        level: u32,
+    ) -> u32 {
+        self.unclip
+            .render(buffers, render_pass, level, render_options);
+        render_pass.set_stencil_reference(level - 1);
+
+        level - 1
     }
+}
 
+impl StencilPipelines {
     fn render<'a>(
         &'a self,
         buffers: &'a WgpuPolygonBuffers,
@@ -102,8 +142,8 @@ impl ClipPipeline {
         }
 
         render_pass.set_stencil_reference(stencil_reference);
-        render_pass.set_vertex_buffer(0, buffers.vertex.slice(..));
-        render_pass.set_index_buffer(buffers.index.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.set_vertex_buffer(0, buffers.vertex.slice());
+        render_pass.set_index_buffer(buffers.index.slice(), wgpu::IndexFormat::Uint32);
         render_pass.draw_indexed(0..buffers.index_count, 0, 0..1);
     }
 }
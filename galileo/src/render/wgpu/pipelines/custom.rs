@@ -0,0 +1,129 @@
+use crate::render::render_bundle::tessellating::PolyVertex;
+use crate::render::wgpu::pipelines::default_targets;
+use crate::render::wgpu::{pipelines, WgpuPolygonBuffers};
+use crate::render::{CustomShader, RenderOptions};
+use wgpu::{
+    BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPass, RenderPipeline, TextureFormat,
+};
+
+/// Uniform buffers are padded to this size, as required by some backends.
+const UNIFORM_ALIGNMENT: usize = 16;
+
+/// Pipeline for map-referenced primitives that uses a [`CustomShader`] instead of the default one.
+pub struct CustomPipeline {
+    wgpu_pipeline: RenderPipeline,
+    wgpu_pipeline_antialias: RenderPipeline,
+    uniform: Option<(Buffer, BindGroup)>,
+}
+
+impl CustomPipeline {
+    pub fn create(
+        device: &Device,
+        format: TextureFormat,
+        map_view_layout: &BindGroupLayout,
+        sample_count: u32,
+        custom_shader: &CustomShader,
+    ) -> Self {
+        let buffers = [PolyVertex::wgpu_desc()];
+        let shader =
+            pipelines::create_shader_module(device, "Custom shader", custom_shader.source());
+
+        let uniform_layout = custom_shader.uniform_data().map(|_| {
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("Custom shader uniform layout"),
+            })
+        });
+
+        let uniform = custom_shader
+            .uniform_data()
+            .zip(uniform_layout.as_ref())
+            .map(|(data, layout)| {
+                let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Custom shader uniform buffer"),
+                    size: padded_size(data.len()) as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                    label: Some("Custom shader uniform bind group"),
+                });
+
+                (buffer, bind_group)
+            });
+
+        let mut bind_group_layouts = vec![map_view_layout];
+        if let Some(uniform_layout) = &uniform_layout {
+            bind_group_layouts.push(uniform_layout);
+        }
+
+        let targets = default_targets(format);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+        let mut desc =
+            pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1);
+        let wgpu_pipeline = device.create_render_pipeline(&desc);
+
+        desc.multisample.count = sample_count;
+        let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
+
+        Self {
+            wgpu_pipeline,
+            wgpu_pipeline_antialias,
+            uniform,
+        }
+    }
+
+    /// Copies the current contents of the shader uniform to the GPU buffer.
+    pub fn write_uniform(&self, queue: &Queue, custom_shader: &CustomShader) {
+        let (Some((buffer, _)), Some(mut data)) = (&self.uniform, custom_shader.uniform_data())
+        else {
+            return;
+        };
+
+        data.resize(padded_size(data.len()), 0);
+        queue.write_buffer(buffer, 0, &data);
+    }
+
+    pub fn render<'a>(
+        &'a self,
+        buffers: &'a WgpuPolygonBuffers,
+        render_pass: &mut RenderPass<'a>,
+        render_options: RenderOptions,
+    ) {
+        if render_options.antialias {
+            render_pass.set_pipeline(&self.wgpu_pipeline_antialias);
+        } else {
+            render_pass.set_pipeline(&self.wgpu_pipeline);
+        }
+
+        if let Some((_, bind_group)) = &self.uniform {
+            render_pass.set_bind_group(1, bind_group, &[]);
+        }
+
+        render_pass.set_vertex_buffer(0, buffers.vertex.slice());
+        render_pass.set_index_buffer(buffers.index.slice(), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..buffers.index_count, 0, 0..1);
+    }
+}
+
+fn padded_size(size: usize) -> usize {
+    size.max(1).div_ceil(UNIFORM_ALIGNMENT) * UNIFORM_ALIGNMENT
+}
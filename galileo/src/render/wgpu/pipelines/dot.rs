@@ -1,4 +1,5 @@
 use crate::render::render_bundle::tessellating::PointInstance;
+use crate::render::wgpu::pipelines;
 use crate::render::wgpu::pipelines::{default_pipeline_descriptor, default_targets};
 use crate::render::wgpu::{WgpuDotBuffers, DEPTH_FORMAT};
 use crate::render::RenderOptions;
@@ -18,12 +19,14 @@ impl DotPipeline {
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
     ) -> Self {
         let mut desc = PointInstance::wgpu_desc();
         desc.step_mode = VertexStepMode::Vertex;
 
         let buffers = [desc];
-        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/dot.wgsl"));
+        let shader =
+            pipelines::create_shader_module(device, "dot", include_str!("./shaders/dot.wgsl"));
 
         let targets = default_targets(format);
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -62,12 +65,12 @@ impl DotPipeline {
         let wgpu_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             primitive,
             depth_stencil: depth_stencil.clone(),
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
+            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1)
         });
         let wgpu_pipeline_antialias = device.create_render_pipeline(&RenderPipelineDescriptor {
             primitive,
             depth_stencil,
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, true)
+            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, sample_count)
         });
         Self {
             wgpu_pipeline,
@@ -87,7 +90,7 @@ impl DotPipeline {
             render_pass.set_pipeline(&self.wgpu_pipeline);
         }
 
-        render_pass.set_vertex_buffer(0, buffers.buffer.slice(..));
+        render_pass.set_vertex_buffer(0, buffers.buffer.slice());
         render_pass.draw(0..buffers.point_count, 0..1);
     }
 }
@@ -1,8 +1,10 @@
 use crate::decoded_image::DecodedImage;
-use crate::render::render_bundle::tessellating::ImageVertex;
+use crate::render::render_bundle::tessellating::{ImageInstance, ImageVertex};
 use crate::render::wgpu::pipelines;
 use crate::render::wgpu::pipelines::default_targets;
+use crate::render::wgpu::WgpuImagePointBuffers;
 use crate::render::RenderOptions;
+use std::mem::size_of;
 use std::sync::Arc;
 use wgpu::util::{DeviceExt, TextureDataOrder};
 use wgpu::{
@@ -12,6 +14,9 @@ use wgpu::{
 
 const INDICES: &[u16] = &[1, 0, 2, 1, 2, 3];
 
+/// Number of vertices in the quad drawn for every image point instance.
+const QUAD_VERTEX_COUNT: u32 = 6;
+
 pub struct WgpuImage {
     pub texture_bind_group: Arc<BindGroup>,
     pub vertex_buffer: wgpu::Buffer,
@@ -22,6 +27,8 @@ pub struct ImagePipeline {
     index_buffer: wgpu::Buffer,
     texture_bind_group_layout: BindGroupLayout,
     pub wgpu_pipeline_antialias: RenderPipeline,
+    points_pipeline: RenderPipeline,
+    points_pipeline_antialias: RenderPipeline,
 }
 
 impl ImagePipeline {
@@ -29,8 +36,10 @@ impl ImagePipeline {
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
     ) -> Self {
-        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/image.wgsl"));
+        let shader =
+            pipelines::create_shader_module(device, "image", include_str!("./shaders/image.wgsl"));
 
         let texture_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -65,13 +74,30 @@ impl ImagePipeline {
         let targets = default_targets(format);
 
         let mut desc = RenderPipelineDescriptor {
-            ..pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
+            ..pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1)
         };
 
         let wgpu_pipeline = device.create_render_pipeline(&desc);
-        desc.multisample.count = 4;
+        desc.multisample.count = sample_count;
         let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
 
+        let points_shader = pipelines::create_shader_module(
+            device,
+            "image_point",
+            include_str!("./shaders/image_point.wgsl"),
+        );
+        let points_buffers = [ImageInstance::wgpu_desc()];
+        let mut points_desc = pipelines::default_pipeline_descriptor(
+            &layout,
+            &points_shader,
+            &targets,
+            &points_buffers,
+            1,
+        );
+        let points_pipeline = device.create_render_pipeline(&points_desc);
+        points_desc.multisample.count = sample_count;
+        let points_pipeline_antialias = device.create_render_pipeline(&points_desc);
+
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Image index buffer"),
             contents: bytemuck::cast_slice(INDICES),
@@ -83,6 +109,8 @@ impl ImagePipeline {
             wgpu_pipeline_antialias,
             texture_bind_group_layout,
             index_buffer,
+            points_pipeline,
+            points_pipeline_antialias,
         }
     }
 
@@ -179,6 +207,67 @@ impl ImagePipeline {
         render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
         render_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..1);
     }
+
+    /// Draws image point symbols, one draw call per run of instances using the same texture.
+    pub fn render_points<'a>(
+        &'a self,
+        buffers: &'a WgpuImagePointBuffers,
+        render_pass: &mut RenderPass<'a>,
+        render_options: RenderOptions,
+    ) {
+        if render_options.antialias {
+            render_pass.set_pipeline(&self.points_pipeline_antialias);
+        } else {
+            render_pass.set_pipeline(&self.points_pipeline);
+        }
+
+        render_pass.set_vertex_buffer(0, buffers.buffer.slice());
+        for (texture, instances) in &buffers.batches {
+            render_pass.set_bind_group(1, texture, &[]);
+            render_pass.draw(0..QUAD_VERTEX_COUNT, instances.clone());
+        }
+    }
+}
+
+impl ImageInstance {
+    fn wgpu_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<ImageInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 9]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32,
+                },
+            ],
+        }
+    }
 }
 
 impl ImageVertex {
@@ -211,6 +300,25 @@ impl ImageVertex {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Float32x2,
                 },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<f32>()
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 2]>())
+                        as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<f32>()
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<f32>())
+                        as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
             ],
         }
     }
@@ -14,9 +14,22 @@ impl MapRefPipeline {
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
     ) -> Self {
         let buffers = [PolyVertex::wgpu_desc()];
-        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/map_ref.wgsl"));
+        let shader = pipelines::create_shader_module(
+            device,
+            "map_ref",
+            include_str!("./shaders/map_ref.wgsl"),
+        );
+        // Without multisampling, the edges of the lines are smoothed by the shader.
+        let smooth_shader = (sample_count == 1).then(|| {
+            pipelines::create_shader_module(
+                device,
+                "map_ref_smooth",
+                include_str!("./shaders/map_ref_smooth.wgsl"),
+            )
+        });
 
         let targets = default_targets(format);
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -25,10 +38,17 @@ impl MapRefPipeline {
             push_constant_ranges: &[],
         });
         let mut desc =
-            pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false);
+            pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1);
         let wgpu_pipeline = device.create_render_pipeline(&desc);
 
-        desc.multisample.count = 4;
+        if let Some(smooth_shader) = &smooth_shader {
+            desc.vertex.module = smooth_shader;
+            if let Some(fragment) = &mut desc.fragment {
+                fragment.module = smooth_shader;
+            }
+        }
+
+        desc.multisample.count = sample_count;
         let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
 
         Self {
@@ -48,8 +68,8 @@ impl MapRefPipeline {
         } else {
             render_pass.set_pipeline(&self.wgpu_pipeline);
         }
-        render_pass.set_vertex_buffer(0, buffers.vertex.slice(..));
-        render_pass.set_index_buffer(buffers.index.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.set_vertex_buffer(0, buffers.vertex.slice());
+        render_pass.set_index_buffer(buffers.index.slice(), wgpu::IndexFormat::Uint32);
         render_pass.draw_indexed(0..buffers.index_count, 0, 0..1);
     }
 }
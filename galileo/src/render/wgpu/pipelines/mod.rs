@@ -1,18 +1,24 @@
+use crate::render::wgpu::pipelines::circle::CirclePipeline;
 use crate::render::wgpu::pipelines::clip::ClipPipeline;
+use crate::render::wgpu::pipelines::custom::CustomPipeline;
 use crate::render::wgpu::pipelines::dot::DotPipeline;
 use crate::render::wgpu::pipelines::image::ImagePipeline;
 use crate::render::wgpu::pipelines::map_ref::MapRefPipeline;
 use crate::render::wgpu::pipelines::screen_ref::ScreenRefPipeline;
 use crate::render::wgpu::{ViewUniform, WgpuPackedBundle, DEPTH_FORMAT};
-use crate::render::RenderOptions;
+use crate::render::{CustomShader, RenderOptions};
+use std::collections::HashMap;
 use std::mem::size_of;
+use std::sync::{Arc, Mutex};
 use wgpu::{
-    BindGroup, Buffer, CompareFunction, DepthStencilState, Device, PipelineLayout, RenderPass,
-    RenderPipelineDescriptor, ShaderModule, StencilFaceState, StencilOperation, StencilState,
-    TextureFormat, VertexBufferLayout,
+    BindGroup, BindGroupLayout, Buffer, CompareFunction, DepthStencilState, Device, PipelineLayout,
+    Queue, RenderPass, RenderPipelineDescriptor, ShaderModule, StencilFaceState, StencilOperation,
+    StencilState, TextureFormat, VertexBufferLayout,
 };
 
+mod circle;
 mod clip;
+mod custom;
 mod dot;
 pub mod image;
 mod map_ref;
@@ -21,16 +27,24 @@ mod screen_ref;
 pub struct Pipelines {
     map_view_binding: BindGroup,
     map_view_buffer: Buffer,
+    map_view_layout: BindGroupLayout,
+    format: TextureFormat,
+    sample_count: u32,
 
     image: ImagePipeline,
     screen_ref: ScreenRefPipeline,
     map_ref: MapRefPipeline,
     clip: ClipPipeline,
     dot: DotPipeline,
+    circle: CirclePipeline,
+
+    custom: Mutex<HashMap<u64, Arc<CustomPipeline>>>,
 }
 
 impl Pipelines {
-    pub fn create(device: &Device, format: TextureFormat) -> Self {
+    /// Creates the pipelines. Antialiasing variants of the pipelines are created with the given number of samples
+    /// per pixel. If it is 1, map-referenced primitives are smoothed by the shader instead.
+    pub fn create(device: &Device, format: TextureFormat, sample_count: u32) -> Self {
         let map_view_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Map view buffer"),
             size: size_of::<ViewUniform>() as wgpu::BufferAddress,
@@ -42,7 +56,7 @@ impl Pipelines {
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -65,33 +79,108 @@ impl Pipelines {
         Self {
             map_view_binding,
             map_view_buffer,
-            image: ImagePipeline::create(device, format, &map_view_bind_group_layout),
-            map_ref: MapRefPipeline::create(device, format, &map_view_bind_group_layout),
-            screen_ref: ScreenRefPipeline::create(device, format, &map_view_bind_group_layout),
-            clip: ClipPipeline::create(device, format, &map_view_bind_group_layout),
-            dot: DotPipeline::create(device, format, &map_view_bind_group_layout),
+            image: ImagePipeline::create(device, format, &map_view_bind_group_layout, sample_count),
+            map_ref: MapRefPipeline::create(
+                device,
+                format,
+                &map_view_bind_group_layout,
+                sample_count,
+            ),
+            screen_ref: ScreenRefPipeline::create(
+                device,
+                format,
+                &map_view_bind_group_layout,
+                sample_count,
+            ),
+            clip: ClipPipeline::create(device, format, &map_view_bind_group_layout, sample_count),
+            dot: DotPipeline::create(device, format, &map_view_bind_group_layout, sample_count),
+            circle: CirclePipeline::create(
+                device,
+                format,
+                &map_view_bind_group_layout,
+                sample_count,
+            ),
+            map_view_layout: map_view_bind_group_layout,
+            format,
+            sample_count,
+            custom: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Returns the pipeline for the custom shader, creating it on the first use, and updates its uniform buffer.
+    pub fn custom_pipeline(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        shader: &CustomShader,
+    ) -> Arc<CustomPipeline> {
+        let pipeline = self
+            .custom
+            .lock()
+            .expect("mutex is poisoned")
+            .entry(shader.id())
+            .or_insert_with(|| {
+                Arc::new(CustomPipeline::create(
+                    device,
+                    self.format,
+                    &self.map_view_layout,
+                    self.sample_count,
+                    shader,
+                ))
+            })
+            .clone();
+        pipeline.write_uniform(queue, shader);
+
+        pipeline
+    }
+
+    /// Restricts all the following drawing in the render pass to the area of the map-referenced primitives of the
+    /// `mask` bundle. Returns the clip level that must be given to [`Pipelines::render`] after that.
+    pub fn apply_mask<'a>(
+        &'a self,
+        render_pass: &mut RenderPass<'a>,
+        mask: &'a WgpuPackedBundle,
+        render_options: RenderOptions,
+    ) -> u32 {
+        self.set_bindings(render_pass);
+        self.clip
+            .clip(&mask.map_ref_buffers, render_pass, render_options, 0)
+    }
+
+    /// Renders the bundle. The `clip_level` is the number of clip areas applied to the render pass (`0` if there
+    /// are none).
     pub fn render<'a>(
         &'a self,
         render_pass: &mut RenderPass<'a>,
         bundle: &'a WgpuPackedBundle,
         render_options: RenderOptions,
+        custom_map_ref: Option<&'a CustomPipeline>,
+        clip_level: u32,
     ) {
         self.set_bindings(render_pass);
+        render_pass.set_stencil_reference(clip_level);
 
         if let Some(clip) = &bundle.clip_area_buffers {
-            self.clip.clip(clip, render_pass, render_options);
+            self.clip
+                .clip(clip, render_pass, render_options, clip_level);
         }
 
         for image in &bundle.image_buffers {
             self.image.render(image, render_pass, render_options);
         }
 
+        if let Some(image_point_buffers) = &bundle.image_point_buffers {
+            self.image
+                .render_points(image_point_buffers, render_pass, render_options);
+        }
+
         if bundle.map_ref_buffers.index_count > 0 {
-            self.map_ref
-                .render(&bundle.map_ref_buffers, render_pass, render_options);
+            match custom_map_ref {
+                Some(custom) => custom.render(&bundle.map_ref_buffers, render_pass, render_options),
+                None => self
+                    .map_ref
+                    .render(&bundle.map_ref_buffers, render_pass, render_options),
+            }
         }
 
         if let Some(screen_ref_buffers) = &bundle.screen_ref_buffers {
@@ -99,12 +188,18 @@ impl Pipelines {
                 .render(screen_ref_buffers, render_pass, render_options);
         }
 
+        if let Some(circle_buffers) = &bundle.circle_buffers {
+            self.circle
+                .render(circle_buffers, render_pass, render_options);
+        }
+
         if let Some(dot_buffers) = &bundle.dot_buffers {
             self.dot.render(dot_buffers, render_pass, render_options);
         }
 
         if let Some(clip) = &bundle.clip_area_buffers {
-            self.clip.unclip(clip, render_pass, render_options);
+            self.clip
+                .unclip(clip, render_pass, render_options, clip_level + 1);
         }
     }
 
@@ -121,6 +216,27 @@ impl Pipelines {
     }
 }
 
+/// Color space conversion functions available to all the shaders.
+pub const COLOR_FUNCTIONS: &str = include_str!("./shaders/color.wgsl");
+
+/// Line pattern functions available to all the shaders.
+pub const LINE_PATTERN_FUNCTIONS: &str = include_str!("./shaders/line_pattern.wgsl");
+
+/// Zoom interpolation functions available to all the shaders.
+pub const ZOOM_FUNCTIONS: &str = include_str!("./shaders/zoom.wgsl");
+
+/// Creates a shader module from WGSL source, prepended with the [`COLOR_FUNCTIONS`],
+/// [`LINE_PATTERN_FUNCTIONS`] and [`ZOOM_FUNCTIONS`].
+fn create_shader_module(device: &Device, label: &str, source: &str) -> ShaderModule {
+    device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(
+            format!("{COLOR_FUNCTIONS}\n{LINE_PATTERN_FUNCTIONS}\n{ZOOM_FUNCTIONS}\n{source}")
+                .into(),
+        ),
+    })
+}
+
 fn default_targets(format: TextureFormat) -> [Option<wgpu::ColorTargetState>; 1] {
     [Some(wgpu::ColorTargetState {
         format,
@@ -134,7 +250,7 @@ fn default_pipeline_descriptor<'a>(
     shader: &'a ShaderModule,
     targets: &'a [Option<wgpu::ColorTargetState>],
     buffers: &'a [VertexBufferLayout<'a>],
-    antialias: bool,
+    sample_count: u32,
 ) -> RenderPipelineDescriptor<'a> {
     let stencil_state = StencilFaceState {
         compare: CompareFunction::Equal,
@@ -178,7 +294,7 @@ fn default_pipeline_descriptor<'a>(
             bias: Default::default(),
         }),
         multisample: wgpu::MultisampleState {
-            count: if antialias { 4 } else { 1 },
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -1,4 +1,5 @@
 use crate::render::render_bundle::tessellating::ScreenRefVertex;
+use crate::render::wgpu::pipelines;
 use crate::render::wgpu::pipelines::{default_pipeline_descriptor, default_targets};
 use crate::render::wgpu::{ScreenRefBuffers, DEPTH_FORMAT};
 use crate::render::RenderOptions;
@@ -18,9 +19,14 @@ impl ScreenRefPipeline {
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
     ) -> Self {
         let buffers = [ScreenRefVertex::wgpu_desc()];
-        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/screen_ref.wgsl"));
+        let shader = pipelines::create_shader_module(
+            device,
+            "screen_ref",
+            include_str!("./shaders/screen_ref.wgsl"),
+        );
 
         let targets = default_targets(format);
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -47,12 +53,12 @@ impl ScreenRefPipeline {
                 },
                 bias: Default::default(),
             }),
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
+            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1)
         };
 
         let wgpu_pipeline = device.create_render_pipeline(&desc);
 
-        desc.multisample.count = 4;
+        desc.multisample.count = sample_count;
         let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
 
         Self {
@@ -72,8 +78,8 @@ impl ScreenRefPipeline {
         } else {
             render_pass.set_pipeline(&self.wgpu_pipeline);
         }
-        render_pass.set_vertex_buffer(0, buffers.vertex.slice(..));
-        render_pass.set_index_buffer(buffers.index.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.set_vertex_buffer(0, buffers.vertex.slice());
+        render_pass.set_index_buffer(buffers.index.slice(), wgpu::IndexFormat::Uint32);
         render_pass.draw_indexed(0..buffers.index_count, 0, 0..1);
     }
 }
@@ -99,6 +105,12 @@ impl ScreenRefVertex {
                     shader_location: 2,
                     format: wgpu::VertexFormat::Uint8x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>() + size_of::<[f32; 2]>() + size_of::<[u8; 4]>())
+                        as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
             ],
         }
     }
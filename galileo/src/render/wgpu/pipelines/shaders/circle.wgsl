@@ -0,0 +1,94 @@
+// Vertex shader
+
+struct ViewUniform {
+    view_proj: mat4x4<f32>,
+    view_rotation: mat4x4<f32>,
+    inv_screen_size: vec2<f32>,
+    resolution: f32,
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> transform: ViewUniform;
+
+struct InstanceInput {
+    @location(0) position: vec3<f32>,
+    @location(1) offset: vec2<f32>,
+    @location(2) radius: f32,
+    @location(3) outline_width: f32,
+    @location(4) center_color: vec4<u32>,
+    @location(5) side_color: vec4<u32>,
+    @location(6) outline_color: vec4<u32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local_position: vec2<f32>,
+    @location(1) center_color: vec4<f32>,
+    @location(2) side_color: vec4<f32>,
+    @location(3) outline_color: vec4<f32>,
+    @location(4) radius: f32,
+    @location(5) outline_width: f32,
+};
+
+// Two triangles covering a unit square.
+var<private> CORNERS: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, -1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, 1.0),
+);
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    // One extra pixel for the smoothed edge.
+    let extent = instance.radius + instance.outline_width + 1.0;
+    let local_position = CORNERS[vertex_index] * extent;
+
+    var point_position = transform.view_proj * vec4<f32>(instance.position, 1.0);
+    var vertex_delta = vec4<f32>((local_position + instance.offset) * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+
+    out.clip_position = point_position + vertex_delta;
+    out.local_position = local_position;
+    out.center_color = linear_color(vec4<f32>(instance.center_color) / 255.0);
+    out.side_color = linear_color(vec4<f32>(instance.side_color) / 255.0);
+    out.outline_color = linear_color(vec4<f32>(instance.outline_color) / 255.0);
+    out.radius = instance.radius;
+    out.outline_width = instance.outline_width;
+
+    return out;
+}
+
+// Fragment shader
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = length(in.local_position);
+
+    var fill = mix(in.center_color, in.side_color, clamp(distance / max(in.radius, 0.0001), 0.0, 1.0));
+    fill.a = fill.a * clamp(in.radius - distance + 0.5, 0.0, 1.0);
+
+    var outline_alpha = 0.0;
+    if (in.outline_width > 0.0) {
+        let to_edge = in.outline_width - abs(distance - in.radius);
+        outline_alpha = in.outline_color.a * clamp(to_edge + 0.5, 0.0, 1.0);
+    }
+
+    let alpha = outline_alpha + fill.a * (1.0 - outline_alpha);
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    let color = (in.outline_color.rgb * outline_alpha + fill.rgb * fill.a * (1.0 - outline_alpha)) / alpha;
+    return output_color(with_opacity(vec4<f32>(color, alpha), transform.opacity), transform.encode_srgb);
+}
@@ -0,0 +1,35 @@
+// Color space conversions shared by all the shaders. This file is prepended to the source of every shader.
+//
+// Colors of the primitives are given in sRGB, while the blending is done with linear colors.
+
+fn srgb_to_linear(value: vec3<f32>) -> vec3<f32> {
+    let lower = value / 12.92;
+    let higher = pow((max(value, vec3<f32>(0.0)) + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(higher, lower, value <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(value: vec3<f32>) -> vec3<f32> {
+    let lower = value * 12.92;
+    let higher = 1.055 * pow(max(value, vec3<f32>(0.0)), vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(higher, lower, value <= vec3<f32>(0.0031308));
+}
+
+// Converts sRGB color of a primitive into linear color.
+fn linear_color(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(srgb_to_linear(color.rgb), color.a);
+}
+
+// Multiplies the alpha of the color by the opacity the primitives are drawn with.
+fn with_opacity(color: vec4<f32>, opacity: f32) -> vec4<f32> {
+    return vec4<f32>(color.rgb, color.a * opacity);
+}
+
+// Prepares linear color to be written to the render target. If the target does not encode colors into sRGB itself,
+// `encode_srgb` is 1.0 and the color is encoded by the shader.
+fn output_color(color: vec4<f32>, encode_srgb: f32) -> vec4<f32> {
+    if (encode_srgb == 0.0) {
+        return color;
+    }
+
+    return vec4<f32>(linear_to_srgb(color.rgb), color.a);
+}
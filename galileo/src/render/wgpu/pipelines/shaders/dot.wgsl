@@ -5,6 +5,10 @@ struct ViewUniform {
     view_rotation: mat4x4<f32>,
     inv_screen_size: vec2<f32>,
     resolution: f32,
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
 }
 
 @group(0) @binding(0)
@@ -25,7 +29,7 @@ fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.color = vec4<f32>(model.color) / 255.0;
+    out.color = linear_color(vec4<f32>(model.color) / 255.0);
     out.clip_position = transform.view_proj * vec4<f32>(model.position, 1.0);
 
     return out;
@@ -35,5 +39,5 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    return output_color(with_opacity(in.color, transform.opacity), transform.encode_srgb);
 }
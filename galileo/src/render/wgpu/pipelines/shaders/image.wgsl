@@ -5,16 +5,31 @@ struct ViewUniform {
     view_rotation: mat4x4<f32>,
     inv_screen_size: vec2<f32>,
     resolution: f32,
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
 }
 
 @group(0) @binding(0)
 var<uniform> transform: ViewUniform;
 
+// Rotates a screen offset (with Y going up) clockwise by `rotation.x` radians. If `rotation.y` is 1.0, the angle is
+// measured from the north of the map, so the rotation of the view is added to it.
+fn rotate_offset(offset: vec2<f32>, rotation: vec2<f32>) -> vec2<f32> {
+    let angle = rotation.x - rotation.y * transform.rotation_z;
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec2<f32>(offset.x * c + offset.y * s, offset.y * c - offset.x * s);
+}
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
     @location(1) opacity: f32,
     @location(2) tex_coord: vec2<f32>,
     @location(3) offset: vec2<f32>,
+    @location(4) map_aligned: f32,
+    @location(5) rotation: vec2<f32>,
 }
 
 struct VertexOutput {
@@ -31,7 +46,13 @@ fn vs_main(
     out.tex_coord = model.tex_coord;
 
     var point_position = transform.view_proj * vec4<f32>(model.position, 0.0, 1.0);
-    var vertex_delta = vec4<f32>(model.offset * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+    let offset = rotate_offset(model.offset, model.rotation);
+    var vertex_delta = vec4<f32>(offset * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+
+    // Images lying on the map plane are rotated with the map, same as the lines.
+    if (model.map_aligned > 0.5) {
+        vertex_delta = vertex_delta * transform.view_rotation;
+    }
 
     out.clip_position = point_position + vertex_delta;
     out.opacity = model.opacity;
@@ -56,5 +77,5 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         discard;
     }
 
-    return color;
+    return output_color(with_opacity(color, transform.opacity), transform.encode_srgb);
 }
@@ -0,0 +1,97 @@
+// Vertex shader
+
+struct ViewUniform {
+    view_proj: mat4x4<f32>,
+    view_rotation: mat4x4<f32>,
+    inv_screen_size: vec2<f32>,
+    resolution: f32,
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> transform: ViewUniform;
+
+// Rotates a screen offset (with Y going up) clockwise by `rotation.x` radians. If `rotation.y` is 1.0, the angle is
+// measured from the north of the map, so the rotation of the view is added to it.
+fn rotate_offset(offset: vec2<f32>, rotation: vec2<f32>) -> vec2<f32> {
+    let angle = rotation.x - rotation.y * transform.rotation_z;
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec2<f32>(offset.x * c + offset.y * s, offset.y * c - offset.x * s);
+}
+
+struct InstanceInput {
+    @location(0) position: vec2<f32>,
+    @location(1) offset: vec2<f32>,
+    @location(2) size: vec2<f32>,
+    @location(3) rotation: vec2<f32>,
+    @location(4) opacity: f32,
+    @location(5) map_aligned: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(1) tex_coord: vec2<f32>,
+    @location(2) opacity: f32,
+};
+
+// Texture coordinates of the corners of two triangles covering the image.
+var<private> CORNERS: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+    vec2<f32>(0.0, 0.0),
+    vec2<f32>(0.0, 1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(0.0, 0.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(1.0, 0.0),
+);
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    let corner = CORNERS[vertex_index];
+    out.tex_coord = corner;
+
+    // The offset points to the top left corner of the image, with Y going up.
+    let local_offset = instance.offset + vec2<f32>(corner.x * instance.size.x, -corner.y * instance.size.y);
+
+    var point_position = transform.view_proj * vec4<f32>(instance.position, 0.0, 1.0);
+    let offset = rotate_offset(local_offset, instance.rotation);
+    var vertex_delta = vec4<f32>(offset * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+
+    // Images lying on the map plane are rotated with the map, same as the lines.
+    if (instance.map_aligned > 0.5) {
+        vertex_delta = vertex_delta * transform.view_rotation;
+    }
+
+    out.clip_position = point_position + vertex_delta;
+    out.opacity = instance.opacity;
+
+    return out;
+}
+
+
+// Fragment shader
+
+@group(1) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1)
+var s_diffuse: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color = textureSample(t_diffuse, s_diffuse, in.tex_coord);
+    color[3] = color[3] * in.opacity;
+
+    if color[3] == 0.0 {
+        discard;
+    }
+
+    return output_color(with_opacity(color, transform.opacity), transform.encode_srgb);
+}
@@ -0,0 +1,28 @@
+// Line patterns shared by the shaders of map-referenced primitives. This file is prepended to the source of every
+// shader.
+
+// Returns opacity of the line pattern at the fragment.
+//
+// `line_position` is the distance from the start of the line in map units and the position across the line from
+// -1.0 to 1.0. `pattern` is (length, gap, speed, kind) with lengths in pixels and speed in pixels per second. Kind
+// 0.0 is a solid line, 1.0 is dashes and 2.0 is arrowheads.
+fn line_pattern_alpha(line_position: vec2<f32>, pattern: vec4<f32>, resolution: f32, time: f32) -> f32 {
+    let kind = pattern.w;
+    let period = pattern.x + pattern.y;
+    if (kind == 0.0 || period <= 0.0) {
+        return 1.0;
+    }
+
+    let distance = line_position.x / resolution - pattern.z * time;
+    let position = distance - floor(distance / period) * period;
+    if (position > pattern.x) {
+        return 0.0;
+    }
+
+    if (kind == 1.0) {
+        return 1.0;
+    }
+
+    // Arrowhead is a triangle with the base at the start of the pattern and the tip at its end.
+    return select(0.0, 1.0, abs(line_position.y) <= 1.0 - position / pattern.x);
+}
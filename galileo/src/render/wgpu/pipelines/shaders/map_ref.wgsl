@@ -5,6 +5,10 @@ struct ViewUniform {
     view_rotation: mat4x4<f32>,
     inv_screen_size: vec2<f32>,
     resolution: f32,
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
 }
 
 @group(0) @binding(0)
@@ -15,11 +19,17 @@ struct VertexInput {
     @location(1) color: vec4<f32>,
     @location(2) norm: vec2<f32>,
     @location(3) norm_limit: f32,
+    @location(4) line_position: vec2<f32>,
+    @location(5) pattern: vec4<f32>,
+    @location(6) zoom_color: vec4<f32>,
+    @location(7) zoom: vec4<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) line_position: vec2<f32>,
+    @location(3) pattern: vec4<f32>,
 };
 
 @vertex
@@ -27,19 +37,23 @@ fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.color = model.color;
+    let zoom = zoom_factor(model.zoom, transform.resolution);
+    out.color = linear_color(mix(model.color, model.zoom_color, zoom));
 
     var vertex_position = transform.view_proj * vec4<f32>(model.position, 1.0);
-    var norm_length = sqrt(model.norm[0] * model.norm[0] + model.norm[1] * model.norm[1]) * transform.resolution;
+    let model_norm = model.norm * mix(model.zoom.z, model.zoom.w, zoom);
+    var norm_length = sqrt(model_norm[0] * model_norm[0] + model_norm[1] * model_norm[1]) * transform.resolution;
 
     var norm_limit = 1.0;
     if (norm_length > model.norm_limit) {
         norm_limit = model.norm_limit / norm_length;
     }
 
-    var norm_scale = vec2<f32>(model.norm[0] * transform.inv_screen_size[0], model.norm[1] * transform.inv_screen_size[1]) * norm_limit;
+    var norm_scale = vec2<f32>(model_norm[0] * transform.inv_screen_size[0], model_norm[1] * transform.inv_screen_size[1]) * norm_limit;
     var norm = vec4<f32>(norm_scale * vertex_position[3] * 2.0, 0.0, 0.0) * transform.view_rotation;
     out.clip_position = vertex_position + norm;
+    out.line_position = model.line_position;
+    out.pattern = model.pattern;
 
     return out;
 }
@@ -49,5 +63,10 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    let alpha = line_pattern_alpha(in.line_position, in.pattern, transform.resolution, transform.time);
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    return output_color(with_opacity(in.color, transform.opacity), transform.encode_srgb);
 }
@@ -0,0 +1,94 @@
+// Map-referenced primitives with analytic anti-aliasing of line edges. Used instead of map_ref.wgsl when
+// multisampling is turned off.
+
+// Vertex shader
+
+struct ViewUniform {
+    view_proj: mat4x4<f32>,
+    view_rotation: mat4x4<f32>,
+    inv_screen_size: vec2<f32>,
+    resolution: f32,
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> transform: ViewUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) norm: vec2<f32>,
+    @location(3) norm_limit: f32,
+    @location(4) line_position: vec2<f32>,
+    @location(5) pattern: vec4<f32>,
+    @location(6) zoom_color: vec4<f32>,
+    @location(7) zoom: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    // Offset of the fragment from the line axis in pixels.
+    @location(2) offset: vec2<f32>,
+    // Half of the line width in pixels. Zero for polygons.
+    @location(3) half_width: f32,
+    @location(4) line_position: vec2<f32>,
+    @location(5) pattern: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let zoom = zoom_factor(model.zoom, transform.resolution);
+    out.color = linear_color(mix(model.color, model.zoom_color, zoom));
+
+    var vertex_position = transform.view_proj * vec4<f32>(model.position, 1.0);
+    let model_norm = model.norm * mix(model.zoom.z, model.zoom.w, zoom);
+    var norm_length = sqrt(model_norm[0] * model_norm[0] + model_norm[1] * model_norm[1]) * transform.resolution;
+
+    var norm_limit = 1.0;
+    if (norm_length > model.norm_limit) {
+        norm_limit = model.norm_limit / norm_length;
+    }
+
+    var offset = model_norm * norm_limit;
+    let half_width = length(offset);
+
+    // Move the edge of the line half a pixel outwards, so that the smoothed edge is centered on the actual edge.
+    if (half_width > 0.0) {
+        offset = offset * (half_width + 0.5) / half_width;
+    }
+
+    var norm_scale = offset * transform.inv_screen_size;
+    var norm = vec4<f32>(norm_scale * vertex_position[3] * 2.0, 0.0, 0.0) * transform.view_rotation;
+    out.clip_position = vertex_position + norm;
+    out.offset = offset;
+    out.half_width = half_width;
+    out.line_position = model.line_position;
+    out.pattern = model.pattern;
+
+    return out;
+}
+
+
+// Fragment shader
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let alpha = line_pattern_alpha(in.line_position, in.pattern, transform.resolution, transform.time);
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    if (in.half_width <= 0.0) {
+        return output_color(with_opacity(in.color, transform.opacity), transform.encode_srgb);
+    }
+
+    let coverage = clamp(in.half_width - length(in.offset) + 0.5, 0.0, 1.0);
+    return output_color(with_opacity(vec4<f32>(in.color.rgb, in.color.a * coverage), transform.opacity), transform.encode_srgb);
+}
@@ -5,15 +5,29 @@ struct ViewUniform {
     view_rotation: mat4x4<f32>,
     inv_screen_size: vec2<f32>,
     resolution: f32,
+    encode_srgb: f32,
+    time: f32,
+    rotation_z: f32,
+    opacity: f32,
 }
 
 @group(0) @binding(0)
 var<uniform> transform: ViewUniform;
 
+// Rotates a screen offset (with Y going up) clockwise by `rotation.x` radians. If `rotation.y` is 1.0, the angle is
+// measured from the north of the map, so the rotation of the view is added to it.
+fn rotate_offset(offset: vec2<f32>, rotation: vec2<f32>) -> vec2<f32> {
+    let angle = rotation.x - rotation.y * transform.rotation_z;
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec2<f32>(offset.x * c + offset.y * s, offset.y * c - offset.x * s);
+}
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec2<f32>,
     @location(2) color: vec4<u32>,
+    @location(3) rotation: vec2<f32>,
 }
 
 struct VertexOutput {
@@ -26,9 +40,10 @@ fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.color = vec4<f32>(model.color) / 255.0;
+    out.color = linear_color(vec4<f32>(model.color) / 255.0);
     var point_position = transform.view_proj * vec4<f32>(model.position, 1.0);
-    var vertex_delta = vec4<f32>(model.normal * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+    let normal = rotate_offset(model.normal, model.rotation);
+    var vertex_delta = vec4<f32>(normal * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
 
     out.clip_position = point_position + vertex_delta;
 
@@ -40,5 +55,5 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    return output_color(with_opacity(in.color, transform.opacity), transform.encode_srgb);
 }
@@ -0,0 +1,14 @@
+// Zoom interpolation of the map-referenced primitives. This file is prepended to the source of every shader.
+
+// Returns the interpolation factor from 0.0 to 1.0 for the zoom interpolation parameters of a vertex at the given
+// resolution.
+//
+// `zoom` is (log2 of the first resolution, log2 of the second resolution, width scale at the first resolution, width
+// scale at the second resolution). If both resolutions are the same, there is no interpolation.
+fn zoom_factor(zoom: vec4<f32>, resolution: f32) -> f32 {
+    if (zoom.x == zoom.y) {
+        return 0.0;
+    }
+
+    return clamp((log2(resolution) - zoom.x) / (zoom.y - zoom.x), 0.0, 1.0);
+}
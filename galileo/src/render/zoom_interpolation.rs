@@ -0,0 +1,115 @@
+//! Paint properties changing with the resolution of the map.
+
+use crate::color::ColorSpace;
+use crate::Color;
+
+/// Linear change of the color and width of a primitive between two resolutions of the map.
+///
+/// The interpolation is done by the renderer for every frame using the current resolution of the view, so the
+/// primitive changes smoothly while zooming without being tessellated again. The interpolation factor changes linearly
+/// with the zoom level (logarithm of the resolution) from `0.0` at `from_resolution` to `1.0` at `to_resolution`, and
+/// the `from_*` and `to_*` values are used beyond these resolutions.
+///
+/// When set, the colors of the interpolation are used instead of the color and the gradient of the paint, and the
+/// widths are used instead of the line width. Widths are only applied to the lines with widths in
+/// [pixels](super::SizeUnit::Pixels).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZoomInterpolation {
+    /// Resolution at which the primitive has the `from_*` values.
+    pub from_resolution: f64,
+    /// Resolution at which the primitive has the `to_*` values.
+    pub to_resolution: f64,
+    /// Color at `from_resolution`.
+    pub from_color: Color,
+    /// Color at `to_resolution`.
+    pub to_color: Color,
+    /// Width of the line at `from_resolution`.
+    pub from_width: f64,
+    /// Width of the line at `to_resolution`.
+    pub to_width: f64,
+}
+
+impl ZoomInterpolation {
+    /// Interpolation that does not change the primitive.
+    pub(crate) const NONE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    /// Largest of the widths. Lines are tessellated with this width and then scaled down by the renderer.
+    pub fn max_width(&self) -> f64 {
+        self.from_width.max(self.to_width)
+    }
+
+    /// Interpolation factor at the given resolution.
+    pub fn factor(&self, resolution: f64) -> f64 {
+        factor(
+            self.from_resolution.log2(),
+            self.to_resolution.log2(),
+            resolution.log2(),
+        )
+    }
+
+    /// Color at the given resolution.
+    pub fn color_at(&self, resolution: f64) -> Color {
+        self.from_color
+            .interpolate(&self.to_color, self.factor(resolution), ColorSpace::Rgb)
+    }
+
+    /// Line width at the given resolution.
+    pub fn width_at(&self, resolution: f64) -> f64 {
+        let t = self.factor(resolution);
+        self.from_width + (self.to_width - self.from_width) * t
+    }
+
+    /// Parameters stored in the vertices of the primitive: `log2` of the two resolutions, and the widths as portions
+    /// of the [max width](ZoomInterpolation::max_width).
+    pub(crate) fn vertex_params(&self) -> [f32; 4] {
+        let max_width = self.max_width();
+        let (from_scale, to_scale) = if max_width > 0.0 {
+            (self.from_width / max_width, self.to_width / max_width)
+        } else {
+            (1.0, 1.0)
+        };
+
+        [
+            self.from_resolution.log2() as f32,
+            self.to_resolution.log2() as f32,
+            from_scale as f32,
+            to_scale as f32,
+        ]
+    }
+}
+
+/// Interpolation factor at the resolution with the given `log2`, for the interpolation between the resolutions with
+/// `log2` of `from` and `to`. Same as `zoom_factor` function of the shaders.
+pub(crate) fn factor(from: f64, to: f64, log_resolution: f64) -> f64 {
+    if from == to {
+        return 0.0;
+    }
+
+    ((log_resolution - from) / (to - from)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolation_by_zoom_level() {
+        let interpolation = ZoomInterpolation {
+            from_resolution: 8.0,
+            to_resolution: 2.0,
+            from_color: Color::rgba(0, 0, 0, 255),
+            to_color: Color::rgba(200, 100, 0, 255),
+            from_width: 1.0,
+            to_width: 3.0,
+        };
+
+        // Resolution 4.0 is half way from 8.0 to 2.0 in zoom levels.
+        assert_eq!(interpolation.factor(4.0), 0.5);
+        assert_eq!(interpolation.color_at(4.0), Color::rgba(100, 50, 0, 255));
+        assert_eq!(interpolation.width_at(4.0), 2.0);
+
+        assert_eq!(interpolation.width_at(16.0), 1.0);
+        assert_eq!(interpolation.width_at(1.0), 3.0);
+        assert_eq!(interpolation.vertex_params(), [3.0, 1.0, 1.0 / 3.0, 1.0]);
+    }
+}
@@ -0,0 +1,842 @@
+//! [`TileSchema`] is used by tile layers to calculate [tile indices](TileIndex) needed for a given ['MapView'].
+
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
+use galileo_types::geo::Crs;
+use nalgebra::Point3;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+#[cfg(target_arch = "wasm32")]
+use js_sys::wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::error::GalileoError;
+use crate::lod::Lod;
+use crate::view::MapView;
+
+mod service;
+
+pub use service::WmtsTileMatrix;
+
+const RESOLUTION_TOLERANCE: f64 = 0.01;
+
+/// Direction of the Y index of tiles.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum VerticalDirection {
+    /// Tiles with `Y == 0` are at the top of the map.
+    TopToBottom,
+    /// Tiles with `Y == 0` are at the bottom of the map.
+    BottomToTop,
+}
+
+/// Index of a tile.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub struct TileIndex {
+    /// Z index.
+    pub z: u32,
+    /// X index.
+    pub x: i32,
+    /// Y index.
+    pub y: i32,
+    pub(crate) display_x: i32,
+}
+
+/// Tile schema specifies how tile indices are calculated based on the map position and resolution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileSchema {
+    /// Position where all tiles have `X == 0, Y == 0` indices.
+    pub origin: Point2d,
+    /// Rectangle that contains all tiles of the tile scheme.
+    pub bounds: Rect,
+    /// Sorted set of levels of detail that specify resolutions for each z-level.
+    pub lods: BTreeSet<Lod>,
+    /// Width of a single tile in pixels.
+    pub tile_width: u32,
+    /// Height of a single tile in pixels.
+    pub tile_height: u32,
+    /// Direction of the Y-axis.
+    pub y_direction: VerticalDirection,
+    /// Crs of the scheme.
+    pub crs: Crs,
+}
+
+impl TileSchema {
+    /// Creates a new schema with levels of detail of the given resolutions.
+    ///
+    /// Resolutions must be given from the least detailed level to the most detailed one. Z-indices of the levels are
+    /// assigned in the same order starting from `0`.
+    pub fn new(
+        origin: Point2d,
+        bounds: Rect,
+        resolutions: impl IntoIterator<Item = f64>,
+        tile_width: u32,
+        tile_height: u32,
+        y_direction: VerticalDirection,
+        crs: Crs,
+    ) -> Result<Self, GalileoError> {
+        if tile_width == 0 || tile_height == 0 {
+            return Err(GalileoError::Generic(
+                "tile size must not be zero".to_string(),
+            ));
+        }
+
+        let mut lods = BTreeSet::new();
+        let mut prev_resolution = f64::INFINITY;
+        for (z, resolution) in resolutions.into_iter().enumerate() {
+            if resolution >= prev_resolution {
+                return Err(GalileoError::Generic(
+                    "resolutions of the tile schema must be strictly decreasing".to_string(),
+                ));
+            }
+
+            let lod = Lod::new(resolution, z as u32).ok_or_else(|| {
+                GalileoError::Generic(format!("invalid resolution of z-level {z}: {resolution}"))
+            })?;
+            lods.insert(lod);
+            prev_resolution = resolution;
+        }
+
+        if lods.is_empty() {
+            return Err(GalileoError::Generic(
+                "tile schema must have at least one level of detail".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            origin,
+            bounds,
+            lods,
+            tile_width,
+            tile_height,
+            y_direction,
+            crs,
+        })
+    }
+
+    /// Creates a schema of the standard tile pyramid covering the `extent` of a CRS: the tiles start at the top left
+    /// corner of the extent, the least detailed level covers the whole extent with one tile along its larger side,
+    /// and each next level has two times smaller resolution.
+    ///
+    /// [`TileSchema::web`] is such a pyramid over the extent of Web Mercator.
+    pub fn pyramid(
+        extent: Rect,
+        tile_size: u32,
+        lods_count: u32,
+        crs: Crs,
+    ) -> Result<Self, GalileoError> {
+        let top_resolution = extent.width().max(extent.height()) / tile_size as f64;
+        Self::new(
+            Point2d::new(extent.x_min(), extent.y_max()),
+            extent,
+            (0..lods_count).map(|z| top_resolution / 2f64.powi(z as i32)),
+            tile_size,
+            tile_size,
+            VerticalDirection::TopToBottom,
+            crs,
+        )
+    }
+
+    /// Resolution of the given z-level, if exists.
+    pub fn lod_resolution(&self, z: u32) -> Option<f64> {
+        for lod in &self.lods {
+            if lod.z_index() == z {
+                return Some(lod.resolution());
+            }
+        }
+
+        None
+    }
+
+    /// Width of a single tile.
+    pub fn tile_width(&self) -> u32 {
+        self.tile_width
+    }
+
+    /// Height of a single tile.
+    pub fn tile_height(&self) -> u32 {
+        self.tile_height
+    }
+
+    /// Maximum z-index of the schema.
+    pub fn max_z(&self) -> Option<u32> {
+        self.lods.iter().map(|lod| lod.z_index()).max()
+    }
+
+    /// Removes the levels of detail with z-index over `max_z`.
+    ///
+    /// When the map is zoomed in beyond the last level of the schema, tile layers display the tiles of the last level
+    /// upscaled. So this can be used with sources that do not have tiles for all the levels of a standard schema, e.g.
+    /// vector tile sources that only provide tiles up to level 14, to stop requesting missing tiles.
+    pub fn with_max_z(mut self, max_z: u32) -> Self {
+        self.lods.retain(|lod| lod.z_index() <= max_z);
+        self
+    }
+
+    /// Returns the schema for the high-DPI variant of the tile set with the same tiles, in which every tile is `scale`
+    /// times larger in pixels (e.g. `@2x` or 512-pixel tiles for `scale == 2.0`).
+    ///
+    /// The tiles of the returned schema have the same indices and cover the same areas as the tiles of this schema,
+    /// but the resolutions of the levels are divided by the actual ratio of the tile sizes.
+    pub fn scaled(&self, scale: f64) -> TileSchema {
+        let tile_width = ((self.tile_width as f64 * scale).round() as u32).max(1);
+        let tile_height = ((self.tile_height as f64 * scale).round() as u32).max(1);
+        let ratio = tile_width as f64 / self.tile_width as f64;
+
+        TileSchema {
+            lods: self
+                .lods
+                .iter()
+                .filter_map(|lod| Lod::new(lod.resolution() / ratio, lod.z_index()))
+                .collect(),
+            tile_width,
+            tile_height,
+            ..self.clone()
+        }
+    }
+
+    /// Select a level of detail for the given resolution.
+    pub fn select_lod(&self, resolution: f64) -> Option<Lod> {
+        if !resolution.is_finite() {
+            return None;
+        }
+
+        let mut prev_lod = self.lods.iter().next()?;
+
+        for lod in self.lods.iter().skip(1) {
+            if lod.resolution() * (1.0 - RESOLUTION_TOLERANCE) > resolution {
+                break;
+            }
+
+            prev_lod = lod;
+        }
+
+        Some(*prev_lod)
+    }
+
+    /// Iterate over tile indices that should be displayed for the given map view.
+    ///
+    /// Tiles are selected from the level of detail matching the view resolution (see [`TileSchema::select_lod`]).
+    /// If the view is rotated or tilted, only the tiles that intersect the visible part of the map
+    /// ([`MapView::footprint_projected`]) are returned, not all the tiles in its bounding box.
+    ///
+    /// In tilted views the resolution of the map changes with the distance from the camera, so the level of detail is
+    /// selected for every tile separately: tiles far from the camera are taken from less detailed levels, and tiles
+    /// close to it from more detailed ones. The returned tiles of different levels do not overlap.
+    ///
+    /// Returns `None` if the CRS of the view is different from the CRS of the schema.
+    pub fn iter_tiles(&self, view: &MapView) -> Option<impl Iterator<Item = TileIndex>> {
+        if *view.crs() != self.crs {
+            return None;
+        }
+
+        let resolution = view.resolution();
+        let bounding_box = view.get_bbox()?;
+        let tiles = self.iter_tiles_over_bbox(resolution, bounding_box)?;
+
+        let is_rotated = view.rotation_x() != 0.0 || view.rotation_z() != 0.0;
+        let footprint = is_rotated
+            .then(|| view.footprint_projected())
+            .flatten()
+            .map(|polygon| polygon.outer_contour.points);
+
+        if view.rotation_x() != 0.0 {
+            if let Some(tiles) = footprint
+                .as_ref()
+                .and_then(|footprint| self.tiles_by_distance(view, footprint))
+            {
+                return Some(tiles.into_iter());
+            }
+        }
+
+        let tiles: Vec<_> = match footprint {
+            Some(footprint) => tiles
+                .filter(|index| {
+                    self.tile_bbox(*index)
+                        .is_some_and(|bbox| convex_polygon_intersects_rect(&footprint, bbox))
+                })
+                .collect(),
+            None => tiles.collect(),
+        };
+
+        Some(tiles.into_iter())
+    }
+
+    /// Tiles intersecting the `footprint` of a tilted view, with the level of detail of each tile selected by the
+    /// resolution of the view at the point of the tile closest to the camera.
+    fn tiles_by_distance(&self, view: &MapView, footprint: &[Point2d]) -> Option<Vec<TileIndex>> {
+        let camera = view.camera()?;
+        let center_distance = camera.distance();
+        let resolution_at = |x: f64, y: f64| {
+            let distance = (camera.eye - Point3::new(x, y, 0.0)).norm();
+            view.resolution() * distance / center_distance
+        };
+
+        let max_resolution = footprint
+            .iter()
+            .map(|p| resolution_at(p.x, p.y))
+            .fold(view.resolution(), f64::max);
+        let lod = self.select_lod(max_resolution)?;
+        let intersects_footprint = |index: &TileIndex| {
+            self.tile_bbox(*index)
+                .is_some_and(|bbox| convex_polygon_intersects_rect(footprint, bbox))
+        };
+
+        let mut to_check: Vec<_> = self
+            .iter_tiles_over_bbox(lod.resolution(), Rect::from_points(footprint.iter())?)?
+            .filter(intersects_footprint)
+            .collect();
+        let mut tiles = vec![];
+        while let Some(index) = to_check.pop() {
+            let (Some(bbox), Some(tile_resolution)) =
+                (self.tile_bbox(index), self.lod_resolution(index.z))
+            else {
+                continue;
+            };
+
+            let required = resolution_at(
+                camera.eye.x.clamp(bbox.x_min(), bbox.x_max()),
+                camera.eye.y.clamp(bbox.y_min(), bbox.y_max()),
+            );
+            match self.lod_under(index.z) {
+                Some(finer) if tile_resolution * (1.0 - RESOLUTION_TOLERANCE) > required => {
+                    let children = self.iter_tiles_over_bbox(
+                        finer.resolution(),
+                        bbox.shrink(finer.resolution()),
+                    )?;
+                    to_check.extend(children.filter(intersects_footprint));
+                }
+                _ => tiles.push(index),
+            }
+        }
+
+        tiles.sort_by_key(|index| (index.z, index.x, index.y));
+        Some(tiles)
+    }
+
+    /// Iterate over tile indices of the level of detail matching the `resolution` that cover the `bounding_box`.
+    pub fn iter_tiles_over_bbox(
+        &self,
+        resolution: f64,
+        bounding_box: Rect,
+    ) -> Option<impl Iterator<Item = TileIndex>> {
+        let lod = self.select_lod(resolution)?;
+
+        let tile_w = lod.resolution() * self.tile_width as f64;
+        let tile_h = lod.resolution() * self.tile_height as f64;
+
+        let x_min = (self.x_adj(bounding_box.x_min()) / tile_w) as i32;
+        let x_min = x_min.max(self.min_x_index(lod.resolution()));
+
+        let x_max_adj = self.x_adj(bounding_box.x_max());
+        let x_add_one = if (x_max_adj % tile_w) < 0.001 { -1 } else { 0 };
+
+        let x_max = (x_max_adj / tile_w) as i32 + x_add_one;
+        let x_max = x_max.min(self.max_x_index(lod.resolution()));
+
+        let (top, bottom) = if self.y_direction == VerticalDirection::TopToBottom {
+            (bounding_box.y_min(), bounding_box.y_max())
+        } else {
+            (bounding_box.y_max(), bounding_box.y_min())
+        };
+
+        let y_min = (self.y_adj(bottom) / tile_h) as i32;
+        let y_min = y_min.max(self.min_y_index(lod.resolution()));
+
+        let y_max_adj = self.y_adj(top);
+        let y_add_one = if (y_max_adj % tile_h) < 0.001 { -1 } else { 0 };
+
+        let y_max = (y_max_adj / tile_h) as i32 + y_add_one;
+        let y_max = y_max.min(self.max_y_index(lod.resolution()));
+
+        Some((x_min..=x_max).flat_map(move |x| {
+            (y_min..=y_max).map(move |y| TileIndex {
+                x,
+                y,
+                z: lod.z_index(),
+                display_x: x,
+            })
+        }))
+    }
+
+    /// Index of the tile of the `z` level that contains the `point`. Returns `None` if the level does not exist in the
+    /// schema, or the point is outside the bounds of the schema.
+    pub fn tile_at(&self, point: &impl CartesianPoint2d<Num = f64>, z: u32) -> Option<TileIndex> {
+        let resolution = self.lod_resolution(z)?;
+        if !self.bounds.contains(point) {
+            return None;
+        }
+
+        let tile_w = resolution * self.tile_width as f64;
+        let tile_h = resolution * self.tile_height as f64;
+        let x = (self.x_adj(point.x()) / tile_w).floor() as i32;
+        let y = (self.y_adj(point.y()) / tile_h).floor() as i32;
+
+        Some(TileIndex {
+            z,
+            x: x.clamp(self.min_x_index(resolution), self.max_x_index(resolution)),
+            y: y.clamp(self.min_y_index(resolution), self.max_y_index(resolution)),
+            display_x: x,
+        })
+    }
+
+    /// Tiles of the next (more detailed) z-level that cover the area of the given tile. Returns `None` if the tile
+    /// is at the most detailed level.
+    pub fn get_substitutes(&self, index: TileIndex) -> Option<impl Iterator<Item = TileIndex>> {
+        let lod = self.lod_over(index.z)?;
+        // todo: we don't really need shrink here, but .iter_tiles_over_bbox return extra tiles
+        // when borders of tiles are exactly on bbox border.
+        self.iter_tiles_over_bbox(
+            lod.resolution(),
+            self.tile_bbox(index)?.shrink(lod.resolution()),
+        )
+    }
+
+    /// Returns lod one z-level over the given.
+    fn lod_over(&self, z: u32) -> Option<&Lod> {
+        let mut lod_iter = self.lods.iter();
+        for lod in lod_iter.by_ref() {
+            if lod.z_index() == z {
+                break;
+            }
+        }
+
+        lod_iter.next()
+    }
+
+    /// Returns lod one z-level under (more detailed than) the given.
+    fn lod_under(&self, z: u32) -> Option<&Lod> {
+        let mut previous = None;
+        for lod in &self.lods {
+            if lod.z_index() == z {
+                return previous;
+            }
+            previous = Some(lod);
+        }
+
+        None
+    }
+
+    fn x_adj(&self, x: f64) -> f64 {
+        x - self.origin.x()
+    }
+
+    fn y_adj(&self, y: f64) -> f64 {
+        match self.y_direction {
+            VerticalDirection::TopToBottom => self.origin.y() - y,
+            VerticalDirection::BottomToTop => y - self.origin.y(),
+        }
+    }
+
+    /// Standard Web Mercator based tile scheme (used, for example, by OSM and Google maps).
+    pub fn web(lods_count: u32) -> Self {
+        const ORIGIN: Point2d = Point2d::new(-20037508.342787, 20037508.342787);
+        const TOP_RESOLUTION: f64 = 156543.03392800014;
+
+        let mut lods = vec![Lod::new(TOP_RESOLUTION, 0).expect("invalid const parameters")];
+        for i in 1..lods_count {
+            lods.push(
+                Lod::new(lods[(i - 1) as usize].resolution() / 2.0, i)
+                    .expect("invalid const parameters"),
+            );
+        }
+
+        TileSchema {
+            origin: ORIGIN,
+            bounds: Rect::new(
+                -20037508.342787,
+                -20037508.342787,
+                20037508.342787,
+                20037508.342787,
+            ),
+            lods: lods.into_iter().collect(),
+            tile_width: 256,
+            tile_height: 256,
+            y_direction: VerticalDirection::TopToBottom,
+            crs: Crs::EPSG3857,
+        }
+    }
+
+    /// Area covered by the tile in projected coordinates. Returns `None` if the z-level of the index does not exist in
+    /// the schema.
+    pub fn tile_bbox(&self, index: TileIndex) -> Option<Rect> {
+        let resolution = self
+            .lods
+            .iter()
+            .find(|lod| lod.z_index() == index.z)?
+            .resolution();
+        let x_min = self.origin.x() + (index.x as f64) * self.tile_width as f64 * resolution;
+        let y_min = match self.y_direction {
+            VerticalDirection::TopToBottom => {
+                self.origin.y() - (index.y + 1) as f64 * self.tile_height as f64 * resolution
+            }
+            VerticalDirection::BottomToTop => {
+                self.origin.y() + (index.y as f64) * self.tile_height as f64 * resolution
+            }
+        };
+
+        Some(Rect::new(
+            x_min,
+            y_min,
+            x_min + self.tile_width as f64 * resolution,
+            y_min + self.tile_height as f64 * resolution,
+        ))
+    }
+
+    fn min_x_index(&self, resolution: f64) -> i32 {
+        ((self.bounds.x_min() - self.origin.x()) / resolution / self.tile_width as f64).floor()
+            as i32
+    }
+
+    fn max_x_index(&self, resolution: f64) -> i32 {
+        let pix_bound = (self.bounds.x_max() - self.origin.x()) / resolution;
+        let floored = pix_bound.floor();
+        if (pix_bound - floored).abs() < 0.1 {
+            (floored / self.tile_width as f64) as i32 - 1
+        } else {
+            (floored / self.tile_width as f64) as i32
+        }
+    }
+
+    fn min_y_index(&self, resolution: f64) -> i32 {
+        match self.y_direction {
+            VerticalDirection::TopToBottom => {
+                ((self.bounds.y_min() + self.origin.y()) / resolution / self.tile_height as f64)
+                    .floor() as i32
+            }
+            VerticalDirection::BottomToTop => {
+                ((self.bounds.y_min() - self.origin.y()) / resolution / self.tile_height as f64)
+                    .floor() as i32
+            }
+        }
+    }
+
+    fn max_y_index(&self, resolution: f64) -> i32 {
+        let pix_bound = match self.y_direction {
+            VerticalDirection::TopToBottom => (self.bounds.y_max() + self.origin.y()) / resolution,
+            VerticalDirection::BottomToTop => (self.bounds.y_max() - self.origin.y()) / resolution,
+        };
+        let floored = pix_bound.floor();
+        if (pix_bound - floored).abs() < 0.1 {
+            (floored / self.tile_height as f64) as i32 - 1
+        } else {
+            (floored / self.tile_height as f64) as i32
+        }
+    }
+}
+
+/// Checks if a convex polygon intersects the rectangle using the separating axis theorem.
+fn convex_polygon_intersects_rect(polygon: &[Point2d], rect: Rect) -> bool {
+    let Some(polygon_bbox) = Rect::from_points(polygon.iter()) else {
+        return false;
+    };
+    if !polygon_bbox.intersects(rect) {
+        return false;
+    }
+
+    let corners = rect.into_quadrangle();
+    let project = |points: &mut dyn Iterator<Item = &Point2d>, axis: (f64, f64)| {
+        points.fold((f64::MAX, f64::MIN), |(min, max), p| {
+            let value = p.x * axis.0 + p.y * axis.1;
+            (min.min(value), max.max(value))
+        })
+    };
+
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let normal = (a.y - b.y, b.x - a.x);
+        let (polygon_min, polygon_max) = project(&mut polygon.iter(), normal);
+        let (rect_min, rect_max) = project(&mut corners.iter(), normal);
+        if polygon_max < rect_min || rect_max < polygon_min {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::Size;
+
+    fn simple_schema() -> TileSchema {
+        TileSchema {
+            origin: Point2d::default(),
+            bounds: Rect::new(0.0, 0.0, 2048.0, 2048.0),
+            lods: [
+                Lod::new(8.0, 0).unwrap(),
+                Lod::new(4.0, 1).unwrap(),
+                Lod::new(2.0, 2).unwrap(),
+            ]
+            .into(),
+            tile_width: 256,
+            tile_height: 256,
+            y_direction: VerticalDirection::BottomToTop,
+            crs: Crs::EPSG3857,
+        }
+    }
+
+    fn get_view(resolution: f64, bbox: Rect) -> MapView {
+        MapView::new_projected(&bbox.center(), resolution).with_size(Size::new(
+            bbox.width() / resolution,
+            bbox.height() / resolution,
+        ))
+    }
+
+    #[test]
+    fn select_lod() {
+        let schema = simple_schema();
+        assert_eq!(schema.select_lod(8.0).unwrap().z_index(), 0);
+        assert_eq!(schema.select_lod(9.0).unwrap().z_index(), 0);
+        assert_eq!(schema.select_lod(16.0).unwrap().z_index(), 0);
+        assert_eq!(schema.select_lod(7.99).unwrap().z_index(), 0);
+        assert_eq!(schema.select_lod(7.5).unwrap().z_index(), 1);
+        assert_eq!(schema.select_lod(4.1).unwrap().z_index(), 1);
+        assert_eq!(schema.select_lod(4.0).unwrap().z_index(), 1);
+        assert_eq!(schema.select_lod(1.5).unwrap().z_index(), 2);
+        assert_eq!(schema.select_lod(1.0).unwrap().z_index(), 2);
+    }
+
+    #[test]
+    fn lod_under() {
+        let schema = simple_schema();
+        assert_eq!(schema.lod_under(0).unwrap().z_index(), 1);
+        assert_eq!(schema.lod_under(1).unwrap().z_index(), 2);
+        assert!(schema.lod_under(2).is_none());
+    }
+
+    #[test]
+    fn iter_indices_full_bbox() {
+        let schema = simple_schema();
+        let bbox = Rect::new(0.0, 0.0, 2048.0, 2048.0);
+        let view = get_view(8.0, bbox);
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 1);
+        for tile in schema.iter_tiles(&view).unwrap() {
+            assert_eq!(tile.x, 0);
+            assert_eq!(tile.y, 0);
+            assert_eq!(tile.z, 0);
+        }
+
+        let view = get_view(4.0, bbox);
+        let mut tiles: Vec<TileIndex> = schema.iter_tiles(&view).unwrap().collect();
+        tiles.dedup();
+        assert_eq!(tiles.len(), 4);
+        for tile in tiles {
+            assert!(tile.x >= 0 && tile.x <= 1);
+            assert!(tile.y >= 0 && tile.y <= 1);
+            assert_eq!(tile.z, 1);
+        }
+
+        let view = get_view(2.0, bbox);
+        let mut tiles: Vec<TileIndex> = schema.iter_tiles(&view).unwrap().collect();
+        tiles.dedup();
+        assert_eq!(tiles.len(), 16);
+        for tile in tiles {
+            assert!(tile.x >= 0 && tile.x <= 3);
+            assert!(tile.y >= 0 && tile.y <= 3);
+            assert_eq!(tile.z, 2);
+        }
+    }
+
+    #[test]
+    fn iter_indices_part_bbox() {
+        let schema = simple_schema();
+        let bbox = Rect::new(200.0, 700.0, 1200.0, 1100.0);
+        let view = get_view(8.0, bbox);
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 1);
+        for tile in schema.iter_tiles(&view).unwrap() {
+            assert_eq!(tile.x, 0);
+            assert_eq!(tile.y, 0);
+            assert_eq!(tile.z, 0);
+        }
+
+        let view = get_view(4.0, bbox);
+        let mut tiles: Vec<TileIndex> = schema.iter_tiles(&view).unwrap().collect();
+        tiles.dedup();
+        assert_eq!(tiles.len(), 4);
+        for tile in tiles {
+            assert!(tile.x >= 0 && tile.x <= 1);
+            assert!(tile.y >= 0 && tile.y <= 1);
+            assert_eq!(tile.z, 1);
+        }
+
+        let view = get_view(2.0, bbox);
+        let mut tiles: Vec<TileIndex> = schema.iter_tiles(&view).unwrap().collect();
+        tiles.dedup();
+        assert_eq!(tiles.len(), 6);
+        for tile in tiles {
+            assert!(tile.x >= 0 && tile.x <= 2);
+            assert!(tile.y >= 1 && tile.y <= 2);
+            assert_eq!(tile.z, 2);
+        }
+    }
+
+    #[test]
+    fn iter_tiles_outside_of_bbox() {
+        let schema = simple_schema();
+        let bbox = Rect::new(-100.0, -100.0, -50.0, -50.0);
+        let view = get_view(8.0, bbox);
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 0);
+        let view = get_view(2.0, bbox);
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 0);
+
+        let bbox = Rect::new(2100.0, 0.0, 2500.0, 2048.0);
+        let view = get_view(8.0, bbox);
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 0);
+        let view = get_view(2.0, bbox);
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn iter_tiles_does_not_include_tiles_outside_bbox() {
+        let schema = simple_schema();
+        let bbox = Rect::new(-2048.0, -2048.0, 4096.0, 4096.0);
+        let view = get_view(8.0, bbox);
+        for tile in schema.iter_tiles(&view).unwrap() {
+            println!("{tile:?}");
+        }
+
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 1);
+        let view = get_view(2.0, bbox);
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 16);
+    }
+
+    #[test]
+    fn scaled_schema_has_same_tiles() {
+        let schema = simple_schema();
+        let scaled = schema.scaled(2.0);
+        assert_eq!(scaled.tile_width(), 512);
+        assert_eq!(scaled.lod_resolution(1), Some(2.0));
+
+        let index = TileIndex {
+            z: 2,
+            x: 1,
+            y: 3,
+            display_x: 1,
+        };
+        assert_eq!(scaled.tile_bbox(index), schema.tile_bbox(index));
+
+        let bbox = Rect::new(0.0, 0.0, 2048.0, 2048.0);
+        let tiles: Vec<_> = schema.iter_tiles(&get_view(4.0, bbox)).unwrap().collect();
+        let scaled_tiles: Vec<_> = scaled.iter_tiles(&get_view(2.0, bbox)).unwrap().collect();
+        assert_eq!(tiles, scaled_tiles);
+    }
+
+    #[test]
+    fn tiles_of_max_z_are_used_when_overzoomed() {
+        let schema = simple_schema().with_max_z(1);
+        assert_eq!(schema.max_z(), Some(1));
+
+        let bbox = Rect::new(0.0, 0.0, 512.0, 512.0);
+        let tiles: Vec<_> = schema.iter_tiles(&get_view(0.5, bbox)).unwrap().collect();
+        assert_eq!(tiles.len(), 1);
+        assert_eq!(tiles[0].z, 1);
+    }
+
+    #[test]
+    fn tile_at_point() {
+        let schema = simple_schema();
+        let index = schema.tile_at(&Point2d::new(1500.0, 300.0), 2).unwrap();
+        assert_eq!((index.x, index.y, index.z), (2, 0, 2));
+        assert_eq!(
+            schema.tile_bbox(index),
+            Some(Rect::new(1024.0, 0.0, 1536.0, 512.0))
+        );
+
+        assert!(schema.tile_at(&Point2d::new(-1.0, 300.0), 2).is_none());
+        assert!(schema.tile_at(&Point2d::new(1500.0, 300.0), 3).is_none());
+    }
+
+    #[test]
+    fn iter_tiles_of_rotated_view() {
+        let schema = simple_schema();
+        let view = MapView::new_projected(&Point2d::new(1024.0, 1024.0), 2.0)
+            .with_size(Size::new(512.0, 512.0));
+        assert_eq!(schema.iter_tiles(&view).unwrap().count(), 4);
+
+        let rotated = view.with_rotation_z(std::f64::consts::FRAC_PI_4);
+        let tiles: Vec<_> = schema.iter_tiles(&rotated).unwrap().collect();
+        assert_eq!(tiles.len(), 12);
+        for corner in [(0, 0), (3, 0), (0, 3), (3, 3)] {
+            assert!(!tiles.iter().any(|t| (t.x, t.y) == corner));
+        }
+
+        let tilted = view.with_rotation_x(std::f64::consts::FRAC_PI_4);
+        let tiles: Vec<_> = schema.iter_tiles(&tilted).unwrap().collect();
+        assert!(tiles.iter().all(|t| t.y >= 1));
+        assert!(tiles.iter().any(|t| t.y == 3));
+    }
+
+    #[test]
+    fn tilted_view_uses_less_detailed_tiles_far_away() {
+        let schema = TileSchema::web(18);
+        let resolution = schema.lod_resolution(12).unwrap();
+        let view = MapView::new_projected(&Point2d::new(1000.0, 1000.0), resolution)
+            .with_size(Size::new(800.0, 600.0))
+            .with_rotation_x(1.2);
+        let tiles: Vec<_> = schema.iter_tiles(&view).unwrap().collect();
+
+        let min_z = tiles.iter().map(|t| t.z).min().unwrap();
+        let max_z = tiles.iter().map(|t| t.z).max().unwrap();
+        assert!(max_z >= 12);
+        assert!(min_z < max_z);
+
+        let camera = view.camera().unwrap();
+        let distance = |index: &TileIndex| {
+            let center = schema.tile_bbox(*index).unwrap().center();
+            (center.x - camera.eye.x).hypot(center.y - camera.eye.y)
+        };
+        let nearest = tiles
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap();
+        let farthest = tiles
+            .iter()
+            .max_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap();
+        assert_eq!(nearest.z, max_z);
+        assert!(farthest.z < nearest.z);
+
+        for (i, a) in tiles.iter().enumerate() {
+            let a_bbox = schema.tile_bbox(*a).unwrap().shrink(1.0);
+            for b in &tiles[i + 1..] {
+                assert!(!a_bbox.intersects(schema.tile_bbox(*b).unwrap().shrink(1.0)));
+            }
+        }
+    }
+
+    #[test]
+    fn pyramid_schema() {
+        let extent = Rect::new(-1000.0, -500.0, 1000.0, 500.0);
+        let schema = TileSchema::pyramid(extent, 250, 3, Crs::EPSG3857).unwrap();
+        assert_eq!(schema.origin, Point2d::new(-1000.0, 500.0));
+        assert_eq!(schema.lod_resolution(0), Some(8.0));
+        assert_eq!(schema.lod_resolution(2), Some(2.0));
+        assert_eq!(
+            schema
+                .tile_at(&Point2d::new(999.0, -499.0), 0)
+                .map(|index| (index.x, index.y)),
+            Some((0, 0))
+        );
+
+        assert!(TileSchema::new(
+            Point2d::default(),
+            extent,
+            [1.0, 2.0],
+            256,
+            256,
+            VerticalDirection::TopToBottom,
+            Crs::EPSG3857
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn lod_over() {
+        let schema = simple_schema();
+        assert_eq!(schema.lod_over(0), None);
+        assert_eq!(schema.lod_over(1).unwrap().z_index(), 0);
+        assert_eq!(schema.lod_over(2).unwrap().z_index(), 1);
+        assert_eq!(schema.lod_over(3), None);
+    }
+}
@@ -0,0 +1,410 @@
+//! Construction of [`TileSchema`] from the metadata of tile services.
+
+use crate::error::GalileoError;
+use crate::lod::Lod;
+use crate::tile_scheme::{TileSchema, VerticalDirection};
+use galileo_types::cartesian::{Point2d, Rect};
+use galileo_types::geo::Crs;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+/// Size of a pixel in meters used by WMTS to convert scale denominators into resolutions.
+const WMTS_PIXEL_SIZE: f64 = 0.00028;
+
+/// Length of one degree of the equator in meters, used by WMTS for geographic coordinate systems.
+const METERS_PER_DEGREE: f64 = 111319.49079327357;
+
+/// Description of one level (`TileMatrix`) of a WMTS tile matrix set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WmtsTileMatrix {
+    /// Identifier of the matrix, used in tile requests instead of the z-index.
+    pub identifier: String,
+    /// Scale denominator of the level.
+    pub scale_denominator: f64,
+    /// Position of the top left corner of the matrix in the CRS coordinates (*x* and *y*, regardless of the axis order
+    /// of the CRS).
+    pub top_left_corner: Point2d,
+    /// Width of one tile in pixels.
+    pub tile_width: u32,
+    /// Height of one tile in pixels.
+    pub tile_height: u32,
+    /// Number of tiles in a row of the matrix.
+    pub matrix_width: u32,
+    /// Number of tiles in a column of the matrix.
+    pub matrix_height: u32,
+}
+
+impl TileSchema {
+    /// Creates a schema from the tile matrices of a WMTS tile matrix set.
+    ///
+    /// Matrices must be ordered from the least detailed to the most detailed, and their z-indices in the schema are
+    /// their positions in the list. All the matrices must have the same tile size and top left corner.
+    ///
+    /// Resolutions are calculated from the scale denominators with the standard WMTS pixel size. If the `crs` is
+    /// [`Crs::WGS84`], the units of the CRS are considered to be degrees, otherwise meters.
+    pub fn from_wmts_matrices(matrices: &[WmtsTileMatrix], crs: Crs) -> Result<Self, GalileoError> {
+        let Some(first) = matrices.first() else {
+            return Err(GalileoError::Generic(
+                "tile matrix set has no tile matrices".to_string(),
+            ));
+        };
+
+        let meters_per_unit = if crs == Crs::WGS84 {
+            METERS_PER_DEGREE
+        } else {
+            1.0
+        };
+
+        let origin = first.top_left_corner;
+        let mut width = 0.0f64;
+        let mut height = 0.0f64;
+        let mut resolutions = Vec::with_capacity(matrices.len());
+        for matrix in matrices {
+            let resolution = matrix.scale_denominator * WMTS_PIXEL_SIZE / meters_per_unit;
+
+            if matrix.tile_width != first.tile_width || matrix.tile_height != first.tile_height {
+                return Err(GalileoError::Generic(format!(
+                    "tile matrix {} has different tile size",
+                    matrix.identifier
+                )));
+            }
+
+            if (matrix.top_left_corner.x - origin.x).abs() > resolution
+                || (matrix.top_left_corner.y - origin.y).abs() > resolution
+            {
+                return Err(GalileoError::Generic(format!(
+                    "tile matrix {} has different top left corner",
+                    matrix.identifier
+                )));
+            }
+
+            width = width.max(matrix.matrix_width as f64 * matrix.tile_width as f64 * resolution);
+            height =
+                height.max(matrix.matrix_height as f64 * matrix.tile_height as f64 * resolution);
+            resolutions.push(resolution);
+        }
+
+        Self::new(
+            origin,
+            Rect::new(origin.x, origin.y - height, origin.x + width, origin.y),
+            resolutions,
+            first.tile_width,
+            first.tile_height,
+            VerticalDirection::TopToBottom,
+            crs,
+        )
+    }
+
+    /// Creates a schema from the tile matrix set with the given identifier in the WMTS capabilities document. A
+    /// standalone `TileMatrixSet` XML document can also be used.
+    ///
+    /// See [`TileSchema::from_wmts_matrices`] for details. If the `crs` is [`Crs::WGS84`], the coordinates of the top
+    /// left corners are expected in the *latitude, longitude* order, as required by the EPSG:4326 definition.
+    pub fn from_wmts_capabilities(
+        xml: &str,
+        tile_matrix_set: &str,
+        crs: Crs,
+    ) -> Result<Self, GalileoError> {
+        let swap_axes = crs == Crs::WGS84;
+        let matrices = parse_wmts_matrices(xml, tile_matrix_set, swap_axes)?.ok_or_else(|| {
+            GalileoError::Generic(format!("tile matrix set {tile_matrix_set} is not found"))
+        })?;
+
+        Self::from_wmts_matrices(&matrices, crs)
+    }
+
+    /// Creates a schema from the description of an ArcGIS tiled map service (the JSON returned by the service root
+    /// URL), which must contain the `tileInfo` and `fullExtent` objects.
+    ///
+    /// Z-indices of the schema are the `level` values of the service levels of detail.
+    pub fn from_arcgis_json(json: &str, crs: Crs) -> Result<Self, GalileoError> {
+        let service: ArcGisService = serde_json::from_str(json).map_err(|err| {
+            GalileoError::Generic(format!("invalid ArcGIS service description: {err}"))
+        })?;
+
+        let tile_info = service.tile_info.ok_or_else(|| {
+            GalileoError::Generic("ArcGIS service description has no tileInfo".to_string())
+        })?;
+        let extent = service.full_extent.ok_or_else(|| {
+            GalileoError::Generic("ArcGIS service description has no fullExtent".to_string())
+        })?;
+
+        let lods: BTreeSet<Lod> = tile_info
+            .lods
+            .iter()
+            .map(|lod| {
+                Lod::new(lod.resolution, lod.level).ok_or_else(|| {
+                    GalileoError::Generic(format!("invalid resolution of level {}", lod.level))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        if lods.is_empty() || lods.len() != tile_info.lods.len() {
+            return Err(GalileoError::Generic(
+                "ArcGIS service must have levels of detail with different resolutions".to_string(),
+            ));
+        }
+
+        if tile_info.rows == 0 || tile_info.cols == 0 {
+            return Err(GalileoError::Generic(
+                "tile size must not be zero".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            origin: Point2d::new(tile_info.origin.x, tile_info.origin.y),
+            bounds: Rect::new(extent.xmin, extent.ymin, extent.xmax, extent.ymax),
+            lods,
+            tile_width: tile_info.cols,
+            tile_height: tile_info.rows,
+            y_direction: VerticalDirection::TopToBottom,
+            crs,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArcGisService {
+    tile_info: Option<ArcGisTileInfo>,
+    full_extent: Option<ArcGisExtent>,
+}
+
+#[derive(Deserialize)]
+struct ArcGisTileInfo {
+    rows: u32,
+    cols: u32,
+    origin: ArcGisPoint,
+    lods: Vec<ArcGisLod>,
+}
+
+#[derive(Deserialize)]
+struct ArcGisPoint {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Deserialize)]
+struct ArcGisLod {
+    level: u32,
+    resolution: f64,
+}
+
+#[derive(Deserialize)]
+struct ArcGisExtent {
+    xmin: f64,
+    ymin: f64,
+    xmax: f64,
+    ymax: f64,
+}
+
+/// Reads the matrices of the tile matrix set with the given identifier. Returns `None` if the set is not found.
+fn parse_wmts_matrices(
+    xml: &str,
+    tile_matrix_set: &str,
+    swap_axes: bool,
+) -> Result<Option<Vec<WmtsTileMatrix>>, GalileoError> {
+    let xml_error =
+        |err: quick_xml::Error| GalileoError::Generic(format!("invalid WMTS document: {err}"));
+
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    // Local names of the currently open elements.
+    let mut path: Vec<String> = vec![];
+    let mut set_identifier = None;
+    let mut matrices = vec![];
+    let mut matrix: Option<WmtsTileMatrix> = None;
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+                let parent = path.last().map(String::as_str);
+
+                if name == "TileMatrixSet" && parent != Some("TileMatrixSetLink") {
+                    set_identifier = None;
+                    matrices.clear();
+                } else if name == "TileMatrix" && parent == Some("TileMatrixSet") {
+                    matrix = Some(WmtsTileMatrix {
+                        identifier: String::new(),
+                        scale_denominator: 0.0,
+                        top_left_corner: Point2d::default(),
+                        tile_width: 0,
+                        tile_height: 0,
+                        matrix_width: 0,
+                        matrix_height: 0,
+                    });
+                }
+
+                path.push(name);
+            }
+            Event::End(_) => {
+                let Some(name) = path.pop() else {
+                    continue;
+                };
+
+                if name == "TileMatrix" && path.last().map(String::as_str) == Some("TileMatrixSet")
+                {
+                    matrices.extend(matrix.take());
+                } else if name == "TileMatrixSet"
+                    && set_identifier.as_deref() == Some(tile_matrix_set)
+                {
+                    return Ok(Some(matrices));
+                }
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(xml_error)?;
+                let text = text.trim();
+                let Some((name, parents)) = path.split_last() else {
+                    continue;
+                };
+
+                match parents.last().map(String::as_str) {
+                    Some("TileMatrixSet") if name == "Identifier" => {
+                        set_identifier = Some(text.to_string());
+                    }
+                    Some("TileMatrix") => {
+                        if let Some(matrix) = &mut matrix {
+                            read_matrix_field(matrix, name, text, swap_axes)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+fn read_matrix_field(
+    matrix: &mut WmtsTileMatrix,
+    name: &str,
+    text: &str,
+    swap_axes: bool,
+) -> Result<(), GalileoError> {
+    fn parse<T: std::str::FromStr>(name: &str, text: &str) -> Result<T, GalileoError> {
+        text.parse()
+            .map_err(|_| GalileoError::Generic(format!("invalid value of {name}: {text}")))
+    }
+
+    match name {
+        "Identifier" => matrix.identifier = text.to_string(),
+        "ScaleDenominator" => matrix.scale_denominator = parse(name, text)?,
+        "TileWidth" => matrix.tile_width = parse(name, text)?,
+        "TileHeight" => matrix.tile_height = parse(name, text)?,
+        "MatrixWidth" => matrix.matrix_width = parse(name, text)?,
+        "MatrixHeight" => matrix.matrix_height = parse(name, text)?,
+        "TopLeftCorner" => {
+            let mut coords = text.split_whitespace();
+            let (Some(first), Some(second)) = (coords.next(), coords.next()) else {
+                return Err(GalileoError::Generic(format!(
+                    "invalid value of {name}: {text}"
+                )));
+            };
+            let (first, second): (f64, f64) = (parse(name, first)?, parse(name, second)?);
+            matrix.top_left_corner = if swap_axes {
+                Point2d::new(second, first)
+            } else {
+                Point2d::new(first, second)
+            };
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    const WMTS_CAPABILITIES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Capabilities xmlns="http://www.opengis.net/wmts/1.0" xmlns:ows="http://www.opengis.net/ows/1.1">
+  <Contents>
+    <Layer>
+      <ows:Identifier>roads</ows:Identifier>
+      <TileMatrixSetLink><TileMatrixSet>GoogleMapsCompatible</TileMatrixSet></TileMatrixSetLink>
+    </Layer>
+    <TileMatrixSet>
+      <ows:Identifier>GoogleMapsCompatible</ows:Identifier>
+      <ows:SupportedCRS>urn:ogc:def:crs:EPSG:6.18.3:3857</ows:SupportedCRS>
+      <TileMatrix>
+        <ows:Identifier>0</ows:Identifier>
+        <ScaleDenominator>559082264.0287178</ScaleDenominator>
+        <TopLeftCorner>-20037508.3427892 20037508.3427892</TopLeftCorner>
+        <TileWidth>256</TileWidth>
+        <TileHeight>256</TileHeight>
+        <MatrixWidth>1</MatrixWidth>
+        <MatrixHeight>1</MatrixHeight>
+      </TileMatrix>
+      <TileMatrix>
+        <ows:Identifier>1</ows:Identifier>
+        <ScaleDenominator>279541132.0143589</ScaleDenominator>
+        <TopLeftCorner>-20037508.3427892 20037508.3427892</TopLeftCorner>
+        <TileWidth>256</TileWidth>
+        <TileHeight>256</TileHeight>
+        <MatrixWidth>2</MatrixWidth>
+        <MatrixHeight>2</MatrixHeight>
+      </TileMatrix>
+    </TileMatrixSet>
+  </Contents>
+</Capabilities>"#;
+
+    #[test]
+    fn schema_from_wmts_capabilities() {
+        let schema = TileSchema::from_wmts_capabilities(
+            WMTS_CAPABILITIES,
+            "GoogleMapsCompatible",
+            Crs::EPSG3857,
+        )
+        .unwrap();
+        let web = TileSchema::web(2);
+
+        assert_eq!(schema.lods.len(), 2);
+        for z in 0..2 {
+            assert_abs_diff_eq!(
+                schema.lod_resolution(z).unwrap(),
+                web.lod_resolution(z).unwrap(),
+                epsilon = 0.001
+            );
+        }
+        assert_abs_diff_eq!(schema.origin, web.origin, epsilon = 0.001);
+        assert_abs_diff_eq!(schema.bounds.x_max(), web.bounds.x_max(), epsilon = 0.01);
+
+        assert!(
+            TileSchema::from_wmts_capabilities(WMTS_CAPABILITIES, "roads", Crs::EPSG3857).is_err()
+        );
+    }
+
+    #[test]
+    fn schema_from_arcgis_json() {
+        let json = r#"{
+            "tileInfo": {
+                "rows": 256,
+                "cols": 512,
+                "dpi": 96,
+                "origin": { "x": -5120900, "y": 9998100 },
+                "spatialReference": { "wkid": 2263 },
+                "lods": [
+                    { "level": 0, "resolution": 416.66666666666669, "scale": 1.5E8 },
+                    { "level": 1, "resolution": 208.33333333333334, "scale": 7.5E7 }
+                ]
+            },
+            "fullExtent": { "xmin": 900000, "ymin": 120000, "xmax": 1070000, "ymax": 275000 }
+        }"#;
+
+        let schema = TileSchema::from_arcgis_json(json, Crs::EPSG3857).unwrap();
+        assert_eq!(schema.tile_width, 512);
+        assert_eq!(schema.tile_height, 256);
+        assert_eq!(schema.origin, Point2d::new(-5120900.0, 9998100.0));
+        assert_eq!(schema.lod_resolution(1), Some(208.33333333333334));
+        assert_eq!(schema.bounds.x_min(), 900000.0);
+
+        assert!(TileSchema::from_arcgis_json("{}", Crs::EPSG3857).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use super::{MapView, DEFAULT_FOV};
+use nalgebra::{Matrix4, Perspective3, Point3, Vector3};
+
+/// Smallest horizontal distance between the eye and the target (relative to their distance) for which the rotation
+/// of the view is calculated from the eye position rather than from the up vector.
+const TOP_DOWN_EPSILON: f64 = 1e-9;
+
+/// Perspective camera looking at the map.
+///
+/// Positions are given in the projected coordinates of the [`MapView`] CRS, with the *Z* axis going up from the map
+/// surface and measured in the same units. A camera can be converted to and from the
+/// center + resolution + rotation + tilt parameters of the view with [`MapView::camera`] and
+/// [`MapView::with_camera`], e.g. to script cinematic camera moves or to synchronize the map with an external 3D
+/// scene.
+///
+/// ```
+/// use galileo::{Camera, MapView};
+/// use galileo_types::cartesian::{Point2d, Size};
+/// use nalgebra::Point3;
+///
+/// let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 10.0).with_size(Size::new(800.0, 600.0));
+/// let camera = Camera::new(Point3::new(0.0, -3000.0, 3000.0), Point3::new(0.0, 0.0, 0.0));
+/// let view = view.with_camera(&camera).expect("camera looks at the map");
+///
+/// assert!((view.rotation_x().to_degrees() - 45.0).abs() < 1e-9);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera {
+    /// Position of the camera.
+    pub eye: Point3<f64>,
+    /// Point the camera looks at.
+    pub target: Point3<f64>,
+    /// Direction that is up on the screen.
+    pub up: Vector3<f64>,
+    /// Vertical field of view in radians.
+    pub fov: f64,
+}
+
+impl Camera {
+    /// Creates a camera looking from `eye` at `target` with the top of the screen facing north (*+Y*) and the
+    /// default field of view of the map view.
+    pub fn new(eye: Point3<f64>, target: Point3<f64>) -> Self {
+        Self {
+            eye,
+            target,
+            up: Vector3::y(),
+            fov: DEFAULT_FOV,
+        }
+    }
+
+    /// Sets the direction that is up on the screen.
+    pub fn with_up(mut self, up: Vector3<f64>) -> Self {
+        self.up = up;
+        self
+    }
+
+    /// Sets the vertical field of view in radians.
+    pub fn with_fov(mut self, fov: f64) -> Self {
+        self.fov = fov;
+        self
+    }
+
+    /// Distance between the eye and the target.
+    pub fn distance(&self) -> f64 {
+        (self.target - self.eye).norm()
+    }
+
+    /// Right-handed view matrix transforming map coordinates into the coordinates of the camera.
+    pub fn view_matrix(&self) -> Matrix4<f64> {
+        Matrix4::look_at_rh(&self.eye, &self.target, &self.up)
+    }
+
+    /// Perspective projection matrix of the camera for a screen with the given aspect ratio (width / height).
+    pub fn projection_matrix(&self, aspect: f64, near: f64, far: f64) -> Matrix4<f64> {
+        Perspective3::new(aspect, self.fov, near, far).to_homogeneous()
+    }
+}
+
+impl MapView {
+    /// Camera that displays this view. Returns `None` if the view has zero size or no valid position.
+    pub fn camera(&self) -> Option<Camera> {
+        if self.size.is_zero() {
+            return None;
+        }
+
+        let target = self.projected_position?;
+        let distance = self.camera_distance_px() * self.resolution;
+        let (sin_x, cos_x) = self.rotation_x.sin_cos();
+        let (sin_z, cos_z) = self.rotation_z.sin_cos();
+
+        let offset = Vector3::new(-sin_x * sin_z, -sin_x * cos_z, cos_x) * distance;
+        let up = Vector3::new(cos_x * sin_z, cos_x * cos_z, sin_x);
+
+        Some(Camera {
+            eye: target + offset,
+            target,
+            up,
+            fov: self.fov,
+        })
+    }
+
+    /// Creates a new view, same as the current one (with the same size and CRS), but displayed by the given camera.
+    ///
+    /// The center of the view is the point where the line of sight of the camera crosses the map surface. The map view
+    /// cannot be rolled, so the up vector of the camera is only used to find the rotation of the view when the camera
+    /// looks straight down.
+    ///
+    /// Returns `None` if the view has zero size, or if the camera does not look down at the map.
+    pub fn with_camera(&self, camera: &Camera) -> Option<Self> {
+        if self.size.is_zero() {
+            return None;
+        }
+
+        let direction = camera.target - camera.eye;
+        if camera.eye.z <= 0.0 || direction.z >= 0.0 {
+            return None;
+        }
+
+        let ground_target = camera.eye + direction * (camera.eye.z / -direction.z);
+        let offset = camera.eye - ground_target;
+        let distance = offset.norm();
+        let horizontal = offset.x.hypot(offset.y);
+
+        let rotation_x = horizontal.atan2(offset.z);
+        let rotation_z = if horizontal > distance * TOP_DOWN_EPSILON {
+            (-offset.x).atan2(-offset.y)
+        } else {
+            camera.up.x.atan2(camera.up.y)
+        };
+
+        let view = self.with_fov(camera.fov);
+        let resolution = distance / view.camera_distance_px();
+
+        Some(Self {
+            projected_position: Some(Point3::new(ground_target.x, ground_target.y, 0.0)),
+            resolution,
+            rotation_x,
+            rotation_z,
+            crs: self.crs.clone(),
+            ..view
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    fn view() -> MapView {
+        MapView::new_projected(&Point2d::new(100.0, 200.0), 2.0).with_size(Size::new(400.0, 300.0))
+    }
+
+    #[test]
+    fn top_down_camera() {
+        let camera = view().camera().unwrap();
+        assert_abs_diff_eq!(camera.eye, Point3::new(100.0, 200.0, 300.0), epsilon = 1e-9);
+        assert_abs_diff_eq!(camera.up, Vector3::y(), epsilon = 1e-9);
+    }
+
+    #[test]
+    fn camera_round_trip() {
+        let view = view().with_rotation(0.6, -2.0).with_fov(40f64.to_radians());
+        let camera = view.camera().unwrap();
+        let restored = view().with_camera(&camera).unwrap();
+
+        assert_abs_diff_eq!(restored.resolution(), view.resolution(), epsilon = 1e-9);
+        assert_abs_diff_eq!(restored.rotation_x(), view.rotation_x(), epsilon = 1e-9);
+        assert_abs_diff_eq!(restored.rotation_z(), view.rotation_z(), epsilon = 1e-9);
+        assert_abs_diff_eq!(restored.fov(), view.fov(), epsilon = 1e-9);
+        assert_abs_diff_eq!(
+            restored.projected_position().unwrap(),
+            view.projected_position().unwrap(),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn camera_target_above_ground() {
+        let camera = Camera::new(Point3::new(0.0, 0.0, 100.0), Point3::new(0.0, 50.0, 50.0));
+        let view = view().with_camera(&camera).unwrap();
+        let center = view.screen_to_map(Point2d::new(200.0, 150.0)).unwrap();
+
+        assert_abs_diff_eq!(center, Point2d::new(0.0, 100.0), epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            view.rotation_x(),
+            std::f64::consts::FRAC_PI_4,
+            epsilon = 1e-9
+        );
+
+        let looking_up = Camera::new(Point3::new(0.0, 0.0, 100.0), Point3::new(0.0, 50.0, 150.0));
+        assert!(view.with_camera(&looking_up).is_none());
+    }
+
+    #[test]
+    fn screen_to_map_with_narrow_fov() {
+        let view = view().with_rotation(0.8, 0.3).with_fov(30f64.to_radians());
+        for point in [Point2d::new(10.0, 20.0), Point2d::new(350.0, 280.0)] {
+            let on_map = view.screen_to_map(point).unwrap();
+            let on_screen = view.map_to_screen(on_map).position().unwrap();
+            assert_abs_diff_eq!(on_screen, point, epsilon = 1e-6);
+        }
+    }
+}
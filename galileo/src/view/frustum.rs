@@ -0,0 +1,120 @@
+use super::MapView;
+use galileo_types::cartesian::Rect;
+use nalgebra::{Matrix4, Point3, Vector4};
+
+/// Part of the space visible to the camera of a [`MapView`], bounded by six planes.
+///
+/// Unlike the bounding rectangle of the view ([`MapView::get_bbox`]), the frustum follows the exact shape of the
+/// visible area when the view is rotated or tilted, so it can be used to check if an object is on the screen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frustum {
+    /// Planes in `ax + by + cz + d = 0` form with normals pointing inside the frustum.
+    planes: [Vector4<f64>; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum planes from a projection matrix that transforms points into clip coordinates with all
+    /// of *X*, *Y* and *Z* in `[-w, w]` range.
+    pub fn from_matrix(matrix: &Matrix4<f64>) -> Self {
+        let row = |i: usize| matrix.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = plane.xyz().norm();
+            if length > 0.0 {
+                plane / length
+            } else {
+                plane
+            }
+        });
+
+        Self { planes }
+    }
+
+    /// Returns true if the point is inside the frustum.
+    pub fn contains_point(&self, point: &Point3<f64>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.dot(&point.to_homogeneous()) >= 0.0)
+    }
+
+    /// Returns true if any part of the axis-aligned box between `min` and `max` can be inside the frustum.
+    ///
+    /// The check is conservative: some boxes near the edges of the frustum may be reported as intersecting it while
+    /// being outside, but a box that is (even partially) inside is never reported as outside.
+    pub fn intersects_box(&self, min: &Point3<f64>, max: &Point3<f64>) -> bool {
+        self.planes.iter().all(|plane| {
+            let farthest_inside = Point3::new(
+                if plane.x >= 0.0 { max.x } else { min.x },
+                if plane.y >= 0.0 { max.y } else { min.y },
+                if plane.z >= 0.0 { max.z } else { min.z },
+            );
+            plane.dot(&farthest_inside.to_homogeneous()) >= 0.0
+        })
+    }
+
+    /// Returns true if any part of the rectangle lying on the map surface (`z = 0`) can be inside the frustum.
+    pub fn intersects_rect(&self, rect: Rect) -> bool {
+        self.intersects_box(
+            &Point3::new(rect.x_min(), rect.y_min(), 0.0),
+            &Point3::new(rect.x_max(), rect.y_max(), 0.0),
+        )
+    }
+}
+
+impl MapView {
+    /// Visible part of the space in the projected coordinates of the view. Returns `None` if the view has zero size or
+    /// no valid position.
+    pub fn frustum(&self) -> Option<Frustum> {
+        Some(Frustum::from_matrix(
+            &self.map_to_screen_center_transform()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    #[test]
+    fn frustum_of_rotated_view() {
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+            .with_size(Size::new(100.0, 100.0))
+            .with_rotation_z(std::f64::consts::FRAC_PI_4);
+        let frustum = view.frustum().unwrap();
+
+        assert!(frustum.contains_point(&Point3::new(0.0, 0.0, 0.0)));
+        // Corner of the bounding rectangle of the view that is outside of the rotated screen.
+        assert!(!frustum.intersects_rect(Rect::new(60.0, 60.0, 65.0, 65.0)));
+        // Corner of the rotated screen that is outside of the non-rotated one.
+        assert!(frustum.intersects_rect(Rect::new(65.0, -5.0, 68.0, 5.0)));
+    }
+
+    #[test]
+    fn frustum_of_tilted_view() {
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+            .with_size(Size::new(100.0, 100.0))
+            .with_rotation_x(1.0);
+        let frustum = view.frustum().unwrap();
+
+        // Behind the camera.
+        assert!(!frustum.intersects_rect(Rect::new(-10.0, -200.0, 10.0, -150.0)));
+
+        // Far part of the view is wider than the screen.
+        let far_point = view.screen_to_map(Point2d::new(0.0, 40.0)).unwrap();
+        assert!(far_point.x < -60.0);
+        assert!(frustum.intersects_rect(Rect::new(
+            far_point.x - 1.0,
+            far_point.y - 1.0,
+            far_point.x + 1.0,
+            far_point.y + 1.0
+        )));
+
+        // Objects raised above the map surface can be visible even if their footprint is not.
+        assert!(!frustum.intersects_rect(Rect::new(-1.0, -39.0, 1.0, -37.0)));
+        assert!(frustum.intersects_box(
+            &Point3::new(-1.0, -39.0, 8.0),
+            &Point3::new(1.0, -37.0, 12.0)
+        ));
+    }
+}
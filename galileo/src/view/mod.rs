@@ -0,0 +1,892 @@
+use crate::tile_scheme::{TileIndex, TileSchema};
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect, Size};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, Datum, GeoPoint, Projection};
+use galileo_types::impls::{ClosedContour, Polygon};
+use nalgebra::{
+    Matrix4, OMatrix, Perspective3, Point2, Point3, Rotation3, Scale3, Translation3, Vector2,
+    Vector3, U4,
+};
+
+mod camera;
+mod frustum;
+mod stereo;
+
+pub use camera::Camera;
+pub use frustum::Frustum;
+pub use stereo::{Eye, EyeFov};
+
+/// Number of segments each side of the screen is split into when calculating the view footprint.
+const FOOTPRINT_EDGE_SEGMENTS: usize = 8;
+
+/// Far distance of the view in its sizes at the center resolution.
+const FAR_DISTANCE_FACTOR: f64 = 2.0;
+
+/// Default vertical field of view of the view.
+const DEFAULT_FOV: f64 = std::f64::consts::FRAC_PI_2;
+
+/// Distance from the camera to the near plane of the perspective projection in pixels.
+const NEAR_PLANE_PX: f64 = 10.0;
+
+/// Map view specifies the area of the map that should be drawn. In other words, it sets the position of "camera" that
+/// looks at the map.
+///
+/// The main view parameters are:
+/// * position - coordinates of the point in the center of the map
+/// * resolution - number of map units in a single pixel at the center of the map
+/// * size - size of the rendering area in pixels
+/// * crs - coordinate system that the map will be rendered to. This specifies the geographic projection that the map is
+///   displayed in. Note, that currently geographic CRSs are not supported, and a map with such a view will not be
+///   drawn.
+///
+/// The view can also specify rotation along *x* (tilt) and *z* (rotation) axis, and the vertical field of view of the
+/// perspective projection. The same parameters can be given as a [`Camera`] with explicit eye and target positions
+/// (see [`MapView::camera`] and [`MapView::with_camera`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapView {
+    projected_position: Option<Point3<f64>>,
+    resolution: f64,
+    rotation_x: f64,
+    rotation_z: f64,
+    fov: f64,
+    size: Size,
+    crs: Crs,
+    eye: Option<Eye>,
+}
+
+impl MapView {
+    /// Creates a new view with the given position and resolution with default CRS (web-mercator EPSG:3857).
+    pub fn new(position: &impl GeoPoint<Num = f64>, resolution: f64) -> Self {
+        Self::new_with_crs(position, resolution, Crs::EPSG3857)
+    }
+
+    /// Creates a new view with the given CRS.
+    pub fn new_with_crs(position: &impl GeoPoint<Num = f64>, resolution: f64, crs: Crs) -> Self {
+        let projected = crs
+            .get_projection()
+            .and_then(|projection| projection.project(&GeoPoint2d::from(position)))
+            .map(|p: Point2d| Point3::new(p.x, p.y, 0.0));
+        Self {
+            projected_position: projected,
+            resolution,
+            rotation_z: 0.0,
+            rotation_x: 0.0,
+            fov: DEFAULT_FOV,
+            size: Default::default(),
+            crs,
+            eye: None,
+        }
+    }
+
+    /// Creates a new view, taking position value as projected coordinates. Default CRS is used (EPSG:3857).
+    pub fn new_projected(position: &impl CartesianPoint2d<Num = f64>, resolution: f64) -> Self {
+        Self::new_projected_with_crs(position, resolution, Crs::EPSG3857)
+    }
+
+    /// Creates a new view, taking position value as projected coordinates.
+    pub fn new_projected_with_crs(
+        position: &impl CartesianPoint2d<Num = f64>,
+        resolution: f64,
+        crs: Crs,
+    ) -> Self {
+        Self {
+            projected_position: Some(Point3::new(position.x(), position.y(), 0.0)),
+            resolution,
+            rotation_z: 0.0,
+            rotation_x: 0.0,
+            fov: DEFAULT_FOV,
+            size: Default::default(),
+            crs,
+            eye: None,
+        }
+    }
+
+    /// CRS of the view.
+    pub fn crs(&self) -> &Crs {
+        &self.crs
+    }
+
+    /// Position of the center point of the map (screen).
+    ///
+    /// If the projected position cannot be projected into geographic coordinates, `None` is returned.
+    pub fn position(&self) -> Option<GeoPoint2d> {
+        self.projected_position.and_then(|p| {
+            self.crs
+                .get_projection()
+                .and_then(|proj| proj.unproject(&Point2d::new(p.x, p.y)))
+        })
+    }
+
+    /// Position of the center point of the map in projected coordinates.
+    pub(crate) fn projected_position(&self) -> Option<Point3<f64>> {
+        self.projected_position
+    }
+
+    /// Resolution at the center of the map.
+    pub fn resolution(&self) -> f64 {
+        self.resolution
+    }
+
+    /// Creates a new view, same as the current one, but with the given resolution.
+    pub fn with_resolution(&self, resolution: f64) -> Self {
+        Self {
+            resolution,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Size of the view in pixels.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Creates a new view, same as the current one, but with the given size.
+    pub fn with_size(&self, new_size: Size) -> Self {
+        Self {
+            size: new_size,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Returns bounding rectangle of the view (in projected coordinates).
+    pub fn get_bbox(&self) -> Option<Rect> {
+        let points = [
+            Point2::new(0.0, 0.0),
+            Point2::new(self.size.width(), 0.0),
+            Point2::new(0.0, self.size.height()),
+            Point2::new(self.size.width(), self.size.height()),
+        ];
+
+        let position = self.projected_position?;
+        let max_bbox = Rect::new(
+            position.x - self.size.half_width() * self.resolution,
+            position.y - self.size.half_height() * self.resolution,
+            position.x + self.size.half_width() * self.resolution,
+            position.y + self.size.half_height() * self.resolution,
+        )
+        .magnify(4.0);
+
+        if let Some(points) = points
+            .into_iter()
+            .map(|p| self.screen_to_map(p))
+            .collect::<Option<Vec<Point2<f64>>>>()
+        {
+            let bbox = Rect::from_points(points.iter())?;
+            Some(bbox.limit(max_bbox))
+        } else {
+            Some(max_bbox)
+        }
+    }
+
+    fn map_to_screen_center_transform(&self) -> Option<OMatrix<f64, U4, U4>> {
+        if self.size.is_zero() {
+            return None;
+        }
+
+        let position = self.projected_position?;
+        let translate = Translation3::new(-position.x, -position.y, -position.z).to_homogeneous();
+        let rotation_x = Rotation3::new(Vector3::new(-self.rotation_x, 0.0, 0.0)).to_homogeneous();
+        let rotation_z = Rotation3::new(Vector3::new(0.0, 0.0, self.rotation_z)).to_homogeneous();
+
+        let scale = Scale3::new(
+            1.0 / self.resolution,
+            1.0 / self.resolution,
+            1.0 / self.resolution,
+        )
+        .to_homogeneous();
+
+        let translate_z = Translation3::new(0.0, 0.0, -self.camera_distance_px()).to_homogeneous();
+        let camera = translate_z * scale * rotation_x * rotation_z * translate;
+        match &self.eye {
+            Some(eye) => Some(
+                eye.fov
+                    .projection_matrix(NEAR_PLANE_PX, self.far_plane_px())
+                    * eye.view_matrix(self.resolution)
+                    * camera,
+            ),
+            None => Some(self.perspective() * camera),
+        }
+    }
+
+    fn perspective(&self) -> Matrix4<f64> {
+        Perspective3::new(
+            self.size.width() / self.size.height(),
+            self.fov,
+            NEAR_PLANE_PX,
+            self.far_plane_px(),
+        )
+        .to_homogeneous()
+    }
+
+    /// Distance from the camera to the far plane of the perspective projection in pixels.
+    fn far_plane_px(&self) -> f64 {
+        self.camera_distance_px() * 2.0
+    }
+
+    /// Distance from the camera to the center of the map in pixels.
+    fn camera_distance_px(&self) -> f64 {
+        self.size.half_height() / (self.fov / 2.0).tan()
+    }
+
+    /// Returns transformation matrix that transforms map coordinates to scene coordinates.
+    ///
+    /// Scene coordinates are `[-1.0, 1.0]` coordinates of the render area with *Y* going from bottom to top.
+    pub fn map_to_scene_transform(&self) -> Option<OMatrix<f64, U4, U4>> {
+        let scale = Scale3::new(1.0, 1.0, 0.5).to_homogeneous();
+        Some(scale * self.map_to_screen_center_transform()?)
+    }
+
+    /// Returns transformation matrix that transforms map coordinates to scene coordinates.
+    ///
+    /// Scene coordinates are `[-1.0, 1.0]` coordinates of the render area with *Y* going from bottom to top.
+    pub fn map_to_scene_mtx(&self) -> Option<[[f32; 4]; 4]> {
+        Some(self.map_to_scene_transform()?.cast::<f32>().data.0)
+    }
+
+    /// Rotation angle around *X* axis in radians (tilt).
+    pub fn rotation_x(&self) -> f64 {
+        self.rotation_x
+    }
+
+    /// Rotation angle around *Z* axis in radians.
+    pub fn rotation_z(&self) -> f64 {
+        self.rotation_z
+    }
+
+    /// Creates a new view, same as the current one, but with the given rotation x.
+    pub fn with_rotation_x(&self, rotation_x: f64) -> Self {
+        Self {
+            rotation_x,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Creates a new view, same as the current one, but with the given rotation z.
+    pub fn with_rotation_z(&self, rotation_z: f64) -> Self {
+        Self {
+            rotation_z,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Vertical field of view of the perspective projection in radians. Default is `PI / 2`.
+    pub fn fov(&self) -> f64 {
+        self.fov
+    }
+
+    /// Creates a new view, same as the current one, but with the given vertical field of view in radians.
+    ///
+    /// The resolution at the center of the map stays the same, so the camera moves closer to the map with a wider
+    /// field of view, and the perspective distortion of a tilted map becomes stronger. The value is clamped to
+    /// `[1, 150]` degrees.
+    pub fn with_fov(&self, fov: f64) -> Self {
+        Self {
+            fov: fov.clamp(1f64.to_radians(), 150f64.to_radians()),
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Creates a new view, same as the current one, but with the given rotation values.
+    pub fn with_rotation(&self, rotation_x: f64, rotation_z: f64) -> Self {
+        Self {
+            rotation_x,
+            rotation_z,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Projects the given screen point into map coordinates at the 0 elevation.
+    ///
+    /// Returns `None` if the point is outside of map (this can be possible, if the map is tilted and the point is
+    /// above the horizon, or if the point is outside the projection bounds).
+    pub fn screen_to_map(&self, px_position: Point2d) -> Option<Point2d> {
+        self.screen_to_map_transform()?.apply(px_position)
+    }
+
+    /// Projects a batch of screen points into map coordinates at the 0 elevation, writing the results into `out`.
+    ///
+    /// The result is the same as calling [`MapView::screen_to_map`] for every point, but the parameters of the
+    /// transformation are calculated only once. Points above the horizon are given as `None`. The `out` vector is
+    /// cleared before projecting.
+    pub fn screen_to_map_batch(&self, px_positions: &[Point2d], out: &mut Vec<Option<Point2d>>) {
+        out.clear();
+        match self.screen_to_map_transform() {
+            Some(transform) => out.extend(px_positions.iter().map(|p| transform.apply(*p))),
+            None => out.resize(px_positions.len(), None),
+        }
+    }
+
+    fn screen_to_map_transform(&self) -> Option<ScreenToMap> {
+        let position = self.projected_position?;
+        Some(ScreenToMap {
+            half_width: self.size.half_width(),
+            half_height: self.size.half_height(),
+            resolution: self.resolution,
+            fov_tan: (self.fov / 2.0).tan(),
+            tilt_tan: (std::f64::consts::FRAC_PI_2 - self.rotation_x).tan(),
+            tilt_cos: self.rotation_x.cos(),
+            rotation_z: Rotation3::new(Vector3::new(0.0, 0.0, -self.rotation_z)),
+            translation: Translation3::new(position.x, position.y, position.z),
+        })
+    }
+
+    /// Projects the given map point at the 0 elevation to the screen.
+    pub fn map_to_screen(&self, point: Point2d) -> ScreenPosition {
+        match self.map_to_screen_px_transform() {
+            Some(transform) => transform.apply(point),
+            None => ScreenPosition::BehindCamera,
+        }
+    }
+
+    /// Projects a batch of map points at the 0 elevation to the screen, writing the results into `out`.
+    ///
+    /// The result is the same as calling [`MapView::map_to_screen`] for every point, but the transformation matrix is
+    /// calculated only once. The `out` vector is cleared before projecting.
+    pub fn map_to_screen_batch(&self, points: &[Point2d], out: &mut Vec<ScreenPosition>) {
+        out.clear();
+        match self.map_to_screen_px_transform() {
+            Some(transform) => out.extend(points.iter().map(|p| transform.apply(*p))),
+            None => out.resize(points.len(), ScreenPosition::BehindCamera),
+        }
+    }
+
+    fn map_to_screen_px_transform(&self) -> Option<MapToScreen> {
+        Some(MapToScreen {
+            matrix: self.map_to_scene_transform()?,
+            size: self.size,
+        })
+    }
+
+    /// Projects the given screen point into map coordinates at the 0 elevation, and then projects them into
+    /// geographic coordinates.
+    ///
+    /// Returns `None` if the point is outside of map (this can be possible, if the map is tilted and the point is
+    /// above the horizon, or if the point is outside the projection bounds).
+    pub fn screen_to_map_geo(&self, px_position: Point2d) -> Option<GeoPoint2d> {
+        self.screen_to_map(px_position).and_then(|p| {
+            self.crs
+                .get_projection()
+                .and_then(|proj| proj.unproject(&Point2d::new(p.x, p.y)))
+        })
+    }
+
+    /// Approximate size of a pixel on the ground in meters at the given screen point.
+    ///
+    /// Unlike [`MapView::resolution`], which is given in projected units at the center of the screen, this takes into
+    /// account the distortion of the projection (e.g. the scale of Web Mercator growing with latitude) and the tilt
+    /// of the view. For tilted views a pixel covers different distances along the *X* and *Y* screen axes, so the
+    /// square root of the ground area covered by the pixel is returned.
+    ///
+    /// Returns `None` if the point is above the horizon, or the CRS of the view cannot be unprojected.
+    pub fn ground_resolution(&self, px_position: Point2d) -> Option<f64> {
+        let projection = self.crs.get_projection::<GeoPoint2d, Point2d>()?;
+        let geo_at = |dx: f64, dy: f64| {
+            self.screen_to_map(Point2d::new(px_position.x + dx, px_position.y + dy))
+                .and_then(|p| projection.unproject(&p))
+        };
+
+        let horizontal = ground_distance(&geo_at(-0.5, 0.0)?, &geo_at(0.5, 0.0)?);
+        let vertical = ground_distance(&geo_at(0.0, -0.5)?, &geo_at(0.0, 0.5)?);
+
+        Some((horizontal * vertical).sqrt())
+    }
+
+    /// Part of the map visible in the view, in projected coordinates.
+    ///
+    /// For tilted views, the part of the screen near (or above) the horizon is cut off at the
+    /// [far distance](MapView::far_distance). Sides of the screen are split into several segments, so that the
+    /// polygon approximates the footprint well after it is unprojected into geographic coordinates.
+    pub fn footprint_projected(&self) -> Option<Polygon<Point2d>> {
+        let top = self.visible_top()?;
+        let (width, height) = (self.size.width(), self.size.height());
+        let corners = [
+            Point2d::new(0.0, top),
+            Point2d::new(width, top),
+            Point2d::new(width, height),
+            Point2d::new(0.0, height),
+        ];
+
+        let mut points = Vec::with_capacity(corners.len() * FOOTPRINT_EDGE_SEGMENTS);
+        for (i, &from) in corners.iter().enumerate() {
+            let to = corners[(i + 1) % corners.len()];
+            for step in 0..FOOTPRINT_EDGE_SEGMENTS {
+                let k = step as f64 / FOOTPRINT_EDGE_SEGMENTS as f64;
+                points.push(self.screen_to_map(from + (to - from) * k)?);
+            }
+        }
+
+        Some(Polygon::new(ClosedContour::new(points), vec![]))
+    }
+
+    /// Part of the map visible in the view, in geographic coordinates. See [`MapView::footprint_projected`].
+    pub fn footprint(&self) -> Option<Polygon<GeoPoint2d>> {
+        let footprint = self.footprint_projected()?;
+        let projection = self.crs.get_projection::<GeoPoint2d, Point2d>()?;
+        let points = footprint
+            .outer_contour
+            .points
+            .iter()
+            .map(|p| projection.unproject(p))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Polygon::new(ClosedContour::new(points), vec![]))
+    }
+
+    /// Distance in map units from the center of the view, beyond which the map is not drawn in tilted views.
+    ///
+    /// When the view is tilted far enough, the part of the map near the horizon would require loading an unlimited
+    /// number of tiles, each covering only a few pixels. Tiles are only loaded up to this distance (see
+    /// [`MapView::footprint_projected`]), and the gap between it and the horizon can be hidden with an
+    /// [`AtmosphereLayer`](crate::layer::AtmosphereLayer).
+    pub fn far_distance(&self) -> f64 {
+        self.size.width().max(self.size.height()) * self.resolution * FAR_DISTANCE_FACTOR
+    }
+
+    /// Indices of the tiles of the given schema needed to draw the view. See [`TileSchema::iter_tiles`].
+    pub fn visible_tiles(&self, tile_schema: &TileSchema) -> Vec<TileIndex> {
+        tile_schema
+            .iter_tiles(self)
+            .map(|tiles| tiles.collect())
+            .unwrap_or_default()
+    }
+
+    /// The smallest screen *Y* coordinate that is not too close to the horizon.
+    fn visible_top(&self) -> Option<f64> {
+        if self.size.is_zero() {
+            return None;
+        }
+
+        let position = self.projected_position?;
+        let max_distance = self.far_distance();
+        let is_visible = |y: f64| {
+            self.screen_to_map(Point2d::new(self.size.half_width(), y))
+                .is_some_and(|p| (p.x - position.x).hypot(p.y - position.y) <= max_distance)
+        };
+
+        if is_visible(0.0) {
+            return Some(0.0);
+        }
+
+        let (mut invisible, mut visible) = (0.0, self.size.half_height());
+        if !is_visible(visible) {
+            return None;
+        }
+
+        for _ in 0..32 {
+            let middle = (invisible + visible) / 2.0;
+            if is_visible(middle) {
+                visible = middle;
+            } else {
+                invisible = middle;
+            }
+        }
+
+        Some(visible)
+    }
+
+    /// Creates a new view, same as the current one, but translated so that point `from` on the current view becomes
+    /// the point `to` in the new view.
+    pub fn translate_by_pixels(&self, from: Point2d, to: Point2d) -> Self {
+        let Some(from_projected) = self.screen_to_map(from) else {
+            return self.clone();
+        };
+        let Some(to_projected) = self.screen_to_map(to) else {
+            return self.clone();
+        };
+
+        const MAX_TRANSLATE: f64 = 100.0;
+        let max_translate = MAX_TRANSLATE * self.resolution;
+        let mut delta = to_projected - from_projected;
+        if delta.x.abs() > max_translate {
+            delta.x = max_translate * delta.x.signum();
+        }
+        if delta.y.abs() > max_translate {
+            delta.y = max_translate * delta.y.signum();
+        }
+
+        self.translate(delta.xy())
+    }
+
+    /// Move the vew by the given projected coorinates delta.
+    pub fn translate(&self, delta: Vector2<f64>) -> Self {
+        match self.projected_position {
+            Some(v) => {
+                let projected_position = v - Vector3::new(delta.x, delta.y, 0.0);
+                Self {
+                    projected_position: Some(projected_position),
+                    crs: self.crs.clone(),
+                    ..*self
+                }
+            }
+            None => Self {
+                crs: self.crs.clone(),
+                ..*self
+            },
+        }
+    }
+
+    pub(crate) fn zoom(&self, zoom: f64, base_point: Point2d) -> Self {
+        let base_point = self.screen_to_map(base_point);
+        let resolution = self.resolution * zoom;
+
+        let new_position = base_point.and_then(|base_point| {
+            self.projected_position.map(|position| {
+                let position2d = Point2::new(position.x, position.y);
+                let result = base_point.add((position2d - base_point) * zoom);
+                Point3::new(result.x, result.y, position.z)
+            })
+        });
+
+        Self {
+            projected_position: new_position,
+            resolution,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    pub(crate) fn interpolate(&self, target: &MapView, k: f64) -> Self {
+        let Some(source_position) = self.projected_position else {
+            return self.clone();
+        };
+        let Some(target_position) = target.projected_position else {
+            return self.clone();
+        };
+
+        let projected_position = source_position + (target_position - source_position) * k;
+        // Rotation goes the shortest way, so that a turn from 350 to 10 degrees does not make a full circle.
+        let rotation_z_delta = (target.rotation_z - self.rotation_z + std::f64::consts::PI)
+            .rem_euclid(std::f64::consts::TAU)
+            - std::f64::consts::PI;
+        Self {
+            projected_position: Some(projected_position),
+            resolution: self.resolution + (target.resolution - self.resolution) * k,
+            rotation_x: self.rotation_x + (target.rotation_x - self.rotation_x) * k,
+            rotation_z: self.rotation_z + rotation_z_delta * k,
+            fov: self.fov + (target.fov - self.fov) * k,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+}
+
+/// Position of a map point on the screen, calculated by [`MapView::map_to_screen`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScreenPosition {
+    /// The point is inside the screen area. The position is given in pixels from the top-left corner.
+    OnScreen(Point2d),
+    /// The point is in front of the camera, but outside the screen area. The position is where the point would be
+    /// drawn if the screen was large enough.
+    OffScreen(Point2d),
+    /// The point is behind the camera and cannot be displayed.
+    BehindCamera,
+}
+
+impl ScreenPosition {
+    /// Position of the point if it is inside the screen area.
+    pub fn on_screen(&self) -> Option<Point2d> {
+        match self {
+            ScreenPosition::OnScreen(position) => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// Position of the point if it is in front of the camera.
+    pub fn position(&self) -> Option<Point2d> {
+        match self {
+            ScreenPosition::OnScreen(position) | ScreenPosition::OffScreen(position) => {
+                Some(*position)
+            }
+            ScreenPosition::BehindCamera => None,
+        }
+    }
+}
+
+/// Parameters of the [`MapView::screen_to_map`] transformation that do not depend on the point.
+struct ScreenToMap {
+    half_width: f64,
+    half_height: f64,
+    resolution: f64,
+    fov_tan: f64,
+    tilt_tan: f64,
+    tilt_cos: f64,
+    rotation_z: Rotation3<f64>,
+    translation: Translation3<f64>,
+}
+
+impl ScreenToMap {
+    fn apply(&self, px_position: Point2d) -> Option<Point2d> {
+        // todo: this must be calculated with matrices somehow but I'm not bright enough
+        // to figure out how to do it...
+        let x = px_position.x;
+        let y = px_position.y;
+        let a = (self.half_height - y) * self.fov_tan / self.half_height;
+
+        let s = 1.0 / (self.tilt_tan / a - 1.0) + 1.0;
+
+        let x0 = (x - self.half_width) * self.resolution;
+        let y0 = (self.half_height - y) * self.resolution;
+
+        if s.is_infinite() || s.is_nan() || s <= 0.0 {
+            return None;
+        }
+
+        let y0_ang = y0 / self.tilt_cos;
+
+        let x0_scaled = x0 * s;
+        let y0_scaled = y0_ang * s;
+
+        let p = Point3::new(x0_scaled, y0_scaled, 0.0);
+        let transformed = self.translation * self.rotation_z * p;
+
+        Some(Point2::new(transformed.x, transformed.y))
+    }
+}
+
+/// Transformation of [`MapView::map_to_screen`].
+struct MapToScreen {
+    matrix: Matrix4<f64>,
+    size: Size,
+}
+
+impl MapToScreen {
+    fn apply(&self, point: Point2d) -> ScreenPosition {
+        let projected = self.matrix * Point3::new(point.x, point.y, 0.0).to_homogeneous();
+        if projected.w <= 0.0 {
+            return ScreenPosition::BehindCamera;
+        }
+
+        let x = (projected.x / projected.w + 1.0) * self.size.half_width();
+        let y = (1.0 - projected.y / projected.w) * self.size.half_height();
+        let position = Point2d::new(x, y);
+
+        if (0.0..=self.size.width()).contains(&x) && (0.0..=self.size.height()).contains(&y) {
+            ScreenPosition::OnScreen(position)
+        } else {
+            ScreenPosition::OffScreen(position)
+        }
+    }
+}
+
+/// Great circle distance between two points in meters.
+fn ground_distance(a: &GeoPoint2d, b: &GeoPoint2d) -> f64 {
+    let d_lat = b.lat_rad() - a.lat_rad();
+    let d_lon = b.lon_rad() - a.lon_rad();
+    let h = (d_lat / 2.0).sin().powi(2)
+        + a.lat_rad().cos() * b.lat_rad().cos() * (d_lon / 2.0).sin().powi(2);
+
+    2.0 * Datum::WGS84.semimajor() * h.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use galileo_types::geo::NewGeoPoint;
+
+    fn test_view() -> MapView {
+        MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+    }
+
+    #[test]
+    fn screen_to_map_size() {
+        let view = test_view().with_size(Size::new(100.0, 100.0));
+
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(0.0, 0.0)).unwrap(),
+            Point2d::new(-50.0, 50.0),
+            epsilon = 0.0001,
+        );
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(50.0, 50.0)).unwrap(),
+            Point2d::new(0.0, 0.0),
+            epsilon = 0.0001,
+        );
+
+        let view = test_view().with_size(Size::new(200.0, 50.0));
+
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(0.0, 0.0)).unwrap(),
+            Point2d::new(-100.0, 25.0),
+            epsilon = 0.0001,
+        );
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(25.0, 49.0)).unwrap(),
+            Point2d::new(-75.0, -24.0),
+            epsilon = 0.0001,
+        );
+    }
+
+    #[test]
+    fn screen_to_map_zero_size() {
+        let view = test_view().with_size(Size::new(0.0, 0.0));
+        let projected = view.screen_to_map(Point2d::new(0.0, 0.0));
+        assert!(projected.is_none());
+    }
+
+    #[test]
+    fn screen_to_map_position() {
+        let view = MapView::new_projected(&Point2d::new(-100.0, -100.0), 1.0)
+            .with_size(Size::new(100.0, 100.0));
+
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(0.0, 0.0)).unwrap(),
+            Point2d::new(-150.0, -50.0),
+            epsilon = 0.0001,
+        );
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(50.0, 50.0)).unwrap(),
+            Point2d::new(-100.0, -100.0),
+            epsilon = 0.0001,
+        );
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(100.0, 100.0)).unwrap(),
+            Point2d::new(-50.0, -150.0),
+            epsilon = 0.0001,
+        );
+    }
+
+    #[test]
+    fn screen_to_map_resolution() {
+        let view = test_view()
+            .with_resolution(2.0)
+            .with_size(Size::new(100.0, 100.0));
+
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(0.0, 0.0)).unwrap(),
+            Point2d::new(-100.0, 100.0),
+            epsilon = 0.0001,
+        );
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(100.0, 100.0)).unwrap(),
+            Point2d::new(100.0, -100.0),
+            epsilon = 0.0001,
+        );
+    }
+
+    #[test]
+    fn screen_to_map_rotation_x() {
+        let view = test_view()
+            .with_rotation_x(std::f64::consts::PI / 4.0)
+            .with_size(Size::new(100.0, 100.0));
+
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(50.0, 50.0)).unwrap(),
+            Point2d::new(0.0, 0.0),
+            epsilon = 0.0001,
+        );
+
+        let projected = view.screen_to_map(Point2d::new(0.0, 0.0));
+        assert!(projected.is_none());
+
+        assert_abs_diff_eq!(
+            view.screen_to_map(Point2d::new(100.0, 100.0)).unwrap(),
+            Point2d::new(25.0, -35.35),
+            epsilon = 0.1,
+        );
+    }
+
+    #[test]
+    fn ground_resolution_depends_on_latitude_and_tilt() {
+        let equator =
+            MapView::new(&GeoPoint2d::latlon(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        assert_abs_diff_eq!(
+            equator.ground_resolution(Point2d::new(50.0, 50.0)).unwrap(),
+            1.0,
+            epsilon = 0.01
+        );
+
+        let north =
+            MapView::new(&GeoPoint2d::latlon(60.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        assert_abs_diff_eq!(
+            north.ground_resolution(Point2d::new(50.0, 50.0)).unwrap(),
+            0.5,
+            epsilon = 0.01
+        );
+
+        let tilted = equator.with_rotation_x(std::f64::consts::PI / 4.0);
+        let near = tilted.ground_resolution(Point2d::new(50.0, 90.0)).unwrap();
+        let far = tilted.ground_resolution(Point2d::new(50.0, 10.0)).unwrap();
+        assert!(near < far);
+    }
+
+    #[test]
+    fn footprint_of_view() {
+        let view = test_view().with_size(Size::new(100.0, 100.0));
+        let footprint = view.footprint_projected().unwrap();
+        let bbox = Rect::from_points(footprint.outer_contour.points.iter()).unwrap();
+        assert_abs_diff_eq!(bbox.x_min(), -50.0, epsilon = 0.0001);
+        assert_abs_diff_eq!(bbox.y_max(), 50.0, epsilon = 0.0001);
+        assert_eq!(
+            footprint.outer_contour.points.len(),
+            4 * FOOTPRINT_EDGE_SEGMENTS
+        );
+
+        let tilted = view.with_rotation_x(80f64.to_radians());
+        let footprint = tilted.footprint_projected().unwrap();
+        let bbox = Rect::from_points(footprint.outer_contour.points.iter()).unwrap();
+        assert!(bbox.y_max() > 50.0);
+        assert!(bbox.y_max() <= 200.0 + 0.0001);
+    }
+
+    #[test]
+    fn map_to_screen_is_inverse_of_screen_to_map() {
+        let view = MapView::new_projected(&Point2d::new(10.0, 20.0), 2.0)
+            .with_size(Size::new(100.0, 100.0))
+            .with_rotation(std::f64::consts::PI / 4.0, 0.3);
+
+        let screen_points = [
+            Point2d::new(10.0, 90.0),
+            Point2d::new(70.0, 40.0),
+            Point2d::new(50.0, 50.0),
+        ];
+        let mut map_points = vec![];
+        view.screen_to_map_batch(&screen_points, &mut map_points);
+
+        let map_points: Vec<_> = map_points.into_iter().map(|p| p.unwrap()).collect();
+        let mut projected = vec![];
+        view.map_to_screen_batch(&map_points, &mut projected);
+
+        for (screen, projected) in screen_points.iter().zip(&projected) {
+            assert_abs_diff_eq!(projected.on_screen().unwrap(), *screen, epsilon = 0.0001);
+        }
+
+        assert!(matches!(
+            view.map_to_screen(Point2d::new(1000.0, 20.0)),
+            ScreenPosition::OffScreen(_)
+        ));
+
+        let behind = Point2d::new(10.0 - 1000.0 * 0.3f64.sin(), 20.0 - 1000.0 * 0.3f64.cos());
+        assert_eq!(view.map_to_screen(behind), ScreenPosition::BehindCamera);
+    }
+
+    #[test]
+    fn map_to_scene() {
+        let view = test_view().with_size(Size::new(100.0, 100.0));
+        let point = Point3::new(-50.0, 50.0, 0.0).to_homogeneous();
+        let transform = view.map_to_scene_transform().unwrap();
+        let projected = transform * point;
+
+        assert_abs_diff_eq!(
+            projected.unscale(projected.w),
+            Point3::new(-1.0, 1.0, 0.388).to_homogeneous(),
+            epsilon = 0.01
+        );
+
+        let point = Point3::new(0.0, 0.0, 0.0).to_homogeneous();
+        let transform = view.map_to_scene_transform().unwrap();
+        let projected = transform * point;
+
+        assert_abs_diff_eq!(
+            projected.unscale(projected.w),
+            Point3::new(0.0, 0.0, 0.3888).to_homogeneous(),
+            epsilon = 0.01
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use super::MapView;
+use nalgebra::{Matrix4, Translation3, UnitQuaternion, Vector3};
+
+/// Field of view of an eye given by the angles (in radians) between the view direction of the eye and the sides of
+/// its frustum.
+///
+/// The angles follow the convention of OpenXR `XrFovf`: `left` and `down` angles are negative, `right` and `up` are
+/// positive. The frustum of an eye in a head-mounted display is usually not symmetric.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EyeFov {
+    /// Angle of the left side of the frustum.
+    pub left: f64,
+    /// Angle of the right side of the frustum.
+    pub right: f64,
+    /// Angle of the top side of the frustum.
+    pub up: f64,
+    /// Angle of the bottom side of the frustum.
+    pub down: f64,
+}
+
+impl EyeFov {
+    /// Symmetric field of view with the given vertical field of view and aspect ratio (width / height).
+    pub fn symmetric(fov: f64, aspect: f64) -> Self {
+        let vertical = fov / 2.0;
+        let horizontal = (vertical.tan() * aspect).atan();
+        Self {
+            left: -horizontal,
+            right: horizontal,
+            up: vertical,
+            down: -vertical,
+        }
+    }
+
+    /// Perspective projection matrix of the frustum with the given near and far planes.
+    pub fn projection_matrix(&self, near: f64, far: f64) -> Matrix4<f64> {
+        let (left, right) = (self.left.tan(), self.right.tan());
+        let (up, down) = (self.up.tan(), self.down.tan());
+
+        Matrix4::new(
+            2.0 / (right - left),
+            0.0,
+            (right + left) / (right - left),
+            0.0,
+            0.0,
+            2.0 / (up - down),
+            (up + down) / (up - down),
+            0.0,
+            0.0,
+            0.0,
+            (far + near) / (near - far),
+            2.0 * far * near / (near - far),
+            0.0,
+            0.0,
+            -1.0,
+            0.0,
+        )
+    }
+}
+
+/// One of the views of a stereo (or any multi-view) rendering, e.g. an eye of a VR headset.
+///
+/// The eye is placed relative to the camera of the [`MapView`] (see [`MapView::camera`]), so the head of the viewer
+/// is positioned over the map by the view, while the eyes follow the pose reported by the XR runtime.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Eye {
+    /// Position of the eye relative to the camera in map units, with *X* axis going to the right of the screen, *Y*
+    /// axis going up the screen and *Z* axis going towards the viewer.
+    pub offset: Vector3<f64>,
+    /// Rotation of the eye relative to the camera.
+    pub orientation: UnitQuaternion<f64>,
+    /// Field of view of the eye.
+    pub fov: EyeFov,
+}
+
+impl Eye {
+    /// Creates an eye at the given offset from the camera, looking in the same direction as the camera.
+    pub fn new(offset: Vector3<f64>, fov: EyeFov) -> Self {
+        Self {
+            offset,
+            orientation: UnitQuaternion::identity(),
+            fov,
+        }
+    }
+
+    /// Sets the rotation of the eye relative to the camera.
+    pub fn with_orientation(mut self, orientation: UnitQuaternion<f64>) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Transformation from the coordinates of the camera into the coordinates of the eye. Coordinates of the camera
+    /// are measured in pixels of the view with the given resolution.
+    pub(super) fn view_matrix(&self, resolution: f64) -> Matrix4<f64> {
+        let translation = Translation3::from(-self.offset / resolution);
+        self.orientation.inverse().to_homogeneous() * translation.to_homogeneous()
+    }
+}
+
+impl MapView {
+    /// Eye the view is rendered for. See [`MapView::with_eye`].
+    pub fn eye(&self) -> Option<&Eye> {
+        self.eye.as_ref()
+    }
+
+    /// Creates a new view, same as the current one, but rendered from the given eye instead of the camera of the view.
+    ///
+    /// The eye only changes the transformation used to render the map ([`MapView::map_to_scene_transform`]) and to
+    /// cull invisible primitives ([`MapView::frustum`]). All the other parameters (e.g. the tiles to load or the
+    /// conversions between screen and map coordinates) are calculated for the camera of the view, so its field of view
+    /// should cover the fields of view of all the eyes.
+    pub fn with_eye(&self, eye: Option<Eye>) -> Self {
+        Self {
+            eye,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Left and right eye views for stereo rendering on a screen (e.g. side-by-side 3D or anaglyph), with the eyes
+    /// `eye_separation` map units apart.
+    ///
+    /// The frustums of the eyes are shifted so that the center of the view is displayed at the center of both eye
+    /// images: the surface of the map around the center appears at the depth of the screen, and the objects above it
+    /// appear in front of the screen. Returns `None` if the view has zero size.
+    ///
+    /// For VR headsets, use [`MapView::with_eye`] with the eye poses and fields of view given by the XR runtime
+    /// instead.
+    pub fn stereo_pair(&self, eye_separation: f64) -> Option<[MapView; 2]> {
+        if self.size.is_zero() {
+            return None;
+        }
+
+        let distance = self.camera_distance_px() * self.resolution;
+        let up = (self.fov / 2.0).tan();
+        let side = up * self.size.width() / self.size.height();
+        let shift = eye_separation / 2.0 / distance;
+
+        Some([-1.0, 1.0].map(|direction| {
+            let fov = EyeFov {
+                left: (-side - direction * shift).atan(),
+                right: (side - direction * shift).atan(),
+                up: up.atan(),
+                down: -up.atan(),
+            };
+            let offset = Vector3::new(direction * eye_separation / 2.0, 0.0, 0.0);
+            self.with_eye(Some(Eye::new(offset, fov)))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::{Point2d, Size};
+    use nalgebra::Vector4;
+
+    fn view() -> MapView {
+        MapView::new_projected(&Point2d::new(100.0, 200.0), 2.0)
+            .with_size(Size::new(400.0, 200.0))
+            .with_rotation(0.6, 0.3)
+    }
+
+    fn scene_x(view: &MapView, point: [f64; 3]) -> f64 {
+        let clip = view.map_to_scene_transform().unwrap()
+            * Vector4::new(point[0], point[1], point[2], 1.0);
+        clip.x / clip.w
+    }
+
+    #[test]
+    fn centered_eye_matches_camera() {
+        let view = view();
+        let fov = EyeFov::symmetric(view.fov(), 2.0);
+        let eye_view = view.with_eye(Some(Eye::new(Vector3::zeros(), fov)));
+
+        assert_abs_diff_eq!(
+            eye_view.map_to_scene_transform().unwrap(),
+            view.map_to_scene_transform().unwrap(),
+            epsilon = 1e-9
+        );
+        assert_eq!(eye_view.with_eye(None), view);
+    }
+
+    #[test]
+    fn stereo_pair_converges_at_center() {
+        let [left, right] = view().stereo_pair(50.0).unwrap();
+
+        assert_abs_diff_eq!(scene_x(&left, [100.0, 200.0, 0.0]), 0.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(scene_x(&right, [100.0, 200.0, 0.0]), 0.0, epsilon = 1e-9);
+
+        // A point above the map is closer to the viewer than the screen, so it is shifted to the right in the image of
+        // the left eye.
+        let above = [100.0, 200.0, 50.0];
+        assert!(scene_x(&left, above) > 0.0);
+        assert!(scene_x(&right, above) < 0.0);
+    }
+}
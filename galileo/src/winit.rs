@@ -1,10 +1,11 @@
 //! Types that help using `Galileo` with `winit`.
 
-use crate::control::{MouseButton, RawUserEvent, TouchEvent};
+use crate::control::{Key, MouseButton, RawUserEvent, ScrollDelta, TouchEvent};
 use crate::messenger::Messenger;
 use galileo_types::cartesian::Point2d;
 use std::sync::Arc;
 use winit::event::{ElementState, MouseScrollDelta, Touch, TouchPhase, WindowEvent};
+use winit::keyboard::NamedKey;
 use winit::window::Window;
 
 /// Converts `winit` events into `Galileo` [`RawUserEvent`]s.
@@ -28,15 +29,15 @@ impl WinitInputHandler {
                 Some(RawUserEvent::PointerMoved(pointer_position))
             }
             WindowEvent::MouseWheel { delta, .. } => {
-                let zoom = match delta {
-                    MouseScrollDelta::LineDelta(_, dy) => *dy as f64,
-                    MouseScrollDelta::PixelDelta(pos) => pos.y / 114.0,
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(_, dy) => ScrollDelta::Lines(*dy as f64),
+                    MouseScrollDelta::PixelDelta(pos) => ScrollDelta::Pixels(pos.y / scale),
                 };
-                if zoom.abs() < 0.0001 {
+                if delta.lines().abs() < 0.0001 {
                     return None;
                 }
 
-                Some(RawUserEvent::Scroll(zoom))
+                Some(RawUserEvent::Scroll(delta))
             }
             WindowEvent::Touch(touch) => match touch.phase {
                 TouchPhase::Started => {
@@ -49,6 +50,13 @@ impl WinitInputHandler {
                     Some(RawUserEvent::TouchEnd(self.get_touch_event(touch, scale)))
                 }
             },
+            WindowEvent::KeyboardInput { event, .. } => {
+                let key = (&event.logical_key).into();
+                match event.state {
+                    ElementState::Pressed => Some(RawUserEvent::KeyPressed(key)),
+                    ElementState::Released => Some(RawUserEvent::KeyReleased(key)),
+                }
+            }
             _ => None,
         }
     }
@@ -74,6 +82,27 @@ impl From<&winit::event::MouseButton> for MouseButton {
     }
 }
 
+impl From<&winit::keyboard::Key> for Key {
+    fn from(value: &winit::keyboard::Key) -> Self {
+        match value {
+            winit::keyboard::Key::Named(NamedKey::ArrowUp) => Key::ArrowUp,
+            winit::keyboard::Key::Named(NamedKey::ArrowDown) => Key::ArrowDown,
+            winit::keyboard::Key::Named(NamedKey::ArrowLeft) => Key::ArrowLeft,
+            winit::keyboard::Key::Named(NamedKey::ArrowRight) => Key::ArrowRight,
+            winit::keyboard::Key::Named(NamedKey::Space) => Key::Space,
+            winit::keyboard::Key::Named(NamedKey::Shift) => Key::Shift,
+            winit::keyboard::Key::Named(NamedKey::Control) => Key::Control,
+            winit::keyboard::Key::Named(NamedKey::Escape) => Key::Escape,
+            winit::keyboard::Key::Character(chars) => match chars.chars().next() {
+                Some(' ') => Key::Space,
+                Some(c) => Key::Char(c.to_ascii_lowercase()),
+                None => Key::Other,
+            },
+            _ => Key::Other,
+        }
+    }
+}
+
 /// Messenger for a `winit` window.
 #[derive(Debug, Clone)]
 pub struct WinitMessenger {